@@ -0,0 +1,194 @@
+//! The `animate` and `animate-project` subcommands: numbered frames (or a
+//! video with the `video` feature) plus a manifest.
+//!
+//! An engine animation can follow a param timeline, hot-reload a params
+//! file, be steered by an audio track (`audio` feature) and resume from a
+//! checkpoint. A project animation evaluates layer keyframes at each output
+//! frame index.
+
+#[cfg(feature = "audio")]
+use crate::audio::AudioArgs;
+use crate::checkpoint::CheckpointArgs;
+use crate::error::CliError;
+use crate::frames::capture_steps;
+use crate::reload::ParamsFile;
+use crate::{
+    load_project, load_timeline, parse_palette, report_frames, simulate_with_frames, FrameArgs,
+    SimArgs, Simulation,
+};
+use art_engine_core::color::ColorSpace;
+use art_engine_core::MemoryBudget;
+#[cfg(not(feature = "audio"))]
+use art_engine_core::ParamTimeline;
+use art_engine_engines::composition::Composition;
+use art_engine_engines::pixel::field_to_rgba_in;
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+/// Options of the `animate` subcommand.
+#[derive(Args)]
+pub struct AnimateArgs {
+    #[command(flatten)]
+    pub sim: SimArgs,
+
+    #[command(flatten)]
+    pub checkpoint: CheckpointArgs,
+
+    #[command(flatten)]
+    pub frames: FrameArgs,
+
+    /// Keyframe engine params and the palette over the run from a JSON
+    /// timeline; its frames count simulation steps. Pass it again when
+    /// resuming.
+    #[arg(long, value_name = "PATH")]
+    pub timeline: Option<PathBuf>,
+
+    /// Reload engine params from a JSON object while rendering; changed
+    /// keys apply from the next check, and the manifest records when.
+    #[arg(long, value_name = "PATH")]
+    pub params_file: Option<PathBuf>,
+
+    /// Frames between checks of --params-file.
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "params_file",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub reload_every: u64,
+
+    #[cfg(feature = "audio")]
+    #[command(flatten)]
+    pub audio: AudioArgs,
+}
+
+/// Runs `animate`, checking engine memory against `memory`.
+pub fn run(args: AnimateArgs, memory: MemoryBudget, json: bool) -> Result<(), CliError> {
+    let AnimateArgs {
+        sim,
+        checkpoint,
+        frames,
+        timeline,
+        params_file,
+        reload_every,
+        #[cfg(feature = "audio")]
+        audio,
+    } = args;
+    let sim = sim.resolve(memory)?;
+    let timeline = timeline.as_deref().map(load_timeline).transpose()?;
+    let plan = checkpoint.plan(&sim);
+    #[cfg(feature = "audio")]
+    let drive = audio.load(frames.fps)?;
+    #[cfg(feature = "audio")]
+    let frames = FrameArgs {
+        duration: frames.duration.or(drive.as_ref().map(|d| d.duration())),
+        ..frames
+    };
+    let every = frames.every(sim.steps)?;
+    #[cfg(feature = "audio")]
+    let steering = drive
+        .as_ref()
+        .map(|drive| drive.steer(timeline.as_ref(), every))
+        .transpose()?;
+    #[cfg(not(feature = "audio"))]
+    let steering: Option<ParamTimeline> = None;
+    let mut reload = params_file
+        .as_deref()
+        .map(|path| ParamsFile::open(path, every.saturating_mul(reload_every as usize)))
+        .transpose()?;
+    let steps = capture_steps(sim.steps, every);
+    let mut out = frames.open(steps.len(), sim.width, sim.height, sim.color_space)?;
+    if let Some(resume) = &sim.resume {
+        // Frames up to the checkpoint were written before it was taken
+        out.resume(&steps[..steps.partition_point(|&s| s <= resume.step)])?;
+    }
+    let mut frame_palette = (sim.palette.clone(), parse_palette(&sim.palette)?);
+    let Simulation { params, .. } = simulate_with_frames(
+        &sim,
+        Some(every),
+        plan.as_ref(),
+        steering.as_ref().or(timeline.as_ref()),
+        reload.as_mut(),
+        |step, field, hue| {
+            let name = timeline.as_ref().and_then(|t| t.palette_at(step as f64));
+            if let Some(name) = name.filter(|name| *name != frame_palette.0) {
+                frame_palette = (name.clone(), parse_palette(&name)?);
+            }
+            let rgba = field_to_rgba_in(field, &frame_palette.1, hue, sim.color_space)?;
+            #[cfg(feature = "audio")]
+            let rgba = match &drive {
+                Some(drive) => drive.apply_post(out.len(), rgba, sim.width, sim.height)?,
+                None => rgba,
+            };
+            let step = *steps.get(out.len()).ok_or_else(|| {
+                CliError::Input("frame options differ from the resumed run".into())
+            })?;
+            out.write(step, rgba, sim.width, sim.height)
+        },
+    )?;
+    let frame_count = out.len();
+    let mut manifest = serde_json::json!({
+        "engine": sim.engine,
+        "width": sim.width,
+        "height": sim.height,
+        "steps": sim.steps,
+        "seed": sim.seed.value,
+        "seed_phrase": sim.seed.phrase,
+        "palette": sim.palette,
+        "color_space": sim.color_space,
+        "params": params,
+        "every": every,
+        "fps": frames.fps,
+        "timeline": timeline,
+    });
+    if let (Some(path), Some(file)) = (&params_file, &reload) {
+        manifest["params_file"] = serde_json::json!({
+            "path": path.display().to_string(),
+            "every": every.saturating_mul(reload_every as usize),
+            "history": file.history(),
+        });
+    }
+    #[cfg(feature = "audio")]
+    if let (Some(track), Some(map)) = (&audio.audio, &audio.audio_map) {
+        manifest["audio"] = serde_json::json!({
+            "track": track.display().to_string(),
+            "map": map.display().to_string(),
+        });
+    }
+    let written = out.finish(manifest)?;
+    report_frames(json, &sim.engine, frame_count, &written)
+}
+
+/// Runs `animate-project` over `steps` steps, or the project's own count.
+pub fn run_project(
+    project: &Path,
+    steps: Option<usize>,
+    frames: &FrameArgs,
+    json: bool,
+) -> Result<(), CliError> {
+    let spec = load_project(project)?;
+    let total = steps.unwrap_or(spec.steps);
+    let every = frames.every(total)?;
+    let captures = capture_steps(total, every);
+    let (width, height) = (spec.canvas.width(), spec.canvas.height());
+    let mut out = frames.open(captures.len(), width, height, ColorSpace::Srgb)?;
+    let mut comp = Composition::from_project(&spec)?;
+    let mut step = 0;
+    captures
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, &target)| {
+            (step..target).try_for_each(|_| comp.step())?;
+            step = target;
+            let image = comp.render_animated(index as f64)?;
+            out.write(target, image.to_rgba8(), width, height)
+        })?;
+    let frame_count = out.len();
+    let written = out.finish(serde_json::json!({
+        "project": project.display().to_string(),
+        "width": width,
+        "height": height,
+        "steps": total,
+        "seed": spec.seed,
+        "every": every,
+        "fps": frames.fps,
+    }))?;
+    let name = project.display().to_string();
+    report_frames(json, &name, frame_count, &written)
+}
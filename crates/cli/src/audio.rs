@@ -0,0 +1,825 @@
+//! Audio-reactive animation: features of a sound track steering engine
+//! params and post effects frame by frame.
+//!
+//! Enabled by the `audio` feature. [`Audio::load`] decodes a WAV (hound) or
+//! Ogg Vorbis (lewton) file and mixes it to mono; [`analyze`] measures it
+//! once per animation frame, frame `j` covering the `1 / fps` seconds from
+//! `j / fps`. Every feature is normalized to [0, 1] by its peak over the
+//! track:
+//!
+//! - `rms` -- loudness, the root mean square of the frame's samples.
+//! - one feature per band (by default `bass` 20-250 Hz, `mid` 250-2000 Hz
+//!   and `treble` 2-16 kHz) -- the RMS magnitude of the band's bins in a
+//!   Hann-windowed FFT of [`WINDOW`] samples centered on the frame.
+//! - `onset` -- spectral flux (summed rises in bin magnitude) above its
+//!   average over the previous [`ONSET_HISTORY`] frames, so it spikes on
+//!   drum hits and note attacks.
+//!
+//! An [`AudioMap`] (`animate --audio-map`) routes features to engine params
+//! and to number fields of a post stack applied to every frame:
+//!
+//! ```json
+//! {
+//!   "bands": { "bass": [20, 250], "hats": [6000, 16000] },
+//!   "post": [{ "kind": "vignette", "strength": 0.2 }, { "kind": "bloom", "intensity": 0 }],
+//!   "routes": [
+//!     { "feature": "bass", "param": "feed_rate", "min": 0.03, "max": 0.05, "smoothing": 0.6 },
+//!     { "feature": "onset", "post": "bloom.intensity", "max": 1.5 }
+//!   ]
+//! }
+//! ```
+//!
+//! A route maps its feature from [0, 1] onto `min..max` (default 0 to 1),
+//! after exponential smoothing (`smoothing` in [0, 1): the share of the
+//! previous frame's value kept). A post target names an effect kind and
+//! field; the first effect of that kind in `post` is driven. Past the end
+//! of the track every feature is 0.
+
+use std::collections::BTreeMap;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use art_engine_core::keyframe::{Easing, Keyframe, Track};
+use art_engine_core::{math, post, Image, ParamTimeline, PostEffect};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::CliError;
+
+/// FFT size for band and onset analysis, in samples.
+pub const WINDOW: usize = 2048;
+
+/// Frames of spectral flux averaged into the onset threshold.
+pub const ONSET_HISTORY: usize = 8;
+
+/// A decoded track, mixed down to mono.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audio {
+    /// Samples per second.
+    pub sample_rate: u32,
+    /// Samples in [-1, 1].
+    pub samples: Vec<f32>,
+}
+
+impl Audio {
+    /// Decodes a `.wav` or `.ogg` (Vorbis) file by its extension.
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        let audio = match ext.as_deref() {
+            Some("wav" | "wave") => Self::load_wav(path),
+            Some("ogg" | "oga") => Self::load_ogg(path),
+            _ => {
+                return Err(CliError::Input(format!(
+                    "{}: unsupported audio format; expected .wav or .ogg",
+                    path.display()
+                )))
+            }
+        }
+        .map_err(|e| CliError::Input(format!("{}: {e}", path.display())))?;
+        if audio.sample_rate == 0 {
+            return Err(CliError::Input(format!(
+                "{}: sample rate is zero",
+                path.display()
+            )));
+        }
+        Ok(audio)
+    }
+
+    fn load_wav(path: &Path) -> Result<Self, String> {
+        let reader = hound::WavReader::open(path).map_err(|e| e.to_string())?;
+        let spec = reader.spec();
+        let interleaved: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .into_samples::<f32>()
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?,
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| e.to_string())?
+            }
+        };
+        Ok(Self {
+            sample_rate: spec.sample_rate,
+            samples: mix_down(&interleaved, spec.channels as usize),
+        })
+    }
+
+    fn load_ogg(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut reader = lewton::inside_ogg::OggStreamReader::new(BufReader::new(file))
+            .map_err(|e| e.to_string())?;
+        let channels = reader.ident_hdr.audio_channels as usize;
+        let mut interleaved = Vec::new();
+        while let Some(packet) = reader.read_dec_packet_itl().map_err(|e| e.to_string())? {
+            interleaved.extend(packet.into_iter().map(|s| s as f32 / 32768.0));
+        }
+        Ok(Self {
+            sample_rate: reader.ident_hdr.audio_sample_rate,
+            samples: mix_down(&interleaved, channels),
+        })
+    }
+}
+
+/// Averages interleaved `channels`-channel samples into one channel.
+fn mix_down(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    interleaved
+        .chunks_exact(channels.max(1))
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// The default bands: `bass`, `mid` and `treble`.
+pub fn default_bands() -> BTreeMap<String, [f64; 2]> {
+    [
+        ("bass", [20.0, 250.0]),
+        ("mid", [250.0, 2000.0]),
+        ("treble", [2000.0, 16000.0]),
+    ]
+    .into_iter()
+    .map(|(name, range)| (name.to_string(), range))
+    .collect()
+}
+
+/// Per-frame audio features, each normalized to [0, 1].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    /// Frames per second the track was measured at.
+    pub fps: u32,
+    /// Feature values by name, one per frame.
+    pub features: BTreeMap<String, Vec<f64>>,
+}
+
+impl Analysis {
+    /// Returns the number of frames measured.
+    pub fn frames(&self) -> usize {
+        self.features.values().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Returns `feature` at `frame`: 0 past the end of the track, `None`
+    /// for an unknown feature.
+    pub fn get(&self, feature: &str, frame: usize) -> Option<f64> {
+        let values = self.features.get(feature)?;
+        Some(values.get(frame).copied().unwrap_or(0.0))
+    }
+}
+
+/// Measures `audio` once per frame at `fps`: `rms`, `onset`, and the
+/// magnitude in each of `bands` (Hz ranges, by feature name).
+pub fn analyze(audio: &Audio, fps: u32, bands: &BTreeMap<String, [f64; 2]>) -> Analysis {
+    let rate = audio.sample_rate as f64;
+    let hop = rate / fps as f64;
+    let frames = (audio.samples.len() as f64 / hop).ceil() as usize;
+    let sample = |i: isize| -> f64 {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| audio.samples.get(i))
+            .map_or(0.0, |&s| s as f64)
+    };
+    let hann: Vec<f64> = (0..WINDOW)
+        .map(|i| 0.5 - 0.5 * math::cos(TAU * i as f64 / WINDOW as f64))
+        .collect();
+    let bins: Vec<(&String, std::ops::Range<usize>)> = bands
+        .iter()
+        .map(|(name, &[lo, hi])| {
+            let bin =
+                |hz: f64| ((hz * WINDOW as f64 / rate).ceil().max(0.0) as usize).min(WINDOW / 2);
+            (name, bin(lo)..bin(hi))
+        })
+        .collect();
+
+    let mut rms = Vec::with_capacity(frames);
+    let mut flux = Vec::with_capacity(frames);
+    let mut band_values: Vec<Vec<f64>> = vec![Vec::with_capacity(frames); bins.len()];
+    let mut previous = vec![0.0; WINDOW / 2];
+    let (mut re, mut im) = (vec![0.0; WINDOW], vec![0.0; WINDOW]);
+    for frame in 0..frames {
+        let (start, end) = (
+            (frame as f64 * hop) as isize,
+            ((frame + 1) as f64 * hop) as isize,
+        );
+        let power: f64 = (start..end).map(|i| sample(i) * sample(i)).sum();
+        rms.push((power / (end - start).max(1) as f64).sqrt());
+
+        let first = (start + end) / 2 - WINDOW as isize / 2;
+        for (i, w) in hann.iter().enumerate() {
+            re[i] = sample(first + i as isize) * w;
+            im[i] = 0.0;
+        }
+        fft(&mut re, &mut im);
+        let magnitude: Vec<f64> = (0..WINDOW / 2).map(|k| math::hypot(re[k], im[k])).collect();
+        flux.push(
+            magnitude
+                .iter()
+                .zip(&previous)
+                .map(|(m, p)| (m - p).max(0.0))
+                .sum::<f64>(),
+        );
+        for ((_, range), values) in bins.iter().zip(&mut band_values) {
+            let power: f64 = magnitude[range.clone()].iter().map(|m| m * m).sum();
+            values.push((power / range.len().max(1) as f64).sqrt());
+        }
+        previous = magnitude;
+    }
+    let onset: Vec<f64> = (0..frames)
+        .map(|j| {
+            let history = &flux[j.saturating_sub(ONSET_HISTORY)..j];
+            let average = history.iter().sum::<f64>() / history.len().max(1) as f64;
+            (flux[j] - average).max(0.0)
+        })
+        .collect();
+
+    let mut features: BTreeMap<String, Vec<f64>> = bins
+        .into_iter()
+        .map(|(name, _)| name.clone())
+        .zip(band_values)
+        .collect();
+    features.insert("rms".into(), rms);
+    features.insert("onset".into(), onset);
+    for values in features.values_mut() {
+        let peak = values.iter().copied().fold(0.0, f64::max);
+        if peak > 0.0 {
+            values.iter_mut().for_each(|v| *v /= peak);
+        }
+    }
+    Analysis { fps, features }
+}
+
+/// In-place radix-2 FFT; `re` and `im` have the same power-of-two length.
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let (sin, cos) = math::sin_cos(-TAU / len as f64);
+        for start in (0..n).step_by(len) {
+            let (mut wr, mut wi) = (1.0, 0.0);
+            for k in start..start + len / 2 {
+                let m = k + len / 2;
+                let (tr, ti) = (re[m] * wr - im[m] * wi, re[m] * wi + im[m] * wr);
+                (re[m], im[m]) = (re[k] - tr, im[k] - ti);
+                re[k] += tr;
+                im[k] += ti;
+                (wr, wi) = (wr * cos - wi * sin, wr * sin + wi * cos);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// What a [`Route`] drives.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    /// An engine parameter, by name.
+    Param(String),
+    /// A post effect field as `kind.field`, e.g. `bloom.intensity`.
+    Post(String),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Param(name) => write!(f, "param \"{name}\""),
+            Target::Post(target) => write!(f, "post \"{target}\""),
+        }
+    }
+}
+
+/// One feature driving one target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    /// Feature name: `rms`, `onset`, or a band.
+    pub feature: String,
+    /// What the feature drives.
+    #[serde(flatten)]
+    pub target: Target,
+    /// Target value when the feature is 0.
+    #[serde(default)]
+    pub min: f64,
+    /// Target value when the feature is 1.
+    #[serde(default = "default_max")]
+    pub max: f64,
+    /// Share of the previous frame's value kept, in [0, 1).
+    #[serde(default)]
+    pub smoothing: f64,
+}
+
+fn default_max() -> f64 {
+    1.0
+}
+
+/// An `--audio-map` file: bands to measure, a post stack, and routes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AudioMap {
+    /// Frequency bands in Hz, by feature name.
+    #[serde(default = "default_bands")]
+    pub bands: BTreeMap<String, [f64; 2]>,
+    /// Post effects applied to every frame, their routed fields overridden.
+    #[serde(default)]
+    pub post: Vec<PostEffect>,
+    /// Feature-to-target routes.
+    pub routes: Vec<Route>,
+}
+
+impl AudioMap {
+    /// Reads and checks a mapping file.
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        let map: Self = serde_json::from_str(&text)
+            .map_err(|e| CliError::Input(format!("{}: invalid audio map: {e}", path.display())))?;
+        map.validate()
+            .map_err(|e| CliError::Input(format!("{}: {e}", path.display())))?;
+        Ok(map)
+    }
+
+    /// Checks bands, features, post targets and smoothing.
+    fn validate(&self) -> Result<(), String> {
+        for (name, &[lo, hi]) in &self.bands {
+            if name == "rms" || name == "onset" {
+                return Err(format!("band \"{name}\" shadows a built-in feature"));
+            }
+            if !(lo >= 0.0 && lo < hi && hi.is_finite()) {
+                return Err(format!("band \"{name}\" needs 0 <= low < high"));
+            }
+        }
+        let mut params = BTreeMap::new();
+        let mut fields = BTreeMap::new();
+        for route in &self.routes {
+            let feature = &route.feature;
+            if feature != "rms" && feature != "onset" && !self.bands.contains_key(feature) {
+                return Err(format!(
+                    "unknown feature \"{feature}\"; expected rms, onset, or a band"
+                ));
+            }
+            if !(0.0..1.0).contains(&route.smoothing) {
+                return Err(format!("{feature}: smoothing must be in [0, 1)"));
+            }
+            if !route.min.is_finite() || !route.max.is_finite() {
+                return Err(format!("{feature}: min and max must be finite"));
+            }
+            let seen = match &route.target {
+                Target::Param(name) => params.insert(name, ()),
+                Target::Post(target) => {
+                    let (index, field) = self.post_field(target)?;
+                    fields.insert((index, field), ())
+                }
+            };
+            if seen.is_some() {
+                return Err(format!("{} is routed twice", route.target));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves a `kind.field` post target to the index of the first
+    /// effect of that kind and a number field it has.
+    fn post_field<'a>(&self, target: &'a str) -> Result<(usize, &'a str), String> {
+        let (kind, field) = target
+            .split_once('.')
+            .ok_or_else(|| format!("post target \"{target}\" should be kind.field"))?;
+        let index = self
+            .post
+            .iter()
+            .position(|effect| effect_json(effect)["kind"] == kind)
+            .ok_or_else(|| format!("post target \"{target}\": no {kind} effect in post"))?;
+        if !effect_json(&self.post[index])[field].is_number() {
+            return Err(format!(
+                "post target \"{target}\": {kind} has no number field \"{field}\""
+            ));
+        }
+        Ok((index, field))
+    }
+}
+
+fn effect_json(effect: &PostEffect) -> Value {
+    serde_json::to_value(effect).unwrap_or_default()
+}
+
+/// An [`AudioMap`] evaluated against a track's [`Analysis`]: every route's
+/// target value at every frame.
+#[derive(Debug, Clone)]
+pub struct AudioDrive {
+    map: AudioMap,
+    fps: u32,
+    values: Vec<Vec<f64>>,
+}
+
+impl AudioDrive {
+    /// Smooths and scales each route's feature over the analysis.
+    ///
+    /// A feature `analysis` lacks reads as 0.
+    pub fn new(map: AudioMap, analysis: &Analysis) -> Self {
+        let values = map
+            .routes
+            .iter()
+            .map(|route| {
+                let mut level = 0.0;
+                (0..analysis.frames())
+                    .map(|frame| {
+                        let x = analysis.get(&route.feature, frame).unwrap_or(0.0);
+                        level = route.smoothing * level + (1.0 - route.smoothing) * x;
+                        route.min + (route.max - route.min) * level
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            map,
+            fps: analysis.fps,
+            values,
+        }
+    }
+
+    /// Returns the length of the analyzed track in seconds, rounded up to
+    /// whole frames.
+    pub fn duration(&self) -> f64 {
+        self.values.first().map_or(0, Vec::len) as f64 / self.fps as f64
+    }
+
+    /// Returns route `index`'s value at `frame`, or its `min` past the end.
+    fn value(&self, index: usize, frame: usize) -> f64 {
+        let values = &self.values[index];
+        values
+            .get(frame)
+            .copied()
+            .unwrap_or(self.map.routes[index].min)
+    }
+
+    /// Returns a timeline keying each routed param to its value for frame
+    /// `j` at step `j * every`, held until the next frame, so the steps
+    /// leading to a frame see that frame's audio.
+    pub fn timeline(&self, every: usize) -> ParamTimeline {
+        let frames = self.values.first().map_or(0, Vec::len);
+        let params = self
+            .map
+            .routes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, route)| match &route.target {
+                Target::Param(name) => Some((name.clone(), index)),
+                Target::Post(_) => None,
+            })
+            .map(|(name, index)| {
+                let keys = (0..=frames)
+                    .map(|frame| {
+                        Keyframe::new((frame * every) as f64, self.value(index, frame).into())
+                            .with_easing(Easing::Step)
+                    })
+                    .collect();
+                (name, Track::new(keys))
+            })
+            .collect();
+        ParamTimeline {
+            params,
+            palette: None,
+        }
+    }
+
+    /// Adds the routed params to `timeline`, refusing params it already keys.
+    pub fn steer(
+        &self,
+        timeline: Option<&ParamTimeline>,
+        every: usize,
+    ) -> Result<ParamTimeline, CliError> {
+        let mut steering = timeline.cloned().unwrap_or_default();
+        for (name, track) in self.timeline(every).params {
+            if steering.params.contains_key(&name) {
+                return Err(CliError::Input(format!(
+                    "param \"{name}\" is keyed by both --timeline and --audio-map"
+                )));
+            }
+            steering.params.insert(name, track);
+        }
+        Ok(steering)
+    }
+
+    /// Returns the map's post stack with routed fields set for `frame`.
+    pub fn post_at(&self, frame: usize) -> Result<Vec<PostEffect>, CliError> {
+        let mut stack: Vec<Value> = self.map.post.iter().map(effect_json).collect();
+        for (index, route) in self.map.routes.iter().enumerate() {
+            if let Target::Post(target) = &route.target {
+                let (effect, field) = self.map.post_field(target).map_err(CliError::Input)?;
+                stack[effect][field] = self.value(index, frame).into();
+            }
+        }
+        stack
+            .into_iter()
+            .map(|effect| {
+                serde_json::from_value(effect)
+                    .map_err(|e| CliError::Input(format!("audio map post effect: {e}")))
+            })
+            .collect()
+    }
+
+    /// Applies the post stack for `frame` to RGBA8 pixels.
+    pub fn apply_post(
+        &self,
+        frame: usize,
+        rgba: Vec<u8>,
+        width: usize,
+        height: usize,
+    ) -> Result<Vec<u8>, CliError> {
+        if self.map.post.is_empty() {
+            return Ok(rgba);
+        }
+        let image = Image::from_rgba8(width, height, &rgba)?;
+        Ok(post::apply_stack(&self.post_at(frame)?, &image).to_rgba8())
+    }
+}
+
+/// Audio-reactive options for `animate`.
+#[derive(Args)]
+pub struct AudioArgs {
+    /// Drive params and post effects from this WAV or Ogg Vorbis track
+    /// through --audio-map; --duration defaults to the track's length.
+    #[arg(long, value_name = "PATH", requires = "audio_map")]
+    pub audio: Option<PathBuf>,
+
+    /// JSON routes from audio features (rms, onset, frequency bands) to
+    /// engine params and post effect fields.
+    #[arg(long, value_name = "PATH", requires = "audio")]
+    pub audio_map: Option<PathBuf>,
+}
+
+impl AudioArgs {
+    /// Decodes and analyzes the track at `fps` frames per second, if one
+    /// was given.
+    pub fn load(&self, fps: u32) -> Result<Option<AudioDrive>, CliError> {
+        let (Some(track), Some(map)) = (&self.audio, &self.audio_map) else {
+            return Ok(None);
+        };
+        let map = AudioMap::load(map)?;
+        let track = Audio::load(track)?;
+        let analysis = analyze(&track, fps, &map.bands);
+        Ok(Some(AudioDrive::new(map, &analysis)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// One second of `hz` at 8 kHz, silent where `gate` is false.
+    fn tone(hz: f64, seconds: f64, gate: impl Fn(f64) -> bool) -> Audio {
+        let rate = 8000;
+        let samples = (0..(seconds * rate as f64) as usize)
+            .map(|i| {
+                let t = i as f64 / rate as f64;
+                if gate(t) {
+                    (math::sin(TAU * hz * t) * 0.5) as f32
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        Audio {
+            sample_rate: rate,
+            samples,
+        }
+    }
+
+    fn map(value: serde_json::Value) -> Result<AudioMap, String> {
+        let map: AudioMap = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        map.validate().map(|()| map)
+    }
+
+    // ---- Analysis ----
+
+    #[test]
+    fn fft_finds_a_pure_tone() {
+        let n = 64;
+        let mut re: Vec<f64> = (0..n)
+            .map(|i| math::cos(TAU * 5.0 * i as f64 / n as f64))
+            .collect();
+        let mut im = vec![0.0; n];
+        fft(&mut re, &mut im);
+        let magnitude: Vec<f64> = (0..n / 2).map(|k| math::hypot(re[k], im[k])).collect();
+        assert!((magnitude[5] - n as f64 / 2.0).abs() < 1e-9);
+        assert!(magnitude
+            .iter()
+            .enumerate()
+            .all(|(k, m)| k == 5 || *m < 1e-9));
+    }
+
+    #[test]
+    fn bands_follow_the_pitch() {
+        let low = tone(100.0, 1.0, |t| t < 0.5);
+        let high = tone(3000.0, 1.0, |t| t >= 0.5);
+        let mixed = Audio {
+            samples: low
+                .samples
+                .iter()
+                .zip(&high.samples)
+                .map(|(a, b)| a + b)
+                .collect(),
+            ..low
+        };
+        let analysis = analyze(&mixed, 10, &default_bands());
+        assert_eq!(analysis.frames(), 10);
+        assert!(analysis.get("bass", 2).unwrap() > 0.9);
+        assert!(analysis.get("treble", 2).unwrap() < 0.05);
+        assert!(analysis.get("treble", 7).unwrap() > 0.9);
+        assert!(analysis.get("bass", 7).unwrap() < 0.05);
+        // Same amplitude throughout
+        assert!((analysis.get("rms", 2).unwrap() - analysis.get("rms", 7).unwrap()).abs() < 0.01);
+        // Past the end, and unknown
+        assert_eq!(analysis.get("rms", 10), Some(0.0));
+        assert_eq!(analysis.get("kick", 0), None);
+    }
+
+    #[test]
+    fn onsets_spike_where_sound_starts() {
+        let audio = tone(440.0, 2.0, |t| {
+            (0.5..0.6).contains(&t) || (1.5..1.6).contains(&t)
+        });
+        let analysis = analyze(&audio, 20, &default_bands());
+        let onset = &analysis.features["onset"];
+        let peak = onset.iter().copied().fold(0.0, f64::max);
+        assert_eq!(peak, 1.0);
+        for (frame, value) in onset.iter().enumerate() {
+            let near_attack = (8..=10).contains(&frame) || (28..=30).contains(&frame);
+            assert!(near_attack || *value < 0.2, "frame {frame}: {value}");
+        }
+        assert!(onset[9] > 0.5 && onset[29] > 0.5);
+        // Silence stays silent rather than dividing by zero
+        let silent = analyze(&tone(440.0, 0.5, |_| false), 10, &default_bands());
+        assert!(silent.features.values().flatten().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn wav_files_decode_and_mix_to_mono() {
+        let path =
+            std::env::temp_dir().join(format!("art-engine-audio-{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..4000 {
+            writer.write_sample(16384i16).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        let audio = Audio::load(&path).unwrap();
+        assert_eq!(audio.sample_rate, 8000);
+        assert_eq!(audio.samples.len(), 4000);
+        assert!(audio.samples.iter().all(|&s| s == 0.25));
+
+        std::fs::write(&path, b"not audio").unwrap();
+        assert!(matches!(Audio::load(&path), Err(CliError::Input(_))));
+        let ogg = path.with_extension("ogg");
+        std::fs::write(&ogg, b"not audio").unwrap();
+        assert!(matches!(Audio::load(&ogg), Err(CliError::Input(_))));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&ogg);
+        assert!(matches!(
+            Audio::load(Path::new("song.mp3")),
+            Err(CliError::Input(_))
+        ));
+    }
+
+    // ---- Mapping ----
+
+    #[test]
+    fn maps_check_features_and_targets() {
+        let ok = map(json!({
+            "bands": { "kick": [40, 120] },
+            "post": [{ "kind": "bloom", "intensity": 0.0 }],
+            "routes": [
+                { "feature": "kick", "param": "feed_rate", "min": 0.03, "max": 0.05 },
+                { "feature": "onset", "post": "bloom.intensity" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(ok.routes[0].target, Target::Param("feed_rate".into()));
+        assert_eq!(ok.routes[1].max, 1.0);
+        // Default bands
+        assert!(map(json!({ "routes": [{ "feature": "treble", "param": "p" }] })).is_ok());
+
+        for bad in [
+            json!({ "routes": [{ "feature": "kick", "param": "p" }] }),
+            json!({ "routes": [{ "feature": "rms", "post": "bloom.intensity" }] }),
+            json!({ "post": [{ "kind": "grain", "amount": 0.1 }],
+                    "routes": [{ "feature": "rms", "post": "grain.size" }] }),
+            json!({ "post": [{ "kind": "grain", "amount": 0.1 }],
+                    "routes": [{ "feature": "rms", "post": "grain" }] }),
+            json!({ "routes": [{ "feature": "rms", "param": "p", "smoothing": 1.0 }] }),
+            json!({ "routes": [{ "feature": "rms", "param": "p" }, { "feature": "bass", "param": "p" }] }),
+            json!({ "bands": { "onset": [0, 100] }, "routes": [] }),
+            json!({ "bands": { "x": [500, 100] }, "routes": [] }),
+            json!({ "routes": [{ "feature": "rms" }] }),
+            json!({ "routes": [], "extra": 1 }),
+        ] {
+            assert!(map(bad.clone()).is_err(), "{bad}");
+        }
+    }
+
+    fn drive() -> AudioDrive {
+        let analysis = Analysis {
+            fps: 10,
+            features: [
+                ("rms".to_string(), vec![0.0, 1.0, 0.5]),
+                ("onset".to_string(), vec![1.0, 0.0, 0.0]),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let map = map(json!({
+            "post": [{ "kind": "vignette", "strength": 0.2 }, { "kind": "bloom", "intensity": 0.0 }],
+            "routes": [
+                { "feature": "rms", "param": "feed_rate", "min": 0.02, "max": 0.06 },
+                { "feature": "onset", "post": "bloom.intensity", "max": 2.0, "smoothing": 0.5 }
+            ]
+        }))
+        .unwrap();
+        AudioDrive::new(map, &analysis)
+    }
+
+    #[test]
+    fn drives_key_params_at_frame_boundaries() {
+        let drive = drive();
+        assert!((drive.duration() - 0.3).abs() < 1e-12);
+        let timeline = drive.timeline(5);
+        let at = |step: f64| timeline.params_at(step)["feed_rate"].as_f64().unwrap();
+        // Held through each frame's steps, then the track ends at min
+        assert_eq!(at(0.0), 0.02);
+        assert_eq!(at(4.0), 0.02);
+        assert!((at(5.0) - 0.06).abs() < 1e-12);
+        assert!((at(10.0) - 0.04).abs() < 1e-12);
+        assert_eq!(at(15.0), 0.02);
+        assert_eq!(at(100.0), 0.02);
+        assert!(timeline.palette.is_none());
+    }
+
+    #[test]
+    fn drives_merge_with_timelines() {
+        let drive = drive();
+        let other: ParamTimeline = serde_json::from_value(json!({
+            "params": { "kill_rate": [{ "frame": 0, "value": 0.06 }] },
+            "palette": [{ "frame": 0, "value": "fire" }]
+        }))
+        .unwrap();
+        let steering = drive.steer(Some(&other), 5).unwrap();
+        assert_eq!(steering.params.len(), 2);
+        assert_eq!(steering.palette_at(0.0).as_deref(), Some("fire"));
+        assert_eq!(drive.steer(None, 5).unwrap().params.len(), 1);
+        let clash: ParamTimeline = serde_json::from_value(json!({
+            "params": { "feed_rate": [{ "frame": 0, "value": 0.05 }] }
+        }))
+        .unwrap();
+        assert!(matches!(
+            drive.steer(Some(&clash), 5),
+            Err(CliError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn drives_set_post_fields_per_frame() {
+        let drive = drive();
+        let bloom = |frame: usize| match &drive.post_at(frame).unwrap()[1] {
+            PostEffect::Bloom(bloom) => bloom.intensity,
+            other => panic!("{other:?}"),
+        };
+        // Smoothed: 2 * (0.5, 0.25, 0.125), then min past the end
+        assert_eq!(bloom(0), 1.0);
+        assert_eq!(bloom(1), 0.5);
+        assert_eq!(bloom(2), 0.25);
+        assert_eq!(bloom(3), 0.0);
+        // Unrouted effects and fields keep their values
+        assert_eq!(drive.post_at(1).unwrap()[0], drive.map.post[0]);
+
+        let rgba = vec![200; 4 * 4 * 4];
+        let out = drive.apply_post(0, rgba.clone(), 4, 4).unwrap();
+        assert_eq!(out.len(), rgba.len());
+        assert_ne!(out, rgba);
+        assert!(drive.apply_post(0, rgba, 3, 4).is_err());
+    }
+}
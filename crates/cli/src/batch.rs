@@ -0,0 +1,295 @@
+//! Batch rendering: a JSON job list executed across worker threads.
+//!
+//! A job file is a JSON array of [`BatchJob`]s, each a [`Seed`] (its fields
+//! inline, `oversample` included) plus an optional output path:
+//!
+//! ```json
+//! [
+//!   { "engine": "gray-scott", "width": 512, "height": 512, "params": {},
+//!     "seed": 1, "steps": 2000, "palette": "fire", "output": "piece-1.png" },
+//!   { "engine": "gray-scott", "width": 512, "height": 512, "params": {},
+//!     "seed": 2, "steps": 2000 }
+//! ]
+//! ```
+//!
+//! Jobs without an `output` are named `NNNN_<engine>_<seed>.png`; no two
+//! jobs may write the same file. Jobs run on a fixed pool of scoped threads
+//! pulling from a shared counter; unless the run continues on error, the
+//! first failure stops new jobs from starting (jobs already running finish).
+
+use crate::error::CliError;
+use crate::{render_png, Sim};
+use art_engine_core::{MemoryBudget, Seed};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// One entry of a job file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchJob {
+    /// What to render.
+    #[serde(flatten)]
+    pub seed: Seed,
+    /// Output PNG path; defaults to [`BatchJob::default_output`].
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+}
+
+impl BatchJob {
+    /// Returns the output path, naming unnamed jobs after their position.
+    pub fn output_path(&self, index: usize) -> PathBuf {
+        self.output
+            .clone()
+            .unwrap_or_else(|| self.default_output(index))
+    }
+
+    /// Checks the seed's dimensions and oversample factor.
+    pub fn validate(&self) -> Result<(), String> {
+        self.seed.validate().map_err(|e| e.to_string())
+    }
+
+    /// Returns `NNNN_<engine>_<seed>.png` for the job at `index`.
+    pub fn default_output(&self, index: usize) -> PathBuf {
+        PathBuf::from(format!(
+            "{index:04}_{}_{}.png",
+            self.seed.engine, self.seed.seed
+        ))
+    }
+}
+
+/// Parses a job file and validates every job's seed and output path.
+///
+/// Returns `CliError::Input` naming the first malformed job, or the first
+/// job writing to the same output as an earlier one (parallel workers would
+/// otherwise race on the file).
+pub fn parse_jobs(text: &str) -> Result<Vec<BatchJob>, CliError> {
+    let jobs: Vec<BatchJob> = serde_json::from_str(text)
+        .map_err(|e| CliError::Input(format!("invalid job list: {e}")))?;
+    jobs.iter().enumerate().try_for_each(|(i, job)| {
+        job.validate()
+            .map_err(|e| CliError::Input(format!("job {i}: {e}")))
+    })?;
+    let mut outputs = HashMap::new();
+    jobs.iter().enumerate().try_for_each(|(i, job)| {
+        let output = job.output_path(i);
+        match outputs.insert(output.clone(), i) {
+            Some(first) => Err(CliError::Input(format!(
+                "job {i}: output {} is also written by job {first}",
+                output.display()
+            ))),
+            None => Ok(()),
+        }
+    })?;
+    Ok(jobs)
+}
+
+/// Runs `work` on every job using `threads` workers and returns the result
+/// of each job, `None` for jobs skipped after a failure.
+///
+/// `report` is called from the worker thread as each job finishes. With
+/// `continue_on_error` false, no new job starts once one has failed.
+pub fn run_jobs<T: Sync, R: Send>(
+    jobs: &[T],
+    threads: usize,
+    continue_on_error: bool,
+    work: impl Fn(usize, &T) -> Result<R, CliError> + Sync,
+    report: impl Fn(usize, &Result<R, CliError>) + Sync,
+) -> Vec<Option<Result<R, CliError>>> {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new((0..jobs.len()).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|scope| {
+        (0..threads.clamp(1, jobs.len().max(1))).for_each(|_| {
+            scope.spawn(|| loop {
+                if !continue_on_error && failed.load(Ordering::Relaxed) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+                let result = work(index, job);
+                if result.is_err() {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                report(index, &result);
+                // A poisoned lock only means another worker panicked mid-store
+                let mut results = results.lock().unwrap_or_else(|e| e.into_inner());
+                results[index] = Some(result);
+            });
+        });
+    });
+    results.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the requested worker count, defaulting to the available cores.
+pub fn worker_threads(requested: Option<u32>) -> usize {
+    requested.map_or_else(
+        || std::thread::available_parallelism().map_or(1, |n| n.get()),
+        |n| n as usize,
+    )
+}
+
+/// Runs `batch`: renders the job file at `jobs` on `threads` workers
+/// within `memory` each, reporting every job as it finishes.
+///
+/// Returns the first failure, so scripts see a meaningful exit code.
+pub fn run(
+    jobs: &Path,
+    threads: Option<u32>,
+    continue_on_error: bool,
+    memory: MemoryBudget,
+    json: bool,
+) -> Result<(), CliError> {
+    let text = std::fs::read_to_string(jobs)
+        .map_err(|e| CliError::Io(format!("{}: {e}", jobs.display())))?;
+    let jobs = parse_jobs(&text)?;
+    let threads = worker_threads(threads);
+    let total = jobs.len();
+    let results = run_jobs(
+        &jobs,
+        threads,
+        continue_on_error,
+        |index, job| {
+            let output = job.output_path(index);
+            let sim = Sim {
+                memory,
+                ..Sim::from_seed(job.seed.clone())
+            };
+            render_png(&sim, &output).map(|()| output)
+        },
+        |index, result| {
+            if json {
+                return;
+            }
+            match result {
+                Ok(output) => {
+                    eprintln!("[{}/{total}] ok     {}", index + 1, output.display())
+                }
+                Err(e) => eprintln!("[{}/{total}] failed {e}", index + 1),
+            }
+        },
+    );
+
+    let rendered = results.iter().flatten().filter(|r| r.is_ok()).count();
+    if json {
+        let status: Vec<serde_json::Value> = results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Some(Ok(output)) => serde_json::json!({
+                    "job": index, "status": "ok", "output": output.display().to_string(),
+                }),
+                Some(Err(e)) => serde_json::json!({
+                    "job": index, "status": "failed", "error": e.to_string(),
+                    "exit_code": e.exit_code(),
+                }),
+                None => serde_json::json!({ "job": index, "status": "skipped" }),
+            })
+            .collect();
+        let info = serde_json::json!({
+            "total": total,
+            "rendered": rendered,
+            "threads": threads,
+            "jobs": status,
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        eprintln!("batch: {rendered} of {total} jobs rendered");
+    }
+    // Exit with the first failure so scripts see a meaningful code
+    if let Some(err) = results.into_iter().flatten().find_map(Result::err) {
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOBS: &str = r#"[
+        {"engine": "gray-scott", "width": 8, "height": 8, "params": {}, "seed": 3,
+         "steps": 1, "output": "a.png"},
+        {"engine": "gray-scott", "width": 8, "height": 8, "params": {}, "seed": 4,
+         "steps": 1, "oversample": 2}
+    ]"#;
+
+    #[test]
+    fn parse_jobs_reads_inline_seeds_and_defaults() {
+        let jobs = parse_jobs(JOBS).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].output_path(0), PathBuf::from("a.png"));
+        assert_eq!(jobs[0].seed.oversample, 1);
+        assert_eq!(jobs[1].seed.seed, 4);
+        assert_eq!(jobs[1].seed.oversample, 2);
+        assert_eq!(
+            jobs[1].output_path(1),
+            PathBuf::from("0001_gray-scott_4.png")
+        );
+    }
+
+    #[test]
+    fn parse_jobs_rejects_invalid_seeds() {
+        let bad = JOBS.replace(
+            r#""width": 8, "height": 8, "params": {}, "seed": 4"#,
+            r#""width": 0, "height": 8, "params": {}, "seed": 4"#,
+        );
+        assert!(matches!(parse_jobs(&bad), Err(CliError::Input(m)) if m.starts_with("job 1")));
+        assert!(parse_jobs("{}").is_err());
+    }
+
+    #[test]
+    fn parse_jobs_rejects_duplicate_outputs() {
+        let clash = JOBS.replace(
+            r#""oversample": 2"#,
+            r#""oversample": 2, "output": "a.png""#,
+        );
+        assert!(matches!(
+            parse_jobs(&clash),
+            Err(CliError::Input(m)) if m == "job 1: output a.png is also written by job 0"
+        ));
+        // An explicit output may also collide with a default name
+        let clash = JOBS.replace("a.png", "0001_gray-scott_4.png");
+        assert!(matches!(parse_jobs(&clash), Err(CliError::Input(m)) if m.starts_with("job 1")));
+    }
+
+    #[test]
+    fn run_jobs_runs_everything_in_parallel() {
+        let jobs: Vec<u32> = (0..20).collect();
+        let done = AtomicUsize::new(0);
+        let results = run_jobs(
+            &jobs,
+            4,
+            false,
+            |_, n| Ok(n * 2),
+            |_, _| {
+                done.fetch_add(1, Ordering::Relaxed);
+            },
+        );
+        assert_eq!(done.load(Ordering::Relaxed), 20);
+        let values: Vec<u32> = results.into_iter().map(|r| r.unwrap().unwrap()).collect();
+        assert_eq!(values, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn first_failure_stops_new_jobs_unless_continuing() {
+        let jobs: Vec<u32> = (0..10).collect();
+        let work = |_: usize, n: &u32| {
+            if *n == 0 {
+                Err(CliError::Input("boom".into()))
+            } else {
+                Ok(*n)
+            }
+        };
+        let stopped = run_jobs(&jobs, 1, false, work, |_, _| {});
+        assert!(stopped[0].as_ref().unwrap().is_err());
+        assert!(stopped[1..].iter().all(Option::is_none));
+
+        let continued = run_jobs(&jobs, 1, true, work, |_, _| {});
+        assert!(continued.iter().all(Option::is_some));
+        assert_eq!(continued.iter().flatten().filter(|r| r.is_ok()).count(), 9);
+    }
+}
@@ -0,0 +1,227 @@
+//! Engine throughput benchmarks.
+//!
+//! Each run builds an engine with default parameters, takes one untimed
+//! warm-up step, then times the requested number of steps through a
+//! [`StepMonitor`], which also records the slowest step. Memory is the
+//! size of the engine's evolving state as reported by `save_state`, falling
+//! back to one `f64` field when the engine cannot save its state.
+
+use crate::error::CliError;
+use art_engine_core::observer::from_fn;
+use art_engine_core::{Engine, StepInfo, StepMonitor};
+use art_engine_engines::EngineKind;
+use serde::Serialize;
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+/// Result of benchmarking one engine at one size.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    /// Engine name.
+    pub engine: String,
+    /// Grid width in cells.
+    pub width: usize,
+    /// Grid height in cells.
+    pub height: usize,
+    /// Timed steps.
+    pub steps: usize,
+    /// Wall-clock time of the timed steps.
+    pub seconds: f64,
+    /// Wall-clock time of the slowest timed step, in milliseconds.
+    pub slowest_step_ms: f64,
+    /// Steps per second.
+    pub steps_per_sec: f64,
+    /// Cells updated per second (steps per second times grid area).
+    pub pixels_per_sec: f64,
+    /// Bytes of engine state.
+    pub state_bytes: usize,
+}
+
+/// Parses a benchmark size: `N` for a square grid or `WxH`.
+pub fn parse_bench_size(text: &str) -> Result<(usize, usize), String> {
+    match text.trim().parse::<usize>() {
+        Ok(0) => Err(format!("invalid size '{text}'")),
+        Ok(n) => Ok((n, n)),
+        Err(_) => crate::parse_size(text),
+    }
+}
+
+/// Runs `bench`: every engine in `engines` (all of them if empty) at every
+/// size, printed as a table or as JSON.
+pub fn run(
+    engines: Vec<String>,
+    sizes: &[(usize, usize)],
+    steps: usize,
+    json: bool,
+) -> Result<(), CliError> {
+    let engines: Vec<String> = match engines.is_empty() {
+        true => EngineKind::list_engines()
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        false => engines,
+    };
+    let mut results = Vec::new();
+    for engine in &engines {
+        for &(width, height) in sizes {
+            if !json {
+                eprintln!("benchmarking {engine} at {width}x{height}...");
+            }
+            results.push(measure(engine, width, height, steps)?);
+        }
+    }
+    if json {
+        let info = serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "results": results,
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        print!("{}", table(&results));
+    }
+    Ok(())
+}
+
+/// Benchmarks `engine` at `width x height` for `steps` timed steps.
+pub fn measure(
+    engine: &str,
+    width: usize,
+    height: usize,
+    steps: usize,
+) -> Result<BenchResult, CliError> {
+    let mut instance = EngineKind::from_name(engine, width, height, 42, &serde_json::json!({}))?;
+    instance.step()?;
+    let (mut elapsed, mut slowest) = (Duration::ZERO, Duration::ZERO);
+    let mut monitor = StepMonitor::new().with_observer(from_fn(|info: &StepInfo| {
+        elapsed = info.elapsed;
+        slowest = slowest.max(info.step_time);
+        ControlFlow::Continue(())
+    }));
+    monitor.start(instance.field());
+    for step in 1..=steps {
+        instance.step()?;
+        // The observer never asks to stop
+        let _ = monitor.observe(step, instance.field());
+    }
+    drop(monitor);
+    // Guard against a zero reading on coarse clocks
+    let seconds = elapsed.as_secs_f64().max(1e-9);
+    let state_bytes = instance
+        .save_state()
+        .map(|state| state.len())
+        .unwrap_or(width * height * std::mem::size_of::<f64>());
+    let steps_per_sec = steps as f64 / seconds;
+    Ok(BenchResult {
+        engine: engine.to_string(),
+        width,
+        height,
+        steps,
+        seconds,
+        slowest_step_ms: slowest.as_secs_f64() * 1e3,
+        steps_per_sec,
+        pixels_per_sec: steps_per_sec * (width * height) as f64,
+        state_bytes,
+    })
+}
+
+/// Formats a rate or count with a k/M/G suffix and three significant digits.
+pub fn si(value: f64) -> String {
+    let (scaled, suffix) = [(1e9, "G"), (1e6, "M"), (1e3, "k")]
+        .into_iter()
+        .find(|(unit, _)| value >= *unit)
+        .map_or((value, ""), |(unit, suffix)| (value / unit, suffix));
+    let decimals = match scaled {
+        s if s >= 100.0 => 0,
+        s if s >= 10.0 => 1,
+        _ => 2,
+    };
+    format!("{scaled:.decimals$}{suffix}")
+}
+
+/// Formats a byte count in binary units.
+pub fn bytes(count: usize) -> String {
+    let mut value = count as f64;
+    let mut unit = "B";
+    for next in ["KiB", "MiB", "GiB"] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    match unit {
+        "B" => format!("{count} B"),
+        _ => format!("{value:.1} {unit}"),
+    }
+}
+
+/// Formats results as an aligned text table.
+pub fn table(results: &[BenchResult]) -> String {
+    let rows: Vec<[String; 5]> = results
+        .iter()
+        .map(|r| {
+            [
+                r.engine.clone(),
+                format!("{}x{}", r.width, r.height),
+                si(r.steps_per_sec),
+                si(r.pixels_per_sec),
+                bytes(r.state_bytes),
+            ]
+        })
+        .collect();
+    let header = ["ENGINE", "SIZE", "STEPS/S", "PIXELS/S", "STATE"].map(String::from);
+    let all = || std::iter::once(&header).chain(&rows);
+    let widths: Vec<usize> = (0..5)
+        .map(|i| all().map(|r| r[i].len()).max().unwrap_or(0))
+        .collect();
+    all()
+        .map(|r| {
+            let line: Vec<String> = (0..5)
+                .map(|i| match i {
+                    0 | 1 => format!("{:<w$}", r[i], w = widths[i]),
+                    _ => format!("{:>w$}", r[i], w = widths[i]),
+                })
+                .collect();
+            format!("{}\n", line.join("  ").trim_end())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_accept_square_and_rectangular_forms() {
+        assert_eq!(parse_bench_size("128"), Ok((128, 128)));
+        assert_eq!(parse_bench_size("64x32"), Ok((64, 32)));
+        assert!(parse_bench_size("0").is_err());
+        assert!(parse_bench_size("big").is_err());
+    }
+
+    #[test]
+    fn units_scale_with_magnitude() {
+        assert_eq!(si(950.0), "950");
+        assert_eq!(si(1234.0), "1.23k");
+        assert_eq!(si(45_600_000.0), "45.6M");
+        assert_eq!(bytes(512), "512 B");
+        assert_eq!(bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn run_measures_throughput_and_state() {
+        let result = measure("gray-scott", 16, 8, 3).unwrap();
+        assert_eq!(result.steps, 3);
+        assert!(result.steps_per_sec > 0.0);
+        assert!(result.slowest_step_ms > 0.0);
+        assert!(result.slowest_step_ms <= result.seconds * 1e3);
+        assert!((result.pixels_per_sec - result.steps_per_sec * 128.0).abs() < 1e-6);
+        // Two fields of dimensions plus f64 data
+        assert_eq!(result.state_bytes, 2 * (16 + 128 * 8));
+        assert!(measure("nope", 8, 8, 1).is_err());
+
+        let text = table(&[result]);
+        assert!(text.starts_with("ENGINE"));
+        assert!(text.lines().nth(1).unwrap().starts_with("gray-scott  16x8"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Checkpoint files for resuming interrupted renders.
+//!
+//! A checkpoint holds everything needed to continue a run: the [`Seed`]
+//! describing it (oversample factor included), the number of steps
+//! completed and the engine's saved state (see `Engine::save_state`). The file layout is:
+//!
+//! ```text
+//! b"ARTCKPT1" | u32 LE header length | header JSON | engine state bytes
+//! ```
+//!
+//! Checkpoints are written to a temporary sibling and renamed into place,
+//! so an interruption mid-write leaves the previous checkpoint intact.
+
+use crate::error::CliError;
+use crate::Sim;
+use art_engine_core::Seed;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Magic bytes identifying a checkpoint file (and its format version).
+const MAGIC: &[u8; 8] = b"ARTCKPT1";
+
+/// Metadata stored ahead of the engine state.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    seed: Seed,
+    step: usize,
+}
+
+/// A saved point in a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// The run being checkpointed; `steps` is its total length.
+    pub seed: Seed,
+    /// Steps completed when the checkpoint was taken.
+    pub step: usize,
+    /// Engine state from `Engine::save_state`.
+    pub state: Vec<u8>,
+}
+
+/// Where and how often a run writes checkpoints.
+#[derive(Debug, Clone)]
+pub struct CheckpointPlan {
+    /// Steps between checkpoints.
+    pub every: usize,
+    /// Checkpoint file, overwritten each time.
+    pub path: PathBuf,
+}
+
+impl Checkpoint {
+    /// Encodes the checkpoint in the file layout described above.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CliError> {
+        let header = serde_json::to_vec(&Header {
+            seed: self.seed.clone(),
+            step: self.step,
+        })?;
+        let len = u32::try_from(header.len())
+            .map_err(|_| CliError::Serialization("checkpoint header too large".into()))?;
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + header.len() + self.state.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&self.state);
+        Ok(bytes)
+    }
+
+    /// Decodes a checkpoint and validates its seed.
+    ///
+    /// Returns `CliError::Input` if the data is not a valid checkpoint.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CliError> {
+        let invalid = |why: &str| CliError::Input(format!("invalid checkpoint: {why}"));
+        let rest = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| invalid("not a checkpoint file"))?;
+        let (len, rest) = rest
+            .split_first_chunk::<4>()
+            .ok_or_else(|| invalid("truncated header"))?;
+        let len = u32::from_le_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err(invalid("truncated header"));
+        }
+        let (header, state) = rest.split_at(len);
+        let header: Header = serde_json::from_slice(header).map_err(|e| invalid(&e.to_string()))?;
+        header
+            .seed
+            .validate()
+            .map_err(|e| invalid(&e.to_string()))?;
+        if header.step > header.seed.steps {
+            return Err(invalid("inconsistent step count"));
+        }
+        Ok(Self {
+            seed: header.seed,
+            step: header.step,
+            state: state.to_vec(),
+        })
+    }
+
+    /// Reads a checkpoint file.
+    pub fn read(path: &Path) -> Result<Self, CliError> {
+        let bytes =
+            std::fs::read(path).map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        Self::from_bytes(&bytes).map_err(|e| match e {
+            CliError::Input(msg) => CliError::Input(format!("{}: {msg}", path.display())),
+            other => other,
+        })
+    }
+
+    /// Writes the checkpoint, replacing `path` atomically.
+    pub fn write(&self, path: &Path) -> Result<(), CliError> {
+        let bytes = self.to_bytes()?;
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        let io_err = |e: std::io::Error| CliError::Io(format!("{}: {e}", path.display()));
+        std::fs::write(&temp, bytes).map_err(io_err)?;
+        std::fs::rename(&temp, path).map_err(io_err)
+    }
+}
+
+/// Checkpoint options shared by `render` and `animate`.
+#[derive(Args)]
+pub struct CheckpointArgs {
+    /// Save a resumable checkpoint every N simulation steps.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    checkpoint_every: Option<u32>,
+
+    /// Checkpoint file [default: the --resume file, or checkpoint.bin].
+    #[arg(long, value_name = "PATH", requires = "checkpoint_every")]
+    checkpoint_file: Option<PathBuf>,
+}
+
+impl CheckpointArgs {
+    /// Returns the checkpoint plan for `sim`, if checkpointing was requested.
+    pub fn plan(&self, sim: &Sim) -> Option<CheckpointPlan> {
+        let every = self.checkpoint_every? as usize;
+        let path = self
+            .checkpoint_file
+            .clone()
+            .or_else(|| sim.resume.as_ref().map(|r| r.path.clone()))
+            .unwrap_or_else(|| PathBuf::from("checkpoint.bin"));
+        Some(CheckpointPlan { every, path })
+    }
+}
+
+/// Where a resumed run picks up.
+#[derive(Clone)]
+pub struct Resume {
+    /// The checkpoint file it was loaded from.
+    pub path: PathBuf,
+    /// Steps already completed.
+    pub step: usize,
+    /// Saved engine state.
+    pub state: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint() -> Checkpoint {
+        Checkpoint {
+            seed: Seed {
+                steps: 100,
+                palette: Some("fire".into()),
+                oversample: 2,
+                ..Seed::new("gray-scott", 8, 4, 9)
+            },
+            step: 40,
+            state: vec![1, 2, 3, 4, 5],
+        }
+    }
+
+    #[test]
+    fn round_trips_through_bytes_and_files() {
+        let original = checkpoint();
+        let decoded = Checkpoint::from_bytes(&original.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, original);
+
+        let path = std::env::temp_dir().join(format!("art-engine-ckpt-{}.bin", std::process::id()));
+        original.write(&path).unwrap();
+        assert_eq!(Checkpoint::read(&path).unwrap(), original);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_foreign_and_inconsistent_data() {
+        assert!(matches!(
+            Checkpoint::from_bytes(b"PNG....."),
+            Err(CliError::Input(_))
+        ));
+        let bytes = checkpoint().to_bytes().unwrap();
+        assert!(Checkpoint::from_bytes(&bytes[..14]).is_err());
+
+        let overrun = Checkpoint {
+            step: 101,
+            ..checkpoint()
+        };
+        let bytes = overrun.to_bytes().unwrap();
+        assert!(Checkpoint::from_bytes(&bytes).is_err());
+    }
+}
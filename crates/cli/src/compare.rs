@@ -0,0 +1,272 @@
+//! Pixel comparison of two rendered images for golden-image checks.
+//!
+//! [`diff_stats`] reports per-channel difference statistics over the RGBA8
+//! data plus the structural similarity (SSIM) of the luma channels, computed
+//! as the mean over 8x8 windows placed every 4 pixels with the standard
+//! constants `C1 = (0.01 * 255)^2` and `C2 = (0.03 * 255)^2`. Identical
+//! images have an SSIM of exactly 1.
+
+use crate::error::CliError;
+use art_engine_core::{EngineError, Field, Palette};
+use serde::Serialize;
+use std::path::Path;
+
+/// SSIM window size in pixels.
+const WINDOW: usize = 8;
+/// Distance between SSIM windows in pixels.
+const STRIDE: usize = 4;
+
+/// Difference statistics between two equally sized RGBA8 images.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffStats {
+    /// Image width in pixels.
+    pub width: usize,
+    /// Image height in pixels.
+    pub height: usize,
+    /// Pixels with at least one differing channel.
+    pub differing_pixels: usize,
+    /// Largest absolute difference of any channel, 0-255.
+    pub max_diff: u8,
+    /// Mean absolute channel difference, 0-255.
+    pub mean_diff: f64,
+    /// Root-mean-square channel difference, 0-255.
+    pub rmse: f64,
+    /// Peak signal-to-noise ratio in dB; `None` for identical images.
+    pub psnr: Option<f64>,
+    /// Mean structural similarity of the luma channels, up to 1.
+    pub ssim: f64,
+}
+
+/// Compares two RGBA8 buffers of `width x height` pixels.
+pub fn diff_stats(a: &[u8], b: &[u8], width: usize, height: usize) -> DiffStats {
+    let diffs = || a.iter().zip(b).map(|(&x, &y)| x.abs_diff(y));
+    let count = a.len().max(1) as f64;
+    let sum: f64 = diffs().map(f64::from).sum();
+    let sum_sq: f64 = diffs().map(|d| f64::from(d).powi(2)).sum();
+    let rmse = (sum_sq / count).sqrt();
+    DiffStats {
+        width,
+        height,
+        differing_pixels: a
+            .chunks_exact(4)
+            .zip(b.chunks_exact(4))
+            .filter(|(p, q)| p != q)
+            .count(),
+        max_diff: diffs().max().unwrap_or(0),
+        mean_diff: sum / count,
+        rmse,
+        psnr: (rmse > 0.0).then(|| 20.0 * (255.0 / rmse).log10()),
+        ssim: ssim(&luma(a), &luma(b), width, height),
+    }
+}
+
+/// Returns the Rec. 601 luma of each RGBA8 pixel, 0-255.
+fn luma(rgba: &[u8]) -> Vec<f64> {
+    rgba.chunks_exact(4)
+        .map(|p| 0.299 * f64::from(p[0]) + 0.587 * f64::from(p[1]) + 0.114 * f64::from(p[2]))
+        .collect()
+}
+
+/// Returns the window origins along one axis: every [`STRIDE`] pixels, with
+/// the last window flush against the edge (a single window if `len` is
+/// smaller than [`WINDOW`]).
+fn window_starts(len: usize) -> Vec<usize> {
+    let last = len.saturating_sub(WINDOW);
+    let mut starts: Vec<usize> = (0..=last).step_by(STRIDE).collect();
+    if starts.last() != Some(&last) {
+        starts.push(last);
+    }
+    starts
+}
+
+/// Mean SSIM of two luma planes over sliding windows.
+fn ssim(a: &[f64], b: &[f64], width: usize, height: usize) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    let (xs, ys) = (window_starts(width), window_starts(height));
+    let (ww, wh) = (WINDOW.min(width), WINDOW.min(height));
+    let n = (ww * wh) as f64;
+    let scores: Vec<f64> = ys
+        .iter()
+        .flat_map(|&y0| xs.iter().map(move |&x0| (x0, y0)))
+        .map(|(x0, y0)| {
+            let pixels =
+                || (y0..y0 + wh).flat_map(move |y| (x0..x0 + ww).map(move |x| y * width + x));
+            let mean_a = pixels().map(|i| a[i]).sum::<f64>() / n;
+            let mean_b = pixels().map(|i| b[i]).sum::<f64>() / n;
+            let (mut var_a, mut var_b, mut cov) = (0.0, 0.0, 0.0);
+            pixels().for_each(|i| {
+                let (da, db) = (a[i] - mean_a, b[i] - mean_b);
+                var_a += da * da;
+                var_b += db * db;
+                cov += da * db;
+            });
+            let (var_a, var_b, cov) = (var_a / n, var_b / n, cov / n);
+            ((2.0 * mean_a * mean_b + C1) * (2.0 * cov + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2))
+        })
+        .collect();
+    scores.iter().sum::<f64>() / scores.len().max(1) as f64
+}
+
+/// Returns each pixel's largest channel difference as a field, scaled so
+/// the largest difference in the image is 1 (all zero for identical
+/// images), ready to map through a palette as a heat map.
+pub fn heat_map(a: &[u8], b: &[u8], width: usize, height: usize) -> Result<Field, EngineError> {
+    let per_pixel: Vec<f64> = a
+        .chunks_exact(4)
+        .zip(b.chunks_exact(4))
+        .map(|(p, q)| {
+            p.iter()
+                .zip(q)
+                .map(|(&x, &y)| f64::from(x.abs_diff(y)))
+                .fold(0.0, f64::max)
+        })
+        .collect();
+    let peak = per_pixel.iter().copied().fold(0.0, f64::max);
+    let data = match peak {
+        0.0 => per_pixel,
+        _ => per_pixel.into_iter().map(|d| d / peak).collect(),
+    };
+    Field::from_data(width, height, data)
+}
+
+/// Runs `compare`: prints how `b` differs from `a` and writes a heat map
+/// of the differences to `diff`, if given.
+///
+/// Returns `CliError::Mismatch` when the sizes differ or the images differ
+/// beyond `tolerance` or `min_ssim`.
+pub fn run(
+    a: &Path,
+    b: &Path,
+    tolerance: u8,
+    min_ssim: Option<f64>,
+    diff: Option<&Path>,
+    json: bool,
+) -> Result<(), CliError> {
+    let (pixels_a, width, height) = art_engine_engines::snapshot::read_rgba_png(a)?;
+    let (pixels_b, width_b, height_b) = art_engine_engines::snapshot::read_rgba_png(b)?;
+    if (width, height) != (width_b, height_b) {
+        return Err(CliError::Mismatch(format!(
+            "image sizes differ: {width}x{height} vs {width_b}x{height_b}"
+        )));
+    }
+    let stats = diff_stats(&pixels_a, &pixels_b, width, height);
+    if let Some(path) = diff {
+        let map = heat_map(&pixels_a, &pixels_b, width, height)?;
+        art_engine_engines::snapshot::write_png(&map, &Palette::from_name("fire")?, path)?;
+    }
+    let failure = if stats.max_diff > tolerance {
+        Some(format!(
+            "images differ: max channel difference {} exceeds tolerance {tolerance}",
+            stats.max_diff
+        ))
+    } else {
+        min_ssim
+            .filter(|&min| stats.ssim < min)
+            .map(|min| format!("images differ: SSIM {:.6} is below {min}", stats.ssim))
+    };
+
+    if json {
+        let mut info = serde_json::to_value(&stats)?;
+        info["match"] = failure.is_none().into();
+        info["diff"] = diff.as_ref().map(|p| p.display().to_string()).into();
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        let total = (width * height).max(1);
+        println!("size:      {width}x{height}");
+        println!(
+            "differing: {} pixels ({:.2}%)",
+            stats.differing_pixels,
+            100.0 * stats.differing_pixels as f64 / total as f64
+        );
+        println!("max diff:  {}", stats.max_diff);
+        println!("mean diff: {:.4}", stats.mean_diff);
+        println!("rmse:      {:.4}", stats.rmse);
+        match stats.psnr {
+            Some(psnr) => println!("psnr:      {psnr:.2} dB"),
+            None => println!("psnr:      inf (identical)"),
+        }
+        println!("ssim:      {:.6}", stats.ssim);
+    }
+    if let Some(message) = failure {
+        return Err(CliError::Mismatch(message));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: usize, height: usize) -> Vec<u8> {
+        (0..width * height)
+            .flat_map(|i| {
+                let v = (i * 255 / (width * height)) as u8;
+                [v, v / 2, 255 - v, 255]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn identical_images_have_no_difference() {
+        let a = gradient(20, 12);
+        let stats = diff_stats(&a, &a, 20, 12);
+        assert_eq!(stats.differing_pixels, 0);
+        assert_eq!(stats.max_diff, 0);
+        assert_eq!(stats.psnr, None);
+        assert_eq!(stats.ssim, 1.0);
+    }
+
+    #[test]
+    fn differences_are_measured_per_channel() {
+        let a = gradient(16, 16);
+        let mut b = a.clone();
+        b[0] = b[0].wrapping_add(10);
+        b[5] = b[5].wrapping_add(4);
+        let stats = diff_stats(&a, &b, 16, 16);
+        assert_eq!(stats.differing_pixels, 2);
+        assert_eq!(stats.max_diff, 10);
+        assert!((stats.mean_diff - 14.0 / 1024.0).abs() < 1e-12);
+        assert!(stats.psnr.unwrap() > 40.0);
+        assert!(stats.ssim < 1.0 && stats.ssim > 0.9);
+    }
+
+    #[test]
+    fn ssim_drops_for_unrelated_structure() {
+        let a = gradient(32, 32);
+        let b: Vec<u8> = a
+            .chunks_exact(4)
+            .enumerate()
+            .flat_map(|(i, _)| {
+                let v = if (i / 32 + i % 32) % 2 == 0 { 0 } else { 255 };
+                [v, v, v, 255]
+            })
+            .collect();
+        assert!(diff_stats(&a, &b, 32, 32).ssim < 0.2);
+    }
+
+    #[test]
+    fn windows_cover_small_and_uneven_sizes() {
+        assert_eq!(window_starts(4), vec![0]);
+        assert_eq!(window_starts(8), vec![0]);
+        assert_eq!(window_starts(14), vec![0, 4, 6]);
+        let a = gradient(3, 2);
+        assert_eq!(diff_stats(&a, &a, 3, 2).ssim, 1.0);
+    }
+
+    #[test]
+    fn heat_map_scales_to_the_largest_difference() {
+        let a = vec![0u8; 12];
+        let mut b = a.clone();
+        b[1] = 50;
+        b[8] = 100;
+        let map = heat_map(&a, &b, 3, 1).unwrap();
+        assert_eq!(map.data(), &[0.5, 0.0, 1.0]);
+        assert!(heat_map(&a, &a, 3, 1)
+            .unwrap()
+            .data()
+            .iter()
+            .all(|&v| v == 0.0));
+    }
+}
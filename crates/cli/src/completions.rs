@@ -0,0 +1,90 @@
+//! Shell completions and manual pages generated from the clap definitions.
+//!
+//! Engine and palette arguments are plain strings when parsing, so unknown
+//! names fail with the structured exit codes rather than clap's usage error.
+//! For generation they are annotated with the registered engine and
+//! built-in palette names, so shells can offer them as completions.
+
+use crate::error::CliError;
+use art_engine_core::Palette;
+use art_engine_engines::EngineKind;
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, Command};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::Path;
+
+/// Name the generated scripts and pages complete and document.
+const BIN_NAME: &str = "art-engine";
+
+/// Adds the engine and palette names as possible values to every `engine`,
+/// `engines` and `palette` argument of `cmd` and its subcommands.
+pub fn annotate(cmd: Command) -> Command {
+    cmd.mut_args(|arg: Arg| match arg.get_id().as_str() {
+        "engine" | "engines" => {
+            arg.value_parser(PossibleValuesParser::new(EngineKind::list_engines()))
+        }
+        "palette" => arg.value_parser(PossibleValuesParser::new(Palette::list_names())),
+        _ => arg,
+    })
+    .mut_subcommands(annotate)
+}
+
+/// Writes the completion script for `shell` to `out`.
+pub fn write_completions(cmd: Command, shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut annotate(cmd), BIN_NAME, out);
+}
+
+/// Writes the top-level manual page to `out`.
+pub fn write_manpage(cmd: Command, out: &mut dyn Write) -> Result<(), CliError> {
+    clap_mangen::Man::new(annotate(cmd).name(BIN_NAME))
+        .render(out)
+        .map_err(|e| CliError::Io(format!("manpage: {e}")))
+}
+
+/// Writes `art-engine.1` and one page per subcommand into `dir`.
+pub fn write_manpages(cmd: Command, dir: &Path) -> Result<(), CliError> {
+    let io_err = |e: std::io::Error| CliError::Io(format!("{}: {e}", dir.display()));
+    std::fs::create_dir_all(dir).map_err(io_err)?;
+    clap_mangen::generate_to(annotate(cmd).name(BIN_NAME), dir).map_err(io_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn completions_offer_engine_and_palette_names() {
+        let mut script = Vec::new();
+        write_completions(crate::Cli::command(), Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("art__engine,render)"));
+        assert!(script.contains("gray-scott"));
+        assert!(script.contains("vapor"));
+    }
+
+    #[test]
+    fn annotation_does_not_change_parsing() {
+        let cli = crate::Cli::try_parse_from(["art-engine", "render", "not-an-engine"]);
+        assert!(cli.is_ok());
+        let annotated = annotate(crate::Cli::command());
+        assert!(annotated
+            .try_get_matches_from(["art-engine", "render", "not-an-engine"])
+            .is_err());
+    }
+
+    #[test]
+    fn manpages_cover_every_subcommand() {
+        let mut page = Vec::new();
+        write_manpage(crate::Cli::command(), &mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.contains(".TH art-engine 1"));
+
+        let dir = std::env::temp_dir().join(format!("art-engine-man-{}", std::process::id()));
+        write_manpages(crate::Cli::command(), &dir).unwrap();
+        assert!(dir.join("art-engine.1").is_file());
+        assert!(dir.join("art-engine-render.1").is_file());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,434 @@
+//! User configuration: defaults applied underneath explicit flags.
+//!
+//! The file is TOML, read from `--config PATH` or, when that is absent,
+//! `$XDG_CONFIG_HOME/art-engine/config.toml` (falling back to
+//! `~/.config/art-engine/config.toml`) if it exists:
+//!
+//! ```toml
+//! palette = "fire"           # instead of ocean
+//! output_dir = "~/art"       # where default output names are written
+//! memory_limit = "16G"       # instead of 8 GiB; or "unlimited"
+//!
+//! [presets]                  # sizes for --preset NAME
+//! square = "1024x1024"
+//! hd = "1920x1080"
+//!
+//! [engines.gray-scott]       # params merged under --params
+//! feed_rate = 0.037
+//! ```
+//!
+//! `--preset` also takes `engine/name` ids: user presets are JSON seed
+//! fragments (see [`Preset`]) at `presets/<engine>/<name>.json` beside the
+//! configuration file, and take precedence over the built-in library.
+//!
+//! Settings only fill in what the command line leaves at its default; a
+//! flag given explicitly always wins, and `--seed-file`/`--resume` runs are
+//! reproduced exactly as recorded.
+
+use crate::error::CliError;
+use art_engine_core::preset::{split_id, Preset};
+use art_engine_core::MemoryBudget;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Parsed configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Default `--palette` (a name or inline color list).
+    #[serde(default)]
+    pub palette: Option<String>,
+    /// Directory for outputs whose path was not given explicitly.
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Default `--memory-limit`.
+    #[serde(default)]
+    pub memory_limit: Option<MemoryBudget>,
+    /// Named `WxH` sizes for `--preset`.
+    #[serde(default)]
+    presets: BTreeMap<String, String>,
+    /// Default params per engine name.
+    #[serde(default)]
+    engines: BTreeMap<String, Value>,
+    /// Directory of user presets, beside the configuration file.
+    #[serde(skip)]
+    preset_dir: Option<PathBuf>,
+}
+
+/// What a `--preset` name refers to.
+#[derive(Debug, PartialEq)]
+pub enum PresetRef {
+    /// A `WxH` canvas size from the `[presets]` table.
+    Size(usize, usize),
+    /// A user or built-in seed fragment.
+    Seed(Preset),
+}
+
+/// Where a listed preset comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetSource {
+    Config,
+    User,
+    Builtin,
+}
+
+impl PresetSource {
+    /// Lowercase name for listings.
+    pub fn name(self) -> &'static str {
+        match self {
+            PresetSource::Config => "config",
+            PresetSource::User => "user",
+            PresetSource::Builtin => "builtin",
+        }
+    }
+}
+
+impl Config {
+    /// Parses and checks configuration text; `origin` names it in errors.
+    ///
+    /// Returns `CliError::Input` for malformed TOML, unknown keys, bad
+    /// preset sizes or engine sections that are not tables.
+    pub fn parse(text: &str, origin: &Path) -> Result<Self, CliError> {
+        let invalid = |msg: String| CliError::Input(format!("{}: {msg}", origin.display()));
+        let mut config: Config = toml::from_str(text).map_err(|e| invalid(e.to_string()))?;
+        config
+            .presets
+            .iter()
+            .try_for_each(|(name, size)| {
+                crate::parse_size(size)
+                    .map(drop)
+                    .map_err(|e| format!("preset '{name}': {e}"))
+            })
+            .map_err(invalid)?;
+        if let Some((name, _)) = config
+            .engines
+            .iter()
+            .find(|(_, params)| !params.is_object())
+        {
+            return Err(invalid(format!("engines.{name} must be a table of params")));
+        }
+        config.output_dir = config.output_dir.map(|dir| expand_home(&dir));
+        Ok(config)
+    }
+
+    /// Loads `explicit` (which must exist), else the default file if there
+    /// is one, else an empty configuration.
+    pub fn load(explicit: Option<&Path>) -> Result<Self, CliError> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.is_file() => path,
+                path => {
+                    return Ok(Self {
+                        preset_dir: path.as_deref().and_then(preset_dir),
+                        ..Self::default()
+                    })
+                }
+            },
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        tracing::debug!(path = %path.display(), "loading configuration");
+        Ok(Self {
+            preset_dir: preset_dir(&path),
+            ..Self::parse(&text, &path)?
+        })
+    }
+
+    /// Looks up a `--preset`: a configured size, else a user preset, else a
+    /// built-in one.
+    ///
+    /// Returns `CliError::Input` for a malformed user preset, or for an
+    /// unknown name, listing the configured sizes when `name` is not an
+    /// `engine/name` id. Unknown ids fail as the built-in library reports.
+    pub fn preset(&self, name: &str) -> Result<PresetRef, CliError> {
+        if let Some(size) = self.presets.get(name) {
+            // Checked by `parse`
+            let (width, height) = crate::parse_size(size).map_err(CliError::Input)?;
+            return Ok(PresetRef::Size(width, height));
+        }
+        if split_id(name).is_none() {
+            return Err(CliError::Input(if self.presets.is_empty() {
+                format!("unknown preset '{name}' (no sizes configured; presets are engine/name)")
+            } else {
+                format!(
+                    "unknown preset '{name}' (configured: {})",
+                    self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            }));
+        }
+        match self.user_preset_path(name).filter(|path| path.is_file()) {
+            Some(path) => read_preset(&path, name).map(PresetRef::Seed),
+            None => Ok(PresetRef::Seed(art_engine_engines::presets::find(name)?)),
+        }
+    }
+
+    /// Lists every preset `--preset` accepts as `(name, source, preset)`:
+    /// configured sizes (with no preset), user presets, then the built-in
+    /// ones that no user preset overrides.
+    ///
+    /// Returns `CliError::Input` for a malformed user preset.
+    pub fn list_presets(&self) -> Result<Vec<(String, PresetSource, Option<Preset>)>, CliError> {
+        let sizes = self
+            .presets
+            .keys()
+            .map(|name| (name.clone(), PresetSource::Config, None));
+        let user = self.user_presets()?;
+        let builtin = art_engine_engines::presets::builtin()
+            .into_iter()
+            .filter(|(id, _)| !user.iter().any(|(name, _)| name == id))
+            .map(|(id, preset)| (id, PresetSource::Builtin, Some(preset)))
+            .collect::<Vec<_>>();
+        Ok(sizes
+            .chain(
+                user.into_iter()
+                    .map(|(name, preset)| (name, PresetSource::User, Some(preset))),
+            )
+            .chain(builtin)
+            .collect())
+    }
+
+    /// Reads every `presets/<engine>/<name>.json`, sorted by id.
+    fn user_presets(&self) -> Result<Vec<(String, Preset)>, CliError> {
+        let Some(dir) = self.preset_dir.as_deref().filter(|dir| dir.is_dir()) else {
+            return Ok(Vec::new());
+        };
+        let read_dir = |dir: &Path| {
+            std::fs::read_dir(dir)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(|e| CliError::Io(format!("{}: {e}", dir.display())))
+        };
+        let mut presets = read_dir(dir)?
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .map(|engine_dir| {
+                let engine = file_name(&engine_dir, None);
+                read_dir(&engine_dir)?
+                    .into_iter()
+                    .filter_map(|path| {
+                        let name = file_name(&path, Some("json"))?;
+                        let id = format!("{}/{name}", engine.as_deref()?);
+                        Some(read_preset(&path, &id).map(|preset| (id, preset)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+        presets.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(presets)
+    }
+
+    /// Path of the user preset file for `engine/name` id `id`.
+    fn user_preset_path(&self, id: &str) -> Option<PathBuf> {
+        let (engine, name) = split_id(id)?;
+        let plain = |part: &str| Path::new(part).file_name() == Some(part.as_ref());
+        let dir = self
+            .preset_dir
+            .as_ref()
+            .filter(|_| plain(engine) && plain(name))?;
+        Some(dir.join(engine).join(format!("{name}.json")))
+    }
+
+    /// Merges the configured defaults for `engine` under explicit `--params`
+    /// JSON, returning the combined JSON. Explicit keys win.
+    ///
+    /// Returns `CliError::Input` if `params` is not a JSON object.
+    pub fn params(&self, engine: &str, params: &str) -> Result<String, CliError> {
+        match self.engines.get(engine) {
+            Some(Value::Object(defaults)) => merge_params(defaults, params),
+            _ => Ok(params.to_string()),
+        }
+    }
+}
+
+/// Merges `defaults` under explicit `--params` JSON, returning the combined
+/// JSON. Explicit keys win.
+///
+/// Returns `CliError::Input` if `params` is not a JSON object.
+pub fn merge_params(defaults: &Map<String, Value>, params: &str) -> Result<String, CliError> {
+    let explicit: Value = serde_json::from_str(params)
+        .map_err(|e| CliError::Input(format!("invalid --params JSON: {e}")))?;
+    let Value::Object(explicit) = explicit else {
+        return Err(CliError::Input("--params must be a JSON object".into()));
+    };
+    let mut merged = defaults.clone();
+    merged.extend(explicit);
+    Ok(Value::Object(merged).to_string())
+}
+
+/// Reads the user preset `id` from `path`, checking it names the engine
+/// its directory does.
+fn read_preset(path: &Path, id: &str) -> Result<Preset, CliError> {
+    let invalid = |msg: String| CliError::Input(format!("{}: {msg}", path.display()));
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+    let preset = Preset::from_json(&text).map_err(|e| invalid(e.to_string()))?;
+    match split_id(id) {
+        Some((engine, _)) if engine != preset.engine => Err(invalid(format!(
+            "preset for engine '{}' filed under '{engine}'",
+            preset.engine
+        ))),
+        _ => Ok(preset),
+    }
+}
+
+/// Returns the file name of `path` without its extension, if the
+/// extension is `ext` (or `ext` is `None`) and the name is valid UTF-8.
+fn file_name(path: &Path, ext: Option<&str>) -> Option<String> {
+    if ext.is_some_and(|ext| !crate::has_extension(path, ext)) {
+        return None;
+    }
+    let name = match ext {
+        Some(_) => path.file_stem(),
+        None => path.file_name(),
+    };
+    name?.to_str().map(str::to_string)
+}
+
+/// Returns the user preset directory beside configuration file `path`.
+fn preset_dir(path: &Path) -> Option<PathBuf> {
+    path.parent().map(|dir| dir.join("presets"))
+}
+
+/// Returns the default configuration file path, if a home can be found.
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("art-engine").join("config.toml"))
+}
+
+/// Expands a leading `~` to `$HOME`.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Config, CliError> {
+        Config::parse(text, Path::new("config.toml"))
+    }
+
+    #[test]
+    fn parses_every_section() {
+        let config = parse(
+            r#"
+            palette = "fire"
+            output_dir = "renders"
+            memory_limit = "16G"
+
+            [presets]
+            hd = "1920x1080"
+
+            [engines.gray-scott]
+            feed_rate = 0.037
+            kill_rate = 0.06
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.palette.as_deref(), Some("fire"));
+        assert_eq!(config.output_dir, Some(PathBuf::from("renders")));
+        assert_eq!(config.memory_limit, Some(MemoryBudget::new(16 << 30)));
+        assert_eq!(config.preset("hd").unwrap(), PresetRef::Size(1920, 1080));
+        let err = config.preset("4k").unwrap_err().to_string();
+        assert!(err.contains("configured: hd"), "{err}");
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_bad_values() {
+        assert!(parse("pallete = \"fire\"").is_err());
+        assert!(parse("[presets]\nhd = \"big\"").is_err());
+        assert!(parse("[engines]\ngray-scott = 3").is_err());
+        assert!(parse("memory_limit = \"lots\"").is_err());
+        let err = parse("[presets]\nhd = \"0x1\"").unwrap_err().to_string();
+        assert!(err.starts_with("config.toml: preset 'hd'"), "{err}");
+        assert!(Config::default().preset("hd").is_err());
+    }
+
+    #[test]
+    fn explicit_params_override_engine_defaults() {
+        let config = parse("[engines.gray-scott]\nfeed_rate = 0.03\ndt = 0.5").unwrap();
+        let merged: Value =
+            serde_json::from_str(&config.params("gray-scott", r#"{"dt": 1.0}"#).unwrap()).unwrap();
+        assert_eq!(merged, serde_json::json!({"feed_rate": 0.03, "dt": 1.0}));
+        assert_eq!(config.params("other", "not json").unwrap(), "not json");
+        assert!(config.params("gray-scott", "[1]").is_err());
+    }
+
+    #[test]
+    fn user_presets_are_found_beside_the_config_and_shadow_builtins() {
+        let dir = std::env::temp_dir().join(format!("art-engine-presets-{}", std::process::id()));
+        let engine_dir = dir.join("presets").join("gray-scott");
+        std::fs::create_dir_all(&engine_dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(
+            &path,
+            "[presets]
+hd = \"1920x1080\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            engine_dir.join("mitosis.json"),
+            r#"{"engine": "gray-scott", "params": {"feed_rate": 0.04}, "steps": 9}"#,
+        )
+        .unwrap();
+        std::fs::write(engine_dir.join("notes.txt"), "ignored").unwrap();
+        let config = Config::load(Some(&path)).unwrap();
+
+        let PresetRef::Seed(mitosis) = config.preset("gray-scott/mitosis").unwrap() else {
+            panic!("expected a seed preset");
+        };
+        assert_eq!(mitosis.steps, Some(9));
+        let PresetRef::Seed(coral) = config.preset("gray-scott/coral").unwrap() else {
+            panic!("expected a seed preset");
+        };
+        assert_eq!(coral.steps, None);
+        assert!(config.preset("gray-scott/../mitosis").is_err());
+
+        let listed = config.list_presets().unwrap();
+        let sources: Vec<_> = listed
+            .iter()
+            .filter(|(name, ..)| name == "hd" || name.ends_with("/mitosis"))
+            .map(|(name, source, _)| (name.as_str(), *source))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                ("hd", PresetSource::Config),
+                ("gray-scott/mitosis", PresetSource::User)
+            ]
+        );
+
+        std::fs::write(
+            engine_dir.join("mitosis.json"),
+            r#"{"engine": "dla", "params": {}}"#,
+        )
+        .unwrap();
+        let err = config.preset("gray-scott/mitosis").unwrap_err().to_string();
+        assert!(err.contains("filed under 'gray-scott'"), "{err}");
+        assert!(config.list_presets().is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn home_is_expanded_in_output_dir() {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let config = parse("output_dir = \"~/art\"").unwrap();
+        assert_eq!(config.output_dir, Some(PathBuf::from(home).join("art")));
+        assert_eq!(expand_home(Path::new("a/~")), PathBuf::from("a/~"));
+    }
+}
@@ -0,0 +1,856 @@
+//! Live control input: OSC and MIDI controllers steering engine params.
+//!
+//! Enabled by the `control` feature. A [`Control`] listens in background
+//! threads and holds the latest value each controller sent; front ends
+//! (`tui`, `watch`, `serve`) call [`Control::poll`] to learn whether
+//! anything moved and [`Control::params`] for the params to lay over their
+//! own.
+//!
+//! - **OSC** (`--osc ADDR`) -- UDP packets, messages and bundles, with
+//!   `i`, `h`, `f`, `d`, `s`, `T` and `F` arguments. `/param/<name> value`
+//!   sets a param to the value as sent; mapped addresses take their first
+//!   numeric argument in [0, 1].
+//! - **MIDI** (`--midi PATH`) -- raw MIDI bytes from a device such as
+//!   `/dev/snd/midiC1D0` or a pipe. Control changes and note velocities
+//!   (0 on note off) are scaled from 0-127 to [0, 1].
+//!
+//! A [`ControlMap`] (`--control-map`) routes inputs to params:
+//!
+//! ```json
+//! {
+//!   "routes": [
+//!     { "osc": "/fader/1", "param": "feed_rate", "min": 0.02, "max": 0.07 },
+//!     { "cc": 21, "channel": 1, "param": "kill_rate" },
+//!     { "note": 36, "param": "diffusion_u", "min": 0.1, "max": 0.3 }
+//!   ]
+//! }
+//! ```
+//!
+//! A route without `min`/`max` spans the param's schema range, or [0, 1]
+//! if the schema gives none; integer params are rounded. `channel` (1-16)
+//! is optional and matches any channel when omitted.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::net::{SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use art_engine_core::params::merge_params;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::CliError;
+use crate::param_schema;
+
+/// Largest OSC packet accepted, in bytes.
+pub const MAX_PACKET: usize = 65_536;
+
+/// OSC address prefix that sets a param directly: `/param/<name>`.
+pub const PARAM_PREFIX: &str = "/param/";
+
+/// One OSC argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    /// `i`: 32-bit integer.
+    Int(i32),
+    /// `h`: 64-bit integer.
+    Long(i64),
+    /// `f`: 32-bit float.
+    Float(f32),
+    /// `d`: 64-bit float.
+    Double(f64),
+    /// `s`: string.
+    Str(String),
+    /// `T`/`F`: boolean, with no data bytes.
+    Bool(bool),
+}
+
+impl OscArg {
+    /// Returns the argument as a number; booleans are 0 or 1.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            OscArg::Int(v) => Some(v.into()),
+            OscArg::Long(v) => Some(v as f64),
+            OscArg::Float(v) => Some(widen(v)),
+            OscArg::Double(v) => Some(v),
+            OscArg::Bool(v) => Some(u8::from(v).into()),
+            OscArg::Str(_) => None,
+        }
+    }
+
+    /// Returns the argument as a JSON value.
+    fn to_json(&self) -> Value {
+        match self {
+            OscArg::Int(v) => (*v).into(),
+            OscArg::Long(v) => (*v).into(),
+            OscArg::Float(v) => widen(*v).into(),
+            OscArg::Double(v) => (*v).into(),
+            OscArg::Str(v) => v.clone().into(),
+            OscArg::Bool(v) => (*v).into(),
+        }
+    }
+}
+
+/// Widens `v` to the `f64` with the same shortest decimal form, so a sent
+/// 0.031 arrives as 0.031 rather than 0.030999999493360519.
+fn widen(v: f32) -> f64 {
+    v.to_string().parse().unwrap_or(v.into())
+}
+
+/// A channel message from a MIDI stream; channels count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage {
+    /// A controller moved.
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// A key went down (a velocity of 0 is sent as [`MidiMessage::NoteOff`]).
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// A key came up.
+    NoteOff { channel: u8, note: u8 },
+}
+
+/// One controller input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// An OSC message.
+    Osc { address: String, args: Vec<OscArg> },
+    /// A MIDI channel message.
+    Midi(MidiMessage),
+}
+
+/// Parses an OSC packet into its messages; bundles are flattened in order.
+pub fn parse_osc(packet: &[u8]) -> Result<Vec<Message>, String> {
+    let mut messages = Vec::new();
+    parse_packet(packet, &mut messages)?;
+    Ok(messages)
+}
+
+fn parse_packet(packet: &[u8], messages: &mut Vec<Message>) -> Result<(), String> {
+    let mut cursor = Cursor { bytes: packet };
+    if packet.starts_with(b"#bundle\0") {
+        cursor.take(16)?; // "#bundle\0" and the time tag
+        while !cursor.bytes.is_empty() {
+            let size = cursor.int()?;
+            let size = usize::try_from(size).map_err(|_| "negative bundle element size")?;
+            parse_packet(cursor.take(size)?, messages)?;
+        }
+        return Ok(());
+    }
+    let address = cursor.string()?;
+    if !address.starts_with('/') {
+        return Err(format!("bad OSC address {address:?}"));
+    }
+    // A missing type tag string is an old-style message without arguments
+    let tags = match cursor.bytes.is_empty() {
+        true => ",".to_string(),
+        false => cursor.string()?,
+    };
+    let tags = tags
+        .strip_prefix(',')
+        .ok_or_else(|| format!("bad OSC type tags {tags:?}"))?;
+    let args = tags
+        .chars()
+        .map(|tag| match tag {
+            'i' => cursor.int().map(OscArg::Int),
+            'h' => cursor.array().map(|b| OscArg::Long(i64::from_be_bytes(b))),
+            'f' => cursor.array().map(|b| OscArg::Float(f32::from_be_bytes(b))),
+            'd' => cursor
+                .array()
+                .map(|b| OscArg::Double(f64::from_be_bytes(b))),
+            's' => cursor.string().map(OscArg::Str),
+            'T' => Ok(OscArg::Bool(true)),
+            'F' => Ok(OscArg::Bool(false)),
+            other => Err(format!("unsupported OSC type tag '{other}'")),
+        })
+        .collect::<Result<_, _>>()?;
+    messages.push(Message::Osc { address, args });
+    Ok(())
+}
+
+/// Big-endian reader over an OSC packet.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if n > self.bytes.len() {
+            return Err("truncated OSC packet".into());
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn int(&mut self) -> Result<i32, String> {
+        self.array().map(i32::from_be_bytes)
+    }
+
+    /// Reads a NUL-terminated string padded to a multiple of 4 bytes.
+    fn string(&mut self) -> Result<String, String> {
+        let len = self
+            .bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("unterminated OSC string")?;
+        let padded = (len + 4) & !3;
+        let text = std::str::from_utf8(&self.take(padded)?[..len])
+            .map_err(|_| "OSC string is not UTF-8")?;
+        Ok(text.to_string())
+    }
+}
+
+/// Turns a raw MIDI byte stream into channel messages.
+///
+/// Follows running status; system exclusive data, system common and
+/// real-time bytes are skipped, as are channel messages other than notes
+/// and control changes.
+#[derive(Debug, Default)]
+pub struct MidiParser {
+    status: Option<u8>,
+    first: Option<u8>,
+    sysex: bool,
+}
+
+impl MidiParser {
+    /// Feeds one byte; returns a message when it completes one.
+    pub fn push(&mut self, byte: u8) -> Option<MidiMessage> {
+        match byte {
+            // Real-time bytes may appear anywhere, even inside a message
+            0xF8..=0xFF => return None,
+            0xF0 => {
+                self.sysex = true;
+                self.status = None;
+                return None;
+            }
+            0xF7 => {
+                self.sysex = false;
+                return None;
+            }
+            0x80..=0xEF => {
+                self.sysex = false;
+                self.status = Some(byte);
+                self.first = None;
+                return None;
+            }
+            0xF1..=0xF6 => {
+                // System common clears running status; its data is skipped
+                self.status = None;
+                return None;
+            }
+            _ => {}
+        }
+        let status = self.status.filter(|_| !self.sysex)?;
+        let channel = (status & 0x0F) + 1;
+        // Program change and channel pressure carry one data byte
+        if matches!(status & 0xF0, 0xC0 | 0xD0) {
+            return None;
+        }
+        let Some(first) = self.first.take() else {
+            self.first = Some(byte);
+            return None;
+        };
+        match status & 0xF0 {
+            0x80 => Some(MidiMessage::NoteOff {
+                channel,
+                note: first,
+            }),
+            0x90 if byte == 0 => Some(MidiMessage::NoteOff {
+                channel,
+                note: first,
+            }),
+            0x90 => Some(MidiMessage::NoteOn {
+                channel,
+                note: first,
+                velocity: byte,
+            }),
+            0xB0 => Some(MidiMessage::ControlChange {
+                channel,
+                controller: first,
+                value: byte,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The input a [`Route`] listens to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    /// An OSC address.
+    Osc(String),
+    /// A MIDI controller number.
+    Cc(u8),
+    /// A MIDI note number; its velocity is the value.
+    Note(u8),
+}
+
+/// One input driving one param.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    /// What the route listens to.
+    #[serde(flatten)]
+    pub source: Source,
+    /// MIDI channel, 1-16 (any when omitted).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+    /// Engine parameter name.
+    pub param: String,
+    /// Param value for an input of 0 (default: the schema minimum).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Param value for an input of 1 (default: the schema maximum).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl Route {
+    /// Returns the route's input in [0, 1] carried by `message`, if any.
+    fn input(&self, message: &Message) -> Option<f64> {
+        let channel_matches = |channel: u8| self.channel.is_none_or(|c| c == channel);
+        match (&self.source, message) {
+            (Source::Osc(route), Message::Osc { address, args }) if route == address => {
+                args.iter().find_map(OscArg::as_f64)
+            }
+            (
+                Source::Cc(cc),
+                Message::Midi(MidiMessage::ControlChange {
+                    channel,
+                    controller,
+                    value,
+                }),
+            ) if cc == controller && channel_matches(*channel) => Some(f64::from(*value) / 127.0),
+            (
+                Source::Note(route),
+                Message::Midi(MidiMessage::NoteOn {
+                    channel,
+                    note,
+                    velocity,
+                }),
+            ) if route == note && channel_matches(*channel) => Some(f64::from(*velocity) / 127.0),
+            (Source::Note(route), Message::Midi(MidiMessage::NoteOff { channel, note }))
+                if route == note && channel_matches(*channel) =>
+            {
+                Some(0.0)
+            }
+            _ => None,
+        }
+        .map(|x| x.clamp(0.0, 1.0))
+    }
+}
+
+/// A `--control-map` file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ControlMap {
+    /// Input-to-param routes; every matching route applies.
+    pub routes: Vec<Route>,
+}
+
+impl ControlMap {
+    /// Reads and checks a mapping file.
+    pub fn load(path: &Path) -> Result<Self, CliError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        let map: Self = serde_json::from_str(&text).map_err(|e| {
+            CliError::Input(format!("{}: invalid control map: {e}", path.display()))
+        })?;
+        map.validate()
+            .map_err(|e| CliError::Input(format!("{}: {e}", path.display())))?;
+        Ok(map)
+    }
+
+    /// Checks addresses, MIDI numbers, channels and ranges.
+    fn validate(&self) -> Result<(), String> {
+        self.routes.iter().try_for_each(|route| {
+            let param = &route.param;
+            match &route.source {
+                Source::Osc(address) if !address.starts_with('/') => {
+                    return Err(format!(
+                        "{param}: OSC address {address:?} must start with /"
+                    ));
+                }
+                Source::Cc(n) | Source::Note(n) if *n > 127 => {
+                    return Err(format!("{param}: MIDI number {n} is above 127"));
+                }
+                _ => {}
+            }
+            if route.channel.is_some_and(|c| !(1..=16).contains(&c)) {
+                return Err(format!("{param}: channel must be 1-16"));
+            }
+            if [route.min, route.max]
+                .iter()
+                .flatten()
+                .any(|v| !v.is_finite())
+            {
+                return Err(format!("{param}: min and max must be finite"));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The latest value held for a param.
+#[derive(Debug, Clone, PartialEq)]
+enum Held {
+    /// Input in [0, 1] from route `index`, scaled when read.
+    Scaled { index: usize, input: f64 },
+    /// A value sent to `/param/<name>`, used as is.
+    Raw(Value),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    held: BTreeMap<String, Held>,
+    changed: bool,
+}
+
+/// Listeners and the latest controller values, shared with their threads.
+#[derive(Debug, Clone, Default)]
+pub struct Control {
+    map: Arc<ControlMap>,
+    state: Arc<Mutex<State>>,
+    osc_addr: Option<SocketAddr>,
+}
+
+impl Control {
+    /// Creates a control routing inputs through `map`, with no listeners.
+    pub fn new(map: ControlMap) -> Self {
+        Self {
+            map: Arc::new(map),
+            ..Self::default()
+        }
+    }
+
+    /// Returns a copy that also listens for OSC packets on `addr`.
+    ///
+    /// Malformed packets are logged and dropped.
+    pub fn with_osc(mut self, addr: SocketAddr) -> Result<Self, CliError> {
+        let socket =
+            UdpSocket::bind(addr).map_err(|e| CliError::Io(format!("--osc {addr}: {e}")))?;
+        self.osc_addr = socket.local_addr().ok();
+        let control = self.clone();
+        std::thread::spawn(move || {
+            let mut buf = vec![0; MAX_PACKET];
+            while let Ok((len, from)) = socket.recv_from(&mut buf) {
+                match parse_osc(&buf[..len]) {
+                    Ok(messages) => messages.iter().for_each(|m| {
+                        control.apply(m);
+                    }),
+                    Err(e) => tracing::debug!(%from, error = %e, "dropped OSC packet"),
+                }
+            }
+        });
+        Ok(self)
+    }
+
+    /// Returns a copy that also reads raw MIDI bytes from `path`, until it
+    /// ends or fails.
+    pub fn with_midi(self, path: &Path) -> Result<Self, CliError> {
+        let file = File::open(path)
+            .map_err(|e| CliError::Io(format!("--midi {}: {e}", path.display())))?;
+        let control = self.clone();
+        std::thread::spawn(move || {
+            let mut parser = MidiParser::default();
+            for byte in std::io::BufReader::new(file).bytes() {
+                let Ok(byte) = byte else { break };
+                if let Some(message) = parser.push(byte) {
+                    control.apply(&Message::Midi(message));
+                }
+            }
+            tracing::debug!("MIDI input ended");
+        });
+        Ok(self)
+    }
+
+    /// Returns the bound OSC address, if listening for OSC.
+    pub fn osc_addr(&self) -> Option<SocketAddr> {
+        self.osc_addr
+    }
+
+    /// Records the values `message` carries; returns `true` if any param
+    /// was addressed.
+    pub fn apply(&self, message: &Message) -> bool {
+        let mut updates: Vec<(String, Held)> = self
+            .map
+            .routes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, route)| {
+                let input = route.input(message)?;
+                Some((route.param.clone(), Held::Scaled { index, input }))
+            })
+            .collect();
+        if let Message::Osc { address, args } = message {
+            if let (Some(name), Some(arg)) = (address.strip_prefix(PARAM_PREFIX), args.first()) {
+                updates.push((name.to_string(), Held::Raw(arg.to_json())));
+            }
+        }
+        if updates.is_empty() {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.held.extend(updates);
+        state.changed = true;
+        true
+    }
+
+    /// Drops the held value for `param`, as when the engine rejected it.
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    pub fn forget(&self, param: &str) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.held.remove(param);
+    }
+
+    /// Returns `true` if any value arrived since the last call.
+    pub fn poll(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        std::mem::take(&mut state.changed)
+    }
+
+    /// Returns every param a controller has set, scaled into the route's
+    /// range or else the param's range in `schema`.
+    pub fn params(&self, schema: &Value) -> serde_json::Map<String, Value> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state
+            .held
+            .iter()
+            .map(|(name, held)| {
+                let value = match held {
+                    Held::Raw(value) => value.clone(),
+                    Held::Scaled { index, input } => {
+                        let route = &self.map.routes[*index];
+                        let spec = &schema[name];
+                        let bound = |key: &str| spec[key].as_f64();
+                        let min = route.min.or(bound("min")).unwrap_or(0.0);
+                        let max = route.max.or(bound("max")).unwrap_or(1.0);
+                        let value = min + (max - min) * input;
+                        match spec["type"] == "integer" {
+                            true => (value.round() as i64).into(),
+                            false => value.into(),
+                        }
+                    }
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// Live controller options for `tui`, `watch` and `serve`.
+#[derive(Args)]
+pub struct ControlArgs {
+    /// Listen for OSC messages on this UDP address (e.g. 127.0.0.1:9000);
+    /// `/param/<name> value` sets a param directly.
+    #[arg(long, value_name = "ADDR")]
+    osc: Option<std::net::SocketAddr>,
+
+    /// Read raw MIDI bytes from this device or pipe (e.g. /dev/snd/midiC1D0).
+    #[arg(long, value_name = "PATH", requires = "control_map")]
+    midi: Option<PathBuf>,
+
+    /// JSON routes from OSC addresses and MIDI controllers or notes to
+    /// engine params.
+    #[arg(long, value_name = "PATH")]
+    control_map: Option<PathBuf>,
+}
+
+impl ControlArgs {
+    /// Starts the requested listeners, if any.
+    pub fn listen(&self) -> Result<Option<Control>, CliError> {
+        if self.osc.is_none() && self.midi.is_none() {
+            return match self.control_map {
+                Some(_) => Err(CliError::Input(
+                    "--control-map needs --osc or --midi".into(),
+                )),
+                None => Ok(None),
+            };
+        }
+        let map = match &self.control_map {
+            Some(path) => ControlMap::load(path)?,
+            None => ControlMap::default(),
+        };
+        let mut control = Control::new(map);
+        if let Some(addr) = self.osc {
+            control = control.with_osc(addr)?;
+            tracing::info!(addr = ?control.osc_addr(), "listening for OSC");
+        }
+        if let Some(path) = &self.midi {
+            control = control.with_midi(path)?;
+            tracing::info!(path = %path.display(), "reading MIDI");
+        }
+        Ok(Some(control))
+    }
+}
+
+/// Lays the params live controllers have set over `params` for `engine`.
+pub fn steer_params(
+    control: &Control,
+    engine: &str,
+    params: &serde_json::Value,
+) -> Result<serde_json::Value, CliError> {
+    let overrides = control.params(&param_schema(engine)?);
+    Ok(merge_params(params, &overrides.into())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Encodes an OSC string: NUL-terminated, padded to 4 bytes.
+    fn osc_string(text: &str) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize((text.len() + 4) & !3, 0);
+        bytes
+    }
+
+    fn osc_message(address: &str, tags: &str, data: &[u8]) -> Vec<u8> {
+        [osc_string(address), osc_string(tags), data.to_vec()].concat()
+    }
+
+    fn map(value: Value) -> Result<ControlMap, String> {
+        let map: ControlMap = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        map.validate().map(|()| map)
+    }
+
+    // ---- OSC ----
+
+    #[test]
+    fn osc_messages_and_bundles_parse() {
+        let data = [
+            &7i32.to_be_bytes()[..],
+            &0.5f32.to_be_bytes(),
+            &osc_string("hi"),
+            &0.25f64.to_be_bytes(),
+        ]
+        .concat();
+        let message = osc_message("/fader/1", ",ifsdT", &data);
+        assert_eq!(
+            parse_osc(&message).unwrap(),
+            [Message::Osc {
+                address: "/fader/1".into(),
+                args: vec![
+                    OscArg::Int(7),
+                    OscArg::Float(0.5),
+                    OscArg::Str("hi".into()),
+                    OscArg::Double(0.25),
+                    OscArg::Bool(true),
+                ],
+            }]
+        );
+
+        let inner = osc_message("/b", ",F", &[]);
+        let bundle = [
+            &b"#bundle\0"[..],
+            &[0, 0, 0, 0, 0, 0, 0, 1],
+            &(message.len() as i32).to_be_bytes(),
+            &message,
+            &(inner.len() as i32).to_be_bytes(),
+            &inner,
+        ]
+        .concat();
+        let messages = parse_osc(&bundle).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(&messages[1], Message::Osc { address, .. } if address == "/b"));
+        // No type tags at all
+        assert!(parse_osc(&osc_string("/ping")).is_ok());
+        assert_eq!(OscArg::Float(0.031).as_f64(), Some(0.031));
+    }
+
+    #[test]
+    fn malformed_osc_is_rejected() {
+        for bad in [
+            osc_message("/x", ",f", &[0, 0]),
+            osc_message("/x", ",q", &[]),
+            osc_message("x", ",", &[]),
+            osc_message("/x", "f", &[0; 4]),
+            b"/unterminated".to_vec(),
+            [&b"#bundle\0"[..], &[0; 8], &[0, 0, 0, 9]].concat(),
+        ] {
+            assert!(parse_osc(&bad).is_err(), "{bad:?}");
+        }
+    }
+
+    // ---- MIDI ----
+
+    #[test]
+    fn midi_parser_follows_running_status() {
+        let mut parser = MidiParser::default();
+        let bytes = [
+            0xB0, 21, 64, // CC 21 on channel 1
+            22, 127,  // running status: CC 22
+            0xF8, // clock tick in between
+            0x91, 60, 0xFE, 100, // note on, channel 2, with active sensing inside
+            60, 0, // running status note on at velocity 0
+            0xF0, 1, 2, 3, 0xF7, // sysex
+            0xC0, 5, // program change
+            0x85, 40, 0, // note off, channel 6
+        ];
+        let messages: Vec<_> = bytes.iter().filter_map(|&b| parser.push(b)).collect();
+        assert_eq!(
+            messages,
+            [
+                MidiMessage::ControlChange {
+                    channel: 1,
+                    controller: 21,
+                    value: 64
+                },
+                MidiMessage::ControlChange {
+                    channel: 1,
+                    controller: 22,
+                    value: 127
+                },
+                MidiMessage::NoteOn {
+                    channel: 2,
+                    note: 60,
+                    velocity: 100
+                },
+                MidiMessage::NoteOff {
+                    channel: 2,
+                    note: 60
+                },
+                MidiMessage::NoteOff {
+                    channel: 6,
+                    note: 40
+                },
+            ]
+        );
+        // Data after sysex without a new status is ignored
+        assert_eq!(parser.push(1), None);
+    }
+
+    // ---- Routing ----
+
+    #[test]
+    fn maps_check_their_routes() {
+        let ok = map(json!({ "routes": [
+            { "osc": "/a", "param": "p" },
+            { "cc": 1, "channel": 16, "param": "q", "min": 0, "max": 2 },
+            { "note": 127, "param": "r" }
+        ]}))
+        .unwrap();
+        assert_eq!(ok.routes[1].source, Source::Cc(1));
+        for bad in [
+            json!({ "routes": [{ "osc": "a", "param": "p" }] }),
+            json!({ "routes": [{ "cc": 128, "param": "p" }] }),
+            json!({ "routes": [{ "cc": 1, "channel": 0, "param": "p" }] }),
+            json!({ "routes": [{ "param": "p" }] }),
+            json!({ "routes": [{ "cc": 1 }] }),
+            json!({ "routes": [], "extra": true }),
+        ] {
+            assert!(map(bad.clone()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn inputs_scale_into_route_or_schema_ranges() {
+        let control = Control::new(
+            map(json!({ "routes": [
+                { "osc": "/feed", "param": "feed_rate", "min": 0.02, "max": 0.06 },
+                { "cc": 21, "channel": 2, "param": "kill_rate" },
+                { "note": 36, "param": "count" },
+                { "osc": "/free", "param": "free" }
+            ]}))
+            .unwrap(),
+        );
+        let schema = json!({
+            "kill_rate": { "type": "number", "min": 0.04, "max": 0.08 },
+            "count": { "type": "integer", "min": 2, "max": 6 },
+        });
+        let osc = |address: &str, args| Message::Osc {
+            address: address.into(),
+            args,
+        };
+        let cc = |channel, value| {
+            Message::Midi(MidiMessage::ControlChange {
+                channel,
+                controller: 21,
+                value,
+            })
+        };
+        assert!(!control.poll());
+        assert!(control.apply(&osc("/feed", vec![OscArg::Float(0.5)])));
+        assert!(control.poll());
+        assert!(!control.poll());
+        // Wrong channel, unrouted address, unrouted note
+        assert!(!control.apply(&cc(1, 127)));
+        assert!(!control.apply(&osc("/other", vec![OscArg::Int(1)])));
+        assert!(!control.apply(&Message::Midi(MidiMessage::NoteOff {
+            channel: 1,
+            note: 35
+        })));
+        assert!(!control.poll());
+
+        control.apply(&cc(2, 127));
+        control.apply(&Message::Midi(MidiMessage::NoteOn {
+            channel: 3,
+            note: 36,
+            velocity: 64,
+        }));
+        // Out of range input clamps; no schema range falls back to [0, 1]
+        control.apply(&osc(
+            "/free",
+            vec![OscArg::Str("x".into()), OscArg::Double(3.0)],
+        ));
+        control.apply(&osc("/param/palette_shift", vec![OscArg::Str("up".into())]));
+        let params = control.params(&schema);
+        assert!((params["feed_rate"].as_f64().unwrap() - 0.04).abs() < 1e-12);
+        assert_eq!(params["kill_rate"], 0.08);
+        assert_eq!(params["count"], 4);
+        assert_eq!(params["free"], 1.0);
+        assert_eq!(params["palette_shift"], "up");
+
+        // Note off drops the value to the bottom of the range
+        control.apply(&Message::Midi(MidiMessage::NoteOff {
+            channel: 3,
+            note: 36,
+        }));
+        assert_eq!(control.params(&schema)["count"], 2);
+        control.forget("count");
+        assert!(!control.params(&schema).contains_key("count"));
+    }
+
+    #[test]
+    fn listeners_feed_the_control() {
+        let control = Control::new(ControlMap::default())
+            .with_osc("127.0.0.1:0".parse().unwrap())
+            .unwrap();
+        let addr = control.osc_addr().unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.send_to(b"garbage", addr).unwrap();
+        let message = osc_message("/param/feed_rate", ",d", &0.05f64.to_be_bytes());
+        socket.send_to(&message, addr).unwrap();
+
+        let path = std::env::temp_dir().join(format!("art-engine-midi-{}", std::process::id()));
+        std::fs::write(&path, [0xB0, 1, 127]).unwrap();
+        let control = control.with_midi(&path).unwrap();
+        assert!(Control::default()
+            .with_midi(&path.with_extension("missing"))
+            .is_err());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !control.params(&json!({})).contains_key("feed_rate") {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "no OSC message arrived"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(control.params(&json!({}))["feed_rate"], 0.05);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
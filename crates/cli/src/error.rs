@@ -7,11 +7,14 @@
 //! - 11: I/O error (file write, snapshot)
 //! - 12: input error (bad palette, bad JSON params)
 //! - 13: serialization error
+//! - 14: mismatch (`compare` found images that differ beyond tolerance)
+//! - 15: GPU error (no headless context, shader failure; `gpu` feature)
 
 use art_engine_core::EngineError;
 use std::fmt;
 
 /// Errors produced by CLI operations, each mapped to a distinct exit code.
+#[derive(Debug)]
 pub enum CliError {
     /// An engine-level error (unknown engine, step failure, bad dimensions).
     Engine(EngineError),
@@ -21,6 +24,11 @@ pub enum CliError {
     Input(String),
     /// A serialization error (JSON output failure).
     Serialization(String),
+    /// A verification failure (compared images differ beyond tolerance).
+    Mismatch(String),
+    /// A GPU failure (no headless context, shader compile error).
+    #[cfg(feature = "gpu")]
+    Gpu(String),
 }
 
 impl CliError {
@@ -31,6 +39,9 @@ impl CliError {
             CliError::Io(_) => 11,
             CliError::Input(_) => 12,
             CliError::Serialization(_) => 13,
+            CliError::Mismatch(_) => 14,
+            #[cfg(feature = "gpu")]
+            CliError::Gpu(_) => 15,
         }
     }
 }
@@ -42,6 +53,9 @@ impl fmt::Display for CliError {
             CliError::Io(msg) => write!(f, "{msg}"),
             CliError::Input(msg) => write!(f, "{msg}"),
             CliError::Serialization(msg) => write!(f, "{msg}"),
+            CliError::Mismatch(msg) => write!(f, "{msg}"),
+            #[cfg(feature = "gpu")]
+            CliError::Gpu(msg) => write!(f, "gpu: {msg}"),
         }
     }
 }
@@ -89,6 +103,19 @@ mod tests {
         assert_eq!(err.exit_code(), 13);
     }
 
+    #[test]
+    fn mismatch_exit_code_is_14() {
+        let err = CliError::Mismatch("images differ".into());
+        assert_eq!(err.exit_code(), 14);
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn gpu_exit_code_is_15() {
+        let err = CliError::Gpu("no EGL display".into());
+        assert_eq!(err.exit_code(), 15);
+    }
+
     #[test]
     fn from_engine_error_io_routes_to_cli_io() {
         let engine_err = EngineError::Io("disk full".into());
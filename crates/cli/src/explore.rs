@@ -0,0 +1,224 @@
+//! Seed exploration: many random variations of one configuration.
+//!
+//! [`candidates`] draws distinct seeds (below one million, so labels stay
+//! short) from a PRNG seeded by the run's `--seed`, so the same command
+//! always explores the same candidates. With a non-zero jitter, every
+//! numeric parameter in the engine schema is also moved by a random amount
+//! of up to `jitter` times its range, clamped to the range.
+
+use crate::batch::{run_jobs, worker_threads};
+use crate::error::CliError;
+use crate::gallery::thumbnail;
+use crate::sweep::{contact_sheet, MAX_CELLS};
+use crate::{simulate, Sim, Simulation};
+use art_engine_core::{Engine, Image, Rng, Seed, SeedValue, Xorshift64};
+use art_engine_engines::pixel::field_to_rgba_in;
+use art_engine_engines::EngineKind;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Exclusive upper bound of explored seeds.
+const SEED_LIMIT: usize = 1_000_000;
+
+/// One explored variation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// Engine seed.
+    pub seed: u64,
+    /// Engine parameters, jittered if requested.
+    pub params: Value,
+}
+
+/// Draws `count` candidates around `base` params (a JSON object).
+///
+/// `count` must not exceed the number of distinct seeds available.
+pub fn candidates(
+    count: usize,
+    rng_seed: u64,
+    jitter: f64,
+    base: &Value,
+    schema: &Value,
+) -> Vec<Candidate> {
+    let mut rng = Xorshift64::new(rng_seed);
+    let mut seen = HashSet::new();
+    (0..count.min(SEED_LIMIT))
+        .map(|_| {
+            let seed = std::iter::repeat_with(|| rng.next_usize(SEED_LIMIT) as u64)
+                .find(|seed| seen.insert(*seed))
+                .unwrap_or_default();
+            let mut params = base.clone();
+            if jitter > 0.0 {
+                schema
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .for_each(|(name, spec)| {
+                        let number = |key: &str| spec.get(key).and_then(Value::as_f64);
+                        let (Some(min), Some(max)) = (number("min"), number("max")) else {
+                            return;
+                        };
+                        let start = base
+                            .get(name)
+                            .and_then(Value::as_f64)
+                            .or(number("default"))
+                            .unwrap_or(min);
+                        let offset = rng.next_range(-jitter, jitter) * (max - min);
+                        params[name] = (start + offset).clamp(min, max).into();
+                    });
+            }
+            Candidate { seed, params }
+        })
+        .collect()
+}
+
+/// Runs `explore`: renders `count` candidates of `sim` as `thumb_size`
+/// thumbnails on `threads` workers, writes the grid to `output` and the
+/// candidates' seeds beside it as a batch job list.
+pub fn run(
+    sim: &Sim,
+    count: u32,
+    jitter: f64,
+    thumb_size: u32,
+    output: &Path,
+    threads: Option<u32>,
+    json: bool,
+) -> Result<(), CliError> {
+    if sim.resume.is_some() {
+        return Err(CliError::Input(
+            "explore renders new seeds, so it cannot --resume a checkpoint".into(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&jitter) {
+        return Err(CliError::Input("--jitter must be between 0 and 1".into()));
+    }
+    let count = count as usize;
+    if count > MAX_CELLS {
+        return Err(CliError::Input(format!(
+            "explore has {count} cells; the limit is {}",
+            MAX_CELLS
+        )));
+    }
+    let base: serde_json::Value = serde_json::from_str(&sim.params)
+        .map_err(|e| CliError::Input(format!("invalid --params JSON: {e}")))?;
+    if !base.is_object() {
+        return Err(CliError::Input("--params must be a JSON object".into()));
+    }
+    let schema =
+        EngineKind::from_name(&sim.engine, 8, 8, 0, &serde_json::json!({}))?.param_schema();
+    let candidates = candidates(count, sim.seed.value, jitter, &base, &schema);
+    let cells: Vec<Sim> = candidates
+        .iter()
+        .map(|candidate| Sim {
+            seed: SeedValue::number(candidate.seed),
+            params: candidate.params.to_string(),
+            ..sim.clone()
+        })
+        .collect();
+    let results = run_jobs(
+        &cells,
+        worker_threads(threads),
+        false,
+        |_, cell| {
+            let Simulation {
+                field,
+                hue,
+                palette,
+                ..
+            } = simulate(cell)?;
+            let rgba = field_to_rgba_in(&field, &palette, hue.as_ref(), cell.color_space)?;
+            let (small, w, h) = thumbnail(&rgba, cell.width, cell.height, thumb_size as usize);
+            Ok(Image::from_rgba8(w, h, &small)?)
+        },
+        |_, _| {},
+    );
+    let thumbs = results
+        .into_iter()
+        .flatten()
+        .collect::<Result<Vec<_>, CliError>>()?;
+    let labels: Vec<Vec<String>> = cells
+        .iter()
+        .map(|cell| vec![cell.seed.to_string()])
+        .collect();
+    let columns = (count as f64).sqrt().ceil() as usize;
+    let sheet = contact_sheet(&thumbs, &labels, columns)?;
+    let (width, height) = (sheet.width(), sheet.height());
+    art_engine_engines::snapshot::write_rgba_png_in(
+        &sheet.to_rgba8(),
+        width,
+        height,
+        sim.color_space,
+        output,
+    )?;
+
+    // Seeds in the same order as the grid, ready for `batch` or
+    // (one at a time) `render --seed-file`
+    let seeds: Vec<Seed> = candidates
+        .iter()
+        .zip(&cells)
+        .map(|(candidate, cell)| cell.to_seed(candidate.params.clone()))
+        .collect();
+    let jobs_path = output.with_extension("json");
+    std::fs::write(&jobs_path, serde_json::to_string_pretty(&seeds)? + "\n")
+        .map_err(|e| CliError::Io(format!("{}: {e}", jobs_path.display())))?;
+
+    if json {
+        let info = serde_json::json!({
+            "engine": sim.engine,
+            "cells": thumbs.len(),
+            "columns": columns.min(thumbs.len()),
+            "seeds": cells.iter().map(|cell| cell.seed.value).collect::<Vec<_>>(),
+            "width": width,
+            "height": height,
+            "output": output.display().to_string(),
+            "jobs": jobs_path.display().to_string(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        eprintln!(
+            "explored {} ({} seeds) -> {} (jobs: {})",
+            sim.engine,
+            thumbs.len(),
+            output.display(),
+            jobs_path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "rate": {"type": "number", "default": 0.5, "min": 0.0, "max": 1.0},
+            "mode": {"type": "string", "default": "a"},
+        })
+    }
+
+    #[test]
+    fn candidates_are_distinct_and_reproducible() {
+        let a = candidates(50, 3, 0.0, &json!({}), &schema());
+        assert_eq!(a.len(), 50);
+        assert_eq!(a.iter().map(|c| c.seed).collect::<HashSet<_>>().len(), 50);
+        assert!(a.iter().all(|c| c.params == json!({})));
+        assert_eq!(a, candidates(50, 3, 0.0, &json!({}), &schema()));
+        assert_ne!(a, candidates(50, 4, 0.0, &json!({}), &schema()));
+    }
+
+    #[test]
+    fn jitter_moves_numeric_params_within_range() {
+        let base = json!({"rate": 0.95, "other": 1});
+        let explored = candidates(40, 9, 0.2, &base, &schema());
+        let rates: Vec<f64> = explored
+            .iter()
+            .map(|c| c.params["rate"].as_f64().unwrap())
+            .collect();
+        assert!(rates.iter().all(|r| (0.75..=1.0).contains(r)));
+        assert!(rates.iter().any(|&r| r != 0.95));
+        assert!(explored.iter().all(|c| c.params["other"] == 1));
+        assert!(explored.iter().all(|c| c.params.get("mode").is_none()));
+    }
+}
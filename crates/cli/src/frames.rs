@@ -0,0 +1,282 @@
+//! Numbered PNG frame sequences with a JSON manifest.
+//!
+//! A [`FrameSequence`] writes `frame_00000.png`, `frame_00001.png`, ... into
+//! an output directory and, when finished, a `manifest.json` describing the
+//! run and listing every frame with the simulation step it captures. The
+//! numbering width fits the expected frame count (at least five digits), so
+//! the files sort lexically and feed straight into
+//! `ffmpeg -i frame_%05d.png`. Frames encoded in a wide-gamut color space
+//! are tagged with it. With the `video` feature, a [`FrameOutput`] can
+//! instead stream the frames straight into an encoder.
+
+use crate::error::CliError;
+#[cfg(feature = "video")]
+use crate::video::VideoEncoder;
+use art_engine_core::color::ColorSpace;
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest written by [`FrameSequence::finish`].
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// One written frame.
+struct FrameEntry {
+    file: String,
+    step: usize,
+}
+
+/// Writes numbered PNG frames into a directory.
+pub struct FrameSequence {
+    dir: PathBuf,
+    digits: usize,
+    space: ColorSpace,
+    frames: Vec<FrameEntry>,
+}
+
+impl FrameSequence {
+    /// Creates `dir` (and any parents) for a sequence of about
+    /// `expected_frames` frames.
+    pub fn create(dir: &Path, expected_frames: usize) -> Result<Self, CliError> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| CliError::Io(format!("{}: {e}", dir.display())))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            digits: expected_frames.to_string().len().max(5),
+            space: ColorSpace::Srgb,
+            frames: Vec::new(),
+        })
+    }
+
+    /// Returns the sequence with frames tagged as encoded in `space`.
+    pub fn with_color_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Returns the number of frames written so far.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the `printf`-style pattern matching the frame file names.
+    pub fn pattern(&self) -> String {
+        format!("frame_%0{}d.png", self.digits)
+    }
+
+    /// Returns the file name of the frame at `index`.
+    fn file_name(&self, index: usize) -> String {
+        format!("frame_{index:0width$}.png", width = self.digits)
+    }
+
+    /// Writes the next frame, captured after `step` simulation steps.
+    pub fn write(
+        &mut self,
+        step: usize,
+        rgba: Vec<u8>,
+        width: usize,
+        height: usize,
+    ) -> Result<(), CliError> {
+        let file = self.file_name(self.frames.len());
+        art_engine_engines::snapshot::write_rgba_png_in(
+            &rgba,
+            width,
+            height,
+            self.space,
+            &self.dir.join(&file),
+        )?;
+        self.frames.push(FrameEntry { file, step });
+        Ok(())
+    }
+
+    /// Records frames an interrupted run already wrote, one per entry of
+    /// `steps`, so numbering and the manifest continue after them.
+    ///
+    /// Returns `CliError::Input` if any of those frames is missing.
+    pub fn resume(&mut self, steps: &[usize]) -> Result<(), CliError> {
+        steps.iter().try_for_each(|&step| {
+            let file = self.file_name(self.frames.len());
+            if !self.dir.join(&file).exists() {
+                return Err(CliError::Input(format!(
+                    "cannot resume: {} is missing",
+                    self.dir.join(&file).display()
+                )));
+            }
+            self.frames.push(FrameEntry { file, step });
+            Ok(())
+        })
+    }
+
+    /// Writes the manifest: the fields of `run` plus `frame_count`,
+    /// `pattern`, and a `frames` list of `{file, step}`. Returns its path.
+    pub fn finish(self, run: serde_json::Value) -> Result<PathBuf, CliError> {
+        let mut manifest = match run {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        manifest.insert("frame_count".into(), self.frames.len().into());
+        manifest.insert("pattern".into(), self.pattern().into());
+        let frames = self
+            .frames
+            .iter()
+            .map(|f| serde_json::json!({ "file": f.file, "step": f.step }))
+            .collect();
+        manifest.insert("frames".into(), serde_json::Value::Array(frames));
+
+        let path = self.dir.join(MANIFEST_NAME);
+        let text = serde_json::to_string_pretty(&manifest)?;
+        std::fs::write(&path, text)
+            .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        Ok(path)
+    }
+}
+
+/// Destination of an animation: a PNG sequence or, with the `video`
+/// feature, an encoded video file.
+pub enum FrameOutput {
+    /// Numbered PNGs plus a manifest.
+    Png(FrameSequence),
+    /// Frames piped into ffmpeg.
+    #[cfg(feature = "video")]
+    Video {
+        encoder: VideoEncoder,
+        path: PathBuf,
+    },
+}
+
+impl FrameOutput {
+    /// Returns the number of frames written so far.
+    pub fn len(&self) -> usize {
+        match self {
+            FrameOutput::Png(seq) => seq.len(),
+            #[cfg(feature = "video")]
+            FrameOutput::Video { encoder, .. } => encoder.len(),
+        }
+    }
+
+    /// Writes the next frame, captured after `step` simulation steps.
+    pub fn write(
+        &mut self,
+        step: usize,
+        rgba: Vec<u8>,
+        width: usize,
+        height: usize,
+    ) -> Result<(), CliError> {
+        match self {
+            FrameOutput::Png(seq) => seq.write(step, rgba, width, height),
+            #[cfg(feature = "video")]
+            FrameOutput::Video { encoder, .. } => encoder.write_frame(&rgba),
+        }
+    }
+
+    /// Continues after frames written by an interrupted run (see
+    /// [`FrameSequence::resume`]).
+    ///
+    /// Returns `CliError::Input` for video output, which cannot be appended to.
+    pub fn resume(&mut self, steps: &[usize]) -> Result<(), CliError> {
+        match self {
+            FrameOutput::Png(seq) => seq.resume(steps),
+            #[cfg(feature = "video")]
+            FrameOutput::Video { .. } => Err(CliError::Input(
+                "--resume cannot continue a video; render PNG frames instead".into(),
+            )),
+        }
+    }
+
+    /// Finishes the output and returns the file a viewer should open: the
+    /// PNG manifest (see [`FrameSequence::finish`]) or the video.
+    pub fn finish(self, run: serde_json::Value) -> Result<PathBuf, CliError> {
+        match self {
+            FrameOutput::Png(seq) => seq.finish(run),
+            #[cfg(feature = "video")]
+            FrameOutput::Video { encoder, path } => {
+                encoder.finish()?;
+                Ok(path)
+            }
+        }
+    }
+}
+
+/// Returns the capture interval that spreads `steps` over about
+/// `duration * fps` frames.
+///
+/// Returns `CliError::Input` unless `duration` is positive and finite.
+pub fn every_for_duration(steps: usize, duration: f64, fps: u32) -> Result<usize, CliError> {
+    if !(duration.is_finite() && duration > 0.0) {
+        return Err(CliError::Input(format!(
+            "--duration must be a positive number of seconds, got {duration}"
+        )));
+    }
+    let frames = (duration * f64::from(fps)).ceil().max(1.0) as usize;
+    Ok(steps.div_ceil(frames).max(1))
+}
+
+/// Returns the steps after which frames are captured: every `every` steps
+/// and after the final step (or once at step 0 when `steps` is 0).
+pub fn capture_steps(steps: usize, every: usize) -> Vec<usize> {
+    let mut out: Vec<usize> = (1..=steps).filter(|s| s % every == 0).collect();
+    if out.last() != Some(&steps) {
+        out.push(steps);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_steps_include_the_final_step() {
+        assert_eq!(capture_steps(10, 5), vec![5, 10]);
+        assert_eq!(capture_steps(12, 5), vec![5, 10, 12]);
+        assert_eq!(capture_steps(3, 5), vec![3]);
+        assert_eq!(capture_steps(0, 5), vec![0]);
+    }
+
+    #[test]
+    fn duration_sets_the_capture_interval() {
+        // 2 s at 30 fps = 60 frames over 600 steps
+        assert_eq!(every_for_duration(600, 2.0, 30).unwrap(), 10);
+        assert_eq!(capture_steps(600, 10).len(), 60);
+        assert_eq!(every_for_duration(10, 5.0, 30).unwrap(), 1);
+        assert_eq!(every_for_duration(601, 2.0, 30).unwrap(), 11);
+        assert!(every_for_duration(10, 0.0, 30).is_err());
+        assert!(every_for_duration(10, f64::NAN, 30).is_err());
+    }
+
+    #[test]
+    fn frames_are_zero_padded_and_listed_in_the_manifest() {
+        let dir = std::env::temp_dir().join(format!("art-engine-frames-{}", std::process::id()));
+        let mut seq = FrameSequence::create(&dir, 120_000).unwrap();
+        assert_eq!(seq.pattern(), "frame_%06d.png");
+        seq.write(10, vec![0; 4], 1, 1).unwrap();
+        seq.write(20, vec![255; 4], 1, 1).unwrap();
+        assert_eq!(seq.len(), 2);
+        let path = seq
+            .finish(serde_json::json!({ "engine": "gray-scott" }))
+            .unwrap();
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(manifest["engine"], "gray-scott");
+        assert_eq!(manifest["frame_count"], 2);
+        assert_eq!(manifest["frames"][1]["file"], "frame_000001.png");
+        assert_eq!(manifest["frames"][1]["step"], 20);
+        assert!(dir.join("frame_000000.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn resumed_sequences_continue_numbering() {
+        let dir = std::env::temp_dir().join(format!("art-engine-resume-{}", std::process::id()));
+        let mut first = FrameSequence::create(&dir, 3).unwrap();
+        first.write(10, vec![0; 4], 1, 1).unwrap();
+
+        let mut resumed = FrameSequence::create(&dir, 3).unwrap();
+        assert!(resumed.resume(&[10, 20]).is_err());
+        let mut resumed = FrameSequence::create(&dir, 3).unwrap();
+        resumed.resume(&[10]).unwrap();
+        resumed.write(20, vec![0; 4], 1, 1).unwrap();
+        assert_eq!(resumed.len(), 2);
+        assert!(dir.join("frame_00001.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+//! Static HTML gallery of rendered pieces.
+//!
+//! [`scan`] collects the PNGs in a directory (sorted by file name) along
+//! with any embedded [`Seed`]; the `gallery` command copies each image into
+//! `images/`, writes a box-filtered thumbnail into `thumbs/`, and emits an
+//! `index.html` with a thumbnail grid and each piece's settings. The site
+//! has no external assets, so it can be opened from disk or served as is.
+
+use crate::batch::{run_jobs, worker_threads};
+use crate::error::CliError;
+use art_engine_core::Seed;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Directory under the site root holding full-size images.
+pub const IMAGES_DIR: &str = "images";
+/// Directory under the site root holding thumbnails.
+pub const THUMBS_DIR: &str = "thumbs";
+
+/// One PNG found by [`scan`].
+#[derive(Debug, Clone)]
+pub struct Piece {
+    /// Path of the source image.
+    pub path: PathBuf,
+    /// File name, used for the copies in the site.
+    pub file: String,
+    /// Embedded seed, if the image was rendered by this tool.
+    pub seed: Option<Seed>,
+}
+
+/// Lists the `.png` files directly inside `dir`, sorted by file name, with
+/// their embedded seeds.
+///
+/// Images whose metadata cannot be read are listed without a seed.
+pub fn scan(dir: &Path) -> Result<Vec<Piece>, CliError> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| CliError::Io(format!("{}: {e}", dir.display())))?;
+    let mut pieces: Vec<Piece> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && crate::has_extension(path, "png"))
+        .filter_map(|path| {
+            let file = path.file_name()?.to_str()?.to_string();
+            let seed = art_engine_engines::snapshot::read_png_seed(&path)
+                .ok()
+                .flatten();
+            Some(Piece { path, file, seed })
+        })
+        .collect();
+    pieces.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(pieces)
+}
+
+/// Box-filters an RGBA8 image so its longest side is at most `max`;
+/// smaller images are returned unchanged. Returns the pixels and size.
+pub fn thumbnail(rgba: &[u8], width: usize, height: usize, max: usize) -> (Vec<u8>, usize, usize) {
+    let longest = width.max(height);
+    if longest <= max {
+        return (rgba.to_vec(), width, height);
+    }
+    let tw = (width * max / longest).max(1);
+    let th = (height * max / longest).max(1);
+    let span = |i: usize, src: usize, dst: usize| {
+        i * src / dst..((i + 1) * src / dst).max(i * src / dst + 1)
+    };
+    let pixels = (0..tw * th)
+        .flat_map(|i| {
+            let (xs, ys) = (span(i % tw, width, tw), span(i / tw, height, th));
+            let count = (xs.len() * ys.len()) as u32;
+            let mut sum = [0u32; 4];
+            ys.flat_map(|y| xs.clone().map(move |x| (y * width + x) * 4))
+                .for_each(|p| (0..4).for_each(|c| sum[c] += u32::from(rgba[p + c])));
+            sum.map(|s| ((s + count / 2) / count) as u8)
+        })
+        .collect();
+    (pixels, tw, th)
+}
+
+/// Escapes text for HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut out, c| {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&#39;"),
+                _ => out.push(c),
+            }
+            out
+        })
+}
+
+/// Percent-encodes a file name for use as a relative URL.
+fn url_encode(name: &str) -> String {
+    name.bytes().fold(String::new(), |mut out, b| {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(char::from(b))
+            }
+            _ => {
+                let _ = write!(out, "%{b:02X}");
+            }
+        }
+        out
+    })
+}
+
+/// Renders `index.html` for `pieces`, whose copies live under
+/// [`IMAGES_DIR`] and [`THUMBS_DIR`].
+pub fn index_html(title: &str, pieces: &[Piece]) -> String {
+    let mut html = String::new();
+    let title = escape(title);
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ margin: 2rem; background: #111; color: #ddd; font: 14px/1.4 system-ui, sans-serif; }}
+h1 {{ font-weight: 400; }}
+main {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(260px, 1fr)); gap: 1.5rem; }}
+figure {{ margin: 0; background: #1b1b1b; padding: 0.75rem; border-radius: 4px; }}
+figure img {{ width: 100%; height: auto; image-rendering: auto; display: block; }}
+figcaption {{ margin-top: 0.5rem; }}
+dl {{ display: grid; grid-template-columns: auto 1fr; gap: 0 0.75rem; margin: 0.5rem 0 0; }}
+dt {{ color: #888; }}
+dd {{ margin: 0; }}
+pre {{ white-space: pre-wrap; word-break: break-all; margin: 0.25rem 0 0; color: #aaa; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{count} pieces</p>
+<main>
+"#,
+        count = pieces.len()
+    );
+    pieces.iter().for_each(|piece| {
+        let (file, url) = (escape(&piece.file), url_encode(&piece.file));
+        let _ = write!(
+            html,
+            "<figure>\n<a href=\"{IMAGES_DIR}/{url}\"><img src=\"{THUMBS_DIR}/{url}\" alt=\"{file}\" loading=\"lazy\"></a>\n<figcaption>{file}"
+        );
+        match &piece.seed {
+            Some(seed) => {
+                let rows = [
+                    ("engine", escape(&seed.engine)),
+                    ("size", format!("{}x{}", seed.width, seed.height)),
+                    ("steps", seed.steps.to_string()),
+                    ("seed", seed.seed.to_string()),
+                    ("palette", escape(seed.palette.as_deref().unwrap_or("ocean"))),
+                ];
+                html.push_str("\n<dl>");
+                rows.iter().for_each(|(name, value)| {
+                    let _ = write!(html, "<dt>{name}</dt><dd>{value}</dd>");
+                });
+                html.push_str("</dl>");
+                let params = serde_json::to_string_pretty(&seed.params).unwrap_or_default();
+                let _ = write!(
+                    html,
+                    "\n<details><summary>params</summary><pre>{}</pre></details>",
+                    escape(&params)
+                );
+            }
+            None => html.push_str("\n<p>no seed metadata</p>"),
+        }
+        html.push_str("</figcaption>\n</figure>\n");
+    });
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+/// Runs `gallery`: builds the site for the PNGs in `dir` into `output`,
+/// making `thumb_size` thumbnails on `threads` workers.
+pub fn run(
+    dir: &Path,
+    output: &Path,
+    title: &str,
+    thumb_size: u32,
+    threads: Option<u32>,
+    json: bool,
+) -> Result<(), CliError> {
+    let pieces = scan(dir)?;
+    if pieces.is_empty() {
+        return Err(CliError::Input(format!(
+            "no PNG files in {}",
+            dir.display()
+        )));
+    }
+    let images = output.join(IMAGES_DIR);
+    let thumbs = output.join(THUMBS_DIR);
+    [&images, &thumbs].iter().try_for_each(|d| {
+        std::fs::create_dir_all(d).map_err(|e| CliError::Io(format!("{}: {e}", d.display())))
+    })?;
+    let results = run_jobs(
+        &pieces,
+        worker_threads(threads),
+        false,
+        |_, piece| {
+            std::fs::copy(&piece.path, images.join(&piece.file))
+                .map_err(|e| CliError::Io(format!("{}: {e}", piece.path.display())))?;
+            let (rgba, w, h) = art_engine_engines::snapshot::read_rgba_png(&piece.path)?;
+            let (small, tw, th) = thumbnail(&rgba, w, h, thumb_size as usize);
+            art_engine_engines::snapshot::write_rgba_png(small, tw, th, &thumbs.join(&piece.file))?;
+            Ok(())
+        },
+        |_, _| {},
+    );
+    results
+        .into_iter()
+        .flatten()
+        .collect::<Result<Vec<_>, _>>()?;
+    let index = output.join("index.html");
+    std::fs::write(&index, index_html(title, &pieces))
+        .map_err(|e| CliError::Io(format!("{}: {e}", index.display())))?;
+
+    if json {
+        let info = serde_json::json!({
+            "pieces": pieces.len(),
+            "with_seed": pieces.iter().filter(|p| p.seed.is_some()).count(),
+            "index": index.display().to_string(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        eprintln!("gallery of {} pieces -> {}", pieces.len(), index.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thumbnail_averages_blocks_and_keeps_small_images() {
+        let rgba: Vec<u8> = [[0, 0, 0, 255], [200, 100, 50, 255]].repeat(8).concat();
+        let (small, w, h) = thumbnail(&rgba, 4, 4, 2);
+        assert_eq!((w, h), (2, 2));
+        assert_eq!(&small[..4], &[100, 50, 25, 255]);
+        assert_eq!(thumbnail(&rgba, 4, 4, 4), (rgba.clone(), 4, 4));
+        let (_, w, h) = thumbnail(&rgba, 16, 1, 4);
+        assert_eq!((w, h), (4, 1));
+    }
+
+    #[test]
+    fn index_lists_pieces_with_escaped_details() {
+        let seed = Seed {
+            palette: Some("fire".into()),
+            params: serde_json::json!({"note": "<b>"}),
+            ..Seed::new("gray-scott", 64, 32, 7)
+        };
+        let pieces = [
+            Piece {
+                path: PathBuf::from("a&b.png"),
+                file: "a&b.png".into(),
+                seed: Some(seed),
+            },
+            Piece {
+                path: PathBuf::from("plain.png"),
+                file: "plain.png".into(),
+                seed: None,
+            },
+        ];
+        let html = index_html("My <Work>", &pieces);
+        assert!(html.contains("<title>My &lt;Work&gt;</title>"));
+        assert!(html.contains(r#"<img src="thumbs/a%26b.png" alt="a&amp;b.png""#));
+        assert!(html.contains("<dt>size</dt><dd>64x32</dd>"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(html.contains("no seed metadata"));
+        assert!(html.contains("2 pieces"));
+    }
+
+    #[test]
+    fn scan_finds_pngs_in_name_order() {
+        let dir = std::env::temp_dir().join(format!("art-engine-gallery-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let seed = Seed::new("gray-scott", 1, 1, 3);
+        art_engine_engines::snapshot::write_rgba_png_with_seed(
+            &[0; 4],
+            1,
+            1,
+            &seed,
+            &dir.join("b.png"),
+        )
+        .unwrap();
+        art_engine_engines::snapshot::write_rgba_png(vec![0; 4], 1, 1, &dir.join("a.PNG")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "x").unwrap();
+
+        let pieces = scan(&dir).unwrap();
+        let files: Vec<&str> = pieces.iter().map(|p| p.file.as_str()).collect();
+        assert_eq!(files, ["a.PNG", "b.png"]);
+        assert!(pieces[0].seed.is_none());
+        assert_eq!(pieces[1].seed.as_ref().unwrap().seed, 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
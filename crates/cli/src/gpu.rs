@@ -0,0 +1,160 @@
+//! GPU color mapping for `render --gpu` (the `gpu` feature).
+//!
+//! The engine still steps on the CPU; the final field is uploaded as an
+//! `R32F` texture with `upload_field` and mapped through a 256-entry palette
+//! lookup texture by a fullscreen pass on a headless EGL context, then read
+//! back for PNG encoding. The lookup table is a `PaletteLut`, so colors match
+//! `field_to_rgba` up to the table's 1/255 quantization. `--bloom`
+//! runs `BloomPass` on the mapped colors before the read-back. Targets are
+//! RGBA8 when the context lacks `EXT_color_buffer_float`.
+
+use art_engine_core::render::{
+    compile_program, preprocess, read_image, upload_field, BloomPass, HeadlessContext, PaletteLut,
+    RenderTarget, FULLSCREEN_VERTEX_SHADER,
+};
+use art_engine_core::{Bloom, Field, Palette};
+
+use crate::error::CliError;
+
+/// The colormap fragment shader: field value through the palette LUT.
+const COLORMAP_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_field;
+uniform sampler2D u_lut;
+out vec4 fragColor;
+#include "palette_lut.glsl"
+void main() {
+    fragColor = palette_lut(u_lut, texture(u_field, v_uv).r);
+}
+"#;
+
+/// Maps `field` through `palette` on a headless GPU context, optionally
+/// applying `bloom`, and returns RGBA8 pixels in the same row order as the
+/// CPU path.
+pub fn field_to_rgba_gpu(
+    field: &Field,
+    palette: &Palette,
+    bloom: Option<&Bloom>,
+) -> Result<Vec<u8>, CliError> {
+    let headless = HeadlessContext::new().map_err(CliError::Gpu)?;
+    let gl = headless.gl();
+    let format = headless.gpu().target_format();
+    let (width, height) = (field.width() as i32, field.height() as i32);
+
+    let field_texture = upload_field(headless.gpu(), field).map_err(CliError::Gpu)?;
+    let lut_texture = PaletteLut::new()
+        .upload(gl, palette)
+        .map_err(CliError::Gpu)?;
+    let shader = preprocess(COLORMAP_SHADER).map_err(|e| CliError::Gpu(e.to_string()))?;
+    let program = compile_program(gl, FULLSCREEN_VERTEX_SHADER, &shader)
+        .map_err(|e| CliError::Gpu(e.to_string()))?;
+    let target = RenderTarget::with_format(gl, width as u32, height as u32, format)
+        .map_err(CliError::Gpu)?;
+
+    draw(gl, program, field_texture, lut_texture, &target);
+    let image = match bloom {
+        Some(bloom) => {
+            BloomPass::with_format(gl, width as u32, height as u32, format).and_then(|mut pass| {
+                let image = pass
+                    .apply(gl, target.texture(), bloom)
+                    .and_then(|output| read_image(gl, output));
+                pass.destroy(gl);
+                image
+            })
+        }
+        None => read_image(gl, &target),
+    };
+
+    target.destroy(gl);
+    delete(gl, program, [field_texture, lut_texture]);
+    Ok(image.map_err(CliError::Gpu)?.to_rgba8())
+}
+
+/// Runs the colormap pass into `target`.
+#[allow(unsafe_code)]
+fn draw(
+    gl: &glow::Context,
+    program: glow::Program,
+    field: glow::Texture,
+    lut: glow::Texture,
+    target: &RenderTarget,
+) {
+    use glow::HasContext;
+
+    target.bind(gl);
+    // SAFETY: all handles are live.
+    unsafe {
+        let vao = gl.create_vertex_array().ok();
+        gl.use_program(Some(program));
+        for (unit, (name, texture)) in [("u_field", field), ("u_lut", lut)].into_iter().enumerate()
+        {
+            gl.active_texture(glow::TEXTURE0 + unit as u32);
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            let location = gl.get_uniform_location(program, name);
+            gl.uniform_1_i32(location.as_ref(), unit as i32);
+        }
+        gl.bind_vertex_array(vao);
+        gl.draw_arrays(glow::TRIANGLES, 0, 3);
+        gl.bind_vertex_array(None);
+        if let Some(vao) = vao {
+            gl.delete_vertex_array(vao);
+        }
+        gl.use_program(None);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+    }
+}
+
+/// Deletes the colormap program and input textures.
+#[allow(unsafe_code)]
+fn delete(gl: &glow::Context, program: glow::Program, textures: [glow::Texture; 2]) {
+    use glow::HasContext;
+
+    // SAFETY: the handles were created by field_to_rgba_gpu and are unused now.
+    unsafe {
+        gl.delete_program(program);
+        textures.into_iter().for_each(|t| gl.delete_texture(t));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use art_engine_core::Image;
+    use art_engine_engines::pixel::field_to_rgba;
+
+    #[test]
+    fn lut_matches_the_cpu_palette_at_its_entries() {
+        let palette = Palette::ocean().with_lut(false);
+        let lut = PaletteLut::new().with_size(700);
+        let ramp = (0..700).map(|i| f64::from(i) / 699.0).collect();
+        let ramp = Field::from_data(700, 1, ramp).unwrap();
+        assert_eq!(lut.bake(&palette), field_to_rgba(&ramp, &palette));
+    }
+
+    #[test]
+    fn gpu_colors_match_the_cpu_path() {
+        let palette = Palette::fire().with_lut(false);
+        // Values on LUT entries map exactly; others land on the nearest one
+        let data = (0..64).map(|i| f64::from(i * 4) / 255.0).collect();
+        let field = Field::from_data(16, 4, data).unwrap();
+        let gpu = field_to_rgba_gpu(&field, &palette, None).unwrap();
+        assert_eq!(gpu, field_to_rgba(&field, &palette));
+    }
+
+    #[test]
+    fn gpu_bloom_matches_the_cpu_bloom() {
+        let palette = Palette::fire();
+        let data = (0..96).map(|i| f64::from(i % 12) / 11.0).collect();
+        let field = Field::from_data(12, 8, data).unwrap();
+        let bloom = Bloom::new(1.0).with_threshold(0.4).with_radius(0.5);
+        let gpu = field_to_rgba_gpu(&field, &palette, Some(&bloom)).unwrap();
+        let cpu = bloom
+            .apply(&Image::from_rgba8(12, 8, &field_to_rgba(&field, &palette)).unwrap())
+            .to_rgba8();
+        assert_ne!(gpu, field_to_rgba(&field, &palette));
+        for (g, c) in gpu.iter().zip(&cpu) {
+            assert!(g.abs_diff(*c) <= 1, "gpu {g} vs cpu {c}");
+        }
+    }
+}
@@ -0,0 +1,675 @@
+//! HTTP render server for `serve --http ADDR`.
+//!
+//! Jobs are [`BatchJob`]s without an `output` path (a [`Seed`]), queued and
+//! rendered one at a time in submission order. Finished jobs keep their PNG
+//! in memory until [`KEEP_FINISHED`] newer jobs have finished.
+//!
+//! At most [`MAX_HANDLERS`] requests are answered at once (an open event
+//! stream counts until it ends) and at most [`MAX_QUEUED`] jobs wait behind
+//! the one rendering; past either limit the server answers `503`.
+//!
+//! | Request                       | Response                                   |
+//! |-------------------------------|--------------------------------------------|
+//! | `GET /engines`                | `{"engines": [name, ...]}`                 |
+//! | `GET /engines/{name}/schema`  | the engine's param schema                  |
+//! | `POST /jobs` (job JSON)       | `202` with the new job's status            |
+//! | `GET /jobs`                   | `{"jobs": [status, ...]}`                  |
+//! | `GET /jobs/{id}`              | the job's status                           |
+//! | `GET /jobs/{id}/events`       | `text/event-stream` of statuses until done |
+//! | `GET /jobs/{id}/png`          | the PNG, with the seed embedded            |
+//!
+//! A status is `{"id", "engine", "state", "step", "steps"}` where `state` is
+//! `queued`, `running`, `done` or `failed`; failed jobs add `error` and
+//! `exit_code` as in the stdio server's error responses. Errors are JSON
+//! `{"error": ...}` bodies with a 4xx status; asking for the PNG of an
+//! unfinished or failed job is a `409` carrying its status.
+//!
+//! [`Seed`]: art_engine_core::Seed
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, ResponseBox};
+
+use crate::batch::BatchJob;
+use crate::error::CliError;
+
+/// Finished jobs kept for status and PNG requests.
+pub const KEEP_FINISHED: usize = 64;
+
+/// Requests answered at once.
+pub const MAX_HANDLERS: usize = 32;
+
+/// Jobs waiting to render.
+pub const MAX_QUEUED: usize = 256;
+
+/// Largest accepted job body in bytes.
+const MAX_BODY: u64 = 1 << 20;
+
+/// Renders a job to PNG bytes, reporting each step reached.
+pub type Render<'a> = dyn FnMut(&BatchJob, &mut dyn FnMut(usize)) -> Result<Vec<u8>, CliError> + 'a;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum State {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug)]
+struct Job {
+    engine: String,
+    state: State,
+    step: usize,
+    steps: usize,
+    png: Option<Arc<Vec<u8>>>,
+    error: Option<(String, i32)>,
+}
+
+impl Job {
+    fn status(&self, id: u64) -> Value {
+        let mut status = json!({
+            "id": id,
+            "engine": self.engine,
+            "state": self.state,
+            "step": self.step,
+            "steps": self.steps,
+        });
+        if let Some((error, exit_code)) = &self.error {
+            status["error"] = json!(error);
+            status["exit_code"] = json!(exit_code);
+        }
+        status
+    }
+
+    fn finished(&self) -> bool {
+        matches!(self.state, State::Done | State::Failed)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Jobs {
+    next: u64,
+    jobs: BTreeMap<u64, Job>,
+}
+
+/// Job table shared by the request threads and the render loop.
+#[derive(Debug, Default)]
+struct Shared {
+    jobs: Mutex<Jobs>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Jobs> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.lock().jobs.get_mut(&id) {
+            f(job);
+        }
+        self.changed.notify_all();
+    }
+
+    fn finish(&self, id: u64, result: Result<Vec<u8>, CliError>) {
+        let mut jobs = self.lock();
+        if let Some(job) = jobs.jobs.get_mut(&id) {
+            match result {
+                Ok(png) => {
+                    job.state = State::Done;
+                    job.step = job.steps;
+                    job.png = Some(Arc::new(png));
+                }
+                Err(e) => {
+                    job.state = State::Failed;
+                    job.error = Some((e.to_string(), e.exit_code()));
+                }
+            }
+        }
+        let finished: Vec<u64> = jobs
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.finished())
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &finished[..finished.len().saturating_sub(KEEP_FINISHED)] {
+            jobs.jobs.remove(id);
+        }
+        drop(jobs);
+        self.changed.notify_all();
+    }
+}
+
+/// A request handler's claim on one of the server's [`MAX_HANDLERS`]
+/// slots, given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn claim(active: &Arc<AtomicUsize>, limit: usize) -> Option<Self> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < limit).then_some(n + 1)
+            })
+            .ok()?;
+        Some(Self(active.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A bound HTTP render server.
+#[derive(Clone)]
+pub struct HttpServer {
+    server: Arc<tiny_http::Server>,
+    shared: Arc<Shared>,
+    /// Requests being answered.
+    active: Arc<AtomicUsize>,
+    max_handlers: usize,
+    max_queued: usize,
+}
+
+impl HttpServer {
+    /// Listens on `addr` (port 0 picks a free port).
+    pub fn bind(addr: SocketAddr) -> Result<Self, CliError> {
+        let server = tiny_http::Server::http(addr)
+            .map_err(|e| CliError::Io(format!("serve --http {addr}: {e}")))?;
+        Ok(Self {
+            server: Arc::new(server),
+            shared: Arc::default(),
+            active: Arc::default(),
+            max_handlers: MAX_HANDLERS,
+            max_queued: MAX_QUEUED,
+        })
+    }
+
+    /// Replaces [`MAX_HANDLERS`] and [`MAX_QUEUED`] (each at least 1).
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_limits(mut self, handlers: usize, queued: usize) -> Self {
+        self.max_handlers = handlers.max(1);
+        self.max_queued = queued.max(1);
+        self
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Makes [`HttpServer::run`] return once the queued jobs are rendered.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn stop(&self) {
+        self.server.unblock();
+    }
+
+    /// Answers requests until stopped, rendering jobs on the calling thread;
+    /// returns the number of jobs rendered.
+    ///
+    /// `schemas` maps each engine name to its param schema. Each request is
+    /// answered on its own thread, so event streams do not hold up others;
+    /// requests past [`MAX_HANDLERS`] get a `503` instead of a thread.
+    pub fn run(&self, schemas: BTreeMap<String, Value>, render: &mut Render<'_>) -> usize {
+        let (queue, jobs) = mpsc::sync_channel(self.max_queued);
+        let accept = {
+            let (this, schemas) = (self.clone(), Arc::new(schemas));
+            std::thread::spawn(move || {
+                for request in this.server.incoming_requests() {
+                    let Some(slot) = Slot::claim(&this.active, this.max_handlers) else {
+                        tracing::warn!(url = request.url(), "serve --http: too many requests");
+                        if let Err(e) = request.respond(error_response(503, "server is busy")) {
+                            tracing::debug!(error = %e, "response not sent");
+                        }
+                        continue;
+                    };
+                    let (this, schemas, queue) = (this.clone(), schemas.clone(), queue.clone());
+                    std::thread::spawn(move || {
+                        this.handle(request, &schemas, &queue);
+                        drop(slot);
+                    });
+                }
+            })
+        };
+        let mut rendered = 0;
+        for (id, job) in jobs {
+            self.shared.update(id, |job| job.state = State::Running);
+            let result = render(&job, &mut |step| {
+                self.shared.update(id, |job| job.step = step);
+            });
+            match &result {
+                Ok(_) => tracing::info!(id, "job done"),
+                Err(e) => tracing::warn!(id, error = %e, "job failed"),
+            }
+            self.shared.finish(id, result);
+            rendered += 1;
+        }
+        if accept.join().is_err() {
+            tracing::warn!("serve --http: accept thread panicked");
+        }
+        rendered
+    }
+
+    fn handle(
+        &self,
+        mut request: Request,
+        schemas: &BTreeMap<String, Value>,
+        queue: &SyncSender<(u64, BatchJob)>,
+    ) {
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        tracing::debug!(method = %request.method(), url, "request");
+        let id = |s: &str| s.parse::<u64>().ok();
+        let response = match (request.method(), segments.as_slice()) {
+            (Method::Get, ["engines"]) => json_response(
+                200,
+                &json!({ "engines": schemas.keys().collect::<Vec<_>>() }),
+            ),
+            (Method::Get, ["engines", name, "schema"]) => match schemas.get(*name) {
+                Some(schema) => json_response(200, schema),
+                None => error_response(404, &format!("unknown engine: {name}")),
+            },
+            (Method::Post, ["jobs"]) => self.submit(&mut request, schemas, queue),
+            (Method::Get, ["jobs"]) => {
+                let jobs = self.shared.lock();
+                let statuses: Vec<Value> =
+                    jobs.jobs.iter().map(|(id, job)| job.status(*id)).collect();
+                json_response(200, &json!({ "jobs": statuses }))
+            }
+            (Method::Get, ["jobs", job]) => match id(job).and_then(|id| self.status(id)) {
+                Some(status) => json_response(200, &status),
+                None => error_response(404, &format!("no job {job}")),
+            },
+            (Method::Get, ["jobs", job, "events"]) => {
+                match id(job).filter(|id| self.status(*id).is_some()) {
+                    Some(id) => return self.stream_events(request, id),
+                    None => error_response(404, &format!("no job {job}")),
+                }
+            }
+            (Method::Get, ["jobs", job, "png"]) => self.png(id(job), job),
+            _ => error_response(404, &format!("no route for {} {path}", request.method())),
+        };
+        if let Err(e) = request.respond(response) {
+            tracing::debug!(url, error = %e, "response not sent");
+        }
+    }
+
+    fn submit(
+        &self,
+        request: &mut Request,
+        schemas: &BTreeMap<String, Value>,
+        queue: &SyncSender<(u64, BatchJob)>,
+    ) -> ResponseBox {
+        let mut body = String::new();
+        if let Err(e) = request
+            .as_reader()
+            .take(MAX_BODY + 1)
+            .read_to_string(&mut body)
+        {
+            return error_response(400, &format!("invalid request: {e}"));
+        }
+        if body.len() as u64 > MAX_BODY {
+            return error_response(413, &format!("jobs are limited to {MAX_BODY} bytes"));
+        }
+        let job: BatchJob = match serde_json::from_str(&body) {
+            Ok(job) => job,
+            Err(e) => return error_response(400, &format!("invalid request: {e}")),
+        };
+        if let Err(e) = job.validate() {
+            return error_response(400, &e);
+        }
+        if job.output.is_some() {
+            return error_response(400, "jobs cannot name an output path; fetch /jobs/{id}/png");
+        }
+        if !schemas.contains_key(&job.seed.engine) {
+            return error_response(400, &format!("unknown engine: {}", job.seed.engine));
+        }
+        let entry = Job {
+            engine: job.seed.engine.clone(),
+            state: State::Queued,
+            step: 0,
+            steps: job.seed.steps,
+            png: None,
+            error: None,
+        };
+        // Held until the job is listed, so the render loop never sees it first
+        let mut jobs = self.shared.lock();
+        let id = jobs.next;
+        match queue.try_send((id, job)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                return error_response(
+                    503,
+                    &format!("job queue is full ({} waiting)", self.max_queued),
+                )
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                return error_response(503, "server is shutting down")
+            }
+        }
+        jobs.next += 1;
+        let status = entry.status(id);
+        jobs.jobs.insert(id, entry);
+        drop(jobs);
+        tracing::info!(id, "job queued");
+        json_response(202, &status)
+    }
+
+    fn status(&self, id: u64) -> Option<Value> {
+        self.shared.lock().jobs.get(&id).map(|job| job.status(id))
+    }
+
+    fn png(&self, id: Option<u64>, name: &str) -> ResponseBox {
+        let jobs = self.shared.lock();
+        match id.and_then(|id| Some((id, jobs.jobs.get(&id)?))) {
+            Some((_, Job { png: Some(png), .. })) => {
+                let png = Vec::clone(png);
+                drop(jobs);
+                with_type(Response::from_data(png), "image/png").boxed()
+            }
+            Some((id, job)) => json_response(409, &job.status(id)),
+            None => error_response(404, &format!("no job {name}")),
+        }
+    }
+
+    /// Writes each new status of job `id` as a server-sent event until the
+    /// job finishes or is dropped.
+    ///
+    /// tiny_http buffers chunked bodies in 8 KiB chunks, so the stream is
+    /// framed here and flushed after every event.
+    fn stream_events(&self, request: Request, id: u64) {
+        let chunked = *request.http_version() >= (1, 1);
+        let mut out = request.into_writer();
+        let head = format!(
+            "HTTP/1.{} 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n{}\r\n",
+            u8::from(chunked),
+            if chunked { "Transfer-Encoding: chunked\r\n" } else { "" },
+        );
+        let write = |out: &mut dyn Write, data: &[u8]| -> io::Result<()> {
+            if chunked {
+                write!(out, "{:x}\r\n", data.len())?;
+                out.write_all(data)?;
+                out.write_all(b"\r\n")?;
+            } else {
+                out.write_all(data)?;
+            }
+            out.flush()
+        };
+        let mut send = || -> io::Result<()> {
+            out.write_all(head.as_bytes())?;
+            let mut last = None;
+            loop {
+                let mut jobs = self.shared.lock();
+                let status = loop {
+                    let status = jobs.jobs.get(&id).map(|job| job.status(id));
+                    if status != last {
+                        break status;
+                    }
+                    jobs = self
+                        .shared
+                        .changed
+                        .wait(jobs)
+                        .unwrap_or_else(PoisonError::into_inner);
+                };
+                drop(jobs);
+                let Some(status) = status else { break };
+                let done = matches!(status["state"].as_str(), Some("done" | "failed"));
+                write(&mut out, format!("data: {status}\n\n").as_bytes())?;
+                if done {
+                    break;
+                }
+                last = Some(status);
+            }
+            if chunked {
+                out.write_all(b"0\r\n\r\n")?;
+            }
+            out.flush()
+        };
+        if let Err(e) = send() {
+            tracing::debug!(id, error = %e, "event stream closed");
+        }
+    }
+}
+
+fn with_type<R: Read>(response: Response<R>, content_type: &str) -> Response<R> {
+    match Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()) {
+        Ok(header) => response.with_header(header),
+        Err(()) => response,
+    }
+}
+
+fn json_response(status: u16, body: &Value) -> ResponseBox {
+    with_type(Response::from_string(body.to_string()), "application/json")
+        .with_status_code(status)
+        .boxed()
+}
+
+fn error_response(status: u16, error: &str) -> ResponseBox {
+    json_response(status, &json!({ "error": error }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpStream;
+
+    /// Sends one HTTP/1.0 request and returns the status code and body.
+    fn send(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.0\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let status = std::str::from_utf8(&response[9..12])
+            .unwrap()
+            .parse()
+            .unwrap();
+        (status, response[split + 4..].to_vec())
+    }
+
+    fn json(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let (status, body) = send(addr, method, path, body);
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn queues_jobs_streams_progress_and_serves_pngs() {
+        let server = HttpServer::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = server.local_addr().unwrap();
+        let (started, release) = mpsc::channel::<()>();
+        let runner = {
+            let server = server.clone();
+            let schemas = BTreeMap::from([("e".to_string(), json!({"rate": {"min": 0.0}}))]);
+            std::thread::spawn(move || {
+                server.run(schemas, &mut |job, progress| {
+                    // Hold the first job until the test has subscribed
+                    release.recv().unwrap();
+                    progress(1);
+                    match job.seed.seed {
+                        13 => Err(CliError::Input("unlucky".into())),
+                        seed => Ok(vec![seed as u8; 3]),
+                    }
+                })
+            })
+        };
+
+        assert_eq!(
+            json(addr, "GET", "/engines", "").1,
+            json!({"engines": ["e"]})
+        );
+        assert_eq!(
+            json(addr, "GET", "/engines/e/schema", "").1["rate"]["min"],
+            0.0
+        );
+        assert_eq!(json(addr, "GET", "/engines/x/schema", "").0, 404);
+
+        let job =
+            r#"{"engine": "e", "width": 4, "height": 4, "params": {}, "seed": 7, "steps": 2}"#;
+        let (status, queued) = json(addr, "POST", "/jobs", job);
+        assert_eq!(
+            (status, queued["id"].clone(), queued["state"].clone()),
+            (202, json!(0), json!("queued"))
+        );
+        assert_eq!(json(addr, "GET", "/jobs/0/png", "").0, 409);
+
+        // HTTP/1.1 streams are chunked and end with the final status
+        let mut events = TcpStream::connect(addr).unwrap();
+        events
+            .write_all(b"GET /jobs/0/events HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        started.send(()).unwrap();
+        let mut stream = String::new();
+        events.read_to_string(&mut stream).unwrap();
+        assert!(stream.contains("Transfer-Encoding: chunked"));
+        assert!(stream.ends_with("0\r\n\r\n"));
+        let last = stream
+            .lines()
+            .rev()
+            .find_map(|l| l.strip_prefix("data: "))
+            .unwrap();
+        let last: Value = serde_json::from_str(last).unwrap();
+        assert_eq!(
+            (last["state"].clone(), last["step"].clone()),
+            (json!("done"), json!(2))
+        );
+        assert_eq!(send(addr, "GET", "/jobs/0/png", ""), (200, vec![7; 3]));
+
+        json(addr, "POST", "/jobs", &job.replace("7", "13"));
+        started.send(()).unwrap();
+        let (_, stream) = send(addr, "GET", "/jobs/1/events", "");
+        assert!(String::from_utf8(stream)
+            .unwrap()
+            .contains(r#""error":"unlucky""#));
+        let (status, failed) = json(addr, "GET", "/jobs/1/png", "");
+        assert_eq!((status, failed["exit_code"].clone()), (409, json!(12)));
+        assert_eq!(
+            json(addr, "GET", "/jobs", "").1["jobs"]
+                .as_array()
+                .map(Vec::len),
+            Some(2)
+        );
+
+        let rejected = [
+            ("POST", "/jobs", "not json"),
+            (
+                "POST",
+                "/jobs",
+                r#"{"engine": "x", "width": 4, "height": 4, "params": {}, "seed": 1, "steps": 1}"#,
+            ),
+            (
+                "POST",
+                "/jobs",
+                r#"{"engine": "e", "width": 0, "height": 4, "params": {}, "seed": 1, "steps": 1}"#,
+            ),
+            (
+                "POST",
+                "/jobs",
+                &job.replace("}", r#", "output": "/tmp/x.png"}"#),
+            ),
+        ];
+        for (method, path, body) in rejected {
+            assert_eq!(json(addr, method, path, body).0, 400, "{body}");
+        }
+        assert_eq!(json(addr, "GET", "/jobs/9", "").0, 404);
+        assert_eq!(json(addr, "DELETE", "/jobs/0", "").0, 404);
+
+        server.stop();
+        assert_eq!(runner.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn busy_servers_answer_503() {
+        let server = HttpServer::bind("127.0.0.1:0".parse().unwrap())
+            .unwrap()
+            .with_limits(1, 1);
+        let addr = server.local_addr().unwrap();
+        let (started, release) = mpsc::channel::<()>();
+        let runner = {
+            let server = server.clone();
+            let schemas = BTreeMap::from([("e".to_string(), json!({}))]);
+            std::thread::spawn(move || {
+                server.run(schemas, &mut |_, _| {
+                    release.recv().unwrap();
+                    Ok(Vec::new())
+                })
+            })
+        };
+        // Waits until `n` requests are being answered
+        let handling = |n: usize| {
+            while server.active.load(Ordering::Acquire) != n {
+                std::thread::yield_now();
+            }
+        };
+        let call = |method: &str, path: &str, body: &str| {
+            handling(0);
+            json(addr, method, path, body)
+        };
+
+        let job =
+            r#"{"engine": "e", "width": 4, "height": 4, "params": {}, "seed": 7, "steps": 2}"#;
+        assert_eq!(call("POST", "/jobs", job).0, 202);
+        while call("GET", "/jobs/0", "").1["state"] != "running" {}
+        // One job may wait behind the running one
+        assert_eq!(call("POST", "/jobs", job).0, 202);
+        let (status, full) = call("POST", "/jobs", job);
+        assert_eq!(status, 503);
+        assert!(full["error"].as_str().unwrap().contains("queue is full"));
+        assert_eq!(
+            call("GET", "/jobs", "").1["jobs"].as_array().map(Vec::len),
+            Some(2)
+        );
+
+        // An open event stream holds the only handler
+        handling(0);
+        let mut events = TcpStream::connect(addr).unwrap();
+        events
+            .write_all(b"GET /jobs/0/events HTTP/1.0\r\n\r\n")
+            .unwrap();
+        handling(1);
+        let (status, busy) = json(addr, "GET", "/engines", "");
+        assert_eq!((status, busy), (503, json!({"error": "server is busy"})));
+
+        started.send(()).unwrap();
+        started.send(()).unwrap();
+        events.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(call("GET", "/engines", "").0, 200);
+        server.stop();
+        assert_eq!(runner.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn only_recent_finished_jobs_are_kept() {
+        let shared = Shared::default();
+        for id in 0..KEEP_FINISHED as u64 + 3 {
+            let job = Job {
+                engine: "e".into(),
+                state: State::Queued,
+                step: 0,
+                steps: 1,
+                png: None,
+                error: None,
+            };
+            shared.lock().jobs.insert(id, job);
+            shared.finish(id, Ok(Vec::new()));
+        }
+        let jobs = shared.lock();
+        assert_eq!(jobs.jobs.len(), KEEP_FINISHED);
+        assert_eq!(jobs.jobs.keys().next(), Some(&3));
+    }
+}
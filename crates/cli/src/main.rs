@@ -2,71 +2,1324 @@
 //! CLI binary for the art-engine generative art system.
 //!
 //! Subcommands:
-//! - `render <engine>` — run an engine N steps, write PNG
-//! - `list` — print available engines and palettes
+//! - `render <engine>` — run an engine N steps, write PNG (or SVG contours),
+//!   optionally streaming raw RGBA/y4m frames for ffmpeg; `--seed-file`
+//!   re-renders a saved `Seed` exactly; `--checkpoint-every` and `--resume`
+//!   let long runs survive interruptions; `--gpu` (`gpu` feature) maps colors
+//!   on a headless GPU context; `--bloom` adds a glow to the PNG
+//! - `list` — print available engines and palettes (`--presets`: presets)
+//! - `preview <engine>` — run an engine N steps, print it to the terminal
+//! - `info <file>` — print the seed embedded in a rendered PNG
+//! - `schema <engine>` — print an engine's tunable parameters
+//! - `validate <file>` — check a seed, job list or project against engine
+//!   schemas and palettes, exiting with the code rendering would fail with
+//! - `compare <a.png> <b.png>` — pixel diff stats and SSIM; exits 14 when the
+//!   images differ beyond tolerance
+//! - `render-project <project>` — render a multi-layer JSON/TOML project to PNG
+//! - `field-preview <spec>` — draw a JSON field source spec as streamlines
+//!   or line-integral convolution, for debugging field compositions
+//! - `animate <engine>` — write a PNG every N steps plus a manifest into a directory
+//! - `sweep <engine> --vary name=start:end:count` — render a parameter grid
+//!   as a labeled contact sheet
+//! - `explore <engine> --count N` — render random seeds (optionally with
+//!   jittered params) as a labeled thumbnail grid plus a batch job list
+//! - `batch <jobs.json>` — render a list of seeds in parallel
+//! - `serve --stdio` — render JSON-lines requests from stdin, answering each
+//!   on stdout with the output path or an inline base64 PNG
+//! - `bench [engines]` — measure steps/sec and pixels/sec at several sizes
+//! - `gallery <dir>` — build a static HTML portfolio of the PNGs in a directory
+//! - `animate-project <project>` — the same for a project, evaluating layer keyframes
+//! - `tui <engine>` — interactive parameter explorer (`tui` feature)
+//! - `completions <shell>`, `manpage` — generate shell completions and man pages
+//! - `watch <config>` — re-render a fast preview whenever a params, seed or
+//!   project file changes
+//!
+//! A TOML configuration file (`--config`, or `~/.config/art-engine/config.toml`)
+//! supplies defaults that explicit flags override; see [`config`].
 
+mod animate;
+#[cfg(feature = "audio")]
+mod audio;
+mod batch;
+mod bench;
+mod checkpoint;
+mod compare;
+mod completions;
+mod config;
+#[cfg(feature = "control")]
+mod control;
 mod error;
+mod explore;
+mod frames;
+mod gallery;
+#[cfg(feature = "gpu")]
+mod gpu;
+#[cfg(feature = "http")]
+mod http;
+mod preview;
+mod raw;
+mod reload;
+mod render;
+mod replay;
+mod serve;
+mod stats;
+mod sweep;
+#[cfg(feature = "tui")]
+mod tui;
+mod validate;
+#[cfg(feature = "video")]
+mod video;
+mod watch;
 
-use art_engine_core::{Engine, Palette};
+use animate::AnimateArgs;
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::color::{split_colors, ColorSpace};
+use art_engine_core::field_preview::{FieldPreview, PreviewStyle};
+use art_engine_core::field_source::FieldSourceSpec;
+use art_engine_core::observer::{self, Convergence};
+use art_engine_core::params::merge_params;
+use art_engine_core::{
+    Bloom, Engine, Field, Image, MemoryBudget, Palette, ParamTimeline, Project, ResizePolicy, Seed,
+    SeedValue, StepInfo, StepMonitor, StopWhenStable,
+};
+use art_engine_engines::composition::Composition;
+use art_engine_engines::pixel::field_to_rgba_in;
 use art_engine_engines::EngineKind;
-use clap::{Parser, Subcommand};
+use checkpoint::{Checkpoint, CheckpointPlan, Resume};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use config::{Config, PresetRef};
+#[cfg(feature = "control")]
+use control::ControlArgs;
 use error::CliError;
-use std::path::PathBuf;
+use frames::{every_for_duration, FrameOutput, FrameSequence};
+use preview::{render_preview, PreviewMode};
+use reload::ParamsFile;
+use render::RenderArgs;
+use serve::ServeOptions;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::{Duration, Instant};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use watch::WatchOptions;
 
 #[derive(Parser)]
-#[command(name = "art-engine", about = "Generative art engine CLI")]
+#[command(name = "art-engine", version, about = "Generative art engine CLI")]
 struct Cli {
     /// Output as JSON instead of human-readable text.
     #[arg(long, global = true)]
     json: bool,
 
+    /// Configuration file (default: ~/.config/art-engine/config.toml).
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Refuse renders estimated to need more memory than SIZE, such as
+    /// "16G" or "unlimited", instead of running out partway through.
+    /// [default: the configuration's memory_limit, else 8 GiB]
+    #[arg(long, global = true, value_name = "SIZE")]
+    memory_limit: Option<MemoryBudget>,
+
+    /// Log progress to stderr (-v info, -vv debug, -vvv trace).
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Log every event and timed span to stderr as JSON lines.
+    #[arg(long, global = true)]
+    trace: bool,
+
     #[command(subcommand)]
     command: Command,
 }
 
+/// Simulation arguments shared by `render`, `preview` and `animate`.
+#[derive(Args)]
+struct SimArgs {
+    /// Engine name (e.g. "gray-scott"); may be left to --preset.
+    #[arg(required_unless_present_any = ["seed_file", "resume", "preset"])]
+    engine: Option<String>,
+
+    /// Canvas width in pixels.
+    #[arg(short = 'W', long, default_value_t = 256, conflicts_with_all = ["seed_file", "resume"])]
+    width: usize,
+
+    /// Canvas height in pixels.
+    #[arg(short = 'H', long, default_value_t = 256, conflicts_with_all = ["seed_file", "resume"])]
+    height: usize,
+
+    /// Start from a preset: a size from the configuration file, or an
+    /// `engine/name` id such as "gray-scott/mitosis" (see `list --presets`).
+    /// Flags given explicitly override it.
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["seed_file", "resume"])]
+    preset: Option<String>,
+
+    /// Number of simulation steps.
+    #[arg(short, long, default_value_t = 1000, conflicts_with_all = ["seed_file", "resume"])]
+    steps: usize,
+
+    /// PRNG seed for deterministic output: a number, or a phrase such as
+    /// "crimson-tide-42" that is hashed to one.
+    #[arg(long, default_value = "42", conflicts_with_all = ["seed_file", "resume"])]
+    seed: SeedValue,
+
+    /// Palette name (ocean, neon, earth, monochrome, vapor, fire), or
+    /// comma-separated colors such as "#001f3f,#0a9396,#94d2bd" (hex,
+    /// rgb(), hsl() or CSS color names).
+    #[arg(short, long, default_value = "ocean", conflicts_with_all = ["seed_file", "resume"])]
+    palette: String,
+
+    /// Read the palette's colors from a file (comma, space or newline
+    /// separated) instead of --palette.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["palette", "seed_file", "resume"])]
+    palette_file: Option<PathBuf>,
+
+    /// Engine parameters as a JSON string.
+    #[arg(long, default_value = "{}", conflicts_with_all = ["seed_file", "resume"])]
+    params: String,
+
+    /// Encode output in this color space (srgb, display-p3, rec2020) and
+    /// tag the PNG with it; wide gamuts keep palette colors sRGB would clamp.
+    /// Overrides a --seed-file's. [default: srgb]
+    #[arg(long, value_name = "SPACE", conflicts_with = "resume")]
+    color_space: Option<ColorSpace>,
+
+    /// Simulate at N times the resolution, then area-downsample for output.
+    /// Overrides a --seed-file's. [default: 1]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "resume")]
+    oversample: Option<u32>,
+
+    /// Reproduce a saved Seed (JSON) instead of taking the engine, size,
+    /// steps, seed, palette and params from the command line.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["engine", "resume"])]
+    seed_file: Option<PathBuf>,
+
+    /// Render a --seed-file at WxH instead of its recorded size. Engines
+    /// simulate at the new size, so the result is no longer bit-identical.
+    #[arg(long, value_name = "WxH", requires = "seed_file", conflicts_with_all = ["engine", "resume"], value_parser = parse_size)]
+    override_size: Option<(usize, usize)>,
+
+    /// Continue an interrupted run from a checkpoint written by
+    /// --checkpoint-every, taking every setting from the checkpoint.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["engine", "seed_file"])]
+    resume: Option<PathBuf>,
+}
+
+/// Bloom post-processing options for `render`.
+#[derive(Args)]
+struct BloomArgs {
+    /// Add bloom with this intensity to the PNG output.
+    #[arg(long, value_name = "INTENSITY")]
+    bloom: Option<f64>,
+
+    /// Luma above which pixels contribute to the bloom.
+    #[arg(long, default_value_t = Bloom::default().threshold, requires = "bloom")]
+    bloom_threshold: f64,
+
+    /// Bloom spread as a fraction of the shorter image side.
+    #[arg(long, default_value_t = Bloom::default().radius, requires = "bloom")]
+    bloom_radius: f64,
+}
+
+impl BloomArgs {
+    /// Returns the bloom settings, if bloom was requested.
+    fn resolve(&self) -> Option<Bloom> {
+        self.bloom.map(|intensity| {
+            Bloom::new(intensity)
+                .with_threshold(self.bloom_threshold)
+                .with_radius(self.bloom_radius)
+        })
+    }
+}
+
+/// Intermediate snapshot options for `render`.
+#[derive(Args)]
+struct SnapshotArgs {
+    /// Also write an intermediate PNG every N simulation steps.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    snapshot_every: Option<u32>,
+
+    /// Directory for intermediate snapshots.
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = "snapshots",
+        requires = "snapshot_every"
+    )]
+    snapshot_dir: PathBuf,
+
+    /// Overwrite DIR/latest.png each time instead of writing numbered files.
+    #[arg(long, requires = "snapshot_every")]
+    snapshot_latest: bool,
+}
+
+impl SnapshotArgs {
+    /// Returns the path of the snapshot taken after `step` steps.
+    fn path(&self, step: usize) -> PathBuf {
+        match self.snapshot_latest {
+            true => self.snapshot_dir.join("latest.png"),
+            false => self.snapshot_dir.join(format!("step_{step:06}.png")),
+        }
+    }
+}
+
+/// Simulation settings after resolving `--seed-file`.
+#[derive(Clone)]
+struct Sim {
+    engine: String,
+    width: usize,
+    height: usize,
+    steps: usize,
+    seed: SeedValue,
+    palette: String,
+    params: String,
+    color_space: ColorSpace,
+    oversample: u32,
+    resume: Option<Resume>,
+    /// Budget the engine and output buffers must fit.
+    memory: MemoryBudget,
+    /// Stop early once the field changes by less than this per step (see
+    /// `StopWhenStable`).
+    until_stable: Option<f64>,
+}
+
+impl SimArgs {
+    /// Resolves the settings, loading and validating `--seed-file` if given,
+    /// to render within `memory`.
+    ///
+    /// A seed without a palette renders with the default palette, `ocean`.
+    /// Warns on stderr when the seed was produced by another version.
+    fn resolve(self, memory: MemoryBudget) -> Result<Sim, CliError> {
+        if let Some(path) = self.resume {
+            let checkpoint = Checkpoint::read(&path)?;
+            warn_on_version(&path, &checkpoint.seed);
+            tracing::debug!(path = %path.display(), step = checkpoint.step, "settings from checkpoint");
+            return Ok(Sim {
+                resume: Some(Resume {
+                    path,
+                    step: checkpoint.step,
+                    state: checkpoint.state,
+                }),
+                memory,
+                ..Sim::from_seed(checkpoint.seed)
+            });
+        }
+        let Some(path) = &self.seed_file else {
+            return Ok(Sim {
+                // clap requires an engine whenever --seed-file is absent
+                engine: self.engine.unwrap_or_default(),
+                width: self.width,
+                height: self.height,
+                steps: self.steps,
+                seed: self.seed,
+                palette: match &self.palette_file {
+                    Some(path) => read_palette_file(path)?,
+                    None => self.palette,
+                },
+                params: self.params,
+                color_space: self.color_space.unwrap_or_default(),
+                oversample: self.oversample.unwrap_or(1),
+                resume: None,
+                memory,
+                until_stable: None,
+            });
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        let mut spec: Seed = serde_json::from_str(&text)
+            .map_err(|e| CliError::Input(format!("{}: invalid seed: {e}", path.display())))?;
+        if let Some((width, height)) = self.override_size {
+            spec.width = width;
+            spec.height = height;
+        }
+        if let Some(space) = self.color_space {
+            spec.color_space = space;
+        }
+        if let Some(oversample) = self.oversample {
+            spec.oversample = oversample;
+        }
+        spec.validate()?;
+        warn_on_version(path, &spec);
+        tracing::debug!(path = %path.display(), "settings from seed file");
+        Ok(Sim {
+            memory,
+            ..Sim::from_seed(spec)
+        })
+    }
+}
+
+/// Warns on stderr when `spec` was produced by another version.
+fn warn_on_version(path: &Path, spec: &Seed) {
+    let current = env!("CARGO_PKG_VERSION");
+    if let Some(version) = spec.version.as_deref().filter(|v| *v != current) {
+        eprintln!(
+            "warning: {} was rendered by version {version}; this is {current}",
+            path.display()
+        );
+    }
+}
+
+impl Sim {
+    /// Takes the settings from a seed; a missing palette means `ocean`, and
+    /// the memory budget is the default.
+    fn from_seed(spec: Seed) -> Self {
+        Sim {
+            engine: spec.engine,
+            width: spec.width,
+            height: spec.height,
+            steps: spec.steps,
+            seed: SeedValue {
+                value: spec.seed,
+                phrase: spec.phrase,
+            },
+            palette: spec.palette.unwrap_or_else(|| "ocean".into()),
+            params: spec.params.to_string(),
+            color_space: spec.color_space,
+            oversample: spec.oversample,
+            resume: None,
+            memory: MemoryBudget::default(),
+            until_stable: None,
+        }
+    }
+
+    /// Returns the seed to embed in output rendered from these settings.
+    fn to_seed(&self, params: serde_json::Value) -> Seed {
+        Seed {
+            steps: self.steps,
+            params,
+            palette: Some(self.palette.clone()),
+            color_space: self.color_space,
+            oversample: self.oversample,
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..Seed::new(&self.engine, self.width, self.height, 0).with_seed(self.seed.clone())
+        }
+    }
+}
+
+/// Simulates and writes a PNG with the seed embedded.
+fn render_png(sim: &Sim, output: &Path) -> Result<(), CliError> {
+    let (rgba, seed) = render_rgba(sim)?;
+    art_engine_engines::snapshot::write_rgba_png_with_seed(
+        &rgba, sim.width, sim.height, &seed, output,
+    )?;
+    Ok(())
+}
+
+/// Simulates and colors the result, returning the RGBA8 pixels and the seed
+/// to embed with them.
+fn render_rgba(sim: &Sim) -> Result<(Vec<u8>, Seed), CliError> {
+    color_simulation(sim, simulate(sim)?)
+}
+
+/// Like [`render_rgba`], but calls `progress` with the step reached about
+/// every hundredth of the run.
+#[cfg(feature = "http")]
+fn render_rgba_reporting(
+    sim: &Sim,
+    progress: &mut dyn FnMut(usize),
+) -> Result<(Vec<u8>, Seed), CliError> {
+    let every = (sim.steps / 100).max(1);
+    let simulation = simulate_with_frames(sim, Some(every), None, None, None, |step, _, _| {
+        progress(step);
+        Ok(())
+    })?;
+    color_simulation(sim, simulation)
+}
+
+/// Colors a finished simulation of `sim`.
+fn color_simulation(sim: &Sim, simulation: Simulation) -> Result<(Vec<u8>, Seed), CliError> {
+    let Simulation {
+        field,
+        hue,
+        palette,
+        params,
+        steps,
+        ..
+    } = simulation;
+    let rgba = field_to_rgba_in(&field, &palette, hue.as_ref(), sim.color_space)?;
+    Ok((
+        rgba,
+        Seed {
+            steps,
+            ..sim.to_seed(params)
+        },
+    ))
+}
+
+/// Applies `bloom` to RGBA8 pixels on the CPU, or returns them unchanged.
+fn apply_bloom(rgba: Vec<u8>, sim: &Sim, bloom: Option<&Bloom>) -> Result<Vec<u8>, CliError> {
+    match bloom {
+        Some(bloom) => {
+            let image = Image::from_rgba8(sim.width, sim.height, &rgba)?;
+            Ok(bloom.apply(&image).to_rgba8())
+        }
+        None => Ok(rgba),
+    }
+}
+
+/// Greatest common divisor, for stepping at the rate several outputs need.
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// Resolves a `--palette` value: a built-in name, or colors separated by
+/// commas (`#001f3f,#0a9396,#94d2bd`, `navy, rgb(0 128 128), #fc0`).
+///
+/// Returns `CliError::Input` for unknown names and malformed colors.
+fn parse_palette(spec: &str) -> Result<Palette, CliError> {
+    let palette = Palette::from_name(spec.trim()).or_else(|unknown| {
+        Palette::from_colors(&split_colors(spec)).map_err(|invalid| {
+            // A lone word that is not a color is most likely a palette name
+            match spec
+                .trim()
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+            {
+                true => unknown,
+                false => invalid,
+            }
+        })
+    });
+    palette.map_err(|e| CliError::Input(e.to_string()))
+}
+
+/// Reads a `--palette-file`: colors separated by commas, spaces or
+/// newlines, with bare hex digits read as hex colors. Returns them as a
+/// `--palette` spec, so the seed embedded in the output carries the colors
+/// themselves.
+fn read_palette_file(path: &Path) -> Result<String, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+    let spec = split_colors(&text)
+        .into_iter()
+        .map(|token| match token.bytes().all(|b| b.is_ascii_hexdigit()) {
+            true => format!("#{token}"),
+            false => token.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    parse_palette(&spec).map_err(|e| CliError::Input(format!("{}: {e}", path.display())))?;
+    Ok(spec)
+}
+
+/// Parses a `WxH` size such as `1920x1080`.
+fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let (w, h) = text
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WxH, got '{text}'"))?;
+    let dim = |s: &str| {
+        s.trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("invalid dimension '{s}' in '{text}'"))
+    };
+    Ok((dim(w)?, dim(h)?))
+}
+
+/// Parses a non-negative `--until-stable` threshold.
+fn parse_epsilon(text: &str) -> Result<f64, String> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|e| e.is_finite() && *e >= 0.0)
+        .ok_or_else(|| format!("expected a non-negative number, got '{text}'"))
+}
+
+/// Terminal preview options shared by `render --preview-terminal` and `preview`.
+#[derive(Args)]
+struct PreviewArgs {
+    /// Terminal encoding for the preview.
+    #[arg(long = "preview-mode", value_enum, default_value_t = PreviewMode::Halfblock)]
+    mode: PreviewMode,
+
+    /// Preview size: columns for halfblock, longest side in pixels for sixel/kitty.
+    #[arg(long = "preview-size", default_value_t = 80)]
+    size: usize,
+}
+
 #[derive(Subcommand)]
 enum Command {
-    /// Run an engine for N steps and write a PNG snapshot.
-    Render {
+    /// Run an engine for N steps and write a PNG snapshot (SVG for `.svg` output).
+    Render(RenderArgs),
+    /// Run an engine for N steps and print the result to the terminal.
+    Preview {
+        #[command(flatten)]
+        sim: SimArgs,
+
+        #[command(flatten)]
+        preview: PreviewArgs,
+    },
+    /// List available engines and palettes.
+    List {
+        /// List the presets --preset accepts instead.
+        #[arg(long)]
+        presets: bool,
+    },
+    /// Print an engine's parameters with defaults, ranges and descriptions.
+    Schema {
         /// Engine name (e.g. "gray-scott").
         engine: String,
+    },
+    /// Compare two images: pixel difference statistics and SSIM.
+    ///
+    /// Exits with code 14 when they differ beyond --tolerance or --min-ssim,
+    /// for golden-image regression checks.
+    Compare {
+        /// Reference image.
+        a: PathBuf,
+
+        /// Image to check against the reference.
+        b: PathBuf,
+
+        /// Largest per-channel difference (0-255) still counted as a match.
+        #[arg(long, default_value_t = 0)]
+        tolerance: u8,
+
+        /// Also require at least this SSIM (0-1).
+        #[arg(long, value_name = "SSIM")]
+        min_ssim: Option<f64>,
+
+        /// Write a heat map of the per-pixel differences to this PNG.
+        #[arg(long, value_name = "PATH")]
+        diff: Option<PathBuf>,
+    },
+    /// Print the seed spec embedded in a PNG rendered by this tool.
+    Info {
+        /// PNG file to inspect.
+        path: PathBuf,
+    },
+    /// Check a seed, batch job list or project file without rendering.
+    Validate {
+        /// Seed or job list (JSON), or project (JSON, or TOML for `.toml`).
+        file: PathBuf,
+    },
+    /// Render a multi-layer project file (JSON, or TOML for `.toml`) to PNG.
+    RenderProject {
+        /// Project file path.
+        project: PathBuf,
+
+        /// Output file path.
+        #[arg(short, long, default_value = "output.png")]
+        output: PathBuf,
+
+        /// Override the project's step count.
+        #[arg(short, long)]
+        steps: Option<usize>,
+
+        /// Render at this width instead of the project's.
+        #[arg(short = 'W', long)]
+        width: Option<usize>,
+
+        /// Render at this height instead of the project's.
+        #[arg(short = 'H', long)]
+        height: Option<usize>,
+
+        /// How layer geometry adapts when --width/--height change the size.
+        #[arg(long, value_enum, default_value_t = ResizeArg::Scale)]
+        resize: ResizeArg,
+    },
+    /// Draw a field source spec (JSON, see `FieldSourceSpec`) as a PNG.
+    FieldPreview {
+        /// Field source spec file.
+        spec: PathBuf,
+
+        /// Output file path.
+        #[arg(short, long, default_value = "field.png")]
+        output: PathBuf,
 
-        /// Canvas width in pixels.
-        #[arg(short = 'W', long, default_value_t = 256)]
+        /// Image width in pixels (also the field's coordinate range).
+        #[arg(short = 'W', long, default_value_t = 512)]
         width: usize,
 
-        /// Canvas height in pixels.
-        #[arg(short = 'H', long, default_value_t = 256)]
+        /// Image height in pixels.
+        #[arg(short = 'H', long, default_value_t = 512)]
         height: usize,
 
-        /// Number of simulation steps.
-        #[arg(short, long, default_value_t = 1000)]
-        steps: usize,
+        /// How to draw the flow.
+        #[arg(long, value_enum, default_value_t = PreviewStyleArg::Streamlines)]
+        style: PreviewStyleArg,
 
-        /// PRNG seed for deterministic output.
-        #[arg(long, default_value_t = 42)]
-        seed: u64,
+        /// Time to sample the field at.
+        #[arg(long, default_value_t = 0.0)]
+        time: f64,
 
-        /// Palette name (ocean, neon, earth, monochrome, vapor, fire).
+        /// Distance between streamline seeds in pixels.
+        #[arg(long, default_value_t = 16.0)]
+        spacing: f64,
+
+        /// Trace length each way from a seed or pixel, in pixels.
+        #[arg(long, default_value_t = 20.0)]
+        length: f64,
+
+        /// Palette name or comma-separated colors for vector magnitude.
         #[arg(short, long, default_value = "ocean")]
         palette: String,
 
-        /// Output file path.
-        #[arg(short, long, default_value = "output.png")]
+        /// Seed for the line-integral convolution noise: a number or a phrase.
+        #[arg(long, default_value = "42")]
+        seed: SeedValue,
+    },
+    /// Run an engine and write a numbered PNG every N steps, plus a manifest.
+    ///
+    /// A resumed animation keeps the frames already written; pass the same
+    /// frame options as the interrupted run.
+    Animate(AnimateArgs),
+    /// Render every combination of swept parameters into a labeled contact sheet.
+    Sweep {
+        #[command(flatten)]
+        sim: SimArgs,
+
+        /// Parameter axis as name=start:end:count (repeatable; the first
+        /// axis runs across columns, the rest down rows).
+        #[arg(long = "vary", value_name = "AXIS", required = true, value_parser = sweep::parse_vary)]
+        vary: Vec<sweep::Vary>,
+
+        /// Contact sheet output path.
+        #[arg(short, long, default_value = "sweep.png")]
+        output: PathBuf,
+
+        /// Worker threads (default: available CPU cores).
+        #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+        threads: Option<u32>,
+    },
+    /// Render many random seeds as a labeled thumbnail grid for picking
+    /// favorites; also writes the candidates as a batch job list next to it.
+    Explore {
+        #[command(flatten)]
+        sim: SimArgs,
+
+        /// Number of seeds to try; `--seed` seeds the choice of candidates.
+        #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+
+        /// Randomly move each numeric parameter by up to this fraction of
+        /// its range (0 keeps `--params` as given).
+        #[arg(long, default_value_t = 0.0)]
+        jitter: f64,
+
+        /// Longest thumbnail side in pixels.
+        #[arg(long, default_value_t = 128, value_parser = clap::value_parser!(u32).range(1..))]
+        thumb_size: u32,
+
+        /// Grid output path; the job list is written beside it as `.json`.
+        #[arg(short, long, default_value = "explore.png")]
+        output: PathBuf,
+
+        /// Worker threads (default: available CPU cores).
+        #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+        threads: Option<u32>,
+    },
+    /// Render a JSON array of seed jobs in parallel.
+    Batch {
+        /// Job file: an array of seeds, each with an optional `output` path.
+        jobs: PathBuf,
+
+        /// Worker threads (default: available CPU cores).
+        #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+        threads: Option<u32>,
+
+        /// Keep rendering the remaining jobs after one fails.
+        #[arg(long)]
+        continue_on_error: bool,
+    },
+    /// Print a shell completion script (engine and palette names included).
+    Completions {
+        /// Shell to generate for.
+        shell: clap_complete::Shell,
+    },
+    /// Print the manual page, or write one page per subcommand into a directory.
+    Manpage {
+        /// Write art-engine.1 and art-engine-SUBCOMMAND.1 here instead.
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// Serve render requests as JSON lines, one response line per request,
+    /// or over HTTP with the `http` feature.
+    #[command(group(clap::ArgGroup::new("transport").required(true)))]
+    Serve {
+        /// Read requests from stdin and answer on stdout.
+        #[arg(long, group = "transport")]
+        stdio: bool,
+
+        /// Listen for HTTP on ADDR: list engines and schemas, queue Seed
+        /// jobs, stream their progress and fetch their PNGs.
+        #[cfg(feature = "http")]
+        #[arg(long, value_name = "ADDR", group = "transport")]
+        http: Option<std::net::SocketAddr>,
+
+        #[cfg(feature = "control")]
+        #[command(flatten)]
+        control: ControlArgs,
+    },
+    /// Benchmark engines at several sizes and report throughput and memory.
+    Bench {
+        /// Engines to benchmark (default: all).
+        engines: Vec<String>,
+
+        /// Grid sizes as N or WxH, comma separated.
+        #[arg(long, value_delimiter = ',', default_value = "128,256,512", value_parser = bench::parse_bench_size)]
+        sizes: Vec<(usize, usize)>,
+
+        /// Timed steps per run.
+        #[arg(short, long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+        steps: u32,
+    },
+    /// Build a static HTML gallery (thumbnails plus settings) from the PNGs in
+    /// a directory.
+    Gallery {
+        /// Directory of rendered PNGs.
+        dir: PathBuf,
+
+        /// Site output directory.
+        #[arg(short, long, default_value = "site")]
         output: PathBuf,
 
-        /// Engine parameters as a JSON string.
+        /// Page title.
+        #[arg(long, default_value = "art-engine gallery")]
+        title: String,
+
+        /// Longest thumbnail side in pixels.
+        #[arg(long, default_value_t = 320, value_parser = clap::value_parser!(u32).range(1..))]
+        thumb_size: u32,
+
+        /// Worker threads (default: available CPU cores).
+        #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+        threads: Option<u32>,
+    },
+    /// Run a project and write a numbered PNG every N steps, plus a manifest.
+    ///
+    /// Layer keyframes are evaluated at the output frame index.
+    AnimateProject {
+        /// Project file path.
+        project: PathBuf,
+
+        /// Override the project's step count.
+        #[arg(short, long)]
+        steps: Option<usize>,
+
+        #[command(flatten)]
+        frames: FrameArgs,
+    },
+    /// Re-render a recorded session (see `tui --record`) as an animation.
+    ///
+    /// Events are re-applied at the steps they were recorded at, so the
+    /// frames match what the session showed.
+    Replay {
+        /// Recording file path.
+        recording: PathBuf,
+
+        #[command(flatten)]
+        frames: FrameArgs,
+    },
+    /// Explore an engine interactively: live sliders, palettes and stepping.
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Engine name (e.g. "gray-scott").
+        engine: String,
+
+        /// Simulation width in pixels.
+        #[arg(short = 'W', long, default_value_t = 128)]
+        width: usize,
+
+        /// Simulation height in pixels.
+        #[arg(short = 'H', long, default_value_t = 128)]
+        height: usize,
+
+        /// PRNG seed for deterministic output: a number or a phrase.
+        #[arg(long, default_value = "42")]
+        seed: SeedValue,
+
+        /// Starting palette name.
+        #[arg(short, long, default_value = "ocean")]
+        palette: String,
+
+        /// Starting engine parameters as a JSON string.
         #[arg(long, default_value = "{}")]
         params: String,
+
+        /// Record parameter changes, palette switches and restarts to a
+        /// replay file on exit.
+        #[arg(long, value_name = "FILE")]
+        record: Option<PathBuf>,
+
+        #[cfg(feature = "control")]
+        #[command(flatten)]
+        control: ControlArgs,
     },
-    /// List available engines and palettes.
-    List,
+    /// Re-render a quick preview to a fixed path whenever a config changes.
+    ///
+    /// The config may be a project (JSON with a `canvas`, or TOML), a seed
+    /// (JSON with an `engine`), or bare engine params used with --engine.
+    Watch {
+        /// Config file to watch.
+        config: PathBuf,
+
+        /// Preview output path, overwritten on every render.
+        #[arg(short, long, default_value = "watch.png")]
+        output: PathBuf,
+
+        /// Engine for a bare params file.
+        #[arg(long)]
+        engine: Option<String>,
+
+        /// Palette name or comma-separated colors for a bare params file.
+        #[arg(short, long, default_value = "ocean")]
+        palette: String,
+
+        /// PRNG seed for a bare params file: a number or a phrase.
+        #[arg(long, default_value = "42")]
+        seed: SeedValue,
+
+        /// Longest preview side in pixels; larger configs are scaled down.
+        #[arg(long, default_value_t = 256, value_parser = clap::value_parser!(u32).range(1..))]
+        max_size: u32,
+
+        /// Most simulation steps per preview; bare params run exactly this many.
+        #[arg(long, default_value_t = 500)]
+        max_steps: usize,
+
+        /// Wait until the file has been unchanged this long (milliseconds).
+        #[arg(long, default_value_t = 200)]
+        debounce_ms: u64,
+
+        #[cfg(feature = "control")]
+        #[command(flatten)]
+        control: ControlArgs,
+    },
+}
+
+/// Returns `engine`'s parameter schema.
+fn param_schema(engine: &str) -> Result<serde_json::Value, CliError> {
+    // The schema does not depend on size or seed; build a tiny instance
+    let instance = EngineKind::from_name(engine, 8, 8, 0, &serde_json::json!({}))?;
+    Ok(instance.param_schema())
+}
+
+/// Frame sequence options shared by `animate` and `animate-project`.
+#[derive(Args)]
+struct FrameArgs {
+    /// Output directory for frames and manifest.json.
+    #[arg(short, long, default_value = "frames")]
+    output: PathBuf,
+
+    /// Write a frame every N simulation steps (the final step is always written).
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    every: u32,
+
+    /// Playback frame rate, recorded in the manifest and used for video.
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    fps: u32,
+
+    /// Target length in seconds; overrides --every to spread the steps over
+    /// duration x fps frames.
+    #[arg(long)]
+    duration: Option<f64>,
+
+    /// Encode straight to an .mp4 (H.264) or .webm (VP9) file with ffmpeg
+    /// instead of writing PNGs.
+    #[cfg(feature = "video")]
+    #[arg(long, value_name = "PATH")]
+    video: Option<PathBuf>,
+
+    /// Constant rate factor for --video (lower is higher quality; default
+    /// 23 for H.264, 31 for VP9).
+    #[cfg(feature = "video")]
+    #[arg(long)]
+    crf: Option<u32>,
+}
+
+impl FrameArgs {
+    /// Returns the capture interval for a run of `steps` steps.
+    fn every(&self, steps: usize) -> Result<usize, CliError> {
+        match self.duration {
+            Some(duration) => every_for_duration(steps, duration, self.fps),
+            None => Ok(self.every as usize),
+        }
+    }
+
+    /// Opens the frame destination for `expected` frames of `width x height`
+    /// encoded in `space`.
+    ///
+    /// Returns `CliError::Input` for a wide-gamut `space` with --video, which
+    /// encodes sRGB only.
+    #[cfg_attr(not(feature = "video"), allow(unused_variables))]
+    fn open(
+        &self,
+        expected: usize,
+        width: usize,
+        height: usize,
+        space: ColorSpace,
+    ) -> Result<FrameOutput, CliError> {
+        #[cfg(feature = "video")]
+        if let Some(path) = &self.video {
+            if !space.is_srgb() {
+                return Err(CliError::Input(format!(
+                    "--video encodes sRGB only; write PNG frames for --color-space {}",
+                    space.name()
+                )));
+            }
+            let encoder = video::VideoEncoder::spawn(path, width, height, self.fps, self.crf)?;
+            return Ok(FrameOutput::Video {
+                encoder,
+                path: path.clone(),
+            });
+        }
+        Ok(FrameOutput::Png(
+            FrameSequence::create(&self.output, expected)?.with_color_space(space),
+        ))
+    }
+}
+
+/// CLI mirror of [`ResizePolicy`].
+#[derive(Clone, Copy, ValueEnum)]
+enum ResizeArg {
+    /// Stretch to the new size.
+    Scale,
+    /// Scale uniformly to cover, cropping overflow.
+    Crop,
+    /// Scale uniformly to fit, extending the margins.
+    Extend,
+}
+
+impl From<ResizeArg> for ResizePolicy {
+    fn from(arg: ResizeArg) -> Self {
+        match arg {
+            ResizeArg::Scale => ResizePolicy::Scale,
+            ResizeArg::Crop => ResizePolicy::Crop,
+            ResizeArg::Extend => ResizePolicy::Extend,
+        }
+    }
+}
+
+/// CLI mirror of [`PreviewStyle`].
+#[derive(Clone, Copy, ValueEnum)]
+enum PreviewStyleArg {
+    /// Streamlines with arrowheads.
+    Streamlines,
+    /// Line-integral convolution texture.
+    Lic,
+}
+
+impl From<PreviewStyleArg> for PreviewStyle {
+    fn from(arg: PreviewStyleArg) -> Self {
+        match arg {
+            PreviewStyleArg::Streamlines => PreviewStyle::Streamlines,
+            PreviewStyleArg::Lic => PreviewStyle::Lic,
+        }
+    }
+}
+
+/// Output of a simulation run, already downsampled to the requested size.
+struct Simulation {
+    field: Field,
+    hue: Option<Field>,
+    palette: Palette,
+    params: serde_json::Value,
+    /// Simulation speed, excluding setup and any steps restored from a
+    /// checkpoint.
+    steps_per_sec: f64,
+    /// Steps the field has been through: `sim.steps`, unless the run
+    /// stopped early.
+    steps: usize,
+    /// Why `sim.until_stable` stopped the run early, if it did.
+    converged: Option<Convergence>,
+}
+
+/// Bytes per output pixel beyond the engine's: the downsampled field and
+/// its RGBA8 pixels.
+const OUTPUT_BYTES_PER_PIXEL: u64 = 8 + 4;
+
+/// Parses shared arguments, runs the engine, and downsamples the result.
+fn simulate(sim: &Sim) -> Result<Simulation, CliError> {
+    simulate_with_frames(sim, None, None, None, None, |_, _, _| Ok(()))
+}
+
+/// Like [`simulate`], but also calls `on_frame` with the downsampled field
+/// and hue field after every `frame_every` steps and after the final step,
+/// and writes a checkpoint as `checkpoint` asks (never after the final
+/// step). A resumed run starts from its checkpoint's step.
+///
+/// With `sim.until_stable`, the run ends at the step a [`StopWhenStable`]
+/// detector stops it; that step gets no extra frame, and the returned field
+/// is the field after it.
+///
+/// A `timeline`'s params at frame 0 are merged into the starting params;
+/// before each later step the keys whose sampled value changed (at the
+/// count of steps already taken) are passed to `Engine::set_params`.
+#[tracing::instrument(
+    level = "info",
+    name = "simulate",
+    skip_all,
+    fields(engine = %sim.engine, width = sim.width, height = sim.height, steps = sim.steps, seed = sim.seed.value)
+)]
+fn simulate_with_frames(
+    sim: &Sim,
+    frame_every: Option<usize>,
+    checkpoint: Option<&CheckpointPlan>,
+    timeline: Option<&ParamTimeline>,
+    mut reload: Option<&mut ParamsFile>,
+    mut on_frame: impl FnMut(usize, &Field, Option<&Field>) -> Result<(), CliError>,
+) -> Result<Simulation, CliError> {
+    let mut params: serde_json::Value = serde_json::from_str(&sim.params)
+        .map_err(|e| CliError::Input(format!("invalid --params JSON: {e}")))?;
+    let mut keyed = serde_json::Map::new();
+    if let Some(timeline) = timeline {
+        keyed = timeline.params_at(0.0);
+        params = merge_params(&params, &keyed.clone().into())?;
+    }
+    if let Some(file) = reload.as_deref() {
+        if let Some(name) = file.current().keys().find(|name| keyed.contains_key(*name)) {
+            return Err(CliError::Input(format!(
+                "--params-file sets {name}, which the timeline animates"
+            )));
+        }
+        params = merge_params(&params, &file.current().clone().into())?;
+    }
+
+    let palette = parse_palette(&sim.palette)?;
+
+    let factor = sim.oversample as usize;
+    let (sim_w, sim_h) = sim
+        .width
+        .checked_mul(factor)
+        .zip(sim.height.checked_mul(factor))
+        .ok_or_else(|| CliError::Input("--oversample overflows dimensions".into()))?;
+
+    // The engine's own allocations, plus the downsampled field and pixels
+    let needed = EngineKind::estimate_memory(&sim.engine, sim_w, sim_h, &params)?
+        .saturating_add(grid_bytes(sim.width, sim.height, OUTPUT_BYTES_PER_PIXEL));
+    sim.memory
+        .check(&format!("{} at {sim_w}x{sim_h}", sim.engine), needed)?;
+    tracing::debug!(needed, limit = %sim.memory, "memory estimate");
+    let mut eng = EngineKind::from_name_within(
+        &sim.engine,
+        sim_w,
+        sim_h,
+        sim.seed.value,
+        &params,
+        sim.memory,
+    )?;
+    let snapshot = |eng: &EngineKind| -> Result<(Field, Option<Field>), CliError> {
+        Ok((
+            eng.field().downsample(factor)?,
+            eng.hue_field()
+                .map(|h| h.downsample_hue(factor))
+                .transpose()?,
+        ))
+    };
+
+    let start = match &sim.resume {
+        Some(resume) => {
+            eng.load_state(&resume.state)?;
+            resume.step
+        }
+        None => 0,
+    };
+    if checkpoint.is_some() {
+        // Fail now rather than hours in if the engine cannot checkpoint
+        eng.save_state()?;
+    }
+    tracing::debug!(params = %params, palette = %sim.palette, oversample = factor, start, "engine ready");
+
+    let mut stable = sim.until_stable.map(StopWhenStable::new);
+    let progress_every = (sim.steps / 10).max(1);
+    let mut monitor = StepMonitor::new().with_observer(observer::from_fn(|info: &StepInfo| {
+        if info.step.is_multiple_of(progress_every) {
+            let stats = info.stats();
+            tracing::debug!(
+                step = info.step,
+                elapsed_ms = info.elapsed.as_millis() as u64,
+                step_ms = info.step_time.as_secs_f64() * 1e3,
+                min = stats.min,
+                max = stats.max,
+                mean = stats.mean,
+                "stepping"
+            );
+        }
+        ControlFlow::Continue(())
+    }));
+    if let Some(stable) = stable.as_mut() {
+        monitor = monitor.with_observer(stable);
+    }
+    monitor.start(eng.field());
+    let started = Instant::now();
+    let mut steps = sim.steps.max(start);
+    for step in start + 1..=sim.steps {
+        if let Some(timeline) = timeline {
+            let changed: serde_json::Map<_, _> = timeline
+                .params_at((step - 1) as f64)
+                .into_iter()
+                .filter(|(name, value)| keyed.get(name) != Some(value))
+                .collect();
+            if !changed.is_empty() {
+                keyed.extend(changed.clone());
+                eng.set_params(&changed.into()).map_err(|e| {
+                    CliError::Input(format!("--timeline at step {}: {e}", step - 1))
+                })?;
+            }
+        }
+        if let Some(file) = reload.as_deref_mut() {
+            if let Some(mut changed) = file.poll(step - 1) {
+                changed.retain(|name, _| {
+                    let animated = keyed.contains_key(name);
+                    if animated {
+                        tracing::warn!(param = %name, "--params-file: ignoring a param the timeline animates");
+                    }
+                    !animated
+                });
+                if !changed.is_empty() {
+                    match eng.set_params(&changed.clone().into()) {
+                        Ok(()) => {
+                            tracing::info!(step = step - 1, params = ?changed.keys().collect::<Vec<_>>(), "params reloaded");
+                            file.applied(step - 1, changed);
+                        }
+                        Err(e) => {
+                            tracing::warn!(step = step - 1, error = %e, "--params-file rejected")
+                        }
+                    }
+                }
+            }
+        }
+        eng.step()?;
+        let stop = monitor.observe(step, eng.field()).is_break();
+        match frame_every {
+            Some(n) if step % n == 0 || step == sim.steps => {
+                let (field, hue) = snapshot(&eng)?;
+                on_frame(step, &field, hue.as_ref())?;
+            }
+            _ => {}
+        }
+        if stop {
+            steps = step;
+            break;
+        }
+        if let Some(plan) = checkpoint.filter(|plan| step % plan.every == 0 && step < sim.steps) {
+            Checkpoint {
+                seed: sim.to_seed(params.clone()),
+                step,
+                state: eng.save_state()?,
+            }
+            .write(&plan.path)?;
+            tracing::debug!(step, path = %plan.path.display(), "checkpoint written");
+        }
+    }
+    drop(monitor);
+    let converged = stable.and_then(|stable| stable.outcome());
+    let seconds = started.elapsed().as_secs_f64();
+    let steps_per_sec = (steps - start) as f64 / seconds.max(1e-9);
+    tracing::info!(
+        steps = steps - start,
+        seconds,
+        steps_per_sec,
+        converged = ?converged,
+        "simulation finished"
+    );
+
+    let (field, hue) = snapshot(&eng)?;
+    if frame_every.is_some() && sim.steps == 0 {
+        on_frame(0, &field, hue.as_ref())?;
+    }
+    Ok(Simulation {
+        field,
+        hue,
+        palette,
+        params,
+        steps_per_sec,
+        steps,
+        converged,
+    })
+}
+
+/// Reads and validates a project file; `.toml` files are parsed as TOML,
+/// everything else as JSON.
+fn load_project(path: &Path) -> Result<Project, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+    parse_project(&text, has_extension(path, "toml"))
+        .map_err(|e| CliError::Input(format!("{}: {e}", path.display())))
+}
+
+/// Reads a `--timeline` file, checking its palettes up front.
+fn load_timeline(path: &Path) -> Result<ParamTimeline, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+    let timeline: ParamTimeline = serde_json::from_str(&text)
+        .map_err(|e| CliError::Input(format!("{}: invalid timeline: {e}", path.display())))?;
+    for key in timeline.palette.iter().flat_map(|t| t.keys()) {
+        parse_palette(&key.value)
+            .map_err(|e| CliError::Input(format!("{}: {e}", path.display())))?;
+    }
+    Ok(timeline)
+}
+
+/// Reads a field source spec file.
+fn load_field_source(path: &Path) -> Result<FieldSourceSpec, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+    serde_json::from_str(&text).map_err(|e| CliError::Input(format!("{}: {e}", path.display())))
+}
+
+/// Parses and validates project text as TOML or JSON.
+fn parse_project(text: &str, toml: bool) -> Result<Project, String> {
+    let project: Project = if toml {
+        toml::from_str(text).map_err(|e| e.to_string())?
+    } else {
+        serde_json::from_str(text).map_err(|e| e.to_string())?
+    };
+    project.validate().map_err(|e| e.to_string())?;
+    Ok(project)
+}
+
+/// Opens the `--raw-output` destination (`-` for stdout).
+fn open_raw_output(path: &Path) -> Result<Box<dyn Write>, CliError> {
+    if path == Path::new("-") {
+        Ok(Box::new(io::stdout().lock()))
+    } else {
+        let file =
+            File::create(path).map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        Ok(Box::new(BufWriter::new(file)))
+    }
 }
 
 fn run(cli: Cli) -> Result<(), CliError> {
+    let memory = cli.memory_limit.unwrap_or_default();
     match cli.command {
-        Command::List => {
+        Command::List { presets: true } => {
+            let presets = Config::load(cli.config.as_deref())?.list_presets()?;
+            if cli.json {
+                let info: Vec<_> = presets
+                    .iter()
+                    .map(|(name, source, preset)| {
+                        serde_json::json!({
+                            "name": name,
+                            "source": source.name(),
+                            "preset": preset,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            } else {
+                println!("Presets:");
+                for (name, source, preset) in &presets {
+                    let detail = match preset {
+                        Some(preset) => preset
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| preset.params.to_string()),
+                        None => "canvas size".into(),
+                    };
+                    println!("  {name:<24} {:<8} {detail}", source.name());
+                }
+            }
+        }
+        Command::List { presets: false } => {
             let engines = EngineKind::list_engines();
             let palettes = Palette::list_names();
             if cli.json {
@@ -84,54 +1337,479 @@ fn run(cli: Cli) -> Result<(), CliError> {
                 println!("  {}", palettes.join(", "));
             }
         }
-        Command::Render {
-            engine,
+        Command::Render(args) => render::run(args, memory, cli.json)?,
+        Command::Preview { sim, preview } => {
+            let sim = sim.resolve(memory)?;
+            let Simulation {
+                field,
+                hue,
+                palette,
+                ..
+            } = simulate(&sim)?;
+            let text = render_preview(&field, hue.as_ref(), &palette, preview.mode, preview.size)?;
+            print!("{text}");
+        }
+        Command::Schema { engine } => {
+            let schema = param_schema(&engine)?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&schema)?);
+            } else {
+                print!("{}", schema_table(&schema));
+            }
+        }
+        Command::Compare {
+            a,
+            b,
+            tolerance,
+            min_ssim,
+            diff,
+        } => compare::run(&a, &b, tolerance, min_ssim, diff.as_deref(), cli.json)?,
+        Command::Info { path } => {
+            let spec = art_engine_engines::snapshot::read_png_seed(&path)?.ok_or_else(|| {
+                CliError::Input(format!("no art-engine seed in {}", path.display()))
+            })?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&spec)?);
+            } else {
+                println!("engine:  {}", spec.engine);
+                println!("size:    {}x{}", spec.width, spec.height);
+                println!("steps:   {}", spec.steps);
+                println!("seed:    {}", spec.seed_value());
+                if let Some(palette) = &spec.palette {
+                    println!("palette: {palette}");
+                }
+                if !spec.color_space.is_srgb() {
+                    println!("colors:  {}", spec.color_space.name());
+                }
+                if let Some(version) = &spec.version {
+                    println!("version: {version}");
+                }
+                println!("params:  {}", spec.params);
+            }
+        }
+        Command::Validate { file } => validate::run(&file, cli.json)?,
+        Command::RenderProject {
+            project,
+            output,
+            steps,
             width,
             height,
-            steps,
-            seed,
-            palette,
-            output,
-            params,
+            resize,
         } => {
-            let params: serde_json::Value = serde_json::from_str(&params)
-                .map_err(|e| CliError::Input(format!("invalid --params JSON: {e}")))?;
-
-            let palette =
-                Palette::from_name(&palette).map_err(|e| CliError::Input(e.to_string()))?;
-
-            let mut eng = EngineKind::from_name(&engine, width, height, seed, &params)?;
-
-            (0..steps).try_for_each(|_| eng.step())?;
-
-            art_engine_engines::snapshot::write_png(eng.field(), &palette, &output)?;
+            let mut spec = load_project(&project)?;
+            let steps = steps.unwrap_or(spec.steps);
+            if width.is_some() || height.is_some() {
+                let w = width.unwrap_or(spec.canvas.width());
+                let h = height.unwrap_or(spec.canvas.height());
+                spec.canvas.resize(w, h, resize.into())?;
+            }
+            let mut comp = Composition::from_project(&spec)?;
+            (0..steps).try_for_each(|_| comp.step())?;
+            let image = comp.render()?;
+            let (width, height) = (image.width(), image.height());
+            art_engine_engines::snapshot::write_rgba_png(image.to_rgba8(), width, height, &output)?;
 
             if cli.json {
                 let info = serde_json::json!({
-                    "engine": engine,
+                    "project": project.display().to_string(),
                     "width": width,
                     "height": height,
+                    "layers": spec.canvas.layer_count(),
+                    "engines": comp.engine_count(),
                     "steps": steps,
-                    "seed": seed,
+                    "seed": spec.seed,
+                    "output": output.display().to_string(),
+                });
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            } else {
+                eprintln!(
+                    "rendered {} ({width}x{height}, {} layers, {steps} steps, seed {}) -> {}",
+                    project.display(),
+                    spec.canvas.layer_count(),
+                    spec.seed,
+                    output.display()
+                );
+            }
+        }
+        Command::FieldPreview {
+            spec,
+            output,
+            width,
+            height,
+            style,
+            time,
+            spacing,
+            length,
+            palette,
+            seed,
+        } => {
+            let source = load_field_source(&spec)?.build();
+            let preview = FieldPreview::new(style.into())
+                .with_time(time)
+                .with_spacing(spacing)
+                .with_length(length)
+                .with_seed(seed.value)
+                .with_palette(parse_palette(&palette)?);
+            let image = preview.render(source.as_ref(), width, height)?;
+            art_engine_engines::snapshot::write_rgba_png(image.to_rgba8(), width, height, &output)?;
+
+            if cli.json {
+                let info = serde_json::json!({
+                    "spec": spec.display().to_string(),
+                    "width": width,
+                    "height": height,
+                    "style": PreviewStyle::from(style),
+                    "time": time,
                     "output": output.display().to_string(),
                 });
                 println!("{}", serde_json::to_string_pretty(&info)?);
             } else {
                 eprintln!(
-                    "rendered {engine} ({width}x{height}, {steps} steps, seed {seed}) -> {}",
+                    "drew {} ({width}x{height}) -> {}",
+                    spec.display(),
                     output.display()
                 );
             }
         }
+        Command::Animate(args) => animate::run(args, memory, cli.json)?,
+        Command::Sweep {
+            sim,
+            vary,
+            output,
+            threads,
+        } => sweep::run(&sim.resolve(memory)?, &vary, &output, threads, cli.json)?,
+        Command::Explore {
+            sim,
+            count,
+            jitter,
+            thumb_size,
+            output,
+            threads,
+        } => explore::run(
+            &sim.resolve(memory)?,
+            count,
+            jitter,
+            thumb_size,
+            &output,
+            threads,
+            cli.json,
+        )?,
+        Command::Batch {
+            jobs,
+            threads,
+            continue_on_error,
+        } => batch::run(&jobs, threads, continue_on_error, memory, cli.json)?,
+        #[cfg(feature = "tui")]
+        Command::Tui {
+            engine,
+            width,
+            height,
+            seed,
+            palette,
+            params,
+            record,
+            #[cfg(feature = "control")]
+            control,
+        } => {
+            let params: serde_json::Value = serde_json::from_str(&params)
+                .map_err(|e| CliError::Input(format!("invalid --params JSON: {e}")))?;
+            let app = tui::App::new(&engine, width, height, seed.value, params, &palette)?;
+            #[cfg(feature = "control")]
+            let app = match control.listen()? {
+                Some(control) => app.with_control(control),
+                None => app,
+            };
+            tui::run(match record {
+                Some(path) => app.with_recording(path),
+                None => app,
+            })?;
+        }
+        Command::Watch {
+            config,
+            output,
+            engine,
+            palette,
+            seed,
+            max_size,
+            max_steps,
+            debounce_ms,
+            #[cfg(feature = "control")]
+            control,
+        } => {
+            let options = WatchOptions {
+                engine,
+                palette,
+                seed,
+                max_size: max_size as usize,
+                max_steps,
+                #[cfg(feature = "control")]
+                control: control.listen()?,
+            };
+            let debounce = Duration::from_millis(debounce_ms);
+            watch::run(&config, &output, &options, debounce, cli.json);
+        }
+        Command::Completions { shell } => {
+            completions::write_completions(Cli::command(), shell, &mut io::stdout().lock());
+        }
+        Command::Manpage { dir } => match dir {
+            Some(dir) => {
+                completions::write_manpages(Cli::command(), &dir)?;
+                if !cli.json {
+                    eprintln!("wrote manual pages to {}", dir.display());
+                }
+            }
+            None => completions::write_manpage(Cli::command(), &mut io::stdout().lock())?,
+        },
+        Command::Serve {
+            stdio: _,
+            #[cfg(feature = "http")]
+            http,
+            #[cfg(feature = "control")]
+            control,
+        } => serve::run(
+            ServeOptions {
+                #[cfg(feature = "http")]
+                http,
+                #[cfg(feature = "control")]
+                control: control.listen()?,
+                memory,
+            },
+            cli.json,
+        )?,
+        Command::Bench {
+            engines,
+            sizes,
+            steps,
+        } => bench::run(engines, &sizes, steps as usize, cli.json)?,
+        Command::Gallery {
+            dir,
+            output,
+            title,
+            thumb_size,
+            threads,
+        } => gallery::run(&dir, &output, &title, thumb_size, threads, cli.json)?,
+        Command::AnimateProject {
+            project,
+            steps,
+            frames,
+        } => animate::run_project(&project, steps, &frames, cli.json)?,
+        Command::Replay { recording, frames } => {
+            replay::run(&recording, &frames, memory, cli.json)?
+        }
     }
 
     Ok(())
 }
 
+/// Formats a `param_schema()` object as an aligned text table.
+fn schema_table(schema: &serde_json::Value) -> String {
+    let field = |spec: &serde_json::Value, key: &str| match spec.get(key) {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+        None => "-".to_string(),
+    };
+    let rows: Vec<[String; 5]> = schema
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, spec)| {
+            let range = match (spec.get("min"), spec.get("max")) {
+                (None, None) => "-".to_string(),
+                _ => format!("{}..{}", field(spec, "min"), field(spec, "max")),
+            };
+            [
+                name.clone(),
+                field(spec, "type"),
+                field(spec, "default"),
+                range,
+                field(spec, "description"),
+            ]
+        })
+        .collect();
+    let header = ["NAME", "TYPE", "DEFAULT", "RANGE", "DESCRIPTION"].map(String::from);
+    let widths: Vec<usize> = (0..4)
+        .map(|i| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|r| r[i].len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|r| {
+            let cells: String = (0..4)
+                .map(|i| format!("{:<w$}  ", r[i], w = widths[i]))
+                .collect();
+            format!("{cells}{}\n", r[4])
+        })
+        .collect()
+}
+
+/// Prints the summary of a finished animation; `output` is the manifest or
+/// video file.
+fn report_frames(
+    json: bool,
+    source: &str,
+    frame_count: usize,
+    output: &Path,
+) -> Result<(), CliError> {
+    if json {
+        let info = serde_json::json!({
+            "source": source,
+            "frames": frame_count,
+            "output": output.display().to_string(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        eprintln!(
+            "animated {source} ({frame_count} frames) -> {}",
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+/// Returns true if the output path has an `.svg` extension (case insensitive).
+fn is_svg_path(path: &Path) -> bool {
+    has_extension(path, "svg")
+}
+
+/// Returns true if `path` has extension `ext` (case insensitive).
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Applies the configuration file underneath the flags the user did not
+/// give explicitly (see [`config`]).
+fn configure(mut cli: Cli, matches: &ArgMatches) -> Result<Cli, CliError> {
+    let config = Config::load(cli.config.as_deref())?;
+    cli.memory_limit = cli.memory_limit.or(config.memory_limit);
+    let Some((_, matches)) = matches.subcommand() else {
+        return Ok(cli);
+    };
+    let defaulted = |id: &str| matches.value_source(id) == Some(ValueSource::DefaultValue);
+    let (sim, output) = match &mut cli.command {
+        Command::Render(RenderArgs { sim, output, .. })
+        | Command::Sweep { sim, output, .. }
+        | Command::Explore { sim, output, .. } => (Some(sim), Some(output)),
+        Command::Animate(AnimateArgs { sim, frames, .. }) => (Some(sim), Some(&mut frames.output)),
+        Command::Preview { sim, .. } => (Some(sim), None),
+        Command::RenderProject { output, .. } | Command::FieldPreview { output, .. } => {
+            (None, Some(output))
+        }
+        Command::AnimateProject { frames, .. } | Command::Replay { frames, .. } => {
+            (None, Some(&mut frames.output))
+        }
+        _ => (None, None),
+    };
+    if let (Some(output), Some(dir)) = (output, &config.output_dir) {
+        if defaulted("output") {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| CliError::Io(format!("{}: {e}", dir.display())))?;
+            *output = dir.join(&*output);
+        }
+    }
+    if let Some(sim) = sim {
+        let mut palette_set = !defaulted("palette") || sim.palette_file.is_some();
+        match sim
+            .preset
+            .take()
+            .map(|name| config.preset(&name).map(|found| (name, found)))
+        {
+            Some(Ok((_, PresetRef::Size(width, height)))) => {
+                if defaulted("width") {
+                    sim.width = width;
+                }
+                if defaulted("height") {
+                    sim.height = height;
+                }
+            }
+            Some(Ok((name, PresetRef::Seed(preset)))) => {
+                match &sim.engine {
+                    Some(engine) if *engine != preset.engine => {
+                        return Err(CliError::Input(format!(
+                            "preset '{name}' is for {}, not {engine}",
+                            preset.engine
+                        )));
+                    }
+                    _ => sim.engine = Some(preset.engine.clone()),
+                }
+                let fill = |value: &mut usize, id: &str, preset: Option<usize>| {
+                    if let Some(preset) = preset.filter(|_| defaulted(id)) {
+                        *value = preset;
+                    }
+                };
+                fill(&mut sim.width, "width", preset.width);
+                fill(&mut sim.height, "height", preset.height);
+                fill(&mut sim.steps, "steps", preset.steps);
+                if let Some(palette) = preset.palette.as_ref().filter(|_| !palette_set) {
+                    sim.palette = palette.clone();
+                    palette_set = true;
+                }
+                if let serde_json::Value::Object(params) = &preset.params {
+                    sim.params = config::merge_params(params, &sim.params)?;
+                }
+                tracing::debug!(preset = %name, "applied preset");
+            }
+            Some(Err(err)) => return Err(err),
+            None => {}
+        }
+        if sim.engine.is_none() && sim.seed_file.is_none() && sim.resume.is_none() {
+            return Err(CliError::Input(
+                "a canvas size preset needs an engine; name one".into(),
+            ));
+        }
+        // Seed files and checkpoints are reproduced as recorded
+        if let Some(engine) = &sim.engine {
+            if let Some(palette) = config.palette.as_ref().filter(|_| !palette_set) {
+                sim.palette = palette.clone();
+            }
+            sim.params = config.params(engine, &sim.params)?;
+            tracing::debug!(palette = %sim.palette, params = %sim.params, "applied configuration defaults");
+        }
+    }
+    Ok(cli)
+}
+
+/// Returns the most verbose level logged for `-v` repeated `verbose` times;
+/// `--trace` logs everything.
+fn log_level(verbose: u8, trace: bool) -> LevelFilter {
+    match (trace, verbose) {
+        (true, _) => LevelFilter::TRACE,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    }
+}
+
+/// Installs the stderr log subscriber: readable lines normally, JSON lines
+/// with span timings (logged when each span closes) under `--trace`.
+fn init_logging(verbose: u8, trace: bool) {
+    let builder = tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_ansi(io::IsTerminal::is_terminal(&io::stderr()))
+        .with_max_level(log_level(verbose, trace));
+    if trace {
+        builder
+            .json()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_current_span(true)
+            .init();
+    } else {
+        builder.init();
+    }
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    init_logging(cli.verbose, cli.trace);
     let json_mode = cli.json;
-    if let Err(e) = run(cli) {
+    if let Err(e) = configure(cli, &matches).and_then(run) {
         if json_mode {
             let j = serde_json::json!({"error": e.to_string(), "exit_code": e.exit_code()});
             eprintln!("{}", serde_json::to_string_pretty(&j).unwrap_or_default());
@@ -141,3 +1819,811 @@ fn main() {
         process::exit(e.exit_code());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_svg_path_matches_extension_case_insensitively() {
+        assert!(is_svg_path(Path::new("out.svg")));
+        assert!(is_svg_path(Path::new("dir/OUT.SVG")));
+        assert!(!is_svg_path(Path::new("out.png")));
+        assert!(!is_svg_path(Path::new("svg")));
+    }
+
+    #[test]
+    fn schema_table_aligns_columns_and_fills_gaps() {
+        let schema = serde_json::json!({
+            "rate": {"type": "number", "default": 0.5, "min": 0.0, "max": 1.0,
+                     "description": "How fast"},
+            "mode": {"type": "string", "default": "fast"},
+        });
+        let table = schema_table(&schema);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("NAME  TYPE"));
+        assert_eq!(lines[1].trim_end(), "mode  string  fast     -         -");
+        assert_eq!(lines[2], "rate  number  0.5      0.0..1.0  How fast");
+    }
+
+    #[test]
+    fn parse_size_accepts_wxh() {
+        assert_eq!(parse_size("1920x1080"), Ok((1920, 1080)));
+        assert_eq!(parse_size("64X32"), Ok((64, 32)));
+        assert!(parse_size("64").is_err());
+        assert!(parse_size("0x10").is_err());
+        assert!(parse_size("ax10").is_err());
+    }
+
+    #[test]
+    fn verbosity_flags_raise_the_log_level() {
+        assert_eq!(log_level(0, false), LevelFilter::WARN);
+        assert_eq!(log_level(1, false), LevelFilter::INFO);
+        assert_eq!(log_level(2, false), LevelFilter::DEBUG);
+        assert_eq!(log_level(5, false), LevelFilter::TRACE);
+        assert_eq!(log_level(0, true), LevelFilter::TRACE);
+        let cli = Cli::try_parse_from(["art-engine", "render", "gray-scott", "-vv"]).unwrap();
+        assert_eq!((cli.verbose, cli.trace), (2, false));
+    }
+
+    #[test]
+    fn palettes_are_names_or_inline_color_lists() {
+        assert_eq!(parse_palette("fire").unwrap().len(), Palette::fire().len());
+        assert_eq!(parse_palette("#001f3f, 0a9396,#94d2bd").unwrap().len(), 3);
+        assert_eq!(parse_palette("#ff0000").unwrap().len(), 1);
+        assert!(matches!(parse_palette("sunset"), Err(CliError::Input(_))));
+        assert_eq!(parse_palette("red").unwrap().len(), 1);
+        assert_eq!(
+            parse_palette("navy, rgb(0, 128, 128), #fc0").unwrap().len(),
+            3
+        );
+        let err = parse_palette("sunset").unwrap_err().to_string();
+        assert!(err.contains("sunset"), "{err}");
+        assert!(matches!(
+            parse_palette("#ff0000,#zz0000"),
+            Err(CliError::Input(_))
+        ));
+
+        let path = std::env::temp_dir().join(format!("art-engine-pal-{}.txt", std::process::id()));
+        std::fs::write(&path, "#001f3f 0a9396\n#94d2bd,\n").unwrap();
+        assert_eq!(read_palette_file(&path).unwrap(), "#001f3f,#0a9396,#94d2bd");
+        std::fs::write(&path, "fc0\nrgb(0, 128, 128) teal\n").unwrap();
+        assert_eq!(
+            read_palette_file(&path).unwrap(),
+            "#fc0,rgb(0, 128, 128),teal"
+        );
+        std::fs::write(&path, "\n").unwrap();
+        assert!(read_palette_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn config_fills_in_only_what_flags_leave_at_default() {
+        let dir = std::env::temp_dir().join(format!("art-engine-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let out = dir.join("out");
+        std::fs::write(
+            &path,
+            format!(
+                "palette = \"fire\"\noutput_dir = {:?}\n[presets]\nwide = \"64x32\"\n\
+                 [engines.gray-scott]\nfeed_rate = 0.03\n",
+                out.display().to_string()
+            ),
+        )
+        .unwrap();
+        let parse = |args: &[&str]| {
+            let mut full = vec!["art-engine", "--config", path.to_str().unwrap()];
+            full.extend_from_slice(args);
+            let matches = Cli::command().try_get_matches_from(full).unwrap();
+            configure(Cli::from_arg_matches(&matches).unwrap(), &matches)
+        };
+
+        let cli = parse(&["render", "gray-scott", "--preset", "wide"]).unwrap();
+        let Command::Render(RenderArgs { sim, output, .. }) = cli.command else {
+            panic!("expected render");
+        };
+        assert_eq!((sim.width, sim.height), (64, 32));
+        assert_eq!(sim.palette, "fire");
+        assert_eq!(sim.params, r#"{"feed_rate":0.03}"#);
+        assert_eq!(output, out.join("output.png"));
+        assert!(out.is_dir());
+
+        let cli = parse(&[
+            "render",
+            "gray-scott",
+            "-p",
+            "neon",
+            "-o",
+            "x.png",
+            "--params",
+            r#"{"feed_rate": 0.05}"#,
+        ])
+        .unwrap();
+        let Command::Render(RenderArgs { sim, output, .. }) = cli.command else {
+            panic!("expected render");
+        };
+        assert_eq!((sim.width, sim.height), (256, 256));
+        assert_eq!(sim.palette, "neon");
+        assert_eq!(sim.params, r#"{"feed_rate":0.05}"#);
+        assert_eq!(output, PathBuf::from("x.png"));
+
+        let cli = parse(&["render", "--seed-file", "s.json"]).unwrap();
+        let Command::Render(RenderArgs { sim, .. }) = cli.command else {
+            panic!("expected render");
+        };
+        assert_eq!((sim.palette.as_str(), sim.params.as_str()), ("ocean", "{}"));
+        assert!(parse(&["preview", "gray-scott", "--preset", "tall"]).is_err());
+        assert!(parse(&["preview", "--preset", "wide"]).is_err());
+        let cli = parse(&["render", "gray-scott", "--preset", "wide", "-W", "8"]).unwrap();
+        let Command::Render(RenderArgs { sim, .. }) = cli.command else {
+            panic!("expected render");
+        };
+        assert_eq!((sim.width, sim.height), (8, 32));
+
+        // Engine presets: config defaults < preset < explicit flags
+        let cli = parse(&[
+            "render",
+            "--preset",
+            "gray-scott/mitosis",
+            "--params",
+            r#"{"kill_rate": 0.06}"#,
+        ])
+        .unwrap();
+        let Command::Render(RenderArgs { sim, .. }) = cli.command else {
+            panic!("expected render");
+        };
+        assert_eq!(sim.engine.as_deref(), Some("gray-scott"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&sim.params).unwrap(),
+            serde_json::json!({"feed_rate": 0.0367, "kill_rate": 0.06})
+        );
+        assert!(parse(&["render", "dla", "--preset", "gray-scott/mitosis"]).is_err());
+        assert!(parse(&["render", "--preset", "gray-scott/nope"]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn seed_file_conflicts_with_explicit_engine_settings() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args.iter().copied());
+        assert!(parse(&["art-engine", "render", "--seed-file", "s.json"]).is_ok());
+        assert!(parse(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--seed-file",
+            "s.json"
+        ])
+        .is_err());
+        assert!(parse(&["art-engine", "render", "--seed-file", "s.json", "-W", "9"]).is_err());
+        assert!(parse(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--override-size",
+            "9x9"
+        ])
+        .is_err());
+        assert!(parse(&["art-engine", "render"]).is_err());
+    }
+
+    #[test]
+    fn resume_takes_every_setting_from_the_checkpoint() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args.iter().copied());
+        assert!(parse(&["art-engine", "animate", "--resume", "c.bin"]).is_ok());
+        for conflicting in [
+            &["gray-scott"][..],
+            &["--seed-file", "s.json"],
+            &["--oversample", "2"],
+            &["-s", "9"],
+            &["--override-size", "9x9"],
+        ] {
+            let mut args = vec!["art-engine", "render", "--resume", "c.bin"];
+            args.extend_from_slice(conflicting);
+            assert!(parse(&args).is_err(), "{conflicting:?}");
+        }
+        assert!(parse(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--checkpoint-file",
+            "c"
+        ])
+        .is_err());
+        assert!(parse(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--checkpoint-every",
+            "50"
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn snapshots_are_numbered_unless_latest_is_requested() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args.iter().copied());
+        let snapshots = |args: &[&str]| match parse(args).unwrap().command {
+            Command::Render(RenderArgs { snapshots, .. }) => snapshots,
+            _ => panic!("expected render"),
+        };
+        let numbered = snapshots(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--snapshot-every",
+            "50",
+        ]);
+        assert_eq!(numbered.snapshot_every, Some(50));
+        assert_eq!(numbered.path(150), Path::new("snapshots/step_000150.png"));
+        let latest = snapshots(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--snapshot-every",
+            "50",
+            "--snapshot-latest",
+            "--snapshot-dir",
+            "out",
+        ]);
+        assert_eq!(latest.path(150), Path::new("out/latest.png"));
+        assert!(parse(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--snapshot-every",
+            "0"
+        ])
+        .is_err());
+        assert!(parse(&["art-engine", "render", "gray-scott", "--snapshot-latest"]).is_err());
+        assert!(parse(&["art-engine", "render", "gray-scott", "--snapshot-dir", "d"]).is_err());
+        assert_eq!(gcd(10, 4), 2);
+        assert_eq!(gcd(7, 7), 7);
+    }
+
+    #[test]
+    fn bloom_settings_need_an_intensity() {
+        let parse = |args: &[&str]| Cli::try_parse_from(args.iter().copied());
+        let bloom = |args: &[&str]| match parse(args).unwrap().command {
+            Command::Render(RenderArgs { bloom, .. }) => bloom.resolve(),
+            _ => panic!("expected render"),
+        };
+        assert_eq!(bloom(&["art-engine", "render", "gray-scott"]), None);
+        assert_eq!(
+            bloom(&[
+                "art-engine",
+                "render",
+                "gray-scott",
+                "--bloom",
+                "0.8",
+                "--bloom-radius",
+                "0.1",
+            ]),
+            Some(Bloom::new(0.8).with_radius(0.1))
+        );
+        assert!(parse(&[
+            "art-engine",
+            "render",
+            "gray-scott",
+            "--bloom-threshold",
+            "0.5"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn seed_phrases_resolve_and_reach_the_embedded_seed() {
+        let sim =
+            match Cli::try_parse_from(["art-engine", "render", "dla", "--seed", "crimson-tide-42"])
+            {
+                Ok(Cli {
+                    command: Command::Render(RenderArgs { sim, .. }),
+                    ..
+                }) => sim.resolve(MemoryBudget::default()).unwrap(),
+                _ => panic!("expected render"),
+            };
+        assert_eq!(sim.seed, SeedValue::phrase("crimson-tide-42"));
+        let spec = sim.to_seed(serde_json::json!({}));
+        assert_eq!(spec.seed_value(), sim.seed);
+        assert!(spec.validate().is_ok());
+        assert!(Cli::try_parse_from(["art-engine", "render", "dla", "--seed", ""]).is_err());
+    }
+
+    #[test]
+    fn memory_limit_refuses_oversized_renders_before_simulating() {
+        let sim = |args: &[&str]| match Cli::try_parse_from(args.iter().copied()) {
+            Ok(Cli {
+                command: Command::Render(RenderArgs { sim, .. }),
+                memory_limit,
+                ..
+            }) => sim.resolve(memory_limit.unwrap_or_default()).unwrap(),
+            _ => panic!("expected render"),
+        };
+        let base = ["art-engine", "render", "gray-scott", "-W", "64", "-H", "64"];
+        let small = sim(&[&base[..], &["--steps", "1", "--memory-limit", "1M"]].concat());
+        assert_eq!(small.memory, MemoryBudget::new(1 << 20));
+        assert!(simulate(&small).is_ok());
+        // Oversampling grows the engine, not the output
+        let oversampled =
+            sim(&[&base[..], &["--memory-limit", "1M", "--oversample", "4"]].concat());
+        let err = simulate(&oversampled).err().unwrap();
+        assert!(
+            matches!(
+                err,
+                CliError::Engine(art_engine_core::EngineError::OverMemoryBudget(_))
+            ),
+            "{err}"
+        );
+        assert!(err.to_string().contains("256x256"), "{err}");
+        assert!(Cli::try_parse_from([&base[..], &["--memory-limit", "lots"]].concat()).is_err());
+    }
+
+    #[test]
+    fn resolve_loads_seed_and_applies_override_size() {
+        let path = std::env::temp_dir().join(format!("art-engine-seed-{}.json", process::id()));
+        let mut spec = Seed::new("gray-scott", 64, 32, 5);
+        spec.steps = 12;
+        spec.params = serde_json::json!({"feed_rate": 0.04});
+        std::fs::write(&path, serde_json::to_string(&spec).unwrap()).unwrap();
+        let arg = path.to_str().unwrap();
+
+        let resolve = |args: &[&str]| match Cli::try_parse_from(args.iter().copied()) {
+            Ok(Cli {
+                command: Command::Render(RenderArgs { sim, .. }),
+                ..
+            }) => sim.resolve(MemoryBudget::default()),
+            _ => panic!("expected render"),
+        };
+        let sim = resolve(&["art-engine", "render", "--seed-file", arg]).unwrap();
+        assert_eq!(
+            (sim.engine.as_str(), sim.width, sim.height),
+            ("gray-scott", 64, 32)
+        );
+        assert_eq!(
+            (sim.steps, sim.seed.value, sim.palette.as_str()),
+            (12, 5, "ocean")
+        );
+        assert_eq!(sim.params, r#"{"feed_rate":0.04}"#);
+
+        let sim = resolve(&[
+            "art-engine",
+            "render",
+            "--seed-file",
+            arg,
+            "--override-size",
+            "128x64",
+        ]);
+        assert_eq!(sim.map(|s| (s.width, s.height)).unwrap(), (128, 64));
+        let sim = resolve(&[
+            "art-engine",
+            "render",
+            "--seed-file",
+            arg,
+            "--color-space",
+            "display-p3",
+        ])
+        .unwrap();
+        assert_eq!(sim.color_space, ColorSpace::DisplayP3);
+        assert_eq!(
+            sim.to_seed(serde_json::json!({})).color_space,
+            ColorSpace::DisplayP3
+        );
+        assert!(
+            Cli::try_parse_from(["art-engine", "render", "dla", "--color-space", "cmyk"]).is_err()
+        );
+        std::fs::write(
+            &path,
+            r#"{"engine":"x","width":0,"height":1,"params":{},"seed":1,"steps":0}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            resolve(&["art-engine", "render", "--seed-file", arg]),
+            Err(CliError::Engine(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn toml_and_json_projects_parse_to_the_same_project() {
+        let json = r##"{
+            "version": 1,
+            "seed": 9,
+            "steps": 20,
+            "palettes": {"duo": ["#000000", "#ffffff"]},
+            "canvas": {
+                "width": 8, "height": 8, "background": "#101010",
+                "layers": [{
+                    "name": "rd", "blend_mode": "screen", "opacity": 1.0,
+                    "visible": true, "content_type": "field",
+                    "content": {"kind": "engine", "engine": "gray-scott", "seed": 1,
+                                "palette": "duo", "params": {"feed_rate": 0.05}},
+                    "mask": {"kind": "circle", "cx": 0.5, "cy": 0.5, "radius": 0.4}
+                }]
+            }
+        }"##;
+        let toml = r##"
+            version = 1
+            seed = 9
+            steps = 20
+
+            [palettes]
+            duo = ["#000000", "#ffffff"]
+
+            [canvas]
+            width = 8
+            height = 8
+            background = "#101010"
+
+            [[canvas.layers]]
+            name = "rd"
+            blend_mode = "screen"
+            opacity = 1.0
+            visible = true
+            content_type = "field"
+            content = { kind = "engine", engine = "gray-scott", seed = 1, palette = "duo", params = { feed_rate = 0.05 } }
+            mask = { kind = "circle", cx = 0.5, cy = 0.5, radius = 0.4 }
+        "##;
+        let from_json = parse_project(json, false).unwrap();
+        let from_toml = parse_project(toml, true).unwrap();
+        assert_eq!(from_json, from_toml);
+        assert_eq!(from_json.canvas.layer_count(), 1);
+    }
+
+    #[test]
+    fn parse_project_reports_validation_errors() {
+        let err = parse_project(
+            r##"{"version": 99, "canvas": {"width": 1, "height": 1, "background": "#000000", "layers": []}}"##,
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("unsupported version 99"));
+    }
+
+    #[test]
+    fn field_source_specs_load_from_json() {
+        let path =
+            std::env::temp_dir().join(format!("art-engine-field-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"kind": "curl", "scale": 0.02, "seed": 3}"#).unwrap();
+        let spec = load_field_source(&path).unwrap();
+        assert!(matches!(spec, FieldSourceSpec::Curl { seed: 3, .. }));
+        std::fs::write(&path, r#"{"kind": "spiral"}"#).unwrap();
+        assert!(matches!(load_field_source(&path), Err(CliError::Input(_))));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(load_field_source(&path), Err(CliError::Io(_))));
+    }
+
+    #[test]
+    fn timelines_load_and_check_their_palettes() {
+        let path = std::env::temp_dir().join(format!("art-engine-timeline-{}.json", process::id()));
+        std::fs::write(
+            &path,
+            r#"{"params": {"feed_rate": [{"frame": 0, "value": 0.03}]},
+                "palette": [{"frame": 0, "value": "ocean"}, {"frame": 5, "value": "navy,white"}]}"#,
+        )
+        .unwrap();
+        let timeline = load_timeline(&path).unwrap();
+        assert_eq!(timeline.palette_at(6.0).as_deref(), Some("navy,white"));
+        std::fs::write(&path, r#"{"palette": [{"frame": 0, "value": "plaid"}]}"#).unwrap();
+        assert!(matches!(load_timeline(&path), Err(CliError::Input(_))));
+        std::fs::write(&path, r#"{"params": {"feed_rate": 0.03}}"#).unwrap();
+        assert!(matches!(load_timeline(&path), Err(CliError::Input(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn timelines_steer_params_as_the_simulation_runs() {
+        let sim = match Cli::try_parse_from([
+            "art-engine",
+            "animate",
+            "gray-scott",
+            "-W",
+            "24",
+            "-H",
+            "24",
+            "--steps",
+            "30",
+        ]) {
+            Ok(Cli {
+                command: Command::Animate(AnimateArgs { sim, .. }),
+                ..
+            }) => sim.resolve(MemoryBudget::default()).unwrap(),
+            _ => panic!("expected animate"),
+        };
+        let run = |timeline: &ParamTimeline| {
+            let mut fields = Vec::new();
+            let result =
+                simulate_with_frames(&sim, Some(10), None, Some(timeline), None, |_, f, _| {
+                    fields.push(f.clone());
+                    Ok(())
+                });
+            result.map(|s| (s.params, fields))
+        };
+        let timeline: ParamTimeline = serde_json::from_value(serde_json::json!({
+            "params": {"kill_rate": [{"frame": 0, "value": 0.06}, {"frame": 10, "value": 0.07}]}
+        }))
+        .unwrap();
+        let (params, steered) = run(&timeline).unwrap();
+        assert_eq!(params["kill_rate"], serde_json::json!(0.06));
+        let (_, held) = run(&ParamTimeline::default()).unwrap();
+        let (_, constant) = run(&serde_json::from_value(serde_json::json!({
+            "params": {"kill_rate": [{"frame": 0, "value": 0.06}]}
+        }))
+        .unwrap())
+        .unwrap();
+        assert_ne!(held, constant);
+        assert_ne!(steered[2], constant[2]);
+
+        let fixed: ParamTimeline = serde_json::from_value(serde_json::json!({
+            "params": {"rng": [{"frame": 0, "value": "xorshift64"}, {"frame": 5, "value": "pcg32"}]}
+        }))
+        .unwrap();
+        let err = run(&fixed).err().unwrap();
+        assert!(err.to_string().contains("step 5"), "{err}");
+    }
+
+    #[test]
+    fn params_files_reload_mid_run_and_replay_from_their_history() {
+        let sim = match Cli::try_parse_from([
+            "art-engine",
+            "animate",
+            "gray-scott",
+            "-W",
+            "24",
+            "-H",
+            "24",
+            "--steps",
+            "30",
+        ]) {
+            Ok(Cli {
+                command: Command::Animate(AnimateArgs { sim, .. }),
+                ..
+            }) => sim.resolve(MemoryBudget::default()).unwrap(),
+            _ => panic!("expected animate"),
+        };
+        let path =
+            std::env::temp_dir().join(format!("art-engine-params-file-{}.json", process::id()));
+        std::fs::write(&path, r#"{"kill_rate": 0.06}"#).unwrap();
+        let mut file = ParamsFile::open(&path, 10).unwrap();
+        let mut reloaded = Vec::new();
+        let result =
+            simulate_with_frames(&sim, Some(10), None, None, Some(&mut file), |step, f, _| {
+                if step == 10 {
+                    std::fs::write(&path, r#"{"kill_rate": 0.065}"#).unwrap();
+                }
+                reloaded.push(f.clone());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(result.params["kill_rate"], 0.06);
+        let history = file.history();
+        assert_eq!(history.params_at(9.0)["kill_rate"], 0.06);
+        assert_eq!(history.params_at(10.0)["kill_rate"], 0.065);
+
+        let mut replayed = Vec::new();
+        simulate_with_frames(&sim, Some(10), None, Some(&history), None, |_, f, _| {
+            replayed.push(f.clone());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(reloaded, replayed);
+
+        // A file may not fight the timeline over a param
+        let mut file = ParamsFile::open(&path, 10).unwrap();
+        let err = simulate_with_frames(
+            &sim,
+            Some(10),
+            None,
+            Some(&history),
+            Some(&mut file),
+            |_, _, _| Ok(()),
+        );
+        assert!(matches!(err, Err(CliError::Input(_))));
+        std::fs::remove_file(&path).unwrap();
+        assert!(Cli::try_parse_from(["art-engine", "animate", "--reload-every", "2"]).is_err());
+    }
+
+    #[cfg(feature = "audio")]
+    #[test]
+    fn audio_drives_animate_for_the_length_of_the_track() {
+        let dir = std::env::temp_dir().join(format!("art-engine-audio-animate-{}", process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (track, map, frames) = (
+            dir.join("beat.wav"),
+            dir.join("map.json"),
+            dir.join("frames"),
+        );
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&track, spec).unwrap();
+        for i in 0..4000 {
+            // A 200 Hz tone pulsing every 0.2 s
+            let on = (i / 800) % 2 == 1;
+            let t = i as f64 / 8000.0;
+            let sample = if on {
+                (std::f64::consts::TAU * 200.0 * t).sin() * 8000.0
+            } else {
+                0.0
+            };
+            writer.write_sample(sample as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        std::fs::write(
+            &map,
+            r#"{"post": [{"kind": "vignette", "strength": 0.0}],
+                "routes": [{"feature": "bass", "param": "feed_rate", "min": 0.03, "max": 0.05},
+                           {"feature": "onset", "post": "vignette.strength"}]}"#,
+        )
+        .unwrap();
+        let args = |extra: &[&str]| {
+            let paths = [
+                track.to_str().unwrap(),
+                map.to_str().unwrap(),
+                frames.to_str().unwrap(),
+            ];
+            let base = [
+                "art-engine",
+                "animate",
+                "gray-scott",
+                "-W",
+                "16",
+                "-H",
+                "16",
+                "--steps",
+                "30",
+                "--fps",
+                "10",
+                "--audio",
+                paths[0],
+                "--audio-map",
+                paths[1],
+                "-o",
+                paths[2],
+            ];
+            Cli::try_parse_from([&base[..], extra].concat())
+        };
+        run(args(&[]).unwrap()).unwrap();
+        // Half a second at 10 fps: 5 frames, a frame every 6 steps
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(frames.join("manifest.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest["every"], 6);
+        assert_eq!(manifest["frames"].as_array().map(Vec::len), Some(5));
+        assert_eq!(manifest["audio"]["track"], track.to_str().unwrap());
+
+        std::fs::write(
+            &map,
+            r#"{"routes": [{"feature": "kick", "param": "feed_rate"}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(run(args(&[]).unwrap()), Err(CliError::Input(_))));
+        assert!(Cli::try_parse_from(["art-engine", "animate", "--audio", "x.wav"]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn animate_encodes_and_tags_frames_in_the_color_space() {
+        let dir = std::env::temp_dir().join(format!("art-engine-p3-frames-{}", process::id()));
+        let args = |space: &str| {
+            Cli::try_parse_from([
+                "art-engine",
+                "animate",
+                "gray-scott",
+                "-W",
+                "8",
+                "-H",
+                "8",
+                "--steps",
+                "4",
+                "--every",
+                "2",
+                "--color-space",
+                space,
+                "-o",
+                dir.to_str().unwrap(),
+            ])
+            .unwrap()
+        };
+        let frame =
+            |index: usize| std::fs::read(dir.join(format!("frame_{index:05}.png"))).unwrap();
+        let tagged = |png: &[u8]| png.windows(4).any(|chunk| chunk == b"cICP");
+
+        run(args("srgb")).unwrap();
+        let srgb = art_engine_engines::snapshot::read_rgba_png(&dir.join("frame_00001.png"))
+            .unwrap()
+            .0;
+        assert!(!tagged(&frame(0)));
+
+        run(args("display-p3")).unwrap();
+        assert!(tagged(&frame(0)) && tagged(&frame(1)));
+        let p3 = art_engine_engines::snapshot::read_rgba_png(&dir.join("frame_00001.png"))
+            .unwrap()
+            .0;
+        assert_ne!(p3, srgb);
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("manifest.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest["color_space"], "display-p3");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn until_stable_stops_early_and_records_the_steps_run() {
+        let sim = |extra: &[&str]| match Cli::try_parse_from(
+            [
+                &["art-engine", "render", "schelling", "-W", "24", "-H", "24"][..],
+                extra,
+            ]
+            .concat(),
+        ) {
+            Ok(Cli {
+                command:
+                    Command::Render(RenderArgs {
+                        sim, until_stable, ..
+                    }),
+                ..
+            }) => Sim {
+                until_stable,
+                ..sim.resolve(MemoryBudget::default()).unwrap()
+            },
+            _ => panic!("expected render"),
+        };
+        let stopped = simulate(&sim(&["--steps", "500", "--until-stable", "1e-3"])).unwrap();
+        assert!(stopped.steps < 500, "ran {} steps", stopped.steps);
+        let Some(Convergence::Stable { step, .. } | Convergence::Oscillating { step, .. }) =
+            stopped.converged
+        else {
+            panic!("no convergence reported");
+        };
+        assert_eq!(step, stopped.steps);
+
+        // Replaying the recorded steps reproduces the early-stopped field
+        let steps = stopped.steps.to_string();
+        let replay = simulate(&sim(&["--steps", &steps])).unwrap();
+        assert_eq!(replay.field, stopped.field);
+        assert_eq!(replay.converged, None);
+
+        let (_, seed) = render_rgba(&sim(&["--steps", "500", "--until-stable", "1e-3"])).unwrap();
+        assert_eq!(seed.steps, stopped.steps);
+        assert!(
+            Cli::try_parse_from(["art-engine", "render", "rps", "--until-stable", "-1"]).is_err()
+        );
+    }
+
+    #[test]
+    fn oversampled_renders_reproduce_from_their_seed() {
+        let resolve = |args: &[&str]| match Cli::try_parse_from(args.iter().copied()) {
+            Ok(Cli {
+                command: Command::Render(RenderArgs { sim, .. }),
+                ..
+            }) => sim.resolve(MemoryBudget::default()).unwrap(),
+            _ => panic!("expected render"),
+        };
+        let args = ["art-engine", "render", "gray-scott", "-W", "12", "-H", "8"];
+        let (rgba, seed) = render_rgba(&resolve(
+            &[&args[..], &["--steps", "20", "--oversample", "2"]].concat(),
+        ))
+        .unwrap();
+        assert_eq!(seed.oversample, 2);
+
+        let path =
+            std::env::temp_dir().join(format!("art-engine-oversample-{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&seed).unwrap()).unwrap();
+        let file = [
+            "art-engine",
+            "render",
+            "--seed-file",
+            path.to_str().unwrap(),
+        ];
+        let (replayed, _) = render_rgba(&resolve(&file)).unwrap();
+        assert_eq!(replayed, rgba);
+        // An explicit --oversample still overrides the seed's
+        let sim = resolve(&[&file[..], &["--oversample", "1"]].concat());
+        assert_eq!(sim.oversample, 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
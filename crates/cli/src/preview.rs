@@ -0,0 +1,316 @@
+//! Terminal preview of a rendered field.
+//!
+//! Three encodings are supported:
+//! - half-block: truecolor ANSI `▀` cells, two pixels per character (works
+//!   in any modern terminal, including over SSH)
+//! - sixel: DEC sixel graphics quantized to a 6x6x6 color cube
+//! - kitty: the kitty graphics protocol with raw RGBA payloads
+//!
+//! The field is box-filtered down to the preview size before palette mapping,
+//! so previews stay cheap regardless of the simulation resolution.
+
+use art_engine_core::{EngineError, Field, Palette};
+use art_engine_engines::pixel::field_to_rgba_with_hue;
+use base64::Engine as _;
+use clap::ValueEnum;
+use std::fmt::Write;
+
+/// Terminal image encoding for previews.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PreviewMode {
+    /// Truecolor ANSI half-block characters.
+    Halfblock,
+    /// DEC sixel graphics.
+    Sixel,
+    /// Kitty terminal graphics protocol.
+    Kitty,
+}
+
+/// Maximum base64 payload bytes per kitty graphics escape sequence.
+const KITTY_CHUNK: usize = 4096;
+
+/// Renders a preview of `field` (optionally hue-rotated) as a string of
+/// terminal escape sequences.
+///
+/// `size` is the maximum width in character cells for half-block mode, or
+/// the maximum length of the longer side in pixels for sixel and kitty.
+/// Previews are never upscaled beyond the field resolution.
+pub fn render_preview(
+    field: &Field,
+    hue: Option<&Field>,
+    palette: &Palette,
+    mode: PreviewMode,
+    size: usize,
+) -> Result<String, EngineError> {
+    let (w, h) = preview_dimensions(field.width(), field.height(), mode, size);
+    let small = resample_box(field, w, h)?;
+    let small_hue = hue.map(|f| resample_box(f, w, h)).transpose()?;
+    let rgba = field_to_rgba_with_hue(&small, palette, small_hue.as_ref())?;
+    Ok(match mode {
+        PreviewMode::Halfblock => halfblock(&rgba, w, h),
+        PreviewMode::Sixel => sixel(&rgba, w, h),
+        PreviewMode::Kitty => kitty(&rgba, w, h),
+    })
+}
+
+/// Computes the preview resolution in pixels, preserving aspect ratio.
+fn preview_dimensions(
+    width: usize,
+    height: usize,
+    mode: PreviewMode,
+    size: usize,
+) -> (usize, usize) {
+    let size = size.max(1);
+    let scale = match mode {
+        PreviewMode::Halfblock => (size as f64 / width as f64).min(1.0),
+        PreviewMode::Sixel | PreviewMode::Kitty => {
+            (size as f64 / width.max(height) as f64).min(1.0)
+        }
+    };
+    let w = ((width as f64 * scale).round() as usize).max(1);
+    let h = ((height as f64 * scale).round() as usize).max(1);
+    (w, h)
+}
+
+/// Box-filters a field to `w x h` by averaging the source cells that fall
+/// into each output cell. Handles non-integer ratios.
+pub(crate) fn resample_box(field: &Field, w: usize, h: usize) -> Result<Field, EngineError> {
+    let (sw, sh) = (field.width(), field.height());
+    let span = |i: usize, src: usize, dst: usize| {
+        let start = i * src / dst;
+        let end = ((i + 1) * src / dst).max(start + 1);
+        start..end
+    };
+    let data = (0..w * h)
+        .map(|i| {
+            let (xs, ys) = (span(i % w, sw, w), span(i / w, sh, h));
+            let count = (xs.len() * ys.len()) as f64;
+            let sum: f64 = ys
+                .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                .map(|(x, y)| field.data()[y * sw + x])
+                .sum();
+            sum / count
+        })
+        .collect();
+    Field::from_data(w, h, data)
+}
+
+/// Encodes RGBA pixels as truecolor half-block rows (top pixel in the
+/// foreground, bottom pixel in the background).
+fn halfblock(rgba: &[u8], w: usize, h: usize) -> String {
+    let px = |x: usize, y: usize| {
+        let i = (y * w + x) * 4;
+        (rgba[i], rgba[i + 1], rgba[i + 2])
+    };
+    let mut out = String::new();
+    for row in (0..h).step_by(2) {
+        for x in 0..w {
+            let (r, g, b) = px(x, row);
+            let _ = write!(out, "\x1b[38;2;{r};{g};{b}m");
+            if row + 1 < h {
+                let (r, g, b) = px(x, row + 1);
+                let _ = write!(out, "\x1b[48;2;{r};{g};{b}m");
+            } else {
+                out.push_str("\x1b[49m");
+            }
+            out.push('▀');
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Maps an 8-bit channel to one of six cube levels.
+fn cube_level(c: u8) -> usize {
+    (usize::from(c) * 5 + 127) / 255
+}
+
+/// Encodes RGBA pixels as a sixel image using a 216-color cube palette.
+fn sixel(rgba: &[u8], w: usize, h: usize) -> String {
+    let index = |x: usize, y: usize| {
+        let i = (y * w + x) * 4;
+        cube_level(rgba[i]) * 36 + cube_level(rgba[i + 1]) * 6 + cube_level(rgba[i + 2])
+    };
+    let mut out = format!("\x1bPq\"1;1;{w};{h}");
+    (0..216).for_each(|i| {
+        let pct = |level: usize| level * 100 / 5;
+        let _ = write!(
+            out,
+            "#{i};2;{};{};{}",
+            pct(i / 36),
+            pct(i / 6 % 6),
+            pct(i % 6)
+        );
+    });
+
+    for band in (0..h).step_by(6) {
+        let rows = band..(band + 6).min(h);
+        let mut colors: Vec<usize> = rows
+            .clone()
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .map(|(x, y)| index(x, y))
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (n, &color) in colors.iter().enumerate() {
+            if n > 0 {
+                out.push('$');
+            }
+            let _ = write!(out, "#{color}");
+            let sixels: Vec<u8> = (0..w)
+                .map(|x| {
+                    rows.clone()
+                        .filter(|&y| index(x, y) == color)
+                        .fold(0u8, |bits, y| bits | (1 << (y - band)))
+                })
+                .collect();
+            push_sixel_run_length(&mut out, &sixels);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Appends sixel data characters, collapsing runs with `!<count><char>`.
+fn push_sixel_run_length(out: &mut String, sixels: &[u8]) {
+    let mut i = 0;
+    while i < sixels.len() {
+        let bits = sixels[i];
+        let run = sixels[i..].iter().take_while(|&&b| b == bits).count();
+        let ch = char::from(63 + bits);
+        if run > 3 {
+            let _ = write!(out, "!{run}{ch}");
+        } else {
+            (0..run).for_each(|_| out.push(ch));
+        }
+        i += run;
+    }
+}
+
+/// Encodes RGBA pixels with the kitty graphics protocol, chunking the base64
+/// payload as the protocol requires.
+fn kitty(rgba: &[u8], w: usize, h: usize) -> String {
+    let payload = base64::engine::general_purpose::STANDARD.encode(rgba);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        // Chunks of a base64 string are ASCII, so this never fails.
+        let data = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            let _ = write!(out, "\x1b_Ga=T,f=32,s={w},v={h},m={more};{data}\x1b\\");
+        } else {
+            let _ = write!(out, "\x1b_Gm={more};{data}\x1b\\");
+        }
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(w: usize, h: usize) -> Field {
+        let data = (0..w * h).map(|i| (i % w) as f64 / w as f64).collect();
+        Field::from_data(w, h, data).unwrap()
+    }
+
+    // -- Sizing --
+
+    #[test]
+    fn halfblock_dimensions_fit_columns_and_keep_aspect() {
+        assert_eq!(
+            preview_dimensions(256, 128, PreviewMode::Halfblock, 64),
+            (64, 32)
+        );
+    }
+
+    #[test]
+    fn graphics_dimensions_fit_longer_side() {
+        assert_eq!(
+            preview_dimensions(100, 400, PreviewMode::Kitty, 200),
+            (50, 200)
+        );
+    }
+
+    #[test]
+    fn preview_never_upscales() {
+        assert_eq!(preview_dimensions(16, 8, PreviewMode::Sixel, 512), (16, 8));
+    }
+
+    #[test]
+    fn resample_box_averages_non_integer_ratio() {
+        let field = Field::from_data(3, 1, vec![0.0, 0.5, 1.0]).unwrap();
+        let small = resample_box(&field, 2, 1).unwrap();
+        // Spans are [0, 1) and [1, 3)
+        assert!((small.data()[0] - 0.0).abs() < 1e-12);
+        assert!((small.data()[1] - 0.75).abs() < 1e-12);
+    }
+
+    // -- Encodings --
+
+    #[test]
+    fn halfblock_emits_one_line_per_two_rows() {
+        let out = render_preview(
+            &gradient(8, 5),
+            None,
+            &Palette::ocean(),
+            PreviewMode::Halfblock,
+            8,
+        )
+        .unwrap();
+        assert_eq!(out.lines().count(), 3);
+        assert_eq!(out.matches('▀').count(), 8 * 3);
+        assert!(out.contains("\x1b[38;2;"));
+        // Odd height: the last row has no background pixel
+        assert!(out.lines().last().unwrap().contains("\x1b[49m"));
+    }
+
+    #[test]
+    fn sixel_has_header_bands_and_terminator() {
+        let out = sixel(&[255, 0, 0, 255].repeat(4 * 7), 4, 7);
+        assert!(out.starts_with("\x1bPq\"1;1;4;7"));
+        assert!(out.ends_with("\x1b\\"));
+        // 7 rows need two sixel bands
+        assert_eq!(out.matches('-').count(), 2);
+        // Pure red is cube index 5 * 36 = 180
+        assert!(out.contains("#180~~~~") || out.contains("#180!4~"));
+    }
+
+    #[test]
+    fn sixel_run_length_collapses_long_runs() {
+        let mut out = String::new();
+        push_sixel_run_length(&mut out, &[63, 63, 63, 63, 63, 0]);
+        assert_eq!(out, "!5~?");
+    }
+
+    #[test]
+    fn kitty_chunks_payload_and_marks_last_chunk() {
+        let rgba = vec![7u8; 64 * 64 * 4];
+        let out = kitty(&rgba, 64, 64);
+        assert!(out.starts_with("\x1b_Ga=T,f=32,s=64,v=64,m=1;"));
+        assert!(out.contains("\x1b_Gm=0;"));
+        let chunks = out.matches("\x1b_G").count();
+        let payload_len = rgba.len().div_ceil(3) * 4;
+        assert_eq!(chunks, payload_len.div_ceil(KITTY_CHUNK));
+    }
+
+    #[test]
+    fn kitty_single_chunk_is_final() {
+        let out = kitty(&[0u8; 4], 1, 1);
+        assert!(out.starts_with("\x1b_Ga=T,f=32,s=1,v=1,m=0;"));
+    }
+
+    #[test]
+    fn render_preview_resamples_hue_field_to_preview_size() {
+        let field = gradient(8, 8);
+        let hue = gradient(4, 4);
+        // Both fields are resampled to the same preview size, so they always agree.
+        assert!(
+            render_preview(&field, Some(&hue), &Palette::fire(), PreviewMode::Kitty, 4).is_ok()
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Raw video frame streaming for piping into encoders such as ffmpeg.
+//!
+//! Two container formats are supported:
+//! - `rgba`: headerless packed RGBA8 frames
+//!   (`ffmpeg -f rawvideo -pix_fmt rgba -s WxH -i -`)
+//! - `y4m`: YUV4MPEG2 with full-resolution 4:4:4 chroma and BT.601
+//!   limited-range coefficients (`ffmpeg -i -`)
+
+use clap::ValueEnum;
+use std::io::{self, Write};
+
+/// Frame container written by [`RawWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RawFormat {
+    /// Headerless packed RGBA8.
+    Rgba,
+    /// YUV4MPEG2 (4:4:4).
+    Y4m,
+}
+
+/// Writes a sequence of equally sized RGBA8 frames in a [`RawFormat`].
+pub struct RawWriter<W: Write> {
+    out: W,
+    format: RawFormat,
+    width: usize,
+    height: usize,
+    fps: u32,
+    header_written: bool,
+}
+
+impl<W: Write> RawWriter<W> {
+    /// Creates a writer for `width x height` frames. The y4m header (which
+    /// records `fps`) is emitted lazily with the first frame.
+    pub fn new(out: W, format: RawFormat, width: usize, height: usize, fps: u32) -> Self {
+        Self {
+            out,
+            format,
+            width,
+            height,
+            fps,
+            header_written: false,
+        }
+    }
+
+    /// Writes one RGBA8 frame.
+    ///
+    /// Returns `InvalidInput` if the buffer is not `width * height * 4` bytes.
+    pub fn write_frame(&mut self, rgba: &[u8]) -> io::Result<()> {
+        if rgba.len() != self.width * self.height * 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame is {} bytes, expected {}x{}x4",
+                    rgba.len(),
+                    self.width,
+                    self.height
+                ),
+            ));
+        }
+        match self.format {
+            RawFormat::Rgba => self.out.write_all(rgba)?,
+            RawFormat::Y4m => {
+                if !self.header_written {
+                    writeln!(
+                        self.out,
+                        "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+                        self.width, self.height, self.fps
+                    )?;
+                    self.header_written = true;
+                }
+                self.out.write_all(b"FRAME\n")?;
+                self.out.write_all(&rgba_to_yuv444(rgba))?;
+            }
+        }
+        self.out.flush()
+    }
+}
+
+/// Converts packed RGBA8 to planar Y, U, V (BT.601 limited range). Alpha is dropped.
+fn rgba_to_yuv444(rgba: &[u8]) -> Vec<u8> {
+    let n = rgba.len() / 4;
+    let mut planes = vec![0u8; n * 3];
+    rgba.chunks_exact(4).enumerate().for_each(|(i, px)| {
+        let (r, g, b) = (
+            f64::from(px[0]) / 255.0,
+            f64::from(px[1]) / 255.0,
+            f64::from(px[2]) / 255.0,
+        );
+        let y = 16.0 + 65.481 * r + 128.553 * g + 24.966 * b;
+        let u = 128.0 - 37.797 * r - 74.203 * g + 112.0 * b;
+        let v = 128.0 + 112.0 * r - 93.786 * g - 18.214 * b;
+        planes[i] = y.round() as u8;
+        planes[n + i] = u.round() as u8;
+        planes[2 * n + i] = v.round() as u8;
+    });
+    planes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgba_frames_are_written_verbatim() {
+        let mut out = Vec::new();
+        let mut writer = RawWriter::new(&mut out, RawFormat::Rgba, 1, 2, 30);
+        writer.write_frame(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        writer.write_frame(&[9; 8]).unwrap();
+        assert_eq!(out.len(), 16);
+        assert_eq!(&out[..8], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn y4m_writes_header_once_and_frame_markers() {
+        let mut out = Vec::new();
+        let mut writer = RawWriter::new(&mut out, RawFormat::Y4m, 2, 1, 24);
+        writer.write_frame(&[0; 8]).unwrap();
+        writer.write_frame(&[255; 8]).unwrap();
+        let header = b"YUV4MPEG2 W2 H1 F24:1 Ip A1:1 C444\n";
+        assert!(out.starts_with(header));
+        assert_eq!(out.len(), header.len() + 2 * (6 + 2 * 3));
+        assert_eq!(out.windows(6).filter(|w| w == b"FRAME\n").count(), 2);
+    }
+
+    #[test]
+    fn write_frame_rejects_wrong_size() {
+        let mut writer = RawWriter::new(Vec::new(), RawFormat::Rgba, 2, 2, 30);
+        let err = writer.write_frame(&[0; 4]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn yuv_of_black_and_white_uses_limited_range() {
+        let planes = rgba_to_yuv444(&[0, 0, 0, 255, 255, 255, 255, 255]);
+        // Y plane: black = 16, white = 235; chroma neutral at 128
+        assert_eq!(planes, vec![16, 235, 128, 128, 128, 128]);
+    }
+}
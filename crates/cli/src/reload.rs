@@ -0,0 +1,178 @@
+//! Hot-reloaded params for `animate --params-file`.
+//!
+//! A [`ParamsFile`] holds a JSON object of engine params. Its contents at
+//! the start are merged into the starting params; every `every` steps the
+//! file is checked (by modification time and length, like watch mode) and
+//! keys whose value changed are handed to `Engine::set_params`. Keys removed
+//! from the file keep their last value. A file that does not parse, as
+//! while an editor is saving it, is skipped and checked again next time.
+//!
+//! Every applied value is kept as a step-eased key at the step it took
+//! effect, so [`ParamsFile::history`] is a [`ParamTimeline`] that replays
+//! the run's params through `animate --timeline`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use art_engine_core::keyframe::{Easing, Keyframe, Track};
+use art_engine_core::ParamTimeline;
+use serde_json::{Map, Value};
+
+use crate::error::CliError;
+use crate::watch::{stamp, Stamp};
+
+/// A params file polled during a run.
+#[derive(Debug)]
+pub struct ParamsFile {
+    path: PathBuf,
+    every: usize,
+    stamp: Option<Stamp>,
+    current: Map<String, Value>,
+    history: BTreeMap<String, Vec<Keyframe<Value>>>,
+}
+
+impl ParamsFile {
+    /// Reads the starting params from `path`, to be checked every `every`
+    /// steps.
+    ///
+    /// Returns `CliError::Input` unless the file holds a JSON object.
+    pub fn open(path: &Path, every: usize) -> Result<Self, CliError> {
+        let stamp = stamp(path);
+        let current = read(path)?;
+        let history = current
+            .iter()
+            .map(|(name, value)| (name.clone(), vec![key(0, value)]))
+            .collect();
+        Ok(Self {
+            path: path.to_path_buf(),
+            every: every.max(1),
+            stamp,
+            current,
+            history,
+        })
+    }
+
+    /// Returns the params the file held when last applied.
+    pub fn current(&self) -> &Map<String, Value> {
+        &self.current
+    }
+
+    /// Returns the keys that changed since the last applied contents, if
+    /// `step` is a check step and the file was rewritten; `None` otherwise.
+    ///
+    /// Call [`ParamsFile::applied`] once the engine accepts them.
+    pub fn poll(&mut self, step: usize) -> Option<Map<String, Value>> {
+        if step == 0 || !step.is_multiple_of(self.every) {
+            return None;
+        }
+        let stamp = stamp(&self.path);
+        if stamp.is_none() || stamp == self.stamp {
+            return None;
+        }
+        match read(&self.path) {
+            Ok(params) => {
+                self.stamp = stamp;
+                let changed: Map<String, Value> = params
+                    .into_iter()
+                    .filter(|(name, value)| self.current.get(name) != Some(value))
+                    .collect();
+                (!changed.is_empty()).then_some(changed)
+            }
+            Err(e) => {
+                tracing::debug!(step, error = %e, "params file not ready");
+                None
+            }
+        }
+    }
+
+    /// Records `changed` as in effect from `step`.
+    pub fn applied(&mut self, step: usize, changed: Map<String, Value>) {
+        for (name, value) in changed {
+            self.history
+                .entry(name.clone())
+                .or_default()
+                .push(key(step, &value));
+            self.current.insert(name, value);
+        }
+    }
+
+    /// Returns every applied value as a timeline keyed by step.
+    pub fn history(&self) -> ParamTimeline {
+        ParamTimeline {
+            params: self
+                .history
+                .iter()
+                .map(|(name, keys)| (name.clone(), Track::new(keys.clone())))
+                .collect(),
+            palette: None,
+        }
+    }
+}
+
+fn key(step: usize, value: &Value) -> Keyframe<Value> {
+    Keyframe::new(step as f64, value.clone()).with_easing(Easing::Step)
+}
+
+/// Reads a params file, which must hold a JSON object.
+fn read(path: &Path) -> Result<Map<String, Value>, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::Input(format!("{}: {e}", path.display())))?;
+    match serde_json::from_str(&text) {
+        Ok(Value::Object(params)) => Ok(params),
+        Ok(_) => Err(CliError::Input(format!(
+            "{}: params must be a JSON object",
+            path.display()
+        ))),
+        Err(e) => Err(CliError::Input(format!("{}: {e}", path.display()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn polls_report_changed_keys_on_check_steps() {
+        let path =
+            std::env::temp_dir().join(format!("art-engine-reload-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"feed_rate": 0.05, "kill_rate": 0.06}"#).unwrap();
+        let mut file = ParamsFile::open(&path, 10).unwrap();
+        assert_eq!(file.current()["feed_rate"], 0.05);
+        // Unchanged file
+        assert_eq!(file.poll(10), None);
+
+        // A longer file always gets a new stamp, whatever the clock's resolution
+        std::fs::write(&path, r#"{"feed_rate": 0.05, "kill_rate": 0.062}"#).unwrap();
+        assert_eq!(file.poll(15), None);
+        let changed = file.poll(20).unwrap();
+        assert_eq!(Value::from(changed.clone()), json!({"kill_rate": 0.062}));
+        file.applied(20, changed);
+        assert_eq!(file.poll(30), None);
+
+        // Half-written files are retried; removed keys hold
+        std::fs::write(&path, r#"{"feed_rate": 0.0"#).unwrap();
+        assert_eq!(file.poll(40), None);
+        std::fs::write(&path, r#"{"feed_rate":   0.04}"#).unwrap();
+        let changed = file.poll(50).unwrap();
+        file.applied(50, changed);
+        assert_eq!(file.current()["kill_rate"], 0.062);
+
+        let history = file.history();
+        let at = |step: f64| Value::from(history.params_at(step));
+        assert_eq!(at(0.0), json!({"feed_rate": 0.05, "kill_rate": 0.06}));
+        assert_eq!(at(49.0), json!({"feed_rate": 0.05, "kill_rate": 0.062}));
+        assert_eq!(at(50.0), json!({"feed_rate": 0.04, "kill_rate": 0.062}));
+
+        std::fs::write(&path, "[1]").unwrap();
+        assert!(matches!(
+            ParamsFile::open(&path, 1),
+            Err(CliError::Input(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            ParamsFile::open(&path, 1),
+            Err(CliError::Input(_))
+        ));
+    }
+}
@@ -0,0 +1,312 @@
+//! The `render` subcommand: one engine run written as a PNG or SVG.
+//!
+//! Along the way the run can stream raw frames (`--raw-output`), save
+//! intermediate snapshots and checkpoints, and stop early once the field
+//! settles (`--until-stable`). The final field may also be written as a
+//! heightmap or normal map, or previewed in the terminal.
+
+use crate::checkpoint::CheckpointArgs;
+use crate::error::CliError;
+use crate::preview::render_preview;
+use crate::raw::{RawFormat, RawWriter};
+use crate::{
+    apply_bloom, gcd, is_svg_path, open_raw_output, parse_epsilon, parse_palette,
+    simulate_with_frames, stats, watch, BloomArgs, PreviewArgs, Sim, SimArgs, Simulation,
+    SnapshotArgs,
+};
+use art_engine_core::observer::Convergence;
+use art_engine_core::{MemoryBudget, Seed};
+use art_engine_engines::pixel::field_to_rgba_in;
+use clap::Args;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Options of the `render` subcommand.
+#[derive(Args)]
+pub struct RenderArgs {
+    #[command(flatten)]
+    pub sim: SimArgs,
+
+    #[command(flatten)]
+    pub checkpoint: CheckpointArgs,
+
+    #[command(flatten)]
+    pub snapshots: SnapshotArgs,
+
+    #[command(flatten)]
+    pub bloom: BloomArgs,
+
+    /// Stop before --steps once a step changes the field by less than
+    /// EPSILON (mean absolute change per cell) or the field starts
+    /// cycling; the embedded seed records the steps actually run.
+    #[arg(long, value_name = "EPSILON", value_parser = parse_epsilon)]
+    pub until_stable: Option<f64>,
+
+    /// Output file path.
+    #[arg(short, long, default_value = "output.png")]
+    pub output: PathBuf,
+
+    /// Number of evenly spaced contour levels for SVG output.
+    #[arg(long, default_value_t = 8)]
+    pub contours: usize,
+
+    /// Also write the field as a 16-bit grayscale heightmap PNG.
+    #[arg(long, value_name = "PATH")]
+    pub heightmap: Option<PathBuf>,
+
+    /// Also write a tangent-space normal map PNG derived from the field.
+    #[arg(long, value_name = "PATH")]
+    pub normal_map: Option<PathBuf>,
+
+    /// Slope scale for --normal-map, in height units per pixel.
+    #[arg(long, default_value_t = 2.0)]
+    pub normal_strength: f64,
+
+    /// Also print a preview of the result to the terminal (on stderr).
+    #[arg(long)]
+    pub preview_terminal: bool,
+
+    /// Map the final field to colors on a headless GPU context.
+    #[cfg(feature = "gpu")]
+    #[arg(long)]
+    pub gpu: bool,
+
+    #[command(flatten)]
+    pub preview: PreviewArgs,
+
+    /// Stream frames while simulating to this path (`-` for stdout).
+    #[arg(long, value_name = "PATH")]
+    pub raw_output: Option<PathBuf>,
+
+    /// Frame container for --raw-output.
+    #[arg(long, value_enum, default_value_t = RawFormat::Rgba)]
+    pub raw_format: RawFormat,
+
+    /// Emit a raw frame every N simulation steps (the final step is always emitted).
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub frame_every: u32,
+
+    /// Frame rate recorded in the y4m header.
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    pub fps: u32,
+}
+
+/// Runs `render`, checking engine and output memory against `memory`.
+pub fn run(args: RenderArgs, memory: MemoryBudget, json: bool) -> Result<(), CliError> {
+    let RenderArgs {
+        sim,
+        checkpoint,
+        snapshots,
+        bloom,
+        until_stable,
+        output,
+        contours,
+        heightmap,
+        normal_map,
+        normal_strength,
+        preview_terminal,
+        #[cfg(feature = "gpu")]
+        gpu,
+        preview,
+        raw_output,
+        raw_format,
+        frame_every,
+        fps,
+    } = args;
+    let started = Instant::now();
+    let sim = Sim {
+        until_stable,
+        ..sim.resolve(memory)?
+    };
+    let plan = checkpoint.plan(&sim);
+    let bloom = bloom.resolve();
+    let frame_palette = parse_palette(&sim.palette)?;
+    let mut raw_writer = raw_output
+        .as_deref()
+        .map(|path| -> Result<_, CliError> {
+            Ok(RawWriter::new(
+                open_raw_output(path)?,
+                raw_format,
+                sim.width,
+                sim.height,
+                fps,
+            ))
+        })
+        .transpose()?;
+    let raw_every = raw_writer.as_ref().map(|_| frame_every as usize);
+    let snapshot_every = snapshots.snapshot_every.map(|n| n as usize);
+    if snapshot_every.is_some() {
+        std::fs::create_dir_all(&snapshots.snapshot_dir)
+            .map_err(|e| CliError::Io(format!("{}: {e}", snapshots.snapshot_dir.display())))?;
+    }
+    // Capture on every step either output needs, then sort them out
+    let every = match (raw_every, snapshot_every) {
+        (Some(a), Some(b)) => Some(gcd(a, b)),
+        (a, b) => a.or(b),
+    };
+    let Simulation {
+        field,
+        hue,
+        palette,
+        params,
+        steps_per_sec,
+        steps,
+        converged,
+    } = simulate_with_frames(
+        &sim,
+        every,
+        plan.as_ref(),
+        None,
+        None,
+        |step, field, hue| {
+            let rgba = field_to_rgba_in(field, &frame_palette, hue, sim.color_space)?;
+            if let (Some(writer), Some(n)) = (raw_writer.as_mut(), raw_every) {
+                if step % n == 0 || step == sim.steps {
+                    writer
+                        .write_frame(&rgba)
+                        .map_err(|e| CliError::Io(format!("raw output: {e}")))?;
+                }
+            }
+            match snapshot_every {
+                // The final step is the output itself
+                Some(n) if step % n == 0 && step < sim.steps => {
+                    let path = snapshots.path(step);
+                    // Reproduces this snapshot exactly when rendered
+                    let seed = Seed {
+                        steps: step,
+                        ..sim.to_seed(serde_json::from_str(&sim.params)?)
+                    };
+                    watch::write_atomically(&path, |temp| {
+                        Ok(art_engine_engines::snapshot::write_rgba_png_with_seed(
+                            &rgba, sim.width, sim.height, &seed, temp,
+                        )?)
+                    })?;
+                    tracing::debug!(step, path = %path.display(), "snapshot written");
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        },
+    )?;
+    if let (Some(writer), Some(n)) = (raw_writer.as_mut(), raw_every) {
+        // A run that stopped early still ends on its final frame
+        if steps < sim.steps && steps % n != 0 {
+            writer
+                .write_frame(&field_to_rgba_in(
+                    &field,
+                    &frame_palette,
+                    hue.as_ref(),
+                    sim.color_space,
+                )?)
+                .map_err(|e| CliError::Io(format!("raw output: {e}")))?;
+        }
+    }
+    let raw_to_stdout = raw_output.as_deref() == Some(Path::new("-"));
+
+    if is_svg_path(&output) {
+        let thresholds = art_engine_engines::contour::even_thresholds(contours);
+        art_engine_engines::snapshot::write_svg(&field, &palette, &thresholds, &output)?;
+    } else {
+        #[cfg(feature = "gpu")]
+        let rgba = if gpu {
+            if hue.is_some() {
+                return Err(CliError::Input(
+                    "--gpu does not support engines with a hue field".into(),
+                ));
+            }
+            if !sim.color_space.is_srgb() {
+                return Err(CliError::Input("--gpu renders sRGB only".into()));
+            }
+            crate::gpu::field_to_rgba_gpu(&field, &palette, bloom.as_ref())?
+        } else {
+            apply_bloom(
+                field_to_rgba_in(&field, &palette, hue.as_ref(), sim.color_space)?,
+                &sim,
+                bloom.as_ref(),
+            )?
+        };
+        #[cfg(not(feature = "gpu"))]
+        let rgba = apply_bloom(
+            field_to_rgba_in(&field, &palette, hue.as_ref(), sim.color_space)?,
+            &sim,
+            bloom.as_ref(),
+        )?;
+        art_engine_engines::snapshot::write_rgba_png_with_seed(
+            &rgba,
+            sim.width,
+            sim.height,
+            &Seed {
+                steps,
+                ..sim.to_seed(params)
+            },
+            &output,
+        )?;
+    }
+    let Sim {
+        engine,
+        width,
+        height,
+        seed,
+        color_space,
+        oversample,
+        ..
+    } = sim;
+
+    if let Some(path) = &heightmap {
+        art_engine_engines::snapshot::write_heightmap_png(&field, path)?;
+    }
+    if let Some(path) = &normal_map {
+        art_engine_engines::snapshot::write_normal_map_png(&field, normal_strength, path)?;
+    }
+
+    if preview_terminal {
+        let text = render_preview(&field, hue.as_ref(), &palette, preview.mode, preview.size)?;
+        eprint!("{text}");
+    }
+
+    if json {
+        let info = serde_json::json!({
+            "engine": engine,
+            "width": width,
+            "height": height,
+            "steps": steps,
+            "seed": seed.value,
+            "seed_phrase": seed.phrase,
+            "color_space": color_space,
+            "oversample": oversample,
+            "output": output.display().to_string(),
+            "heightmap": heightmap.as_ref().map(|p| p.display().to_string()),
+            "normal_map": normal_map.as_ref().map(|p| p.display().to_string()),
+            "raw_output": raw_output.as_ref().map(|p| p.display().to_string()),
+            "elapsed_secs": started.elapsed().as_secs_f64(),
+            "steps_per_sec": steps_per_sec,
+            "converged": converged,
+            "field": stats::FieldStats::of(&field),
+            "file": stats::FileStats::of(&output)?,
+        });
+        // Keep stdout clean for frame data when streaming raw video there.
+        if raw_to_stdout {
+            eprintln!("{}", serde_json::to_string_pretty(&info)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        }
+    } else {
+        eprintln!(
+            "rendered {engine} ({width}x{height}, {steps} steps, seed {seed}) -> {}",
+            output.display()
+        );
+        match converged {
+            Some(Convergence::Stable { change: 0.0, .. }) => {
+                eprintln!("stopped early: the field stopped changing")
+            }
+            Some(Convergence::Stable { change, .. }) => {
+                eprintln!("stopped early: the field settled (mean change {change:.1e})")
+            }
+            Some(Convergence::Oscillating { period, .. }) => {
+                eprintln!("stopped early: the field cycles every {period} steps")
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! The `replay` subcommand: a recorded `tui --record` session re-rendered
+//! as an animation.
+//!
+//! Events are re-applied at the steps they were recorded at, so the frames
+//! match what the session showed.
+
+use crate::error::CliError;
+use crate::frames::capture_steps;
+use crate::{parse_palette, report_frames, FrameArgs};
+use art_engine_core::color::ColorSpace;
+use art_engine_core::{Engine, MemoryBudget, Palette};
+use art_engine_engines::pixel::field_to_rgba_with_hue;
+use art_engine_engines::EngineKind;
+use std::path::Path;
+
+/// Runs `replay`, building the engine within `memory`.
+pub fn run(
+    recording: &Path,
+    frames: &FrameArgs,
+    memory: MemoryBudget,
+    json: bool,
+) -> Result<(), CliError> {
+    let text = std::fs::read_to_string(recording)
+        .map_err(|e| CliError::Io(format!("{}: {e}", recording.display())))?;
+    let session = art_engine_core::Recording::from_json(&text)
+        .map_err(|e| CliError::Input(format!("{}: {e}", recording.display())))?;
+    let (width, height) = (session.width, session.height);
+    let every = frames.every(session.steps)?;
+    let captures = capture_steps(session.steps, every);
+    let mut out = frames.open(captures.len(), width, height, ColorSpace::Srgb)?;
+    // Re-parsed only when the recording switches palettes
+    let mut palette: Option<(String, Palette)> = None;
+    session.replay(
+        |params| {
+            EngineKind::from_name_within(
+                &session.engine,
+                width,
+                height,
+                session.seed,
+                params,
+                memory,
+            )
+            .map_err(CliError::from)
+        },
+        |step, engine, name| {
+            if captures.get(out.len()) != Some(&step) {
+                return Ok(());
+            }
+            let name = name.unwrap_or("ocean");
+            let current = match palette.take() {
+                Some((last, parsed)) if last == name => (last, parsed),
+                _ => (name.to_string(), parse_palette(name)?),
+            };
+            let rgba = field_to_rgba_with_hue(
+                engine.field(),
+                &palette.insert(current).1,
+                engine.hue_field(),
+            )?;
+            out.write(step, rgba, width, height)
+        },
+    )?;
+    let frame_count = out.len();
+    let written = out.finish(serde_json::json!({
+        "recording": recording.display().to_string(),
+        "engine": session.engine,
+        "width": width,
+        "height": height,
+        "steps": session.steps,
+        "seed": session.seed,
+        "events": session.events.len(),
+        "every": every,
+        "fps": frames.fps,
+    }))?;
+    report_frames(json, &session.engine, frame_count, &written)
+}
@@ -0,0 +1,245 @@
+//! JSON-lines render server on stdin/stdout.
+//!
+//! Each input line is a [`Request`]: a batch job (see [`crate::batch`]) plus
+//! an optional `id` that is echoed back. Requests are handled one at a time
+//! and each produces exactly one response line, flushed immediately:
+//!
+//! ```text
+//! > {"id": 1, "engine": "gray-scott", "width": 64, "height": 64, "params": {}, "seed": 7, "steps": 100}
+//! < {"id": 1, "ok": true, "width": 64, "height": 64, "png": "iVBORw0KGgo..."}
+//! > {"id": 2, "engine": "nope", ...}
+//! < {"id": 2, "ok": false, "error": "unknown engine: nope", "exit_code": 10}
+//! ```
+//!
+//! Jobs with an `output` path are written there and answered with the path;
+//! jobs without one are answered with the base64 PNG inline. Blank lines are
+//! ignored and the server exits at end of input.
+
+use crate::batch::BatchJob;
+#[cfg(feature = "control")]
+use crate::control::{steer_params, Control};
+use crate::error::CliError;
+use crate::{render_png, render_rgba, Sim};
+use art_engine_core::MemoryBudget;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// One request line.
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// Caller-chosen identifier echoed in the response (`null` if absent).
+    #[serde(default)]
+    pub id: Value,
+    /// What to render and where.
+    #[serde(flatten)]
+    pub job: BatchJob,
+}
+
+/// Reads requests from `input` until it ends, answering each on `output`.
+///
+/// `render` handles a validated job and returns the fields to add to a
+/// successful response. Malformed lines and render failures are answered
+/// with an error response; only I/O failures on the streams end the loop.
+pub fn serve(
+    input: impl BufRead,
+    mut output: impl Write,
+    mut render: impl FnMut(&BatchJob) -> Result<Value, CliError>,
+) -> Result<usize, CliError> {
+    let io_err = |e: std::io::Error| CliError::Io(format!("serve: {e}"));
+    let mut handled = 0;
+    for line in input.lines() {
+        let line = line.map_err(io_err)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = respond(&line, &mut render);
+        writeln!(output, "{response}").map_err(io_err)?;
+        output.flush().map_err(io_err)?;
+        handled += 1;
+    }
+    Ok(handled)
+}
+
+/// Handles one request line and builds its response.
+fn respond(line: &str, render: &mut impl FnMut(&BatchJob) -> Result<Value, CliError>) -> Value {
+    let parsed: Result<Request, CliError> = serde_json::from_str::<Request>(line)
+        .map_err(|e| CliError::Input(format!("invalid request: {e}")))
+        .and_then(|request| {
+            request.job.validate().map_err(CliError::Input)?;
+            Ok(request)
+        });
+    // Echo the id even when the rest of the request is malformed
+    let id = |line: &str| {
+        serde_json::from_str::<Value>(line)
+            .ok()
+            .and_then(|v| v.get("id").cloned())
+            .unwrap_or(Value::Null)
+    };
+    let (id, result) = match parsed {
+        Ok(request) => (request.id, render(&request.job)),
+        Err(e) => (id(line), Err(e)),
+    };
+    match result {
+        Ok(fields) => {
+            let mut response = json!({ "id": id, "ok": true });
+            if let (Some(response), Value::Object(fields)) = (response.as_object_mut(), fields) {
+                response.extend(fields);
+            }
+            response
+        }
+        Err(e) => json!({
+            "id": id,
+            "ok": false,
+            "error": e.to_string(),
+            "exit_code": e.exit_code(),
+        }),
+    }
+}
+
+/// How `serve` takes jobs and renders them.
+pub struct ServeOptions {
+    /// Answer over HTTP on this address instead of stdin/stdout.
+    #[cfg(feature = "http")]
+    pub http: Option<std::net::SocketAddr>,
+    /// Live controllers whose params are laid over each job's.
+    #[cfg(feature = "control")]
+    pub control: Option<Control>,
+    /// Budget each job's engine and output buffers must fit.
+    pub memory: MemoryBudget,
+}
+
+/// Runs `serve` until its input ends (or, over HTTP, until stopped).
+pub fn run(options: ServeOptions, json: bool) -> Result<(), CliError> {
+    let ServeOptions {
+        #[cfg(feature = "http")]
+        http,
+        #[cfg(feature = "control")]
+        control,
+        memory,
+    } = options;
+    let job_sim = |job: &BatchJob| -> Result<Sim, CliError> {
+        #[cfg_attr(not(feature = "control"), allow(unused_mut))]
+        let mut seed = job.seed.clone();
+        #[cfg(feature = "control")]
+        if let Some(control) = &control {
+            seed.params = steer_params(control, &seed.engine, &seed.params)?;
+        }
+        Ok(Sim {
+            memory,
+            ..Sim::from_seed(seed)
+        })
+    };
+    #[cfg(feature = "http")]
+    if let Some(addr) = http {
+        let server = crate::http::HttpServer::bind(addr)?;
+        let schemas = art_engine_engines::EngineKind::list_engines()
+            .iter()
+            .map(|name| Ok((name.to_string(), crate::param_schema(name)?)))
+            .collect::<Result<_, CliError>>()?;
+        if let Some(addr) = server.local_addr() {
+            eprintln!("serve: listening on http://{addr}");
+        }
+        let rendered = server.run(schemas, &mut |job, progress| {
+            let sim = job_sim(job)?;
+            let (rgba, seed) = crate::render_rgba_reporting(&sim, progress)?;
+            Ok(art_engine_engines::snapshot::encode_rgba_png_with_seed(
+                &rgba, sim.width, sim.height, &seed,
+            )?)
+        });
+        if !json {
+            eprintln!("serve: rendered {rendered} jobs");
+        }
+        return Ok(());
+    }
+    let handled = serve(std::io::stdin().lock(), std::io::stdout().lock(), |job| {
+        let sim = job_sim(job)?;
+        match &job.output {
+            Some(output) => {
+                render_png(&sim, output)?;
+                Ok(json!({
+                    "width": sim.width,
+                    "height": sim.height,
+                    "output": output.display().to_string(),
+                }))
+            }
+            None => {
+                use base64::Engine as _;
+                let (rgba, seed) = render_rgba(&sim)?;
+                let png = art_engine_engines::snapshot::encode_rgba_png_with_seed(
+                    &rgba, sim.width, sim.height, &seed,
+                )?;
+                Ok(json!({
+                    "width": sim.width,
+                    "height": sim.height,
+                    "png": base64::engine::general_purpose::STANDARD.encode(png),
+                }))
+            }
+        }
+    })?;
+    if !json {
+        eprintln!("serve: answered {handled} requests");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str) -> Vec<Value> {
+        let mut out = Vec::new();
+        serve(input.as_bytes(), &mut out, |job| {
+            if job.seed.engine == "fail" {
+                return Err(CliError::Engine(
+                    art_engine_core::EngineError::UnknownEngine("fail".into()),
+                ));
+            }
+            Ok(json!({ "seed": job.seed.seed, "output": job.output }))
+        })
+        .unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn answers_each_request_in_order_with_its_id() {
+        let responses = run(concat!(
+            r#"{"id": "a", "engine": "e", "width": 4, "height": 4, "params": {}, "seed": 3, "steps": 1}"#,
+            "\n\n",
+            r#"{"engine": "e", "width": 4, "height": 4, "params": {}, "seed": 5, "steps": 1, "output": "x.png"}"#,
+            "\n",
+        ));
+        assert_eq!(responses.len(), 2);
+        assert_eq!(
+            responses[0],
+            json!({ "id": "a", "ok": true, "seed": 3, "output": null })
+        );
+        assert_eq!(responses[1]["id"], Value::Null);
+        assert_eq!(responses[1]["output"], "x.png");
+    }
+
+    #[test]
+    fn failures_become_error_responses() {
+        let responses = run(concat!(
+            "not json\n",
+            r#"{"id": 2, "width": 4}"#,
+            "\n",
+            r#"{"id": 3, "engine": "e", "width": 0, "height": 4, "params": {}, "seed": 1, "steps": 1}"#,
+            "\n",
+            r#"{"id": 4, "engine": "fail", "width": 4, "height": 4, "params": {}, "seed": 1, "steps": 1}"#,
+            "\n",
+        ));
+        let codes: Vec<_> = responses.iter().map(|r| r["exit_code"].clone()).collect();
+        assert_eq!(codes, [json!(12), json!(12), json!(12), json!(10)]);
+        assert!(responses.iter().all(|r| r["ok"] == false));
+        assert_eq!(responses[1]["id"], 2);
+        assert!(responses[0]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid request"));
+    }
+}
@@ -0,0 +1,63 @@
+//! Statistics reported by `render --json`.
+//!
+//! [`FieldStats`] (from core's `observer` module) summarizes the final
+//! (downsampled) field, so a log of renders shows at a glance which ones
+//! collapsed to a flat image; [`FileStats`] records what was written, with
+//! a SHA-256 digest for checking that a re-render reproduced the same bytes.
+
+use crate::error::CliError;
+pub use art_engine_core::FieldStats;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Size and digest of a written file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileStats {
+    pub bytes: u64,
+    /// Lowercase hex SHA-256 of the contents.
+    pub sha256: String,
+}
+
+impl FileStats {
+    /// Reads `path` back and summarizes it.
+    ///
+    /// Returns `CliError::Io` if the file cannot be read.
+    pub fn of(path: &Path) -> Result<Self, CliError> {
+        let bytes =
+            std::fs::read(path).map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        Ok(Self::of_bytes(&bytes))
+    }
+
+    /// Summarizes in-memory file contents.
+    pub fn of_bytes(bytes: &[u8]) -> Self {
+        let sha256 =
+            Sha256::digest(bytes)
+                .iter()
+                .fold(String::with_capacity(64), |mut hex, byte| {
+                    let _ = write!(hex, "{byte:02x}");
+                    hex
+                });
+        Self {
+            bytes: bytes.len() as u64,
+            sha256,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stats_hash_the_contents() {
+        let stats = FileStats::of_bytes(b"abc");
+        assert_eq!(stats.bytes, 3);
+        assert_eq!(
+            stats.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(FileStats::of(Path::new("/nonexistent/file.png")).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//! Parameter sweeps: a grid of renders assembled into a labeled contact sheet.
+//!
+//! Each `--vary name=start:end:count` axis contributes `count` evenly spaced
+//! values (inclusive of both ends). The sweep renders every combination; the
+//! first axis runs along the columns and the remaining axes, in order, down
+//! the rows. Every cell is labeled with the varied values that produced it
+//! using the built-in bitmap font.
+
+use crate::batch::{run_jobs, worker_threads};
+use crate::error::CliError;
+use crate::{simulate, Sim, Simulation};
+use art_engine_core::font::{draw_text, ADVANCE, GLYPH_HEIGHT};
+use art_engine_core::{EngineError, Image, Srgb};
+use art_engine_engines::pixel::field_to_rgba_in;
+use std::path::Path;
+
+/// Largest number of cells a single sweep may render.
+pub const MAX_CELLS: usize = 1024;
+
+/// Gap between cells and around the sheet, in pixels.
+const GAP: usize = 4;
+
+/// One swept parameter and its values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vary {
+    /// Engine parameter name.
+    pub name: String,
+    /// Values to render, in order.
+    pub values: Vec<f64>,
+}
+
+/// Parses `name=start:end:count` into evenly spaced values.
+pub fn parse_vary(text: &str) -> Result<Vary, String> {
+    let usage = || format!("expected name=start:end:count, got '{text}'");
+    let (name, range) = text.split_once('=').ok_or_else(usage)?;
+    let parts: Vec<&str> = range.split(':').collect();
+    let [start, end, count] = parts[..] else {
+        return Err(usage());
+    };
+    let number = |s: &str| {
+        s.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("invalid number '{s}' in '{text}'"))
+    };
+    let (start, end) = (number(start)?, number(end)?);
+    let count: usize = count
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("count must be a positive integer in '{text}'"))?;
+    if name.trim().is_empty() {
+        return Err(usage());
+    }
+    let values = (0..count)
+        .map(|i| match count {
+            1 => start,
+            _ => start + (end - start) * i as f64 / (count - 1) as f64,
+        })
+        .collect();
+    Ok(Vary {
+        name: name.trim().to_string(),
+        values,
+    })
+}
+
+/// Returns every combination of values, first axis varying fastest.
+pub fn combinations(axes: &[Vary]) -> Vec<Vec<(&str, f64)>> {
+    axes.iter().fold(vec![Vec::new()], |acc, axis| {
+        axis.values
+            .iter()
+            .flat_map(|&v| {
+                acc.iter().map(move |combo| {
+                    let mut combo = combo.clone();
+                    combo.push((axis.name.as_str(), v));
+                    combo
+                })
+            })
+            .collect()
+    })
+}
+
+/// Formats a swept value compactly: at most 4 decimals, no trailing zeros.
+pub fn format_value(value: f64) -> String {
+    let text = format!("{value:.4}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// Shortens a `name=value` label to at most `max` characters, trimming the
+/// name before the value.
+pub fn fit_label(text: &str, max: usize) -> String {
+    let len = text.chars().count();
+    if len <= max {
+        return text.to_string();
+    }
+    match text.split_once('=') {
+        Some((name, value)) if value.chars().count() + 2 <= max => {
+            let keep = max - value.chars().count() - 1;
+            format!("{}={value}", name.chars().take(keep).collect::<String>())
+        }
+        _ => text
+            .chars()
+            .rev()
+            .take(max)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect(),
+    }
+}
+
+/// Lays equally sized cells out in `columns` columns with each cell's label
+/// lines underneath, on a dark background. Labels wider than a cell are
+/// shortened with [`fit_label`].
+///
+/// Returns `EngineError::InvalidDimensions` if there are no cells or the
+/// sheet would be too large.
+pub fn contact_sheet(
+    cells: &[Image],
+    labels: &[Vec<String>],
+    columns: usize,
+) -> Result<Image, EngineError> {
+    let first = cells.first().ok_or(EngineError::InvalidDimensions)?;
+    let (cell_w, cell_h) = (first.width(), first.height());
+    let scale = (cell_w / 128).clamp(1, 3);
+    let line_h = (GLYPH_HEIGHT + 2) * scale;
+    let max_chars = (cell_w + scale) / (ADVANCE * scale);
+    let lines = labels.iter().map(Vec::len).max().unwrap_or(0);
+    let label_h = if lines == 0 { 0 } else { lines * line_h + GAP };
+
+    let columns = columns.clamp(1, cells.len());
+    let rows = cells.len().div_ceil(columns);
+    let pitch_x = cell_w + GAP;
+    let pitch_y = cell_h + label_h + GAP;
+    let background = Srgb {
+        r: 0.08,
+        g: 0.08,
+        b: 0.08,
+    };
+    let ink = Srgb {
+        r: 0.9,
+        g: 0.9,
+        b: 0.9,
+    };
+    let mut sheet = Image::filled(
+        columns * pitch_x + GAP,
+        rows * pitch_y + GAP,
+        background,
+        1.0,
+    )?;
+    cells.iter().enumerate().for_each(|(i, cell)| {
+        let x = GAP + (i % columns) * pitch_x;
+        let y = GAP + (i / columns) * pitch_y;
+        sheet.paste(cell, x, y);
+        let label = labels.get(i).map(Vec::as_slice).unwrap_or_default();
+        label.iter().enumerate().for_each(|(line, text)| {
+            let ty = y + cell_h + GAP + line * line_h;
+            draw_text(&mut sheet, x, ty, &fit_label(text, max_chars), ink, scale);
+        });
+    });
+    Ok(sheet)
+}
+
+/// Runs `sweep`: renders `sim` once per combination of `vary` values on
+/// `threads` workers and writes the contact sheet to `output`.
+pub fn run(
+    sim: &Sim,
+    vary: &[Vary],
+    output: &Path,
+    threads: Option<u32>,
+    json: bool,
+) -> Result<(), CliError> {
+    if sim.resume.is_some() {
+        return Err(CliError::Input(
+            "sweep varies parameters, so it cannot --resume a checkpoint".into(),
+        ));
+    }
+    let base: serde_json::Value = serde_json::from_str(&sim.params)
+        .map_err(|e| CliError::Input(format!("invalid --params JSON: {e}")))?;
+    if !base.is_object() {
+        return Err(CliError::Input("--params must be a JSON object".into()));
+    }
+    let combos = combinations(vary);
+    if combos.len() > MAX_CELLS {
+        return Err(CliError::Input(format!(
+            "sweep has {} cells; the limit is {}",
+            combos.len(),
+            MAX_CELLS
+        )));
+    }
+    let results = run_jobs(
+        &combos,
+        worker_threads(threads),
+        false,
+        |_, combo| {
+            let mut params = base.clone();
+            combo.iter().for_each(|(name, value)| {
+                params[*name] = serde_json::json!(value);
+            });
+            let cell = Sim {
+                params: params.to_string(),
+                ..sim.clone()
+            };
+            let Simulation {
+                field,
+                hue,
+                palette,
+                ..
+            } = simulate(&cell)?;
+            let rgba = field_to_rgba_in(&field, &palette, hue.as_ref(), cell.color_space)?;
+            Ok(Image::from_rgba8(cell.width, cell.height, &rgba)?)
+        },
+        |_, _| {},
+    );
+    let cells = results
+        .into_iter()
+        .flatten()
+        .collect::<Result<Vec<_>, CliError>>()?;
+    let labels: Vec<Vec<String>> = combos
+        .iter()
+        .map(|combo| {
+            combo
+                .iter()
+                .map(|(name, value)| format!("{name}={}", format_value(*value)))
+                .collect()
+        })
+        .collect();
+    let sheet = contact_sheet(&cells, &labels, vary[0].values.len())?;
+    let (width, height) = (sheet.width(), sheet.height());
+    art_engine_engines::snapshot::write_rgba_png_in(
+        &sheet.to_rgba8(),
+        width,
+        height,
+        sim.color_space,
+        output,
+    )?;
+
+    if json {
+        let info = serde_json::json!({
+            "engine": sim.engine,
+            "cells": cells.len(),
+            "columns": vary[0].values.len(),
+            "axes": vary.iter().map(|v| serde_json::json!({
+                "name": v.name, "values": v.values,
+            })).collect::<Vec<_>>(),
+            "width": width,
+            "height": height,
+            "output": output.display().to_string(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        eprintln!(
+            "swept {} ({} cells) -> {}",
+            sim.engine,
+            cells.len(),
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_vary_spaces_values_inclusively() {
+        let vary = parse_vary("feed_rate=0.02:0.08:4").unwrap();
+        assert_eq!(vary.name, "feed_rate");
+        assert_eq!(vary.values.len(), 4);
+        assert!((vary.values[1] - 0.04).abs() < 1e-12);
+        assert_eq!(vary.values[3], 0.08);
+        assert_eq!(parse_vary("k=5:9:1").unwrap().values, vec![5.0]);
+    }
+
+    #[test]
+    fn parse_vary_rejects_malformed_axes() {
+        for bad in ["feed", "f=1:2", "f=1:2:0", "f=a:2:3", "=1:2:3", "f=1:inf:2"] {
+            assert!(parse_vary(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn combinations_vary_the_first_axis_fastest() {
+        let axes = [
+            parse_vary("a=0:1:2").unwrap(),
+            parse_vary("b=5:6:2").unwrap(),
+        ];
+        let combos = combinations(&axes);
+        assert_eq!(combos.len(), 4);
+        assert_eq!(combos[1], vec![("a", 1.0), ("b", 5.0)]);
+        assert_eq!(combos[2], vec![("a", 0.0), ("b", 6.0)]);
+    }
+
+    #[test]
+    fn format_value_trims_noise() {
+        assert_eq!(format_value(0.020000000000000004), "0.02");
+        assert_eq!(format_value(3.0), "3");
+        assert_eq!(format_value(-0.00001), "0");
+        assert_eq!(format_value(0.06126), "0.0613");
+    }
+
+    #[test]
+    fn fit_label_trims_the_name_first() {
+        assert_eq!(fit_label("feed_rate=0.02", 20), "feed_rate=0.02");
+        assert_eq!(fit_label("feed_rate=0.02", 8), "fee=0.02");
+        assert_eq!(fit_label("feed_rate=0.0213", 5), ".0213");
+    }
+
+    #[test]
+    fn contact_sheet_lays_out_cells_and_labels() {
+        let white = Srgb {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        };
+        let cell = Image::filled(10, 8, white, 1.0).unwrap();
+        let cells = vec![cell.clone(), cell.clone(), cell];
+        let labels = vec![vec!["A=1".to_string()]; 3];
+        let sheet = contact_sheet(&cells, &labels, 2).unwrap();
+        // 2 columns x 2 rows, one 9 px label line per cell
+        assert_eq!(sheet.width(), 2 * (10 + GAP) + GAP);
+        assert_eq!(sheet.height(), 2 * (8 + 9 + GAP + GAP) + GAP);
+        assert_eq!(sheet.pixel(GAP, GAP).unwrap(), [1.0; 4]);
+        assert!(contact_sheet(&[], &[], 2).is_err());
+    }
+}
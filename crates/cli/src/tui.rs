@@ -0,0 +1,743 @@
+//! Interactive terminal UI for exploring an engine's parameters live.
+//!
+//! Enabled by the `tui` feature. The screen is split into a half-block
+//! preview of the running simulation and a panel of parameter sliders built
+//! from the engine's `param_schema()`. Engines take their parameters at
+//! construction, so changing a slider restarts the simulation from step 0
+//! with the new values.
+//!
+//! Keys:
+//! - `↑`/`↓` (`k`/`j`): select a parameter
+//! - `←`/`→` (`h`/`l`): adjust it by 1% of its range (10% with Shift)
+//! - `d`: reset it to its default
+//! - `p`/`P`: next/previous palette
+//! - `space`: pause or resume; `.`: advance one step while paused
+//! - `+`/`-`: double or halve the steps per frame
+//! - `r`: restart; `s`: save a PNG with the seed embedded
+//! - `q`/`esc`: quit
+//!
+//! With `--record FILE`, every parameter change, palette switch and restart
+//! is written to a replay file on exit (see [`art_engine_core::replay`]),
+//! which `replay` re-renders into the identical animation.
+//!
+//! With the `control` feature, `--osc`/`--midi` controllers move the
+//! sliders (or set other params) between frames, restarting and recording
+//! like a key press would.
+
+use crate::error::CliError;
+use crate::preview::resample_box;
+use art_engine_core::replay::Action;
+use art_engine_core::{Engine, Palette, Recording, Seed};
+use art_engine_engines::pixel::field_to_rgba_with_hue;
+use art_engine_engines::EngineKind;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Widget, Wrap};
+use ratatui::Frame;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Most simulation steps run between two redraws.
+const MAX_STEPS_PER_FRAME: usize = 1024;
+
+/// Width of the slider bar in characters.
+const BAR_WIDTH: usize = 12;
+
+/// One numeric engine parameter with its allowed range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slider {
+    /// Parameter name.
+    pub name: String,
+    /// Current value.
+    pub value: f64,
+    /// Lowest allowed value.
+    pub min: f64,
+    /// Highest allowed value.
+    pub max: f64,
+    /// Schema default.
+    pub default: f64,
+    /// Schema description, if any.
+    pub description: String,
+}
+
+impl Slider {
+    /// Builds sliders for every numeric parameter with a finite range in
+    /// `schema`, starting from the values in `params`.
+    pub fn from_schema(schema: &serde_json::Value, params: &serde_json::Value) -> Vec<Slider> {
+        schema
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, spec)| {
+                let number = |key: &str| spec.get(key).and_then(serde_json::Value::as_f64);
+                let (min, max) = (number("min")?, number("max")?);
+                if !(min.is_finite() && max.is_finite() && min < max) {
+                    return None;
+                }
+                let default = number("default").unwrap_or(min).clamp(min, max);
+                let value = params
+                    .get(name)
+                    .and_then(serde_json::Value::as_f64)
+                    .unwrap_or(default)
+                    .clamp(min, max);
+                Some(Slider {
+                    name: name.clone(),
+                    value,
+                    min,
+                    max,
+                    default,
+                    description: spec
+                        .get("description")
+                        .and_then(serde_json::Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Moves the value by `fraction` of the range, clamped to the range.
+    pub fn nudge(&mut self, fraction: f64) {
+        self.value = (self.value + fraction * (self.max - self.min)).clamp(self.min, self.max);
+    }
+
+    /// Returns the value's position in the range, from 0 to 1.
+    fn position(&self) -> f64 {
+        (self.value - self.min) / (self.max - self.min)
+    }
+}
+
+/// State of an interactive session.
+pub struct App {
+    engine_name: String,
+    width: usize,
+    height: usize,
+    seed: u64,
+    base_params: serde_json::Value,
+    engine: EngineKind,
+    sliders: Vec<Slider>,
+    selected: usize,
+    palette_index: usize,
+    palette: Palette,
+    step: usize,
+    steps_per_frame: usize,
+    running: bool,
+    status: String,
+    /// Steps advanced since the session began, across restarts.
+    clock: usize,
+    started: Instant,
+    recording: Option<(Recording, PathBuf)>,
+    #[cfg(feature = "control")]
+    control: Option<crate::control::Control>,
+}
+
+impl App {
+    /// Starts `engine` at `width x height` with `params` (a JSON object).
+    ///
+    /// Returns `CliError::Input` for an unknown palette or non-object params.
+    pub fn new(
+        engine: &str,
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: serde_json::Value,
+        palette: &str,
+    ) -> Result<Self, CliError> {
+        if !params.is_object() {
+            return Err(CliError::Input("--params must be a JSON object".into()));
+        }
+        let palette_index = Palette::list_names()
+            .iter()
+            .position(|name| *name == palette)
+            .ok_or_else(|| CliError::Input(format!("unknown palette: {palette}")))?;
+        let instance = EngineKind::from_name(engine, width, height, seed, &params)?;
+        let sliders = Slider::from_schema(&instance.param_schema(), &instance.params());
+        Ok(Self {
+            engine_name: engine.to_string(),
+            width,
+            height,
+            seed,
+            base_params: params,
+            engine: instance,
+            sliders,
+            selected: 0,
+            palette_index,
+            palette: Palette::from_name(palette)?,
+            step: 0,
+            steps_per_frame: 8,
+            running: true,
+            status: String::new(),
+            clock: 0,
+            started: Instant::now(),
+            recording: None,
+            #[cfg(feature = "control")]
+            control: None,
+        })
+    }
+
+    /// Returns a copy that records its interactions to `path` on exit.
+    pub fn with_recording(mut self, path: PathBuf) -> Self {
+        let recording = Recording::new(
+            &self.engine_name,
+            self.width,
+            self.height,
+            self.seed,
+            self.params(),
+        )
+        .with_palette(Palette::list_names()[self.palette_index]);
+        self.recording = Some((recording, path));
+        self
+    }
+
+    /// Returns a copy whose params follow `control`'s controllers.
+    #[cfg(feature = "control")]
+    pub fn with_control(mut self, control: crate::control::Control) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Applies controller values that arrived since the last call.
+    ///
+    /// Values the engine rejects leave the params as they were, are
+    /// dropped from the control, and are reported in the status line.
+    #[cfg(feature = "control")]
+    pub fn poll_control(&mut self) -> Result<(), CliError> {
+        let Some(control) = self.control.clone().filter(|c| c.poll()) else {
+            return Ok(());
+        };
+        let before = (self.sliders.clone(), self.base_params.clone());
+        let current = self.params();
+        let changed: Vec<(String, serde_json::Value)> = control
+            .params(&self.engine.param_schema())
+            .into_iter()
+            .filter(|(name, value)| current.get(name) != Some(value))
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        for (name, value) in &changed {
+            match (
+                self.sliders.iter_mut().find(|s| s.name == *name),
+                value.as_f64(),
+            ) {
+                (Some(slider), Some(v)) => slider.value = v.clamp(slider.min, slider.max),
+                _ => self.base_params[name] = value.clone(),
+            }
+        }
+        if let Err(e) = self.restart() {
+            (self.sliders, self.base_params) = before;
+            changed.iter().for_each(|(name, _)| control.forget(name));
+            self.status = format!("control: {e}");
+            return Ok(());
+        }
+        let names: Vec<&str> = changed.iter().map(|(name, _)| name.as_str()).collect();
+        self.status = format!("control: set {}", names.join(", "));
+        let params = self.params();
+        for (name, _) in changed {
+            let value = params[&name].clone();
+            self.record(Action::SetParam { name, value })?;
+        }
+        Ok(())
+    }
+
+    /// Appends `action` to the recording, if there is one.
+    fn record(&mut self, action: Action) -> Result<(), CliError> {
+        if let Some((recording, _)) = &mut self.recording {
+            let ms = self.started.elapsed().as_millis() as u64;
+            recording.record(self.clock, ms, action)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the recording, if there is one, ending it at the current step.
+    pub fn save_recording(&mut self) -> Result<Option<PathBuf>, CliError> {
+        let Some((recording, path)) = &mut self.recording else {
+            return Ok(None);
+        };
+        recording.steps = self.clock;
+        std::fs::write(&*path, serde_json::to_string_pretty(recording)? + "\n")
+            .map_err(|e| CliError::Io(format!("{}: {e}", path.display())))?;
+        Ok(Some(path.clone()))
+    }
+
+    /// Returns the engine parameters: the initial params with every slider
+    /// value applied.
+    pub fn params(&self) -> serde_json::Value {
+        let mut params = self.base_params.clone();
+        if let Some(map) = params.as_object_mut() {
+            self.sliders.iter().for_each(|s| {
+                map.insert(s.name.clone(), s.value.into());
+            });
+        }
+        params
+    }
+
+    /// Rebuilds the engine from the current parameters at step 0.
+    pub fn restart(&mut self) -> Result<(), CliError> {
+        self.engine = EngineKind::from_name(
+            &self.engine_name,
+            self.width,
+            self.height,
+            self.seed,
+            &self.params(),
+        )?;
+        self.step = 0;
+        Ok(())
+    }
+
+    /// Advances the simulation by `steps`.
+    fn advance(&mut self, steps: usize) -> Result<(), CliError> {
+        (0..steps).try_for_each(|_| self.engine.step())?;
+        self.step += steps;
+        self.clock += steps;
+        Ok(())
+    }
+
+    /// Runs one frame's worth of steps unless paused.
+    pub fn tick(&mut self) -> Result<(), CliError> {
+        if self.running {
+            self.advance(self.steps_per_frame)?;
+        }
+        Ok(())
+    }
+
+    /// Applies a key press; returns `true` when the user asked to quit.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<bool, CliError> {
+        let coarse = key.modifiers.contains(KeyModifiers::SHIFT);
+        let nudge = if coarse { 0.1 } else { 0.01 };
+        let count = self.sliders.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Up | KeyCode::Char('k') if count > 0 => {
+                self.selected = (self.selected + count - 1) % count;
+            }
+            KeyCode::Down | KeyCode::Char('j') if count > 0 => {
+                self.selected = (self.selected + 1) % count;
+            }
+            KeyCode::Left | KeyCode::Char('h' | 'H') => self.adjust(|s| s.nudge(-nudge))?,
+            KeyCode::Right | KeyCode::Char('l' | 'L') => self.adjust(|s| s.nudge(nudge))?,
+            KeyCode::Char('d') => self.adjust(|s| s.value = s.default)?,
+            KeyCode::Char('p') => self.cycle_palette(1)?,
+            KeyCode::Char('P') => self.cycle_palette(Palette::list_names().len() - 1)?,
+            KeyCode::Char(' ') => self.running = !self.running,
+            KeyCode::Char('.') if !self.running => self.advance(1)?,
+            KeyCode::Char('+' | '=') => {
+                self.steps_per_frame = (self.steps_per_frame * 2).min(MAX_STEPS_PER_FRAME);
+            }
+            KeyCode::Char('-') => self.steps_per_frame = (self.steps_per_frame / 2).max(1),
+            KeyCode::Char('r') => {
+                self.record(Action::Restart)?;
+                self.restart()?;
+            }
+            KeyCode::Char('s') => {
+                self.status = match self.save() {
+                    Ok(path) => format!("saved {}", path.display()),
+                    Err(e) => format!("save failed: {e}"),
+                };
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Changes the selected slider and restarts if its value moved.
+    fn adjust(&mut self, change: impl FnOnce(&mut Slider)) -> Result<(), CliError> {
+        let Some(slider) = self.sliders.get_mut(self.selected) else {
+            return Ok(());
+        };
+        let before = slider.value;
+        change(slider);
+        if slider.value != before {
+            let action = Action::SetParam {
+                name: slider.name.clone(),
+                value: slider.value.into(),
+            };
+            self.record(action)?;
+            self.restart()?;
+        }
+        Ok(())
+    }
+
+    /// Moves `offset` places through the built-in palettes.
+    fn cycle_palette(&mut self, offset: usize) -> Result<(), CliError> {
+        let names = Palette::list_names();
+        self.palette_index = (self.palette_index + offset) % names.len();
+        self.palette = Palette::from_name(names[self.palette_index])?;
+        self.record(Action::SetPalette {
+            palette: names[self.palette_index].to_string(),
+        })
+    }
+
+    /// Writes the current state as a PNG with a seed that reproduces it.
+    fn save(&self) -> Result<PathBuf, CliError> {
+        let path = PathBuf::from(format!(
+            "tui_{}_{}_{:06}.png",
+            self.engine_name, self.seed, self.step
+        ));
+        let seed = Seed {
+            steps: self.step,
+            params: self.params(),
+            palette: Some(Palette::list_names()[self.palette_index].to_string()),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            ..Seed::new(&self.engine_name, self.width, self.height, self.seed)
+        };
+        let rgba =
+            field_to_rgba_with_hue(self.engine.field(), &self.palette, self.engine.hue_field())?;
+        art_engine_engines::snapshot::write_rgba_png_with_seed(
+            &rgba,
+            self.width,
+            self.height,
+            &seed,
+            &path,
+        )?;
+        Ok(path)
+    }
+
+    /// Draws the preview and the control panel.
+    fn draw(&self, frame: &mut Frame) {
+        let [preview, panel] =
+            Layout::horizontal([Constraint::Min(10), Constraint::Length(44)]).areas(frame.area());
+        let block = Block::bordered().title(format!(" {} ", self.engine_name));
+        let inner = block.inner(preview);
+        frame.render_widget(block, preview);
+        frame.render_widget(
+            Preview {
+                engine: &self.engine,
+                palette: &self.palette,
+            },
+            inner,
+        );
+        frame.render_widget(
+            Paragraph::new(self.panel_lines())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" parameters ")),
+            panel,
+        );
+    }
+
+    /// Returns the text of the control panel.
+    fn panel_lines(&self) -> Vec<Line<'_>> {
+        let name_width = self.sliders.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        let mut lines: Vec<Line> = self
+            .sliders
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let filled = (s.position() * BAR_WIDTH as f64).round() as usize;
+                let text = format!(
+                    "{:<name_width$} {:>8.4} {}{}",
+                    s.name,
+                    s.value,
+                    "█".repeat(filled),
+                    "░".repeat(BAR_WIDTH - filled)
+                );
+                let style = match i == self.selected {
+                    true => Style::default().add_modifier(Modifier::REVERSED),
+                    false => Style::default(),
+                };
+                Line::styled(text, style)
+            })
+            .collect();
+        if let Some(slider) = self.sliders.get(self.selected) {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                format!("{} ({}..{})", slider.description, slider.min, slider.max),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        lines.extend([
+            Line::default(),
+            Line::from(format!(
+                "palette {}",
+                Palette::list_names()[self.palette_index]
+            )),
+            Line::from(format!(
+                "step {} ({} per frame, {})",
+                self.step,
+                self.steps_per_frame,
+                if self.running { "running" } else { "paused" }
+            )),
+            Line::styled(self.status.clone(), Style::default().fg(Color::Yellow)),
+            Line::default(),
+            Line::styled(
+                "↑↓ select  ←→ adjust  d default  p palette  space pause  . step  +- speed  r restart  s save  q quit",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        lines
+    }
+}
+
+/// Half-block rendering of an engine's current field, scaled to fit.
+struct Preview<'a> {
+    engine: &'a EngineKind,
+    palette: &'a Palette,
+}
+
+impl Widget for Preview<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let field = self.engine.field();
+        let (cols, rows) = (usize::from(area.width), usize::from(area.height) * 2);
+        if cols == 0 || rows == 0 {
+            return;
+        }
+        // Fit the field into cols x rows pixels, preserving the aspect ratio
+        let scale = (cols as f64 / field.width() as f64).min(rows as f64 / field.height() as f64);
+        let w = ((field.width() as f64 * scale) as usize).clamp(1, cols);
+        let h = ((field.height() as f64 * scale) as usize).clamp(1, rows);
+        let Ok(small) = resample_box(field, w, h) else {
+            return;
+        };
+        let hue = self
+            .engine
+            .hue_field()
+            .and_then(|f| resample_box(f, w, h).ok());
+        let Ok(rgba) = field_to_rgba_with_hue(&small, self.palette, hue.as_ref()) else {
+            return;
+        };
+        let color = |x: usize, y: usize| {
+            let i = (y * w + x) * 4;
+            Color::Rgb(rgba[i], rgba[i + 1], rgba[i + 2])
+        };
+        for (row, y) in (0..h).step_by(2).enumerate() {
+            for x in 0..w {
+                let position = (area.x + x as u16, area.y + row as u16);
+                if let Some(cell) = buf.cell_mut(position) {
+                    cell.set_char('▀').set_fg(color(x, y));
+                    if y + 1 < h {
+                        cell.set_bg(color(x, y + 1));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs the interactive session until the user quits.
+///
+/// The terminal is restored before returning, including on errors, and
+/// any recording is written afterwards.
+pub fn run(mut app: App) -> Result<(), CliError> {
+    let mut terminal = ratatui::try_init().map_err(|e| CliError::Io(format!("terminal: {e}")))?;
+    let result = event_loop(&mut app, &mut terminal);
+    ratatui::restore();
+    let saved = app.save_recording();
+    result?;
+    if let Some(path) = saved? {
+        eprintln!("recorded session -> {}", path.display());
+    }
+    Ok(())
+}
+
+fn event_loop(app: &mut App, terminal: &mut ratatui::DefaultTerminal) -> Result<(), CliError> {
+    let io_err = |e: std::io::Error| CliError::Io(format!("terminal: {e}"));
+    loop {
+        terminal.draw(|frame| app.draw(frame)).map_err(io_err)?;
+        let wait = Duration::from_millis(if app.running { 16 } else { 250 });
+        if event::poll(wait).map_err(io_err)? {
+            if let Event::Key(key) = event::read().map_err(io_err)? {
+                if key.kind == KeyEventKind::Press && app.handle_key(key)? {
+                    return Ok(());
+                }
+            }
+        }
+        #[cfg(feature = "control")]
+        app.poll_control()?;
+        app.tick()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        App::new("gray-scott", 16, 16, 7, serde_json::json!({}), "ocean").unwrap()
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn sliders_follow_the_schema_and_params() {
+        let schema = serde_json::json!({
+            "rate": {"type": "number", "default": 0.5, "min": 0.0, "max": 1.0},
+            "name": {"type": "string", "default": "x"},
+            "flat": {"type": "number", "min": 1.0, "max": 1.0},
+        });
+        let sliders = Slider::from_schema(&schema, &serde_json::json!({"rate": 2.0}));
+        assert_eq!(sliders.len(), 1);
+        assert_eq!(sliders[0].value, 1.0);
+        assert_eq!(sliders[0].default, 0.5);
+    }
+
+    #[test]
+    fn nudge_clamps_to_the_range() {
+        let mut slider = Slider {
+            name: "rate".into(),
+            value: 0.5,
+            min: 0.0,
+            max: 2.0,
+            default: 0.5,
+            description: String::new(),
+        };
+        slider.nudge(0.1);
+        assert!((slider.value - 0.7).abs() < 1e-12);
+        slider.nudge(-5.0);
+        assert_eq!(slider.value, 0.0);
+    }
+
+    #[test]
+    fn adjusting_a_parameter_restarts_the_simulation() {
+        let mut app = app();
+        app.tick().unwrap();
+        assert_eq!(app.step, 8);
+        let name = app.sliders[0].name.clone();
+        let before = app.params()[&name].as_f64().unwrap();
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.step, 0);
+        assert!(app.params()[&name].as_f64().unwrap() < before);
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.params()[&name].as_f64().unwrap(), before);
+    }
+
+    #[test]
+    fn keys_control_stepping_and_palette() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char(' '));
+        app.tick().unwrap();
+        assert_eq!(app.step, 0);
+        press(&mut app, KeyCode::Char('.'));
+        assert_eq!(app.step, 1);
+        press(&mut app, KeyCode::Char('+'));
+        assert_eq!(app.steps_per_frame, 16);
+        press(&mut app, KeyCode::Char('p'));
+        press(&mut app, KeyCode::Char('P'));
+        assert_eq!(Palette::list_names()[app.palette_index], "ocean");
+        assert!(press(&mut app, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn recorded_sessions_replay_to_the_same_field() {
+        let path = std::env::temp_dir().join(format!("art-engine-rec-{}.json", std::process::id()));
+        let mut app = app().with_recording(path.clone());
+        app.tick().unwrap();
+        press(&mut app, KeyCode::Right);
+        app.tick().unwrap();
+        press(&mut app, KeyCode::Char('p'));
+        app.tick().unwrap();
+        press(&mut app, KeyCode::Char('r'));
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char('.'));
+        app.tick().unwrap();
+        assert_eq!(app.save_recording().unwrap(), Some(path.clone()));
+
+        let recording = Recording::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(recording.steps, 25);
+        assert_eq!(recording.events.len(), 3);
+        let mut last = None;
+        recording
+            .replay(
+                |params| EngineKind::from_name("gray-scott", 16, 16, 7, params),
+                |step, engine, palette| {
+                    if step == recording.steps {
+                        last = Some((engine.field().clone(), palette.map(str::to_string)));
+                    }
+                    Ok::<_, art_engine_core::EngineError>(())
+                },
+            )
+            .unwrap();
+        let (field, palette) = last.unwrap();
+        assert_eq!(field.data(), app.engine.field().data());
+        assert_eq!(
+            palette.as_deref(),
+            Some(Palette::list_names()[app.palette_index])
+        );
+    }
+
+    #[cfg(feature = "control")]
+    #[test]
+    fn controllers_move_sliders_and_restart() {
+        use crate::control::{Control, ControlMap, Message, OscArg};
+        let map: ControlMap = serde_json::from_value(serde_json::json!({
+            "routes": [{ "osc": "/feed", "param": "feed_rate" }]
+        }))
+        .unwrap();
+        let control = Control::new(map);
+        let path = std::env::temp_dir().join(format!("art-engine-ctl-{}.json", std::process::id()));
+        let mut app = App::new(
+            "gray-scott-advected",
+            16,
+            16,
+            7,
+            serde_json::json!({}),
+            "ocean",
+        )
+        .unwrap()
+        .with_control(control.clone())
+        .with_recording(path);
+        let send = |address: &str, arg| {
+            control.apply(&Message::Osc {
+                address: address.into(),
+                args: vec![arg],
+            });
+        };
+        app.tick().unwrap();
+        app.poll_control().unwrap();
+        assert_eq!(app.step, 8);
+
+        // The full slider range, from the schema
+        send("/feed", OscArg::Float(1.0));
+        app.poll_control().unwrap();
+        let slider = app.sliders.iter().find(|s| s.name == "feed_rate").unwrap();
+        assert_eq!(slider.value, slider.max);
+        assert_eq!(app.step, 0);
+        assert_eq!(app.recording.as_ref().unwrap().0.events.len(), 1);
+
+        // A value the engine rejects is reported and rolled back
+        app.tick().unwrap();
+        send("/param/velocity", OscArg::Str("gale".into()));
+        app.poll_control().unwrap();
+        assert!(app.status.starts_with("control:"), "{}", app.status);
+        assert_eq!(app.params().get("velocity"), None);
+        assert_eq!(app.step, 8);
+        assert_eq!(app.recording.as_ref().unwrap().0.events.len(), 1);
+        send("/feed", OscArg::Float(0.0));
+        app.poll_control().unwrap();
+        assert_eq!(app.status, "control: set feed_rate");
+        assert_eq!(app.recording.as_ref().unwrap().0.events.len(), 2);
+    }
+
+    #[test]
+    fn preview_fills_cells_with_half_blocks() {
+        let app = app();
+        let area = Rect::new(0, 0, 8, 4);
+        let mut buf = Buffer::empty(area);
+        Preview {
+            engine: &app.engine,
+            palette: &app.palette,
+        }
+        .render(area, &mut buf);
+        assert_eq!(buf[(0, 0)].symbol(), "▀");
+        assert_eq!(buf[(7, 3)].symbol(), "▀");
+    }
+
+    #[test]
+    fn rejects_unknown_palette_and_bad_params() {
+        let new = |params, palette| App::new("gray-scott", 8, 8, 1, params, palette);
+        assert!(matches!(
+            new(serde_json::json!({}), "nope"),
+            Err(CliError::Input(_))
+        ));
+        assert!(matches!(
+            new(serde_json::json!([1]), "ocean"),
+            Err(CliError::Input(_))
+        ));
+    }
+}
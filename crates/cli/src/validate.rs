@@ -0,0 +1,358 @@
+//! Static checks for seed, job list and project files.
+//!
+//! [`validate`] parses a document and collects every problem rather than
+//! stopping at the first: unknown engines, parameters the engine does not
+//! declare, values of the wrong type or outside the schema's `min`/`max`,
+//! and palettes that are neither built in, valid inline colors nor
+//! defined by the project. Each
+//! [`Issue`] carries the [`CliError`] (and so the exit code) the problem
+//! would cause when rendering.
+
+use crate::batch::BatchJob;
+use crate::error::CliError;
+use crate::has_extension;
+use art_engine_core::{Engine, LayerContent, Project};
+use art_engine_engines::EngineKind;
+use serde_json::Value;
+use std::path::Path;
+
+/// One problem found in a document.
+#[derive(Debug)]
+pub struct Issue {
+    /// Where the problem is, e.g. `layer 'bg': params.feed_rate`.
+    pub location: String,
+    /// The error rendering would report.
+    pub error: CliError,
+}
+
+/// What a document was recognized as, and its problems.
+#[derive(Debug)]
+pub struct Report {
+    /// `"seed"`, `"job list"` or `"project"`.
+    pub kind: &'static str,
+    /// Seeds, jobs or engine layers checked.
+    pub items: usize,
+    /// Problems found, in document order.
+    pub issues: Vec<Issue>,
+}
+
+/// Checks a document: a seed (a JSON object with `engine`), a job list (a
+/// JSON array of seeds) or a project (JSON with `canvas`, or TOML).
+///
+/// Returns `CliError::Input` if the text cannot be read as any of them.
+pub fn validate(text: &str, toml: bool) -> Result<Report, CliError> {
+    if toml {
+        return check_project(text, true);
+    }
+    let value: Value =
+        serde_json::from_str(text).map_err(|e| CliError::Input(format!("invalid JSON: {e}")))?;
+    match &value {
+        Value::Object(object) if object.contains_key("canvas") => check_project(text, false),
+        Value::Object(_) => Ok(Report {
+            kind: "seed",
+            items: 1,
+            issues: check_job(String::new(), value),
+        }),
+        Value::Array(entries) => Ok(Report {
+            kind: "job list",
+            items: entries.len(),
+            issues: entries
+                .iter()
+                .enumerate()
+                .flat_map(|(i, entry)| check_job(format!("[{i}]"), entry.clone()))
+                .collect(),
+        }),
+        _ => Err(CliError::Input(
+            "expected a seed object, an array of seeds or a project".into(),
+        )),
+    }
+}
+
+/// Checks one seed or batch job; `prefix` locates it in the document.
+fn check_job(prefix: String, value: Value) -> Vec<Issue> {
+    let at = |field: &str| match (prefix.as_str(), field) {
+        ("", field) => field.to_string(),
+        (prefix, "") => prefix.to_string(),
+        (prefix, field) => format!("{prefix}.{field}"),
+    };
+    let job: BatchJob = match serde_json::from_value(value) {
+        Ok(job) => job,
+        Err(e) => {
+            return vec![Issue {
+                location: at(""),
+                error: CliError::Input(format!("invalid seed: {e}")),
+            }]
+        }
+    };
+    let mut issues = Vec::new();
+    if let Err(e) = job.seed.validate() {
+        issues.push(Issue {
+            location: at(""),
+            error: CliError::Input(format!("invalid seed: {e}")),
+        });
+    }
+    issues.extend(check_params(&at, &job.seed.engine, &job.seed.params));
+    if let Some(Err(error)) = job.seed.palette.as_deref().map(crate::parse_palette) {
+        issues.push(Issue {
+            location: at("palette"),
+            error,
+        });
+    }
+    issues
+}
+
+/// Checks a project's structure, then every engine layer's binding.
+fn check_project(text: &str, toml: bool) -> Result<Report, CliError> {
+    let project: Project = crate::parse_project(text, toml)
+        .map_err(|e| CliError::Input(format!("invalid project: {e}")))?;
+    let mut items = 0;
+    let mut issues = Vec::new();
+    project.canvas.layers().for_each(|layer| {
+        let Some(LayerContent::Engine(binding)) = layer.content() else {
+            return;
+        };
+        items += 1;
+        let at = |field: &str| format!("layer '{}': {field}", layer.name());
+        issues.extend(check_params(&at, &binding.engine, &binding.params));
+        if let Err(e) = project.palette(&binding.palette) {
+            issues.push(Issue {
+                location: at("palette"),
+                error: CliError::Input(e.to_string()),
+            });
+        }
+    });
+    Ok(Report {
+        kind: "project",
+        items,
+        issues,
+    })
+}
+
+/// Checks that `engine` exists and `params` match its schema. `at` turns a
+/// field path into a location.
+fn check_params(at: &dyn Fn(&str) -> String, engine: &str, params: &Value) -> Vec<Issue> {
+    // The schema does not depend on size or seed; build a tiny instance
+    let schema = match EngineKind::from_name(engine, 8, 8, 0, &serde_json::json!({})) {
+        Ok(instance) => instance.param_schema(),
+        Err(e) => {
+            return vec![Issue {
+                location: at("engine"),
+                error: CliError::Engine(e),
+            }]
+        }
+    };
+    let Some(params) = params.as_object() else {
+        return vec![Issue {
+            location: at("params"),
+            error: CliError::Input("params must be a JSON object".into()),
+        }];
+    };
+    params
+        .iter()
+        .filter_map(|(name, value)| {
+            let problem = param_problem(engine, name, value, schema.get(name))?;
+            Some(Issue {
+                location: at(&format!("params.{name}")),
+                error: CliError::Input(problem),
+            })
+        })
+        .collect()
+}
+
+/// Describes what is wrong with one parameter value, if anything.
+fn param_problem(engine: &str, name: &str, value: &Value, spec: Option<&Value>) -> Option<String> {
+    let Some(spec) = spec else {
+        return Some(format!("{engine} has no parameter '{name}'"));
+    };
+    let expected = spec.get("type").and_then(Value::as_str).unwrap_or("any");
+    let type_ok = match expected {
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        _ => true,
+    };
+    if !type_ok {
+        return Some(format!("expected {expected}, got {value}"));
+    }
+    let number = value.as_f64()?;
+    let bound = |key: &str| spec.get(key).and_then(Value::as_f64);
+    match (bound("min"), bound("max")) {
+        (Some(min), _) if number < min => Some(format!("{number} is below the minimum {min}")),
+        (_, Some(max)) if number > max => Some(format!("{number} is above the maximum {max}")),
+        _ => None,
+    }
+}
+
+/// Runs `validate` on `file`, printing the report.
+///
+/// Returns the error of the first problem found, so the exit code is the
+/// one rendering would fail with.
+pub fn run(file: &Path, json: bool) -> Result<(), CliError> {
+    let text = std::fs::read_to_string(file)
+        .map_err(|e| CliError::Io(format!("{}: {e}", file.display())))?;
+    let report = validate(&text, has_extension(file, "toml")).map_err(|e| match e {
+        CliError::Input(msg) => CliError::Input(format!("{}: {msg}", file.display())),
+        other => other,
+    })?;
+    if json {
+        let info = serde_json::json!({
+            "file": file.display().to_string(),
+            "kind": report.kind,
+            "items": report.items,
+            "valid": report.issues.is_empty(),
+            "issues": report.issues.iter().map(|issue| serde_json::json!({
+                "location": issue.location,
+                "error": issue.error.to_string(),
+                "exit_code": issue.error.exit_code(),
+            })).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else if report.issues.is_empty() {
+        println!(
+            "{}: valid {} ({} checked)",
+            file.display(),
+            report.kind,
+            report.items
+        );
+    } else {
+        println!(
+            "{}: {} problem(s) in {}",
+            file.display(),
+            report.issues.len(),
+            report.kind
+        );
+        report
+            .issues
+            .iter()
+            .for_each(|issue| match issue.location.as_str() {
+                "" => println!("  {}", issue.error),
+                location => println!("  {location}: {}", issue.error),
+            });
+    }
+    // Exit with the code of the first problem
+    if let Some(issue) = report.issues.into_iter().next() {
+        return Err(match issue.error {
+            CliError::Input(msg) if !issue.location.is_empty() => {
+                CliError::Input(format!("{}: {msg}", issue.location))
+            }
+            other => other,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locations(report: &Report) -> Vec<&str> {
+        report.issues.iter().map(|i| i.location.as_str()).collect()
+    }
+
+    #[test]
+    fn valid_seed_has_no_issues() {
+        let report = validate(
+            r#"{"engine": "gray-scott", "width": 8, "height": 8, "seed": 1, "steps": 10,
+                "params": {"feed_rate": 0.05}, "palette": "fire"}"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(report.kind, "seed");
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn seed_problems_are_collected_with_exit_codes() {
+        let report = validate(
+            r#"{"engine": "gray-scott", "width": 8, "height": 8, "seed": 1, "steps": 10,
+                "params": {"feed_rate": 0.5, "dt": "fast", "speed": 1}, "palette": "nope"}"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            locations(&report),
+            ["params.dt", "params.feed_rate", "params.speed", "palette"]
+        );
+        assert!(report.issues[1]
+            .error
+            .to_string()
+            .contains("above the maximum"));
+        assert!(report.issues.iter().all(|i| i.error.exit_code() == 12));
+
+        let report = validate(
+            r#"{"engine": "nope", "width": 8, "height": 8, "seed": 1, "steps": 10, "params": {}}"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(locations(&report), ["engine"]);
+        assert_eq!(report.issues[0].error.exit_code(), 10);
+    }
+
+    #[test]
+    fn job_lists_locate_issues_by_index() {
+        let report = validate(
+            r#"[{"engine": "gray-scott", "width": 8, "height": 8, "seed": 1, "steps": 10,
+                 "params": {}, "output": "a.png"},
+                {"engine": "gray-scott", "width": 0, "height": 8, "seed": 2, "steps": 10,
+                 "params": {}},
+                {"width": 8}]"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(report.kind, "job list");
+        assert_eq!(report.items, 3);
+        assert_eq!(locations(&report), ["[1]", "[2]"]);
+    }
+
+    #[test]
+    fn projects_check_each_engine_layer() {
+        let report = validate(
+            r##"
+            version = 1
+
+            [palettes]
+            dusk = ["#000000", "#ffffff"]
+
+            [canvas]
+            width = 16
+            height = 16
+            background = "#000000"
+
+            [[canvas.layers]]
+            name = "ok"
+            blend_mode = "normal"
+            opacity = 1.0
+            visible = true
+            content_type = "field"
+            content = { kind = "engine", engine = "gray-scott", seed = 1, palette = "dusk" }
+
+            [[canvas.layers]]
+            name = "bad"
+            blend_mode = "normal"
+            opacity = 1.0
+            visible = true
+            content_type = "field"
+            content = { kind = "engine", engine = "gray-scott", seed = 1, palette = "dawn", params = { kill_rate = -1 } }
+        "##,
+            true,
+        )
+        .unwrap();
+        assert_eq!(report.kind, "project");
+        assert_eq!(report.items, 2);
+        assert_eq!(
+            locations(&report),
+            ["layer 'bad': params.kill_rate", "layer 'bad': palette"]
+        );
+    }
+
+    #[test]
+    fn unreadable_documents_are_input_errors() {
+        assert!(matches!(validate("{", false), Err(CliError::Input(_))));
+        assert!(matches!(validate("3", false), Err(CliError::Input(_))));
+        assert!(matches!(
+            validate(r#"{"version": 9, "canvas": {}}"#, false),
+            Err(CliError::Input(_))
+        ));
+    }
+}
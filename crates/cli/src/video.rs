@@ -0,0 +1,280 @@
+//! Direct MP4/WebM encoding by piping frames into an `ffmpeg` subprocess.
+//!
+//! Enabled by the `video` feature. Frames are streamed as raw RGBA8 on
+//! ffmpeg's stdin (see [`RawFormat::Rgba`]), so no intermediate PNGs are
+//! written. The container is chosen from the output extension:
+//! - `.mp4`: H.264 (`libx264`, yuv420p, CRF 0-51, default 23)
+//! - `.webm`: VP9 (`libvpx-vp9`, yuv420p, CRF 0-63, default 31)
+//!
+//! The ffmpeg binary is looked up on `PATH`, or taken from the
+//! `ART_ENGINE_FFMPEG` environment variable when set.
+
+use crate::error::CliError;
+use crate::raw::{RawFormat, RawWriter};
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Video codec, chosen from the output file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoCodec {
+    /// H.264 in an MP4 container.
+    H264,
+    /// VP9 in a WebM container.
+    Vp9,
+}
+
+impl VideoCodec {
+    /// Picks the codec for `.mp4` or `.webm` output (case insensitive).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "mp4" => Some(VideoCodec::H264),
+            "webm" => Some(VideoCodec::Vp9),
+            _ => None,
+        }
+    }
+
+    /// Returns the ffmpeg encoder name.
+    fn encoder(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::Vp9 => "libvpx-vp9",
+        }
+    }
+
+    /// Returns the default constant rate factor.
+    pub fn default_crf(self) -> u32 {
+        match self {
+            VideoCodec::H264 => 23,
+            VideoCodec::Vp9 => 31,
+        }
+    }
+
+    /// Returns the highest accepted constant rate factor.
+    pub fn max_crf(self) -> u32 {
+        match self {
+            VideoCodec::H264 => 51,
+            VideoCodec::Vp9 => 63,
+        }
+    }
+}
+
+/// Builds the ffmpeg argument list for encoding raw RGBA8 frames from stdin.
+///
+/// Odd dimensions are padded to even, which yuv420p requires.
+fn ffmpeg_args(
+    codec: VideoCodec,
+    width: usize,
+    height: usize,
+    fps: u32,
+    crf: u32,
+    output: &Path,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = [
+        "-y",
+        "-loglevel",
+        "error",
+        "-f",
+        "rawvideo",
+        "-pix_fmt",
+        "rgba",
+        "-s",
+        &format!("{width}x{height}"),
+        "-r",
+        &fps.to_string(),
+        "-i",
+        "-",
+        "-vf",
+        "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+        "-c:v",
+        codec.encoder(),
+        "-pix_fmt",
+        "yuv420p",
+        "-crf",
+        &crf.to_string(),
+    ]
+    .iter()
+    .map(OsString::from)
+    .collect();
+    if codec == VideoCodec::Vp9 {
+        // VP9 only honors CRF in constant-quality mode
+        args.extend(["-b:v", "0"].map(OsString::from));
+    }
+    args.push(output.as_os_str().to_owned());
+    args
+}
+
+/// A running ffmpeg process accepting RGBA8 frames.
+///
+/// An encoder dropped without [`VideoEncoder::finish`], as when a run fails
+/// partway, kills ffmpeg and reaps it rather than leaving it running.
+pub struct VideoEncoder {
+    child: Child,
+    writer: Option<RawWriter<ChildStdin>>,
+    frames: usize,
+}
+
+impl VideoEncoder {
+    /// Starts ffmpeg writing `output` from `width x height` frames.
+    ///
+    /// Returns `CliError::Input` for an unsupported extension or a CRF out
+    /// of range for the codec (`None` picks the codec default), and
+    /// `CliError::Io` if ffmpeg cannot be started.
+    pub fn spawn(
+        output: &Path,
+        width: usize,
+        height: usize,
+        fps: u32,
+        crf: Option<u32>,
+    ) -> Result<Self, CliError> {
+        let codec = VideoCodec::from_path(output).ok_or_else(|| {
+            CliError::Input(format!(
+                "unsupported video format {} (use .mp4 or .webm)",
+                output.display()
+            ))
+        })?;
+        let crf = crf.unwrap_or(codec.default_crf());
+        if crf > codec.max_crf() {
+            return Err(CliError::Input(format!(
+                "--crf {crf} out of range for {} (0-{})",
+                codec.encoder(),
+                codec.max_crf()
+            )));
+        }
+        let program = std::env::var_os("ART_ENGINE_FFMPEG").unwrap_or_else(|| "ffmpeg".into());
+        let child = Command::new(&program)
+            .args(ffmpeg_args(codec, width, height, fps, crf, output))
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                CliError::Io(format!("cannot start {}: {e}", program.to_string_lossy()))
+            })?;
+        Self::attach(child, width, height, fps)
+    }
+
+    /// Wraps a spawned child whose stdin is piped.
+    fn attach(mut child: Child, width: usize, height: usize, fps: u32) -> Result<Self, CliError> {
+        let Some(stdin) = child.stdin.take() else {
+            // Reap the child before reporting
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CliError::Io("ffmpeg stdin unavailable".into()));
+        };
+        Ok(Self {
+            child,
+            writer: Some(RawWriter::new(stdin, RawFormat::Rgba, width, height, fps)),
+            frames: 0,
+        })
+    }
+
+    /// Returns the number of frames sent so far.
+    pub fn len(&self) -> usize {
+        self.frames
+    }
+
+    /// Sends one RGBA8 frame to the encoder.
+    pub fn write_frame(&mut self, rgba: &[u8]) -> Result<(), CliError> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| CliError::Io("video encoder already finished".into()))?;
+        writer
+            .write_frame(rgba)
+            .map_err(|e| CliError::Io(format!("ffmpeg: {e}")))?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Closes ffmpeg's input and waits for it to finish writing the file.
+    ///
+    /// Returns `CliError::Io` if ffmpeg exits unsuccessfully.
+    pub fn finish(mut self) -> Result<(), CliError> {
+        // Dropping stdin signals end of stream
+        drop(self.writer.take());
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| CliError::Io(format!("ffmpeg: {e}")))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(CliError::Io(format!("ffmpeg exited with {status}")))
+        }
+    }
+}
+
+impl Drop for VideoEncoder {
+    fn drop(&mut self) {
+        // `finish` takes the writer; anything else is an abandoned encode
+        if self.writer.take().is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_follows_extension() {
+        assert_eq!(
+            VideoCodec::from_path(Path::new("out.MP4")),
+            Some(VideoCodec::H264)
+        );
+        assert_eq!(
+            VideoCodec::from_path(Path::new("a/out.webm")),
+            Some(VideoCodec::Vp9)
+        );
+        assert_eq!(VideoCodec::from_path(Path::new("out.gif")), None);
+        assert_eq!(VideoCodec::from_path(Path::new("mp4")), None);
+    }
+
+    #[test]
+    fn ffmpeg_args_describe_raw_input_and_codec() {
+        let args = ffmpeg_args(VideoCodec::Vp9, 64, 48, 24, 30, Path::new("o.webm"));
+        let args: Vec<&str> = args.iter().map(|a| a.to_str().unwrap()).collect();
+        let after = |flag: &str| args[args.iter().position(|a| *a == flag).unwrap() + 1];
+        assert_eq!(after("-s"), "64x48");
+        assert_eq!(after("-r"), "24");
+        assert_eq!(after("-c:v"), "libvpx-vp9");
+        assert_eq!(after("-crf"), "30");
+        assert_eq!(after("-b:v"), "0");
+        assert_eq!(args.last(), Some(&"o.webm"));
+
+        let args = ffmpeg_args(VideoCodec::H264, 2, 2, 30, 18, Path::new("o.mp4"));
+        assert!(!args.iter().any(|a| a == "-b:v"));
+    }
+
+    #[test]
+    fn spawn_rejects_bad_extension_and_crf() {
+        assert!(matches!(
+            VideoEncoder::spawn(Path::new("o.avi"), 2, 2, 30, None),
+            Err(CliError::Input(_))
+        ));
+        assert!(matches!(
+            VideoEncoder::spawn(Path::new("o.mp4"), 2, 2, 30, Some(52)),
+            Err(CliError::Input(msg)) if msg.contains("0-51")
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dropping_an_unfinished_encoder_kills_ffmpeg() {
+        let child = Command::new("sleep")
+            .arg("30")
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let start = std::time::Instant::now();
+        drop(VideoEncoder::attach(child, 2, 2, 30).unwrap());
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        if cfg!(target_os = "linux") {
+            // Reaped, not left as a zombie
+            assert!(!Path::new(&format!("/proc/{pid}")).exists());
+        }
+    }
+}
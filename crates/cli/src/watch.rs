@@ -0,0 +1,437 @@
+//! Watch mode: re-render a quick preview whenever a config file changes.
+//!
+//! The watched file is polled for changes to its modification time and
+//! length; a change is acted on once the file has been stable for the
+//! debounce interval, so editors that write in several steps trigger one
+//! render. The config is classified by content (see [`WatchConfig`]) and
+//! each preview overwrites the same output path via a rename, so an
+//! auto-reloading image viewer never sees a half-written file.
+
+#[cfg(feature = "control")]
+use crate::control::{steer_params, Control};
+use crate::error::CliError;
+use crate::{has_extension, render_png, Sim};
+use art_engine_core::color::ColorSpace;
+use art_engine_core::{MemoryBudget, Project, ResizePolicy, Seed, SeedValue};
+use art_engine_engines::composition::Composition;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// What a watched file describes.
+#[derive(Debug)]
+pub enum WatchConfig {
+    /// A multi-layer project (an object with a `canvas`, or any `.toml` file).
+    Project(Box<Project>),
+    /// A full seed (an object with an `engine`).
+    Seed(Seed),
+    /// Bare engine parameters, rendered with the engine named on the
+    /// command line.
+    Params(serde_json::Value),
+}
+
+impl WatchConfig {
+    /// Classifies and parses config text; `.toml` text is always a project.
+    pub fn parse(text: &str, toml: bool) -> Result<Self, String> {
+        if toml {
+            return crate::parse_project(text, true).map(|p| WatchConfig::Project(Box::new(p)));
+        }
+        let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let Some(object) = value.as_object() else {
+            return Err("expected a JSON object".into());
+        };
+        if object.contains_key("canvas") {
+            crate::parse_project(text, false).map(|p| WatchConfig::Project(Box::new(p)))
+        } else if object.contains_key("engine") {
+            let seed: Seed = serde_json::from_value(value).map_err(|e| e.to_string())?;
+            seed.validate().map_err(|e| e.to_string())?;
+            Ok(WatchConfig::Seed(seed))
+        } else {
+            Ok(WatchConfig::Params(value))
+        }
+    }
+}
+
+/// Scales `width x height` down to fit within `max` on the longest side,
+/// keeping the aspect ratio; sizes that already fit are unchanged.
+pub fn fit_within(width: usize, height: usize, max: usize) -> (usize, usize) {
+    let longest = width.max(height);
+    if longest <= max {
+        return (width, height);
+    }
+    let scale = |n: usize| ((n as f64 * max as f64 / longest as f64).round() as usize).max(1);
+    (scale(width), scale(height))
+}
+
+/// Identity of a file's current contents, as far as polling can tell.
+pub type Stamp = (SystemTime, u64);
+
+/// Reads the stamp of `path`, or `None` if it is missing or unreadable.
+pub fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Turns a stream of file stamps into debounced change events.
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    settled: Option<Stamp>,
+    pending: Option<(Stamp, Instant)>,
+}
+
+impl Debouncer {
+    /// Creates a debouncer that treats `initial` as already handled.
+    pub fn new(delay: Duration, initial: Option<Stamp>) -> Self {
+        Self {
+            delay,
+            settled: initial,
+            pending: None,
+        }
+    }
+
+    /// Records the stamp observed at `now`; returns `true` once a new stamp
+    /// has held for the full delay. A missing file is ignored, since editors
+    /// often save by replacing the file.
+    pub fn observe(&mut self, stamp: Option<Stamp>, now: Instant) -> bool {
+        let Some(stamp) = stamp else {
+            return false;
+        };
+        if Some(stamp) == self.settled {
+            self.pending = None;
+            return false;
+        }
+        match self.pending {
+            Some((pending, since)) if pending == stamp => {
+                if now.duration_since(since) >= self.delay {
+                    self.settled = Some(stamp);
+                    self.pending = None;
+                    return true;
+                }
+                false
+            }
+            _ => {
+                self.pending = Some((stamp, now));
+                false
+            }
+        }
+    }
+}
+
+/// Polls `path` forever, calling `on_change` after each debounced change,
+/// and also whenever `moved` (checked at every poll) returns `true`.
+pub fn watch_file(
+    path: &Path,
+    poll: Duration,
+    debounce: Duration,
+    mut moved: impl FnMut() -> bool,
+    mut on_change: impl FnMut(),
+) -> ! {
+    let mut debouncer = Debouncer::new(debounce, stamp(path));
+    loop {
+        std::thread::sleep(poll);
+        let changed = debouncer.observe(stamp(path), Instant::now());
+        if moved() | changed {
+            on_change();
+        }
+    }
+}
+
+/// Writes `output` through a temporary sibling, then renames it into place.
+///
+/// The temporary name keeps the extension so format detection still works.
+pub fn write_atomically(
+    output: &Path,
+    write: impl FnOnce(&Path) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let name = output
+        .file_name()
+        .ok_or_else(|| CliError::Input(format!("{} is not a file path", output.display())))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(".tmp");
+    if let Some(ext) = output.extension() {
+        temp_name.push(".");
+        temp_name.push(ext);
+    }
+    let temp: PathBuf = output.with_file_name(temp_name);
+    write(&temp)?;
+    std::fs::rename(&temp, output).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        CliError::Io(format!("{}: {e}", output.display()))
+    })
+}
+
+/// Watch-mode preview limits and the settings a bare params file lacks.
+pub struct WatchOptions {
+    pub engine: Option<String>,
+    pub palette: String,
+    pub seed: SeedValue,
+    pub max_size: usize,
+    pub max_steps: usize,
+    #[cfg(feature = "control")]
+    pub control: Option<Control>,
+}
+
+impl WatchOptions {
+    /// Returns the params to preview `engine` with: `params`, under any
+    /// set by live controllers.
+    fn params_for(
+        &self,
+        engine: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, CliError> {
+        #[cfg(feature = "control")]
+        if let Some(control) = &self.control {
+            return steer_params(control, engine, &params);
+        }
+        let _ = engine;
+        Ok(params)
+    }
+}
+
+/// Renders one watch-mode preview of `config` to `output` and returns the
+/// preview size.
+fn render_watch_preview(
+    config: &Path,
+    output: &Path,
+    options: &WatchOptions,
+) -> Result<(usize, usize), CliError> {
+    let text = std::fs::read_to_string(config)
+        .map_err(|e| CliError::Io(format!("{}: {e}", config.display())))?;
+    let parsed = WatchConfig::parse(&text, has_extension(config, "toml"))
+        .map_err(|e| CliError::Input(format!("{}: {e}", config.display())))?;
+    let sim = match parsed {
+        WatchConfig::Project(mut spec) => {
+            let (w, h) = (spec.canvas.width(), spec.canvas.height());
+            let (fit_w, fit_h) = fit_within(w, h, options.max_size);
+            if (fit_w, fit_h) != (w, h) {
+                spec.canvas.resize(fit_w, fit_h, ResizePolicy::Scale)?;
+            }
+            let mut comp = Composition::from_project(&spec)?;
+            (0..spec.steps.min(options.max_steps)).try_for_each(|_| comp.step())?;
+            let image = comp.render()?;
+            let (width, height) = (image.width(), image.height());
+            write_atomically(output, |path| {
+                Ok(art_engine_engines::snapshot::write_rgba_png(
+                    image.to_rgba8(),
+                    width,
+                    height,
+                    path,
+                )?)
+            })?;
+            return Ok((width, height));
+        }
+        // Previews skip oversampling to keep the edit loop fast
+        WatchConfig::Seed(spec) => Sim::from_seed(Seed {
+            params: options.params_for(&spec.engine, spec.params.clone())?,
+            oversample: 1,
+            ..spec
+        }),
+        WatchConfig::Params(params) => {
+            let engine = options.engine.clone().ok_or_else(|| {
+                CliError::Input(format!(
+                    "{} holds bare params; pass --engine to render them",
+                    config.display()
+                ))
+            })?;
+            let params = options.params_for(&engine, params)?;
+            Sim {
+                engine,
+                width: options.max_size,
+                height: options.max_size,
+                steps: options.max_steps,
+                seed: options.seed.clone(),
+                palette: options.palette.clone(),
+                params: params.to_string(),
+                color_space: ColorSpace::Srgb,
+                oversample: 1,
+                resume: None,
+                memory: MemoryBudget::default(),
+                until_stable: None,
+            }
+        }
+    };
+    let (width, height) = fit_within(sim.width, sim.height, options.max_size);
+    let sim = Sim {
+        width,
+        height,
+        steps: sim.steps.min(options.max_steps),
+        ..sim
+    };
+    write_atomically(output, |path| render_png(&sim, path))?;
+    Ok((width, height))
+}
+
+/// Runs `watch`: renders a preview of `config` to `output` now and after
+/// every change, until interrupted.
+///
+/// Render failures (often a half-edited file) are reported and the watch
+/// carries on.
+pub fn run(config: &Path, output: &Path, options: &WatchOptions, debounce: Duration, json: bool) {
+    let render = || {
+        let started = Instant::now();
+        let result = render_watch_preview(config, output, options);
+        let ms = started.elapsed().as_millis();
+        match (&result, json) {
+            (Ok((w, h)), true) => println!(
+                "{}",
+                serde_json::json!({
+                    "event": "rendered",
+                    "output": output.display().to_string(),
+                    "width": w,
+                    "height": h,
+                    "ms": ms,
+                })
+            ),
+            (Ok((w, h)), false) => {
+                eprintln!("rendered {w}x{h} in {ms} ms -> {}", output.display())
+            }
+            (Err(e), true) => println!(
+                "{}",
+                serde_json::json!({ "event": "error", "error": e.to_string() })
+            ),
+            (Err(e), false) => eprintln!("error: {e}"),
+        }
+    };
+    if !json {
+        eprintln!("watching {} (Ctrl-C to stop)", config.display());
+    }
+    render();
+    // Controller input re-renders without waiting for the file
+    let moved = || {
+        #[cfg(feature = "control")]
+        if let Some(control) = &options.control {
+            return control.poll();
+        }
+        false
+    };
+    watch_file(config, Duration::from_millis(50), debounce, moved, render);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs_are_classified_by_content() {
+        let seed = r#"{"engine": "gray-scott", "width": 8, "height": 8, "params": {},
+                       "seed": 1, "steps": 2}"#;
+        assert!(matches!(
+            WatchConfig::parse(seed, false),
+            Ok(WatchConfig::Seed(s)) if s.seed == 1
+        ));
+        assert!(matches!(
+            WatchConfig::parse(r#"{"feed_rate": 0.05}"#, false),
+            Ok(WatchConfig::Params(p)) if p["feed_rate"] == 0.05
+        ));
+        assert!(WatchConfig::parse(r#"{"canvas": 3}"#, false).is_err());
+        assert!(WatchConfig::parse("[1]", false).is_err());
+        assert!(WatchConfig::parse("{", false).is_err());
+    }
+
+    #[test]
+    fn fit_within_keeps_aspect_and_small_sizes() {
+        assert_eq!(fit_within(100, 50, 256), (100, 50));
+        assert_eq!(fit_within(1920, 1080, 256), (256, 144));
+        assert_eq!(fit_within(1000, 1, 10), (10, 1));
+    }
+
+    #[test]
+    fn debouncer_waits_for_a_stable_stamp() {
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+        let a = (SystemTime::UNIX_EPOCH, 1);
+        let b = (SystemTime::UNIX_EPOCH + ms(5), 2);
+        let c = (SystemTime::UNIX_EPOCH + ms(9), 3);
+        let mut d = Debouncer::new(ms(100), Some(a));
+        assert!(!d.observe(Some(a), t0));
+        assert!(!d.observe(Some(b), t0));
+        // Still changing: the timer restarts
+        assert!(!d.observe(Some(c), t0 + ms(80)));
+        assert!(!d.observe(Some(c), t0 + ms(150)));
+        assert!(!d.observe(None, t0 + ms(170)));
+        assert!(d.observe(Some(c), t0 + ms(180)));
+        assert!(!d.observe(Some(c), t0 + ms(400)));
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_output() {
+        let dir = std::env::temp_dir().join(format!("art-engine-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("out.png");
+        write_atomically(&output, |temp| {
+            assert_eq!(temp.file_name().unwrap(), ".out.png.tmp.png");
+            std::fs::write(temp, b"data").map_err(|e| CliError::Io(e.to_string()))
+        })
+        .unwrap();
+        assert_eq!(std::fs::read(&output).unwrap(), b"data");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "control")]
+    #[test]
+    fn controllers_steer_watch_previews() {
+        use crate::control::{ControlMap, Message, MidiMessage, OscArg};
+        use crate::{param_schema, Cli, Command};
+        use clap::Parser;
+        let map: ControlMap = serde_json::from_value(serde_json::json!({
+            "routes": [{ "cc": 7, "param": "kill_rate" }]
+        }))
+        .unwrap();
+        let control = Control::new(map);
+        control.apply(&Message::Osc {
+            address: "/param/feed_rate".into(),
+            args: vec![OscArg::Double(0.03)],
+        });
+        control.apply(&Message::Midi(MidiMessage::ControlChange {
+            channel: 1,
+            controller: 7,
+            value: 0,
+        }));
+        let schema = param_schema("gray-scott").unwrap();
+        let params = steer_params(
+            &control,
+            "gray-scott",
+            &serde_json::json!({"feed_rate": 0.05}),
+        )
+        .unwrap();
+        assert_eq!(params["feed_rate"], 0.03);
+        assert_eq!(params["kill_rate"], schema["kill_rate"]["min"]);
+
+        let dir = std::env::temp_dir().join(format!("art-engine-control-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = dir.join("params.json");
+        std::fs::write(&config, "{}").unwrap();
+        let preview = |control: Option<Control>| {
+            let options = WatchOptions {
+                engine: Some("gray-scott".into()),
+                palette: "ocean".into(),
+                seed: "1".parse().unwrap(),
+                max_size: 24,
+                max_steps: 40,
+                control,
+            };
+            let output = dir.join("watch.png");
+            render_watch_preview(&config, &output, &options).unwrap();
+            std::fs::read(&output).unwrap()
+        };
+        assert_ne!(preview(None), preview(Some(control)));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let listen = |args: &[&str]| match Cli::try_parse_from(
+            [&["art-engine", "watch", "p.json"][..], args].concat(),
+        ) {
+            Ok(Cli {
+                command: Command::Watch { control, .. },
+                ..
+            }) => control.listen().map(|c| c.is_some()),
+            Ok(_) => panic!("expected watch"),
+            Err(e) => Err(CliError::Input(e.to_string())),
+        };
+        assert!(!listen(&[]).unwrap());
+        assert!(listen(&["--osc", "127.0.0.1:0"]).unwrap());
+        assert!(listen(&["--control-map", "m.json"]).is_err());
+        assert!(listen(&["--midi", "/dev/null"]).is_err());
+    }
+}
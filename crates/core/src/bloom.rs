@@ -0,0 +1,347 @@
+//! Bloom post-processing: a glow around the bright parts of a final image.
+//!
+//! The pipeline is shared by the CPU implementation here (used for
+//! snapshots) and the GPU pass in `render::bloom`, which follows the same
+//! steps so both produce the same image up to half-float precision:
+//!
+//! 1. **Bright pass** -- each pixel keeps the part of its color above
+//!    `threshold` in luma: `rgb * max(luma - threshold, 0) / luma`.
+//! 2. **Pyramid** -- the bright image is repeatedly halved into
+//!    [`Bloom::levels`] levels, each pixel averaging four bilinear
+//!    (clamp-to-edge) samples half a source pixel around its center, and
+//!    each level is blurred with a separable 5-tap binomial kernel.
+//! 3. **Recombine** -- from the smallest level up, each blurred level is
+//!    added to the bilinear upsample of the sum below it; the total is
+//!    upsampled to full size, averaged over the levels, scaled by
+//!    `intensity`, and added to the image (clamped to [0, 1]).
+//!
+//! Like layer effects, `radius` is a fraction of the image's shorter side,
+//! so a bloom keeps its look at any resolution. Alpha is left unchanged.
+
+use serde::{Deserialize, Serialize};
+
+use crate::image::Image;
+
+/// Most pyramid levels a bloom uses.
+pub const MAX_LEVELS: usize = 8;
+
+/// Taps of the binomial blur applied to every pyramid level.
+pub const BLUR_KERNEL: [f64; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+
+/// Bloom settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bloom {
+    /// Strength of the glow added to the image.
+    pub intensity: f64,
+    /// Luma in [0, 1] below which pixels do not glow.
+    pub threshold: f64,
+    /// Glow reach as a fraction of the image's shorter side.
+    pub radius: f64,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self {
+            intensity: 0.6,
+            threshold: 0.6,
+            radius: 0.05,
+        }
+    }
+}
+
+impl Bloom {
+    /// Returns bloom settings with the given intensity and default threshold
+    /// and radius.
+    pub fn new(intensity: f64) -> Self {
+        Self {
+            intensity,
+            ..Self::default()
+        }
+    }
+
+    /// Returns these settings with a different threshold.
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Returns these settings with a different radius.
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Number of pyramid levels for a `width x height` image: enough halvings
+    /// to reach the radius in pixels, at least one and at most
+    /// [`MAX_LEVELS`].
+    pub fn levels(&self, width: usize, height: usize) -> usize {
+        let reach = (self.radius * width.min(height) as f64).max(2.0);
+        let levels = if reach.is_finite() {
+            reach.log2().ceil() as usize
+        } else {
+            MAX_LEVELS
+        };
+        levels.clamp(1, MAX_LEVELS)
+    }
+
+    /// Returns a new image with the bloom applied.
+    pub fn apply(&self, image: &Image) -> Image {
+        let (w, h) = (image.width(), image.height());
+        let levels = self.levels(w, h);
+
+        let bright = Plane::bright_pass(image, self.threshold);
+        let mut pyramid = Vec::with_capacity(levels);
+        let mut previous = &bright;
+        for k in 0..levels {
+            let (lw, lh) = level_size(w, h, k);
+            pyramid.push(previous.downsample(lw, lh));
+            previous = &pyramid[k];
+        }
+
+        let mut sum = pyramid[levels - 1].blur();
+        for level in pyramid[..levels - 1].iter().rev() {
+            let mut blurred = level.blur();
+            let below = sum.resample(level.width, level.height);
+            blurred.add(&below);
+            sum = blurred;
+        }
+        let glow = sum.resample(w, h);
+
+        let scale = self.intensity / levels as f64;
+        let mut out = image.clone();
+        out.data_mut()
+            .chunks_exact_mut(4)
+            .zip(&glow.rgb)
+            .for_each(|(px, add)| {
+                for c in 0..3 {
+                    px[c] = (px[c] + scale * add[c]).clamp(0.0, 1.0);
+                }
+            });
+        out
+    }
+}
+
+/// Size of pyramid level `k`: the image halved `k + 1` times, at least 1x1.
+pub fn level_size(width: usize, height: usize, k: usize) -> (usize, usize) {
+    ((width >> (k + 1)).max(1), (height >> (k + 1)).max(1))
+}
+
+/// An RGB working buffer for the bloom pyramid.
+struct Plane {
+    width: usize,
+    height: usize,
+    rgb: Vec<[f64; 3]>,
+}
+
+impl Plane {
+    /// Keeps the part of each pixel's color above `threshold` in luma.
+    fn bright_pass(image: &Image, threshold: f64) -> Self {
+        let rgb = image
+            .data()
+            .chunks_exact(4)
+            .map(|px| {
+                let luma = 0.2126 * px[0] + 0.7152 * px[1] + 0.0722 * px[2];
+                let keep = if luma > 0.0 {
+                    (luma - threshold).max(0.0) / luma
+                } else {
+                    0.0
+                };
+                [px[0] * keep, px[1] * keep, px[2] * keep]
+            })
+            .collect();
+        Self {
+            width: image.width(),
+            height: image.height(),
+            rgb,
+        }
+    }
+
+    fn at(&self, x: isize, y: isize) -> [f64; 3] {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.rgb[y * self.width + x]
+    }
+
+    /// Bilinear sample at normalized coordinates, clamped at the edges
+    /// (GL `LINEAR` filtering with `CLAMP_TO_EDGE`).
+    fn sample(&self, u: f64, v: f64) -> [f64; 3] {
+        let x = u * self.width as f64 - 0.5;
+        let y = v * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let [a, b, c, d] = [
+            self.at(x0, y0),
+            self.at(x0 + 1, y0),
+            self.at(x0, y0 + 1),
+            self.at(x0 + 1, y0 + 1),
+        ];
+        std::array::from_fn(|i| {
+            let top = a[i] + (b[i] - a[i]) * fx;
+            let bottom = c[i] + (d[i] - c[i]) * fx;
+            top + (bottom - top) * fy
+        })
+    }
+
+    /// Resamples to `width x height`, sampling at each pixel center.
+    fn resample(&self, width: usize, height: usize) -> Self {
+        let rgb = (0..height)
+            .flat_map(|y| {
+                (0..width).map(move |x| {
+                    self.sample(
+                        (x as f64 + 0.5) / width as f64,
+                        (y as f64 + 0.5) / height as f64,
+                    )
+                })
+            })
+            .collect();
+        Self { width, height, rgb }
+    }
+
+    /// Shrinks to `width x height`, averaging four bilinear samples half a
+    /// source pixel around each pixel center so that no source pixel is
+    /// skipped at odd sizes (an exact 2x2 box at even sizes).
+    fn downsample(&self, width: usize, height: usize) -> Self {
+        let (du, dv) = (0.5 / self.width as f64, 0.5 / self.height as f64);
+        let rgb = (0..height)
+            .flat_map(|y| {
+                (0..width).map(move |x| {
+                    let u = (x as f64 + 0.5) / width as f64;
+                    let v = (y as f64 + 0.5) / height as f64;
+                    let taps = [
+                        self.sample(u - du, v - dv),
+                        self.sample(u + du, v - dv),
+                        self.sample(u - du, v + dv),
+                        self.sample(u + du, v + dv),
+                    ];
+                    std::array::from_fn(|c| taps.iter().map(|t| t[c]).sum::<f64>() / 4.0)
+                })
+            })
+            .collect();
+        Self { width, height, rgb }
+    }
+
+    /// Separable [`BLUR_KERNEL`] blur, clamped at the edges.
+    fn blur(&self) -> Self {
+        let pass = |plane: &Plane, dx: isize, dy: isize| -> Plane {
+            let rgb = (0..plane.height as isize)
+                .flat_map(|y| {
+                    (0..plane.width as isize).map(move |x| {
+                        let mut acc = [0.0; 3];
+                        for (k, weight) in BLUR_KERNEL.iter().enumerate() {
+                            let o = k as isize - 2;
+                            let px = plane.at(x + o * dx, y + o * dy);
+                            for c in 0..3 {
+                                acc[c] += px[c] * weight;
+                            }
+                        }
+                        acc
+                    })
+                })
+                .collect();
+            Plane {
+                width: plane.width,
+                height: plane.height,
+                rgb,
+            }
+        };
+        pass(&pass(self, 1, 0), 0, 1)
+    }
+
+    fn add(&mut self, other: &Plane) {
+        self.rgb.iter_mut().zip(&other.rgb).for_each(|(a, b)| {
+            for c in 0..3 {
+                a[c] += b[c];
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Srgb;
+
+    fn gray(v: f64) -> Srgb {
+        Srgb { r: v, g: v, b: v }
+    }
+
+    #[test]
+    fn images_below_the_threshold_are_unchanged() {
+        let image = Image::filled(32, 16, gray(0.4), 1.0).unwrap();
+        assert_eq!(Bloom::new(1.0).with_threshold(0.5).apply(&image), image);
+    }
+
+    #[test]
+    fn bright_spots_glow_into_their_surroundings() {
+        let mut image = Image::filled(33, 33, gray(0.0), 1.0).unwrap();
+        image.set_pixel(16, 16, [1.0, 1.0, 1.0, 1.0]).unwrap();
+        let bloomed = Bloom::new(1.0)
+            .with_threshold(0.2)
+            .with_radius(0.2)
+            .apply(&image);
+        let near = bloomed.pixel(17, 16).unwrap()[0];
+        let far = bloomed.pixel(22, 16).unwrap()[0];
+        let corner = bloomed.pixel(0, 0).unwrap()[0];
+        assert!(near > far && far > corner, "{near} {far} {corner}");
+        assert!(near > 0.0);
+        assert_eq!(bloomed.pixel(16, 16).unwrap()[0], 1.0);
+        assert_eq!(bloomed.pixel(16, 16).unwrap()[3], 1.0);
+    }
+
+    #[test]
+    fn intensity_scales_the_glow() {
+        let mut image = Image::filled(16, 16, gray(0.1), 1.0).unwrap();
+        image.set_pixel(8, 8, [1.0, 0.9, 0.8, 1.0]).unwrap();
+        let glow_at = |intensity: f64| {
+            let bloom = Bloom::new(intensity).with_threshold(0.3);
+            bloom.apply(&image).pixel(10, 8).unwrap()[1] - 0.1
+        };
+        let (weak, strong) = (glow_at(0.25), glow_at(0.5));
+        assert!(weak > 0.0);
+        assert!((strong - 2.0 * weak).abs() < 1e-12, "{weak} {strong}");
+        assert_eq!(glow_at(0.0), 0.0);
+    }
+
+    #[test]
+    fn levels_follow_the_radius() {
+        let bloom = Bloom::default().with_radius(0.05);
+        assert_eq!(bloom.levels(640, 320), 4); // 16 px reach
+        assert_eq!(bloom.with_radius(0.0).levels(640, 320), 1);
+        assert_eq!(bloom.with_radius(10.0).levels(640, 320), MAX_LEVELS);
+        assert_eq!(bloom.with_radius(f64::NAN).levels(640, 320), 1);
+    }
+
+    #[test]
+    fn levels_halve_down_to_one_pixel() {
+        assert_eq!(level_size(64, 48, 0), (32, 24));
+        assert_eq!(level_size(64, 48, 3), (4, 3));
+        assert_eq!(level_size(64, 48, 7), (1, 1));
+    }
+
+    #[test]
+    fn resampling_a_constant_plane_is_exact() {
+        let plane = Plane {
+            width: 5,
+            height: 3,
+            rgb: vec![[0.25, 0.5, 0.75]; 15],
+        };
+        let half = plane.downsample(2, 1);
+        assert!(half.rgb.iter().all(|px| *px == [0.25, 0.5, 0.75]));
+        let full = half.resample(5, 3);
+        assert!(full.rgb.iter().all(|px| *px == [0.25, 0.5, 0.75]));
+        assert!(plane.blur().rgb.iter().all(|px| px
+            .iter()
+            .zip([0.25, 0.5, 0.75])
+            .all(|(a, b)| (a - b).abs() < 1e-12)));
+    }
+
+    #[test]
+    fn bloom_serde_fills_defaults() {
+        let bloom: Bloom = serde_json::from_str(r#"{"intensity": 1.5}"#).unwrap();
+        assert_eq!(bloom, Bloom::new(1.5));
+        let json = serde_json::to_string(&bloom).unwrap();
+        assert_eq!(serde_json::from_str::<Bloom>(&json).unwrap(), bloom);
+    }
+}
@@ -0,0 +1,240 @@
+//! Memory budgets: refuse oversized work before allocating it.
+//!
+//! Engines allocate their whole grid up front and then more each step, so a
+//! mistyped `100000x100000` would otherwise grow until the operating system
+//! kills the process, possibly hours into a render. Callers estimate what a
+//! run needs with [`grid_bytes`] and ask a [`MemoryBudget`] first; one that
+//! is over budget fails with `EngineError::OverMemoryBudget`, before any
+//! allocation.
+//!
+//! Budgets parse from sizes such as `512M`, `8G` or `1.5GiB` (binary units
+//! either way; a bare number is bytes), or `unlimited`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::EngineError;
+
+/// One gibibyte, in bytes.
+const GIB: u64 = 1 << 30;
+
+/// An upper bound on the memory a single run may allocate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MemoryBudget {
+    /// Limit in bytes; `None` for no limit.
+    limit: Option<u64>,
+}
+
+impl Default for MemoryBudget {
+    /// [`MemoryBudget::DEFAULT_LIMIT`]: generous for art, but well short of
+    /// what a typo in a canvas size asks for.
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LIMIT)
+    }
+}
+
+impl MemoryBudget {
+    /// The default limit, 8 GiB.
+    pub const DEFAULT_LIMIT: u64 = 8 * GIB;
+
+    /// A budget of `bytes`.
+    pub fn new(bytes: u64) -> Self {
+        Self { limit: Some(bytes) }
+    }
+
+    /// A budget that allows anything.
+    pub fn unlimited() -> Self {
+        Self { limit: None }
+    }
+
+    /// The limit in bytes, or `None` when unlimited.
+    pub fn limit(self) -> Option<u64> {
+        self.limit
+    }
+
+    /// Checks that `bytes` fits the budget; `what` names the work in the
+    /// error, e.g. `"gray-scott at 4096x4096"`.
+    ///
+    /// Returns `EngineError::OverMemoryBudget`, suggesting a smaller size or
+    /// rendering in tiles, if it does not.
+    pub fn check(self, what: &str, bytes: u64) -> Result<(), EngineError> {
+        match self.limit {
+            Some(limit) if bytes > limit => Err(EngineError::OverMemoryBudget(format!(
+                "{what} needs about {}, over the {} limit; render a smaller size \
+                 (or in tiles) or raise the limit",
+                format_bytes(bytes),
+                format_bytes(limit)
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limit {
+            Some(limit) => f.write_str(&format_bytes(limit)),
+            None => f.write_str("unlimited"),
+        }
+    }
+}
+
+impl FromStr for MemoryBudget {
+    type Err = EngineError;
+
+    /// Parses a size with an optional `K`, `M`, `G` or `T` suffix (`B`,
+    /// `iB` and case are ignored), or `unlimited`.
+    ///
+    /// Returns `EngineError::InvalidBudget` for anything else, or a size
+    /// under one byte.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("unlimited") {
+            return Ok(Self::unlimited());
+        }
+        let invalid = || EngineError::InvalidBudget(format!("'{s}' is not a size like 512M or 8G"));
+        let lower = s.to_ascii_lowercase();
+        let number = lower
+            .strip_suffix("ib")
+            .or_else(|| lower.strip_suffix('b'))
+            .unwrap_or(&lower);
+        let (number, shift) = match number.char_indices().last() {
+            Some((i, 'k')) => (&number[..i], 10),
+            Some((i, 'm')) => (&number[..i], 20),
+            Some((i, 'g')) => (&number[..i], 30),
+            Some((i, 't')) => (&number[..i], 40),
+            _ => (number, 0),
+        };
+        let value: f64 = number.trim().parse().map_err(|_| invalid())?;
+        let bytes = value * (1u64 << shift) as f64;
+        if !bytes.is_finite() || bytes < 1.0 || bytes >= u64::MAX as f64 {
+            return Err(invalid());
+        }
+        Ok(Self::new(bytes as u64))
+    }
+}
+
+impl TryFrom<String> for MemoryBudget {
+    type Error = EngineError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<MemoryBudget> for String {
+    fn from(budget: MemoryBudget) -> Self {
+        budget.to_string()
+    }
+}
+
+/// Bytes for a `width`×`height` grid at `bytes_per_cell`, saturating at
+/// `u64::MAX` so absurd sizes fail the budget instead of overflowing.
+pub fn grid_bytes(width: usize, height: usize, bytes_per_cell: u64) -> u64 {
+    (width as u64)
+        .saturating_mul(height as u64)
+        .saturating_mul(bytes_per_cell)
+}
+
+/// Formats a byte count in binary units with one decimal, e.g. `"1.5 GiB"`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{value:.1} {}", UNITS[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -- Parsing tests --
+
+    #[test]
+    fn parses_sizes_with_binary_units() {
+        let bytes = |s: &str| s.parse::<MemoryBudget>().unwrap().limit();
+        assert_eq!(bytes("4096"), Some(4096));
+        assert_eq!(bytes("512K"), Some(512 << 10));
+        assert_eq!(bytes("512M"), Some(512 << 20));
+        assert_eq!(bytes("8G"), Some(8 << 30));
+        assert_eq!(bytes("8gb"), Some(8 << 30));
+        assert_eq!(bytes("1.5GiB"), Some(3 << 29));
+        assert_eq!(bytes(" 2 T "), Some(2 << 40));
+        assert_eq!(bytes("unlimited"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_sizes() {
+        for bad in ["", "G", "eight", "-1G", "0", "8X", "1e30T", "NaN"] {
+            assert!(
+                matches!(
+                    bad.parse::<MemoryBudget>(),
+                    Err(EngineError::InvalidBudget(_))
+                ),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn display_round_trips_through_serde() {
+        let budget = MemoryBudget::new(3 << 29);
+        assert_eq!(budget.to_string(), "1.5 GiB");
+        let json = serde_json::to_string(&budget).unwrap();
+        assert_eq!(json, "\"1.5 GiB\"");
+        assert_eq!(serde_json::from_str::<MemoryBudget>(&json).unwrap(), budget);
+        let unlimited: MemoryBudget = serde_json::from_str("\"unlimited\"").unwrap();
+        assert_eq!(unlimited, MemoryBudget::unlimited());
+        assert!(serde_json::from_str::<MemoryBudget>("\"lots\"").is_err());
+    }
+
+    // -- Check tests --
+
+    #[test]
+    fn check_refuses_only_what_exceeds_the_limit() {
+        let budget = MemoryBudget::new(1000);
+        assert!(budget.check("small", 1000).is_ok());
+        let err = budget.check("gray-scott at 9x9", 1001).unwrap_err();
+        assert!(matches!(err, EngineError::OverMemoryBudget(_)));
+        let msg = err.to_string();
+        assert!(msg.contains("gray-scott at 9x9"), "{msg}");
+        assert!(msg.contains("tiles"), "{msg}");
+        assert!(MemoryBudget::unlimited().check("huge", u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn default_allows_ordinary_canvases() {
+        let budget = MemoryBudget::default();
+        assert_eq!(budget.limit(), Some(MemoryBudget::DEFAULT_LIMIT));
+        assert!(budget.check("4k", grid_bytes(4096, 4096, 64)).is_ok());
+        assert!(budget
+            .check("typo", grid_bytes(100_000, 100_000, 8))
+            .is_err());
+    }
+
+    // -- Estimate tests --
+
+    #[test]
+    fn grid_bytes_saturates() {
+        assert_eq!(grid_bytes(4, 3, 8), 96);
+        assert_eq!(grid_bytes(usize::MAX, usize::MAX, 8), u64::MAX);
+    }
+
+    #[test]
+    fn format_bytes_picks_a_unit() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(8 << 30), "8.0 GiB");
+        assert_eq!(format_bytes(u64::MAX), "16777216.0 TiB");
+    }
+}
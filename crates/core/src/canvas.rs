@@ -5,14 +5,21 @@
 //! (index 0 = bottom).
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::color::Srgb;
+use crate::effect::Effect;
 use crate::error::EngineError;
+use crate::keyframe::LayerAnimation;
+use crate::mask::Mask;
+use crate::transform::Transform;
 
 /// Blend mode used when compositing a layer onto the canvas.
 ///
 /// `Normal` and `Additive` can use hardware `gl.blendFunc` as a fast path.
-/// `Multiply`, `Screen`, and `Overlay` require shader-based compositing.
+/// All other modes require shader-based compositing (`render::GpuCompositor`
+/// with the `render` feature). Formulas follow the W3C
+/// Compositing and Blending spec (see [`crate::compositor::blend_channel`]).
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum BlendMode {
@@ -22,6 +29,31 @@ pub enum BlendMode {
     Multiply,
     Screen,
     Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    SoftLight,
+    Difference,
+    Exclusion,
+}
+
+impl BlendMode {
+    /// Every blend mode, in declaration order.
+    pub const ALL: [BlendMode; 12] = [
+        BlendMode::Normal,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::ColorDodge,
+        BlendMode::ColorBurn,
+        BlendMode::SoftLight,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+    ];
 }
 
 /// The kind of content a layer renders.
@@ -33,10 +65,59 @@ pub enum ContentType {
     Field,
 }
 
+/// An engine instance that realizes the pixels of a [`ContentType::Field`] layer.
+///
+/// The engine runs at the canvas resolution; its primary field (and hue
+/// field, if any) is mapped through the named palette.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EngineBinding {
+    pub engine: String,
+    pub seed: u64,
+    #[serde(default = "empty_params")]
+    pub params: Value,
+    pub palette: String,
+}
+
+impl EngineBinding {
+    /// Creates a binding with default params (`{}`).
+    pub fn new(engine: &str, seed: u64, palette: &str) -> Self {
+        Self {
+            engine: engine.to_string(),
+            seed,
+            params: empty_params(),
+            palette: palette.to_string(),
+        }
+    }
+
+    /// Returns a new binding with the given engine parameters.
+    pub fn with_params(mut self, params: Value) -> Self {
+        self.params = params;
+        self
+    }
+}
+
+/// Serde default for [`EngineBinding::params`].
+fn empty_params() -> Value {
+    Value::Object(serde_json::Map::new())
+}
+
+/// The source of a layer's pixels.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "engine", "engine": "gray-scott", ...}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LayerContent {
+    /// Pixels come from stepping and rendering an engine.
+    Engine(EngineBinding),
+}
+
 /// A single layer in the canvas stack.
 ///
 /// Layers are identified by unique names within a [`Canvas`]. Each layer has
-/// a blend mode, opacity, visibility flag, and content type.
+/// a blend mode, opacity, visibility flag, content type, an optional content
+/// binding that says where its pixels come from, an optional [`Mask`] that
+/// scales its alpha, a [`Transform`] and [`Effect`] stack applied before
+/// compositing, and optional keyframe tracks ([`LayerAnimation`]).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Layer {
     name: String,
@@ -44,6 +125,16 @@ pub struct Layer {
     opacity: f64,
     visible: bool,
     content_type: ContentType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<LayerContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mask: Option<Mask>,
+    #[serde(default, skip_serializing_if = "Transform::is_identity")]
+    transform: Transform,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<Effect>,
+    #[serde(default, skip_serializing_if = "LayerAnimation::is_empty")]
+    animation: LayerAnimation,
 }
 
 impl Layer {
@@ -57,6 +148,11 @@ impl Layer {
             opacity: 1.0,
             visible: true,
             content_type,
+            content: None,
+            mask: None,
+            transform: Transform::IDENTITY,
+            effects: Vec::new(),
+            animation: LayerAnimation::default(),
         }
     }
 
@@ -100,6 +196,56 @@ impl Layer {
         self.content_type
     }
 
+    /// Returns the content binding, if any.
+    pub fn content(&self) -> Option<&LayerContent> {
+        self.content.as_ref()
+    }
+
+    /// Sets or clears the content binding.
+    pub fn set_content(&mut self, content: Option<LayerContent>) {
+        self.content = content;
+    }
+
+    /// Returns the alpha mask, if any.
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    /// Sets or clears the alpha mask.
+    pub fn set_mask(&mut self, mask: Option<Mask>) {
+        self.mask = mask;
+    }
+
+    /// Returns the 2D transform applied before compositing.
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// Sets the 2D transform.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    /// Returns the effect stack, applied in order.
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    /// Replaces the effect stack.
+    pub fn set_effects(&mut self, effects: Vec<Effect>) {
+        self.effects = effects;
+    }
+
+    /// Returns the keyframe tracks animating this layer.
+    pub fn animation(&self) -> &LayerAnimation {
+        &self.animation
+    }
+
+    /// Replaces the keyframe tracks.
+    pub fn set_animation(&mut self, animation: LayerAnimation) {
+        self.animation = animation;
+    }
+
     /// Returns a new layer with the given blend mode.
     pub fn with_blend_mode(mut self, mode: BlendMode) -> Self {
         self.blend_mode = mode;
@@ -117,18 +263,338 @@ impl Layer {
         self.visible = visible;
         self
     }
+
+    /// Returns a new layer with the given content binding.
+    pub fn with_content(mut self, content: LayerContent) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    /// Returns a new layer with the given alpha mask.
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Returns a new layer with the given 2D transform.
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Returns a new layer with `effect` appended to its effect stack.
+    pub fn with_effect(mut self, effect: Effect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// Returns a new layer with the given keyframe tracks.
+    pub fn with_animation(mut self, animation: LayerAnimation) -> Self {
+        self.animation = animation;
+        self
+    }
+}
+
+/// How layer geometry adapts when a [`Canvas`] is resized.
+///
+/// Only masks, transforms and effects are reframed. Engine-bound content
+/// has no stored pixels and is not resampled (see [`Canvas::resize`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizePolicy {
+    /// Stretch the old frame onto the new one; normalized geometry is kept as is.
+    #[default]
+    Scale,
+    /// Scale uniformly until the old frame covers the new one, cropping overflow.
+    Crop,
+    /// Scale uniformly until the old frame fits inside the new one, extending
+    /// the margins.
+    Extend,
+}
+
+/// Maps normalized geometry from an old canvas frame to a resized one.
+///
+/// The old frame lands in the new canvas at `offset` with size `scale` (both
+/// normalized to the new canvas); `unit` converts lengths measured in
+/// shorter-side units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Reframe {
+    pub(crate) scale: [f64; 2],
+    pub(crate) offset: [f64; 2],
+    pub(crate) unit: f64,
+}
+
+impl Reframe {
+    fn new(from: (usize, usize), to: (usize, usize), policy: ResizePolicy) -> Self {
+        let (w, h) = (from.0 as f64, from.1 as f64);
+        let (nw, nh) = (to.0 as f64, to.1 as f64);
+        let k = match policy {
+            ResizePolicy::Scale => {
+                return Self {
+                    scale: [1.0, 1.0],
+                    offset: [0.0, 0.0],
+                    unit: 1.0,
+                }
+            }
+            ResizePolicy::Crop => (nw / w).max(nh / h),
+            ResizePolicy::Extend => (nw / w).min(nh / h),
+        };
+        let scale = [w * k / nw, h * k / nh];
+        Self {
+            scale,
+            offset: [(1.0 - scale[0]) / 2.0, (1.0 - scale[1]) / 2.0],
+            unit: w.min(h) * k / nw.min(nh),
+        }
+    }
+
+    /// Maps a normalized position.
+    pub(crate) fn point(&self, p: [f64; 2]) -> [f64; 2] {
+        [
+            p[0] * self.scale[0] + self.offset[0],
+            p[1] * self.scale[1] + self.offset[1],
+        ]
+    }
+
+    /// Maps a normalized displacement.
+    pub(crate) fn vector(&self, v: [f64; 2]) -> [f64; 2] {
+        [v[0] * self.scale[0], v[1] * self.scale[1]]
+    }
+
+    /// Maps a length in shorter-side units.
+    pub(crate) fn length(&self, l: f64) -> f64 {
+        l * self.unit
+    }
+
+    /// Maps a normalized position in the new frame back to the old one.
+    pub(crate) fn inverse_point(&self, p: [f64; 2]) -> [f64; 2] {
+        [
+            (p[0] - self.offset[0]) / self.scale[0],
+            (p[1] - self.offset[1]) / self.scale[1],
+        ]
+    }
 }
 
-/// A canvas with dimensions, background color, and an ordered layer stack.
+/// A named group of layer nodes composited as a unit.
 ///
-/// Layers are stored bottom-to-top: index 0 is the bottom layer, rendered
-/// first. Layer names must be unique within a canvas.
+/// Children are rendered bottom-to-top into an isolated, initially
+/// transparent buffer, which is then blended onto the parent with the
+/// group's own blend mode and opacity. Hiding a group hides all its children.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayerGroup {
+    name: String,
+    #[serde(default)]
+    blend_mode: BlendMode,
+    #[serde(default = "default_opacity")]
+    opacity: f64,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    children: Vec<LayerNode>,
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+impl LayerGroup {
+    /// Creates an empty group.
+    ///
+    /// Defaults: `BlendMode::Normal`, opacity `1.0`, visible `true`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            blend_mode: BlendMode::Normal,
+            opacity: 1.0,
+            visible: true,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the group name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the blend mode used to composite the group.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets the blend mode.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Returns the group opacity in [0.0, 1.0].
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Sets the opacity, clamping to [0.0, 1.0].
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Returns whether the group is visible.
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Sets the visibility flag.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Returns the child nodes (bottom-to-top order).
+    pub fn children(&self) -> &[LayerNode] {
+        &self.children
+    }
+
+    /// Returns a new group with the given blend mode.
+    pub fn with_blend_mode(mut self, mode: BlendMode) -> Self {
+        self.blend_mode = mode;
+        self
+    }
+
+    /// Returns a new group with the given opacity, clamped to [0.0, 1.0].
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns a new group with the given visibility.
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Returns a new group with `child` added on top of its children.
+    ///
+    /// Name uniqueness is checked when the group is added to a [`Canvas`].
+    pub fn with_child(mut self, child: impl Into<LayerNode>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+}
+
+/// An entry in the layer tree: a single layer or a group of nodes.
+///
+/// Serialized untagged, so a plain layer object is a valid node and
+/// pre-group canvases still deserialize; groups are recognized by their
+/// `children` array.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum LayerNode {
+    Group(LayerGroup),
+    /// Boxed: a layer is several times the size of a group.
+    Layer(Box<Layer>),
+}
+
+impl LayerNode {
+    /// Returns the node name.
+    pub fn name(&self) -> &str {
+        match self {
+            LayerNode::Group(group) => group.name(),
+            LayerNode::Layer(layer) => layer.name(),
+        }
+    }
+
+    /// Returns the layer if this node is one.
+    pub fn as_layer(&self) -> Option<&Layer> {
+        match self {
+            LayerNode::Layer(layer) => Some(layer),
+            LayerNode::Group(_) => None,
+        }
+    }
+
+    /// Unwraps the layer if this node is one.
+    pub fn into_layer(self) -> Option<Layer> {
+        match self {
+            LayerNode::Layer(layer) => Some(*layer),
+            LayerNode::Group(_) => None,
+        }
+    }
+
+    /// Returns the group if this node is one.
+    pub fn as_group(&self) -> Option<&LayerGroup> {
+        match self {
+            LayerNode::Group(group) => Some(group),
+            LayerNode::Layer(_) => None,
+        }
+    }
+
+    /// Appends this node's name and all descendant names, depth-first.
+    fn collect_names<'a>(&'a self, out: &mut Vec<&'a str>) {
+        out.push(self.name());
+        if let LayerNode::Group(group) = self {
+            group
+                .children
+                .iter()
+                .for_each(|child| child.collect_names(out));
+        }
+    }
+
+    /// Iterates over every leaf layer in this subtree, bottom-to-top.
+    fn layers(&self) -> Box<dyn Iterator<Item = &Layer> + '_> {
+        match self {
+            LayerNode::Layer(layer) => Box::new(std::iter::once(&**layer)),
+            LayerNode::Group(group) => Box::new(group.children.iter().flat_map(LayerNode::layers)),
+        }
+    }
+
+    /// Calls `f` on every leaf layer in this subtree.
+    fn for_each_layer_mut(&mut self, f: &mut impl FnMut(&mut Layer)) {
+        match self {
+            LayerNode::Layer(layer) => f(layer),
+            LayerNode::Group(group) => group
+                .children
+                .iter_mut()
+                .for_each(|child| child.for_each_layer_mut(f)),
+        }
+    }
+}
+
+impl From<Layer> for LayerNode {
+    fn from(layer: Layer) -> Self {
+        LayerNode::Layer(Box::new(layer))
+    }
+}
+
+impl From<LayerGroup> for LayerNode {
+    fn from(group: LayerGroup) -> Self {
+        LayerNode::Group(group)
+    }
+}
+
+/// Finds the sibling list containing the node named `name` and its index.
+fn find_siblings<'a>(
+    nodes: &'a mut Vec<LayerNode>,
+    name: &str,
+) -> Option<(&'a mut Vec<LayerNode>, usize)> {
+    if let Some(idx) = nodes.iter().position(|n| n.name() == name) {
+        return Some((nodes, idx));
+    }
+    nodes.iter_mut().find_map(|node| match node {
+        LayerNode::Group(group) => find_siblings(&mut group.children, name),
+        LayerNode::Layer(_) => None,
+    })
+}
+
+/// A canvas with dimensions, background color, and an ordered layer tree.
+///
+/// Nodes are stored bottom-to-top: index 0 is the bottom node, rendered
+/// first. Nodes are layers or [`LayerGroup`]s, and names must be unique
+/// across the whole tree. Layer lookups and moves work at any depth; moves
+/// reorder a node among its siblings.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Canvas {
     width: usize,
     height: usize,
     background: Srgb,
-    layers: Vec<Layer>,
+    layers: Vec<LayerNode>,
 }
 
 impl Canvas {
@@ -171,109 +637,246 @@ impl Canvas {
         self.background = background;
     }
 
-    /// Returns the number of layers.
+    /// Resizes the canvas, adapting every layer's mask, transform and effects
+    /// to the new frame according to `policy`.
+    ///
+    /// Engine bindings are left as they are, so engine content re-simulates
+    /// at the new pixel size with the same params. Engine params are measured
+    /// in cells, not in canvas fractions, so a larger canvas shows more of the
+    /// pattern at the same scale rather than the preview's pattern enlarged.
+    /// Scale resolution-dependent params (e.g. diffusion rates, radii) in the
+    /// bindings to keep the look.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero,
+    /// or if `width * height` would overflow `usize`; the canvas is then
+    /// left unchanged.
+    pub fn resize(
+        &mut self,
+        width: usize,
+        height: usize,
+        policy: ResizePolicy,
+    ) -> Result<(), EngineError> {
+        Canvas::new(width, height, self.background)?;
+        let frame = Reframe::new((self.width, self.height), (width, height), policy);
+        let mut reframe = |layer: &mut Layer| {
+            layer.mask = layer.mask.take().map(|m| m.reframe(&frame));
+            layer.transform = layer.transform.reframe(&frame);
+            layer
+                .effects
+                .iter_mut()
+                .for_each(|e| *e = e.reframe(&frame));
+            layer.animation = layer.animation.reframe(&frame);
+        };
+        self.layers
+            .iter_mut()
+            .for_each(|node| node.for_each_layer_mut(&mut reframe));
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    /// Returns `true` if any layer carries keyframe tracks.
+    pub fn is_animated(&self) -> bool {
+        self.layers().any(|l| !l.animation.is_empty())
+    }
+
+    /// Returns a copy with every layer's animated properties evaluated at
+    /// `frame` (see [`LayerAnimation`]).
+    pub fn at_frame(&self, frame: f64) -> Canvas {
+        let mut canvas = self.clone();
+        canvas.layers.iter_mut().for_each(|node| {
+            node.for_each_layer_mut(&mut |layer| {
+                let animation = std::mem::take(&mut layer.animation);
+                animation.apply(layer, frame);
+                layer.animation = animation;
+            })
+        });
+        canvas
+    }
+
+    /// Returns the number of layers, counting those inside groups.
     pub fn layer_count(&self) -> usize {
-        self.layers.len()
+        self.layers().count()
+    }
+
+    /// Returns every layer, including those inside groups, in bottom-to-top
+    /// render order.
+    pub fn layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter().flat_map(LayerNode::layers)
     }
 
-    /// Returns a slice of all layers (bottom-to-top order).
-    pub fn layers(&self) -> &[Layer] {
+    /// Returns the top-level nodes (bottom-to-top order).
+    pub fn nodes(&self) -> &[LayerNode] {
         &self.layers
     }
 
     /// Adds a layer to the top of the stack.
     ///
-    /// Returns `EngineError::DuplicateLayerName` if a layer with the same
+    /// Returns `EngineError::DuplicateLayerName` if a node with the same
     /// name already exists.
     pub fn add_layer(&mut self, layer: Layer) -> Result<(), EngineError> {
-        let has_duplicate = self.layers.iter().any(|l| l.name == layer.name);
-        if has_duplicate {
-            return Err(EngineError::DuplicateLayerName(layer.name));
-        }
-        self.layers.push(layer);
+        self.add_node(LayerNode::Layer(Box::new(layer)))
+    }
+
+    /// Adds a group to the top of the stack.
+    ///
+    /// Returns `EngineError::DuplicateLayerName` if any name in the group is
+    /// repeated or already exists in the canvas.
+    pub fn add_group(&mut self, group: LayerGroup) -> Result<(), EngineError> {
+        self.add_node(LayerNode::Group(group))
+    }
+
+    fn add_node(&mut self, node: LayerNode) -> Result<(), EngineError> {
+        let mut names = self.names();
+        node.collect_names(&mut names);
+        duplicate_name(&names)?;
+        self.layers.push(node);
         Ok(())
     }
 
-    /// Removes a layer by name and returns it.
+    /// Checks that every node name in the tree is unique.
+    ///
+    /// Deserialization does not enforce this, so call it on loaded canvases.
+    /// Returns `EngineError::DuplicateLayerName` for the first repeated name.
+    pub fn validate_names(&self) -> Result<(), EngineError> {
+        duplicate_name(&self.names())
+    }
+
+    /// Every node name in the tree, depth-first.
+    fn names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.layers
+            .iter()
+            .for_each(|node| node.collect_names(&mut names));
+        names
+    }
+
+    /// Removes a layer by name, at any depth, and returns it.
     ///
-    /// Returns `EngineError::LayerNotFound` if no layer with the given name exists.
+    /// Returns `EngineError::LayerNotFound` if no layer with the given name
+    /// exists (groups are removed with [`Canvas::remove_node`]).
     pub fn remove_layer(&mut self, name: &str) -> Result<Layer, EngineError> {
-        let idx = self.index_of(name)?;
-        Ok(self.layers.remove(idx))
+        let (siblings, idx) = self.siblings_of(name)?;
+        match &siblings[idx] {
+            LayerNode::Layer(_) => siblings.remove(idx).into_layer(),
+            LayerNode::Group(_) => None,
+        }
+        .ok_or_else(|| EngineError::LayerNotFound(name.to_string()))
+    }
+
+    /// Removes a layer or group by name, at any depth, and returns it.
+    ///
+    /// Returns `EngineError::LayerNotFound` if no node with the given name exists.
+    pub fn remove_node(&mut self, name: &str) -> Result<LayerNode, EngineError> {
+        let (siblings, idx) = self.siblings_of(name)?;
+        Ok(siblings.remove(idx))
     }
 
-    /// Returns a reference to the layer with the given name.
+    /// Returns a reference to the layer with the given name, at any depth.
     ///
     /// Returns `EngineError::LayerNotFound` if not found.
     pub fn layer(&self, name: &str) -> Result<&Layer, EngineError> {
-        self.layers
-            .iter()
+        self.layers()
             .find(|l| l.name == name)
             .ok_or_else(|| EngineError::LayerNotFound(name.to_string()))
     }
 
-    /// Returns a mutable reference to the layer with the given name.
+    /// Returns a mutable reference to the layer with the given name, at any depth.
     ///
     /// Returns `EngineError::LayerNotFound` if not found.
     pub fn layer_mut(&mut self, name: &str) -> Result<&mut Layer, EngineError> {
-        self.layers
-            .iter_mut()
-            .find(|l| l.name == name)
-            .ok_or_else(|| EngineError::LayerNotFound(name.to_string()))
+        let (siblings, idx) = self.siblings_of(name)?;
+        match &mut siblings[idx] {
+            LayerNode::Layer(layer) => Ok(layer),
+            LayerNode::Group(_) => Err(EngineError::LayerNotFound(name.to_string())),
+        }
     }
 
-    /// Moves a layer to the given index in the stack.
+    /// Returns a reference to the group with the given name, at any depth.
     ///
-    /// Index 0 is the bottom. If `index >= layer_count()`, the layer moves
-    /// to the top.
+    /// Returns `EngineError::LayerNotFound` if not found.
+    pub fn group(&self, name: &str) -> Result<&LayerGroup, EngineError> {
+        fn find<'a>(nodes: &'a [LayerNode], name: &str) -> Option<&'a LayerGroup> {
+            nodes.iter().find_map(|node| match node {
+                LayerNode::Group(g) if g.name == name => Some(g),
+                LayerNode::Group(g) => find(&g.children, name),
+                LayerNode::Layer(_) => None,
+            })
+        }
+        find(&self.layers, name).ok_or_else(|| EngineError::LayerNotFound(name.to_string()))
+    }
+
+    /// Returns a mutable reference to the group with the given name, at any depth.
+    ///
+    /// Returns `EngineError::LayerNotFound` if not found.
+    pub fn group_mut(&mut self, name: &str) -> Result<&mut LayerGroup, EngineError> {
+        let (siblings, idx) = self.siblings_of(name)?;
+        match &mut siblings[idx] {
+            LayerNode::Group(group) => Ok(group),
+            LayerNode::Layer(_) => Err(EngineError::LayerNotFound(name.to_string())),
+        }
+    }
+
+    /// Moves a layer or group to the given index among its siblings.
     ///
-    /// Returns `EngineError::LayerNotFound` if the layer doesn't exist.
+    /// Index 0 is the bottom. If `index` is past the end, the node moves
+    /// to the top of its sibling list.
+    ///
+    /// Returns `EngineError::LayerNotFound` if the node doesn't exist.
     pub fn move_layer_to(&mut self, name: &str, index: usize) -> Result<(), EngineError> {
-        let idx = self.index_of(name)?;
-        let layer = self.layers.remove(idx);
-        let target = index.min(self.layers.len());
-        self.layers.insert(target, layer);
+        let (siblings, idx) = self.siblings_of(name)?;
+        let node = siblings.remove(idx);
+        let target = index.min(siblings.len());
+        siblings.insert(target, node);
         Ok(())
     }
 
-    /// Moves a layer one position up (toward the top) in the stack.
+    /// Moves a layer or group one position up (toward the top) among its siblings.
     ///
-    /// If the layer is already at the top, this is a no-op.
+    /// If the node is already at the top, this is a no-op.
     ///
-    /// Returns `EngineError::LayerNotFound` if the layer doesn't exist.
+    /// Returns `EngineError::LayerNotFound` if the node doesn't exist.
     pub fn move_layer_up(&mut self, name: &str) -> Result<(), EngineError> {
-        let idx = self.index_of(name)?;
-        if idx + 1 < self.layers.len() {
-            self.layers.swap(idx, idx + 1);
+        let (siblings, idx) = self.siblings_of(name)?;
+        if idx + 1 < siblings.len() {
+            siblings.swap(idx, idx + 1);
         }
         Ok(())
     }
 
-    /// Moves a layer one position down (toward the bottom) in the stack.
+    /// Moves a layer or group one position down (toward the bottom) among its siblings.
     ///
-    /// If the layer is already at the bottom, this is a no-op.
+    /// If the node is already at the bottom, this is a no-op.
     ///
-    /// Returns `EngineError::LayerNotFound` if the layer doesn't exist.
+    /// Returns `EngineError::LayerNotFound` if the node doesn't exist.
     pub fn move_layer_down(&mut self, name: &str) -> Result<(), EngineError> {
-        let idx = self.index_of(name)?;
+        let (siblings, idx) = self.siblings_of(name)?;
         if idx > 0 {
-            self.layers.swap(idx, idx - 1);
+            siblings.swap(idx, idx - 1);
         }
         Ok(())
     }
 
-    /// Finds the index of a layer by name.
-    fn index_of(&self, name: &str) -> Result<usize, EngineError> {
-        self.layers
-            .iter()
-            .position(|l| l.name == name)
+    /// Finds the sibling list holding a node and the node's index in it.
+    fn siblings_of(&mut self, name: &str) -> Result<(&mut Vec<LayerNode>, usize), EngineError> {
+        find_siblings(&mut self.layers, name)
             .ok_or_else(|| EngineError::LayerNotFound(name.to_string()))
     }
 }
 
+/// Returns `EngineError::DuplicateLayerName` for the first repeated name.
+fn duplicate_name(names: &[&str]) -> Result<(), EngineError> {
+    let mut seen = std::collections::HashSet::new();
+    match names.iter().find(|name| !seen.insert(**name)) {
+        Some(name) => Err(EngineError::DuplicateLayerName(name.to_string())),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::keyframe::{Keyframe, Track};
 
     fn black() -> Srgb {
         Srgb {
@@ -300,14 +903,7 @@ mod tests {
 
     #[test]
     fn blend_mode_serde_round_trip() {
-        let modes = [
-            BlendMode::Normal,
-            BlendMode::Additive,
-            BlendMode::Multiply,
-            BlendMode::Screen,
-            BlendMode::Overlay,
-        ];
-        for mode in &modes {
+        for mode in &BlendMode::ALL {
             let json = serde_json::to_string(mode).unwrap();
             let deserialized: BlendMode = serde_json::from_str(&json).unwrap();
             assert_eq!(*mode, deserialized);
@@ -336,6 +932,22 @@ mod tests {
             serde_json::to_string(&BlendMode::Overlay).unwrap(),
             "\"overlay\""
         );
+        assert_eq!(
+            serde_json::to_string(&BlendMode::ColorDodge).unwrap(),
+            "\"color_dodge\""
+        );
+        assert_eq!(
+            serde_json::to_string(&BlendMode::ColorBurn).unwrap(),
+            "\"color_burn\""
+        );
+        assert_eq!(
+            serde_json::to_string(&BlendMode::SoftLight).unwrap(),
+            "\"soft_light\""
+        );
+        assert_eq!(
+            serde_json::to_string(&BlendMode::Difference).unwrap(),
+            "\"difference\""
+        );
     }
 
     // ── ContentType tests ──────────────────────────────────────────
@@ -451,6 +1063,311 @@ mod tests {
         assert_eq!(layer, deserialized);
     }
 
+    // ── LayerContent tests ─────────────────────────────────────────
+
+    #[test]
+    fn layer_new_has_no_content() {
+        assert!(Layer::new("bg", ContentType::Field).content().is_none());
+    }
+
+    #[test]
+    fn layer_with_content_and_set_content() {
+        let binding = EngineBinding::new("gray-scott", 7, "ocean");
+        let mut layer = Layer::new("rd", ContentType::Field)
+            .with_content(LayerContent::Engine(binding.clone()));
+        assert_eq!(layer.content(), Some(&LayerContent::Engine(binding)));
+        layer.set_content(None);
+        assert!(layer.content().is_none());
+    }
+
+    #[test]
+    fn layer_content_serializes_with_kind_tag() {
+        let layer = Layer::new("rd", ContentType::Field).with_content(LayerContent::Engine(
+            EngineBinding::new("gray-scott", 7, "fire")
+                .with_params(serde_json::json!({"feed_rate": 0.04})),
+        ));
+        let value = serde_json::to_value(&layer).unwrap();
+        assert_eq!(value["content"]["kind"], "engine");
+        assert_eq!(value["content"]["engine"], "gray-scott");
+        assert_eq!(value["content"]["params"]["feed_rate"], 0.04);
+        let restored: Layer = serde_json::from_value(value).unwrap();
+        assert_eq!(layer, restored);
+    }
+
+    #[test]
+    fn layer_without_content_omits_key_and_old_json_still_parses() {
+        let value = serde_json::to_value(Layer::new("bg", ContentType::Field)).unwrap();
+        assert!(value.get("content").is_none());
+        let json = r#"{"name":"bg","blend_mode":"normal","opacity":1.0,"visible":true,"content_type":"field"}"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        assert!(layer.content().is_none());
+    }
+
+    #[test]
+    fn engine_binding_params_default_to_empty_object() {
+        let json = r#"{"kind":"engine","engine":"gray-scott","seed":1,"palette":"ocean"}"#;
+        let content: LayerContent = serde_json::from_str(json).unwrap();
+        let LayerContent::Engine(binding) = content;
+        assert_eq!(binding.params, serde_json::json!({}));
+    }
+
+    // ── Mask tests ─────────────────────────────────────────────────
+
+    #[test]
+    fn layer_with_mask_and_set_mask() {
+        let mask = Mask::Circle {
+            cx: 0.5,
+            cy: 0.5,
+            radius: 0.25,
+            feather: 0.0,
+        };
+        let mut layer = Layer::new("m", ContentType::Field).with_mask(mask.clone());
+        assert_eq!(layer.mask(), Some(&mask));
+        layer.set_mask(None);
+        assert!(layer.mask().is_none());
+    }
+
+    #[test]
+    fn layer_mask_serde_round_trip() {
+        let field = crate::field::Field::from_data(2, 1, vec![0.0, 1.0]).unwrap();
+        let layer = Layer::new("m", ContentType::Field).with_mask(Mask::Field(field));
+        let value = serde_json::to_value(&layer).unwrap();
+        assert_eq!(value["mask"]["kind"], "field");
+        let restored: Layer = serde_json::from_value(value).unwrap();
+        assert_eq!(layer, restored);
+        let plain = serde_json::to_value(Layer::new("bg", ContentType::Field)).unwrap();
+        assert!(plain.get("mask").is_none());
+    }
+
+    // ── Transform tests ────────────────────────────────────────────
+
+    #[test]
+    fn layer_defaults_to_identity_transform_and_omits_it() {
+        let layer = Layer::new("t", ContentType::Field);
+        assert!(layer.transform().is_identity());
+        let value = serde_json::to_value(&layer).unwrap();
+        assert!(value.get("transform").is_none());
+    }
+
+    #[test]
+    fn layer_transform_serde_round_trip() {
+        use crate::transform::WrapMode;
+        let mut layer = Layer::new("t", ContentType::Field)
+            .with_transform(Transform::IDENTITY.with_rotation(30.0));
+        layer.set_transform(layer.transform().with_wrap(WrapMode::Mirror));
+        let value = serde_json::to_value(&layer).unwrap();
+        assert_eq!(value["transform"]["rotation"], 30.0);
+        assert_eq!(value["transform"]["wrap"], "mirror");
+        let restored: Layer = serde_json::from_value(value).unwrap();
+        assert_eq!(layer, restored);
+    }
+
+    // ── Effect tests ───────────────────────────────────────────────
+
+    #[test]
+    fn layer_effects_stack_in_order_and_round_trip() {
+        let mut layer = Layer::new("fx", ContentType::Field)
+            .with_effect(Effect::Blur { radius: 0.01 })
+            .with_effect(Effect::Glow {
+                threshold: 0.8,
+                radius: 0.02,
+                intensity: 1.5,
+            });
+        assert!(matches!(layer.effects()[0], Effect::Blur { .. }));
+        assert!(matches!(layer.effects()[1], Effect::Glow { .. }));
+        let value = serde_json::to_value(&layer).unwrap();
+        assert_eq!(value["effects"][1]["kind"], "glow");
+        let restored: Layer = serde_json::from_value(value).unwrap();
+        assert_eq!(layer, restored);
+        layer.set_effects(Vec::new());
+        let value = serde_json::to_value(&layer).unwrap();
+        assert!(value.get("effects").is_none());
+    }
+
+    // ── Animation tests ────────────────────────────────────────────
+
+    fn fading_layer() -> Layer {
+        Layer::new("fade", ContentType::Field).with_animation(LayerAnimation {
+            opacity: Some(Track::new(vec![
+                Keyframe::new(0.0, 0.0),
+                Keyframe::new(10.0, 1.0),
+            ])),
+            translate: Some(Track::new(vec![Keyframe::new(0.0, [0.5, 0.0])])),
+            ..LayerAnimation::default()
+        })
+    }
+
+    #[test]
+    fn at_frame_evaluates_nested_layers_and_keeps_tracks() {
+        let mut canvas = Canvas::new(10, 10, black()).unwrap();
+        canvas
+            .add_group(LayerGroup::new("g").with_child(fading_layer()))
+            .unwrap();
+        assert!(canvas.is_animated());
+        let frame = canvas.at_frame(5.0);
+        assert_eq!(frame.layer("fade").unwrap().opacity(), 0.5);
+        assert_eq!(
+            frame.layer("fade").unwrap().transform().translate,
+            [0.5, 0.0]
+        );
+        assert_eq!(canvas.layer("fade").unwrap().opacity(), 1.0);
+        assert_eq!(frame.at_frame(10.0).layer("fade").unwrap().opacity(), 1.0);
+    }
+
+    #[test]
+    fn layer_animation_omitted_when_empty_and_round_trips() {
+        let plain = serde_json::to_value(Layer::new("a", ContentType::Field)).unwrap();
+        assert!(plain.get("animation").is_none());
+        let layer = fading_layer();
+        let json = serde_json::to_string(&layer).unwrap();
+        assert_eq!(serde_json::from_str::<Layer>(&json).unwrap(), layer);
+    }
+
+    #[test]
+    fn resize_reframes_translate_keys() {
+        let mut canvas = Canvas::new(10, 10, black()).unwrap();
+        canvas.add_layer(fading_layer()).unwrap();
+        canvas.resize(20, 10, ResizePolicy::Extend).unwrap();
+        let track = canvas.layer("fade").unwrap().animation().translate.clone();
+        assert_eq!(track.unwrap().keys()[0].value, [0.25, 0.0]);
+    }
+
+    // ── Resize tests ───────────────────────────────────────────────
+
+    fn circle_layer(cx: f64, cy: f64, radius: f64) -> Layer {
+        Layer::new("c", ContentType::Field).with_mask(Mask::Circle {
+            cx,
+            cy,
+            radius,
+            feather: 0.0,
+        })
+    }
+
+    fn mask_raster(canvas: &Canvas) -> Vec<f64> {
+        let layer = canvas.layer("c").unwrap();
+        let mask = layer.mask().unwrap();
+        mask.rasterize(canvas.width(), canvas.height())
+            .unwrap()
+            .data()
+            .to_vec()
+    }
+
+    #[test]
+    fn resize_rejects_invalid_dimensions_and_keeps_size() {
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        assert!(matches!(
+            canvas.resize(0, 8, ResizePolicy::Scale),
+            Err(EngineError::InvalidDimensions)
+        ));
+        assert_eq!((canvas.width(), canvas.height()), (8, 8));
+    }
+
+    #[test]
+    fn resize_scale_keeps_normalized_geometry() {
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas
+            .add_layer(circle_layer(0.25, 0.5, 0.125).with_effect(Effect::Blur { radius: 0.1 }))
+            .unwrap();
+        let before = canvas.layer("c").unwrap().clone();
+        canvas.resize(32, 16, ResizePolicy::Scale).unwrap();
+        assert_eq!((canvas.width(), canvas.height()), (32, 16));
+        assert_eq!(canvas.layer("c").unwrap(), &before);
+    }
+
+    #[test]
+    fn resize_extend_keeps_shapes_at_the_same_pixels() {
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas.add_layer(circle_layer(0.25, 0.5, 0.125)).unwrap();
+        let before = mask_raster(&canvas);
+        canvas.resize(16, 8, ResizePolicy::Extend).unwrap();
+        let after = mask_raster(&canvas);
+        // The old 8x8 frame now sits at columns 4..12
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(after[y * 16 + x + 4], before[y * 8 + x], "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn resize_crop_scales_uniformly_and_trims_overflow() {
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas.add_layer(circle_layer(0.5, 0.5, 0.25)).unwrap();
+        canvas.resize(16, 8, ResizePolicy::Crop).unwrap();
+        // Old frame is now 16x16 centered, so the circle doubles in pixels
+        // and the radius grows relative to the 8 px shorter side
+        match canvas.layer("c").unwrap().mask().unwrap() {
+            Mask::Circle { cx, cy, radius, .. } => {
+                assert!((cx - 0.5).abs() < 1e-12 && (cy - 0.5).abs() < 1e-12);
+                assert!((radius - 0.5).abs() < 1e-12);
+            }
+            other => panic!("unexpected mask {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resize_maps_transforms_effects_and_field_masks() {
+        let field = crate::field::Field::from_data(2, 1, vec![0.25, 0.75]).unwrap();
+        let mut canvas = Canvas::new(4, 4, black()).unwrap();
+        canvas
+            .add_layer(
+                Layer::new("l", ContentType::Field)
+                    .with_mask(Mask::Field(field))
+                    .with_transform(
+                        Transform::IDENTITY
+                            .with_translate(0.5, 0.0)
+                            .with_pivot(0.0, 0.0),
+                    )
+                    .with_effect(Effect::Blur { radius: 0.25 }),
+            )
+            .unwrap();
+        canvas.resize(8, 4, ResizePolicy::Extend).unwrap();
+        let layer = canvas.layer("l").unwrap();
+        assert_eq!(layer.transform().translate, [0.25, 0.0]);
+        assert_eq!(layer.transform().pivot, [0.25, 0.0]);
+        assert_eq!(layer.effects()[0], Effect::Blur { radius: 0.25 });
+        match layer.mask().unwrap() {
+            Mask::Field(f) => {
+                assert_eq!((f.width(), f.height()), (4, 1));
+                assert_eq!(f.data(), &[0.0, 0.25, 0.75, 0.0]);
+            }
+            other => panic!("unexpected mask {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resize_leaves_engine_bindings_untouched() {
+        let binding = EngineBinding::new("gray-scott", 7, "ocean")
+            .with_params(serde_json::json!({"feed_rate": 0.05}));
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas
+            .add_layer(
+                Layer::new("e", ContentType::Field)
+                    .with_content(LayerContent::Engine(binding.clone())),
+            )
+            .unwrap();
+        for policy in [
+            ResizePolicy::Scale,
+            ResizePolicy::Crop,
+            ResizePolicy::Extend,
+        ] {
+            canvas.resize(32, 16, policy).unwrap();
+            assert_eq!(
+                canvas.layer("e").unwrap().content(),
+                Some(&LayerContent::Engine(binding.clone()))
+            );
+        }
+    }
+
+    #[test]
+    fn resize_policy_serializes_as_snake_case() {
+        assert_eq!(
+            serde_json::to_string(&ResizePolicy::Extend).unwrap(),
+            "\"extend\""
+        );
+        assert_eq!(ResizePolicy::default(), ResizePolicy::Scale);
+    }
+
     // ── Canvas construction tests ──────────────────────────────────
 
     #[test]
@@ -499,8 +1416,8 @@ mod tests {
             .add_layer(Layer::new("top", ContentType::Particles))
             .unwrap();
         assert_eq!(canvas.layer_count(), 2);
-        assert_eq!(canvas.layers()[0].name(), "bottom");
-        assert_eq!(canvas.layers()[1].name(), "top");
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
+        assert_eq!(names, ["bottom", "top"]);
     }
 
     #[test]
@@ -544,7 +1461,7 @@ mod tests {
             .add_layer(Layer::new("c", ContentType::Shapes))
             .unwrap();
         canvas.remove_layer("b").unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["a", "c"]);
     }
 
@@ -595,7 +1512,7 @@ mod tests {
             .add_layer(Layer::new("c", ContentType::Shapes))
             .unwrap();
         canvas.move_layer_up("a").unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["b", "a", "c"]);
     }
 
@@ -609,7 +1526,7 @@ mod tests {
             .add_layer(Layer::new("b", ContentType::Particles))
             .unwrap();
         canvas.move_layer_up("b").unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["a", "b"]);
     }
 
@@ -626,7 +1543,7 @@ mod tests {
             .add_layer(Layer::new("c", ContentType::Shapes))
             .unwrap();
         canvas.move_layer_down("c").unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["a", "c", "b"]);
     }
 
@@ -640,7 +1557,7 @@ mod tests {
             .add_layer(Layer::new("b", ContentType::Particles))
             .unwrap();
         canvas.move_layer_down("a").unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["a", "b"]);
     }
 
@@ -658,7 +1575,7 @@ mod tests {
             .unwrap();
         // Move "c" from top (index 2) to bottom (index 0)
         canvas.move_layer_to("c", 0).unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["c", "a", "b"]);
     }
 
@@ -672,7 +1589,7 @@ mod tests {
             .add_layer(Layer::new("b", ContentType::Particles))
             .unwrap();
         canvas.move_layer_to("a", 100).unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["b", "a"]);
     }
 
@@ -754,14 +1671,127 @@ mod tests {
         canvas
             .add_layer(Layer::new("top", ContentType::Shapes))
             .unwrap();
-        let names: Vec<&str> = canvas.layers().iter().map(|l| l.name()).collect();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
         assert_eq!(names, vec!["bottom", "middle", "top"]);
     }
 
     #[test]
     fn layers_iter_empty_canvas() {
         let canvas = Canvas::new(100, 100, black()).unwrap();
-        assert_eq!(canvas.layers().iter().count(), 0);
+        assert_eq!(canvas.layers().count(), 0);
+    }
+
+    // ── Group tests ────────────────────────────────────────────────
+
+    fn grouped_canvas() -> Canvas {
+        let mut canvas = Canvas::new(10, 10, black()).unwrap();
+        canvas
+            .add_layer(Layer::new("bottom", ContentType::Field))
+            .unwrap();
+        canvas
+            .add_group(
+                LayerGroup::new("g")
+                    .with_opacity(0.5)
+                    .with_child(Layer::new("a", ContentType::Field))
+                    .with_child(
+                        LayerGroup::new("inner").with_child(Layer::new("b", ContentType::Shapes)),
+                    ),
+            )
+            .unwrap();
+        canvas
+    }
+
+    #[test]
+    fn layers_flatten_groups_in_render_order() {
+        let canvas = grouped_canvas();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
+        assert_eq!(names, vec!["bottom", "a", "b"]);
+        assert_eq!(canvas.layer_count(), 3);
+        assert_eq!(canvas.nodes().len(), 2);
+    }
+
+    #[test]
+    fn add_group_rejects_names_taken_anywhere_in_the_tree() {
+        let mut canvas = grouped_canvas();
+        let err = canvas
+            .add_group(LayerGroup::new("h").with_child(Layer::new("b", ContentType::Field)))
+            .unwrap_err();
+        assert!(matches!(err, EngineError::DuplicateLayerName(n) if n == "b"));
+        assert!(canvas
+            .add_layer(Layer::new("inner", ContentType::Field))
+            .is_err());
+        assert_eq!(canvas.nodes().len(), 2);
+    }
+
+    #[test]
+    fn nested_lookup_and_mutation() {
+        let mut canvas = grouped_canvas();
+        assert_eq!(
+            canvas.layer("b").unwrap().content_type(),
+            ContentType::Shapes
+        );
+        canvas.layer_mut("b").unwrap().set_opacity(0.25);
+        assert_eq!(canvas.layer("b").unwrap().opacity(), 0.25);
+        canvas.group_mut("inner").unwrap().set_visible(false);
+        assert!(!canvas.group("inner").unwrap().visible());
+        assert!(canvas.layer("inner").is_err());
+        assert!(canvas.group("a").is_err());
+    }
+
+    #[test]
+    fn remove_and_move_work_within_groups() {
+        let mut canvas = grouped_canvas();
+        canvas.move_layer_to("a", 10).unwrap();
+        let names: Vec<&str> = canvas.layers().map(|l| l.name()).collect();
+        assert_eq!(names, vec!["bottom", "b", "a"]);
+        assert!(canvas.remove_layer("g").is_err());
+        assert_eq!(canvas.remove_layer("b").unwrap().name(), "b");
+        let group = canvas.remove_node("g").unwrap();
+        assert_eq!(group.as_group().unwrap().children().len(), 2);
+        assert_eq!(canvas.layer_count(), 1);
+    }
+
+    #[test]
+    fn group_opacity_clamps_and_defaults() {
+        let group = LayerGroup::new("g").with_opacity(2.0);
+        assert_eq!(group.opacity(), 1.0);
+        assert_eq!(group.blend_mode(), BlendMode::Normal);
+        assert!(group.visible());
+    }
+
+    #[test]
+    fn group_serde_round_trip_and_flat_json_still_parses() {
+        let canvas = grouped_canvas();
+        let json = serde_json::to_string(&canvas).unwrap();
+        let restored: Canvas = serde_json::from_str(&json).unwrap();
+        assert_eq!(canvas, restored);
+
+        let value = serde_json::to_value(&canvas).unwrap();
+        assert_eq!(value["layers"][1]["children"][0]["name"], "a");
+        let flat = r##"{"width":2,"height":2,"background":"#000000",
+            "layers":[{"name":"x","blend_mode":"normal","opacity":1.0,"visible":true,"content_type":"field"}]}"##;
+        let flat: Canvas = serde_json::from_str(flat).unwrap();
+        assert!(flat.nodes()[0].as_layer().is_some());
+    }
+
+    #[test]
+    fn resize_reaches_layers_inside_groups() {
+        let mut canvas = Canvas::new(10, 10, black()).unwrap();
+        canvas
+            .add_group(LayerGroup::new("g").with_child(circle_layer(0.5, 0.5, 0.2)))
+            .unwrap();
+        canvas.resize(20, 10, ResizePolicy::Extend).unwrap();
+        assert!(matches!(
+            canvas.layer("c").unwrap().mask(),
+            Some(Mask::Circle { cx, .. }) if (cx - 0.5).abs() < 1e-12
+        ));
+        assert_ne!(canvas, {
+            let mut other = Canvas::new(10, 10, black()).unwrap();
+            other
+                .add_group(LayerGroup::new("g").with_child(circle_layer(0.5, 0.5, 0.2)))
+                .unwrap();
+            other
+        });
     }
 
     // ── Property-based tests ───────────────────────────────────────
@@ -1,13 +1,28 @@
 //! Color types and conversion functions for the art-engine.
 //!
 //! Provides four color types (`Srgb`, `LinearRgb`, `OkLab`, `OkLch`) and
-//! pure conversion functions between them. All conversions are pure functions
-//! (no methods with side effects). Uses `f64` throughout for precision.
+//! pure conversion functions between them, plus straight-alpha variants
+//! (`Srgba`, `OkLcha`) with premultiplication helpers. For imported
+//! palettes and external color specifications it also converts sRGB to and
+//! from `Hsl`, `Hsv`, CIE `Xyz` and CIELAB (`Lab`), all relative to the D65
+//! white point. All conversions are pure functions (no methods with side
+//! effects). Uses `f64` throughout for precision.
+//!
+//! Output can be encoded in wide-gamut RGB ([`ColorSpace`]: Display P3 or
+//! Rec.2020) so saturated OKLCh colors outside sRGB are kept rather than
+//! clamped.
+//!
+//! [`parse_color`] reads CSS-style color strings (hex, `rgb()`, `hsl()`,
+//! named colors) wherever colors come from users.
+//!
+//! [`delta_e_ok`] measures perceptual difference (Euclidean distance in
+//! OKLab) and [`nearest_color`] picks the closest of a set of colors.
 //!
 //! The OKLab color space provides perceptually uniform gradients, making it
 //! ideal for generative art palette interpolation.
 
 use crate::error::EngineError;
+use crate::math;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// sRGB color with components in [0, 1].
@@ -46,6 +61,72 @@ pub struct OkLch {
     pub h: f64,
 }
 
+/// HSL: hue in degrees [0, 360), saturation and lightness in [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+}
+
+/// HSV: hue in degrees [0, 360), saturation and value in [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+}
+
+/// CIE 1931 XYZ relative to D65, with `y` (luminance) 1.0 for white.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Xyz {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// CIELAB (L*a*b*) relative to D65, with `l` in [0, 100].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// sRGB color with straight (non-premultiplied) alpha, components in [0, 1].
+///
+/// Serializes as a hex string `"#rrggbbaa"`; `"#rrggbb"` also parses, as
+/// fully opaque. Converts to and from the `[r, g, b, a]` pixels of
+/// [`Image`](crate::image::Image).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Srgba {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub alpha: f64,
+}
+
+/// OKLCh with straight alpha.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OkLcha {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+    pub alpha: f64,
+}
+
+/// Parses the 8-bit hex component `name` at `hex[at..at + 2]` into [0, 1].
+fn hex_component(hex: &str, at: usize, name: &str) -> Result<f64, EngineError> {
+    u8::from_str_radix(&hex[at..at + 2], 16)
+        .map(|v| v as f64 / 255.0)
+        .map_err(|e| EngineError::InvalidColor(format!("invalid {name} component: {e}")))
+}
+
+/// Quantizes a [0, 1] component to 8 bits with clamping and rounding.
+fn to_u8(c: f64) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
 impl Srgb {
     /// Parses a hex color string like "#ff00aa" or "ff00aa" (case insensitive).
     ///
@@ -58,16 +139,10 @@ impl Srgb {
                 hex.len()
             )));
         }
-        let r = u8::from_str_radix(&hex[0..2], 16)
-            .map_err(|e| EngineError::InvalidColor(format!("invalid red component: {e}")))?;
-        let g = u8::from_str_radix(&hex[2..4], 16)
-            .map_err(|e| EngineError::InvalidColor(format!("invalid green component: {e}")))?;
-        let b = u8::from_str_radix(&hex[4..6], 16)
-            .map_err(|e| EngineError::InvalidColor(format!("invalid blue component: {e}")))?;
         Ok(Srgb {
-            r: r as f64 / 255.0,
-            g: g as f64 / 255.0,
-            b: b as f64 / 255.0,
+            r: hex_component(hex, 0, "red")?,
+            g: hex_component(hex, 2, "green")?,
+            b: hex_component(hex, 4, "blue")?,
         })
     }
 
@@ -75,11 +150,137 @@ impl Srgb {
     ///
     /// Components are quantized to 8-bit (0–255) with rounding.
     pub fn to_hex(self) -> String {
-        let r = (self.r.clamp(0.0, 1.0) * 255.0).round() as u8;
-        let g = (self.g.clamp(0.0, 1.0) * 255.0).round() as u8;
-        let b = (self.b.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let (r, g, b) = (to_u8(self.r), to_u8(self.g), to_u8(self.b));
         format!("#{r:02x}{g:02x}{b:02x}")
     }
+
+    /// Returns this color with the given alpha.
+    pub fn with_alpha(self, alpha: f64) -> Srgba {
+        Srgba {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+            alpha,
+        }
+    }
+}
+
+impl Srgba {
+    /// Parses `"#rrggbbaa"` or, as fully opaque, `"#rrggbb"`; the `#` is
+    /// optional and case is ignored.
+    ///
+    /// Returns `EngineError::InvalidColor` if the input is not 6 or 8 hex digits.
+    pub fn from_hex(hex: &str) -> Result<Srgba, EngineError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        match digits.len() {
+            6 => Ok(Srgb::from_hex(digits)?.with_alpha(1.0)),
+            8 => Ok(Srgb::from_hex(&digits[..6])?.with_alpha(hex_component(digits, 6, "alpha")?)),
+            len => Err(EngineError::InvalidColor(format!(
+                "expected 6 or 8 hex digits, got {len}"
+            ))),
+        }
+    }
+
+    /// Converts the color to a hex string like `"#rrggbbaa"`, quantizing
+    /// each component to 8 bits.
+    pub fn to_hex(self) -> String {
+        format!("{}{:02x}", self.rgb().to_hex(), to_u8(self.alpha))
+    }
+
+    /// The color without its alpha.
+    pub fn rgb(self) -> Srgb {
+        Srgb {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+        }
+    }
+
+    /// Quantizes to RGBA8 with clamping and rounding.
+    pub fn to_rgba8(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.alpha].map(to_u8)
+    }
+
+    /// Builds a color from RGBA8 components.
+    pub fn from_rgba8(rgba: [u8; 4]) -> Srgba {
+        let [r, g, b, alpha] = rgba.map(|v| v as f64 / 255.0);
+        Srgba { r, g, b, alpha }
+    }
+}
+
+impl From<Srgb> for Srgba {
+    /// Fully opaque.
+    fn from(c: Srgb) -> Self {
+        c.with_alpha(1.0)
+    }
+}
+
+impl From<[f64; 4]> for Srgba {
+    fn from([r, g, b, alpha]: [f64; 4]) -> Self {
+        Srgba { r, g, b, alpha }
+    }
+}
+
+impl From<Srgba> for [f64; 4] {
+    fn from(c: Srgba) -> Self {
+        [c.r, c.g, c.b, c.alpha]
+    }
+}
+
+impl Serialize for Srgba {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Srgba {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_color(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for Srgba {
+    type Err = EngineError;
+
+    /// Parses any color string [`parse_color`] accepts.
+    fn from_str(color: &str) -> Result<Self, Self::Err> {
+        parse_color(color)
+    }
+}
+
+/// Multiplies the color components by alpha (clamped to [0, 1]).
+pub fn premultiply(c: Srgba) -> Srgba {
+    let alpha = c.alpha.clamp(0.0, 1.0);
+    Srgba {
+        r: c.r * alpha,
+        g: c.g * alpha,
+        b: c.b * alpha,
+        alpha,
+    }
+}
+
+/// Divides premultiplied color components by alpha, recovering straight
+/// alpha.
+///
+/// A fully transparent color has no recoverable color and becomes
+/// transparent black.
+pub fn unpremultiply(c: Srgba) -> Srgba {
+    let alpha = c.alpha.clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return Srgba {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            alpha: 0.0,
+        };
+    }
+    Srgba {
+        r: (c.r / alpha).clamp(0.0, 1.0),
+        g: (c.g / alpha).clamp(0.0, 1.0),
+        b: (c.b / alpha).clamp(0.0, 1.0),
+        alpha,
+    }
 }
 
 impl Serialize for Srgb {
@@ -91,7 +292,27 @@ impl Serialize for Srgb {
 impl<'de> Deserialize<'de> for Srgb {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let s = String::deserialize(deserializer)?;
-        Srgb::from_hex(&s).map_err(serde::de::Error::custom)
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for Srgb {
+    type Err = EngineError;
+
+    /// Parses any color string [`parse_color`] accepts, as long as it is
+    /// fully opaque.
+    ///
+    /// Returns `EngineError::InvalidColor` for a malformed or translucent
+    /// color.
+    fn from_str(color: &str) -> Result<Self, Self::Err> {
+        let rgba = parse_color(color)?;
+        if rgba.alpha < 1.0 {
+            return Err(EngineError::InvalidColor(format!(
+                "'{}': expected an opaque color",
+                color.trim()
+            )));
+        }
+        Ok(rgba.rgb())
     }
 }
 
@@ -100,7 +321,7 @@ fn srgb_component_to_linear(c: f64) -> f64 {
     if c <= 0.04045 {
         c / 12.92
     } else {
-        ((c + 0.055) / 1.055).powf(2.4)
+        math::powf((c + 0.055) / 1.055, 2.4)
     }
 }
 
@@ -109,7 +330,7 @@ fn linear_component_to_srgb(c: f64) -> f64 {
     if c <= 0.0031308 {
         c * 12.92
     } else {
-        1.055 * c.powf(1.0 / 2.4) - 0.055
+        1.055 * math::powf(c, 1.0 / 2.4) - 0.055
     }
 }
 
@@ -137,9 +358,9 @@ pub fn linear_to_oklab(c: LinearRgb) -> OkLab {
     let m_ = 0.2119034982 * c.r + 0.6806995451 * c.g + 0.1073969566 * c.b;
     let s_ = 0.0883024619 * c.r + 0.2817188376 * c.g + 0.6299787005 * c.b;
 
-    let l_c = l_.cbrt();
-    let m_c = m_.cbrt();
-    let s_c = s_.cbrt();
+    let l_c = math::cbrt(l_);
+    let m_c = math::cbrt(m_);
+    let s_c = math::cbrt(s_);
 
     OkLab {
         l: 0.2104542553 * l_c + 0.7936177850 * m_c - 0.0040720468 * s_c,
@@ -174,7 +395,7 @@ pub fn oklab_to_oklch(c: OkLab) -> OkLch {
     let h = if ch < 1e-10 {
         0.0
     } else {
-        c.b.atan2(c.a).to_degrees().rem_euclid(360.0)
+        math::atan2(c.b, c.a).to_degrees().rem_euclid(360.0)
     };
     OkLch { l: c.l, c: ch, h }
 }
@@ -184,8 +405,8 @@ pub fn oklch_to_oklab(c: OkLch) -> OkLab {
     let h_rad = c.h.to_radians();
     OkLab {
         l: c.l,
-        a: c.c * h_rad.cos(),
-        b: c.c * h_rad.sin(),
+        a: c.c * math::cos(h_rad),
+        b: c.c * math::sin(h_rad),
     }
 }
 
@@ -205,6 +426,636 @@ pub fn oklch_to_srgb(c: OkLch) -> Srgb {
     }
 }
 
+/// Perceptual difference between two sRGB colors (ΔE-OK): the Euclidean
+/// distance between them in OKLab.
+///
+/// 0 for identical colors, about 1.0 for black against white; differences
+/// below roughly 0.02 are hard to see.
+pub fn delta_e_ok(a: Srgb, b: Srgb) -> f64 {
+    oklab_distance(
+        linear_to_oklab(srgb_to_linear(a)),
+        linear_to_oklab(srgb_to_linear(b)),
+    )
+}
+
+/// Euclidean distance between two OKLab colors.
+fn oklab_distance(a: OkLab, b: OkLab) -> f64 {
+    ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt()
+}
+
+/// Finds the candidate perceptually closest to `target`.
+///
+/// Returns the candidate's index and its ΔE-OK from `target`, or `None`
+/// when `candidates` is empty. Ties go to the earliest candidate.
+pub fn nearest_color(target: Srgb, candidates: &[Srgb]) -> Option<(usize, f64)> {
+    let target = linear_to_oklab(srgb_to_linear(target));
+    candidates
+        .iter()
+        .map(|&c| oklab_distance(target, linear_to_oklab(srgb_to_linear(c))))
+        .enumerate()
+        .fold(None, |best, (i, d)| match best {
+            Some((_, best_d)) if best_d <= d => best,
+            _ => Some((i, d)),
+        })
+}
+
+/// Returns the hue in degrees [0, 360) of an RGB color with the given
+/// largest component and chroma (largest minus smallest).
+///
+/// Achromatic colors (chroma below 1e-10) get hue 0.
+fn rgb_hue(c: Srgb, max: f64, chroma: f64) -> f64 {
+    if chroma < 1e-10 {
+        return 0.0;
+    }
+    let sector = if max == c.r {
+        (c.g - c.b) / chroma
+    } else if max == c.g {
+        (c.b - c.r) / chroma + 2.0
+    } else {
+        (c.r - c.g) / chroma + 4.0
+    };
+    (sector * 60.0).rem_euclid(360.0)
+}
+
+/// Builds the RGB color with hue `h` (degrees), chroma `chroma`, and
+/// `offset` added to every component.
+fn hue_chroma_to_srgb(h: f64, chroma: f64, offset: f64) -> Srgb {
+    let sector = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Srgb {
+        r: r + offset,
+        g: g + offset,
+        b: b + offset,
+    }
+}
+
+/// Converts sRGB to HSL.
+pub fn srgb_to_hsl(c: Srgb) -> Hsl {
+    let max = c.r.max(c.g).max(c.b);
+    let min = c.r.min(c.g).min(c.b);
+    let chroma = max - min;
+    let l = (max + min) / 2.0;
+    let s = if chroma < 1e-10 {
+        0.0
+    } else {
+        chroma / (1.0 - (2.0 * l - 1.0).abs())
+    };
+    Hsl {
+        h: rgb_hue(c, max, chroma),
+        s,
+        l,
+    }
+}
+
+/// Converts HSL to sRGB. Saturation and lightness are clamped to [0, 1];
+/// any hue is accepted.
+pub fn hsl_to_srgb(c: Hsl) -> Srgb {
+    let (s, l) = (c.s.clamp(0.0, 1.0), c.l.clamp(0.0, 1.0));
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_chroma_to_srgb(c.h, chroma, l - chroma / 2.0)
+}
+
+/// Converts sRGB to HSV.
+pub fn srgb_to_hsv(c: Srgb) -> Hsv {
+    let max = c.r.max(c.g).max(c.b);
+    let chroma = max - c.r.min(c.g).min(c.b);
+    let s = if max < 1e-10 { 0.0 } else { chroma / max };
+    Hsv {
+        h: rgb_hue(c, max, chroma),
+        s,
+        v: max,
+    }
+}
+
+/// Converts HSV to sRGB. Saturation and value are clamped to [0, 1]; any
+/// hue is accepted.
+pub fn hsv_to_srgb(c: Hsv) -> Srgb {
+    let (s, v) = (c.s.clamp(0.0, 1.0), c.v.clamp(0.0, 1.0));
+    let chroma = v * s;
+    hue_chroma_to_srgb(c.h, chroma, v - chroma)
+}
+
+/// D65 reference white in XYZ.
+const D65_WHITE: Xyz = Xyz {
+    x: 0.95047,
+    y: 1.0,
+    z: 1.08883,
+};
+
+/// Converts linear RGB (sRGB primaries) to CIE XYZ.
+pub fn linear_to_xyz(c: LinearRgb) -> Xyz {
+    Xyz {
+        x: 0.4124564 * c.r + 0.3575761 * c.g + 0.1804375 * c.b,
+        y: 0.2126729 * c.r + 0.7151522 * c.g + 0.0721750 * c.b,
+        z: 0.0193339 * c.r + 0.1191920 * c.g + 0.9503041 * c.b,
+    }
+}
+
+/// Converts CIE XYZ to linear RGB (sRGB primaries). Colors outside the
+/// sRGB gamut come out below 0 or above 1.
+pub fn xyz_to_linear(c: Xyz) -> LinearRgb {
+    LinearRgb {
+        r: 3.2404542 * c.x - 1.5371385 * c.y - 0.4985314 * c.z,
+        g: -0.9692660 * c.x + 1.8760108 * c.y + 0.0415560 * c.z,
+        b: 0.0556434 * c.x - 0.2040259 * c.y + 1.0572252 * c.z,
+    }
+}
+
+/// CIELAB threshold (6/29) between the cube-root and linear segments.
+const LAB_DELTA: f64 = 6.0 / 29.0;
+
+/// Converts CIE XYZ to CIELAB relative to D65.
+pub fn xyz_to_lab(c: Xyz) -> Lab {
+    let f = |t: f64| {
+        if t > LAB_DELTA.powi(3) {
+            math::cbrt(t)
+        } else {
+            t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+        }
+    };
+    let fx = f(c.x / D65_WHITE.x);
+    let fy = f(c.y / D65_WHITE.y);
+    let fz = f(c.z / D65_WHITE.z);
+    Lab {
+        l: 116.0 * fy - 16.0,
+        a: 500.0 * (fx - fy),
+        b: 200.0 * (fy - fz),
+    }
+}
+
+/// Converts CIELAB relative to D65 to CIE XYZ.
+pub fn lab_to_xyz(c: Lab) -> Xyz {
+    let f_inv = |t: f64| {
+        if t > LAB_DELTA {
+            t * t * t
+        } else {
+            3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0)
+        }
+    };
+    let fy = (c.l + 16.0) / 116.0;
+    Xyz {
+        x: D65_WHITE.x * f_inv(fy + c.a / 500.0),
+        y: D65_WHITE.y * f_inv(fy),
+        z: D65_WHITE.z * f_inv(fy - c.b / 200.0),
+    }
+}
+
+/// Convenience: sRGB to CIELAB via the chain sRGB -> linear -> XYZ -> Lab.
+pub fn srgb_to_lab(c: Srgb) -> Lab {
+    xyz_to_lab(linear_to_xyz(srgb_to_linear(c)))
+}
+
+/// Convenience: CIELAB to sRGB via the chain Lab -> XYZ -> linear -> sRGB,
+/// with output clamped to [0, 1].
+pub fn lab_to_srgb(c: Lab) -> Srgb {
+    let srgb = linear_to_srgb(xyz_to_linear(lab_to_xyz(c)));
+    Srgb {
+        r: srgb.r.clamp(0.0, 1.0),
+        g: srgb.g.clamp(0.0, 1.0),
+        b: srgb.b.clamp(0.0, 1.0),
+    }
+}
+
+/// RGB color space that output pixels are encoded in.
+///
+/// All three use the D65 white point and the sRGB transfer curve; they
+/// differ in primaries, so Display P3 and Rec.2020 hold progressively more
+/// saturated colors. Serialized by [`ColorSpace::name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorSpace {
+    /// sRGB, the default.
+    #[default]
+    Srgb,
+    /// Display P3 (DCI-P3 primaries, D65), as on most modern displays.
+    DisplayP3,
+    /// ITU-R BT.2020 primaries.
+    Rec2020,
+}
+
+/// Linear sRGB to linear Display P3.
+const SRGB_TO_DISPLAY_P3: [[f64; 3]; 3] = [
+    [0.8224621, 0.1775380, 0.0000000],
+    [0.0331941, 0.9668058, 0.0000000],
+    [0.0170827, 0.0723974, 0.9105199],
+];
+/// Linear Display P3 to linear sRGB.
+const DISPLAY_P3_TO_SRGB: [[f64; 3]; 3] = [
+    [1.2249401, -0.2249404, 0.0000000],
+    [-0.0420569, 1.0420571, 0.0000000],
+    [-0.0196376, -0.0786361, 1.0982735],
+];
+/// Linear sRGB to linear Rec.2020.
+const SRGB_TO_REC2020: [[f64; 3]; 3] = [
+    [0.6274039, 0.3292830, 0.0433131],
+    [0.0690973, 0.9195404, 0.0113623],
+    [0.0163914, 0.0880133, 0.8955953],
+];
+/// Linear Rec.2020 to linear sRGB.
+const REC2020_TO_SRGB: [[f64; 3]; 3] = [
+    [1.6604910, -0.5876411, -0.0728499],
+    [-0.1245505, 1.1328999, -0.0083494],
+    [-0.0181508, -0.1005789, 1.1187297],
+];
+
+/// Multiplies a linear RGB color by a 3x3 matrix.
+fn transform(m: &[[f64; 3]; 3], c: LinearRgb) -> LinearRgb {
+    let row = |r: [f64; 3]| r[0] * c.r + r[1] * c.g + r[2] * c.b;
+    LinearRgb {
+        r: row(m[0]),
+        g: row(m[1]),
+        b: row(m[2]),
+    }
+}
+
+impl ColorSpace {
+    /// Every color space, in declaration order.
+    pub const ALL: [ColorSpace; 3] = [ColorSpace::Srgb, ColorSpace::DisplayP3, ColorSpace::Rec2020];
+
+    /// The color space's name, e.g. `"display-p3"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::DisplayP3 => "display-p3",
+            ColorSpace::Rec2020 => "rec2020",
+        }
+    }
+
+    /// Looks a color space up by [`ColorSpace::name`], ignoring case.
+    pub fn from_name(name: &str) -> Option<ColorSpace> {
+        Self::ALL
+            .into_iter()
+            .find(|space| space.name().eq_ignore_ascii_case(name))
+    }
+
+    /// True for sRGB, the default.
+    pub fn is_srgb(&self) -> bool {
+        *self == ColorSpace::Srgb
+    }
+
+    /// Converts linear sRGB, which may lie outside the sRGB gamut, to
+    /// linear RGB in this space.
+    pub fn from_linear_srgb(self, c: LinearRgb) -> LinearRgb {
+        match self {
+            ColorSpace::Srgb => c,
+            ColorSpace::DisplayP3 => transform(&SRGB_TO_DISPLAY_P3, c),
+            ColorSpace::Rec2020 => transform(&SRGB_TO_REC2020, c),
+        }
+    }
+
+    /// Converts linear RGB in this space to linear sRGB.
+    pub fn to_linear_srgb(self, c: LinearRgb) -> LinearRgb {
+        match self {
+            ColorSpace::Srgb => c,
+            ColorSpace::DisplayP3 => transform(&DISPLAY_P3_TO_SRGB, c),
+            ColorSpace::Rec2020 => transform(&REC2020_TO_SRGB, c),
+        }
+    }
+
+    /// Encodes an OKLCh color as gamma-encoded RGB in this space, clamped
+    /// to its gamut. For sRGB this is exactly [`oklch_to_srgb`].
+    pub fn encode_oklch(self, c: OkLch) -> [f64; 3] {
+        let rgb = match self {
+            ColorSpace::Srgb => oklch_to_srgb(c),
+            wide => {
+                let linear = wide.from_linear_srgb(oklab_to_linear(oklch_to_oklab(c)));
+                Srgb {
+                    r: linear_component_to_srgb(linear.r.clamp(0.0, 1.0)),
+                    g: linear_component_to_srgb(linear.g.clamp(0.0, 1.0)),
+                    b: linear_component_to_srgb(linear.b.clamp(0.0, 1.0)),
+                }
+            }
+        };
+        [rgb.r, rgb.g, rgb.b]
+    }
+}
+
+impl std::str::FromStr for ColorSpace {
+    type Err = EngineError;
+
+    /// Returns `EngineError::InvalidColor` for an unknown name.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::from_name(name).ok_or_else(|| {
+            EngineError::InvalidColor(format!(
+                "unknown color space '{name}' (expected {})",
+                Self::ALL.map(ColorSpace::name).join(", ")
+            ))
+        })
+    }
+}
+
+/// [`srgb_to_oklch`] carrying alpha through unchanged.
+pub fn srgba_to_oklcha(c: Srgba) -> OkLcha {
+    let OkLch { l, c: chroma, h } = srgb_to_oklch(c.rgb());
+    OkLcha {
+        l,
+        c: chroma,
+        h,
+        alpha: c.alpha,
+    }
+}
+
+/// [`oklch_to_srgb`] carrying alpha through unchanged.
+pub fn oklcha_to_srgba(c: OkLcha) -> Srgba {
+    oklch_to_srgb(OkLch {
+        l: c.l,
+        c: c.c,
+        h: c.h,
+    })
+    .with_alpha(c.alpha)
+}
+
+// -- CSS color strings --
+
+/// Parses a CSS-style color string into straight-alpha sRGB.
+///
+/// Accepts, case-insensitively and with surrounding whitespace ignored:
+///
+/// - hex: `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` (the `#` may be left off
+///   the 6- and 8-digit forms);
+/// - `rgb(255, 128, 0)` / `rgba(...)`, channels 0-255 or percentages;
+/// - `hsl(210, 50%, 40%)` / `hsla(...)`, hue in degrees;
+/// - the CSS named colors (`rebeccapurple`) and `transparent`.
+///
+/// The functional forms also take the space-separated syntax with an
+/// optional `/ alpha` (`rgb(255 128 0 / 50%)`). Alpha is a number in
+/// [0, 1] or a percentage, and out-of-range values are clamped.
+///
+/// Returns `EngineError::InvalidColor` if the string is none of these.
+pub fn parse_color(color: &str) -> Result<Srgba, EngineError> {
+    let lower = color.trim().to_ascii_lowercase();
+    let invalid = |msg: String| EngineError::InvalidColor(format!("'{}': {msg}", color.trim()));
+    if let Some((function, args)) = lower.strip_suffix(')').and_then(|f| f.split_once('(')) {
+        return parse_color_function(function.trim(), args).map_err(invalid);
+    }
+    if lower == "transparent" {
+        return Ok(Srgba::from_rgba8([0, 0, 0, 0]));
+    }
+    if let Ok(i) = NAMED_COLORS.binary_search_by_key(&lower.as_str(), |&(name, _)| name) {
+        let [_, r, g, b] = NAMED_COLORS[i].1.to_be_bytes();
+        return Ok(Srgba::from_rgba8([r, g, b, 255]));
+    }
+    let (hash, digits) = match lower.strip_prefix('#') {
+        Some(digits) => (true, digits),
+        None => (false, lower.as_str()),
+    };
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        match digits.len() {
+            3 | 4 if hash => {
+                let long: String = digits.chars().flat_map(|c| [c, c]).collect();
+                return Srgba::from_hex(&long);
+            }
+            6 | 8 => return Srgba::from_hex(digits),
+            _ => {}
+        }
+    }
+    Err(invalid(
+        "expected a hex color, rgb(), hsl() or a color name".into(),
+    ))
+}
+
+/// Parses the arguments of an `rgb()`/`rgba()`/`hsl()`/`hsla()` color.
+fn parse_color_function(function: &str, args: &str) -> Result<Srgba, String> {
+    // Legacy comma syntax, or space-separated channels with "/ alpha"
+    let parts: Vec<&str> = if args.contains(',') {
+        args.split(',').map(str::trim).collect()
+    } else {
+        let (channels, alpha) = match args.split_once('/') {
+            Some((channels, alpha)) => (channels, Some(alpha.trim())),
+            None => (args, None),
+        };
+        channels.split_whitespace().chain(alpha).collect()
+    };
+    let (channels, alpha) = match parts.as_slice() {
+        [a, b, c] => ([*a, *b, *c], 1.0),
+        [a, b, c, alpha] => ([*a, *b, *c], css_fraction(alpha, 1.0)?),
+        _ => return Err(format!("expected 3 or 4 values, got {}", parts.len())),
+    };
+    let rgb = match function {
+        "rgb" | "rgba" => {
+            let [r, g, b] = channels;
+            Srgb {
+                r: css_fraction(r, 255.0)?,
+                g: css_fraction(g, 255.0)?,
+                b: css_fraction(b, 255.0)?,
+            }
+        }
+        "hsl" | "hsla" => {
+            let [h, s, l] = channels;
+            let h = css_number(h.strip_suffix("deg").unwrap_or(h))?.rem_euclid(360.0);
+            let percent = |v: &str| css_fraction(v.strip_suffix('%').unwrap_or(v), 100.0);
+            hsl_to_srgb(Hsl {
+                h,
+                s: percent(s)?,
+                l: percent(l)?,
+            })
+        }
+        _ => return Err(format!("unknown color function '{function}'")),
+    };
+    Ok(rgb.with_alpha(alpha))
+}
+
+/// Parses a CSS value as a fraction in [0, 1]: a percentage, or a number
+/// divided by `scale`.
+fn css_fraction(value: &str, scale: f64) -> Result<f64, String> {
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => css_number(percent)? / 100.0,
+        None => css_number(value)? / scale,
+    };
+    Ok(fraction.clamp(0.0, 1.0))
+}
+
+/// Parses a finite CSS number.
+fn css_number(value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| format!("invalid number '{value}'"))
+}
+
+/// Splits a list of color strings on commas and whitespace, keeping the
+/// insides of `rgb(...)`-style functions together.
+pub fn split_colors(list: &str) -> Vec<&str> {
+    let mut colors = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && (c == ',' || c.is_whitespace()) => {
+                colors.push(&list[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    colors.push(&list[start..]);
+    colors.retain(|color| !color.is_empty());
+    colors
+}
+
+/// The CSS Color Module Level 4 named colors, sorted by name.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -702,6 +1553,296 @@ mod tests {
         assert_eq!(once.b.to_bits(), twice.b.to_bits());
     }
 
+    // -- Alpha tests --
+
+    #[test]
+    fn srgba_parses_eight_and_six_digit_hex() {
+        let c = Srgba::from_hex("#ff000080").unwrap();
+        assert!(approx_eq(c.r, 1.0) && approx_eq(c.g, 0.0));
+        assert!(approx_eq(c.alpha, 128.0 / 255.0));
+        assert!(approx_eq(Srgba::from_hex("00ff00").unwrap().alpha, 1.0));
+        assert_eq!(Srgba::from_hex("#C0FFEE80").unwrap().to_hex(), "#c0ffee80");
+        assert!(Srgba::from_hex("#ff00ff0").is_err());
+        assert!(Srgba::from_hex("#ff00ffzz").is_err());
+        assert!(Srgba::from_hex("").is_err());
+    }
+
+    #[test]
+    fn srgba_serializes_as_eight_digit_hex() {
+        let c = Srgb::from_hex("#804020").unwrap().with_alpha(0.0);
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(json, "\"#80402000\"");
+        let back: Srgba = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_hex(), "#80402000");
+        let opaque: Srgba = serde_json::from_str("\"#804020\"").unwrap();
+        assert_eq!(opaque, Srgba::from(Srgb::from_hex("#804020").unwrap()));
+        assert!(serde_json::from_str::<Srgba>("\"#80402\"").is_err());
+    }
+
+    #[test]
+    fn srgba_converts_to_pixels_and_rgba8() {
+        let c = Srgba {
+            r: 1.0,
+            g: 0.5,
+            b: -0.2,
+            alpha: 0.25,
+        };
+        assert_eq!(<[f64; 4]>::from(c), [1.0, 0.5, -0.2, 0.25]);
+        assert_eq!(Srgba::from([1.0, 0.5, -0.2, 0.25]), c);
+        assert_eq!(c.to_rgba8(), [255, 128, 0, 64]);
+        assert_eq!(Srgba::from_rgba8([255, 0, 51, 255]).to_hex(), "#ff0033ff");
+    }
+
+    #[test]
+    fn premultiplication_round_trips() {
+        let c = Srgba::from_hex("#8040c080").unwrap();
+        let pre = premultiply(c);
+        assert!(approx_eq(pre.r, c.r * c.alpha));
+        assert!(approx_eq(pre.alpha, c.alpha));
+        let back = unpremultiply(pre);
+        assert!(approx_eq(back.r, c.r) && approx_eq(back.g, c.g) && approx_eq(back.b, c.b));
+        let clear = unpremultiply(premultiply(c.rgb().with_alpha(0.0)));
+        assert_eq!(<[f64; 4]>::from(clear), [0.0; 4]);
+    }
+
+    #[test]
+    fn oklcha_carries_alpha_through_conversion() {
+        let c = Srgba::from_hex("#c0ffee40").unwrap();
+        let lch = srgba_to_oklcha(c);
+        assert!(approx_eq(lch.alpha, c.alpha));
+        assert_eq!(oklcha_to_srgba(lch).to_hex(), "#c0ffee40");
+    }
+
+    // -- HSL / HSV / XYZ / CIELAB tests --
+
+    #[test]
+    fn hsl_and_hsv_of_primary_colors() {
+        let red = srgb_to_hsl(Srgb::from_hex("#ff0000").unwrap());
+        assert!(approx_eq(red.h, 0.0) && approx_eq(red.s, 1.0) && approx_eq(red.l, 0.5));
+        let blue = srgb_to_hsv(Srgb::from_hex("#0000ff").unwrap());
+        assert!(approx_eq(blue.h, 240.0) && approx_eq(blue.s, 1.0) && approx_eq(blue.v, 1.0));
+        let olive = srgb_to_hsl(Srgb::from_hex("#808000").unwrap());
+        assert!(approx_eq(olive.h, 60.0) && approx_eq(olive.s, 1.0));
+        let gray = srgb_to_hsv(Srgb::from_hex("#808080").unwrap());
+        assert!(approx_eq(gray.h, 0.0) && approx_eq(gray.s, 0.0));
+        assert_eq!(
+            hsl_to_srgb(Hsl {
+                h: 210.0,
+                s: 0.5,
+                l: 0.25
+            })
+            .to_hex(),
+            "#204060"
+        );
+        assert_eq!(
+            hsv_to_srgb(Hsv {
+                h: -60.0,
+                s: 1.0,
+                v: 1.0
+            })
+            .to_hex(),
+            "#ff00ff"
+        );
+    }
+
+    #[test]
+    fn xyz_of_white_is_the_d65_white_point() {
+        let white = linear_to_xyz(LinearRgb {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        });
+        assert!((white.x - 0.95047).abs() < 1e-4);
+        assert!(approx_eq(white.y, 1.0));
+        assert!((white.z - 1.08883).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cielab_of_reference_colors() {
+        let white = srgb_to_lab(Srgb::from_hex("#ffffff").unwrap());
+        assert!((white.l - 100.0).abs() < 1e-3);
+        assert!(white.a.abs() < 1e-2 && white.b.abs() < 1e-2);
+        // Published sRGB red: L 53.24, a 80.09, b 67.20
+        let red = srgb_to_lab(Srgb::from_hex("#ff0000").unwrap());
+        assert!((red.l - 53.24).abs() < 0.01, "{red:?}");
+        assert!((red.a - 80.09).abs() < 0.01, "{red:?}");
+        assert!((red.b - 67.20).abs() < 0.01, "{red:?}");
+        let black = srgb_to_lab(Srgb::from_hex("#000000").unwrap());
+        assert!(black.l.abs() < 1e-9);
+        assert_eq!(lab_to_srgb(red).to_hex(), "#ff0000");
+    }
+
+    // -- Wide-gamut tests --
+
+    #[test]
+    fn color_spaces_round_trip_through_their_names() {
+        for space in ColorSpace::ALL {
+            assert_eq!(space.name().parse::<ColorSpace>().unwrap(), space);
+            let json = serde_json::to_string(&space).unwrap();
+            assert_eq!(json, format!("\"{}\"", space.name()));
+        }
+        assert_eq!(
+            ColorSpace::from_name("Display-P3"),
+            Some(ColorSpace::DisplayP3)
+        );
+        assert!("adobe-rgb".parse::<ColorSpace>().is_err());
+        assert!(ColorSpace::default().is_srgb());
+    }
+
+    #[test]
+    fn wide_gamut_matrices_invert_and_keep_white() {
+        let white = LinearRgb {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        };
+        let color = LinearRgb {
+            r: 0.8,
+            g: 0.1,
+            b: 0.3,
+        };
+        for space in ColorSpace::ALL {
+            let w = space.from_linear_srgb(white);
+            assert!(
+                (w.r - 1.0).abs() < 1e-6 && (w.g - 1.0).abs() < 1e-6 && (w.b - 1.0).abs() < 1e-6
+            );
+            let back = space.to_linear_srgb(space.from_linear_srgb(color));
+            assert!((back.r - color.r).abs() < 1e-6, "{space:?}: {back:?}");
+            assert!((back.g - color.g).abs() < 1e-6, "{space:?}: {back:?}");
+            assert!((back.b - color.b).abs() < 1e-6, "{space:?}: {back:?}");
+        }
+    }
+
+    #[test]
+    fn wide_gamut_keeps_colors_srgb_would_clamp() {
+        // sRGB red is well inside P3, so it is no longer fully saturated there
+        let red = srgb_to_oklch(Srgb::from_hex("#ff0000").unwrap());
+        let p3 = ColorSpace::DisplayP3.encode_oklch(red);
+        assert!(p3[0] < 1.0 && p3[1] > 0.0, "{p3:?}");
+        // A more saturated red clamps in sRGB but not in P3
+        let vivid = OkLch { c: 0.28, ..red };
+        let clamped = ColorSpace::Srgb.encode_oklch(vivid);
+        let kept = ColorSpace::DisplayP3.encode_oklch(vivid);
+        assert_eq!(clamped[0], 1.0);
+        assert!(kept.iter().all(|&v| v > 0.0 && v < 1.0), "{kept:?}");
+        let srgb = oklch_to_srgb(vivid);
+        assert_eq!(clamped, [srgb.r, srgb.g, srgb.b]);
+    }
+
+    // -- CSS color string tests --
+
+    #[test]
+    fn parse_color_accepts_every_hex_form() {
+        let rgba = |color: &str| parse_color(color).unwrap().to_rgba8();
+        assert_eq!(rgba("#f80"), [0xff, 0x88, 0x00, 0xff]);
+        assert_eq!(rgba("#f808"), [0xff, 0x88, 0x00, 0x88]);
+        assert_eq!(rgba("#FF8800"), [0xff, 0x88, 0x00, 0xff]);
+        assert_eq!(rgba("ff880080"), [0xff, 0x88, 0x00, 0x80]);
+        assert_eq!(rgba("  #ff8800 "), [0xff, 0x88, 0x00, 0xff]);
+        for bad in ["f80", "#ff88000", "#ff880", "#gg8800", "#ééé", "", "#"] {
+            assert!(
+                matches!(parse_color(bad), Err(EngineError::InvalidColor(_))),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_color_accepts_rgb_functions() {
+        let rgba = |color: &str| parse_color(color).unwrap().to_rgba8();
+        assert_eq!(rgba("rgb(255, 128, 0)"), [255, 128, 0, 255]);
+        assert_eq!(rgba("RGBA(255,128,0,0.5)"), [255, 128, 0, 128]);
+        assert_eq!(rgba("rgb(100% 50% 0% / 25%)"), [255, 128, 0, 64]);
+        assert_eq!(rgba("rgb(300, -5, 0)"), [255, 0, 0, 255]);
+        for bad in [
+            "rgb(1, 2)",
+            "rgb(1, 2, x)",
+            "rgb(1 2 3 4 5)",
+            "cmyk(0, 0, 0, 0)",
+        ] {
+            assert!(parse_color(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn parse_color_accepts_hsl_functions() {
+        let rgba = |color: &str| parse_color(color).unwrap().to_rgba8();
+        assert_eq!(rgba("hsl(0, 100%, 50%)"), [255, 0, 0, 255]);
+        assert_eq!(rgba("hsl(120deg 100% 25%)"), [0, 128, 0, 255]);
+        assert_eq!(rgba("hsla(-120, 100%, 50%, 0)"), [0, 0, 255, 0]);
+        assert!(parse_color("hsl(0, 100%)").is_err());
+    }
+
+    #[test]
+    fn parse_color_accepts_named_colors() {
+        let hex = |color: &str| parse_color(color).unwrap().to_hex();
+        assert_eq!(hex("rebeccapurple"), "#663399ff");
+        assert_eq!(hex("Red"), "#ff0000ff");
+        assert_eq!(hex("aliceblue"), "#f0f8ffff");
+        assert_eq!(hex("yellowgreen"), "#9acd32ff");
+        assert_eq!(hex("transparent"), "#00000000");
+        assert!(parse_color("reddish").is_err());
+        assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn srgb_parses_only_opaque_colors() {
+        let red: Srgb = "rgb(255 0 0)".parse().unwrap();
+        assert_eq!(red, Srgb::from_hex("#ff0000").unwrap());
+        assert!("#ff000080".parse::<Srgb>().is_err());
+        assert!("transparent".parse::<Srgb>().is_err());
+        let teal: Srgb = serde_json::from_str("\"teal\"").unwrap();
+        assert_eq!(teal.to_hex(), "#008080");
+        let half: Srgba = serde_json::from_str("\"hsla(0, 0%, 100%, 0.5)\"").unwrap();
+        assert_eq!(half.to_rgba8(), [255, 255, 255, 128]);
+    }
+
+    #[test]
+    fn split_colors_keeps_functions_whole() {
+        assert_eq!(
+            split_colors("#fff, rgb(1, 2, 3)\nhsl(0 50% 50%),,red "),
+            ["#fff", "rgb(1, 2, 3)", "hsl(0 50% 50%)", "red"]
+        );
+        assert!(split_colors(" ,\n").is_empty());
+    }
+
+    // -- Color difference tests --
+
+    #[test]
+    fn delta_e_ok_spans_zero_to_one_between_black_and_white() {
+        let black = Srgb::from_hex("#000000").unwrap();
+        let white = Srgb::from_hex("#ffffff").unwrap();
+        assert_eq!(delta_e_ok(white, white), 0.0);
+        assert!(approx_eq(delta_e_ok(black, white), 1.0));
+        assert_eq!(delta_e_ok(black, white), delta_e_ok(white, black));
+    }
+
+    #[test]
+    fn delta_e_ok_ranks_perceptual_differences() {
+        let gray = Srgb::from_hex("#808080").unwrap();
+        let near = Srgb::from_hex("#818181").unwrap();
+        let red = Srgb::from_hex("#ff0000").unwrap();
+        assert!(delta_e_ok(gray, near) < 0.01);
+        assert!(delta_e_ok(gray, red) > 0.2);
+    }
+
+    #[test]
+    fn nearest_color_picks_the_closest_candidate() {
+        let hex = |h: &str| Srgb::from_hex(h).unwrap();
+        let candidates = [hex("#000000"), hex("#ff0000"), hex("#0000ff")];
+        let (i, d) = nearest_color(hex("#e01010"), &candidates).unwrap();
+        assert_eq!(i, 1);
+        assert!(approx_eq(d, delta_e_ok(hex("#e01010"), candidates[1])));
+        assert_eq!(nearest_color(hex("#0000ff"), &candidates), Some((2, 0.0)));
+        assert_eq!(nearest_color(hex("#ffffff"), &[]), None);
+    }
+
+    #[test]
+    fn nearest_color_ties_go_to_the_first_candidate() {
+        let gray = Srgb::from_hex("#808080").unwrap();
+        assert_eq!(nearest_color(gray, &[gray, gray]), Some((0, 0.0)));
+    }
+
     // -- Property-based tests --
 
     mod proptests {
@@ -804,6 +1945,42 @@ mod tests {
                 );
             }
 
+            #[test]
+            fn hsl_hsv_and_lab_round_trip_within_epsilon(
+                r in srgb_component(),
+                g in srgb_component(),
+                b in srgb_component(),
+            ) {
+                let original = Srgb { r, g, b };
+                for (name, round_tripped, tolerance) in [
+                    ("hsl", hsl_to_srgb(srgb_to_hsl(original)), 1e-9),
+                    ("hsv", hsv_to_srgb(srgb_to_hsv(original)), 1e-9),
+                    ("lab", lab_to_srgb(srgb_to_lab(original)), 1e-5),
+                ] {
+                    prop_assert!(
+                        (round_tripped.r - original.r).abs() < tolerance
+                            && (round_tripped.g - original.g).abs() < tolerance
+                            && (round_tripped.b - original.b).abs() < tolerance,
+                        "{name}: {round_tripped:?} vs {original:?}"
+                    );
+                }
+                let hsl = srgb_to_hsl(original);
+                prop_assert!(hsl.h >= 0.0 && hsl.h < 360.0, "hue {}", hsl.h);
+                prop_assert!((0.0..=1.0 + 1e-12).contains(&hsl.s), "saturation {}", hsl.s);
+            }
+
+            #[test]
+            fn hex_round_trip_is_perceptually_invisible(
+                r in srgb_component(),
+                g in srgb_component(),
+                b in srgb_component(),
+            ) {
+                let original = Srgb { r, g, b };
+                let round_tripped = Srgb::from_hex(&original.to_hex()).unwrap();
+                let d = delta_e_ok(original, round_tripped);
+                prop_assert!(d < 0.01, "ΔE-OK {d} for {original:?}");
+            }
+
             #[test]
             fn oklch_hue_is_never_nan(
                 l in 0.0_f64..=1.0,
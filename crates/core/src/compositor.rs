@@ -0,0 +1,668 @@
+//! Software compositor that renders a [`Canvas`] layer stack to an [`Image`].
+//!
+//! Layers are composited bottom-to-top over an opaque background using the
+//! W3C Compositing and Blending model: the blend function mixes source and
+//! backdrop colors, weighted by backdrop alpha, and the result is combined
+//! with source-over alpha compositing. Blending happens on sRGB-encoded
+//! values, matching CSS `mix-blend-mode` behavior.
+//!
+//! The canvas only describes layers; pixel content is supplied by the caller
+//! per layer name, so any renderer (engine fields, particles, shapes) can feed
+//! the compositor. A layer's [`Transform`](crate::transform::Transform) is
+//! applied to its image first, followed by its [`Effect`](crate::effect::Effect)
+//! stack; its [`Mask`](crate::mask::Mask), if any, is then
+//! rasterized at canvas resolution (untransformed) and multiplied into the
+//! source alpha. Layer groups are composited recursively as isolated units.
+
+use crate::canvas::{BlendMode, Canvas, Layer, LayerNode};
+use crate::effect::apply_effects;
+use crate::error::EngineError;
+use crate::field::Field;
+use crate::image::Image;
+
+/// Applies a separable blend function to one channel.
+///
+/// `backdrop` is the color already on the canvas (Cb) and `source` is the
+/// layer color (Cs), both in [0, 1].
+pub fn blend_channel(mode: BlendMode, backdrop: f64, source: f64) -> f64 {
+    match mode {
+        BlendMode::Normal => source,
+        BlendMode::Additive => (backdrop + source).min(1.0),
+        BlendMode::Multiply => backdrop * source,
+        BlendMode::Screen => screen(backdrop, source),
+        BlendMode::Overlay => hard_light(backdrop, source),
+        BlendMode::Darken => backdrop.min(source),
+        BlendMode::Lighten => backdrop.max(source),
+        BlendMode::ColorDodge => color_dodge(backdrop, source),
+        BlendMode::ColorBurn => color_burn(backdrop, source),
+        BlendMode::SoftLight => soft_light(backdrop, source),
+        BlendMode::Difference => (backdrop - source).abs(),
+        BlendMode::Exclusion => backdrop + source - 2.0 * backdrop * source,
+    }
+}
+
+/// Composites one straight-alpha source pixel over a backdrop pixel.
+///
+/// `opacity` scales the source alpha. Returns the straight-alpha result.
+pub fn composite_pixel(
+    mode: BlendMode,
+    backdrop: [f64; 4],
+    source: [f64; 4],
+    opacity: f64,
+) -> [f64; 4] {
+    let ab = backdrop[3];
+    let as_ = (source[3] * opacity).clamp(0.0, 1.0);
+    let ao = as_ + ab * (1.0 - as_);
+    if ao <= 0.0 {
+        return [0.0; 4];
+    }
+    let channel = |i: usize| {
+        let (cb, cs) = (backdrop[i], source[i]);
+        // Blend result, faded toward the raw source where the backdrop is transparent
+        let mixed = (1.0 - ab) * cs + ab * blend_channel(mode, cb, cs);
+        ((as_ * mixed + ab * cb * (1.0 - as_)) / ao).clamp(0.0, 1.0)
+    };
+    [channel(0), channel(1), channel(2), ao]
+}
+
+/// Composites `source` onto `target` in place with the given mode and opacity.
+///
+/// Returns `EngineError::DimensionMismatch` if the images differ in size.
+pub fn composite_onto(
+    target: &mut Image,
+    source: &Image,
+    mode: BlendMode,
+    opacity: f64,
+) -> Result<(), EngineError> {
+    composite_onto_masked(target, source, mode, opacity, None)
+}
+
+/// Like [`composite_onto`], but additionally scales source alpha per pixel by
+/// `mask` (values clamped to [0, 1]).
+///
+/// Returns `EngineError::DimensionMismatch` if the source or mask differs in
+/// size from the target.
+pub fn composite_onto_masked(
+    target: &mut Image,
+    source: &Image,
+    mode: BlendMode,
+    opacity: f64,
+    mask: Option<&Field>,
+) -> Result<(), EngineError> {
+    let (w, h) = (target.width(), target.height());
+    let sizes = std::iter::once((source.width(), source.height()))
+        .chain(mask.map(|m| (m.width(), m.height())));
+    for (rhs_w, rhs_h) in sizes {
+        if (rhs_w, rhs_h) != (w, h) {
+            return Err(EngineError::DimensionMismatch {
+                lhs_w: w,
+                lhs_h: h,
+                rhs_w,
+                rhs_h,
+            });
+        }
+    }
+    let coverage = |i: usize| mask.map_or(1.0, |m| m.data()[i].clamp(0.0, 1.0));
+    target
+        .data_mut()
+        .chunks_exact_mut(4)
+        .zip(source.data().chunks_exact(4))
+        .enumerate()
+        .for_each(|(i, (dst, src))| {
+            let out = composite_pixel(
+                mode,
+                [dst[0], dst[1], dst[2], dst[3]],
+                [src[0], src[1], src[2], src[3]],
+                opacity * coverage(i),
+            );
+            dst.copy_from_slice(&out);
+        });
+    Ok(())
+}
+
+/// Renders the canvas: fills the background, then composites each visible
+/// layer bottom-to-top using its transform, effects, blend mode, opacity,
+/// and mask.
+///
+/// `layer_image` supplies the pixels for a layer by name; layers for which it
+/// returns `None` are skipped. Returns `EngineError::DimensionMismatch` if a
+/// layer image differs in size from the canvas.
+pub fn composite<'a>(
+    canvas: &Canvas,
+    layer_image: impl Fn(&str) -> Option<&'a Image>,
+) -> Result<Image, EngineError> {
+    let mut out = Image::filled(canvas.width(), canvas.height(), canvas.background(), 1.0)?;
+    composite_nodes(&mut out, canvas.nodes(), &layer_image)?;
+    Ok(out)
+}
+
+/// Composites a sibling list of nodes onto `target`, recursing into groups.
+///
+/// Each visible group renders its children into a transparent buffer that is
+/// then blended onto `target` with the group's mode and opacity.
+fn composite_nodes<'a>(
+    target: &mut Image,
+    nodes: &[LayerNode],
+    layer_image: &impl Fn(&str) -> Option<&'a Image>,
+) -> Result<(), EngineError> {
+    nodes.iter().try_for_each(|node| match node {
+        LayerNode::Layer(layer) => composite_layer(target, layer, layer_image),
+        LayerNode::Group(group) if group.visible() && group.opacity() > 0.0 => {
+            let mut buffer = Image::new(target.width(), target.height())?;
+            composite_nodes(&mut buffer, group.children(), layer_image)?;
+            composite_onto(target, &buffer, group.blend_mode(), group.opacity())
+        }
+        LayerNode::Group(_) => Ok(()),
+    })
+}
+
+/// Composites one layer onto `target`, applying its transform, effects and mask.
+fn composite_layer<'a>(
+    target: &mut Image,
+    layer: &Layer,
+    layer_image: &impl Fn(&str) -> Option<&'a Image>,
+) -> Result<(), EngineError> {
+    if !layer.visible() || layer.opacity() <= 0.0 {
+        return Ok(());
+    }
+    let Some(image) = layer_image(layer.name()) else {
+        return Ok(());
+    };
+    let prepared;
+    let image = if layer.transform().is_identity() && layer.effects().is_empty() {
+        image
+    } else {
+        prepared = apply_effects(&layer.transform().apply(image), layer.effects());
+        &prepared
+    };
+    let mask = layer
+        .mask()
+        .map(|m| m.rasterize(target.width(), target.height()))
+        .transpose()?;
+    composite_onto_masked(
+        target,
+        image,
+        layer.blend_mode(),
+        layer.opacity(),
+        mask.as_ref(),
+    )
+}
+
+/// Screen: `Cb + Cs - Cb * Cs`.
+fn screen(cb: f64, cs: f64) -> f64 {
+    cb + cs - cb * cs
+}
+
+/// Hard light of `cs` over `cb`; overlay is hard light with the arguments swapped.
+fn hard_light(cs: f64, cb: f64) -> f64 {
+    if cs <= 0.5 {
+        cb * 2.0 * cs
+    } else {
+        screen(cb, 2.0 * cs - 1.0)
+    }
+}
+
+/// Color dodge: brightens the backdrop to reflect the source.
+fn color_dodge(cb: f64, cs: f64) -> f64 {
+    if cb == 0.0 {
+        0.0
+    } else if cs >= 1.0 {
+        1.0
+    } else {
+        (cb / (1.0 - cs)).min(1.0)
+    }
+}
+
+/// Color burn: darkens the backdrop to reflect the source.
+fn color_burn(cb: f64, cs: f64) -> f64 {
+    if cb >= 1.0 {
+        1.0
+    } else if cs <= 0.0 {
+        0.0
+    } else {
+        1.0 - ((1.0 - cb) / cs).min(1.0)
+    }
+}
+
+/// Soft light, using the W3C variant of the Photoshop curve.
+fn soft_light(cb: f64, cs: f64) -> f64 {
+    if cs <= 0.5 {
+        cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+    } else {
+        let d = if cb <= 0.25 {
+            ((16.0 * cb - 12.0) * cb + 4.0) * cb
+        } else {
+            cb.sqrt()
+        };
+        cb + (2.0 * cs - 1.0) * (d - cb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::{ContentType, Layer};
+    use crate::color::Srgb;
+    use crate::mask::Mask;
+    use std::collections::HashMap;
+
+    const EPS: f64 = 1e-9;
+
+    fn gray(v: f64) -> Srgb {
+        Srgb { r: v, g: v, b: v }
+    }
+
+    fn approx(a: [f64; 4], b: [f64; 4]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-6)
+    }
+
+    // -- Blend functions --
+
+    #[test]
+    fn blend_channel_matches_w3c_formulas() {
+        let (cb, cs) = (0.25, 0.5);
+        assert!((blend_channel(BlendMode::Normal, cb, cs) - 0.5).abs() < EPS);
+        assert!((blend_channel(BlendMode::Additive, cb, cs) - 0.75).abs() < EPS);
+        assert!((blend_channel(BlendMode::Multiply, cb, cs) - 0.125).abs() < EPS);
+        assert!((blend_channel(BlendMode::Screen, cb, cs) - 0.625).abs() < EPS);
+        // Overlay with Cb <= 0.5: 2 * Cb * Cs
+        assert!((blend_channel(BlendMode::Overlay, cb, cs) - 0.25).abs() < EPS);
+        // Overlay with Cb > 0.5: screen(Cs, 2 * Cb - 1)
+        assert!((blend_channel(BlendMode::Overlay, 0.75, 0.5) - 0.75).abs() < EPS);
+        // Overlay branches on the backdrop, not the source
+        assert!((blend_channel(BlendMode::Overlay, 0.25, 0.75) - 0.375).abs() < EPS);
+        assert!((blend_channel(BlendMode::Overlay, 0.75, 0.25) - 0.625).abs() < EPS);
+    }
+
+    #[test]
+    fn blend_channel_matches_w3c_formulas_for_extended_modes() {
+        // (mode, Cb, Cs, expected)
+        let cases = [
+            (BlendMode::Darken, 0.25, 0.5, 0.25),
+            (BlendMode::Lighten, 0.25, 0.5, 0.5),
+            (BlendMode::Difference, 0.25, 0.5, 0.25),
+            (BlendMode::Difference, 0.75, 0.25, 0.5),
+            (BlendMode::Exclusion, 0.25, 0.5, 0.5),
+            (BlendMode::Exclusion, 1.0, 0.25, 0.75),
+            // Color dodge: Cb / (1 - Cs), with Cb = 0 and Cs = 1 special cases
+            (BlendMode::ColorDodge, 0.25, 0.5, 0.5),
+            (BlendMode::ColorDodge, 0.75, 0.5, 1.0),
+            (BlendMode::ColorDodge, 0.0, 1.0, 0.0),
+            (BlendMode::ColorDodge, 0.1, 1.0, 1.0),
+            // Color burn: 1 - (1 - Cb) / Cs, with Cb = 1 and Cs = 0 special cases
+            (BlendMode::ColorBurn, 0.75, 0.5, 0.5),
+            (BlendMode::ColorBurn, 0.25, 0.5, 0.0),
+            (BlendMode::ColorBurn, 1.0, 0.0, 1.0),
+            (BlendMode::ColorBurn, 0.9, 0.0, 0.0),
+            // Soft light: Cs <= 0.5 branch
+            (BlendMode::SoftLight, 0.5, 0.25, 0.375),
+            // Cs > 0.5 with Cb <= 0.25 uses the polynomial D(Cb)
+            (BlendMode::SoftLight, 0.25, 0.75, 0.375),
+            // Cs > 0.5 with Cb > 0.25 uses sqrt(Cb)
+            (BlendMode::SoftLight, 0.64, 1.0, 0.8),
+            (BlendMode::SoftLight, 0.3, 0.5, 0.3),
+        ];
+        for (mode, cb, cs, expected) in cases {
+            let got = blend_channel(mode, cb, cs);
+            assert!(
+                (got - expected).abs() < EPS,
+                "{mode:?}({cb}, {cs}) = {got}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn neutral_sources_leave_backdrop_unchanged() {
+        // Each mode's identity source value, per the W3C definitions
+        let neutral = [
+            (BlendMode::Multiply, 1.0),
+            (BlendMode::Screen, 0.0),
+            (BlendMode::Darken, 1.0),
+            (BlendMode::Lighten, 0.0),
+            (BlendMode::ColorDodge, 0.0),
+            (BlendMode::ColorBurn, 1.0),
+            (BlendMode::SoftLight, 0.5),
+            (BlendMode::Difference, 0.0),
+            (BlendMode::Exclusion, 0.0),
+        ];
+        for (mode, cs) in neutral {
+            for cb in [0.0, 0.2, 0.5, 0.9, 1.0] {
+                let got = blend_channel(mode, cb, cs);
+                assert!((got - cb).abs() < EPS, "{mode:?}({cb}, {cs}) = {got}");
+            }
+        }
+    }
+
+    #[test]
+    fn additive_saturates_at_one() {
+        assert_eq!(blend_channel(BlendMode::Additive, 0.8, 0.7), 1.0);
+    }
+
+    // -- Pixel compositing --
+
+    #[test]
+    fn opaque_normal_source_replaces_backdrop() {
+        let out = composite_pixel(
+            BlendMode::Normal,
+            [0.1, 0.2, 0.3, 1.0],
+            [0.9, 0.8, 0.7, 1.0],
+            1.0,
+        );
+        assert!(approx(out, [0.9, 0.8, 0.7, 1.0]));
+    }
+
+    #[test]
+    fn half_opacity_normal_is_linear_mix() {
+        let out = composite_pixel(
+            BlendMode::Normal,
+            [0.0, 0.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0, 1.0],
+            0.5,
+        );
+        assert!(approx(out, [0.5, 0.5, 0.5, 1.0]));
+    }
+
+    #[test]
+    fn transparent_source_leaves_backdrop_unchanged() {
+        let backdrop = [0.3, 0.6, 0.9, 1.0];
+        for mode in BlendMode::ALL {
+            let out = composite_pixel(mode, backdrop, [1.0, 0.0, 0.5, 0.0], 1.0);
+            assert!(approx(out, backdrop), "{mode:?} changed backdrop: {out:?}");
+        }
+    }
+
+    #[test]
+    fn blend_over_transparent_backdrop_shows_raw_source() {
+        let out = composite_pixel(BlendMode::Multiply, [0.0; 4], [0.4, 0.5, 0.6, 1.0], 1.0);
+        assert!(approx(out, [0.4, 0.5, 0.6, 1.0]));
+    }
+
+    #[test]
+    fn fully_transparent_result_is_zero() {
+        assert_eq!(
+            composite_pixel(BlendMode::Screen, [0.0; 4], [0.2, 0.2, 0.2, 0.0], 1.0),
+            [0.0; 4]
+        );
+    }
+
+    // -- Canvas compositing --
+
+    #[test]
+    fn empty_canvas_renders_background() {
+        let canvas = Canvas::new(2, 2, gray(0.25)).unwrap();
+        let img = composite(&canvas, |_| None).unwrap();
+        assert!(approx(img.pixel(1, 1).unwrap(), [0.25, 0.25, 0.25, 1.0]));
+    }
+
+    #[test]
+    fn layers_composite_bottom_to_top_with_modes() {
+        let mut canvas = Canvas::new(1, 1, gray(0.5)).unwrap();
+        canvas
+            .add_layer(Layer::new("base", ContentType::Field))
+            .unwrap();
+        canvas
+            .add_layer(Layer::new("mul", ContentType::Field).with_blend_mode(BlendMode::Multiply))
+            .unwrap();
+        let images: HashMap<&str, Image> = HashMap::from([
+            ("base", Image::filled(1, 1, gray(0.8), 1.0).unwrap()),
+            ("mul", Image::filled(1, 1, gray(0.5), 1.0).unwrap()),
+        ]);
+        let img = composite(&canvas, |name| images.get(name)).unwrap();
+        // base replaces 0.5 with 0.8, then multiply by 0.5
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.4, 0.4, 0.4, 1.0]));
+    }
+
+    #[test]
+    fn hidden_and_zero_opacity_layers_are_skipped() {
+        let mut canvas = Canvas::new(1, 1, gray(0.2)).unwrap();
+        canvas
+            .add_layer(Layer::new("hidden", ContentType::Field).with_visible(false))
+            .unwrap();
+        canvas
+            .add_layer(Layer::new("clear", ContentType::Field).with_opacity(0.0))
+            .unwrap();
+        let white = Image::filled(1, 1, gray(1.0), 1.0).unwrap();
+        let img = composite(&canvas, |_| Some(&white)).unwrap();
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.2, 0.2, 0.2, 1.0]));
+    }
+
+    #[test]
+    fn layer_opacity_is_applied() {
+        let mut canvas = Canvas::new(1, 1, gray(0.0)).unwrap();
+        canvas
+            .add_layer(Layer::new("half", ContentType::Field).with_opacity(0.25))
+            .unwrap();
+        let white = Image::filled(1, 1, gray(1.0), 1.0).unwrap();
+        let img = composite(&canvas, |_| Some(&white)).unwrap();
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.25, 0.25, 0.25, 1.0]));
+    }
+
+    #[test]
+    fn mismatched_layer_image_is_an_error() {
+        let mut canvas = Canvas::new(2, 2, gray(0.0)).unwrap();
+        canvas
+            .add_layer(Layer::new("a", ContentType::Field))
+            .unwrap();
+        let small = Image::new(1, 1).unwrap();
+        assert!(matches!(
+            composite(&canvas, |_| Some(&small)),
+            Err(EngineError::DimensionMismatch { .. })
+        ));
+    }
+
+    // -- Groups --
+
+    #[test]
+    fn group_opacity_applies_to_flattened_children() {
+        use crate::canvas::LayerGroup;
+        let mut canvas = Canvas::new(1, 1, gray(0.0)).unwrap();
+        canvas
+            .add_group(
+                LayerGroup::new("g")
+                    .with_opacity(0.5)
+                    .with_child(Layer::new("a", ContentType::Field))
+                    .with_child(
+                        Layer::new("b", ContentType::Field).with_blend_mode(BlendMode::Multiply),
+                    ),
+            )
+            .unwrap();
+        let images: HashMap<&str, Image> = HashMap::from([
+            ("a", Image::filled(1, 1, gray(0.8), 1.0).unwrap()),
+            ("b", Image::filled(1, 1, gray(0.5), 1.0).unwrap()),
+        ]);
+        let img = composite(&canvas, |name| images.get(name)).unwrap();
+        // Children flatten to 0.4 inside the group, then land at half opacity
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.2, 0.2, 0.2, 1.0]));
+    }
+
+    #[test]
+    fn group_is_isolated_from_the_backdrop() {
+        use crate::canvas::LayerGroup;
+        let mut canvas = Canvas::new(1, 1, gray(0.5)).unwrap();
+        canvas
+            .add_group(LayerGroup::new("g").with_child(
+                Layer::new("mul", ContentType::Field).with_blend_mode(BlendMode::Multiply),
+            ))
+            .unwrap();
+        let white = Image::filled(1, 1, gray(1.0), 1.0).unwrap();
+        let img = composite(&canvas, |_| Some(&white)).unwrap();
+        // Multiply has no backdrop inside the group, so the white passes through
+        assert!(approx(img.pixel(0, 0).unwrap(), [1.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn hidden_group_hides_its_children_and_nested_groups_recurse() {
+        use crate::canvas::LayerGroup;
+        let mut canvas = Canvas::new(1, 1, gray(0.0)).unwrap();
+        canvas
+            .add_group(
+                LayerGroup::new("off")
+                    .with_visible(false)
+                    .with_child(Layer::new("a", ContentType::Field)),
+            )
+            .unwrap();
+        canvas
+            .add_group(
+                LayerGroup::new("outer").with_opacity(0.5).with_child(
+                    LayerGroup::new("inner")
+                        .with_opacity(0.5)
+                        .with_child(Layer::new("b", ContentType::Field)),
+                ),
+            )
+            .unwrap();
+        let white = Image::filled(1, 1, gray(1.0), 1.0).unwrap();
+        let img = composite(&canvas, |_| Some(&white)).unwrap();
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.25, 0.25, 0.25, 1.0]));
+    }
+
+    // -- Masks --
+
+    #[test]
+    fn field_mask_scales_source_alpha_per_pixel() {
+        let mut target = Image::filled(3, 1, gray(0.0), 1.0).unwrap();
+        let white = Image::filled(3, 1, gray(1.0), 1.0).unwrap();
+        let mask = Field::from_data(3, 1, vec![0.0, 0.5, 1.0]).unwrap();
+        composite_onto_masked(&mut target, &white, BlendMode::Normal, 1.0, Some(&mask)).unwrap();
+        assert!(approx(target.pixel(0, 0).unwrap(), [0.0, 0.0, 0.0, 1.0]));
+        assert!(approx(target.pixel(1, 0).unwrap(), [0.5, 0.5, 0.5, 1.0]));
+        assert!(approx(target.pixel(2, 0).unwrap(), [1.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn mismatched_mask_is_an_error() {
+        let mut target = Image::new(2, 2).unwrap();
+        let source = Image::new(2, 2).unwrap();
+        let mask = Field::new(1, 1).unwrap();
+        assert!(matches!(
+            composite_onto_masked(&mut target, &source, BlendMode::Normal, 1.0, Some(&mask)),
+            Err(EngineError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn layer_mask_multiplies_with_opacity() {
+        let mut canvas = Canvas::new(2, 1, gray(0.0)).unwrap();
+        // Left half masked out, right half kept
+        let mask = Mask::Rect {
+            x0: 0.5,
+            y0: 0.0,
+            x1: 1.0,
+            y1: 1.0,
+            feather: 0.0,
+        };
+        canvas
+            .add_layer(
+                Layer::new("half", ContentType::Field)
+                    .with_opacity(0.5)
+                    .with_mask(mask),
+            )
+            .unwrap();
+        let white = Image::filled(2, 1, gray(1.0), 1.0).unwrap();
+        let img = composite(&canvas, |_| Some(&white)).unwrap();
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.0, 0.0, 0.0, 1.0]));
+        assert!(approx(img.pixel(1, 0).unwrap(), [0.5, 0.5, 0.5, 1.0]));
+    }
+
+    #[test]
+    fn layer_transform_is_applied_before_compositing() {
+        use crate::transform::{Transform, WrapMode};
+        let mut canvas = Canvas::new(2, 1, gray(0.0)).unwrap();
+        canvas
+            .add_layer(
+                Layer::new("flip", ContentType::Field).with_transform(
+                    Transform::IDENTITY
+                        .with_scale(-1.0, 1.0)
+                        .with_wrap(WrapMode::Clamp),
+                ),
+            )
+            .unwrap();
+        let img_in = Image::from_rgba8(2, 1, &[255, 255, 255, 255, 0, 0, 0, 255]).unwrap();
+        let img = composite(&canvas, |_| Some(&img_in)).unwrap();
+        assert!(approx(img.pixel(0, 0).unwrap(), [0.0, 0.0, 0.0, 1.0]));
+        assert!(approx(img.pixel(1, 0).unwrap(), [1.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn layer_effects_are_applied_before_blending() {
+        use crate::effect::Effect;
+        let mut canvas = Canvas::new(3, 1, gray(0.0)).unwrap();
+        canvas
+            .add_layer(
+                Layer::new("shadowed", ContentType::Field).with_effect(Effect::DropShadow {
+                    offset: [1.0, 0.0],
+                    radius: 0.0,
+                    opacity: 1.0,
+                    color: gray(0.5),
+                }),
+            )
+            .unwrap();
+        let mut dot = Image::new(3, 1).unwrap();
+        dot.set_pixel(0, 0, [1.0, 1.0, 1.0, 1.0]).unwrap();
+        let img = composite(&canvas, |_| Some(&dot)).unwrap();
+        assert!(approx(img.pixel(0, 0).unwrap(), [1.0, 1.0, 1.0, 1.0]));
+        assert!(approx(img.pixel(1, 0).unwrap(), [0.5, 0.5, 0.5, 1.0]));
+        assert!(approx(img.pixel(2, 0).unwrap(), [0.0, 0.0, 0.0, 1.0]));
+    }
+
+    // -- Property-based tests --
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn mode() -> impl Strategy<Value = BlendMode> {
+            prop::sample::select(BlendMode::ALL.to_vec())
+        }
+
+        proptest! {
+            #[test]
+            fn composite_pixel_stays_in_unit_range(
+                mode in mode(),
+                b in prop::array::uniform4(0.0_f64..=1.0),
+                s in prop::array::uniform4(0.0_f64..=1.0),
+                opacity in 0.0_f64..=1.0,
+            ) {
+                let out = composite_pixel(mode, b, s, opacity);
+                for v in out {
+                    prop_assert!((0.0..=1.0).contains(&v), "{mode:?} produced {v}");
+                }
+            }
+
+            #[test]
+            fn blend_channel_stays_in_unit_range(
+                mode in mode(),
+                cb in 0.0_f64..=1.0,
+                cs in 0.0_f64..=1.0,
+            ) {
+                let v = blend_channel(mode, cb, cs);
+                prop_assert!((0.0..=1.0).contains(&v), "{mode:?}({cb}, {cs}) = {v}");
+            }
+
+            #[test]
+            fn commutative_modes_are_symmetric(
+                mode in prop::sample::select(vec![
+                    BlendMode::Multiply,
+                    BlendMode::Screen,
+                    BlendMode::Darken,
+                    BlendMode::Lighten,
+                    BlendMode::Difference,
+                    BlendMode::Exclusion,
+                ]),
+                a in 0.0_f64..=1.0,
+                b in 0.0_f64..=1.0,
+            ) {
+                prop_assert!((blend_channel(mode, a, b) - blend_channel(mode, b, a)).abs() < 1e-12);
+            }
+
+            #[test]
+            fn opaque_backdrop_stays_opaque(
+                mode in mode(),
+                rgb in prop::array::uniform3(0.0_f64..=1.0),
+                s in prop::array::uniform4(0.0_f64..=1.0),
+            ) {
+                let out = composite_pixel(mode, [rgb[0], rgb[1], rgb[2], 1.0], s, 1.0);
+                prop_assert!((out[3] - 1.0).abs() < 1e-12);
+            }
+        }
+    }
+}
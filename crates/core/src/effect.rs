@@ -0,0 +1,439 @@
+//! CPU layer effects: gaussian blur, outer glow and drop shadow.
+//!
+//! Effects are applied to a layer's [`Image`] by the compositor before the
+//! layer is blended onto the canvas, in the order they appear in the layer's
+//! effect stack. Distances (blur radius, shadow offset) are fractions of the
+//! image's shorter side so a composition keeps its look at any resolution.
+//! Pixels outside the image are treated as transparent, so blurred layers
+//! fade out at their edges.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::{BlendMode, Reframe};
+use crate::color::Srgb;
+use crate::compositor::composite_pixel;
+use crate::image::Image;
+use crate::math;
+
+/// A single entry in a layer's effect stack.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "blur", "radius": 0.01}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Effect {
+    /// Gaussian blur with standard deviation `radius`.
+    Blur { radius: f64 },
+    /// Blurred copy of the pixels brighter than `threshold` (luma in [0, 1]),
+    /// scaled by `intensity` and placed behind the layer.
+    Glow {
+        threshold: f64,
+        radius: f64,
+        #[serde(default = "default_intensity")]
+        intensity: f64,
+    },
+    /// Blurred, offset silhouette of the layer in `color`, placed behind it.
+    DropShadow {
+        offset: [f64; 2],
+        radius: f64,
+        #[serde(default = "default_shadow_opacity")]
+        opacity: f64,
+        #[serde(default = "default_shadow_color")]
+        color: Srgb,
+    },
+}
+
+fn default_intensity() -> f64 {
+    1.0
+}
+
+fn default_shadow_opacity() -> f64 {
+    0.6
+}
+
+fn default_shadow_color() -> Srgb {
+    Srgb {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+    }
+}
+
+impl Effect {
+    /// Returns a new image with the effect applied to `image`.
+    pub fn apply(&self, image: &Image) -> Image {
+        let unit = image.width().min(image.height()) as f64;
+        match self {
+            Effect::Blur { radius } => gaussian_blur(image, radius * unit),
+            Effect::Glow {
+                threshold,
+                radius,
+                intensity,
+            } => {
+                let mut bright = image.clone();
+                bright.data_mut().chunks_exact_mut(4).for_each(|px| {
+                    let luma = 0.2126 * px[0] + 0.7152 * px[1] + 0.0722 * px[2];
+                    if luma < *threshold {
+                        px[3] = 0.0;
+                    }
+                });
+                let mut glow = gaussian_blur(&bright, radius * unit);
+                glow.data_mut()
+                    .chunks_exact_mut(4)
+                    .for_each(|px| px[3] = (px[3] * intensity).clamp(0.0, 1.0));
+                over(image, &glow)
+            }
+            Effect::DropShadow {
+                offset,
+                radius,
+                opacity,
+                color,
+            } => {
+                let (w, h) = (image.width(), image.height());
+                let dx = (offset[0] * unit).round() as isize;
+                let dy = (offset[1] * unit).round() as isize;
+                let alpha = opacity.clamp(0.0, 1.0);
+                let mut silhouette = Image::blank_like(image);
+                silhouette
+                    .data_mut()
+                    .chunks_exact_mut(4)
+                    .enumerate()
+                    .for_each(|(i, px)| {
+                        let sx = (i % w) as isize - dx;
+                        let sy = (i / w) as isize - dy;
+                        let a = if (0..w as isize).contains(&sx) && (0..h as isize).contains(&sy) {
+                            image.data()[(sy as usize * w + sx as usize) * 4 + 3]
+                        } else {
+                            0.0
+                        };
+                        px.copy_from_slice(&[color.r, color.g, color.b, a * alpha]);
+                    });
+                over(image, &gaussian_blur(&silhouette, radius * unit))
+            }
+        }
+    }
+
+    /// Re-expresses the effect's distances in a resized canvas frame.
+    pub(crate) fn reframe(&self, frame: &Reframe) -> Effect {
+        match self.clone() {
+            Effect::Blur { radius } => Effect::Blur {
+                radius: frame.length(radius),
+            },
+            Effect::Glow {
+                threshold,
+                radius,
+                intensity,
+            } => Effect::Glow {
+                threshold,
+                radius: frame.length(radius),
+                intensity,
+            },
+            Effect::DropShadow {
+                offset,
+                radius,
+                opacity,
+                color,
+            } => Effect::DropShadow {
+                offset: offset.map(|o| frame.length(o)),
+                radius: frame.length(radius),
+                opacity,
+                color,
+            },
+        }
+    }
+}
+
+/// Applies an effect stack in order.
+pub fn apply_effects(image: &Image, effects: &[Effect]) -> Image {
+    effects
+        .iter()
+        .fold(image.clone(), |acc, effect| effect.apply(&acc))
+}
+
+/// Separable gaussian blur with standard deviation `sigma` in pixels.
+///
+/// Blurring is done on premultiplied color so transparent pixels do not
+/// bleed dark fringes. A non-positive or non-finite `sigma` returns a copy.
+/// The kernel reaches no further than the image's longer side, past which
+/// every tap would fall outside the image.
+pub fn gaussian_blur(image: &Image, sigma: f64) -> Image {
+    if !(sigma.is_finite() && sigma > 0.0) {
+        return image.clone();
+    }
+    let (w, h) = (image.width(), image.height());
+    let kernel = gaussian_kernel(sigma, w.max(h));
+    let mut buf: Vec<f64> = image
+        .data()
+        .chunks_exact(4)
+        .flat_map(|px| [px[0] * px[3], px[1] * px[3], px[2] * px[3], px[3]])
+        .collect();
+    buf = convolve(&buf, w, h, &kernel, 1, w);
+    buf = convolve(&buf, h, w, &kernel, w, 1);
+    let mut out = Image::blank_like(image);
+    out.data_mut()
+        .chunks_exact_mut(4)
+        .zip(buf.chunks_exact(4))
+        .for_each(|(dst, src)| {
+            let a = src[3].clamp(0.0, 1.0);
+            if a > 0.0 {
+                dst[0] = (src[0] / src[3]).clamp(0.0, 1.0);
+                dst[1] = (src[1] / src[3]).clamp(0.0, 1.0);
+                dst[2] = (src[2] / src[3]).clamp(0.0, 1.0);
+                dst[3] = a;
+            }
+        });
+    out
+}
+
+/// Normalized 1D gaussian kernel truncated at three standard deviations,
+/// or at `max_half_width` taps either side of the center if that is nearer.
+fn gaussian_kernel(sigma: f64, max_half_width: usize) -> Vec<f64> {
+    // The float-to-int cast saturates, so huge sigmas clamp too
+    let r = ((3.0 * sigma).ceil() as usize).min(max_half_width) as isize;
+    let weights: Vec<f64> = (-r..=r)
+        .map(|i| math::exp(-(i * i) as f64 / (2.0 * sigma * sigma)))
+        .collect();
+    let sum: f64 = weights.iter().sum();
+    weights.into_iter().map(|v| v / sum).collect()
+}
+
+/// Convolves premultiplied RGBA rows along one axis.
+///
+/// `len` is the number of pixels along the blur axis, `lines` the number of
+/// independent lines, `step` the pixel stride along the axis and `line_step`
+/// the pixel stride between lines. Samples past the ends are transparent.
+fn convolve(
+    src: &[f64],
+    len: usize,
+    lines: usize,
+    kernel: &[f64],
+    step: usize,
+    line_step: usize,
+) -> Vec<f64> {
+    let r = (kernel.len() / 2) as isize;
+    let mut dst = vec![0.0; src.len()];
+    for line in 0..lines {
+        for i in 0..len {
+            let out = (line * line_step + i * step) * 4;
+            for (k, weight) in kernel.iter().enumerate() {
+                let j = i as isize + k as isize - r;
+                if (0..len as isize).contains(&j) {
+                    let inp = (line * line_step + j as usize * step) * 4;
+                    for c in 0..4 {
+                        dst[out + c] += src[inp + c] * weight;
+                    }
+                }
+            }
+        }
+    }
+    dst
+}
+
+/// Places `top` over `bottom` with normal source-over compositing.
+fn over(top: &Image, bottom: &Image) -> Image {
+    let mut out = bottom.clone();
+    out.data_mut()
+        .chunks_exact_mut(4)
+        .zip(top.data().chunks_exact(4))
+        .for_each(|(dst, src)| {
+            let px = composite_pixel(
+                BlendMode::Normal,
+                [dst[0], dst[1], dst[2], dst[3]],
+                [src[0], src[1], src[2], src[3]],
+                1.0,
+            );
+            dst.copy_from_slice(&px);
+        });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white() -> Srgb {
+        Srgb {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        }
+    }
+
+    /// `size x size` transparent image with a single opaque white pixel in the middle.
+    fn dot(size: usize) -> Image {
+        let mut img = Image::new(size, size).unwrap();
+        img.set_pixel(size / 2, size / 2, [1.0, 1.0, 1.0, 1.0])
+            .unwrap();
+        img
+    }
+
+    fn alpha_sum(img: &Image) -> f64 {
+        img.data().chunks_exact(4).map(|px| px[3]).sum()
+    }
+
+    // -- Blur --
+
+    #[test]
+    fn kernel_is_normalized_and_symmetric() {
+        let k = gaussian_kernel(1.5, 100);
+        assert_eq!(k.len(), 11);
+        assert!((k.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        let n = k.len();
+        (0..n).for_each(|i| assert!((k[i] - k[n - 1 - i]).abs() < 1e-15));
+    }
+
+    #[test]
+    fn kernel_is_bounded_by_the_image() {
+        assert_eq!(gaussian_kernel(1e300, 8).len(), 17);
+        let out = gaussian_blur(&dot(9), 1e12);
+        assert_eq!((out.width(), out.height()), (9, 9));
+        assert!(out.data().iter().all(|v| v.is_finite()));
+        assert!(alpha_sum(&out) <= 1.0 + 1e-9);
+    }
+
+    #[test]
+    fn blur_spreads_alpha_and_preserves_color() {
+        let img = dot(15);
+        let out = gaussian_blur(&img, 1.5);
+        // Energy is conserved when the kernel fits inside the image
+        assert!((alpha_sum(&out) - 1.0).abs() < 1e-9);
+        let center = out.pixel(7, 7).unwrap();
+        let near = out.pixel(8, 7).unwrap();
+        assert!(center[3] > near[3] && near[3] > 0.0);
+        // Premultiplied blur keeps the color white instead of darkening it
+        assert!((near[0] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn blur_of_uniform_opaque_image_is_unchanged_in_the_interior() {
+        let img = Image::filled(16, 16, white(), 1.0).unwrap();
+        let out = gaussian_blur(&img, 1.0);
+        assert!((out.pixel(8, 8).unwrap()[3] - 1.0).abs() < 1e-9);
+        // Edges fade because outside is transparent
+        assert!(out.pixel(0, 0).unwrap()[3] < 1.0);
+    }
+
+    #[test]
+    fn zero_radius_blur_is_a_copy() {
+        let img = dot(5);
+        assert_eq!(Effect::Blur { radius: 0.0 }.apply(&img), img);
+        assert_eq!(gaussian_blur(&img, f64::NAN), img);
+    }
+
+    // -- Glow --
+
+    #[test]
+    fn glow_adds_halo_around_bright_pixels_only() {
+        let mut img = dot(15);
+        // A dim opaque pixel that stays below the threshold
+        img.set_pixel(1, 1, [0.1, 0.1, 0.1, 1.0]).unwrap();
+        let glow = Effect::Glow {
+            threshold: 0.5,
+            radius: 0.1,
+            intensity: 1.0,
+        };
+        let out = glow.apply(&img);
+        // Original pixels remain on top
+        assert_eq!(out.pixel(7, 7).unwrap(), [1.0, 1.0, 1.0, 1.0]);
+        assert!(out.pixel(8, 7).unwrap()[3] > 0.0);
+        assert_eq!(out.pixel(2, 1).unwrap()[3], 0.0);
+    }
+
+    // -- Drop shadow --
+
+    #[test]
+    fn drop_shadow_offsets_silhouette_behind_layer() {
+        let img = dot(10);
+        let shadow = Effect::DropShadow {
+            offset: [0.2, 0.1],
+            radius: 0.0,
+            opacity: 0.5,
+            color: default_shadow_color(),
+        };
+        let out = shadow.apply(&img);
+        assert_eq!(out.pixel(5, 5).unwrap(), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(out.pixel(7, 6).unwrap(), [0.0, 0.0, 0.0, 0.5]);
+        assert!((alpha_sum(&out) - 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn effect_stack_applies_in_order() {
+        let img = dot(9);
+        let stack = [
+            Effect::Blur { radius: 0.1 },
+            Effect::DropShadow {
+                offset: [0.0, 0.0],
+                radius: 0.0,
+                opacity: 1.0,
+                color: default_shadow_color(),
+            },
+        ];
+        let manual = stack[1].apply(&stack[0].apply(&img));
+        assert_eq!(apply_effects(&img, &stack), manual);
+        assert_eq!(apply_effects(&img, &[]), img);
+    }
+
+    // -- Serde --
+
+    #[test]
+    fn serde_round_trip_and_defaults() {
+        let effects = vec![
+            Effect::Blur { radius: 0.02 },
+            Effect::Glow {
+                threshold: 0.7,
+                radius: 0.05,
+                intensity: 2.0,
+            },
+            Effect::DropShadow {
+                offset: [0.01, 0.02],
+                radius: 0.01,
+                opacity: 0.4,
+                color: Srgb::from_hex("#102030").unwrap(),
+            },
+        ];
+        let json = serde_json::to_string(&effects).unwrap();
+        let restored: Vec<Effect> = serde_json::from_str(&json).unwrap();
+        assert_eq!(effects, restored);
+
+        let shadow: Effect =
+            serde_json::from_str(r#"{"kind":"drop_shadow","offset":[0.1,0.1],"radius":0.02}"#)
+                .unwrap();
+        assert_eq!(
+            shadow,
+            Effect::DropShadow {
+                offset: [0.1, 0.1],
+                radius: 0.02,
+                opacity: 0.6,
+                color: default_shadow_color(),
+            }
+        );
+    }
+
+    // -- Property-based tests --
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn blur_output_stays_in_unit_range(
+                bytes in prop::collection::vec(any::<u8>(), 36),
+                sigma in 0.0_f64..4.0,
+            ) {
+                let img = Image::from_rgba8(3, 3, &bytes).unwrap();
+                for v in gaussian_blur(&img, sigma).data() {
+                    prop_assert!((0.0..=1.0).contains(v));
+                }
+            }
+
+            #[test]
+            fn blur_never_increases_total_alpha(
+                bytes in prop::collection::vec(any::<u8>(), 64),
+                sigma in 0.1_f64..3.0,
+            ) {
+                let img = Image::from_rgba8(4, 4, &bytes).unwrap();
+                prop_assert!(alpha_sum(&gaussian_blur(&img, sigma)) <= alpha_sum(&img) + 1e-9);
+            }
+        }
+    }
+}
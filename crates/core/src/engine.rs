@@ -31,6 +31,28 @@ pub trait Engine {
     /// Schema describing all available parameters, their types, ranges, and defaults.
     fn param_schema(&self) -> Value;
 
+    /// Applies a partial params object without resetting the simulation,
+    /// so parameters can evolve over a run. Keys missing from `params` keep
+    /// their current values; out-of-range values are clamped as in the
+    /// engine's constructor.
+    ///
+    /// Returns `EngineError::Unsupported` if a key cannot change once the
+    /// engine is built, or by default.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let _ = params;
+        Err(EngineError::Unsupported(
+            "this engine cannot change its params mid-run".into(),
+        ))
+    }
+
+    /// Named parameter sets worth starting from, as a JSON object mapping
+    /// each preset name to a (partial) params object.
+    ///
+    /// Returns an empty object by default.
+    fn presets(&self) -> Value {
+        Value::Object(Default::default())
+    }
+
     /// Optional secondary field encoding per-cell hue offset.
     ///
     /// Returns `None` by default. Engines that modulate color spatially
@@ -38,6 +60,29 @@ pub trait Engine {
     fn hue_field(&self) -> Option<&Field> {
         None
     }
+
+    /// Serializes the evolving simulation state for checkpointing.
+    ///
+    /// The state excludes parameters and dimensions, which the caller
+    /// records separately. Returns `EngineError::Unsupported` by default;
+    /// engines opt in with a [`StateWriter`](crate::StateWriter).
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        Err(EngineError::Unsupported(
+            "this engine cannot save its state".into(),
+        ))
+    }
+
+    /// Restores state written by [`Engine::save_state`] into an engine
+    /// built with the same size and parameters.
+    ///
+    /// Returns `EngineError::InvalidState` if the data does not fit, or
+    /// `EngineError::Unsupported` by default.
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let _ = state;
+        Err(EngineError::Unsupported(
+            "this engine cannot load saved state".into(),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -119,6 +164,34 @@ mod tests {
         assert_eq!(schema["step_count"]["type"], "integer");
     }
 
+    #[test]
+    fn default_state_methods_are_unsupported() {
+        let mut engine = MockEngine::new();
+        assert!(matches!(
+            engine.save_state(),
+            Err(EngineError::Unsupported(_))
+        ));
+        assert!(matches!(
+            engine.load_state(&[]),
+            Err(EngineError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn default_set_params_is_unsupported() {
+        let mut engine = MockEngine::new();
+        assert!(matches!(
+            engine.set_params(&json!({"step_count": 1})),
+            Err(EngineError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn default_presets_are_empty() {
+        let engine = MockEngine::new();
+        assert_eq!(engine.presets(), json!({}));
+    }
+
     #[test]
     fn default_hue_field_is_none() {
         let engine = MockEngine::new();
@@ -63,6 +63,45 @@ pub enum EngineError {
     #[error("unknown palette: {0}")]
     UnknownPalette(String),
 
+    /// A project file failed validation (unsupported version, bad canvas).
+    #[error("invalid project: {0}")]
+    InvalidProject(String),
+
+    /// An interaction recording failed validation (unsupported version,
+    /// events out of order).
+    #[error("invalid replay: {0}")]
+    InvalidReplay(String),
+
+    /// A PRNG seed or seed phrase was malformed, or a phrase did not match
+    /// its seed.
+    #[error("invalid seed: {0}")]
+    InvalidSeed(String),
+
+    /// A preset was malformed or could not be found.
+    #[error("invalid preset: {0}")]
+    InvalidPreset(String),
+
+    /// A declarative field source description was malformed.
+    #[error("invalid field source: {0}")]
+    InvalidFieldSource(String),
+
+    /// Saved engine state could not be restored (truncated, or from an
+    /// engine of another size).
+    #[error("invalid engine state: {0}")]
+    InvalidState(String),
+
+    /// A run would allocate more than its memory budget allows.
+    #[error("over memory budget: {0}")]
+    OverMemoryBudget(String),
+
+    /// A memory budget size could not be parsed.
+    #[error("invalid memory budget: {0}")]
+    InvalidBudget(String),
+
+    /// The engine does not support the requested operation.
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+
     /// An I/O or external library error.
     #[error("I/O error: {0}")]
     Io(String),
@@ -188,6 +227,12 @@ mod tests {
         );
     }
 
+    #[test]
+    fn invalid_project_includes_message() {
+        let err = EngineError::InvalidProject("unsupported version 9".into());
+        assert!(format!("{err}").contains("unsupported version 9"));
+    }
+
     #[test]
     fn io_error_includes_message() {
         let err = EngineError::Io("file not found".into());
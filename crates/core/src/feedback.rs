@@ -0,0 +1,182 @@
+//! Feedback trails: each animation frame drawn over a faded, warped copy of
+//! the one before.
+//!
+//! The classic video-feedback look. Every frame, the previous output is
+//!
+//! 1. **Warped** -- scaled by `zoom` and turned by `rotation` radians about
+//!    the image center (positive turns clockwise on screen, where y points
+//!    down), with bilinear sampling; pixels warped in from outside the image
+//!    are transparent black;
+//! 2. **Faded** -- multiplied by `decay`, alpha included;
+//! 3. **Merged** -- kept wherever it is brighter than the new frame, per
+//!    channel (a lighten blend), so trails never outshine their sources.
+//!
+//! A zoom above 1 makes trails stream outward, below 1 inward. Colors are
+//! clamped to [0, 1]. `render::FeedbackPass` runs the same formula on the
+//! GPU.
+
+use serde::{Deserialize, Serialize};
+
+use crate::image::Image;
+use crate::math;
+use crate::post::sample;
+
+/// Feedback settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Feedback {
+    /// Fraction of the previous frame kept each frame, in [0, 1].
+    pub decay: f64,
+    /// Scale applied to the previous frame each frame; must be positive.
+    pub zoom: f64,
+    /// Turn applied to the previous frame each frame, in radians.
+    pub rotation: f64,
+}
+
+impl Default for Feedback {
+    fn default() -> Self {
+        Self {
+            decay: 0.9,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl Feedback {
+    /// Returns feedback settings with the given decay and no warp.
+    pub fn new(decay: f64) -> Self {
+        Self {
+            decay,
+            ..Self::default()
+        }
+    }
+
+    /// Returns these settings with a different zoom.
+    pub fn with_zoom(mut self, zoom: f64) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Returns these settings with a different rotation.
+    pub fn with_rotation(mut self, rotation: f64) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Where output pixel coordinates `(x, y)` (centers at +0.5) read the
+    /// previous frame of a `w x h` image.
+    pub fn source(&self, x: f64, y: f64, w: f64, h: f64) -> (f64, f64) {
+        let (cx, cy) = (w * 0.5, h * 0.5);
+        let (dx, dy) = ((x - cx) / self.zoom, (y - cy) / self.zoom);
+        let (sin, cos) = math::sin_cos(self.rotation);
+        (cx + cos * dx + sin * dy, cy - sin * dx + cos * dy)
+    }
+
+    /// Returns `current` merged over the faded, warped `previous` frame.
+    ///
+    /// `previous` should be the last output at the same size; the first
+    /// frame of an animation is used as is.
+    pub fn apply(&self, previous: &Image, current: &Image) -> Image {
+        let w = current.width();
+        let (pw, ph) = (previous.width() as f64, previous.height() as f64);
+        let mut out = current.clone();
+        out.data_mut()
+            .chunks_exact_mut(4)
+            .enumerate()
+            .for_each(|(i, px)| {
+                let (x, y) = ((i % w) as f64 + 0.5, (i / w) as f64 + 0.5);
+                let (sx, sy) = self.source(x, y, pw, ph);
+                let inside = (0.0..=pw).contains(&sx) && (0.0..=ph).contains(&sy);
+                let trail = if inside {
+                    sample(previous, sx, sy)
+                } else {
+                    [0.0; 4]
+                };
+                for c in 0..4 {
+                    px[c] = px[c].max(self.decay * trail[c]).clamp(0.0, 1.0);
+                }
+            });
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Srgb;
+
+    fn dot(w: usize, h: usize, x: usize, y: usize) -> Image {
+        let mut image = Image::filled(
+            w,
+            h,
+            Srgb {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+            },
+            1.0,
+        )
+        .unwrap();
+        image.data_mut()[(y * w + x) * 4..(y * w + x) * 4 + 3].copy_from_slice(&[1.0, 0.5, 0.25]);
+        image
+    }
+
+    #[test]
+    fn unwarped_trails_fade_by_decay() {
+        let feedback = Feedback::new(0.5);
+        let previous = dot(5, 4, 1, 2);
+        let current = dot(5, 4, 3, 0);
+        let out = feedback.apply(&previous, &current);
+        assert_eq!(out.pixel(1, 2).unwrap(), [0.5, 0.25, 0.125, 1.0]);
+        assert_eq!(out.pixel(3, 0).unwrap(), [1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(out.pixel(0, 0).unwrap(), [0.0, 0.0, 0.0, 1.0]);
+
+        // A second pass halves the old trail again
+        let again = feedback.apply(&out, &dot(5, 4, 3, 0));
+        assert_eq!(again.pixel(1, 2).unwrap()[0], 0.25);
+    }
+
+    #[test]
+    fn zoom_pushes_trails_away_from_the_center() {
+        let feedback = Feedback::new(1.0).with_zoom(2.0);
+        // 8x8 center is (4, 4): the dot's center (5.5, 4.5) lands on (7, 5)
+        assert_eq!(feedback.source(7.0, 5.0, 8.0, 8.0), (5.5, 4.5));
+        let out = feedback.apply(&dot(8, 8, 5, 4), &Image::new(8, 8).unwrap());
+        assert!(out.pixel(6, 4).unwrap()[0] > 0.5);
+        assert!(out.pixel(5, 4).unwrap()[0] < 0.5);
+    }
+
+    #[test]
+    fn rotation_turns_clockwise_on_screen() {
+        let feedback = Feedback::new(1.0).with_rotation(std::f64::consts::FRAC_PI_2);
+        // Right of center moves below center (y down)
+        let (x, y) = feedback.source(4.0, 6.0, 8.0, 8.0);
+        assert!((x - 6.0).abs() < 1e-12 && (y - 4.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pixels_from_outside_the_image_are_transparent() {
+        let feedback = Feedback::new(1.0).with_zoom(0.5);
+        let previous = Image::filled(
+            4,
+            4,
+            Srgb {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            },
+            1.0,
+        )
+        .unwrap();
+        let out = feedback.apply(&previous, &Image::new(4, 4).unwrap());
+        assert_eq!(out.pixel(0, 0).unwrap(), [0.0; 4]);
+        assert_eq!(out.pixel(2, 2).unwrap(), [1.0; 4]);
+    }
+
+    #[test]
+    fn serializes_with_defaults() {
+        let feedback: Feedback = serde_json::from_str(r#"{"zoom": 1.02}"#).unwrap();
+        assert_eq!(feedback, Feedback::new(0.9).with_zoom(1.02));
+    }
+}
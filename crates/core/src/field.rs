@@ -5,15 +5,38 @@
 //! so negative and overflowing indices are valid.
 
 use crate::error::EngineError;
+use crate::math;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
 
 /// A 2D scalar field with values clamped to [0, 1] and toroidal coordinate wrapping.
-#[derive(Debug, Clone)]
+///
+/// Serializes as `{"width", "height", "data"}`; deserialization validates
+/// that `data.len() == width * height`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "FieldRepr")]
 pub struct Field {
     width: usize,
     height: usize,
     data: Vec<f64>,
 }
 
+/// Unvalidated serde representation of a [`Field`].
+#[derive(Deserialize)]
+struct FieldRepr {
+    width: usize,
+    height: usize,
+    data: Vec<f64>,
+}
+
+impl TryFrom<FieldRepr> for Field {
+    type Error = EngineError;
+
+    fn try_from(repr: FieldRepr) -> Result<Self, Self::Error> {
+        Field::from_data(repr.width, repr.height, repr.data)
+    }
+}
+
 impl Field {
     /// Creates a zero-filled field of the given dimensions.
     ///
@@ -227,6 +250,70 @@ impl Field {
         }
     }
 
+    /// Area-averages the field down by an integer `factor` in both axes.
+    ///
+    /// Each output cell is the mean of the corresponding `factor x factor`
+    /// block. A factor of 1 returns a copy.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if `factor` is zero or does
+    /// not evenly divide both dimensions.
+    pub fn downsample(&self, factor: usize) -> Result<Field, EngineError> {
+        let norm = (factor * factor) as f64;
+        self.downsample_blocks(factor, |rows| {
+            rows.map(|row| row.iter().sum::<f64>()).sum::<f64>() / norm
+        })
+    }
+
+    /// Downsamples a hue field (values in turns, where 0 and 1 are the same
+    /// hue) like [`Field::downsample`], but with the circular mean of each
+    /// block, so hues either side of the wrap average to red rather than
+    /// cyan. A block whose hues cancel out (two opposite hues) averages
+    /// to 0. A factor of 1 returns a copy.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if `factor` is zero or does
+    /// not evenly divide both dimensions.
+    pub fn downsample_hue(&self, factor: usize) -> Result<Field, EngineError> {
+        if factor == 1 {
+            return Ok(self.clone());
+        }
+        self.downsample_blocks(factor, |rows| {
+            let (sin, cos) = rows
+                .flatten()
+                .map(|&turns| math::sin_cos(turns * TAU))
+                .fold((0.0, 0.0), |(s, c), (ds, dc)| (s + ds, c + dc));
+            (math::atan2(sin, cos) / TAU).rem_euclid(1.0)
+        })
+    }
+
+    /// Reduces each `factor x factor` block, given as an iterator over its
+    /// row slices, to one cell of a field `factor` times smaller.
+    fn downsample_blocks(
+        &self,
+        factor: usize,
+        reduce: impl Fn(&mut dyn Iterator<Item = &[f64]>) -> f64,
+    ) -> Result<Field, EngineError> {
+        if factor == 0 || !self.width.is_multiple_of(factor) || !self.height.is_multiple_of(factor)
+        {
+            return Err(EngineError::InvalidDimensions);
+        }
+        let width = self.width / factor;
+        let height = self.height / factor;
+        let data = (0..width * height)
+            .map(|i| {
+                let (ox, oy) = ((i % width) * factor, (i / width) * factor);
+                reduce(&mut (oy..oy + factor).map(|y| {
+                    let row = y * self.width;
+                    &self.data[row + ox..row + ox + factor]
+                }))
+            })
+            .collect();
+        Ok(Field {
+            width,
+            height,
+            data,
+        })
+    }
+
     /// Iterates over all cells yielding `(x, y, value)` in row-major order.
     pub fn iter(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
         self.data.iter().enumerate().map(|(i, &v)| {
@@ -611,6 +698,85 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // -- downsample --
+
+    #[test]
+    fn downsample_averages_blocks() {
+        let data = vec![
+            0.0, 1.0, 0.2, 0.2, //
+            1.0, 0.0, 0.2, 0.2, //
+        ];
+        let field = Field::from_data(4, 2, data).unwrap();
+        let small = field.downsample(2).unwrap();
+        assert_eq!(small.width(), 2);
+        assert_eq!(small.height(), 1);
+        assert!((small.get(0, 0) - 0.5).abs() < 1e-12);
+        assert!((small.get(1, 0) - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn downsample_by_one_is_identity() {
+        let field = Field::from_data(2, 2, vec![0.1, 0.2, 0.3, 0.4]).unwrap();
+        assert_eq!(field.downsample(1).unwrap().data(), field.data());
+    }
+
+    #[test]
+    fn downsample_rejects_zero_and_non_dividing_factors() {
+        let field = Field::new(6, 4).unwrap();
+        assert!(matches!(
+            field.downsample(0),
+            Err(EngineError::InvalidDimensions)
+        ));
+        assert!(matches!(
+            field.downsample(3),
+            Err(EngineError::InvalidDimensions)
+        ));
+    }
+
+    #[test]
+    fn downsample_hue_takes_the_circular_mean() {
+        // Hues either side of the wrap average to the wrap, not to cyan
+        let data = vec![
+            0.95, 0.05, 0.2, 0.3, //
+            0.9, 0.1, 0.3, 0.2, //
+        ];
+        let field = Field::from_data(4, 2, data).unwrap();
+        let hue = field.downsample_hue(2).unwrap();
+        assert_eq!((hue.width(), hue.height()), (2, 1));
+        let wrap_distance = |v: f64| v.min(1.0 - v);
+        assert!(wrap_distance(hue.get(0, 0)) < 1e-9, "{}", hue.get(0, 0));
+        assert!((hue.get(1, 0) - 0.25).abs() < 1e-9);
+        assert!((field.downsample(2).unwrap().get(0, 0) - 0.5).abs() < 1e-12);
+
+        // Uniform blocks keep their hue; factor 1 is an exact copy
+        let flat = Field::from_data(2, 2, vec![0.7; 4]).unwrap();
+        assert!((flat.downsample_hue(2).unwrap().get(0, 0) - 0.7).abs() < 1e-9);
+        assert_eq!(field.downsample_hue(1).unwrap(), field);
+        assert!(field.downsample_hue(3).is_err());
+    }
+
+    // -- Serde --
+
+    #[test]
+    fn serde_round_trip() {
+        let field = Field::from_data(2, 2, vec![0.0, 0.25, 0.5, 1.0]).unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+        let restored: Field = serde_json::from_str(&json).unwrap();
+        assert_eq!(field, restored);
+    }
+
+    #[test]
+    fn deserialize_rejects_wrong_data_length() {
+        let json = r#"{"width":2,"height":2,"data":[0.0,1.0]}"#;
+        assert!(serde_json::from_str::<Field>(json).is_err());
+    }
+
+    #[test]
+    fn deserialize_rejects_zero_dimensions() {
+        let json = r#"{"width":0,"height":2,"data":[]}"#;
+        assert!(serde_json::from_str::<Field>(json).is_err());
+    }
+
     // -- Property-based tests --
 
     mod proptests {
@@ -633,6 +799,20 @@ mod tests {
         }
 
         proptest! {
+            #[test]
+            fn downsample_preserves_mean(
+                w in 1_usize..=16,
+                h in 1_usize..=16,
+                factor in 1_usize..=4,
+                v in 0.0_f64..=1.0,
+            ) {
+                let mut field = Field::filled(w * factor, h * factor, v).unwrap();
+                field.set(0, 0, 1.0 - v);
+                let small = field.downsample(factor).unwrap();
+                let mean = |f: &Field| f.data().iter().sum::<f64>() / f.data().len() as f64;
+                prop_assert!((mean(&field) - mean(&small)).abs() < 1e-9);
+            }
+
             #[test]
             fn get_after_set_returns_clamped_value(
                 w in dimension(),
@@ -0,0 +1,421 @@
+//! Debug images of field sources.
+//!
+//! [`FieldPreview`] draws any [`FieldSource`] as an [`Image`] so a field
+//! composition can be checked before it drives a simulation:
+//!
+//! - [`PreviewStyle::Streamlines`] traces streamlines from an even grid of
+//!   seed points, with an arrowhead at the downstream end of each.
+//! - [`PreviewStyle::Lic`] smears white noise along the flow (line-integral
+//!   convolution), showing the flow's structure at every pixel.
+//!
+//! Both color by vector magnitude relative to the strongest vector in view,
+//! through a [`Palette`]. Sources are sampled in pixel space, at the pixel
+//! centers used by [`rasterize_vectors`], and interpolated between them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::Srgb;
+use crate::error::EngineError;
+use crate::field::Field;
+use crate::field_source::{rasterize_vectors, FieldSource};
+use crate::image::Image;
+use crate::palette::Palette;
+use crate::prng::{Rng, Xorshift64};
+
+/// Magnitudes below this count as no flow: streamlines stop there.
+const STALL_EPS: f64 = 1e-9;
+
+/// Distance (in pixels) a streamline advances per integration step.
+const TRACE_STEP: f64 = 0.5;
+
+/// Arrowhead stroke length in pixels.
+const ARROW_LENGTH: f64 = 4.0;
+
+/// Angle between an arrowhead stroke and the reversed flow direction.
+const ARROW_SPREAD: f64 = 0.5;
+
+/// Background of streamline previews.
+const BACKGROUND: Srgb = Srgb {
+    r: 0.04,
+    g: 0.04,
+    b: 0.05,
+};
+
+/// How a [`FieldPreview`] draws the flow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewStyle {
+    /// Streamlines with arrowheads over a dark background.
+    #[default]
+    Streamlines,
+    /// Line-integral convolution of white noise.
+    Lic,
+}
+
+/// Settings for drawing a field source as an image.
+#[derive(Debug, Clone)]
+pub struct FieldPreview {
+    style: PreviewStyle,
+    time: f64,
+    spacing: f64,
+    length: f64,
+    seed: u64,
+    palette: Palette,
+}
+
+impl Default for FieldPreview {
+    fn default() -> Self {
+        Self::new(PreviewStyle::default())
+    }
+}
+
+impl FieldPreview {
+    /// Creates a preview at time 0 with 16-pixel streamline spacing, a
+    /// 20-pixel trace length, seed 0, and the `ocean` palette.
+    pub fn new(style: PreviewStyle) -> Self {
+        Self {
+            style,
+            time: 0.0,
+            spacing: 16.0,
+            length: 20.0,
+            seed: 0,
+            palette: Palette::ocean(),
+        }
+    }
+
+    /// Returns this preview sampling the source at `time`.
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Returns this preview with streamline seeds `spacing` pixels apart
+    /// (at least 1).
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing.max(1.0);
+        self
+    }
+
+    /// Returns this preview tracing `length` pixels each way from a
+    /// streamline seed or LIC pixel (at least 1).
+    pub fn with_length(mut self, length: f64) -> Self {
+        self.length = length.max(1.0);
+        self
+    }
+
+    /// Returns this preview with a different LIC noise seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns this preview coloring magnitudes through `palette`.
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Returns the drawing style.
+    pub fn style(&self) -> PreviewStyle {
+        self.style
+    }
+
+    /// Draws `source` as a `width x height` opaque image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EngineError::InvalidDimensions` if either dimension is zero.
+    pub fn render(
+        &self,
+        source: &dyn FieldSource,
+        width: usize,
+        height: usize,
+    ) -> Result<Image, EngineError> {
+        let (dx, dy) = rasterize_vectors(source, width, height, self.time)?;
+        let flow = Flow::new(dx, dy);
+        match self.style {
+            PreviewStyle::Streamlines => self.streamlines(&flow),
+            PreviewStyle::Lic => self.lic(&flow),
+        }
+    }
+
+    fn streamlines(&self, flow: &Flow) -> Result<Image, EngineError> {
+        let mut image = Image::filled(flow.width, flow.height, BACKGROUND, 1.0)?;
+        let steps = (self.length / TRACE_STEP).ceil() as usize;
+        let (cols, rows) = (
+            (flow.width as f64 / self.spacing).ceil() as usize,
+            (flow.height as f64 / self.spacing).ceil() as usize,
+        );
+        for row in 0..rows {
+            for col in 0..cols {
+                let seed = (
+                    (col as f64 + 0.5) * self.spacing,
+                    (row as f64 + 0.5) * self.spacing,
+                );
+                if !flow.contains(seed) {
+                    continue;
+                }
+                let color = self.palette.sample(flow.relative_magnitude(seed));
+                let back = flow.trace(seed, -TRACE_STEP, steps);
+                let ahead = flow.trace(seed, TRACE_STEP, steps);
+                for &(x, y) in back.iter().chain(&ahead) {
+                    plot(&mut image, x, y, color);
+                }
+                if let [.., before, tip] = ahead[..] {
+                    draw_arrowhead(&mut image, before, tip, color);
+                }
+            }
+        }
+        Ok(image)
+    }
+
+    fn lic(&self, flow: &Flow) -> Result<Image, EngineError> {
+        let (width, height) = (flow.width, flow.height);
+        let mut rng = Xorshift64::new(self.seed);
+        let noise: Vec<f64> = (0..width * height).map(|_| rng.next_f64()).collect();
+        let steps = self.length.ceil() as usize;
+
+        let mut smeared = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let center = (x as f64 + 0.5, y as f64 + 0.5);
+                let mut points = flow.trace(center, -1.0, steps);
+                points.extend(flow.trace(center, 1.0, steps));
+                let sum: f64 = points
+                    .iter()
+                    .map(|&(px, py)| noise[py as usize * width + px as usize])
+                    .sum();
+                smeared.push((sum + noise[y * width + x]) / (points.len() + 1) as f64);
+            }
+        }
+
+        // Averaging flattens the noise toward its mean; stretch it back
+        let (lo, hi) = smeared
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                (lo.min(v), hi.max(v))
+            });
+        let span = if hi - lo > STALL_EPS { hi - lo } else { 1.0 };
+        let mut image = Image::new(width, height)?;
+        for (i, &v) in smeared.iter().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let center = (x as f64 + 0.5, y as f64 + 0.5);
+            let color = self.palette.sample(flow.relative_magnitude(center));
+            let shade = 0.25 + 0.75 * (v - lo) / span;
+            image.set_pixel(
+                x,
+                y,
+                [color.r * shade, color.g * shade, color.b * shade, 1.0],
+            )?;
+        }
+        Ok(image)
+    }
+}
+
+/// Rasterized vectors with bilinear lookup in pixel space.
+struct Flow {
+    dx: Field,
+    dy: Field,
+    width: usize,
+    height: usize,
+    max_magnitude: f64,
+}
+
+impl Flow {
+    fn new(dx: Field, dy: Field) -> Self {
+        let max_magnitude = dx
+            .data()
+            .iter()
+            .zip(dy.data())
+            .map(|(x, y)| x.hypot(*y))
+            .fold(0.0, f64::max);
+        Self {
+            width: dx.width(),
+            height: dx.height(),
+            dx,
+            dy,
+            max_magnitude,
+        }
+    }
+
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64
+    }
+
+    /// The vector at `(x, y)`, interpolated between pixel centers and
+    /// clamped at the edges.
+    fn at(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let fx = (x - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let fy = (y - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let (x0, y0) = (fx.floor() as usize, fy.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (fx - x0 as f64, fy - y0 as f64);
+        let lerp = |field: &Field| {
+            let data = field.data();
+            let top = data[y0 * self.width + x0] * (1.0 - tx) + data[y0 * self.width + x1] * tx;
+            let bottom = data[y1 * self.width + x0] * (1.0 - tx) + data[y1 * self.width + x1] * tx;
+            top * (1.0 - ty) + bottom * ty
+        };
+        (lerp(&self.dx), lerp(&self.dy))
+    }
+
+    /// Magnitude at `p` as a fraction of the largest in view (0 if the
+    /// field is zero everywhere).
+    fn relative_magnitude(&self, p: (f64, f64)) -> f64 {
+        if self.max_magnitude < STALL_EPS {
+            return 0.0;
+        }
+        let (vx, vy) = self.at(p);
+        (vx.hypot(vy) / self.max_magnitude).clamp(0.0, 1.0)
+    }
+
+    /// Unit flow direction at `p`, or `None` where the flow stalls.
+    fn direction(&self, p: (f64, f64)) -> Option<(f64, f64)> {
+        let (vx, vy) = self.at(p);
+        let len = vx.hypot(vy);
+        (len > STALL_EPS).then(|| (vx / len, vy / len))
+    }
+
+    /// Follows the flow from `start` for up to `steps` midpoint steps of
+    /// `step` pixels (negative to go upstream), stopping where it stalls
+    /// or leaves the image. The start point is not included.
+    fn trace(&self, start: (f64, f64), step: f64, steps: usize) -> Vec<(f64, f64)> {
+        let mut points = Vec::with_capacity(steps);
+        let mut p = start;
+        for _ in 0..steps {
+            let Some((ux, uy)) = self.direction(p) else {
+                break;
+            };
+            let mid = (p.0 + ux * step * 0.5, p.1 + uy * step * 0.5);
+            let Some((mx, my)) = self.direction(mid) else {
+                break;
+            };
+            p = (p.0 + mx * step, p.1 + my * step);
+            if !self.contains(p) {
+                break;
+            }
+            points.push(p);
+        }
+        points
+    }
+}
+
+/// Sets the pixel containing `(x, y)` to `color`, if it is in the image.
+fn plot(image: &mut Image, x: f64, y: f64, color: Srgb) {
+    if x >= 0.0 && y >= 0.0 {
+        // Out-of-bounds points are simply skipped
+        let _ = image.set_pixel(x as usize, y as usize, [color.r, color.g, color.b, 1.0]);
+    }
+}
+
+/// Draws two short strokes back from `tip`, against the direction from
+/// `before` to `tip`.
+fn draw_arrowhead(image: &mut Image, before: (f64, f64), tip: (f64, f64), color: Srgb) {
+    let back = (before.1 - tip.1).atan2(before.0 - tip.0);
+    for angle in [back - ARROW_SPREAD, back + ARROW_SPREAD] {
+        let (dx, dy) = (angle.cos(), angle.sin());
+        let mut t = 0.0;
+        while t <= ARROW_LENGTH {
+            plot(image, tip.0 + dx * t, tip.1 + dy * t, color);
+            t += TRACE_STEP;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field_source::{CurlField, Vortex};
+
+    /// A uniform flow.
+    struct Constant(f64, f64);
+
+    impl FieldSource for Constant {
+        fn sample(&self, _x: f64, _y: f64, _time: f64) -> (f64, f64) {
+            (self.0, self.1)
+        }
+    }
+
+    fn lit(image: &Image, x: usize, y: usize) -> bool {
+        let [r, g, b, _] = image.pixel(x, y).unwrap();
+        (r, g, b) != (BACKGROUND.r, BACKGROUND.g, BACKGROUND.b)
+    }
+
+    #[test]
+    fn streamlines_follow_a_uniform_flow() {
+        let preview = FieldPreview::new(PreviewStyle::Streamlines)
+            .with_spacing(16.0)
+            .with_length(6.0);
+        let image = preview.render(&Constant(1.0, 0.0), 32, 32).unwrap();
+        // Seeds at (8, 8), (24, 8), ...; lines run along the rows
+        assert!(lit(&image, 8, 8));
+        assert!(lit(&image, 3, 8) && lit(&image, 13, 8));
+        assert!(!lit(&image, 8, 16));
+        // The arrowhead opens upstream of the tip at x = 14
+        assert!(lit(&image, 11, 6) || lit(&image, 11, 10));
+        assert!(!lit(&image, 16, 6) && !lit(&image, 16, 10));
+    }
+
+    #[test]
+    fn still_fields_draw_only_background() {
+        let image = FieldPreview::default()
+            .render(&Constant(0.0, 0.0), 20, 10)
+            .unwrap();
+        for y in 0..10 {
+            for x in 0..20 {
+                assert!(!lit(&image, x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn lic_smears_noise_along_the_flow() {
+        let preview = FieldPreview::new(PreviewStyle::Lic).with_length(8.0);
+        let image = preview.render(&Constant(0.0, 1.0), 48, 48).unwrap();
+        let shade = |x: usize, y: usize| image.pixel(x, y).unwrap()[0];
+        // Neighbors along a column share most of their kernel; across
+        // columns they share none
+        let (mut along, mut across) = (0.0, 0.0);
+        for y in 10..38 {
+            for x in 0..47 {
+                along += (shade(x, y) - shade(x, y + 1)).abs();
+                across += (shade(x, y) - shade(x + 1, y)).abs();
+            }
+        }
+        assert!(along * 3.0 < across, "along {along}, across {across}");
+    }
+
+    #[test]
+    fn previews_are_deterministic_and_opaque() {
+        let source = CurlField::new(0.05, 1.0, 3);
+        for style in [PreviewStyle::Streamlines, PreviewStyle::Lic] {
+            let preview = FieldPreview::new(style).with_seed(9);
+            let a = preview.render(&source, 40, 30).unwrap();
+            assert_eq!(a, preview.render(&source, 40, 30).unwrap());
+            assert!(a.data().chunks(4).all(|px| px[3] == 1.0));
+        }
+    }
+
+    #[test]
+    fn colors_track_relative_magnitude() {
+        let vortex = Vortex {
+            x: 32.0,
+            y: 32.0,
+            strength: 1.0,
+            radius: 10.0,
+        };
+        let (dx, dy) = rasterize_vectors(&vortex, 64, 64, 0.0).unwrap();
+        let flow = Flow::new(dx, dy);
+        let near = flow.relative_magnitude((32.0, 42.0));
+        let far = flow.relative_magnitude((2.0, 2.0));
+        assert!(near > far);
+        assert!((0.0..=1.0).contains(&near) && (0.0..=1.0).contains(&far));
+    }
+
+    #[test]
+    fn zero_dimensions_are_rejected() {
+        assert!(FieldPreview::default()
+            .render(&Constant(1.0, 0.0), 0, 8)
+            .is_err());
+    }
+}
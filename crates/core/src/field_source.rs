@@ -2,12 +2,38 @@
 //!
 //! A [`FieldSource`] produces (dx, dy) displacement vectors at any point in
 //! space and time. Sources include noise generators (Perlin, Simplex, Curl,
-//! Worley, Turbulence), geometric attractors (point, line, orbital, gravity
-//! well), vortices, and composites that sum multiple sources.
+//! Worley, Turbulence, fBm, ridged multifractal), geometric attractors
+//! (point, line, orbital, gravity well), vortices, signed-distance shapes
+//! (circle, box, polygon, text) whose flow follows their outlines,
+//! composites that combine multiple sources, domain warps that sample one
+//! source through another, and region masks that confine a source to part
+//! of the canvas.
 //!
 //! All implementations are deterministic: same inputs produce the same output.
+//! Noise sources tile seamlessly with `with_period`, e.g. at a [`Field`]'s
+//! size so flow wraps with its toroidal edges.
+//! The noise and SDF sources are also [`ScalarSource`]s, and [`rasterize`]
+//! / [`rasterize_vectors`] sample either kind onto a [`Field`] grid.
+//!
+//! Sources can also be described declaratively with [`FieldSourceSpec`], a
+//! `"kind"`-tagged serde enum, and built with [`FieldSourceSpec::build`] or
+//! [`from_json`] (or [`FieldSourceSpec::build_scalar`] for a scalar source):
+//!
+//! ```json
+//! {"kind": "composite", "sources": [
+//!     {"kind": "curl", "scale": 0.01, "strength": 2.0, "seed": 7},
+//!     {"kind": "vortex", "x": 128.0, "y": 128.0, "strength": 1.5, "radius": 40.0}
+//! ]}
+//! ```
+
+use std::f64::consts::TAU;
 
 use noise::{NoiseFn, OpenSimplex, Perlin};
+use serde::{Deserialize, Serialize};
+
+use crate::error::EngineError;
+use crate::field::Field;
+use crate::math;
 
 /// A source of 2D vector values for field-based simulation.
 ///
@@ -19,6 +45,66 @@ pub trait FieldSource: Send + Sync {
     fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64);
 }
 
+/// A source of scalar values, the single-channel counterpart of
+/// [`FieldSource`].
+///
+/// Noise sources give their noise value times `strength`, [`WorleyField`]
+/// its cellular distance times `strength`, and [`SdfField`] its signed
+/// distance. Like field sources, implementations must be deterministic.
+pub trait ScalarSource: Send + Sync {
+    /// Sample the value at position (x, y) at the given time.
+    fn value(&self, x: f64, y: f64, time: f64) -> f64;
+}
+
+/// Samples `source` at the pixel centers `(x + 0.5, y + 0.5)` of a
+/// `width x height` grid into a [`Field`].
+///
+/// Values are stored as sampled; remap them into [0, 1] before using the
+/// field as an engine field or mask.
+///
+/// # Errors
+///
+/// Returns `EngineError::InvalidDimensions` if either dimension is zero.
+pub fn rasterize(
+    source: &dyn ScalarSource,
+    width: usize,
+    height: usize,
+    time: f64,
+) -> Result<Field, EngineError> {
+    let mut field = Field::new(width, height)?;
+    for (i, v) in field.data_mut().iter_mut().enumerate() {
+        let (x, y) = ((i % width) as f64 + 0.5, (i / width) as f64 + 0.5);
+        *v = source.value(x, y, time);
+    }
+    Ok(field)
+}
+
+/// Samples a vector `source` like [`rasterize`], returning the `dx` and
+/// `dy` components as two fields.
+///
+/// # Errors
+///
+/// Returns `EngineError::InvalidDimensions` if either dimension is zero.
+pub fn rasterize_vectors(
+    source: &dyn FieldSource,
+    width: usize,
+    height: usize,
+    time: f64,
+) -> Result<(Field, Field), EngineError> {
+    let mut dx = Field::new(width, height)?;
+    let mut dy = Field::new(width, height)?;
+    for (i, (vx, vy)) in dx
+        .data_mut()
+        .iter_mut()
+        .zip(dy.data_mut().iter_mut())
+        .enumerate()
+    {
+        let (x, y) = ((i % width) as f64 + 0.5, (i / width) as f64 + 0.5);
+        (*vx, *vy) = source.sample(x, y, time);
+    }
+    Ok((dx, dy))
+}
+
 // ---------------------------------------------------------------------------
 // Noise-based sources
 // ---------------------------------------------------------------------------
@@ -29,6 +115,7 @@ pub struct PerlinField {
     noise: Perlin,
     scale: f64,
     strength: f64,
+    period: Option<(f64, f64)>,
 }
 
 /// Simplex (OpenSimplex) noise field, same pattern as [`PerlinField`].
@@ -36,6 +123,7 @@ pub struct SimplexField {
     noise: OpenSimplex,
     scale: f64,
     strength: f64,
+    period: Option<(f64, f64)>,
 }
 
 /// Curl noise field: the curl of a scalar Perlin noise, producing
@@ -45,19 +133,88 @@ pub struct CurlField {
     scale: f64,
     strength: f64,
     eps: f64,
+    period: Option<(f64, f64)>,
 }
 
-/// Worley (cellular/Voronoi) noise field producing gradient-like displacement.
+/// Worley (cellular/Voronoi) noise field: displacement along the gradient
+/// of the distance to the nearest feature points.
 ///
-/// Uses two Perlin noise generators at different seeds to approximate
-/// cellular noise gradients while remaining `Send + Sync` safe. The
+/// Space is cut into unit cells (after `scale`), each holding one feature
+/// point placed by hashing the cell coordinates with the seed; over time
+/// the points orbit inside their cells. The scalar value (see
+/// [`ScalarSource`]) is a [`WorleyMode`] combination of the nearest distances under a
+/// [`DistanceMetric`], and `sample` returns its gradient times `strength`
+/// -- for F1, unit vectors pointing away from the nearest point, flipping
+/// along the cell borders. The hashing is self-contained (the
 /// `noise::Worley` type uses `Rc` internally and cannot satisfy the
-/// thread-safety bounds required by [`FieldSource`].
+/// thread-safety bounds required by [`FieldSource`]).
 pub struct WorleyField {
-    noise_x: Perlin,
-    noise_y: Perlin,
+    seed: u32,
     scale: f64,
     strength: f64,
+    mode: WorleyMode,
+    metric: DistanceMetric,
+    period: Option<(f64, f64)>,
+}
+
+/// Which nearest-point distances a [`WorleyField`] combines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorleyMode {
+    /// Distance to the nearest point: round cells.
+    #[default]
+    F1,
+    /// Distance to the second-nearest point: softer, lumpier cells.
+    F2,
+    /// Difference of the two: zero along cell borders, a crackle network.
+    F2MinusF1,
+}
+
+/// How a [`WorleyField`] measures distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMetric {
+    /// Straight-line distance: rounded cells.
+    #[default]
+    Euclidean,
+    /// Sum of axis distances: diamond-edged cells.
+    Manhattan,
+    /// Largest axis distance: square-edged cells.
+    Chebyshev,
+}
+
+impl DistanceMetric {
+    /// Returns the distance of offset `(dx, dy)` and its gradient with
+    /// respect to the offset (zero at the origin).
+    fn measure(self, dx: f64, dy: f64) -> (f64, f64, f64) {
+        let sign = |v: f64| {
+            if v > 0.0 {
+                1.0
+            } else if v < 0.0 {
+                -1.0
+            } else {
+                0.0
+            }
+        };
+        match self {
+            DistanceMetric::Euclidean => {
+                let d = (dx * dx + dy * dy).sqrt();
+                if d < SINGULARITY_EPS {
+                    (d, 0.0, 0.0)
+                } else {
+                    (d, dx / d, dy / d)
+                }
+            }
+            DistanceMetric::Manhattan => (dx.abs() + dy.abs(), sign(dx), sign(dy)),
+            DistanceMetric::Chebyshev => {
+                if dx.abs() >= dy.abs() {
+                    (dx.abs(), sign(dx), 0.0)
+                } else {
+                    (dy.abs(), 0.0, sign(dy))
+                }
+            }
+        }
+    }
 }
 
 /// Multi-octave turbulence noise: sum of scaled noise at increasing
@@ -69,6 +226,41 @@ pub struct TurbulenceField {
     octaves: u32,
     persistence: f64,
     lacunarity: f64,
+    period: Option<(f64, f64)>,
+}
+
+/// Fractal Brownian motion: octaves of Perlin noise at rising frequencies
+/// and falling amplitudes, normalized by the total amplitude so values stay
+/// in [-1, 1] times `strength` whatever the octave count.
+///
+/// Unlike [`TurbulenceField`], which sums raw octaves sampled on aligned
+/// lattices, each octave is shifted to decorrelate them, giving smooth,
+/// terrain-like flow. Vectors are two offset channels, as in
+/// [`PerlinField`].
+pub struct FbmField {
+    noise: Perlin,
+    scale: f64,
+    strength: f64,
+    octaves: u32,
+    lacunarity: f64,
+    gain: f64,
+    period: Option<(f64, f64)>,
+}
+
+/// Ridged multifractal noise (after Musgrave): each octave folds the noise
+/// into sharp ridges, `(1 - |n|)^2`, weighted by the octave before it so
+/// ridges gather detail where they are already high.
+///
+/// The scalar value is in [0, 1] times `strength`; vectors are its curl, so
+/// flow runs along the ridges as thin, filament-like streams.
+pub struct RidgedField {
+    noise: Perlin,
+    scale: f64,
+    strength: f64,
+    octaves: u32,
+    lacunarity: f64,
+    gain: f64,
+    period: Option<(f64, f64)>,
 }
 
 // ---------------------------------------------------------------------------
@@ -76,6 +268,7 @@ pub struct TurbulenceField {
 // ---------------------------------------------------------------------------
 
 /// Point attractor: pulls toward a single point with distance-based falloff.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PointAttractor {
     pub x: f64,
     pub y: f64,
@@ -84,6 +277,7 @@ pub struct PointAttractor {
 }
 
 /// Point repulsor: pushes away from a single point (negated attractor).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PointRepulsor {
     pub x: f64,
     pub y: f64,
@@ -92,6 +286,7 @@ pub struct PointRepulsor {
 }
 
 /// Line attractor: pulls toward the nearest point on a line segment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LineAttractor {
     pub x0: f64,
     pub y0: f64,
@@ -102,6 +297,7 @@ pub struct LineAttractor {
 }
 
 /// Orbital attractor: creates circular orbits around a center point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OrbitalAttractor {
     pub x: f64,
     pub y: f64,
@@ -111,6 +307,7 @@ pub struct OrbitalAttractor {
 
 /// Gravity well: inverse-square attraction toward a point, clamped to avoid
 /// singularity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GravityWell {
     pub x: f64,
     pub y: f64,
@@ -122,6 +319,7 @@ pub struct GravityWell {
 // ---------------------------------------------------------------------------
 
 /// Rotational vortex field with Gaussian distance falloff.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Vortex {
     pub x: f64,
     pub y: f64,
@@ -129,13 +327,158 @@ pub struct Vortex {
     pub radius: f64,
 }
 
+// ---------------------------------------------------------------------------
+// Signed distance fields
+// ---------------------------------------------------------------------------
+
+/// A shape for [`SdfField`], in the same coordinates as the field samples.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "circle", "x": 64.0, "y":
+/// 64.0, "radius": 20.0}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SdfShape {
+    /// A disc around `(x, y)`.
+    Circle { x: f64, y: f64, radius: f64 },
+    /// An axis-aligned box centered on `(x, y)`.
+    Box {
+        x: f64,
+        y: f64,
+        half_width: f64,
+        half_height: f64,
+    },
+    /// A closed polygon through `points`, in either winding order.
+    Polygon { points: Vec<[f64; 2]> },
+    /// Text in the built-in [`font`](crate::font) with its top-left corner
+    /// at `(x, y)`, each font pixel a `size`-wide square.
+    Text {
+        text: String,
+        x: f64,
+        y: f64,
+        size: f64,
+    },
+}
+
+/// How an [`SdfField`] moves points relative to its shape's boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SdfMode {
+    /// Toward the boundary from both sides, so flow collects on the outline.
+    #[default]
+    Attract,
+    /// Away from the boundary on both sides.
+    Repel,
+    /// Along the boundary, counter-clockwise around the shape.
+    Flow,
+}
+
+/// Signed-distance-field source: displacement relative to the boundary of
+/// an [`SdfShape`].
+///
+/// The shape's signed distance is negative inside and positive outside; its
+/// normalized gradient (the outward normal) gives the direction, turned per
+/// [`SdfMode`], and the magnitude falls off as `strength / (1 + |d| /
+/// radius)` with the distance `d` to the boundary.
+pub struct SdfField {
+    shape: SdfShape,
+    strength: f64,
+    radius: f64,
+    mode: SdfMode,
+    /// Centers of the lit font pixels, for text shapes.
+    cells: Vec<(f64, f64)>,
+}
+
 // ---------------------------------------------------------------------------
 // Composite
 // ---------------------------------------------------------------------------
 
-/// Sums the displacements from multiple [`FieldSource`] objects.
+/// Combines the displacements from multiple [`FieldSource`] objects.
+///
+/// Each source's vector is scaled by its weight (1 unless added with
+/// [`CompositeField::add_weighted`]) and the results merged per
+/// [`CombineMode`] -- by default, summed.
 pub struct CompositeField {
-    sources: Vec<Box<dyn FieldSource>>,
+    sources: Vec<(Box<dyn FieldSource>, f64)>,
+    mode: CombineMode,
+    mask: Option<(SdfField, f64)>,
+}
+
+/// How a [`CompositeField`] merges its weighted source vectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CombineMode {
+    /// The sum of the vectors.
+    #[default]
+    Add,
+    /// The longest vector (the first one on ties).
+    MaxMagnitude,
+    /// The component-wise product of the vectors.
+    Multiply,
+    /// A blend along the sources in order by the mask value `m` in [0, 1]:
+    /// the first source at 0, the last at 1, linear in between. Without a
+    /// mask, the first source.
+    Lerp,
+}
+
+/// A shape mask for [`CombineMode::Lerp`]: 1 inside the shape, 0 outside,
+/// with a linear ramp `feather` wide centered on the boundary (hard when 0).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SdfMask {
+    pub shape: SdfShape,
+    #[serde(default)]
+    pub feather: f64,
+}
+
+/// Domain-warped source: samples `inner` at coordinates pushed by the
+/// vectors of `warp`, `inner.sample(x + amount * wx, y + amount * wy, t)`.
+///
+/// Warps nest -- a warped field can be the `warp` or `inner` of another --
+/// for the layered, folded look of organic flow.
+pub struct WarpedField {
+    inner: Box<dyn FieldSource>,
+    warp: Box<dyn FieldSource>,
+    amount: f64,
+}
+
+/// Region-masked source: the vectors of `inner` scaled by a [`RegionMask`]
+/// value in [0, 1] (or one minus it, when inverted).
+///
+/// Adding masked sources to a [`CompositeField`] gives different areas of
+/// the canvas different force regimes, e.g. curl noise inside a disc and a
+/// vortex everywhere else.
+pub struct MaskedField {
+    inner: Box<dyn FieldSource>,
+    mask: RegionMask,
+    sdf: Option<SdfField>,
+    invert: bool,
+}
+
+/// Where a [`MaskedField`] applies, as a value in [0, 1] over the same
+/// coordinates as the field samples.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "radial", "x": 64.0,
+/// "y": 64.0, "inner": 16.0, "outer": 48.0}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RegionMask {
+    /// Explicit values, one cell per unit: interpolated bilinearly between
+    /// cell centers and wrapping toroidally like the field itself.
+    Field(Field),
+    /// 1 inside the shape, 0 outside, with a linear ramp `feather` wide
+    /// centered on the boundary (hard when 0).
+    Sdf {
+        shape: SdfShape,
+        #[serde(default)]
+        feather: f64,
+    },
+    /// 1 within `inner` of (x, y), easing (smoothstep) to 0 at `outer`; a
+    /// hard disc when `outer <= inner`.
+    Radial {
+        x: f64,
+        y: f64,
+        inner: f64,
+        outer: f64,
+    },
 }
 
 // ---------------------------------------------------------------------------
@@ -149,8 +492,17 @@ impl PerlinField {
             noise: Perlin::new(seed),
             scale,
             strength,
+            period: None,
         }
     }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
 }
 
 impl SimplexField {
@@ -160,8 +512,17 @@ impl SimplexField {
             noise: OpenSimplex::new(seed),
             scale,
             strength,
+            period: None,
         }
     }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
 }
 
 impl CurlField {
@@ -172,19 +533,229 @@ impl CurlField {
             scale,
             strength,
             eps: 0.001,
+            period: None,
         }
     }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
 }
 
 impl WorleyField {
-    /// Creates a new Worley-like noise field source using two Perlin generators
-    /// at distinct seeds to approximate cellular noise gradients.
+    /// Creates a new Worley noise field source with F1 Euclidean distances.
     pub fn new(scale: f64, strength: f64, seed: u32) -> Self {
         Self {
-            noise_x: Perlin::new(seed),
-            noise_y: Perlin::new(seed.wrapping_add(7919)),
+            seed,
             scale,
             strength,
+            mode: WorleyMode::F1,
+            metric: DistanceMetric::Euclidean,
+            period: None,
+        }
+    }
+
+    /// Returns this field with a different distance combination.
+    pub fn with_mode(mut self, mode: WorleyMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns this field with a different distance metric.
+    pub fn with_metric(mut self, metric: DistanceMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    ///
+    /// Tiles hold whole cells, so each axis rounds `period * scale` to a
+    /// cell count (at least 1) and stretches its cells slightly to fit.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
+
+    /// Returns the feature point of cell `(cx, cy)` at `time`, in scaled
+    /// coordinates. With `cells` per axis, the points repeat every `cells`.
+    fn feature_point(&self, cx: i64, cy: i64, cells: Option<(i64, i64)>, time: f64) -> (f64, f64) {
+        let (hx, hy) = cells.map_or((cx, cy), |(nx, ny)| (cx.rem_euclid(nx), cy.rem_euclid(ny)));
+        let h = hash_u32((hx as u32) ^ hash_u32((hy as u32) ^ hash_u32(self.seed)));
+        let angle = std::f64::consts::TAU * unit(h) + time;
+        let radius = WORLEY_JITTER * unit(hash_u32(h));
+        (
+            cx as f64 + 0.5 + radius * math::cos(angle),
+            cy as f64 + 0.5 + radius * math::sin(angle),
+        )
+    }
+
+    /// Returns the mode's distance value at point `(x, y)` and its gradient
+    /// in scaled coordinates.
+    fn evaluate(&self, x: f64, y: f64, time: f64) -> (f64, f64, f64) {
+        let (cells, sx, sy) = match self.period {
+            Some((px, py)) => {
+                let nx = (px * self.scale).round().max(1.0);
+                let ny = (py * self.scale).round().max(1.0);
+                (Some((nx as i64, ny as i64)), x * nx / px, y * ny / py)
+            }
+            None => (None, x * self.scale, y * self.scale),
+        };
+        let (cx, cy) = (sx.floor() as i64, sy.floor() as i64);
+        let mut nearest = [(f64::INFINITY, 0.0, 0.0); 2];
+        // Feature points stay inside their cells, so the nearest two are
+        // always within two cells
+        for ny in cy - 2..=cy + 2 {
+            for nx in cx - 2..=cx + 2 {
+                let (fx, fy) = self.feature_point(nx, ny, cells, time);
+                let measured = self.metric.measure(sx - fx, sy - fy);
+                if measured.0 < nearest[0].0 {
+                    nearest = [measured, nearest[0]];
+                } else if measured.0 < nearest[1].0 {
+                    nearest[1] = measured;
+                }
+            }
+        }
+        let [f1, f2] = nearest;
+        match self.mode {
+            WorleyMode::F1 => f1,
+            WorleyMode::F2 => f2,
+            WorleyMode::F2MinusF1 => (f2.0 - f1.0, f2.1 - f1.1, f2.2 - f1.2),
+        }
+    }
+}
+
+impl FbmField {
+    /// Creates an fBm source with 5 octaves, lacunarity 2, and gain 0.5.
+    pub fn new(scale: f64, strength: f64, seed: u32) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            strength,
+            octaves: 5,
+            lacunarity: 2.0,
+            gain: 0.5,
+            period: None,
+        }
+    }
+
+    /// Returns this source with a different octave count (at least 1).
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    /// Returns this source with a different frequency step per octave.
+    pub fn with_lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Returns this source with a different amplitude step per octave.
+    pub fn with_gain(mut self, gain: f64) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
+
+    /// Normalized fBm at scaled point `(sx, sy)`, read `channel` units off
+    /// along the diagonal for a second, independent channel.
+    fn fbm(&self, sx: f64, sy: f64, channel: f64, time: f64) -> f64 {
+        let (total, norm, _, _) =
+            (0..self.octaves).fold((0.0, 0.0, 1.0, 1.0), |(total, norm, amp, freq), octave| {
+                let shift = channel * freq + f64::from(octave) * OCTAVE_SHIFT;
+                let period = scaled_period(self.period, self.scale * freq);
+                let n = noise_at(&self.noise, sx * freq, sy * freq, shift, time, period);
+                (
+                    total + n * amp,
+                    norm + amp,
+                    amp * self.gain,
+                    freq * self.lacunarity,
+                )
+            });
+        if norm.abs() < SINGULARITY_EPS {
+            0.0
+        } else {
+            total / norm
+        }
+    }
+}
+
+impl RidgedField {
+    /// Creates a ridged source with 5 octaves, lacunarity 2, and gain 0.5.
+    pub fn new(scale: f64, strength: f64, seed: u32) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            strength,
+            octaves: 5,
+            lacunarity: 2.0,
+            gain: 0.5,
+            period: None,
+        }
+    }
+
+    /// Returns this source with a different octave count (at least 1).
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    /// Returns this source with a different frequency step per octave.
+    pub fn with_lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Returns this source with a different amplitude step per octave.
+    pub fn with_gain(mut self, gain: f64) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
+
+    /// Normalized ridged multifractal at scaled point `(sx, sy)`.
+    fn ridged(&self, sx: f64, sy: f64, time: f64) -> f64 {
+        let (total, norm, _, _, _) = (0..self.octaves).fold(
+            (0.0, 0.0, 1.0, 1.0, 1.0),
+            |(total, norm, amp, freq, weight), octave| {
+                let shift = f64::from(octave) * OCTAVE_SHIFT;
+                let period = scaled_period(self.period, self.scale * freq);
+                let n = noise_at(&self.noise, sx * freq, sy * freq, shift, time, period);
+                let ridge = (1.0 - n.abs()).powi(2) * weight;
+                (
+                    total + ridge * amp,
+                    norm + amp,
+                    amp * self.gain,
+                    freq * self.lacunarity,
+                    // Sharpen the next octave where this one is high
+                    (ridge * 2.0).clamp(0.0, 1.0),
+                )
+            },
+        );
+        if norm.abs() < SINGULARITY_EPS {
+            0.0
+        } else {
+            total / norm
         }
     }
 }
@@ -206,24 +777,194 @@ impl TurbulenceField {
             octaves,
             persistence,
             lacunarity,
+            period: None,
+        }
+    }
+
+    /// Returns this source tiling seamlessly every `width` x `height` units;
+    /// pass a [`Field`]'s dimensions to wrap with its toroidal edges. A
+    /// non-positive period turns tiling off.
+    pub fn with_period(mut self, width: f64, height: f64) -> Self {
+        self.period = (width > 0.0 && height > 0.0).then_some((width, height));
+        self
+    }
+}
+
+impl SdfField {
+    /// Creates a new SDF source attracting toward the boundary of `shape`.
+    pub fn new(shape: SdfShape, strength: f64, radius: f64) -> Self {
+        let cells = match &shape {
+            SdfShape::Text { text, x, y, size } => crate::font::text_pixels(text)
+                .map(|(px, py)| (x + (px as f64 + 0.5) * size, y + (py as f64 + 0.5) * size))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self {
+            shape,
+            strength,
+            radius,
+            mode: SdfMode::Attract,
+            cells,
+        }
+    }
+
+    /// Returns this source with a different mode.
+    pub fn with_mode(mut self, mode: SdfMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the shape's signed distance at (x, y): negative inside.
+    ///
+    /// Exact for circles, boxes, and polygons; for text, the union of the
+    /// font pixel squares, exact outside and a bound inside.
+    pub fn distance(&self, x: f64, y: f64) -> f64 {
+        match &self.shape {
+            SdfShape::Circle {
+                x: cx,
+                y: cy,
+                radius,
+            } => ((x - cx).powi(2) + (y - cy).powi(2)).sqrt() - radius,
+            SdfShape::Box {
+                x: cx,
+                y: cy,
+                half_width,
+                half_height,
+            } => box_distance(x - cx, y - cy, *half_width, *half_height),
+            SdfShape::Polygon { points } => polygon_distance(points, x, y),
+            SdfShape::Text { size, .. } => {
+                let half = size * 0.5;
+                self.cells
+                    .iter()
+                    .map(|(cx, cy)| box_distance(x - cx, y - cy, half, half))
+                    .fold(f64::INFINITY, f64::min)
+            }
         }
     }
 }
 
 impl CompositeField {
-    /// Creates an empty composite field.
+    /// Creates an empty composite field that sums its sources.
     pub fn new() -> Self {
         Self {
             sources: Vec::new(),
+            mode: CombineMode::Add,
+            mask: None,
         }
     }
 
-    /// Adds a source to the composite (builder pattern).
+    /// Adds a source to the composite with weight 1 (builder pattern).
     #[allow(clippy::should_implement_trait)]
-    pub fn add(mut self, source: Box<dyn FieldSource>) -> Self {
-        self.sources.push(source);
+    pub fn add(self, source: Box<dyn FieldSource>) -> Self {
+        self.add_weighted(source, 1.0)
+    }
+
+    /// Adds a source whose vectors are scaled by `weight`.
+    pub fn add_weighted(mut self, source: Box<dyn FieldSource>, weight: f64) -> Self {
+        self.sources.push((source, weight));
+        self
+    }
+
+    /// Returns this composite with a different combination mode.
+    pub fn with_mode(mut self, mode: CombineMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns this composite with a mask for [`CombineMode::Lerp`].
+    pub fn with_mask(mut self, mask: SdfMask) -> Self {
+        self.mask = Some((SdfField::new(mask.shape, 0.0, 1.0), mask.feather));
+        self
+    }
+
+    /// Returns the mask value at (x, y): 0 without a mask.
+    pub fn mask_value(&self, x: f64, y: f64) -> f64 {
+        let Some((sdf, feather)) = &self.mask else {
+            return 0.0;
+        };
+        feathered_inside(sdf.distance(x, y), *feather)
+    }
+}
+
+impl WarpedField {
+    /// Creates a source sampling `inner` through `warp` at full amount.
+    pub fn new(inner: Box<dyn FieldSource>, warp: Box<dyn FieldSource>) -> Self {
+        Self {
+            inner,
+            warp,
+            amount: 1.0,
+        }
+    }
+
+    /// Returns this source with the warp vectors scaled by `amount`.
+    pub fn with_amount(mut self, amount: f64) -> Self {
+        self.amount = amount;
+        self
+    }
+}
+
+impl MaskedField {
+    /// Creates a source applying `inner` where `mask` is set.
+    pub fn new(inner: Box<dyn FieldSource>, mask: RegionMask) -> Self {
+        let sdf = match &mask {
+            RegionMask::Sdf { shape, .. } => Some(SdfField::new(shape.clone(), 0.0, 1.0)),
+            _ => None,
+        };
+        Self {
+            inner,
+            mask,
+            sdf,
+            invert: false,
+        }
+    }
+
+    /// Returns this source applying `inner` where the mask is unset instead.
+    pub fn inverted(mut self) -> Self {
+        self.invert = !self.invert;
         self
     }
+
+    /// Returns the weight of `inner` at (x, y), in [0, 1].
+    pub fn mask_value(&self, x: f64, y: f64) -> f64 {
+        let value = match (&self.mask, &self.sdf) {
+            (RegionMask::Field(field), _) => bilinear(field, x, y),
+            (RegionMask::Sdf { feather, .. }, Some(sdf)) => {
+                feathered_inside(sdf.distance(x, y), *feather)
+            }
+            (RegionMask::Sdf { .. }, None) => 0.0,
+            (
+                RegionMask::Radial {
+                    x: cx,
+                    y: cy,
+                    inner,
+                    outer,
+                },
+                _,
+            ) => {
+                let d = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+                if outer <= inner {
+                    if d <= *inner {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                } else {
+                    let t = ((outer - d) / (outer - inner)).clamp(0.0, 1.0);
+                    t * t * (3.0 - 2.0 * t)
+                }
+            }
+        };
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        if self.invert {
+            1.0 - value
+        } else {
+            value
+        }
+    }
 }
 
 impl Default for CompositeField {
@@ -242,6 +983,101 @@ const SINGULARITY_EPS: f64 = 1e-10;
 /// Maximum force magnitude for gravity wells to avoid singularity blowup.
 const MAX_GRAVITY_FORCE: f64 = 1000.0;
 
+/// Offset between the lattices of successive fBm and ridged octaves, so
+/// their zeros do not line up.
+const OCTAVE_SHIFT: f64 = 17.31;
+
+/// Step (in scaled noise space) for the ridged field's curl.
+const RIDGED_CURL_STEP: f64 = 1e-3;
+
+/// Step for the central differences giving an SDF's normal.
+const SDF_GRADIENT_STEP: f64 = 1e-4;
+
+/// Largest offset of a Worley feature point from its cell center, keeping
+/// it inside the cell.
+const WORLEY_JITTER: f64 = 0.45;
+
+/// Integer hash with good avalanche (lowbias32).
+fn hash_u32(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
+
+/// Maps a hash to [0, 1).
+fn unit(h: u32) -> f64 {
+    f64::from(h) / 4_294_967_296.0
+}
+
+/// Maps a signed distance (negative inside) to 1 inside and 0 outside, with
+/// a linear ramp `feather` wide centered on the boundary (hard when 0).
+fn feathered_inside(d: f64, feather: f64) -> f64 {
+    if feather > 0.0 {
+        (0.5 - d / feather).clamp(0.0, 1.0)
+    } else if d <= 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Samples `field` at (x, y), one cell per unit with values at the cell
+/// centers, interpolating bilinearly and wrapping toroidally.
+fn bilinear(field: &Field, x: f64, y: f64) -> f64 {
+    let (fx, fy) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (fx.floor(), fy.floor());
+    let (tx, ty) = (fx - x0, fy - y0);
+    let (ix, iy) = (x0 as isize, y0 as isize);
+    let top = field.get(ix, iy) * (1.0 - tx) + field.get(ix + 1, iy) * tx;
+    let bottom = field.get(ix, iy + 1) * (1.0 - tx) + field.get(ix + 1, iy + 1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// Returns a period in noise space at `factor` (scale times octave
+/// frequency), or `None` when untiled or too small to wrap.
+fn scaled_period(period: Option<(f64, f64)>, factor: f64) -> Option<(f64, f64)> {
+    period
+        .map(|(px, py)| (px * factor, py * factor))
+        .filter(|(px, py)| px.abs() > SINGULARITY_EPS && py.abs() > SINGULARITY_EPS)
+}
+
+/// Samples `noise` at scaled point `(sx, sy)`, offset by `shift`.
+///
+/// Untiled, this is the plane point `[sx + shift, sy + shift, time]`. With
+/// a scaled `period`, each axis is rolled into a circle whose circumference
+/// is its period, putting the point on a torus in 4D noise space: values
+/// repeat exactly every period with features at the same scale, and
+/// `shift` and `time` move the torus through the noise.
+fn noise_at<N>(
+    noise: &N,
+    sx: f64,
+    sy: f64,
+    shift: f64,
+    time: f64,
+    period: Option<(f64, f64)>,
+) -> f64
+where
+    N: NoiseFn<f64, 3> + NoiseFn<f64, 4>,
+{
+    match period {
+        None => noise.get([sx + shift, sy + shift, time]),
+        Some((px, py)) => {
+            let (ax, ay) = (TAU * sx / px, TAU * sy / py);
+            let (rx, ry) = (px / TAU, py / TAU);
+            let offset = shift + 0.5 * time;
+            noise.get([
+                rx * math::cos(ax) + offset,
+                rx * math::sin(ax) + offset,
+                ry * math::cos(ay) + offset,
+                ry * math::sin(ay) + offset,
+            ])
+        }
+    }
+}
+
 /// Computes the displacement vector toward a target point with distance-based
 /// falloff. Returns (0, 0) at singularity.
 fn attract_toward(
@@ -267,6 +1103,40 @@ fn attract_toward(
     (nx * magnitude, ny * magnitude)
 }
 
+/// Signed distance from offset (px, py) to a centered box.
+fn box_distance(px: f64, py: f64, half_width: f64, half_height: f64) -> f64 {
+    let qx = px.abs() - half_width;
+    let qy = py.abs() - half_height;
+    let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+    outside + qx.max(qy).min(0.0)
+}
+
+/// Signed distance from (px, py) to a closed polygon: the distance to the
+/// nearest edge, negative when an even-odd crossing test puts the point
+/// inside. Fewer than 3 points are treated as a polyline (never inside).
+fn polygon_distance(points: &[[f64; 2]], px: f64, py: f64) -> f64 {
+    let n = points.len();
+    if n == 0 {
+        return f64::INFINITY;
+    }
+    let mut nearest = f64::INFINITY;
+    let mut inside = false;
+    for i in 0..n {
+        let [x0, y0] = points[i];
+        let [x1, y1] = points[(i + 1) % n];
+        let (nx, ny) = nearest_point_on_segment(x0, y0, x1, y1, px, py);
+        nearest = nearest.min(((px - nx).powi(2) + (py - ny).powi(2)).sqrt());
+        if (y0 > py) != (y1 > py) && px < x0 + (py - y0) / (y1 - y0) * (x1 - x0) {
+            inside = !inside;
+        }
+    }
+    if inside && n >= 3 {
+        -nearest
+    } else {
+        nearest
+    }
+}
+
 /// Projects point (px, py) onto the line segment from (x0, y0) to (x1, y1),
 /// returning the nearest point on the segment.
 fn nearest_point_on_segment(x0: f64, y0: f64, x1: f64, y1: f64, px: f64, py: f64) -> (f64, f64) {
@@ -288,20 +1158,20 @@ fn nearest_point_on_segment(x0: f64, y0: f64, x1: f64, y1: f64, px: f64, py: f64
 
 impl FieldSource for PerlinField {
     fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
-        let sx = x * self.scale;
-        let sy = y * self.scale;
-        let dx = self.noise.get([sx, sy, time]) * self.strength;
-        let dy = self.noise.get([sx + 100.0, sy + 100.0, time]) * self.strength;
+        let (sx, sy) = (x * self.scale, y * self.scale);
+        let period = scaled_period(self.period, self.scale);
+        let dx = noise_at(&self.noise, sx, sy, 0.0, time, period) * self.strength;
+        let dy = noise_at(&self.noise, sx, sy, 100.0, time, period) * self.strength;
         (dx, dy)
     }
 }
 
 impl FieldSource for SimplexField {
     fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
-        let sx = x * self.scale;
-        let sy = y * self.scale;
-        let dx = self.noise.get([sx, sy, time]) * self.strength;
-        let dy = self.noise.get([sx + 100.0, sy + 100.0, time]) * self.strength;
+        let (sx, sy) = (x * self.scale, y * self.scale);
+        let period = scaled_period(self.period, self.scale);
+        let dx = noise_at(&self.noise, sx, sy, 0.0, time, period) * self.strength;
+        let dy = noise_at(&self.noise, sx, sy, 100.0, time, period) * self.strength;
         (dx, dy)
     }
 }
@@ -316,21 +1186,18 @@ impl FieldSource for CurlField {
         }
         // Curl of a 2D scalar field F:
         //   dx = dF/dy, dy = -dF/dx
-        let df_dy = (self.noise.get([sx, sy + eps, time]) - self.noise.get([sx, sy - eps, time]))
-            / (2.0 * eps);
-        let df_dx = (self.noise.get([sx + eps, sy, time]) - self.noise.get([sx - eps, sy, time]))
-            / (2.0 * eps);
+        let period = scaled_period(self.period, self.scale);
+        let n = |px: f64, py: f64| noise_at(&self.noise, px, py, 0.0, time, period);
+        let df_dy = (n(sx, sy + eps) - n(sx, sy - eps)) / (2.0 * eps);
+        let df_dx = (n(sx + eps, sy) - n(sx - eps, sy)) / (2.0 * eps);
         (df_dy * self.strength, -df_dx * self.strength)
     }
 }
 
 impl FieldSource for WorleyField {
     fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
-        let sx = x * self.scale;
-        let sy = y * self.scale;
-        let dx = self.noise_x.get([sx, sy, time]) * self.strength;
-        let dy = self.noise_y.get([sx, sy, time]) * self.strength;
-        (dx, dy)
+        let (_, gx, gy) = self.evaluate(x, y, time);
+        (gx * self.strength, gy * self.strength)
     }
 }
 
@@ -340,9 +1207,10 @@ impl FieldSource for TurbulenceField {
             (0..self.octaves).fold((0.0, 0.0, 1.0, 1.0), |(dx, dy, amp, freq), _| {
                 let sx = x * self.scale * freq;
                 let sy = y * self.scale * freq;
+                let period = scaled_period(self.period, self.scale * freq);
                 (
-                    dx + self.noise.get([sx, sy, time]) * amp,
-                    dy + self.noise.get([sx + 100.0, sy + 100.0, time]) * amp,
+                    dx + noise_at(&self.noise, sx, sy, 0.0, time, period) * amp,
+                    dy + noise_at(&self.noise, sx, sy, 100.0, time, period) * amp,
                     amp * self.persistence,
                     freq * self.lacunarity,
                 )
@@ -351,6 +1219,28 @@ impl FieldSource for TurbulenceField {
     }
 }
 
+impl FieldSource for FbmField {
+    fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
+        let (sx, sy) = (x * self.scale, y * self.scale);
+        (
+            self.fbm(sx, sy, 0.0, time) * self.strength,
+            self.fbm(sx, sy, 100.0, time) * self.strength,
+        )
+    }
+}
+
+impl FieldSource for RidgedField {
+    fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
+        let (sx, sy) = (x * self.scale, y * self.scale);
+        let eps = RIDGED_CURL_STEP;
+        let df_dy =
+            (self.ridged(sx, sy + eps, time) - self.ridged(sx, sy - eps, time)) / (2.0 * eps);
+        let df_dx =
+            (self.ridged(sx + eps, sy, time) - self.ridged(sx - eps, sy, time)) / (2.0 * eps);
+        (df_dy * self.strength, -df_dx * self.strength)
+    }
+}
+
 impl FieldSource for PointAttractor {
     fn sample(&self, x: f64, y: f64, _time: f64) -> (f64, f64) {
         attract_toward(self.x, self.y, x, y, self.strength, self.radius)
@@ -419,7 +1309,7 @@ impl FieldSource for Vortex {
             return (0.0, 0.0);
         }
         // Gaussian falloff
-        let falloff = (-dist_sq / (2.0 * self.radius * self.radius)).exp();
+        let falloff = math::exp(-dist_sq / (2.0 * self.radius * self.radius));
         // Perpendicular direction (counter-clockwise)
         let perp_x = -ry / dist;
         let perp_y = rx / dist;
@@ -430,13 +1320,583 @@ impl FieldSource for Vortex {
     }
 }
 
-impl FieldSource for CompositeField {
-    fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
-        self.sources.iter().fold((0.0, 0.0), |(ax, ay), source| {
-            let (sx, sy) = source.sample(x, y, time);
-            (ax + sx, ay + sy)
-        })
-    }
+impl FieldSource for SdfField {
+    fn sample(&self, x: f64, y: f64, _time: f64) -> (f64, f64) {
+        if self.radius.abs() < SINGULARITY_EPS {
+            return (0.0, 0.0);
+        }
+        let d = self.distance(x, y);
+        if !d.is_finite() {
+            return (0.0, 0.0);
+        }
+        // Outward normal from central differences
+        let h = SDF_GRADIENT_STEP;
+        let gx = self.distance(x + h, y) - self.distance(x - h, y);
+        let gy = self.distance(x, y + h) - self.distance(x, y - h);
+        let len = (gx * gx + gy * gy).sqrt();
+        if len < SINGULARITY_EPS {
+            return (0.0, 0.0);
+        }
+        let (nx, ny) = (gx / len, gy / len);
+        let magnitude = self.strength / (1.0 + d.abs() / self.radius);
+        // Toward the boundary is against the normal outside, along it inside
+        let toward = if d > 0.0 {
+            -1.0
+        } else if d < 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        match self.mode {
+            SdfMode::Attract => (nx * toward * magnitude, ny * toward * magnitude),
+            SdfMode::Repel => (-nx * toward * magnitude, -ny * toward * magnitude),
+            SdfMode::Flow => (-ny * magnitude, nx * magnitude),
+        }
+    }
+}
+
+impl FieldSource for CompositeField {
+    fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
+        let weighted = |(source, weight): &(Box<dyn FieldSource>, f64)| {
+            let (sx, sy) = source.sample(x, y, time);
+            (sx * weight, sy * weight)
+        };
+        match self.mode {
+            CombineMode::Add => self
+                .sources
+                .iter()
+                .map(weighted)
+                .fold((0.0, 0.0), |(ax, ay), (sx, sy)| (ax + sx, ay + sy)),
+            CombineMode::MaxMagnitude => {
+                self.sources
+                    .iter()
+                    .map(weighted)
+                    .fold((0.0, 0.0), |(ax, ay), (sx, sy)| {
+                        if sx * sx + sy * sy > ax * ax + ay * ay {
+                            (sx, sy)
+                        } else {
+                            (ax, ay)
+                        }
+                    })
+            }
+            CombineMode::Multiply => self
+                .sources
+                .iter()
+                .map(weighted)
+                .reduce(|(ax, ay), (sx, sy)| (ax * sx, ay * sy))
+                .unwrap_or((0.0, 0.0)),
+            CombineMode::Lerp => {
+                let Some(last) = self.sources.len().checked_sub(1) else {
+                    return (0.0, 0.0);
+                };
+                let position = self.mask_value(x, y) * last as f64;
+                let i = (position.floor() as usize).min(last.saturating_sub(1));
+                let t = position - i as f64;
+                let (ax, ay) = weighted(&self.sources[i]);
+                if t <= 0.0 || last == 0 {
+                    return (ax, ay);
+                }
+                let (bx, by) = weighted(&self.sources[i + 1]);
+                (ax + (bx - ax) * t, ay + (by - ay) * t)
+            }
+        }
+    }
+}
+
+impl FieldSource for MaskedField {
+    fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
+        let m = self.mask_value(x, y);
+        if m <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let (dx, dy) = self.inner.sample(x, y, time);
+        (dx * m, dy * m)
+    }
+}
+
+impl FieldSource for WarpedField {
+    fn sample(&self, x: f64, y: f64, time: f64) -> (f64, f64) {
+        let (wx, wy) = self.warp.sample(x, y, time);
+        self.inner
+            .sample(x + self.amount * wx, y + self.amount * wy, time)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ScalarSource implementations
+// ---------------------------------------------------------------------------
+
+impl ScalarSource for PerlinField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        let period = scaled_period(self.period, self.scale);
+        noise_at(
+            &self.noise,
+            x * self.scale,
+            y * self.scale,
+            0.0,
+            time,
+            period,
+        ) * self.strength
+    }
+}
+
+impl ScalarSource for SimplexField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        let period = scaled_period(self.period, self.scale);
+        noise_at(
+            &self.noise,
+            x * self.scale,
+            y * self.scale,
+            0.0,
+            time,
+            period,
+        ) * self.strength
+    }
+}
+
+/// The scalar potential whose curl is the flow.
+impl ScalarSource for CurlField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        let period = scaled_period(self.period, self.scale);
+        noise_at(
+            &self.noise,
+            x * self.scale,
+            y * self.scale,
+            0.0,
+            time,
+            period,
+        ) * self.strength
+    }
+}
+
+impl ScalarSource for WorleyField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        self.evaluate(x, y, time).0 * self.strength
+    }
+}
+
+impl ScalarSource for TurbulenceField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        let (total, _, _) = (0..self.octaves).fold((0.0, 1.0, 1.0), |(total, amp, freq), _| {
+            let (sx, sy) = (x * self.scale * freq, y * self.scale * freq);
+            let period = scaled_period(self.period, self.scale * freq);
+            (
+                total + noise_at(&self.noise, sx, sy, 0.0, time, period) * amp,
+                amp * self.persistence,
+                freq * self.lacunarity,
+            )
+        });
+        total * self.strength
+    }
+}
+
+impl ScalarSource for FbmField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        self.fbm(x * self.scale, y * self.scale, 0.0, time) * self.strength
+    }
+}
+
+impl ScalarSource for RidgedField {
+    fn value(&self, x: f64, y: f64, time: f64) -> f64 {
+        self.ridged(x * self.scale, y * self.scale, time) * self.strength
+    }
+}
+
+impl ScalarSource for SdfField {
+    fn value(&self, x: f64, y: f64, _time: f64) -> f64 {
+        self.distance(x, y)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Declarative descriptions
+// ---------------------------------------------------------------------------
+
+/// A serializable description of any field source, including composites.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "perlin", "scale": 0.02}`.
+/// Noise kinds default to scale 1, strength 1, and seed 0; Worley to F1
+/// Euclidean; turbulence to 4 octaves with persistence 0.5 and lacunarity
+/// 2; fBm and ridged to 5 octaves with lacunarity 2 and gain 0.5; SDF
+/// sources to strength 1, radius 1, and attraction. Noise kinds take an
+/// optional `"period": [width, height]` to tile (see `with_period`).
+/// Attractor and vortex fields are required.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FieldSourceSpec {
+    /// A [`PerlinField`].
+    Perlin {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// A [`SimplexField`].
+    Simplex {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// A [`CurlField`].
+    Curl {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// A [`WorleyField`].
+    Worley {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default)]
+        mode: WorleyMode,
+        #[serde(default)]
+        metric: DistanceMetric,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// A [`TurbulenceField`].
+    Turbulence {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+        #[serde(default = "default_persistence")]
+        persistence: f64,
+        #[serde(default = "default_lacunarity")]
+        lacunarity: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// An [`FbmField`].
+    Fbm {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default = "default_fractal_octaves")]
+        octaves: u32,
+        #[serde(default = "default_lacunarity")]
+        lacunarity: f64,
+        #[serde(default = "default_persistence")]
+        gain: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// A [`RidgedField`].
+    Ridged {
+        #[serde(default = "default_one")]
+        scale: f64,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default)]
+        seed: u32,
+        #[serde(default = "default_fractal_octaves")]
+        octaves: u32,
+        #[serde(default = "default_lacunarity")]
+        lacunarity: f64,
+        #[serde(default = "default_persistence")]
+        gain: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        period: Option<[f64; 2]>,
+    },
+    /// A [`PointAttractor`].
+    PointAttractor(PointAttractor),
+    /// A [`PointRepulsor`].
+    PointRepulsor(PointRepulsor),
+    /// A [`LineAttractor`].
+    LineAttractor(LineAttractor),
+    /// An [`OrbitalAttractor`].
+    OrbitalAttractor(OrbitalAttractor),
+    /// A [`GravityWell`].
+    GravityWell(GravityWell),
+    /// A [`Vortex`].
+    Vortex(Vortex),
+    /// An [`SdfField`].
+    Sdf {
+        shape: SdfShape,
+        #[serde(default = "default_one")]
+        strength: f64,
+        #[serde(default = "default_one")]
+        radius: f64,
+        #[serde(default)]
+        mode: SdfMode,
+    },
+    /// A [`WarpedField`].
+    Warped {
+        inner: Box<FieldSourceSpec>,
+        warp: Box<FieldSourceSpec>,
+        #[serde(default = "default_one")]
+        amount: f64,
+    },
+    /// A [`MaskedField`]; `invert` applies the source outside the mask.
+    Masked {
+        source: Box<FieldSourceSpec>,
+        mask: RegionMask,
+        #[serde(default)]
+        invert: bool,
+    },
+    /// A [`CompositeField`] combining the listed sources; missing weights
+    /// are 1.
+    Composite {
+        sources: Vec<FieldSourceSpec>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        weights: Vec<f64>,
+        #[serde(default)]
+        mode: CombineMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mask: Option<SdfMask>,
+    },
+}
+
+fn default_one() -> f64 {
+    1.0
+}
+
+fn default_octaves() -> u32 {
+    4
+}
+
+fn default_fractal_octaves() -> u32 {
+    5
+}
+
+fn default_persistence() -> f64 {
+    0.5
+}
+
+fn default_lacunarity() -> f64 {
+    2.0
+}
+
+/// Applies a spec's optional `[width, height]` period with `with_period`.
+fn tiled<S>(source: S, period: &Option<[f64; 2]>, with_period: fn(S, f64, f64) -> S) -> S {
+    match period {
+        Some([width, height]) => with_period(source, *width, *height),
+        None => source,
+    }
+}
+
+impl FieldSourceSpec {
+    /// Builds the described source.
+    pub fn build(&self) -> Box<dyn FieldSource> {
+        match self.construct() {
+            Built::Dual(source) => source,
+            Built::Vector(source) => source,
+        }
+    }
+
+    /// Builds the described source as a [`ScalarSource`], for the noise and
+    /// SDF kinds; `None` for kinds that only produce vectors.
+    pub fn build_scalar(&self) -> Option<Box<dyn ScalarSource>> {
+        self.build_dual()
+            .map(|source| source as Box<dyn ScalarSource>)
+    }
+
+    /// Builds a noise or SDF kind, which is both kinds of source.
+    fn build_dual(&self) -> Option<Box<dyn DualSource>> {
+        match self.construct() {
+            Built::Dual(source) => Some(source),
+            Built::Vector(_) => None,
+        }
+    }
+
+    /// Builds the described source, keeping noise and SDF kinds usable as
+    /// scalar sources too.
+    fn construct(&self) -> Built {
+        match self {
+            FieldSourceSpec::Perlin {
+                scale,
+                strength,
+                seed,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                PerlinField::new(*scale, *strength, *seed),
+                period,
+                PerlinField::with_period,
+            ))),
+            FieldSourceSpec::Simplex {
+                scale,
+                strength,
+                seed,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                SimplexField::new(*scale, *strength, *seed),
+                period,
+                SimplexField::with_period,
+            ))),
+            FieldSourceSpec::Curl {
+                scale,
+                strength,
+                seed,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                CurlField::new(*scale, *strength, *seed),
+                period,
+                CurlField::with_period,
+            ))),
+            FieldSourceSpec::Worley {
+                scale,
+                strength,
+                seed,
+                mode,
+                metric,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                WorleyField::new(*scale, *strength, *seed)
+                    .with_mode(*mode)
+                    .with_metric(*metric),
+                period,
+                WorleyField::with_period,
+            ))),
+            FieldSourceSpec::Turbulence {
+                scale,
+                strength,
+                seed,
+                octaves,
+                persistence,
+                lacunarity,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                TurbulenceField::new(
+                    *scale,
+                    *strength,
+                    *seed,
+                    *octaves,
+                    *persistence,
+                    *lacunarity,
+                ),
+                period,
+                TurbulenceField::with_period,
+            ))),
+            FieldSourceSpec::Fbm {
+                scale,
+                strength,
+                seed,
+                octaves,
+                lacunarity,
+                gain,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                FbmField::new(*scale, *strength, *seed)
+                    .with_octaves(*octaves)
+                    .with_lacunarity(*lacunarity)
+                    .with_gain(*gain),
+                period,
+                FbmField::with_period,
+            ))),
+            FieldSourceSpec::Ridged {
+                scale,
+                strength,
+                seed,
+                octaves,
+                lacunarity,
+                gain,
+                period,
+            } => Built::Dual(Box::new(tiled(
+                RidgedField::new(*scale, *strength, *seed)
+                    .with_octaves(*octaves)
+                    .with_lacunarity(*lacunarity)
+                    .with_gain(*gain),
+                period,
+                RidgedField::with_period,
+            ))),
+            FieldSourceSpec::Sdf {
+                shape,
+                strength,
+                radius,
+                mode,
+            } => Built::Dual(Box::new(
+                SdfField::new(shape.clone(), *strength, *radius).with_mode(*mode),
+            )),
+            FieldSourceSpec::PointAttractor(source) => Built::Vector(Box::new(source.clone())),
+            FieldSourceSpec::PointRepulsor(source) => Built::Vector(Box::new(source.clone())),
+            FieldSourceSpec::LineAttractor(source) => Built::Vector(Box::new(source.clone())),
+            FieldSourceSpec::OrbitalAttractor(source) => Built::Vector(Box::new(source.clone())),
+            FieldSourceSpec::GravityWell(source) => Built::Vector(Box::new(source.clone())),
+            FieldSourceSpec::Vortex(source) => Built::Vector(Box::new(source.clone())),
+            FieldSourceSpec::Warped {
+                inner,
+                warp,
+                amount,
+            } => Built::Vector(Box::new(
+                WarpedField::new(inner.build(), warp.build()).with_amount(*amount),
+            )),
+            FieldSourceSpec::Masked {
+                source,
+                mask,
+                invert,
+            } => {
+                let masked = MaskedField::new(source.build(), mask.clone());
+                Built::Vector(Box::new(if *invert { masked.inverted() } else { masked }))
+            }
+            FieldSourceSpec::Composite {
+                sources,
+                weights,
+                mode,
+                mask,
+            } => {
+                let composite = sources.iter().enumerate().fold(
+                    CompositeField::new().with_mode(*mode),
+                    |composite, (i, spec)| {
+                        composite.add_weighted(spec.build(), weights.get(i).copied().unwrap_or(1.0))
+                    },
+                );
+                Built::Vector(Box::new(match mask {
+                    Some(mask) => composite.with_mask(mask.clone()),
+                    None => composite,
+                }))
+            }
+        }
+    }
+}
+
+/// A built source, as the kind of source its spec describes.
+enum Built {
+    /// Noise and SDF kinds.
+    Dual(Box<dyn DualSource>),
+    /// Kinds that only produce vectors.
+    Vector(Box<dyn FieldSource>),
+}
+
+/// A source with both vector and scalar values.
+trait DualSource: FieldSource + ScalarSource {}
+
+impl<T: FieldSource + ScalarSource> DualSource for T {}
+
+/// Parses a [`FieldSourceSpec`] from JSON and builds it.
+///
+/// # Errors
+///
+/// Returns `EngineError::InvalidFieldSource` if `value` does not describe a
+/// field source (unknown `kind`, missing or mistyped fields).
+pub fn from_json(value: &serde_json::Value) -> Result<Box<dyn FieldSource>, EngineError> {
+    FieldSourceSpec::deserialize(value)
+        .map(|spec| spec.build())
+        .map_err(|e| EngineError::InvalidFieldSource(e.to_string()))
 }
 
 // ---------------------------------------------------------------------------
@@ -682,6 +2142,238 @@ mod tests {
         );
     }
 
+    // =======================================================================
+    // Worley tests
+    // =======================================================================
+
+    #[test]
+    fn worley_f1_vanishes_at_feature_points_and_pushes_away() {
+        let field = WorleyField::new(1.0, 1.0, 42);
+        for (cx, cy) in [(0, 0), (3, -2), (-5, 7)] {
+            let (fx, fy) = field.feature_point(cx, cy, None, 0.0);
+            assert!((0.05..=0.95).contains(&(fx - cx as f64)));
+            assert!((0.05..=0.95).contains(&(fy - cy as f64)));
+            assert!(field.value(fx, fy, 0.0) < 1e-12);
+            // Just right of the point, F1 grows to the right
+            let (dx, dy) = field.sample(fx + 0.01, fy, 0.0);
+            assert!((dx - 1.0).abs() < 1e-9 && dy.abs() < 1e-9, "({dx}, {dy})");
+        }
+    }
+
+    #[test]
+    fn worley_modes_order_their_distances() {
+        let f1 = WorleyField::new(0.7, 1.0, 9);
+        let f2 = WorleyField::new(0.7, 1.0, 9).with_mode(WorleyMode::F2);
+        let crackle = WorleyField::new(0.7, 1.0, 9).with_mode(WorleyMode::F2MinusF1);
+        for i in 0..200 {
+            let (x, y) = (i as f64 * 0.173, i as f64 * 0.091 - 4.0);
+            let (a, b) = (f1.value(x, y, 0.3), f2.value(x, y, 0.3));
+            assert!(a <= b && a < 1.3, "F1 {a} vs F2 {b}");
+            assert!((crackle.value(x, y, 0.3) - (b - a)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn worley_metrics_bound_each_other() {
+        let with = |metric| WorleyField::new(1.0, 1.0, 5).with_metric(metric);
+        let (euclid, manhattan, chebyshev) = (
+            with(DistanceMetric::Euclidean),
+            with(DistanceMetric::Manhattan),
+            with(DistanceMetric::Chebyshev),
+        );
+        for i in 0..200 {
+            let (x, y) = (i as f64 * 0.131, i as f64 * 0.057);
+            let e = euclid.value(x, y, 0.0);
+            assert!(chebyshev.value(x, y, 0.0) <= e + 1e-12);
+            assert!(e <= manhattan.value(x, y, 0.0) + 1e-12);
+        }
+    }
+
+    #[test]
+    fn worley_gradient_matches_finite_differences() {
+        let field = WorleyField::new(1.0, 1.0, 3).with_mode(WorleyMode::F2);
+        let h = 1e-6;
+        let mut checked = 0;
+        for i in 0..50 {
+            let (x, y) = (0.37 + i as f64 * 0.211, 1.9 + i as f64 * 0.143);
+            let ddx = (field.value(x + h, y, 0.0) - field.value(x - h, y, 0.0)) / (2.0 * h);
+            let ddy = (field.value(x, y + h, 0.0) - field.value(x, y - h, 0.0)) / (2.0 * h);
+            let (dx, dy) = field.sample(x, y, 0.0);
+            // Skip the few points straddling a cell border
+            if (ddx - dx).abs() < 1e-4 && (ddy - dy).abs() < 1e-4 {
+                checked += 1;
+            }
+        }
+        assert!(checked >= 45, "{checked} of 50 gradients matched");
+    }
+
+    #[test]
+    fn worley_points_move_with_time_and_seed() {
+        let field = WorleyField::new(1.0, 1.0, 42);
+        assert_ne!(
+            field.feature_point(2, 2, None, 0.0),
+            field.feature_point(2, 2, None, 0.5)
+        );
+        assert_ne!(
+            field.feature_point(2, 2, None, 0.0),
+            WorleyField::new(1.0, 1.0, 43).feature_point(2, 2, None, 0.0)
+        );
+        assert_eq!(
+            field.sample(1.3, 2.7, 0.5),
+            WorleyField::new(1.0, 1.0, 42).sample(1.3, 2.7, 0.5)
+        );
+    }
+
+    /// Prints the Worley golden values for `worley_golden_values_seed_42`.
+    #[test]
+    #[ignore = "run once to capture golden bits, then pin in worley_golden_values_seed_42"]
+    fn worley_capture_golden_bits() {
+        let field = WorleyField::new(1.0, 1.0, 42);
+        let (dx, dy) = field.sample(1.3, 2.7, 0.5);
+        let crackle = field
+            .with_mode(WorleyMode::F2MinusF1)
+            .with_metric(DistanceMetric::Manhattan)
+            .value(1.3, 2.7, 0.5);
+        panic!(
+            "GOLDEN: sample = ({dx:#018x}, {dy:#018x}), crackle = {crackle:#018x}",
+            dx = dx.to_bits(),
+            dy = dy.to_bits(),
+            crackle = crackle.to_bits(),
+        );
+    }
+
+    #[test]
+    fn worley_golden_values_seed_42() {
+        // Pin: the feature point hashing. If this changes, all replay files
+        // using Worley fields are invalidated.
+        // To recapture: cargo test -p art-engine-core -- --ignored worley_capture_golden_bits --nocapture
+        const GOLDEN_DX: u64 = 0xbfb0_a437_bf49_ce13;
+        const GOLDEN_DY: u64 = 0x3fef_eeac_4a23_be04;
+        const GOLDEN_CRACKLE: u64 = 0x3fdc_5c7a_8bbd_3d64;
+        let field = WorleyField::new(1.0, 1.0, 42);
+        let (dx, dy) = field.sample(1.3, 2.7, 0.5);
+        let crackle = field
+            .with_mode(WorleyMode::F2MinusF1)
+            .with_metric(DistanceMetric::Manhattan)
+            .value(1.3, 2.7, 0.5);
+        assert_eq!(dx.to_bits(), GOLDEN_DX, "Worley dx changed: {dx}");
+        assert_eq!(dy.to_bits(), GOLDEN_DY, "Worley dy changed: {dy}");
+        assert_eq!(
+            crackle.to_bits(),
+            GOLDEN_CRACKLE,
+            "Worley F2-F1 changed: {crackle}"
+        );
+    }
+
+    // =======================================================================
+    // SDF tests
+    // =======================================================================
+
+    fn circle() -> SdfShape {
+        SdfShape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 2.0,
+        }
+    }
+
+    #[test]
+    fn sdf_distances_are_signed() {
+        let disc = SdfField::new(circle(), 1.0, 1.0);
+        assert_eq!(disc.distance(5.0, 0.0), 3.0);
+        assert_eq!(disc.distance(0.0, 0.0), -2.0);
+
+        let square = SdfField::new(
+            SdfShape::Box {
+                x: 1.0,
+                y: 1.0,
+                half_width: 1.0,
+                half_height: 1.0,
+            },
+            1.0,
+            1.0,
+        );
+        let polygon = SdfField::new(
+            SdfShape::Polygon {
+                points: vec![[0.0, 0.0], [0.0, 2.0], [2.0, 2.0], [2.0, 0.0]],
+            },
+            1.0,
+            1.0,
+        );
+        assert_eq!(square.distance(5.0, 1.0), 3.0);
+        for (x, y) in [(5.0, 4.0), (1.0, 1.5), (-0.5, 1.0), (3.0, 3.0), (1.9, 0.4)] {
+            let (a, b) = (square.distance(x, y), polygon.distance(x, y));
+            assert!((a - b).abs() < 1e-12, "({x}, {y}): box {a} vs polygon {b}");
+        }
+    }
+
+    #[test]
+    fn sdf_text_covers_the_lit_font_pixels() {
+        let text = SdfField::new(
+            SdfShape::Text {
+                text: "-".into(),
+                x: 10.0,
+                y: 20.0,
+                size: 2.0,
+            },
+            1.0,
+            1.0,
+        );
+        // The dash is font row 3, columns 0-4: y in [26, 28], x in [10, 20]
+        assert_eq!(text.distance(15.0, 27.0), -1.0);
+        assert_eq!(text.distance(15.0, 30.0), 2.0);
+        assert_eq!(text.distance(24.0, 27.0), 4.0);
+        let empty = SdfField::new(
+            SdfShape::Text {
+                text: " ".into(),
+                x: 0.0,
+                y: 0.0,
+                size: 1.0,
+            },
+            1.0,
+            1.0,
+        );
+        assert_eq!(empty.sample(1.0, 1.0, 0.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn sdf_attraction_collects_on_the_boundary() {
+        let field = SdfField::new(circle(), 1.0, 1.0);
+        let (dx, dy) = field.sample(5.0, 0.0, 0.0);
+        assert!((dx + 0.25).abs() < 1e-6 && dy.abs() < 1e-6, "({dx}, {dy})");
+        let (dx, _) = field.sample(1.0, 0.0, 0.0);
+        assert!((dx - 0.5).abs() < 1e-6, "inside pushes outward: {dx}");
+
+        let repel = SdfField::new(circle(), 1.0, 1.0).with_mode(SdfMode::Repel);
+        assert!(repel.sample(5.0, 0.0, 0.0).0 > 0.0);
+    }
+
+    #[test]
+    fn sdf_flow_runs_along_the_outline() {
+        let field = SdfField::new(circle(), 2.0, 1.0).with_mode(SdfMode::Flow);
+        let (dx, dy) = field.sample(2.0, 0.0, 0.0);
+        // Tangent to the circle, counter-clockwise, at full strength
+        assert!(dx.abs() < 1e-6 && (dy - 2.0).abs() < 1e-6, "({dx}, {dy})");
+        let (dx, dy) = field.sample(0.0, 4.0, 0.0);
+        assert!(
+            (dx + 2.0 / 3.0).abs() < 1e-6 && dy.abs() < 1e-6,
+            "({dx}, {dy})"
+        );
+    }
+
+    #[test]
+    fn sdf_sources_compose_with_noise() {
+        let json = serde_json::json!({"kind": "composite", "sources": [
+            {"kind": "sdf", "shape": {"kind": "circle", "x": 0.0, "y": 0.0, "radius": 2.0}},
+            {"kind": "perlin", "scale": 0.3, "strength": 0.1, "seed": 4},
+        ]});
+        let source = from_json(&json).unwrap();
+        let sdf = SdfField::new(circle(), 1.0, 1.0).sample(3.0, 1.0, 0.2);
+        let noise = PerlinField::new(0.3, 0.1, 4).sample(3.0, 1.0, 0.2);
+        let (dx, dy) = source.sample(3.0, 1.0, 0.2);
+        assert!((dx - sdf.0 - noise.0).abs() < 1e-12 && (dy - sdf.1 - noise.1).abs() < 1e-12);
+    }
+
     // =======================================================================
     // Zero-radius / NaN guard tests
     // =======================================================================
@@ -738,6 +2430,7 @@ mod tests {
             scale: 0.0,
             strength: 1.0,
             eps: 0.001,
+            period: None,
         };
         let (dx, dy) = field.sample(1.0, 1.0, 0.0);
         assert!(
@@ -902,6 +2595,591 @@ mod tests {
         assert!(dy > 0.0, "nested composite should produce non-zero dy");
     }
 
+    /// A source with the same vector everywhere.
+    struct Constant(f64, f64);
+
+    impl FieldSource for Constant {
+        fn sample(&self, _x: f64, _y: f64, _time: f64) -> (f64, f64) {
+            (self.0, self.1)
+        }
+    }
+
+    fn constants(mode: CombineMode) -> CompositeField {
+        CompositeField::new()
+            .with_mode(mode)
+            .add(Box::new(Constant(1.0, -2.0)))
+            .add_weighted(Box::new(Constant(3.0, 0.5)), 2.0)
+    }
+
+    #[test]
+    fn composite_weights_scale_each_source() {
+        assert_eq!(
+            constants(CombineMode::Add).sample(0.0, 0.0, 0.0),
+            (7.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn composite_max_magnitude_and_multiply() {
+        assert_eq!(
+            constants(CombineMode::MaxMagnitude).sample(0.0, 0.0, 0.0),
+            (6.0, 1.0)
+        );
+        assert_eq!(
+            constants(CombineMode::Multiply).sample(0.0, 0.0, 0.0),
+            (6.0, -2.0)
+        );
+        for mode in [
+            CombineMode::MaxMagnitude,
+            CombineMode::Multiply,
+            CombineMode::Lerp,
+        ] {
+            let empty = CompositeField::new().with_mode(mode);
+            assert_eq!(empty.sample(1.0, 1.0, 0.0), (0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn composite_lerps_along_sources_by_mask() {
+        let mask = SdfMask {
+            shape: SdfShape::Circle {
+                x: 0.0,
+                y: 0.0,
+                radius: 2.0,
+            },
+            feather: 2.0,
+        };
+        let field = constants(CombineMode::Lerp)
+            .add(Box::new(Constant(0.0, 0.0)))
+            .with_mask(mask);
+        // Mask 0 far outside, 1 deep inside, 0.5 on the boundary
+        assert_eq!(field.mask_value(9.0, 0.0), 0.0);
+        assert_eq!(field.mask_value(0.0, 0.0), 1.0);
+        assert_eq!(field.mask_value(2.0, 0.0), 0.5);
+        assert_eq!(field.sample(9.0, 0.0, 0.0), (1.0, -2.0));
+        assert_eq!(field.sample(2.0, 0.0, 0.0), (6.0, 1.0));
+        assert_eq!(field.sample(0.0, 0.0, 0.0), (0.0, 0.0));
+        // A quarter of the way: halfway between the first two sources
+        assert_eq!(field.sample(2.5, 0.0, 0.0), (3.5, -0.5));
+        // Without a mask, the first source
+        assert_eq!(
+            constants(CombineMode::Lerp).sample(0.0, 0.0, 0.0),
+            (1.0, -2.0)
+        );
+    }
+
+    #[test]
+    fn json_composites_take_weights_modes_and_masks() {
+        let json = serde_json::json!({"kind": "composite", "mode": "lerp",
+        "weights": [2.0],
+        "mask": {"shape": {"kind": "box", "x": 0.0, "y": 0.0, "half_width": 1.0, "half_height": 1.0}},
+        "sources": [
+            {"kind": "gravity_well", "x": 5.0, "y": 0.0, "mass": 1.0},
+            {"kind": "gravity_well", "x": -5.0, "y": 0.0, "mass": 1.0},
+        ]});
+        let source = from_json(&json).unwrap();
+        // Outside the hard mask: the first well, doubled
+        let (dx, _) = source.sample(3.0, 0.0, 0.0);
+        assert!((dx - 0.5).abs() < 1e-12, "{dx}");
+        // Inside: the second well at weight 1
+        let (dx, _) = source.sample(0.0, 0.0, 0.0);
+        assert!((dx + 0.04).abs() < 1e-12, "{dx}");
+    }
+
+    // =======================================================================
+    // WarpedField tests
+    // =======================================================================
+
+    #[test]
+    fn warps_shift_the_inner_sample_point() {
+        let inner = || {
+            Box::new(PointAttractor {
+                x: 0.0,
+                y: 0.0,
+                strength: 1.0,
+                radius: 1.0,
+            })
+        };
+        let shifted = WarpedField::new(inner(), Box::new(Constant(2.0, -1.0))).with_amount(0.5);
+        assert_eq!(shifted.sample(3.0, 4.0, 0.0), inner().sample(4.0, 3.5, 0.0));
+
+        let still = WarpedField::new(inner(), Box::new(Constant(0.0, 0.0)));
+        assert_eq!(still.sample(3.0, 4.0, 0.0), inner().sample(3.0, 4.0, 0.0));
+    }
+
+    #[test]
+    fn warps_nest() {
+        let noise = || Box::new(PerlinField::new(0.5, 1.0, 11));
+        let once = WarpedField::new(noise(), noise());
+        let twice = WarpedField::new(noise(), Box::new(WarpedField::new(noise(), noise())));
+        let (x, y, t) = (1.7, -0.4, 0.3);
+        let (wx, wy) = once.sample(x, y, t);
+        assert_eq!(twice.sample(x, y, t), noise().sample(x + wx, y + wy, t));
+    }
+
+    #[test]
+    fn json_warps_match_their_rust_counterparts() {
+        let json = serde_json::json!({"kind": "warped", "amount": 4.0,
+            "inner": {"kind": "curl", "scale": 0.2, "seed": 1},
+            "warp": {"kind": "simplex", "scale": 0.1, "seed": 2}});
+        let source = from_json(&json).unwrap();
+        let expected = WarpedField::new(
+            Box::new(CurlField::new(0.2, 1.0, 1)),
+            Box::new(SimplexField::new(0.1, 1.0, 2)),
+        )
+        .with_amount(4.0);
+        assert_eq!(source.sample(3.3, 1.2, 0.5), expected.sample(3.3, 1.2, 0.5));
+    }
+
+    // =======================================================================
+    // Masked source tests
+    // =======================================================================
+
+    #[test]
+    fn field_masks_interpolate_between_cell_centers_and_wrap() {
+        let field = Field::from_data(2, 1, vec![0.0, 1.0]).unwrap();
+        let masked = MaskedField::new(Box::new(Constant(2.0, -4.0)), RegionMask::Field(field));
+        assert_eq!(masked.mask_value(0.5, 0.5), 0.0);
+        assert_eq!(masked.mask_value(1.5, 0.5), 1.0);
+        assert_eq!(masked.sample(1.0, 0.5, 0.0), (1.0, -2.0));
+        // Past the right edge the mask wraps back toward cell 0
+        assert!((masked.mask_value(2.25, 0.5) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sdf_masks_feather_across_the_boundary() {
+        let mask = RegionMask::Sdf {
+            shape: circle(),
+            feather: 2.0,
+        };
+        let masked = MaskedField::new(Box::new(Constant(1.0, 1.0)), mask);
+        // circle() is centered at the origin with radius 2
+        assert_eq!(masked.sample(0.0, 0.0, 0.0), (1.0, 1.0));
+        assert_eq!(masked.mask_value(2.0, 0.0), 0.5);
+        assert_eq!(masked.sample(0.0, 4.0, 0.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn radial_masks_ease_from_inner_to_outer() {
+        let radial = |inner, outer| RegionMask::Radial {
+            x: 0.0,
+            y: 0.0,
+            inner,
+            outer,
+        };
+        let soft = MaskedField::new(Box::new(Constant(1.0, 0.0)), radial(2.0, 6.0));
+        assert_eq!(soft.mask_value(1.0, 1.0), 1.0);
+        assert_eq!(soft.mask_value(4.0, 0.0), 0.5);
+        assert!(soft.mask_value(5.0, 0.0) < soft.mask_value(3.0, 0.0));
+        assert_eq!(soft.mask_value(0.0, -7.0), 0.0);
+        let hard = MaskedField::new(Box::new(Constant(1.0, 0.0)), radial(3.0, 3.0));
+        assert_eq!(
+            (hard.mask_value(3.0, 0.0), hard.mask_value(3.1, 0.0)),
+            (1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn inverted_masks_split_the_canvas_between_regimes() {
+        let mask = RegionMask::Radial {
+            x: 0.0,
+            y: 0.0,
+            inner: 5.0,
+            outer: 10.0,
+        };
+        let regimes = CompositeField::new()
+            .add(Box::new(MaskedField::new(
+                Box::new(Constant(1.0, 0.0)),
+                mask.clone(),
+            )))
+            .add(Box::new(
+                MaskedField::new(Box::new(Constant(0.0, 1.0)), mask).inverted(),
+            ));
+        assert_eq!(regimes.sample(1.0, 0.0, 0.0), (1.0, 0.0));
+        assert_eq!(regimes.sample(20.0, 0.0, 0.0), (0.0, 1.0));
+        let (dx, dy) = regimes.sample(7.5, 0.0, 0.0);
+        assert!((dx + dy - 1.0).abs() < 1e-12, "({dx}, {dy})");
+    }
+
+    #[test]
+    fn json_masks_match_their_rust_counterparts() {
+        let json = serde_json::json!({"kind": "masked", "invert": true,
+            "source": {"kind": "curl", "scale": 0.2, "seed": 1},
+            "mask": {"kind": "sdf", "shape": {"kind": "box", "x": 4.0, "y": 4.0,
+                "half_width": 2.0, "half_height": 1.0}, "feather": 1.0}});
+        let source = from_json(&json).unwrap();
+        let expected = MaskedField::new(
+            Box::new(CurlField::new(0.2, 1.0, 1)),
+            RegionMask::Sdf {
+                shape: SdfShape::Box {
+                    x: 4.0,
+                    y: 4.0,
+                    half_width: 2.0,
+                    half_height: 1.0,
+                },
+                feather: 1.0,
+            },
+        )
+        .inverted();
+        for (x, y) in [(4.0, 4.0), (6.0, 4.5), (0.5, 9.0)] {
+            assert_eq!(source.sample(x, y, 0.1), expected.sample(x, y, 0.1));
+        }
+    }
+
+    // =======================================================================
+    // Fractal noise tests
+    // =======================================================================
+
+    #[test]
+    fn single_octave_fbm_is_plain_perlin() {
+        let fbm = FbmField::new(0.4, 2.0, 21).with_octaves(1);
+        let perlin = PerlinField::new(0.4, 2.0, 21);
+        for (x, y, t) in [(1.3, 2.7, 0.5), (-4.1, 0.2, 0.0)] {
+            assert_eq!(fbm.sample(x, y, t), perlin.sample(x, y, t));
+        }
+    }
+
+    #[test]
+    fn fractal_values_stay_normalized() {
+        let fbm = FbmField::new(0.2, 3.0, 5).with_octaves(8).with_gain(0.7);
+        let ridged = RidgedField::new(0.2, 3.0, 5).with_octaves(8);
+        for i in 0..300 {
+            let (x, y) = (i as f64 * 0.37, i as f64 * 0.19 - 20.0);
+            assert!(fbm.value(x, y, 0.1).abs() <= 3.0);
+            assert!((0.0..=3.0).contains(&ridged.value(x, y, 0.1)));
+        }
+        // More octaves add detail without changing the range
+        assert_ne!(
+            fbm.value(1.1, 2.2, 0.0),
+            FbmField::new(0.2, 3.0, 5)
+                .with_octaves(2)
+                .value(1.1, 2.2, 0.0)
+        );
+    }
+
+    #[test]
+    fn ridges_peak_where_the_noise_crosses_zero() {
+        let ridged = RidgedField::new(1.0, 1.0, 42).with_octaves(1);
+        let noise = Perlin::new(42);
+        for i in 0..100 {
+            let (x, y) = (i as f64 * 0.113 + 0.05, i as f64 * 0.071 + 0.05);
+            let n = noise.get([x, y, 0.0]);
+            assert!((ridged.value(x, y, 0.0) - (1.0 - n.abs()).powi(2)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn ridged_flow_is_divergence_free() {
+        let field = RidgedField::new(0.5, 1.0, 9).with_octaves(3);
+        let h = 1e-3;
+        for (px, py) in [(1.0, 1.0), (2.5, 3.7), (0.1, 0.9)] {
+            let ddx =
+                (field.sample(px + h, py, 0.0).0 - field.sample(px - h, py, 0.0).0) / (2.0 * h);
+            let ddy =
+                (field.sample(px, py + h, 0.0).1 - field.sample(px, py - h, 0.0).1) / (2.0 * h);
+            assert!(
+                (ddx + ddy).abs() < 0.1,
+                "divergence at ({px}, {py}): {}",
+                ddx + ddy
+            );
+        }
+    }
+
+    #[test]
+    fn json_fractals_use_their_defaults() {
+        let json = serde_json::json!({"kind": "composite", "sources": [
+            {"kind": "fbm", "scale": 0.1, "seed": 3},
+            {"kind": "ridged", "octaves": 3, "gain": 0.6},
+        ]});
+        let source = from_json(&json).unwrap();
+        let expected = CompositeField::new()
+            .add(Box::new(FbmField::new(0.1, 1.0, 3)))
+            .add(Box::new(
+                RidgedField::new(1.0, 1.0, 0).with_octaves(3).with_gain(0.6),
+            ));
+        assert_eq!(source.sample(2.2, 3.3, 0.4), expected.sample(2.2, 3.3, 0.4));
+    }
+
+    // =======================================================================
+    // Periodic noise tests
+    // =======================================================================
+
+    fn periodic_sources(width: f64, height: f64) -> Vec<Box<dyn FieldSource>> {
+        vec![
+            Box::new(PerlinField::new(0.05, 1.0, 3).with_period(width, height)),
+            Box::new(SimplexField::new(0.05, 1.0, 3).with_period(width, height)),
+            Box::new(CurlField::new(0.05, 1.0, 3).with_period(width, height)),
+            Box::new(
+                WorleyField::new(0.07, 1.0, 3)
+                    .with_mode(WorleyMode::F2MinusF1)
+                    .with_period(width, height),
+            ),
+            Box::new(TurbulenceField::new(0.05, 1.0, 3, 3, 0.5, 2.0).with_period(width, height)),
+            Box::new(FbmField::new(0.05, 1.0, 3).with_period(width, height)),
+            Box::new(RidgedField::new(0.05, 1.0, 3).with_period(width, height)),
+        ]
+    }
+
+    #[test]
+    fn periodic_noise_repeats_every_period() {
+        let (w, h) = (64.0, 40.0);
+        for source in periodic_sources(w, h) {
+            for (x, y, t) in [(1.5, 2.5, 0.0), (33.3, 17.9, 0.7), (63.9, 0.1, 2.0)] {
+                let (dx, dy) = source.sample(x, y, t);
+                for (ox, oy) in [(w, 0.0), (0.0, h), (-w, 2.0 * h)] {
+                    let (wx, wy) = source.sample(x + ox, y + oy, t);
+                    assert!(
+                        (dx - wx).abs() < 1e-6 && (dy - wy).abs() < 1e-6,
+                        "({x}, {y}) + ({ox}, {oy}): ({dx}, {dy}) vs ({wx}, {wy})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn periodic_noise_is_seamless_across_field_edges() {
+        let source = FbmField::new(0.1, 1.0, 8).with_period(32.0, 32.0);
+        let field = rasterize(&source, 32, 32, 0.0).unwrap();
+        // The step across the wrapped edge is no bigger than steps inside
+        let largest_inner = (0..31)
+            .flat_map(|x| (0..32).map(move |y| (x, y)))
+            .map(|(x, y)| (field.get(x + 1, y) - field.get(x, y)).abs())
+            .fold(0.0, f64::max);
+        for y in 0..32 {
+            assert!((field.get(0, y) - field.get(31, y)).abs() <= largest_inner);
+        }
+    }
+
+    #[test]
+    fn periodic_noise_still_varies_and_differs_from_plane_noise() {
+        let tiled = PerlinField::new(0.1, 1.0, 4).with_period(50.0, 50.0);
+        let plane = PerlinField::new(0.1, 1.0, 4);
+        let (a, b) = (tiled.sample(10.3, 4.1, 0.0), tiled.sample(30.7, 22.2, 0.0));
+        assert_ne!(a, b);
+        assert_ne!(a.0, a.1, "channels must stay independent");
+        assert_ne!(a, plane.sample(10.3, 4.1, 0.0));
+        assert_ne!(tiled.sample(10.3, 4.1, 0.5), a, "tiles evolve with time");
+        // A non-positive period leaves the plane noise untouched
+        let off = PerlinField::new(0.1, 1.0, 4).with_period(0.0, 50.0);
+        assert_eq!(off.sample(10.3, 4.1, 0.0), plane.sample(10.3, 4.1, 0.0));
+    }
+
+    #[test]
+    fn json_periods_match_with_period() {
+        let json = serde_json::json!({"kind": "composite", "sources": [
+            {"kind": "curl", "scale": 0.1, "seed": 2, "period": [48.0, 24.0]},
+            {"kind": "worley", "scale": 0.2, "period": [48.0, 24.0]},
+        ]});
+        let source = from_json(&json).unwrap();
+        let expected = CompositeField::new()
+            .add(Box::new(
+                CurlField::new(0.1, 1.0, 2).with_period(48.0, 24.0),
+            ))
+            .add(Box::new(
+                WorleyField::new(0.2, 1.0, 0).with_period(48.0, 24.0),
+            ));
+        for (x, y) in [(1.0, 2.0), (47.5, 23.5)] {
+            assert_eq!(source.sample(x, y, 0.3), expected.sample(x, y, 0.3));
+        }
+    }
+
+    // =======================================================================
+    // ScalarSource tests
+    // =======================================================================
+
+    #[test]
+    fn noise_scalars_are_the_x_channel_of_their_vectors() {
+        let perlin = PerlinField::new(0.3, 2.0, 8);
+        let turbulence = TurbulenceField::new(0.3, 2.0, 8, 3, 0.5, 2.0);
+        for (x, y, t) in [(1.25, 3.5, 0.0), (-2.0, 0.75, 1.5)] {
+            assert_eq!(perlin.value(x, y, t), perlin.sample(x, y, t).0);
+            assert_eq!(turbulence.value(x, y, t), turbulence.sample(x, y, t).0);
+        }
+        let worley = WorleyField::new(1.0, 3.0, 8);
+        let unit = WorleyField::new(1.0, 1.0, 8);
+        assert_eq!(worley.value(0.4, 0.9, 0.0), 3.0 * unit.value(0.4, 0.9, 0.0));
+    }
+
+    #[test]
+    fn scalar_sources_rasterize_at_pixel_centers() {
+        let disc = SdfField::new(
+            SdfShape::Circle {
+                x: 2.0,
+                y: 1.0,
+                radius: 0.5,
+            },
+            1.0,
+            1.0,
+        );
+        let field = rasterize(&disc, 4, 3, 0.0).unwrap();
+        assert_eq!((field.width(), field.height()), (4, 3));
+        // Pixel (1, 0) is centered at (1.5, 0.5), on the circle
+        assert!((field.get(1, 0) - (0.5_f64.sqrt() - 0.5)).abs() < 1e-12);
+        assert!(field.get(3, 2) > field.get(2, 1));
+        assert!(rasterize(&disc, 0, 3, 0.0).is_err());
+    }
+
+    #[test]
+    fn vector_sources_rasterize_into_components() {
+        let vortex = Vortex {
+            x: 2.0,
+            y: 2.0,
+            strength: 1.0,
+            radius: 2.0,
+        };
+        let (dx, dy) = rasterize_vectors(&vortex, 4, 4, 0.0).unwrap();
+        for (x, y, v) in dx.iter() {
+            let expected = vortex.sample(x as f64 + 0.5, y as f64 + 0.5, 0.0);
+            assert_eq!((v, dy.get(x as isize, y as isize)), expected);
+        }
+        assert!(rasterize_vectors(&vortex, 4, 0, 0.0).is_err());
+    }
+
+    // =======================================================================
+    // Declarative description tests
+    // =======================================================================
+
+    #[test]
+    fn json_sources_match_their_rust_counterparts() {
+        let json = serde_json::json!({"kind": "composite", "sources": [
+            {"kind": "curl", "scale": 0.5, "strength": 2.0, "seed": 7},
+            {"kind": "vortex", "x": 1.0, "y": 2.0, "strength": 1.5, "radius": 3.0},
+            {"kind": "turbulence", "octaves": 2},
+        ]});
+        let source = from_json(&json).unwrap();
+        let expected = CompositeField::new()
+            .add(Box::new(CurlField::new(0.5, 2.0, 7)))
+            .add(Box::new(Vortex {
+                x: 1.0,
+                y: 2.0,
+                strength: 1.5,
+                radius: 3.0,
+            }))
+            .add(Box::new(TurbulenceField::new(1.0, 1.0, 0, 2, 0.5, 2.0)));
+        for (x, y, t) in [(0.3, 0.7, 0.0), (2.5, 1.0, 0.4), (-1.0, 4.0, 2.0)] {
+            assert_eq!(source.sample(x, y, t), expected.sample(x, y, t));
+        }
+    }
+
+    #[test]
+    fn noise_and_sdf_specs_build_scalar_sources() {
+        let spec: FieldSourceSpec =
+            serde_json::from_value(serde_json::json!({"kind": "fbm", "scale": 0.3, "seed": 4}))
+                .unwrap();
+        let scalar = spec.build_scalar().unwrap();
+        let expected = FbmField::new(0.3, 1.0, 4);
+        for (x, y) in [(0.5, 1.5), (7.25, -3.0)] {
+            assert_eq!(scalar.value(x, y, 0.0), expected.value(x, y, 0.0));
+        }
+        let vortex: FieldSourceSpec = serde_json::from_value(
+            serde_json::json!({"kind": "vortex", "x": 0.0, "y": 0.0, "strength": 1.0, "radius": 2.0}),
+        )
+        .unwrap();
+        assert!(vortex.build_scalar().is_none());
+        assert_eq!(
+            vortex.build().sample(1.0, 0.0, 0.0),
+            Vortex {
+                x: 0.0,
+                y: 0.0,
+                strength: 1.0,
+                radius: 2.0
+            }
+            .sample(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn every_kind_round_trips_through_json() {
+        let specs = vec![
+            FieldSourceSpec::Perlin {
+                scale: 0.1,
+                strength: 2.0,
+                seed: 1,
+                period: Some([64.0, 32.0]),
+            },
+            FieldSourceSpec::Simplex {
+                scale: 0.2,
+                strength: 1.0,
+                seed: 2,
+                period: None,
+            },
+            FieldSourceSpec::Worley {
+                scale: 0.3,
+                strength: 1.0,
+                seed: 3,
+                mode: WorleyMode::F2MinusF1,
+                metric: DistanceMetric::Chebyshev,
+                period: None,
+            },
+            FieldSourceSpec::PointRepulsor(PointRepulsor {
+                x: 1.0,
+                y: 1.0,
+                strength: 1.0,
+                radius: 2.0,
+            }),
+            FieldSourceSpec::LineAttractor(LineAttractor {
+                x0: 0.0,
+                y0: 0.0,
+                x1: 4.0,
+                y1: 0.0,
+                strength: 1.0,
+                radius: 1.0,
+            }),
+            FieldSourceSpec::Composite {
+                sources: vec![
+                    FieldSourceSpec::OrbitalAttractor(OrbitalAttractor {
+                        x: 0.0,
+                        y: 0.0,
+                        strength: 1.0,
+                        radius: 1.0,
+                    }),
+                    FieldSourceSpec::GravityWell(GravityWell {
+                        x: 2.0,
+                        y: 2.0,
+                        mass: -1.0,
+                    }),
+                    FieldSourceSpec::PointAttractor(PointAttractor {
+                        x: 5.0,
+                        y: 5.0,
+                        strength: 1.0,
+                        radius: 1.0,
+                    }),
+                ],
+                weights: vec![0.5, 2.0],
+                mode: CombineMode::Lerp,
+                mask: Some(SdfMask {
+                    shape: SdfShape::Box {
+                        x: 0.0,
+                        y: 0.0,
+                        half_width: 3.0,
+                        half_height: 1.0,
+                    },
+                    feather: 0.5,
+                }),
+            },
+        ];
+        let json = serde_json::to_string(&specs).unwrap();
+        assert!(json.contains(r#""kind":"gravity_well""#), "{json}");
+        let back: Vec<FieldSourceSpec> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, specs);
+    }
+
+    #[test]
+    fn invalid_json_sources_are_errors() {
+        for json in [
+            serde_json::json!({"kind": "whirlpool"}),
+            serde_json::json!({"kind": "vortex", "x": 1.0}),
+            serde_json::json!({"kind": "perlin", "seed": "seven"}),
+            serde_json::json!({"scale": 1.0}),
+        ] {
+            assert!(matches!(
+                from_json(&json),
+                Err(EngineError::InvalidFieldSource(_))
+            ));
+        }
+    }
+
     // =======================================================================
     // Property-based tests
     // =======================================================================
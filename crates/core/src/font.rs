@@ -0,0 +1,169 @@
+//! Tiny built-in 5x7 bitmap font for labeling images.
+//!
+//! Covers digits, letters (lowercase is drawn as uppercase) and the
+//! punctuation used in parameter labels; anything else is drawn as `?`.
+//! Glyphs advance [`ADVANCE`] pixels per character at scale 1 and are
+//! scaled by pixel replication, so text stays crisp at any size.
+
+use crate::color::Srgb;
+use crate::image::Image;
+
+/// Glyph width in pixels at scale 1.
+pub const GLYPH_WIDTH: usize = 5;
+/// Glyph height in pixels at scale 1.
+pub const GLYPH_HEIGHT: usize = 7;
+/// Horizontal distance between glyph origins at scale 1.
+pub const ADVANCE: usize = GLYPH_WIDTH + 1;
+
+/// Returns the glyph rows for `c`, top to bottom; bit 4 is the left column.
+#[rustfmt::skip]
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        ' ' => [0; GLYPH_HEIGHT],
+        '.' => [0, 0, 0, 0, 0, 0b01100, 0b01100],
+        ',' => [0, 0, 0, 0, 0b01100, 0b00100, 0b01000],
+        '-' => [0, 0, 0, 0b11111, 0, 0, 0],
+        '_' => [0, 0, 0, 0, 0, 0, 0b11111],
+        '=' => [0, 0, 0b11111, 0, 0b11111, 0, 0],
+        ':' => [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '+' => [0, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '/' => [0, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0],
+        '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0, 0b00100],
+    }
+}
+
+/// Returns the width in pixels of `text` drawn at `scale` (no trailing gap).
+pub fn text_width(text: &str, scale: usize) -> usize {
+    let n = text.chars().count();
+    (n * ADVANCE).saturating_sub(1) * scale
+}
+
+/// Returns the lit font pixels of `text` at scale 1, as `(x, y)` offsets
+/// from its top-left corner.
+pub fn text_pixels(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    text.chars().enumerate().flat_map(|(i, c)| {
+        glyph(c)
+            .into_iter()
+            .enumerate()
+            .flat_map(move |(row, bits)| {
+                (0..GLYPH_WIDTH)
+                    .filter(move |col| bits & (0b10000 >> col) != 0)
+                    .map(move |col| (i * ADVANCE + col, row))
+            })
+    })
+}
+
+/// Draws `text` with its top-left corner at `(x, y)` in an opaque color.
+///
+/// Each font pixel becomes a `scale x scale` block; pixels falling outside
+/// the image are clipped. A scale of 0 draws nothing.
+pub fn draw_text(image: &mut Image, x: usize, y: usize, text: &str, color: Srgb, scale: usize) {
+    let rgba = [color.r, color.g, color.b, 1.0];
+    for (px, py) in text_pixels(text) {
+        for (dy, dx) in (0..scale).flat_map(|dy| (0..scale).map(move |dx| (dy, dx))) {
+            // Out-of-bounds writes are clipped
+            let _ = image.set_pixel(x + px * scale + dx, y + py * scale + dy, rgba);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white() -> Srgb {
+        Srgb {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        }
+    }
+
+    fn lit(image: &Image) -> usize {
+        image.data().chunks(4).filter(|p| p[3] > 0.0).count()
+    }
+
+    #[test]
+    fn text_pixels_follow_the_glyph_rows() {
+        let pixels: Vec<_> = text_pixels("-1").collect();
+        // The dash's middle row, then the 1 starting one advance over
+        assert_eq!(&pixels[..5], &[(0, 3), (1, 3), (2, 3), (3, 3), (4, 3)]);
+        assert_eq!(pixels[5], (ADVANCE + 2, 0));
+        assert_eq!(text_pixels(" ").count(), 0);
+    }
+
+    #[test]
+    fn text_width_counts_advance_without_trailing_gap() {
+        assert_eq!(text_width("", 1), 0);
+        assert_eq!(text_width("A", 1), 5);
+        assert_eq!(text_width("AB", 2), 22);
+    }
+
+    #[test]
+    fn draws_glyph_pixels_at_scale() {
+        let mut image = Image::new(12, 16).unwrap();
+        draw_text(&mut image, 0, 0, "-", white(), 2);
+        // A 5-pixel bar, 2x2 blocks, on font row 3
+        assert_eq!(lit(&image), 5 * 4);
+        assert_eq!(image.pixel(0, 6).unwrap(), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(image.pixel(0, 5).unwrap()[3], 0.0);
+    }
+
+    #[test]
+    fn lowercase_matches_uppercase_and_unknown_is_question_mark() {
+        let draw = |text: &str| {
+            let mut image = Image::new(6, 7).unwrap();
+            draw_text(&mut image, 0, 0, text, white(), 1);
+            image
+        };
+        assert_eq!(draw("k"), draw("K"));
+        assert_eq!(draw("~"), draw("?"));
+        assert_ne!(draw("0"), draw("8"));
+    }
+
+    #[test]
+    fn text_is_clipped_at_the_edges() {
+        let mut image = Image::new(3, 3).unwrap();
+        draw_text(&mut image, 1, 1, "WWW", white(), 1);
+        assert!(lit(&image) > 0);
+    }
+}
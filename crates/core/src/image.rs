@@ -0,0 +1,285 @@
+//! RGBA raster image used as compositor input and output.
+//!
+//! An [`Image`] stores `width * height` pixels as four `f64` channels
+//! (sRGB-encoded R, G, B and straight alpha, all in [0, 1]) in row-major
+//! order. Unlike [`Field`](crate::field::Field), coordinates are not wrapped.
+
+use crate::color::Srgb;
+use crate::error::EngineError;
+
+/// An RGBA image with sRGB-encoded color and straight (non-premultiplied) alpha.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    data: Vec<f64>,
+}
+
+impl Image {
+    /// Creates a fully transparent image.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if either dimension is zero
+    /// or if `width * height * 4` overflows `usize`.
+    pub fn new(width: usize, height: usize) -> Result<Self, EngineError> {
+        let len = pixel_len(width, height)?;
+        Ok(Self {
+            width,
+            height,
+            data: vec![0.0; len],
+        })
+    }
+
+    /// Creates a fully transparent image the size of `other`.
+    ///
+    /// Infallible, since `other` already has valid dimensions.
+    pub fn blank_like(other: &Image) -> Self {
+        Self {
+            width: other.width,
+            height: other.height,
+            data: vec![0.0; other.data.len()],
+        }
+    }
+
+    /// Creates an image filled with a single color and alpha (clamped to [0, 1]).
+    ///
+    /// Returns `EngineError::InvalidDimensions` under the same conditions as [`Image::new`].
+    pub fn filled(
+        width: usize,
+        height: usize,
+        color: Srgb,
+        alpha: f64,
+    ) -> Result<Self, EngineError> {
+        let len = pixel_len(width, height)?;
+        let px = [
+            color.r.clamp(0.0, 1.0),
+            color.g.clamp(0.0, 1.0),
+            color.b.clamp(0.0, 1.0),
+            alpha.clamp(0.0, 1.0),
+        ];
+        Ok(Self {
+            width,
+            height,
+            data: px.iter().copied().cycle().take(len).collect(),
+        })
+    }
+
+    /// Creates an image from an RGBA8 buffer of length `width * height * 4`.
+    ///
+    /// Returns `EngineError::DimensionMismatch` if the buffer length is wrong.
+    pub fn from_rgba8(width: usize, height: usize, rgba: &[u8]) -> Result<Self, EngineError> {
+        let len = pixel_len(width, height)?;
+        if rgba.len() != len {
+            return Err(EngineError::DimensionMismatch {
+                lhs_w: width,
+                lhs_h: height,
+                rhs_w: rgba.len(),
+                rhs_h: 1,
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            data: rgba.iter().map(|&b| f64::from(b) / 255.0).collect(),
+        })
+    }
+
+    /// Image width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Image height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Read-only access to the row-major RGBA channel data.
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// Mutable access to the row-major RGBA channel data.
+    ///
+    /// Values written here are not clamped.
+    pub fn data_mut(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
+    /// Returns the `[r, g, b, a]` pixel at `(x, y)`, or `None` if out of bounds.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<[f64; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y * self.width + x) * 4;
+        Some([
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ])
+    }
+
+    /// Sets the pixel at `(x, y)`, clamping channels to [0, 1].
+    ///
+    /// Returns `EngineError::OutOfBounds` if the coordinates are outside the image.
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgba: [f64; 4]) -> Result<(), EngineError> {
+        if x >= self.width || y >= self.height {
+            return Err(EngineError::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        let i = (y * self.width + x) * 4;
+        self.data[i..i + 4]
+            .iter_mut()
+            .zip(rgba)
+            .for_each(|(d, v)| *d = v.clamp(0.0, 1.0));
+        Ok(())
+    }
+
+    /// Copies `source` into this image with its top-left corner at `(x, y)`,
+    /// replacing the covered pixels. Parts outside this image are clipped.
+    pub fn paste(&mut self, source: &Image, x: usize, y: usize) {
+        let w = source.width.min(self.width.saturating_sub(x));
+        (0..source.height.min(self.height.saturating_sub(y))).for_each(|row| {
+            let src = row * source.width * 4;
+            let dst = ((y + row) * self.width + x) * 4;
+            self.data[dst..dst + w * 4].copy_from_slice(&source.data[src..src + w * 4]);
+        });
+    }
+
+    /// Quantizes the image to an RGBA8 buffer. NaN channels map to 0.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .map(|&v| {
+                if v.is_nan() {
+                    0
+                } else {
+                    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+                }
+            })
+            .collect()
+    }
+}
+
+/// Validates dimensions and returns the channel count `width * height * 4`.
+fn pixel_len(width: usize, height: usize) -> Result<usize, EngineError> {
+    if width == 0 || height == 0 {
+        return Err(EngineError::InvalidDimensions);
+    }
+    width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(4))
+        .ok_or(EngineError::InvalidDimensions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_copies_and_clips() {
+        let mut target = Image::new(3, 2).unwrap();
+        let red = Srgb {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+        };
+        let source = Image::filled(2, 2, red, 0.5).unwrap();
+        target.paste(&source, 2, 1);
+        assert_eq!(target.pixel(2, 1).unwrap(), [1.0, 0.0, 0.0, 0.5]);
+        assert_eq!(target.pixel(1, 1).unwrap(), [0.0; 4]);
+        assert_eq!(target.pixel(2, 0).unwrap(), [0.0; 4]);
+        target.paste(&source, 5, 5);
+    }
+
+    fn red() -> Srgb {
+        Srgb {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+        }
+    }
+
+    // -- Construction --
+
+    #[test]
+    fn new_is_transparent() {
+        let img = Image::new(3, 2).unwrap();
+        assert_eq!(img.data().len(), 24);
+        assert!(img.data().iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn new_rejects_zero_and_overflowing_dimensions() {
+        assert!(matches!(
+            Image::new(0, 4),
+            Err(EngineError::InvalidDimensions)
+        ));
+        assert!(matches!(
+            Image::new(usize::MAX, 2),
+            Err(EngineError::InvalidDimensions)
+        ));
+    }
+
+    #[test]
+    fn blank_like_matches_size_and_is_transparent() {
+        let blank = Image::blank_like(&Image::filled(3, 2, red(), 1.0).unwrap());
+        assert_eq!(blank, Image::new(3, 2).unwrap());
+    }
+
+    #[test]
+    fn filled_sets_every_pixel_and_clamps_alpha() {
+        let img = Image::filled(2, 2, red(), 1.5).unwrap();
+        for y in 0..2 {
+            for x in 0..2 {
+                assert_eq!(img.pixel(x, y), Some([1.0, 0.0, 0.0, 1.0]));
+            }
+        }
+    }
+
+    #[test]
+    fn from_rgba8_round_trips_through_to_rgba8() {
+        let bytes = [0, 64, 128, 255, 255, 0, 10, 20];
+        let img = Image::from_rgba8(2, 1, &bytes).unwrap();
+        assert_eq!(img.to_rgba8(), bytes.to_vec());
+    }
+
+    #[test]
+    fn from_rgba8_rejects_wrong_length() {
+        assert!(matches!(
+            Image::from_rgba8(2, 2, &[0; 15]),
+            Err(EngineError::DimensionMismatch { .. })
+        ));
+    }
+
+    // -- Pixel access --
+
+    #[test]
+    fn pixel_out_of_bounds_is_none() {
+        let img = Image::new(2, 2).unwrap();
+        assert_eq!(img.pixel(2, 0), None);
+        assert_eq!(img.pixel(0, 2), None);
+    }
+
+    #[test]
+    fn set_pixel_clamps_and_rejects_out_of_bounds() {
+        let mut img = Image::new(2, 2).unwrap();
+        img.set_pixel(1, 1, [2.0, -1.0, 0.5, 1.0]).unwrap();
+        assert_eq!(img.pixel(1, 1), Some([1.0, 0.0, 0.5, 1.0]));
+        assert!(matches!(
+            img.set_pixel(5, 0, [0.0; 4]),
+            Err(EngineError::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn to_rgba8_maps_nan_to_zero() {
+        let mut img = Image::new(1, 1).unwrap();
+        img.data_mut()[0] = f64::NAN;
+        assert_eq!(img.to_rgba8()[0], 0);
+    }
+}
@@ -0,0 +1,552 @@
+//! Keyframed layer properties: values that change over frames.
+//!
+//! A [`Track`] is a list of [`Keyframe`]s sorted by frame. Sampling a track
+//! between two keys interpolates with the earlier key's [`Easing`]; before
+//! the first key or after the last, the nearest key's value holds. Frames
+//! are whatever unit the driver advances (animation frames or simulation
+//! steps) and may be fractional.
+//!
+//! A [`LayerAnimation`] groups the tracks a layer can carry (opacity, blend
+//! mode, and transform translate/rotation/scale) and writes the sampled
+//! values into the layer, so front ends render frame `n` with
+//! [`Canvas::at_frame`](crate::canvas::Canvas::at_frame).
+//!
+//! A [`ParamTimeline`] keys engine parameters the same way, for front ends
+//! that call [`Engine::set_params`](crate::Engine::set_params) as a
+//! simulation runs, plus the palette the frames are colored with.
+//!
+//! ```json
+//! "animation": {
+//!   "opacity": [{ "frame": 0, "value": 0.0, "easing": "ease_in" }, { "frame": 60, "value": 1.0 }],
+//!   "rotation": [{ "frame": 0, "value": 0.0 }, { "frame": 120, "value": 360.0 }]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::canvas::{BlendMode, Layer, Reframe};
+
+/// Shape of the interpolation from one keyframe to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Hold the value until the next key.
+    Step,
+    /// Start slow (quadratic).
+    EaseIn,
+    /// End slow (quadratic).
+    EaseOut,
+    /// Start and end slow (smoothstep).
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps linear progress `t` in [0, 1] through the easing curve.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::Step => 0.0,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A value type that can be interpolated between keyframes.
+pub trait Lerp: Clone {
+    /// Returns the value a fraction `t` of the way from `a` to `b`.
+    fn lerp(a: Self, b: Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        a + (b - a) * t
+    }
+}
+
+impl Lerp for [f64; 2] {
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        [f64::lerp(a[0], b[0], t), f64::lerp(a[1], b[1], t)]
+    }
+}
+
+/// Blend modes are discrete: the earlier key holds until the next is reached.
+impl Lerp for BlendMode {
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        if t >= 1.0 {
+            b
+        } else {
+            a
+        }
+    }
+}
+
+/// Palette names are discrete, like blend modes.
+impl Lerp for String {
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        if t >= 1.0 {
+            b
+        } else {
+            a
+        }
+    }
+}
+
+/// Parameter values interpolate structurally: numbers lerp (rounding when
+/// both keys are integers, so counts stay integers), and objects with the
+/// same keys or arrays of the same length interpolate member by member.
+/// Anything else (strings, booleans, mismatched shapes) switches at the
+/// next key.
+impl Lerp for Value {
+    fn lerp(a: Self, b: Self, t: f64) -> Self {
+        match (a, b) {
+            (Value::Number(a), Value::Number(b)) => {
+                let (Some(x), Some(y)) = (a.as_f64(), b.as_f64()) else {
+                    return Value::Number(if t >= 1.0 { b } else { a });
+                };
+                let value = f64::lerp(x, y, t);
+                if a.is_f64() || b.is_f64() {
+                    Value::from(value)
+                } else {
+                    Value::from(value.round() as i64)
+                }
+            }
+            (Value::Object(a), Value::Object(b))
+                if a.len() == b.len() && a.keys().all(|k| b.contains_key(k)) =>
+            {
+                let mut b = b;
+                a.into_iter()
+                    .map(|(key, x)| {
+                        let y = b.remove(&key).unwrap_or(Value::Null);
+                        (key, Value::lerp(x, y, t))
+                    })
+                    .collect::<serde_json::Map<_, _>>()
+                    .into()
+            }
+            (Value::Array(a), Value::Array(b)) if a.len() == b.len() => a
+                .into_iter()
+                .zip(b)
+                .map(|(x, y)| Value::lerp(x, y, t))
+                .collect(),
+            (a, b) => {
+                if t >= 1.0 {
+                    b
+                } else {
+                    a
+                }
+            }
+        }
+    }
+}
+
+/// A value pinned to a frame, with the easing used toward the next key.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe<T> {
+    /// Frame (or step) at which the value is reached.
+    pub frame: f64,
+    /// Value at this frame.
+    pub value: T,
+    /// Interpolation from this key to the next.
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+impl<T> Keyframe<T> {
+    /// Creates a linear keyframe.
+    pub fn new(frame: f64, value: T) -> Self {
+        Self {
+            frame,
+            value,
+            easing: Easing::Linear,
+        }
+    }
+
+    /// Returns a copy with the given easing.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+/// Keyframes sorted by frame, serialized as a plain list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    from = "Vec<Keyframe<T>>",
+    into = "Vec<Keyframe<T>>",
+    bound(
+        serialize = "T: Serialize + Clone",
+        deserialize = "T: DeserializeOwned"
+    )
+)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T> From<Vec<Keyframe<T>>> for Track<T> {
+    fn from(keys: Vec<Keyframe<T>>) -> Self {
+        Track::new(keys)
+    }
+}
+
+impl<T> From<Track<T>> for Vec<Keyframe<T>> {
+    fn from(track: Track<T>) -> Self {
+        track.keys
+    }
+}
+
+impl<T> Track<T> {
+    /// Creates a track, sorting the keys by frame (stable for equal frames).
+    pub fn new(mut keys: Vec<Keyframe<T>>) -> Self {
+        keys.sort_by(|a, b| a.frame.total_cmp(&b.frame));
+        Self { keys }
+    }
+
+    /// Returns the keys in frame order.
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    /// Returns a copy with every value mapped through `f`.
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Track<U> {
+        Track {
+            keys: self
+                .keys
+                .iter()
+                .map(|k| Keyframe {
+                    frame: k.frame,
+                    value: f(&k.value),
+                    easing: k.easing,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T: Lerp> Track<T> {
+    /// Returns the value at `frame`, or `None` for an empty track.
+    ///
+    /// Frames outside the keyed range hold the first or last value.
+    pub fn sample(&self, frame: f64) -> Option<T> {
+        let first = self.keys.first()?;
+        let next = self.keys.partition_point(|k| k.frame <= frame);
+        if next == 0 {
+            return Some(first.value.clone());
+        }
+        let a = &self.keys[next - 1];
+        let Some(b) = self.keys.get(next) else {
+            return Some(a.value.clone());
+        };
+        let t = (frame - a.frame) / (b.frame - a.frame);
+        Some(T::lerp(a.value.clone(), b.value.clone(), a.easing.apply(t)))
+    }
+}
+
+/// Keyframe tracks for the animatable properties of a [`Layer`].
+///
+/// Unset tracks leave the layer's static value alone. Transform tracks
+/// override only their component; pivot and wrap mode stay static.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayerAnimation {
+    /// Layer opacity (clamped to [0, 1] when applied).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<Track<f64>>,
+    /// Layer blend mode, switching at each key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blend_mode: Option<Track<BlendMode>>,
+    /// Transform translation, as a fraction of the image size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translate: Option<Track<[f64; 2]>>,
+    /// Transform rotation in degrees clockwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Track<f64>>,
+    /// Transform per-axis scale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<Track<[f64; 2]>>,
+}
+
+impl LayerAnimation {
+    /// Returns `true` if no property is animated.
+    pub fn is_empty(&self) -> bool {
+        self.opacity.is_none()
+            && self.blend_mode.is_none()
+            && self.translate.is_none()
+            && self.rotation.is_none()
+            && self.scale.is_none()
+    }
+
+    /// Writes the animated values at `frame` into `layer`.
+    pub fn apply(&self, layer: &mut Layer, frame: f64) {
+        let sample = |track: &Option<Track<f64>>| track.as_ref().and_then(|t| t.sample(frame));
+        let sample2 =
+            |track: &Option<Track<[f64; 2]>>| track.as_ref().and_then(|t| t.sample(frame));
+        if let Some(opacity) = sample(&self.opacity) {
+            layer.set_opacity(opacity);
+        }
+        if let Some(mode) = self.blend_mode.as_ref().and_then(|t| t.sample(frame)) {
+            layer.set_blend_mode(mode);
+        }
+        let mut transform = *layer.transform();
+        if let Some(translate) = sample2(&self.translate) {
+            transform.translate = translate;
+        }
+        if let Some(rotation) = sample(&self.rotation) {
+            transform.rotation = rotation;
+        }
+        if let Some(scale) = sample2(&self.scale) {
+            transform.scale = scale;
+        }
+        layer.set_transform(transform);
+    }
+
+    /// Re-expresses translation keys in a resized canvas frame.
+    pub(crate) fn reframe(&self, frame: &Reframe) -> LayerAnimation {
+        LayerAnimation {
+            translate: self.translate.as_ref().map(|t| t.map(|v| frame.vector(*v))),
+            ..self.clone()
+        }
+    }
+}
+
+/// Keyframe tracks for an engine's parameters over a simulation, with
+/// frames counted in steps.
+///
+/// ```json
+/// {
+///   "params": {
+///     "feed_rate": [{ "frame": 0, "value": 0.03 }, { "frame": 2000, "value": 0.055, "easing": "ease_in_out" }],
+///     "velocity": [
+///       { "frame": 0, "value": { "kind": "curl", "scale": 0.01, "strength": 0.2, "seed": 0 } },
+///       { "frame": 3000, "value": { "kind": "curl", "scale": 0.01, "strength": 1.5, "seed": 0 } }
+///     ]
+///   },
+///   "palette": [{ "frame": 0, "value": "ocean" }, { "frame": 1500, "value": "fire" }]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParamTimeline {
+    /// Engine parameter tracks by parameter name; values interpolate as
+    /// described on [`Lerp`] for [`Value`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Track<Value>>,
+    /// Palette name, switching at each key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Track<String>>,
+}
+
+impl ParamTimeline {
+    /// Returns `true` if nothing is keyed.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.palette.is_none()
+    }
+
+    /// Returns the keyed parameters at `frame`: a partial params object
+    /// for [`Engine::set_params`](crate::Engine::set_params).
+    pub fn params_at(&self, frame: f64) -> serde_json::Map<String, Value> {
+        self.params
+            .iter()
+            .filter_map(|(name, track)| Some((name.clone(), track.sample(frame)?)))
+            .collect()
+    }
+
+    /// Returns the palette name at `frame`, if the palette is keyed.
+    pub fn palette_at(&self, frame: f64) -> Option<String> {
+        self.palette.as_ref().and_then(|t| t.sample(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::ContentType;
+    use serde_json::json;
+
+    fn ramp() -> Track<f64> {
+        Track::new(vec![Keyframe::new(10.0, 1.0), Keyframe::new(0.0, 0.0)])
+    }
+
+    // -- Easing --
+
+    #[test]
+    fn easing_curves_hit_endpoints() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::Step.apply(0.99), 0.0);
+    }
+
+    // -- Tracks --
+
+    #[test]
+    fn track_sorts_keys_and_interpolates() {
+        let track = ramp();
+        assert_eq!(track.keys()[0].frame, 0.0);
+        assert_eq!(track.sample(5.0), Some(0.5));
+        assert_eq!(track.sample(2.5), Some(0.25));
+    }
+
+    #[test]
+    fn track_holds_outside_keyed_range() {
+        let track = ramp();
+        assert_eq!(track.sample(-3.0), Some(0.0));
+        assert_eq!(track.sample(10.0), Some(1.0));
+        assert_eq!(track.sample(99.0), Some(1.0));
+        assert_eq!(Track::<f64>::new(Vec::new()).sample(0.0), None);
+    }
+
+    #[test]
+    fn easing_belongs_to_the_earlier_key() {
+        let track = Track::new(vec![
+            Keyframe::new(0.0, 0.0).with_easing(Easing::Step),
+            Keyframe::new(10.0, 1.0),
+            Keyframe::new(20.0, 0.0).with_easing(Easing::EaseIn),
+        ]);
+        assert_eq!(track.sample(9.9), Some(0.0));
+        assert_eq!(track.sample(15.0), Some(0.5));
+    }
+
+    #[test]
+    fn blend_mode_track_switches_at_keys() {
+        let track = Track::new(vec![
+            Keyframe::new(0.0, BlendMode::Normal),
+            Keyframe::new(4.0, BlendMode::Screen),
+        ]);
+        assert_eq!(track.sample(3.9), Some(BlendMode::Normal));
+        assert_eq!(track.sample(4.0), Some(BlendMode::Screen));
+    }
+
+    // -- Layer animation --
+
+    #[test]
+    fn apply_overrides_only_animated_properties() {
+        let animation = LayerAnimation {
+            opacity: Some(Track::new(vec![
+                Keyframe::new(0.0, 0.0),
+                Keyframe::new(10.0, 2.0),
+            ])),
+            rotation: Some(Track::new(vec![
+                Keyframe::new(0.0, 0.0),
+                Keyframe::new(10.0, 90.0),
+            ])),
+            ..LayerAnimation::default()
+        };
+        let mut layer = Layer::new("a", ContentType::Field)
+            .with_transform(crate::transform::Transform::IDENTITY.with_scale(2.0, 2.0));
+        animation.apply(&mut layer, 5.0);
+        assert_eq!(layer.opacity(), 1.0);
+        assert_eq!(layer.transform().rotation, 45.0);
+        assert_eq!(layer.transform().scale, [2.0, 2.0]);
+        assert_eq!(layer.blend_mode(), BlendMode::Normal);
+    }
+
+    // -- Serde --
+
+    #[test]
+    fn serde_reads_unsorted_list_with_default_easing() {
+        let json = r#"{"opacity":[{"frame":8,"value":1.0},{"frame":0,"value":0.0,"easing":"ease_in_out"}],
+            "translate":[{"frame":0,"value":[0.1,0.0]}]}"#;
+        let animation: LayerAnimation = serde_json::from_str(json).unwrap();
+        let opacity = animation.opacity.as_ref().unwrap();
+        assert_eq!(opacity.keys()[0].easing, Easing::EaseInOut);
+        assert_eq!(opacity.keys()[1].easing, Easing::Linear);
+        assert!(animation.rotation.is_none());
+
+        let value = serde_json::to_value(&animation).unwrap();
+        assert!(value["opacity"].is_array());
+        assert!(value.get("scale").is_none());
+        let restored: LayerAnimation = serde_json::from_value(value).unwrap();
+        assert_eq!(restored, animation);
+    }
+
+    // -- Param timelines --
+
+    fn keyed(a: Value, b: Value) -> Track<Value> {
+        Track::new(vec![Keyframe::new(0.0, a), Keyframe::new(10.0, b)])
+    }
+
+    #[test]
+    fn value_numbers_lerp_and_integers_round() {
+        let track = keyed(json!(0.0), json!(1.0));
+        assert_eq!(track.sample(2.5), Some(json!(0.25)));
+        let counts = keyed(json!(3), json!(7));
+        assert_eq!(counts.sample(5.0), Some(json!(5)));
+        assert_eq!(counts.sample(6.0), Some(json!(5)));
+        assert!(counts.sample(6.0).unwrap().is_u64());
+        let mixed = keyed(json!(0), json!(1.0));
+        assert_eq!(mixed.sample(5.0), Some(json!(0.5)));
+    }
+
+    #[test]
+    fn value_objects_and_arrays_lerp_member_by_member() {
+        let track = keyed(
+            json!({"kind": "curl", "strength": 0.0, "seed": 0, "offset": [0.0, 10.0]}),
+            json!({"kind": "curl", "strength": 2.0, "seed": 4, "offset": [1.0, 20.0]}),
+        );
+        assert_eq!(
+            track.sample(5.0),
+            Some(json!({"kind": "curl", "strength": 1.0, "seed": 2, "offset": [0.5, 15.0]}))
+        );
+    }
+
+    #[test]
+    fn value_mismatched_shapes_switch_at_next_key() {
+        let kinds = keyed(
+            json!({"kind": "curl", "scale": 0.01}),
+            json!({"kind": "vortex", "x": 0.5}),
+        );
+        assert_eq!(
+            kinds.sample(9.0),
+            Some(json!({"kind": "curl", "scale": 0.01}))
+        );
+        assert_eq!(
+            kinds.sample(10.0),
+            Some(json!({"kind": "vortex", "x": 0.5}))
+        );
+        let flags = keyed(json!(false), json!(true));
+        assert_eq!(flags.sample(9.9), Some(json!(false)));
+        assert_eq!(flags.sample(10.0), Some(json!(true)));
+    }
+
+    #[test]
+    fn param_timeline_samples_params_and_palette() {
+        let json = r#"{"params":{"feed_rate":[{"frame":0,"value":0.02},{"frame":100,"value":0.06}],
+            "render":[{"frame":50,"value":"cells"}]},
+            "palette":[{"frame":0,"value":"ocean"},{"frame":60,"value":"fire"}]}"#;
+        let timeline: ParamTimeline = serde_json::from_str(json).unwrap();
+        assert!(!timeline.is_empty());
+        let params = timeline.params_at(25.0);
+        assert!((params["feed_rate"].as_f64().unwrap() - 0.03).abs() < 1e-12);
+        assert_eq!(params["render"], "cells");
+        assert_eq!(timeline.palette_at(59.0).as_deref(), Some("ocean"));
+        assert_eq!(timeline.palette_at(60.0).as_deref(), Some("fire"));
+
+        let restored: ParamTimeline =
+            serde_json::from_value(serde_json::to_value(&timeline).unwrap()).unwrap();
+        assert_eq!(restored, timeline);
+    }
+
+    #[test]
+    fn empty_param_timeline_samples_nothing() {
+        let timeline: ParamTimeline = serde_json::from_str("{}").unwrap();
+        assert!(timeline.is_empty());
+        assert!(timeline.params_at(3.0).is_empty());
+        assert_eq!(timeline.palette_at(3.0), None);
+    }
+}
@@ -2,28 +2,69 @@
 //! Core types and traits for the art-engine generative art system.
 //!
 //! Provides the `Engine` trait, `Field` type, `Canvas`/`Layer`/`BlendMode`/`ContentType`
-//! data model, color types (`Srgb`, `OkLab`, `OkLch`), `Palette` (OKLab/OKLCh),
-//! `Xorshift64` PRNG, `Seed`, and parameter helpers.
+//! data model with layer `Mask`s, `Transform`s and `Effect`s, color types
+//! (`Srgb`, `OkLab`, `OkLch`, with alpha `Srgba`, `OkLcha`; HSL, HSV, XYZ and CIELAB conversions), `Palette` (OKLab/OKLCh), `Rng` PRNGs (`Xorshift64`, `Pcg32`, `SplitMix64`),
+//! `Seed`, named `Preset`s, parameter helpers, binary engine `state` for checkpoints, a CPU `compositor` that renders a `Canvas` to an
+//! RGBA `Image`, `post` effect stacks (including `Bloom`), `Feedback` trails for animations, versioned `Project` files,
+//! interaction `replay` recordings, `field_preview` images of field sources, per-step `observer`s, `math` that the `portable` feature makes
+//! bit-for-bit reproducible across targets, and `MemoryBudget`s that refuse
+//! oversized runs before they allocate.
 
+pub mod bloom;
+pub mod budget;
 pub mod canvas;
 pub mod color;
+pub mod compositor;
+pub mod effect;
 pub mod engine;
 pub mod error;
+pub mod feedback;
 pub mod field;
+pub mod field_preview;
 pub mod field_source;
+pub mod font;
+pub mod image;
+pub mod keyframe;
+pub mod mask;
+pub mod math;
+pub mod observer;
 pub mod palette;
 pub mod params;
+pub mod post;
+pub mod preset;
 pub mod prng;
+pub mod project;
+pub mod replay;
 pub mod seed;
+pub mod state;
+pub mod transform;
 
 #[cfg(feature = "render")]
 pub mod render;
 
-pub use canvas::{BlendMode, Canvas, ContentType, Layer};
-pub use color::{LinearRgb, OkLab, OkLch, Srgb};
+pub use bloom::Bloom;
+pub use budget::MemoryBudget;
+pub use canvas::{
+    BlendMode, Canvas, ContentType, EngineBinding, Layer, LayerContent, LayerGroup, LayerNode,
+    ResizePolicy,
+};
+pub use color::{Hsl, Hsv, Lab, LinearRgb, OkLab, OkLch, OkLcha, Srgb, Srgba, Xyz};
+pub use effect::Effect;
 pub use engine::Engine;
 pub use error::EngineError;
+pub use feedback::Feedback;
 pub use field::Field;
+pub use image::Image;
+pub use keyframe::{Easing, Keyframe, LayerAnimation, ParamTimeline, Track};
+pub use mask::Mask;
+pub use observer::{Convergence, FieldStats, StepInfo, StepMonitor, StepObserver, StopWhenStable};
 pub use palette::Palette;
-pub use prng::Xorshift64;
-pub use seed::Seed;
+pub use params::Precision;
+pub use post::PostEffect;
+pub use preset::Preset;
+pub use prng::{Pcg32, Rng, RngAlgorithm, SplitMix64, Xorshift64};
+pub use project::Project;
+pub use replay::Recording;
+pub use seed::{Seed, SeedValue};
+pub use state::{StateReader, StateWriter};
+pub use transform::{Transform, WrapMode};
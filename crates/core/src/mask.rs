@@ -0,0 +1,302 @@
+//! Layer masks: per-pixel coverage that scales a layer's alpha.
+//!
+//! A [`Mask`] is either an explicit [`Field`] or a procedural shape. Shape
+//! positions are normalized to [0, 1] across the canvas and lengths (radius,
+//! feather) are fractions of the canvas's shorter side, so masks are
+//! resolution independent and circles stay round on non-square canvases.
+//! Field masks are resampled (nearest cell) when their size differs from the
+//! canvas.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::Reframe;
+use crate::error::EngineError;
+use crate::field::Field;
+
+/// Per-pixel coverage in [0, 1] multiplied into a layer's alpha.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "circle", "cx": 0.5, ...}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mask {
+    /// Explicit coverage values, stretched over the canvas.
+    Field(Field),
+    /// Disc centered at `(cx, cy)` with `radius`, softened over `feather`.
+    Circle {
+        cx: f64,
+        cy: f64,
+        radius: f64,
+        #[serde(default)]
+        feather: f64,
+    },
+    /// Axis-aligned rectangle from `(x0, y0)` to `(x1, y1)`, softened over `feather`.
+    Rect {
+        x0: f64,
+        y0: f64,
+        x1: f64,
+        y1: f64,
+        #[serde(default)]
+        feather: f64,
+    },
+}
+
+impl Mask {
+    /// Returns the coverage at pixel `(x, y)` of a `width x height` canvas.
+    ///
+    /// Shapes are evaluated at the pixel center.
+    pub fn coverage(&self, x: usize, y: usize, width: usize, height: usize) -> f64 {
+        let u = (x as f64 + 0.5) / width as f64;
+        let v = (y as f64 + 0.5) / height as f64;
+        // Normalized-to-shorter-side factors for each axis
+        let unit = width.min(height) as f64;
+        let (kx, ky) = (width as f64 / unit, height as f64 / unit);
+        let value = match self {
+            Mask::Field(field) => {
+                let fx = ((u * field.width() as f64) as usize).min(field.width() - 1);
+                let fy = ((v * field.height() as f64) as usize).min(field.height() - 1);
+                field.data()[fy * field.width() + fx]
+            }
+            Mask::Circle {
+                cx,
+                cy,
+                radius,
+                feather,
+            } => {
+                let d = (((u - cx) * kx).powi(2) + ((v - cy) * ky).powi(2)).sqrt();
+                edge(radius - d, *feather)
+            }
+            Mask::Rect {
+                x0,
+                y0,
+                x1,
+                y1,
+                feather,
+            } => {
+                // Signed distance to the nearest edge, positive inside
+                let inside = ((u - x0.min(*x1)) * kx)
+                    .min((x0.max(*x1) - u) * kx)
+                    .min((v - y0.min(*y1)) * ky)
+                    .min((y0.max(*y1) - v) * ky);
+                edge(inside, *feather)
+            }
+        };
+        if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        }
+    }
+
+    /// Evaluates the mask at every pixel of a `width x height` canvas.
+    ///
+    /// Returns `EngineError::InvalidDimensions` for zero or overflowing sizes.
+    pub fn rasterize(&self, width: usize, height: usize) -> Result<Field, EngineError> {
+        let mut field = Field::new(width, height)?;
+        field
+            .data_mut()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = self.coverage(i % width, i / width, width, height));
+        Ok(field)
+    }
+
+    /// Re-expresses the mask in a resized canvas frame.
+    ///
+    /// Field masks are resampled so their cells keep the same size relative
+    /// to the old frame; the area outside the old frame gets zero coverage.
+    pub(crate) fn reframe(&self, frame: &Reframe) -> Mask {
+        match self {
+            Mask::Field(field) if frame.scale != [1.0, 1.0] || frame.offset != [0.0, 0.0] => {
+                let (fw, fh) = (field.width(), field.height());
+                let nw = (fw as f64 / frame.scale[0]).round().max(1.0) as usize;
+                let nh = (fh as f64 / frame.scale[1]).round().max(1.0) as usize;
+                let data = (0..nw * nh)
+                    .map(|i| {
+                        let u = ((i % nw) as f64 + 0.5) / nw as f64;
+                        let v = ((i / nw) as f64 + 0.5) / nh as f64;
+                        let [ou, ov] = frame.inverse_point([u, v]);
+                        if (0.0..1.0).contains(&ou) && (0.0..1.0).contains(&ov) {
+                            let x = ((ou * fw as f64) as usize).min(fw - 1);
+                            let y = ((ov * fh as f64) as usize).min(fh - 1);
+                            field.data()[y * fw + x]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect();
+                // Keep the original cells if the resampled size would overflow
+                Mask::Field(Field::from_data(nw, nh, data).unwrap_or_else(|_| field.clone()))
+            }
+            Mask::Field(field) => Mask::Field(field.clone()),
+            Mask::Circle {
+                cx,
+                cy,
+                radius,
+                feather,
+            } => {
+                let [cx, cy] = frame.point([*cx, *cy]);
+                Mask::Circle {
+                    cx,
+                    cy,
+                    radius: frame.length(*radius),
+                    feather: frame.length(*feather),
+                }
+            }
+            Mask::Rect {
+                x0,
+                y0,
+                x1,
+                y1,
+                feather,
+            } => {
+                let [x0, y0] = frame.point([*x0, *y0]);
+                let [x1, y1] = frame.point([*x1, *y1]);
+                Mask::Rect {
+                    x0,
+                    y0,
+                    x1,
+                    y1,
+                    feather: frame.length(*feather),
+                }
+            }
+        }
+    }
+}
+
+/// Maps a signed distance (positive inside) to coverage with a linear
+/// feather band centered on the edge.
+fn edge(distance: f64, feather: f64) -> f64 {
+    if feather <= 0.0 {
+        if distance >= 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    } else {
+        (distance / feather + 0.5).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -- Shapes --
+
+    #[test]
+    fn hard_circle_covers_center_not_corner() {
+        let mask = Mask::Circle {
+            cx: 0.5,
+            cy: 0.5,
+            radius: 0.25,
+            feather: 0.0,
+        };
+        assert_eq!(mask.coverage(8, 8, 16, 16), 1.0);
+        assert_eq!(mask.coverage(0, 0, 16, 16), 0.0);
+    }
+
+    #[test]
+    fn feathered_circle_is_half_on_the_edge() {
+        // Pixel center (0.75, 0.75) sits exactly on the radius
+        let mask = Mask::Circle {
+            cx: 0.25,
+            cy: 0.75,
+            radius: 0.5,
+            feather: 0.2,
+        };
+        assert!((mask.coverage(1, 1, 2, 2) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn circle_stays_round_on_wide_canvas() {
+        // radius 0.25 of the 8 px shorter side = 2 px on a 32x8 canvas
+        let mask = Mask::Circle {
+            cx: 0.5,
+            cy: 0.5,
+            radius: 0.25,
+            feather: 0.0,
+        };
+        let row: Vec<f64> = (0..32).map(|x| mask.coverage(x, 4, 32, 8)).collect();
+        assert_eq!(row.iter().filter(|&&c| c == 1.0).count(), 4);
+    }
+
+    #[test]
+    fn rect_accepts_swapped_corners() {
+        let a = Mask::Rect {
+            x0: 0.0,
+            y0: 0.0,
+            x1: 0.5,
+            y1: 1.0,
+            feather: 0.0,
+        };
+        let b = Mask::Rect {
+            x0: 0.5,
+            y0: 1.0,
+            x1: 0.0,
+            y1: 0.0,
+            feather: 0.0,
+        };
+        assert_eq!(a.rasterize(4, 2).unwrap(), b.rasterize(4, 2).unwrap());
+        assert_eq!(a.coverage(0, 0, 4, 2), 1.0);
+        assert_eq!(a.coverage(3, 0, 4, 2), 0.0);
+    }
+
+    // -- Field masks --
+
+    #[test]
+    fn field_mask_is_stretched_over_canvas() {
+        let mask = Mask::Field(Field::from_data(2, 1, vec![0.0, 1.0]).unwrap());
+        let raster = mask.rasterize(4, 2).unwrap();
+        assert_eq!(raster.data(), &[0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn field_mask_clamps_out_of_range_values() {
+        let mask = Mask::Field(Field::from_data(2, 1, vec![-1.0, 3.0]).unwrap());
+        assert_eq!(mask.coverage(0, 0, 2, 1), 0.0);
+        assert_eq!(mask.coverage(1, 0, 2, 1), 1.0);
+    }
+
+    #[test]
+    fn rasterize_rejects_zero_dimensions() {
+        let mask = Mask::Field(Field::new(1, 1).unwrap());
+        assert!(mask.rasterize(0, 4).is_err());
+    }
+
+    // -- Serde --
+
+    #[test]
+    fn serde_round_trip_all_kinds() {
+        let masks = [
+            Mask::Field(Field::from_data(1, 2, vec![0.25, 0.75]).unwrap()),
+            Mask::Circle {
+                cx: 0.5,
+                cy: 0.4,
+                radius: 0.3,
+                feather: 0.05,
+            },
+            Mask::Rect {
+                x0: 0.1,
+                y0: 0.2,
+                x1: 0.9,
+                y1: 0.8,
+                feather: 0.0,
+            },
+        ];
+        for mask in masks {
+            let json = serde_json::to_string(&mask).unwrap();
+            let restored: Mask = serde_json::from_str(&json).unwrap();
+            assert_eq!(mask, restored);
+        }
+    }
+
+    #[test]
+    fn serde_uses_kind_tag_and_default_feather() {
+        let json = r#"{"kind":"circle","cx":0.5,"cy":0.5,"radius":0.2}"#;
+        let mask: Mask = serde_json::from_str(json).unwrap();
+        assert!(matches!(mask, Mask::Circle { feather, .. } if feather == 0.0));
+        let field = serde_json::to_value(Mask::Field(Field::new(1, 1).unwrap())).unwrap();
+        assert_eq!(field["kind"], "field");
+        assert_eq!(field["width"], 1);
+    }
+}
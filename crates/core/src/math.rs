@@ -0,0 +1,485 @@
+//! Transcendental functions for engine and color math, optionally portable.
+//!
+//! IEEE 754 rounds `+ - * /` and `sqrt` the same way on every target, but
+//! `sin`, `exp`, `powf`, `hypot` and friends come from the platform's math
+//! library, whose last bits differ between glibc, macOS, Windows and WASM.
+//! Engines call the functions here instead of the `f64` methods. By default
+//! they are those methods; with the `portable` feature they are computed
+//! from basic operations only, so native and WASM renders match bit for
+//! bit. Portable results agree with `std` to within a few ULPs, not
+//! exactly, so a seed renders slightly differently with and without the
+//! feature.
+//!
+//! Integer powers with a constant exponent (`x.powi(2)`) compile to plain
+//! multiplies and need no wrapper; `powi` here is for variable exponents.
+//! Angles are reduced against a three-part π/2, accurate for |x| up to
+//! about 10⁶; larger arguments stay deterministic but lose precision.
+
+/// Whether the `portable` feature is on.
+pub const PORTABLE: bool = cfg!(feature = "portable");
+
+/// Sine of `x` radians.
+#[inline]
+pub fn sin(x: f64) -> f64 {
+    if PORTABLE {
+        portable::sin_cos(x).0
+    } else {
+        x.sin()
+    }
+}
+
+/// Cosine of `x` radians.
+#[inline]
+pub fn cos(x: f64) -> f64 {
+    if PORTABLE {
+        portable::sin_cos(x).1
+    } else {
+        x.cos()
+    }
+}
+
+/// Sine and cosine of `x` radians.
+#[inline]
+pub fn sin_cos(x: f64) -> (f64, f64) {
+    if PORTABLE {
+        portable::sin_cos(x)
+    } else {
+        x.sin_cos()
+    }
+}
+
+/// Four-quadrant arctangent of `y / x`, in radians.
+#[inline]
+pub fn atan2(y: f64, x: f64) -> f64 {
+    if PORTABLE {
+        portable::atan2(y, x)
+    } else {
+        y.atan2(x)
+    }
+}
+
+/// `e` raised to `x`.
+#[inline]
+pub fn exp(x: f64) -> f64 {
+    if PORTABLE {
+        portable::exp(x)
+    } else {
+        x.exp()
+    }
+}
+
+/// Natural logarithm of `x`.
+#[inline]
+pub fn ln(x: f64) -> f64 {
+    if PORTABLE {
+        portable::ln(x)
+    } else {
+        x.ln()
+    }
+}
+
+/// `x` raised to the power `y`.
+#[inline]
+pub fn powf(x: f64, y: f64) -> f64 {
+    if PORTABLE {
+        portable::powf(x, y)
+    } else {
+        x.powf(y)
+    }
+}
+
+/// `x` raised to the integer power `n`.
+#[inline]
+pub fn powi(x: f64, n: i32) -> f64 {
+    if PORTABLE {
+        portable::powi(x, n)
+    } else {
+        x.powi(n)
+    }
+}
+
+/// Cube root of `x`.
+#[inline]
+pub fn cbrt(x: f64) -> f64 {
+    if PORTABLE {
+        portable::cbrt(x)
+    } else {
+        x.cbrt()
+    }
+}
+
+/// Length of the vector `(x, y)`, without undue overflow or underflow.
+#[inline]
+pub fn hypot(x: f64, y: f64) -> f64 {
+    if PORTABLE {
+        portable::hypot(x, y)
+    } else {
+        x.hypot(y)
+    }
+}
+
+/// Implementations from IEEE basic operations, compiled on every build so
+/// they are tested against `std` whether or not the feature is on.
+mod portable {
+    use std::f64::consts::{FRAC_2_PI, FRAC_PI_2, FRAC_PI_4, LN_2, PI, SQRT_2};
+
+    /// ln 2 split so `k * LN2_HI` is exact for the exponents `exp` meets.
+    const LN2_HI: f64 = f64::from_bits(0x3fe6_2e42_fee0_0000);
+    const LN2_LO: f64 = f64::from_bits(0x3dea_39ef_3579_3c76);
+    /// π/2 in three 33-bit parts, so `k * PIO2_n` is exact for |k| < 2²⁰.
+    const PIO2_1: f64 = f64::from_bits(0x3ff9_21fb_5440_0000);
+    const PIO2_2: f64 = f64::from_bits(0x3dd0_b461_1a60_0000);
+    const PIO2_3: f64 = f64::from_bits(0x3ba3_198a_2e00_0000);
+
+    /// `x * 2^k`, in steps that keep each factor representable.
+    fn scale2(mut x: f64, mut k: i32) -> f64 {
+        while k > 1023 {
+            x *= f64::from_bits(0x7fe << 52);
+            k -= 1023;
+        }
+        while k < -1022 {
+            x *= f64::from_bits(1 << 52);
+            k += 1022;
+        }
+        x * f64::from_bits(((k + 1023) as u64) << 52)
+    }
+
+    pub fn exp(x: f64) -> f64 {
+        if x.is_nan() {
+            return x;
+        }
+        if x > 709.782_712_893_384 {
+            return f64::INFINITY;
+        }
+        if x < -745.133_219_101_941_1 {
+            return 0.0;
+        }
+        let k = (x / LN_2).round();
+        let r = (x - k * LN2_HI) - k * LN2_LO;
+        // Taylor series on |r| <= ln 2 / 2, to well below an ULP
+        let poly = (1..=13).rev().fold(1.0, |p, n| 1.0 + p * r / f64::from(n));
+        scale2(poly, k as i32)
+    }
+
+    pub fn ln(x: f64) -> f64 {
+        if x.is_nan() || x < 0.0 {
+            return f64::NAN;
+        }
+        if x == 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if x == f64::INFINITY {
+            return x;
+        }
+        let (mut m, mut e) = (x, 0);
+        if m < f64::MIN_POSITIVE {
+            m *= f64::from_bits((1023 + 54) << 52);
+            e -= 54;
+        }
+        let bits = m.to_bits();
+        e += ((bits >> 52) & 0x7ff) as i32 - 1023;
+        m = f64::from_bits((bits & !(0x7ff << 52)) | (1023 << 52));
+        if m > SQRT_2 {
+            m *= 0.5;
+            e += 1;
+        }
+        // ln m = 2 atanh f, a series in f² <= 0.03
+        let f = (m - 1.0) / (m + 1.0);
+        let s = f * f;
+        let series = (0..=12)
+            .rev()
+            .fold(0.0, |p, k| p * s + 1.0 / f64::from(2 * k + 1));
+        let e = f64::from(e);
+        e * LN2_HI + (2.0 * f * series + e * LN2_LO)
+    }
+
+    pub fn powi(x: f64, n: i32) -> f64 {
+        let (mut base, mut e, mut result) = (x, n.unsigned_abs(), 1.0);
+        while e > 0 {
+            if e & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            e >>= 1;
+        }
+        if n < 0 {
+            1.0 / result
+        } else {
+            result
+        }
+    }
+
+    pub fn powf(x: f64, y: f64) -> f64 {
+        if y == 0.0 || x == 1.0 {
+            return 1.0;
+        }
+        if x.is_nan() || y.is_nan() {
+            return f64::NAN;
+        }
+        if y.is_infinite() {
+            return match x.abs() {
+                1.0 => 1.0,
+                a if (a > 1.0) == (y > 0.0) => f64::INFINITY,
+                _ => 0.0,
+            };
+        }
+        let integer = y.fract() == 0.0;
+        let odd = integer && (y * 0.5).fract() != 0.0;
+        let sign = if odd && x.is_sign_negative() {
+            -1.0
+        } else {
+            1.0
+        };
+        if x == 0.0 || x.is_infinite() {
+            let magnitude = if (x == 0.0) == (y > 0.0) {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+            return sign * magnitude;
+        }
+        if x < 0.0 && !integer {
+            return f64::NAN;
+        }
+        sign * exp(y * ln(x.abs()))
+    }
+
+    pub fn cbrt(x: f64) -> f64 {
+        if x == 0.0 || !x.is_finite() {
+            return x;
+        }
+        let a = x.abs();
+        let guess = exp(ln(a) / 3.0);
+        // One Newton step, arranged so no intermediate overflows
+        let root = guess - (guess - a / (guess * guess)) / 3.0;
+        root.copysign(x)
+    }
+
+    pub fn hypot(x: f64, y: f64) -> f64 {
+        let (a, b) = (x.abs(), y.abs());
+        if a.is_infinite() || b.is_infinite() {
+            return f64::INFINITY;
+        }
+        if a.is_nan() || b.is_nan() {
+            return f64::NAN;
+        }
+        let (big, small) = if a >= b { (a, b) } else { (b, a) };
+        if (1e-150..1e150).contains(&big) {
+            return (a * a + b * b).sqrt();
+        }
+        if big == 0.0 {
+            return 0.0;
+        }
+        let ratio = small / big;
+        big * (1.0 + ratio * ratio).sqrt()
+    }
+
+    pub fn sin_cos(x: f64) -> (f64, f64) {
+        if !x.is_finite() {
+            return (f64::NAN, f64::NAN);
+        }
+        if x == 0.0 {
+            return (x, 1.0);
+        }
+        let k = (x * FRAC_2_PI).round();
+        let r = ((x - k * PIO2_1) - k * PIO2_2) - k * PIO2_3;
+        // Taylor series on |r| <= π/4
+        let r2 = r * r;
+        let sin = r
+            * (1..=8)
+                .rev()
+                .fold(1.0, |p, n| 1.0 - r2 / f64::from((2 * n) * (2 * n + 1)) * p);
+        let cos = (1..=9)
+            .rev()
+            .fold(1.0, |p, n| 1.0 - r2 / f64::from((2 * n - 1) * (2 * n)) * p);
+        match (k as i64) & 3 {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            2 => (-sin, -cos),
+            _ => (-cos, sin),
+        }
+    }
+
+    fn atan(x: f64) -> f64 {
+        if x.is_nan() {
+            return x;
+        }
+        let a = x.abs();
+        let (mut t, reflected) = if a > 1.0 { (1.0 / a, true) } else { (a, false) };
+        // Halve the angle twice, leaving t <= tan(π/16)
+        for _ in 0..2 {
+            t /= 1.0 + (1.0 + t * t).sqrt();
+        }
+        let s = t * t;
+        let series = (0..=12)
+            .rev()
+            .fold(0.0, |p, n| 1.0 / f64::from(2 * n + 1) - s * p);
+        let angle = 4.0 * t * series;
+        let angle = if reflected { FRAC_PI_2 - angle } else { angle };
+        angle.copysign(x)
+    }
+
+    pub fn atan2(y: f64, x: f64) -> f64 {
+        if x.is_nan() || y.is_nan() {
+            return f64::NAN;
+        }
+        if x.is_infinite() && y.is_infinite() {
+            let angle = if x > 0.0 { FRAC_PI_4 } else { 3.0 * FRAC_PI_4 };
+            return angle.copysign(y);
+        }
+        if y == 0.0 {
+            return if x.is_sign_negative() {
+                PI.copysign(y)
+            } else {
+                y
+            };
+        }
+        let angle = atan(y / x);
+        if x.is_sign_negative() {
+            angle + PI.copysign(y)
+        } else {
+            angle
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::portable;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    /// Asserts `actual` is within `ulps` units in the last place of `expected`.
+    fn assert_close(actual: f64, expected: f64, ulps: f64, what: &str) {
+        if expected.is_nan() {
+            assert!(actual.is_nan(), "{what}: {actual} is not NaN");
+            return;
+        }
+        if expected.is_infinite() || expected == 0.0 {
+            assert_eq!(actual, expected, "{what}");
+            return;
+        }
+        let ulp = f64::EPSILON * expected.abs();
+        assert!(
+            (actual - expected).abs() <= ulps * ulp,
+            "{what}: {actual} vs {expected}"
+        );
+    }
+
+    /// Arguments from tiny to large, both signs.
+    fn samples() -> impl Iterator<Item = f64> {
+        (-400..=400).map(|i| f64::from(i) * 0.0731 + 1e-3)
+    }
+
+    // -- Trigonometry --
+
+    #[test]
+    fn sin_cos_match_std_over_many_periods() {
+        for x in samples() {
+            let (sin, cos) = portable::sin_cos(x);
+            // Absolute error near zeros of sin and cos, relative elsewhere
+            assert!((sin - x.sin()).abs() <= 4.0 * f64::EPSILON, "sin {x}");
+            assert!((cos - x.cos()).abs() <= 4.0 * f64::EPSILON, "cos {x}");
+        }
+        assert_eq!(portable::sin_cos(0.0), (0.0, 1.0));
+        assert!(portable::sin_cos(f64::INFINITY).0.is_nan());
+    }
+
+    #[test]
+    fn atan2_covers_every_quadrant_and_edge() {
+        for y in samples().step_by(7) {
+            for x in samples().step_by(11) {
+                assert_close(portable::atan2(y, x), y.atan2(x), 4.0, "atan2");
+            }
+        }
+        for (y, x) in [
+            (0.0, 1.0),
+            (-0.0, 1.0),
+            (0.0, -1.0),
+            (-0.0, -1.0),
+            (1.0, 0.0),
+            (-1.0, 0.0),
+            (f64::INFINITY, 1.0),
+            (1.0, f64::NEG_INFINITY),
+            (f64::INFINITY, f64::NEG_INFINITY),
+        ] {
+            let (actual, expected) = (portable::atan2(y, x), y.atan2(x));
+            assert_close(actual, expected, 2.0, "atan2 edge");
+            assert_eq!(actual.is_sign_negative(), expected.is_sign_negative());
+        }
+        assert_close(portable::atan2(1.0, 0.0), FRAC_PI_2, 1.0, "atan2 axis");
+        assert_close(portable::atan2(0.0, -1.0), PI, 1.0, "atan2 axis");
+    }
+
+    // -- Exponentials --
+
+    #[test]
+    fn exp_and_ln_match_std() {
+        for x in samples() {
+            assert_close(portable::exp(x), x.exp(), 2.0, "exp");
+            let positive = x.abs() * 1e3;
+            assert_close(portable::ln(positive), positive.ln(), 2.0, "ln");
+        }
+        for x in [1e-310, 5e-324, 1e300, f64::MAX, 0.5, 1.0, 2.0] {
+            assert_close(portable::ln(x), x.ln(), 2.0, "ln extreme");
+        }
+        assert_eq!(portable::exp(-800.0), 0.0);
+        assert_eq!(portable::exp(710.0), f64::INFINITY);
+        assert_close(
+            portable::exp(-740.0),
+            (-740.0f64).exp(),
+            1e12,
+            "exp subnormal",
+        );
+        assert_eq!(portable::ln(0.0), f64::NEG_INFINITY);
+        assert!(portable::ln(-1.0).is_nan());
+    }
+
+    #[test]
+    fn powers_match_std() {
+        for x in samples().map(f64::abs) {
+            for y in [-2.5, -1.0, 0.3, 1.0 / 2.4, 2.4, 7.0] {
+                assert_close(portable::powf(x, y), x.powf(y), 64.0, "powf");
+            }
+            for n in [-3, 0, 1, 5, 12] {
+                assert_close(portable::powi(x, n), x.powi(n), 8.0, "powi");
+            }
+            assert_close(portable::cbrt(x), x.cbrt(), 2.0, "cbrt");
+            assert_close(portable::cbrt(-x), (-x).cbrt(), 2.0, "cbrt");
+        }
+        for (x, y) in [
+            (-2.0, 3.0),
+            (-2.0, 0.5),
+            (0.0, -1.0),
+            (-0.0, -3.0),
+            (-0.0, 2.0),
+            (f64::NEG_INFINITY, 3.0),
+            (f64::INFINITY, -1.0),
+            (0.5, f64::INFINITY),
+            (-1.0, f64::INFINITY),
+            (f64::NAN, 0.0),
+        ] {
+            let (actual, expected) = (portable::powf(x, y), x.powf(y));
+            assert_close(actual, expected, 2.0, &format!("powf({x}, {y})"));
+            if !expected.is_nan() {
+                assert_eq!(actual.is_sign_negative(), expected.is_sign_negative());
+            }
+        }
+    }
+
+    #[test]
+    fn hypot_matches_std_without_overflow() {
+        for x in samples() {
+            for y in samples().step_by(13) {
+                assert_close(portable::hypot(x, y), x.hypot(y), 2.0, "hypot");
+            }
+        }
+        assert_close(
+            portable::hypot(1e300, 1e300),
+            1e300f64.hypot(1e300),
+            2.0,
+            "big",
+        );
+        assert_close(portable::hypot(3e-320, 4e-320), 5e-320, 1e6, "tiny");
+        assert_eq!(portable::hypot(f64::NAN, f64::INFINITY), f64::INFINITY);
+    }
+}
@@ -0,0 +1,475 @@
+//! Per-step observers: watch a simulation as it runs.
+//!
+//! A loop that steps an engine drives a [`StepMonitor`], calling
+//! [`StepMonitor::observe`] after every step. The monitor times the step and
+//! hands each [`StepObserver`] a [`StepInfo`]: the step index, elapsed time,
+//! and the field, from which [`StepInfo::stats`] and [`StepInfo::change`]
+//! (mean absolute change since the previous step) are computed on demand.
+//!
+//! Observers return [`ControlFlow::Break`] to ask the loop to stop early,
+//! which is how auto-stopping heuristics such as [`StopWhenStable`] work.
+//! Closures become observers through [`from_fn`]; a `&mut` observer is one
+//! too, so its state can be read back once the monitor is dropped.
+//!
+//! Timing uses [`Instant`], which is unavailable on
+//! `wasm32-unknown-unknown`; drive monitors from native front ends.
+
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::field::Field;
+use crate::prng::{fnv1a, fnv1a_extend};
+
+/// Value range and mean of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FieldStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl FieldStats {
+    /// Computes the statistics of `field`'s values.
+    pub fn of(field: &Field) -> Self {
+        let data = field.data();
+        let (min, max, sum) = data.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY, 0.0),
+            |(min, max, sum), &v| (min.min(v), max.max(v), sum + v),
+        );
+        Self {
+            min,
+            max,
+            // Fields are never empty
+            mean: sum / data.len() as f64,
+        }
+    }
+}
+
+/// Mean absolute difference between two fields' values, or `None` if their
+/// sizes differ.
+pub fn mean_change(previous: &Field, current: &Field) -> Option<f64> {
+    if (previous.width(), previous.height()) != (current.width(), current.height()) {
+        return None;
+    }
+    let total: f64 = previous
+        .data()
+        .iter()
+        .zip(current.data())
+        .map(|(a, b)| (b - a).abs())
+        .sum();
+    Some(total / current.data().len() as f64)
+}
+
+/// What an observer sees after a step.
+#[derive(Debug, Clone, Copy)]
+pub struct StepInfo<'a> {
+    /// Steps completed, counting any taken before monitoring started.
+    pub step: usize,
+    /// Time spent since the previous observed step (or since
+    /// [`StepMonitor::start`]).
+    pub step_time: Duration,
+    /// Time spent since [`StepMonitor::start`].
+    pub elapsed: Duration,
+    /// The engine's field after the step.
+    pub field: &'a Field,
+    /// The field before the step, kept only when an observer
+    /// [wants it](StepObserver::wants_previous).
+    pub previous: Option<&'a Field>,
+}
+
+impl StepInfo<'_> {
+    /// Range and mean of the field.
+    pub fn stats(&self) -> FieldStats {
+        FieldStats::of(self.field)
+    }
+
+    /// Mean absolute change of the field over the step, if the previous
+    /// field was kept.
+    pub fn change(&self) -> Option<f64> {
+        mean_change(self.previous?, self.field)
+    }
+}
+
+/// Receives a [`StepInfo`] after every step.
+pub trait StepObserver {
+    /// Handles one step; [`ControlFlow::Break`] asks the loop to stop.
+    fn on_step(&mut self, info: &StepInfo<'_>) -> ControlFlow<()>;
+
+    /// Whether [`StepInfo::previous`] is needed, which costs a copy of the
+    /// field per step. Defaults to `false`.
+    fn wants_previous(&self) -> bool {
+        false
+    }
+}
+
+impl<T: StepObserver + ?Sized> StepObserver for &mut T {
+    fn on_step(&mut self, info: &StepInfo<'_>) -> ControlFlow<()> {
+        (**self).on_step(info)
+    }
+
+    fn wants_previous(&self) -> bool {
+        (**self).wants_previous()
+    }
+}
+
+/// An observer that calls `f` on every step.
+pub fn from_fn<F: FnMut(&StepInfo<'_>) -> ControlFlow<()>>(f: F) -> FromFn<F> {
+    FromFn(f)
+}
+
+/// Observer returned by [`from_fn`].
+#[derive(Debug, Clone)]
+pub struct FromFn<F>(F);
+
+impl<F: FnMut(&StepInfo<'_>) -> ControlFlow<()>> StepObserver for FromFn<F> {
+    fn on_step(&mut self, info: &StepInfo<'_>) -> ControlFlow<()> {
+        (self.0)(info)
+    }
+}
+
+/// Why a [`StopWhenStable`] stopped a run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "lowercase")]
+pub enum Convergence {
+    /// Step `step` changed the field by `change` (mean absolute change per
+    /// cell), below the threshold.
+    Stable { step: usize, change: f64 },
+    /// The field after step `step` was identical to the one `period` steps
+    /// earlier, so the run cycles without settling.
+    Oscillating { step: usize, period: usize },
+}
+
+/// Stops a run once the field changes by less than `epsilon` (mean
+/// absolute change per cell) over a step, or once it repeats a state seen
+/// at most `max_period` steps earlier.
+///
+/// Repeats are spotted by hashing every field, so only exact cycles count;
+/// they are what discrete engines fall into when they stop evolving.
+#[derive(Debug, Clone)]
+pub struct StopWhenStable {
+    /// Mean absolute change per cell below which the field counts as
+    /// settled.
+    pub epsilon: f64,
+    /// Longest cycle, in steps, recognized as oscillation; 0 turns
+    /// oscillation detection off.
+    pub max_period: usize,
+    /// Hashes of the most recent fields, newest last.
+    recent: VecDeque<u64>,
+    outcome: Option<Convergence>,
+}
+
+impl StopWhenStable {
+    /// Longest cycle [`StopWhenStable::new`] looks for.
+    pub const DEFAULT_MAX_PERIOD: usize = 16;
+
+    /// Stops once a step changes the field by less than `epsilon`, or the
+    /// field cycles with a period of up to [`Self::DEFAULT_MAX_PERIOD`].
+    pub fn new(epsilon: f64) -> Self {
+        Self {
+            epsilon,
+            max_period: Self::DEFAULT_MAX_PERIOD,
+            recent: VecDeque::new(),
+            outcome: None,
+        }
+    }
+
+    /// Returns this detector looking for cycles of up to `max_period`
+    /// steps (0 for none).
+    pub fn with_max_period(mut self, max_period: usize) -> Self {
+        self.max_period = max_period;
+        self
+    }
+
+    /// Why the run was stopped, once it has been.
+    pub fn outcome(&self) -> Option<Convergence> {
+        self.outcome
+    }
+
+    /// Hash of the field's values, remembered for spotting cycles.
+    fn state_hash(field: &Field) -> u64 {
+        field.data().iter().fold(fnv1a(&[]), |hash, value| {
+            fnv1a_extend(hash, &value.to_bits().to_le_bytes())
+        })
+    }
+}
+
+impl StepObserver for StopWhenStable {
+    fn on_step(&mut self, info: &StepInfo<'_>) -> ControlFlow<()> {
+        let step = info.step;
+        if let Some(change) = info.change().filter(|change| *change < self.epsilon) {
+            self.outcome = Some(Convergence::Stable { step, change });
+            return ControlFlow::Break(());
+        }
+        if self.max_period == 0 {
+            return ControlFlow::Continue(());
+        }
+        let hash = Self::state_hash(info.field);
+        let period = self.recent.iter().rev().position(|&h| h == hash);
+        if self.recent.len() == self.max_period {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(hash);
+        match period {
+            // An unchanged field has settled, whatever the threshold
+            Some(0) => {
+                self.outcome = Some(Convergence::Stable { step, change: 0.0 });
+                ControlFlow::Break(())
+            }
+            Some(back) => {
+                self.outcome = Some(Convergence::Oscillating {
+                    step,
+                    period: back + 1,
+                });
+                ControlFlow::Break(())
+            }
+            None => ControlFlow::Continue(()),
+        }
+    }
+
+    fn wants_previous(&self) -> bool {
+        true
+    }
+}
+
+/// Times steps and fans them out to observers.
+pub struct StepMonitor<'a> {
+    observers: Vec<Box<dyn StepObserver + 'a>>,
+    started: Instant,
+    last: Instant,
+    previous: Option<Field>,
+}
+
+impl Default for StepMonitor<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> StepMonitor<'a> {
+    /// Creates a monitor with no observers, its clock started now.
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            observers: Vec::new(),
+            started: now,
+            last: now,
+            previous: None,
+        }
+    }
+
+    /// Returns this monitor with `observer` added.
+    pub fn with_observer(mut self, observer: impl StepObserver + 'a) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Returns `true` if no observer is attached.
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// Restarts the clock before the first step, remembering `field` as
+    /// the starting point for [`StepInfo::change`].
+    pub fn start(&mut self, field: &Field) {
+        self.started = Instant::now();
+        self.last = self.started;
+        self.previous = self.wants_previous().then(|| field.clone());
+    }
+
+    /// Reports step `step`, which left the engine with `field`, to every
+    /// observer. Returns [`ControlFlow::Break`] if any asked to stop; all
+    /// observers see the step either way.
+    pub fn observe(&mut self, step: usize, field: &Field) -> ControlFlow<()> {
+        let now = Instant::now();
+        let info = StepInfo {
+            step,
+            step_time: now - self.last,
+            elapsed: now - self.started,
+            field,
+            previous: self.previous.as_ref(),
+        };
+        let mut flow = ControlFlow::Continue(());
+        for observer in &mut self.observers {
+            if observer.on_step(&info).is_break() {
+                flow = ControlFlow::Break(());
+            }
+        }
+        self.last = now;
+        if self.wants_previous() {
+            match &mut self.previous {
+                Some(previous)
+                    if previous.width() == field.width() && previous.height() == field.height() =>
+                {
+                    previous.data_mut().copy_from_slice(field.data());
+                }
+                _ => self.previous = Some(field.clone()),
+            }
+        }
+        flow
+    }
+
+    fn wants_previous(&self) -> bool {
+        self.observers.iter().any(|o| o.wants_previous())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(value: f64) -> Field {
+        Field::filled(2, 2, value).unwrap()
+    }
+
+    // -- Field stats --
+
+    #[test]
+    fn field_stats_cover_every_value() {
+        let field = Field::from_data(2, 2, vec![0.25, -1.0, 0.75, 2.0]).unwrap();
+        assert_eq!(
+            FieldStats::of(&field),
+            FieldStats {
+                min: -1.0,
+                max: 2.0,
+                mean: 0.5
+            }
+        );
+    }
+
+    #[test]
+    fn mean_change_needs_matching_sizes() {
+        let a = Field::from_data(2, 1, vec![0.0, 1.0]).unwrap();
+        let b = Field::from_data(2, 1, vec![0.5, 0.0]).unwrap();
+        assert_eq!(mean_change(&a, &b), Some(0.75));
+        assert_eq!(mean_change(&a, &filled(0.0)), None);
+    }
+
+    // -- Monitor --
+
+    #[test]
+    fn monitor_reports_steps_to_every_observer() {
+        let mut seen = Vec::new();
+        let mut means = Vec::new();
+        let mut monitor = StepMonitor::new()
+            .with_observer(from_fn(|info: &StepInfo| {
+                seen.push((info.step, info.previous.is_some()));
+                ControlFlow::Continue(())
+            }))
+            .with_observer(from_fn(|info: &StepInfo| {
+                means.push(info.stats().mean);
+                ControlFlow::Break(())
+            }));
+        monitor.start(&filled(0.0));
+        assert!(monitor.observe(1, &filled(0.5)).is_break());
+        assert!(monitor.observe(2, &filled(1.0)).is_break());
+        drop(monitor);
+        assert_eq!(seen, [(1, false), (2, false)]);
+        assert_eq!(means, [0.5, 1.0]);
+    }
+
+    #[test]
+    fn elapsed_time_accumulates_step_times() {
+        let mut times = Vec::new();
+        let mut monitor = StepMonitor::new().with_observer(from_fn(|info: &StepInfo| {
+            times.push((info.step_time, info.elapsed));
+            ControlFlow::Continue(())
+        }));
+        monitor.start(&filled(0.0));
+        for step in 1..=3 {
+            std::thread::sleep(Duration::from_millis(1));
+            assert!(monitor.observe(step, &filled(0.0)).is_continue());
+        }
+        drop(monitor);
+        let total: Duration = times.iter().map(|(step, _)| *step).sum();
+        assert_eq!(total, times[2].1);
+        assert!(times
+            .iter()
+            .all(|(step, _)| *step >= Duration::from_millis(1)));
+    }
+
+    // -- Stop when stable --
+
+    #[test]
+    fn stop_when_stable_waits_for_small_changes() {
+        let mut changes = Vec::new();
+        let mut monitor = StepMonitor::new()
+            .with_observer(StopWhenStable::new(0.01))
+            .with_observer(from_fn(|info: &StepInfo| {
+                changes.push(info.change());
+                ControlFlow::Continue(())
+            }));
+        monitor.start(&filled(0.0));
+        assert!(monitor.observe(1, &filled(0.5)).is_continue());
+        assert!(monitor.observe(2, &filled(0.6)).is_continue());
+        assert!(monitor.observe(3, &filled(0.605)).is_break());
+        drop(monitor);
+        assert_eq!(changes.len(), 3);
+        assert!((changes[1].unwrap() - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stop_when_stable_reports_why_it_stopped() {
+        let mut stable = StopWhenStable::new(0.01);
+        let mut monitor = StepMonitor::new().with_observer(&mut stable);
+        monitor.start(&filled(0.0));
+        assert!(monitor.observe(1, &filled(0.5)).is_continue());
+        assert!(monitor.observe(2, &filled(0.505)).is_break());
+        drop(monitor);
+        let Some(Convergence::Stable { step: 2, change }) = stable.outcome() else {
+            panic!("{:?}", stable.outcome());
+        };
+        assert!((change - 0.005).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stop_when_stable_detects_cycles() {
+        let mut stable = StopWhenStable::new(0.01);
+        let mut monitor = StepMonitor::new().with_observer(&mut stable);
+        monitor.start(&filled(0.0));
+        // A three-step cycle: large changes, but the states repeat
+        for (step, value) in [(1, 0.3), (2, 0.6), (3, 0.0), (4, 0.3)] {
+            let flow = monitor.observe(step, &filled(value));
+            assert_eq!(flow.is_break(), step == 4, "step {step}");
+        }
+        drop(monitor);
+        assert_eq!(
+            stable.outcome(),
+            Some(Convergence::Oscillating { step: 4, period: 3 })
+        );
+        let json = serde_json::to_value(stable.outcome()).unwrap();
+        assert_eq!(json["reason"], "oscillating");
+    }
+
+    #[test]
+    fn cycles_longer_than_the_max_period_run_on() {
+        let mut stable = StopWhenStable::new(0.0).with_max_period(2);
+        let mut monitor = StepMonitor::new().with_observer(&mut stable);
+        monitor.start(&filled(0.0));
+        for (step, value) in [(1, 0.3), (2, 0.6), (3, 0.0), (4, 0.3)] {
+            assert!(monitor.observe(step, &filled(value)).is_continue());
+        }
+        // An unchanged field still counts as settled at epsilon 0
+        assert!(monitor.observe(5, &filled(0.3)).is_break());
+        drop(monitor);
+        assert_eq!(
+            stable.outcome(),
+            Some(Convergence::Stable {
+                step: 5,
+                change: 0.0
+            })
+        );
+    }
+
+    #[test]
+    fn monitors_without_change_observers_keep_no_copy() {
+        let mut monitor =
+            StepMonitor::new().with_observer(from_fn(|_: &StepInfo| ControlFlow::Continue(())));
+        monitor.start(&filled(0.0));
+        assert!(monitor.previous.is_none());
+        assert!(!monitor.is_empty());
+        assert!(StepMonitor::default().is_empty());
+    }
+}
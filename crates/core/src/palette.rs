@@ -3,10 +3,23 @@
 //! Interpolation happens in OKLCh space for perceptually uniform gradients.
 //! Hue interpolation uses shortest-arc wrapping to avoid unexpected color
 //! journeys through the color wheel.
+//!
+//! For bulk conversion, [`Palette::sample_fast`] interpolates in an sRGB
+//! table of up to [`LUT_SIZE`] entries, built on first use and cached with
+//! the palette. It rounds to the same 8-bit values as [`Palette::sample`]
+//! at a fraction of the cost, so PNGs are byte-identical either way.
+
+use std::sync::OnceLock;
 
-use crate::color::{oklch_to_srgb, srgb_to_oklch, OkLch, Srgb};
+use crate::color::{
+    linear_to_srgb, nearest_color, oklab_to_linear, oklch_to_oklab, oklch_to_srgb, srgb_to_oklch,
+    OkLch, Srgb,
+};
 use crate::error::EngineError;
 
+/// Most entries in the sRGB lookup table behind [`Palette::sample_fast`].
+pub const LUT_SIZE: usize = 1024;
+
 /// All built-in palette names, kept in sync with `from_name`.
 const BUILTIN_PALETTE_NAMES: &[&str] = &["ocean", "neon", "earth", "monochrome", "vapor", "fire"];
 
@@ -17,6 +30,10 @@ const BUILTIN_PALETTE_NAMES: &[&str] = &["ocean", "neon", "earth", "monochrome",
 #[derive(Debug, Clone)]
 pub struct Palette {
     colors: Vec<OkLch>,
+    /// Whether `sample_fast` reads the lookup table.
+    use_lut: bool,
+    /// Built on first use of `sample_fast`.
+    lut: OnceLock<Lut>,
 }
 
 impl Palette {
@@ -29,7 +46,27 @@ impl Palette {
                 "palette requires at least 1 color".to_string(),
             ));
         }
-        Ok(Self { colors })
+        Ok(Self::from_stops(colors))
+    }
+
+    /// Wraps non-empty stops, with the lookup table enabled.
+    fn from_stops(colors: Vec<OkLch>) -> Self {
+        Self {
+            colors,
+            use_lut: true,
+            lut: OnceLock::new(),
+        }
+    }
+
+    /// Returns this palette with the [`Palette::sample_fast`] lookup table
+    /// enabled (the default) or not.
+    ///
+    /// Without it `sample_fast` is exactly [`Palette::sample`]; with it the
+    /// two agree once rounded to 8 bits, so only callers that need the
+    /// unrounded components (such as 16-bit output) turn it off.
+    pub fn with_lut(mut self, enabled: bool) -> Self {
+        self.use_lut = enabled;
+        self
     }
 
     /// Creates a palette by parsing hex color strings and converting to OKLCh.
@@ -49,6 +86,19 @@ impl Palette {
         Self::new(colors?)
     }
 
+    /// Creates a palette from color strings in any opaque form
+    /// [`parse_color`](crate::color::parse_color) accepts (`#336699`,
+    /// `rgb(51, 102, 153)`, `steelblue`, ...).
+    ///
+    /// Requires at least one color.
+    pub fn from_colors(colors: &[&str]) -> Result<Self, EngineError> {
+        let colors: Result<Vec<OkLch>, EngineError> = colors
+            .iter()
+            .map(|c| c.parse::<Srgb>().map(srgb_to_oklch))
+            .collect();
+        Self::new(colors?)
+    }
+
     /// Returns the number of color stops in this palette.
     pub fn len(&self) -> usize {
         self.colors.len()
@@ -65,11 +115,61 @@ impl Palette {
     /// For a single-color palette, returns that color for any `t`.
     /// The `t` parameter is clamped to [0, 1].
     pub fn sample(&self, t: f64) -> Srgb {
+        oklch_to_srgb(self.sample_oklch(t))
+    }
+
+    /// Samples the palette at `t` like [`Palette::sample`], but by linear
+    /// interpolation in a cached sRGB table of up to [`LUT_SIZE`] entries.
+    ///
+    /// Each component rounds (`(c * 255).round()`) to the same byte as
+    /// `sample`'s: a component that lands too close to a rounding boundary
+    /// for the table's error bound is resampled exactly. Many times cheaper
+    /// per call once the table is built. Falls back to `sample` when the
+    /// table is disabled with [`Palette::with_lut`].
+    pub fn sample_fast(&self, t: f64) -> Srgb {
+        if !self.use_lut {
+            return self.sample(t);
+        }
+        let lut = self.lut.get_or_init(|| Lut::build(self));
+        let segments = self.colors.len() - 1;
+        if segments == 0 {
+            return clamp_srgb(lut.entries[0]);
+        }
+
+        // Same segment choice as `sample_oklch`, then the step within it
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let scaled = t * segments as f64;
+        let segment = (scaled as usize).min(segments - 1);
+        let within = (scaled - segment as f64) * lut.per_segment as f64;
+        let step = (within as usize).min(lut.per_segment - 1);
+        let i = segment * lut.per_segment + step;
+        if lut.exact[i] {
+            return self.sample(t);
+        }
+        let frac = within - step as f64;
+        let (c0, c1) = (lut.entries[i], lut.entries[i + 1]);
+        let c = clamp_srgb(Srgb {
+            r: c0.r + frac * (c1.r - c0.r),
+            g: c0.g + frac * (c1.g - c0.g),
+            b: c0.b + frac * (c1.b - c0.b),
+        });
+        if [c.r, c.g, c.b].into_iter().any(Lut::near_rounding_boundary) {
+            return self.sample(t);
+        }
+        c
+    }
+
+    /// Samples the palette at parameter `t` in [0, 1], returning the
+    /// interpolated color in OKLCh without converting to sRGB.
+    ///
+    /// Useful when the caller adjusts the color further (e.g. hue rotation)
+    /// before the final gamut-clamped conversion.
+    pub fn sample_oklch(&self, t: f64) -> OkLch {
         let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
         let n = self.colors.len();
 
         if n == 1 {
-            return oklch_to_srgb(self.colors[0]);
+            return self.colors[0];
         }
 
         // Map t to segment index and local interpolation factor
@@ -84,7 +184,7 @@ impl Palette {
         let c = c0.c + frac * (c1.c - c0.c);
         let h = interpolate_hue(c0.h, c1.h, frac);
 
-        oklch_to_srgb(OkLch { l, c, h })
+        OkLch { l, c, h }
     }
 
     // -- Palette generators --
@@ -97,7 +197,7 @@ impl Palette {
     /// distributed across the spread.
     pub fn analogous(base: OkLch, spread: f64, count: usize) -> Self {
         if count <= 1 {
-            return Self { colors: vec![base] };
+            return Self::from_stops(vec![base]);
         }
         let colors = (0..count)
             .map(|i| {
@@ -109,59 +209,53 @@ impl Palette {
                 }
             })
             .collect();
-        Self { colors }
+        Self::from_stops(colors)
     }
 
     /// Creates a complementary palette: base and base+180 degrees.
     pub fn complementary(base: OkLch) -> Self {
-        Self {
-            colors: vec![
-                base,
-                OkLch {
-                    l: base.l,
-                    c: base.c,
-                    h: normalize_hue(base.h + 180.0),
-                },
-            ],
-        }
+        Self::from_stops(vec![
+            base,
+            OkLch {
+                l: base.l,
+                c: base.c,
+                h: normalize_hue(base.h + 180.0),
+            },
+        ])
     }
 
     /// Creates a triadic palette: base, base+120, base+240 degrees.
     pub fn triadic(base: OkLch) -> Self {
-        Self {
-            colors: vec![
-                base,
-                OkLch {
-                    l: base.l,
-                    c: base.c,
-                    h: normalize_hue(base.h + 120.0),
-                },
-                OkLch {
-                    l: base.l,
-                    c: base.c,
-                    h: normalize_hue(base.h + 240.0),
-                },
-            ],
-        }
+        Self::from_stops(vec![
+            base,
+            OkLch {
+                l: base.l,
+                c: base.c,
+                h: normalize_hue(base.h + 120.0),
+            },
+            OkLch {
+                l: base.l,
+                c: base.c,
+                h: normalize_hue(base.h + 240.0),
+            },
+        ])
     }
 
     /// Creates a split-complementary palette: base, base+150, base+210 degrees.
     pub fn split_complementary(base: OkLch) -> Self {
-        Self {
-            colors: vec![
-                base,
-                OkLch {
-                    l: base.l,
-                    c: base.c,
-                    h: normalize_hue(base.h + 150.0),
-                },
-                OkLch {
-                    l: base.l,
-                    c: base.c,
-                    h: normalize_hue(base.h + 210.0),
-                },
-            ],
-        }
+        Self::from_stops(vec![
+            base,
+            OkLch {
+                l: base.l,
+                c: base.c,
+                h: normalize_hue(base.h + 150.0),
+            },
+            OkLch {
+                l: base.l,
+                c: base.c,
+                h: normalize_hue(base.h + 210.0),
+            },
+        ])
     }
 
     /// Creates a gradient palette with `count` colors evenly spaced between
@@ -170,9 +264,7 @@ impl Palette {
     /// Uses shortest-arc hue interpolation. Requires `count >= 1`.
     pub fn gradient(start: OkLch, end: OkLch, count: usize) -> Self {
         if count <= 1 {
-            return Self {
-                colors: vec![start],
-            };
+            return Self::from_stops(vec![start]);
         }
         let colors = (0..count)
             .map(|i| {
@@ -184,7 +276,7 @@ impl Palette {
                 }
             })
             .collect();
-        Self { colors }
+        Self::from_stops(colors)
     }
 
     // -- Built-in palettes --
@@ -225,6 +317,34 @@ impl Palette {
             .expect("fire palette hex values are valid")
     }
 
+    // -- Color lookup --
+
+    /// Returns the stops as displayed, i.e. converted to (clamped) sRGB.
+    pub fn stops_srgb(&self) -> Vec<Srgb> {
+        self.colors.iter().map(|&c| oklch_to_srgb(c)).collect()
+    }
+
+    /// Finds the stop perceptually closest to `color`, returning its index
+    /// and ΔE-OK (see [`delta_e_ok`](crate::color::delta_e_ok)).
+    ///
+    /// Returns `None` only for an empty palette, which the constructors
+    /// never build.
+    pub fn nearest_stop(&self, color: Srgb) -> Option<(usize, f64)> {
+        nearest_color(color, &self.stops_srgb())
+    }
+
+    /// Returns the smallest ΔE-OK between any two stops, or `None` for a
+    /// single-color palette.
+    ///
+    /// Values below about 0.02 mean two stops are practically
+    /// indistinguishable, which is worth flagging in hand-made palettes.
+    pub fn min_stop_delta_e(&self) -> Option<f64> {
+        let stops = self.stops_srgb();
+        (1..stops.len())
+            .filter_map(|i| nearest_color(stops[i], &stops[..i]).map(|(_, d)| d))
+            .reduce(f64::min)
+    }
+
     // -- Registry --
 
     /// Returns a slice of all built-in palette names.
@@ -248,6 +368,92 @@ impl Palette {
     }
 }
 
+/// Lookup table behind [`Palette::sample_fast`].
+///
+/// Every stop lands on an entry, so no interval straddles the kink at a
+/// stop. Entries are unclamped so interpolation follows the smooth curve
+/// into out-of-gamut colors, clamping afterwards as `sample` does. Where
+/// the curve bends too sharply to interpolate (mostly near zero, where
+/// the sRGB transfer curve is steep) an interval is sampled exactly.
+///
+/// Interpolated components are within [`Lut::MARGIN`] of the exact ones,
+/// so any component at least that far from an 8-bit rounding boundary
+/// rounds to the same byte; the rest are resampled exactly.
+#[derive(Debug, Clone)]
+struct Lut {
+    /// Table steps per palette segment.
+    per_segment: usize,
+    entries: Vec<Srgb>,
+    /// Per interval: whether to bypass the table.
+    exact: Vec<bool>,
+}
+
+impl Lut {
+    /// Bound on the interpolation error of a table interval, and the
+    /// distance from a rounding boundary below which a component is
+    /// resampled exactly.
+    const MARGIN: f64 = 0.02 / 255.0;
+
+    /// Largest error measured inside an interval that still counts as
+    /// within [`Lut::MARGIN`]; the error of interpolating a smooth curve
+    /// peaks near the middle, and half the margin leaves room for the
+    /// points between the probes.
+    const TOLERANCE: f64 = Self::MARGIN / 2.0;
+
+    /// Fractions of an interval where the interpolation error is measured.
+    const PROBES: [f64; 3] = [0.25, 0.5, 0.75];
+
+    /// Whether `c` is within [`Lut::MARGIN`] of rounding to a different byte.
+    fn near_rounding_boundary(c: f64) -> bool {
+        let level = c * 255.0;
+        (level - level.floor() - 0.5).abs() <= Self::MARGIN * 255.0
+    }
+
+    fn build(palette: &Palette) -> Self {
+        let segments = palette.colors.len() - 1;
+        let per_segment = ((LUT_SIZE - 1) / segments.max(1)).max(1);
+        let last = (segments * per_segment).max(1) as f64;
+        let unclamped = |t: f64| {
+            let c = palette.sample_oklch(t);
+            linear_to_srgb(oklab_to_linear(oklch_to_oklab(c)))
+        };
+        let entries: Vec<Srgb> = (0..=segments * per_segment)
+            .map(|i| unclamped(i as f64 / last))
+            .collect();
+        let exact = entries
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                Self::PROBES.iter().any(|&f| {
+                    let got = clamp_srgb(Srgb {
+                        r: pair[0].r + f * (pair[1].r - pair[0].r),
+                        g: pair[0].g + f * (pair[1].g - pair[0].g),
+                        b: pair[0].b + f * (pair[1].b - pair[0].b),
+                    });
+                    let want = palette.sample((i as f64 + f) / last);
+                    [got.r - want.r, got.g - want.g, got.b - want.b]
+                        .iter()
+                        .any(|d| d.abs() > Self::TOLERANCE)
+                })
+            })
+            .collect();
+        Self {
+            per_segment,
+            entries,
+            exact,
+        }
+    }
+}
+
+/// Clamps each component to [0, 1].
+fn clamp_srgb(c: Srgb) -> Srgb {
+    Srgb {
+        r: c.r.clamp(0.0, 1.0),
+        g: c.g.clamp(0.0, 1.0),
+        b: c.b.clamp(0.0, 1.0),
+    }
+}
+
 /// Interpolates hue using shortest-arc logic, handling wraparound at 360.
 fn interpolate_hue(h0: f64, h1: f64, t: f64) -> f64 {
     let delta = match h1 - h0 {
@@ -312,6 +518,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn from_colors_accepts_any_opaque_color_string() {
+        let palette = Palette::from_colors(&["navy", "rgb(0, 128, 128)", "#fc0"]).unwrap();
+        assert_eq!(palette.len(), 3);
+        let teal = Srgb::from_hex("#008080").unwrap();
+        assert_eq!(palette.nearest_stop(teal).unwrap().0, 1);
+        assert!(Palette::from_colors(&[]).is_err());
+        assert!(Palette::from_colors(&["navy", "#00008080"]).is_err());
+    }
+
     // -- Sampling tests --
 
     #[test]
@@ -415,6 +631,62 @@ mod tests {
         assert!(approx_eq(at_one.b, above.b));
     }
 
+    #[test]
+    fn sample_oklch_matches_sample_after_conversion() {
+        let palette = Palette::vapor();
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            let via_oklch = oklch_to_srgb(palette.sample_oklch(t));
+            let direct = palette.sample(t);
+            assert!(approx_eq(via_oklch.r, direct.r), "r diverged at t={t}");
+            assert!(approx_eq(via_oklch.g, direct.g), "g diverged at t={t}");
+            assert!(approx_eq(via_oklch.b, direct.b), "b diverged at t={t}");
+        }
+    }
+
+    // -- Lookup table tests --
+
+    #[test]
+    fn sample_fast_rounds_to_the_same_bytes_as_sample() {
+        let byte = |v: f64| (v * 255.0).round() as u8;
+        let mut palettes: Vec<Palette> = Palette::list_names()
+            .iter()
+            .map(|name| Palette::from_name(name).unwrap())
+            .collect();
+        palettes.push(Palette::from_hex(&["#000000", "#ff0000", "#00ff80", "#ffffff"]).unwrap());
+        for palette in &palettes {
+            let mut exact_samples = 0;
+            for i in 0..=100_000 {
+                let t = i as f64 / 100_000.0;
+                let (fast, exact) = (palette.sample_fast(t), palette.sample(t));
+                for (f, e) in [(fast.r, exact.r), (fast.g, exact.g), (fast.b, exact.b)] {
+                    assert_eq!(byte(f), byte(e), "{palette:?} at t={t}");
+                }
+                exact_samples += usize::from(fast == exact);
+            }
+            // The table still serves most samples
+            assert!(exact_samples < 50_000, "{exact_samples} exact samples");
+        }
+    }
+
+    #[test]
+    fn sample_fast_is_exact_at_the_ends_and_clamps() {
+        let palette = Palette::fire();
+        assert_eq!(palette.sample_fast(0.0), palette.sample(0.0));
+        assert_eq!(palette.sample_fast(-1.0), palette.sample(0.0));
+        assert_eq!(palette.sample_fast(f64::NAN), palette.sample(0.0));
+        let (fast, exact) = (palette.sample_fast(2.0), palette.sample(1.0));
+        assert!((fast.r - exact.r).abs() < EPSILON && (fast.b - exact.b).abs() < EPSILON);
+    }
+
+    #[test]
+    fn with_lut_false_samples_exactly() {
+        let palette = Palette::vapor().with_lut(false);
+        for t in [0.1, 0.333, 0.5, 0.87] {
+            assert_eq!(palette.sample_fast(t), palette.sample(t));
+        }
+    }
+
     // -- Hue wraparound tests --
 
     #[test]
@@ -657,6 +929,46 @@ mod tests {
         ));
     }
 
+    // -- Color lookup tests --
+
+    #[test]
+    fn nearest_stop_finds_the_closest_gray() {
+        let palette = Palette::monochrome();
+        let (i, d) = palette
+            .nearest_stop(Srgb::from_hex("#7c7c7c").unwrap())
+            .unwrap();
+        assert_eq!(i, 2);
+        assert!(d > 0.0 && d < 0.02, "ΔE-OK {d}");
+        let (i, d) = palette
+            .nearest_stop(Srgb::from_hex("#ffffff").unwrap())
+            .unwrap();
+        assert_eq!(i, 4);
+        assert!(d < EPSILON);
+    }
+
+    #[test]
+    fn min_stop_delta_e_flags_near_duplicate_stops() {
+        assert_eq!(
+            Palette::from_hex(&["#336699"]).unwrap().min_stop_delta_e(),
+            None
+        );
+        let spread = Palette::monochrome().min_stop_delta_e().unwrap();
+        assert!(spread > 0.1, "monochrome stops ΔE-OK {spread}");
+        let dupes = Palette::from_hex(&["#000000", "#336699", "#336698"]).unwrap();
+        assert!(dupes.min_stop_delta_e().unwrap() < 0.01);
+    }
+
+    #[test]
+    fn built_in_palettes_have_distinct_stops() {
+        for name in Palette::list_names() {
+            let d = Palette::from_name(name)
+                .unwrap()
+                .min_stop_delta_e()
+                .unwrap();
+            assert!(d > 0.02, "{name}: stops only {d} apart");
+        }
+    }
+
     // -- Built-in palette tests --
 
     #[test]
@@ -750,7 +1062,7 @@ mod tests {
             ) {
                 let h = interpolate_hue(h0, h1, t);
                 prop_assert!(
-                    h >= 0.0 && h < 360.0,
+                    (0.0..360.0).contains(&h),
                     "hue {} out of [0, 360) for h0={h0}, h1={h1}, t={t}", h
                 );
             }
@@ -759,7 +1071,7 @@ mod tests {
             fn normalize_hue_always_in_range(h in -1000.0_f64..1000.0) {
                 let n = normalize_hue(h);
                 prop_assert!(
-                    n >= 0.0 && n < 360.0,
+                    (0.0..360.0).contains(&n),
                     "normalize_hue({h}) = {n}, not in [0, 360)"
                 );
             }
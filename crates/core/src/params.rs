@@ -2,15 +2,62 @@
 //!
 //! Each helper takes a JSON value, a key name, and a default. If the key is
 //! missing or the value is not the expected type, the default is returned.
-//! These never fail — they always produce a usable value.
+//! These never fail — they always produce a usable value. A value of the
+//! wrong type is reported as a `debug` tracing event, since it is silently
+//! replaced.
+//!
+//! [`merge_params`] and [`check_fixed`] help engines apply partial updates
+//! mid-run.
+//!
+//! [`Precision`] names the floating-point type an engine simulates in, for
+//! engines that take it as a `precision` parameter.
 
+use crate::error::EngineError;
+use crate::prng::RngAlgorithm;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt::Debug;
+
+/// Floating-point precision an engine runs its simulation in.
+///
+/// Serialized by [`Precision::name`]: `"f64"` or `"f32"`. Output fields are
+/// always `f64`; only the engine's internal state changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    /// 64-bit, the default: existing seeds replay unchanged.
+    #[default]
+    F64,
+    /// 32-bit: half the state memory and better cache behavior on very
+    /// large grids. Results are close to, but not bit-identical with, f64.
+    F32,
+}
+
+impl Precision {
+    /// Every precision, in declaration order.
+    pub const ALL: [Precision; 2] = [Precision::F64, Precision::F32];
+
+    /// The precision's parameter name, e.g. `"f32"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Precision::F64 => "f64",
+            Precision::F32 => "f32",
+        }
+    }
+
+    /// Looks a precision up by [`Precision::name`], ignoring case.
+    pub fn from_name(name: &str) -> Option<Precision> {
+        Self::ALL
+            .into_iter()
+            .find(|precision| precision.name().eq_ignore_ascii_case(name))
+    }
+}
 
 /// Extracts an `f64` from `params[name]`, returning `default` if missing or wrong type.
 ///
 /// Accepts both JSON numbers (including integers) and converts them to f64.
 pub fn param_f64(params: &Value, name: &str, default: f64) -> f64 {
-    params.get(name).and_then(Value::as_f64).unwrap_or(default)
+    extract(params, name, default, Value::as_f64)
 }
 
 /// Extracts a `usize` from `params[name]`, returning `default` if missing or wrong type.
@@ -18,25 +65,105 @@ pub fn param_f64(params: &Value, name: &str, default: f64) -> f64 {
 /// Only succeeds if the JSON value is a non-negative integer that fits in `u64`,
 /// then converts to `usize`.
 pub fn param_usize(params: &Value, name: &str, default: usize) -> usize {
-    params
-        .get(name)
-        .and_then(Value::as_u64)
-        .map(|v| v as usize)
-        .unwrap_or(default)
+    extract(params, name, default, |v| v.as_u64().map(|v| v as usize))
 }
 
 /// Extracts a `bool` from `params[name]`, returning `default` if missing or wrong type.
 pub fn param_bool(params: &Value, name: &str, default: bool) -> bool {
-    params.get(name).and_then(Value::as_bool).unwrap_or(default)
+    extract(params, name, default, Value::as_bool)
 }
 
 /// Extracts a `String` from `params[name]`, returning `default` if missing or wrong type.
 pub fn param_string(params: &Value, name: &str, default: &str) -> String {
-    params
-        .get(name)
-        .and_then(Value::as_str)
-        .map(String::from)
-        .unwrap_or_else(|| default.to_owned())
+    extract(params, name, default.to_owned(), |v| {
+        v.as_str().map(String::from)
+    })
+}
+
+/// Extracts an [`RngAlgorithm`] named by the string `params[name]` (see
+/// [`RngAlgorithm::name`]), returning `default` if missing, not a string,
+/// or not a known algorithm.
+pub fn param_rng(params: &Value, name: &str, default: RngAlgorithm) -> RngAlgorithm {
+    extract(params, name, default, |v| {
+        v.as_str().and_then(RngAlgorithm::from_name)
+    })
+}
+
+/// Extracts a [`Precision`] named by the string `params[name]` (see
+/// [`Precision::name`]), returning `default` if missing, not a string, or
+/// not a known precision.
+pub fn param_precision(params: &Value, name: &str, default: Precision) -> Precision {
+    extract(params, name, default, |v| {
+        v.as_str().and_then(Precision::from_name)
+    })
+}
+
+/// Returns `current` with each key of the `patch` object replacing its
+/// namesake, for engines applying a partial update in
+/// [`Engine::set_params`](crate::Engine::set_params).
+///
+/// Returns `EngineError::ParamTypeMismatch` if `patch` is not an object.
+pub fn merge_params(current: &Value, patch: &Value) -> Result<Value, EngineError> {
+    let Some(patch) = patch.as_object() else {
+        return Err(EngineError::ParamTypeMismatch {
+            name: "params".into(),
+            expected: "object".into(),
+            got: json_type(patch).into(),
+        });
+    };
+    let mut merged = current.clone();
+    if !merged.is_object() {
+        merged = Value::Object(Default::default());
+    }
+    for (key, value) in patch {
+        merged[key] = value.clone();
+    }
+    Ok(merged)
+}
+
+/// Returns `EngineError::Unsupported` if `patch` gives any of the `fixed`
+/// keys a value other than the one in `current`: params that shaped the
+/// engine's state when it was built and cannot change mid-run.
+pub fn check_fixed(current: &Value, patch: &Value, fixed: &[&str]) -> Result<(), EngineError> {
+    for &name in fixed {
+        if let Some(value) = patch.get(name) {
+            if current.get(name) != Some(value) {
+                return Err(EngineError::Unsupported(format!(
+                    "'{name}' cannot change after the engine is built"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Names the JSON type of `value`, for error messages.
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Reads `params[name]` with `get`, falling back to `default` when the key
+/// is missing or `get` rejects the value.
+fn extract<T: Debug>(
+    params: &Value,
+    name: &str,
+    default: T,
+    get: impl FnOnce(&Value) -> Option<T>,
+) -> T {
+    let Some(value) = params.get(name) else {
+        return default;
+    };
+    get(value).unwrap_or_else(|| {
+        tracing::debug!(param = name, %value, ?default, "parameter has the wrong type; using the default");
+        default
+    })
 }
 
 #[cfg(test)]
@@ -166,4 +293,90 @@ mod tests {
         let params = json!({"name": ""});
         assert_eq!(param_string(&params, "name", "default"), "");
     }
+
+    // -- param_rng --
+
+    #[test]
+    fn param_rng_extracts_known_algorithms_by_name() {
+        let params = json!({"rng": "PCG32"});
+        assert_eq!(
+            param_rng(&params, "rng", RngAlgorithm::Xorshift64),
+            RngAlgorithm::Pcg32
+        );
+    }
+
+    #[test]
+    fn param_rng_returns_default_for_unknown_or_mistyped_values() {
+        for params in [json!({}), json!({"rng": "mersenne"}), json!({"rng": 3})] {
+            assert_eq!(
+                param_rng(&params, "rng", RngAlgorithm::SplitMix64),
+                RngAlgorithm::SplitMix64
+            );
+        }
+    }
+
+    // -- param_precision --
+
+    #[test]
+    fn param_precision_extracts_known_precisions_by_name() {
+        let params = json!({"precision": "F32"});
+        assert_eq!(
+            param_precision(&params, "precision", Precision::F64),
+            Precision::F32
+        );
+        assert_eq!(serde_json::to_value(Precision::F32).unwrap(), json!("f32"));
+    }
+
+    #[test]
+    fn param_precision_returns_default_for_unknown_or_mistyped_values() {
+        for params in [
+            json!({}),
+            json!({"precision": "f16"}),
+            json!({"precision": 32}),
+        ] {
+            assert_eq!(
+                param_precision(&params, "precision", Precision::F64),
+                Precision::F64
+            );
+        }
+    }
+
+    // -- merge_params --
+
+    #[test]
+    fn merge_params_replaces_only_patched_keys() {
+        let current = json!({"feed_rate": 0.05, "kill_rate": 0.06});
+        let merged = merge_params(&current, &json!({"kill_rate": 0.07, "dt": 0.5})).unwrap();
+        assert_eq!(
+            merged,
+            json!({"feed_rate": 0.05, "kill_rate": 0.07, "dt": 0.5})
+        );
+    }
+
+    #[test]
+    fn merge_params_rejects_non_object_patch() {
+        let err = merge_params(&json!({}), &json!([1, 2])).unwrap_err();
+        assert!(matches!(err, EngineError::ParamTypeMismatch { ref got, .. } if got == "array"));
+    }
+
+    // -- check_fixed --
+
+    #[test]
+    fn check_fixed_allows_unchanged_fixed_keys() {
+        let current = json!({"species": 3, "mobility": 1.0});
+        check_fixed(
+            &current,
+            &json!({"species": 3, "mobility": 2.0}),
+            &["species"],
+        )
+        .unwrap();
+        check_fixed(&current, &json!({"mobility": 2.0}), &["species"]).unwrap();
+    }
+
+    #[test]
+    fn check_fixed_rejects_changed_fixed_keys() {
+        let err =
+            check_fixed(&json!({"species": 3}), &json!({"species": 5}), &["species"]).unwrap_err();
+        assert!(err.to_string().contains("species"), "{err}");
+    }
 }
@@ -0,0 +1,337 @@
+//! Post-processing effects applied to a final composited image.
+//!
+//! A post stack is an ordered list of [`PostEffect`]s run over the whole
+//! image after compositing, for stylizing an output without external
+//! editing. The functions here are the CPU path used for snapshots;
+//! `render::PostPass` runs the same formulas on the GPU:
+//!
+//! - **Vignette** -- `rgb *= 1 - strength * smoothstep(radius, radius +
+//!   softness, d)`, where `d` is the distance from the center, 1 at the
+//!   corners.
+//! - **Chromatic aberration** -- red is sampled `amount` (a fraction of the
+//!   shorter side, reached at the corners) further out along the radius and
+//!   blue the same distance further in, with bilinear clamp-to-edge
+//!   sampling.
+//! - **Grain** -- adds the same noise to each channel, uniform in
+//!   `[-amount / 2, amount / 2)` and fixed per pixel by `seed`.
+//! - **Scanlines** -- `count` dark lines over the image height, darkening
+//!   by up to `intensity` with a cosine profile.
+//!
+//! Every effect clamps colors to [0, 1] and leaves alpha unchanged.
+
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bloom::Bloom;
+use crate::image::Image;
+use crate::math;
+
+/// A single entry in a post stack.
+///
+/// Serialized with a `"kind"` tag, e.g. `{"kind": "vignette", "strength":
+/// 0.5}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PostEffect {
+    /// Glow around bright areas; see [`Bloom`].
+    Bloom(Bloom),
+    /// Darkening toward the corners.
+    Vignette {
+        strength: f64,
+        #[serde(default = "default_vignette_radius")]
+        radius: f64,
+        #[serde(default = "default_vignette_softness")]
+        softness: f64,
+    },
+    /// Radial red/blue fringing, strongest at the corners.
+    ChromaticAberration { amount: f64 },
+    /// Monochrome film grain.
+    Grain {
+        amount: f64,
+        #[serde(default)]
+        seed: u64,
+    },
+    /// Horizontal CRT-style lines.
+    Scanlines {
+        count: f64,
+        #[serde(default = "default_scanline_intensity")]
+        intensity: f64,
+    },
+}
+
+fn default_vignette_radius() -> f64 {
+    0.5
+}
+
+fn default_vignette_softness() -> f64 {
+    0.5
+}
+
+fn default_scanline_intensity() -> f64 {
+    0.25
+}
+
+impl PostEffect {
+    /// Returns a new image with the effect applied to `image`.
+    pub fn apply(&self, image: &Image) -> Image {
+        let (w, h) = (image.width(), image.height());
+        let mut out = image.clone();
+        match *self {
+            PostEffect::Bloom(bloom) => return bloom.apply(image),
+            PostEffect::Vignette {
+                strength,
+                radius,
+                softness,
+            } => map_pixels(&mut out, |x, y, rgb| {
+                let d = center_distance(x, y, w, h);
+                let factor = 1.0 - strength * smoothstep(radius, radius + softness, d);
+                rgb.map(|c| c * factor)
+            }),
+            PostEffect::ChromaticAberration { amount } => {
+                let (cx, cy) = (w as f64 / 2.0, h as f64 / 2.0);
+                let scale = amount * w.min(h) as f64 / math::hypot(cx, cy);
+                map_pixels(&mut out, |x, y, [_, g, _]| {
+                    let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                    let (dx, dy) = ((px - cx) * scale, (py - cy) * scale);
+                    let r = sample(image, px + dx, py + dy)[0];
+                    let b = sample(image, px - dx, py - dy)[2];
+                    [r, g, b]
+                })
+            }
+            PostEffect::Grain { amount, seed } => {
+                let seed = grain_seed(seed);
+                map_pixels(&mut out, |x, y, rgb| {
+                    let noise = grain_noise(x as u32, y as u32, seed);
+                    rgb.map(|c| c + (noise - 0.5) * amount)
+                })
+            }
+            PostEffect::Scanlines { count, intensity } => map_pixels(&mut out, |_, y, rgb| {
+                let phase = (y as f64 + 0.5) * count / h as f64;
+                let factor = 1.0 - intensity * 0.5 * (1.0 + math::cos(TAU * phase));
+                rgb.map(|c| c * factor)
+            }),
+        }
+        out
+    }
+}
+
+/// Runs every effect of a post stack over `image`, in order.
+pub fn apply_stack(effects: &[PostEffect], image: &Image) -> Image {
+    effects
+        .iter()
+        .fold(image.clone(), |image, effect| effect.apply(&image))
+}
+
+/// Integer hash used for grain noise (mirrored by the GPU shader).
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x
+}
+
+/// Folds a grain seed into the 32-bit value the noise hash mixes in.
+pub(crate) fn grain_seed(seed: u64) -> u32 {
+    hash((seed ^ (seed >> 32)) as u32)
+}
+
+/// Grain noise in [0, 1) for pixel (`x`, `y`) and a hashed seed.
+fn grain_noise(x: u32, y: u32, seed: u32) -> f64 {
+    f64::from(hash(x ^ hash(y ^ seed))) / 4_294_967_296.0
+}
+
+/// Replaces each pixel's color with `f(x, y, rgb)`, clamped to [0, 1].
+fn map_pixels(image: &mut Image, f: impl Fn(usize, usize, [f64; 3]) -> [f64; 3]) {
+    let w = image.width();
+    image
+        .data_mut()
+        .chunks_exact_mut(4)
+        .enumerate()
+        .for_each(|(i, px)| {
+            let rgb = f(i % w, i / w, [px[0], px[1], px[2]]);
+            for c in 0..3 {
+                px[c] = rgb[c].clamp(0.0, 1.0);
+            }
+        });
+}
+
+/// Distance of pixel (`x`, `y`)'s center from the image center, 1 at the
+/// corners.
+fn center_distance(x: usize, y: usize, w: usize, h: usize) -> f64 {
+    let (cx, cy) = (w as f64 / 2.0, h as f64 / 2.0);
+    math::hypot(x as f64 + 0.5 - cx, y as f64 + 0.5 - cy) / math::hypot(cx, cy)
+}
+
+/// GLSL `smoothstep`.
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Bilinear clamp-to-edge sample at pixel coordinates (centers at +0.5).
+pub(crate) fn sample(image: &Image, x: f64, y: f64) -> [f64; 4] {
+    let (w, h) = (image.width() as isize, image.height() as isize);
+    let (x, y) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let at = |x: isize, y: isize| {
+        let (x, y) = (x.clamp(0, w - 1) as usize, y.clamp(0, h - 1) as usize);
+        image.pixel(x, y).unwrap_or_default()
+    };
+    let (x0, y0) = (x0 as isize, y0 as isize);
+    let [a, b, c, d] = [
+        at(x0, y0),
+        at(x0 + 1, y0),
+        at(x0, y0 + 1),
+        at(x0 + 1, y0 + 1),
+    ];
+    std::array::from_fn(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Srgb;
+
+    fn gray(w: usize, h: usize, v: f64) -> Image {
+        Image::filled(w, h, Srgb { r: v, g: v, b: v }, 0.5).unwrap()
+    }
+
+    #[test]
+    fn serde_uses_kind_tags_and_defaults() {
+        let stack: Vec<PostEffect> = serde_json::from_str(
+            r#"[
+                {"kind": "bloom", "intensity": 0.8},
+                {"kind": "vignette", "strength": 0.4},
+                {"kind": "chromatic_aberration", "amount": 0.01},
+                {"kind": "grain", "amount": 0.05},
+                {"kind": "scanlines", "count": 120}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            stack,
+            [
+                PostEffect::Bloom(Bloom::new(0.8)),
+                PostEffect::Vignette {
+                    strength: 0.4,
+                    radius: 0.5,
+                    softness: 0.5,
+                },
+                PostEffect::ChromaticAberration { amount: 0.01 },
+                PostEffect::Grain {
+                    amount: 0.05,
+                    seed: 0,
+                },
+                PostEffect::Scanlines {
+                    count: 120.0,
+                    intensity: 0.25,
+                },
+            ]
+        );
+        let json = serde_json::to_string(&stack).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<PostEffect>>(&json).unwrap(),
+            stack
+        );
+    }
+
+    #[test]
+    fn vignette_darkens_corners_not_the_center() {
+        let image = gray(20, 20, 0.8);
+        let out = PostEffect::Vignette {
+            strength: 0.5,
+            radius: 0.3,
+            softness: 0.5,
+        }
+        .apply(&image);
+        assert_eq!(out.pixel(10, 10).unwrap()[0], 0.8);
+        let corner = out.pixel(0, 0).unwrap();
+        assert!((corner[0] - 0.4).abs() < 1e-12, "{corner:?}");
+        let edge = out.pixel(0, 10).unwrap()[0];
+        assert!(edge > 0.4 && edge < 0.8, "{edge}");
+        assert_eq!(corner[3], 0.5);
+    }
+
+    #[test]
+    fn chromatic_aberration_splits_red_and_blue_at_edges() {
+        let mut image = gray(21, 21, 0.0);
+        image.set_pixel(18, 10, [1.0, 1.0, 1.0, 1.0]).unwrap();
+        let out = PostEffect::ChromaticAberration { amount: 0.2 }.apply(&image);
+        // Red comes from further out, so it appears closer to the center
+        let row: Vec<[f64; 4]> = (0..21).map(|x| out.pixel(x, 10).unwrap()).collect();
+        let peak = |c: usize| {
+            (0..21)
+                .max_by(|&a, &b| row[a][c].total_cmp(&row[b][c]))
+                .unwrap()
+        };
+        assert!(
+            peak(0) < 18 && peak(2) > 18,
+            "red {} blue {}",
+            peak(0),
+            peak(2)
+        );
+        assert_eq!(peak(1), 18);
+        assert_eq!(out.pixel(10, 10), image.pixel(10, 10));
+    }
+
+    #[test]
+    fn grain_is_seeded_and_centered() {
+        let image = gray(64, 64, 0.5);
+        let grain = |seed| PostEffect::Grain { amount: 0.2, seed }.apply(&image);
+        assert_eq!(grain(3), grain(3));
+        assert_ne!(grain(3), grain(4));
+        let out = grain(3);
+        let reds: Vec<f64> = out.data().chunks(4).map(|px| px[0]).collect();
+        let mean = reds.iter().sum::<f64>() / reds.len() as f64;
+        assert!((mean - 0.5).abs() < 0.01, "mean {mean}");
+        assert!(reds.iter().all(|r| (r - 0.5).abs() <= 0.1));
+        let px = out.pixel(5, 7).unwrap();
+        assert_eq!((px[0], px[1], px[3]), (px[2], px[2], 0.5));
+    }
+
+    #[test]
+    fn scanlines_repeat_count_times() {
+        let image = gray(4, 40, 1.0);
+        let out = PostEffect::Scanlines {
+            count: 10.0,
+            intensity: 0.5,
+        }
+        .apply(&image);
+        let column: Vec<f64> = (0..40).map(|y| out.pixel(0, y).unwrap()[0]).collect();
+        for (a, b) in column[..4].iter().zip(&column[4..8]) {
+            assert!((a - b).abs() < 1e-12, "{a} vs {b}");
+        }
+        // Four rows per line sample the profile an eighth of a line off its
+        // darkest point
+        let dark = column.iter().copied().fold(1.0, f64::min);
+        let expected = 1.0 - 0.25 * (1.0 + std::f64::consts::FRAC_1_SQRT_2);
+        assert!((dark - expected).abs() < 1e-12, "{dark}");
+    }
+
+    #[test]
+    fn stack_applies_in_order() {
+        let image = gray(16, 16, 0.5);
+        let stack = [
+            PostEffect::Grain {
+                amount: 0.1,
+                seed: 1,
+            },
+            PostEffect::Vignette {
+                strength: 1.0,
+                radius: 0.0,
+                softness: 1.0,
+            },
+        ];
+        let expected = stack[1].apply(&stack[0].apply(&image));
+        assert_eq!(apply_stack(&stack, &image), expected);
+        assert_eq!(apply_stack(&[], &image), image);
+    }
+}
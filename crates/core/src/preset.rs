@@ -0,0 +1,212 @@
+//! Named presets: partial [`Seed`]s worth starting from.
+//!
+//! A [`Preset`] names an engine and a bundle of params, and may also pin a
+//! canvas size, step count and palette. Presets are addressed as
+//! `engine/name` (`gray-scott/mitosis`); anything a preset leaves unset
+//! keeps its usual default, and flags given explicitly override it.
+//!
+//! ```json
+//! {
+//!   "engine": "gray-scott",
+//!   "description": "Dividing cells",
+//!   "params": { "feed_rate": 0.0367, "kill_rate": 0.0649 },
+//!   "steps": 4000,
+//!   "palette": "neon"
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::EngineError;
+use crate::seed::Seed;
+
+/// A seed fragment: an engine and params, plus optional canvas settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    /// Engine the params belong to.
+    pub engine: String,
+    /// One line on what the preset looks like.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Partial params object, merged over the engine's defaults.
+    #[serde(default = "empty_params")]
+    pub params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<usize>,
+    /// Palette name or inline hex list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+}
+
+fn empty_params() -> Value {
+    Value::Object(Default::default())
+}
+
+impl Preset {
+    /// A preset holding only an engine and params.
+    pub fn new(engine: &str, params: Value) -> Self {
+        Self {
+            engine: engine.to_string(),
+            description: None,
+            params,
+            width: None,
+            height: None,
+            steps: None,
+            palette: None,
+        }
+    }
+
+    /// Returns this preset with a description.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Parses and validates preset JSON.
+    ///
+    /// Returns `EngineError::InvalidPreset` for malformed JSON or a preset
+    /// that fails [`Preset::validate`].
+    pub fn from_json(json: &str) -> Result<Self, EngineError> {
+        let preset: Preset =
+            serde_json::from_str(json).map_err(|e| EngineError::InvalidPreset(e.to_string()))?;
+        preset.validate()?;
+        Ok(preset)
+    }
+
+    /// Checks invariants that serde alone cannot enforce.
+    ///
+    /// Returns `EngineError::InvalidPreset` if the engine name is empty or
+    /// contains `/`, the params are not an object, or a size is zero.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        let invalid = |msg: &str| Err(EngineError::InvalidPreset(msg.into()));
+        if self.engine.is_empty() || self.engine.contains('/') {
+            return invalid("engine must be a plain engine name");
+        }
+        if !self.params.is_object() {
+            return invalid("params must be a JSON object");
+        }
+        if self.width == Some(0) || self.height == Some(0) {
+            return invalid("width and height must be non-zero");
+        }
+        Ok(())
+    }
+
+    /// Fills the preset out to a full seed, taking the size, step count
+    /// and palette from `base` wherever the preset leaves them unset.
+    ///
+    /// The preset's params are merged over `base`'s (when that is an
+    /// object) and the engine is always the preset's.
+    pub fn apply(&self, base: Seed) -> Seed {
+        let params = match (&base.params, &self.params) {
+            (Value::Object(under), Value::Object(over)) => {
+                let mut merged = under.clone();
+                merged.extend(over.clone());
+                Value::Object(merged)
+            }
+            _ => self.params.clone(),
+        };
+        Seed {
+            engine: self.engine.clone(),
+            width: self.width.unwrap_or(base.width),
+            height: self.height.unwrap_or(base.height),
+            steps: self.steps.unwrap_or(base.steps),
+            palette: self.palette.clone().or(base.palette),
+            params,
+            ..base
+        }
+    }
+}
+
+/// Splits a preset id into its engine and preset name.
+///
+/// Returns `None` unless `id` is `engine/name` with both parts non-empty.
+pub fn split_id(id: &str) -> Option<(&str, &str)> {
+    id.split_once('/')
+        .filter(|(engine, name)| !engine.is_empty() && !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_a_full_preset() {
+        let preset = Preset::from_json(
+            r#"{"engine": "gray-scott", "description": "Dividing cells",
+                "params": {"feed_rate": 0.0367}, "width": 64, "steps": 10, "palette": "neon"}"#,
+        )
+        .unwrap();
+        assert_eq!(preset.engine, "gray-scott");
+        assert_eq!(preset.description.as_deref(), Some("Dividing cells"));
+        assert_eq!((preset.width, preset.height), (Some(64), None));
+        assert_eq!(preset.steps, Some(10));
+        assert_eq!(preset.palette.as_deref(), Some("neon"));
+    }
+
+    #[test]
+    fn params_default_to_an_empty_object() {
+        let preset = Preset::from_json(r#"{"engine": "dla"}"#).unwrap();
+        assert_eq!(preset, Preset::new("dla", json!({})));
+        let json = serde_json::to_value(&preset).unwrap();
+        assert_eq!(json, json!({"engine": "dla", "params": {}}));
+    }
+
+    #[test]
+    fn rejects_invalid_presets() {
+        let err = |json: &str| Preset::from_json(json).unwrap_err();
+        assert!(matches!(err("{}"), EngineError::InvalidPreset(_)));
+        assert!(matches!(
+            err(r#"{"engine": "dla", "seed": 4}"#),
+            EngineError::InvalidPreset(_)
+        ));
+        assert!(matches!(
+            err(r#"{"engine": "a/b"}"#),
+            EngineError::InvalidPreset(_)
+        ));
+        assert!(matches!(
+            err(r#"{"engine": "dla", "params": [1]}"#),
+            EngineError::InvalidPreset(_)
+        ));
+        assert!(matches!(
+            err(r#"{"engine": "dla", "height": 0}"#),
+            EngineError::InvalidPreset(_)
+        ));
+    }
+
+    #[test]
+    fn apply_fills_in_only_what_the_preset_sets() {
+        let base = Seed {
+            steps: 100,
+            params: json!({"feed_rate": 0.05, "dt": 0.5}),
+            ..Seed::new("gray-scott", 32, 16, 7)
+        };
+        let preset = Preset {
+            height: Some(64),
+            palette: Some("fire".into()),
+            ..Preset::new("gray-scott", json!({"feed_rate": 0.0367}))
+        };
+        let seed = preset.apply(base);
+        assert_eq!((seed.width, seed.height, seed.steps), (32, 64, 100));
+        assert_eq!(seed.seed, 7);
+        assert_eq!(seed.palette.as_deref(), Some("fire"));
+        assert_eq!(seed.params, json!({"feed_rate": 0.0367, "dt": 0.5}));
+    }
+
+    #[test]
+    fn ids_split_into_engine_and_name() {
+        assert_eq!(
+            split_id("gray-scott/mitosis"),
+            Some(("gray-scott", "mitosis"))
+        );
+        assert_eq!(split_id("mitosis"), None);
+        assert_eq!(split_id("/mitosis"), None);
+        assert_eq!(split_id("gray-scott/"), None);
+    }
+}
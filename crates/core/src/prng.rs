@@ -1,12 +1,191 @@
-//! Deterministic PRNG based on the Xorshift64 algorithm.
+//! Deterministic PRNGs behind a common [`Rng`] trait.
 //!
-//! Provides a fast, seedable pseudo-random number generator suitable for
-//! reproducible generative art. Same seed always produces the same sequence
+//! Provides fast, seedable pseudo-random number generators suitable for
+//! reproducible generative art: [`Xorshift64`] (the default), [`Pcg32`], and
+//! [`SplitMix64`]. Same algorithm and seed always produce the same sequence
 //! of values across all platforms (pure integer arithmetic, no floating point
-//! in the core algorithm).
+//! in the core algorithms). [`RngAlgorithm`] names them so engines can take
+//! the algorithm as a parameter.
+//!
+//! [`derive_seed`] splits one root seed into independent, labeled
+//! sub-streams (`"particles"`, `"palette"`, `"layer-2"`), and each generator
+//! can `fork` a labeled child the same way.
+
+use std::f64::consts::TAU;
 
 use serde::{Deserialize, Serialize};
 
+use crate::math;
+
+/// A deterministic source of random 64-bit values.
+///
+/// Implementors supply [`Rng::next_u64`]; the floating-point, range, and
+/// distribution helpers (gaussian, exponential, weighted choice, Poisson,
+/// shuffle) derive from it identically for every algorithm. Object safe,
+/// so engines can hold a `Box<dyn Rng>` chosen at runtime.
+pub trait Rng {
+    /// Advances the state and returns the next 64-bit value.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a uniformly distributed f64 in [0, 1).
+    ///
+    /// Uses the upper 53 bits of `next_u64()` divided by 2^53 for
+    /// full mantissa precision.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a uniformly distributed f64 in [min, max).
+    fn next_range(&mut self, min: f64, max: f64) -> f64 {
+        min + self.next_f64() * (max - min)
+    }
+
+    /// Returns a uniformly distributed usize in [0, max).
+    ///
+    /// Uses simple modulo reduction. For non-power-of-two `max` values,
+    /// this introduces negligible bias at 64-bit state width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is 0 (division by zero in modulo).
+    fn next_usize(&mut self, max: usize) -> usize {
+        (self.next_u64() as usize) % max
+    }
+
+    /// Returns a standard normal sample (mean 0, standard deviation 1).
+    ///
+    /// Box-Muller transform of two `next_f64()` draws; the second normal
+    /// it yields is discarded so every call consumes exactly two values.
+    fn next_gaussian(&mut self) -> f64 {
+        // 1 - u is in (0, 1], keeping the logarithm finite
+        let radius = (-2.0 * math::ln(1.0 - self.next_f64())).sqrt();
+        radius * math::cos(TAU * self.next_f64())
+    }
+
+    /// Returns an exponential sample with rate `lambda` (mean `1 / lambda`),
+    /// by inverting the CDF of one `next_f64()` draw.
+    ///
+    /// Returns 0 for a non-positive or NaN `lambda`.
+    fn next_exponential(&mut self, lambda: f64) -> f64 {
+        if lambda > 0.0 {
+            -math::ln(1.0 - self.next_f64()) / lambda
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns an index into `weights` drawn with probability proportional
+    /// to its weight, from one `next_f64()` draw.
+    ///
+    /// Negative and NaN weights count as 0. Returns `None` when no weight
+    /// is positive (including an empty slice).
+    fn next_weighted(&mut self, weights: &[f64]) -> Option<usize> {
+        let weight = |w: &f64| if *w > 0.0 { *w } else { 0.0 };
+        let total: f64 = weights.iter().map(weight).sum();
+        if !(total > 0.0 && total.is_finite()) {
+            return None;
+        }
+        let target = self.next_f64() * total;
+        let last = weights.iter().rposition(|w| weight(w) > 0.0)?;
+        // Rounding can leave the running sum just short of `target`; the
+        // last positive weight catches it
+        let index = weights
+            .iter()
+            .scan(0.0, |sum, w| {
+                *sum += weight(w);
+                Some(*sum)
+            })
+            .position(|sum| target < sum);
+        Some(index.unwrap_or(last))
+    }
+
+    /// Returns a Poisson sample with mean `lambda`.
+    ///
+    /// Knuth's product-of-uniforms method, run over chunks of at most
+    /// `POISSON_CHUNK` (the sum of Poisson samples is Poisson) so
+    /// `exp(-lambda)` never underflows; draws about `lambda + 1` values.
+    /// Returns 0 for a non-positive, infinite, or NaN `lambda`.
+    fn next_poisson(&mut self, lambda: f64) -> u64 {
+        if !(lambda > 0.0 && lambda.is_finite()) {
+            return 0;
+        }
+        let chunks = (lambda / POISSON_CHUNK).ceil() as u64;
+        let chunk = lambda / chunks as f64;
+        let limit = math::exp(-chunk);
+        (0..chunks)
+            .map(|_| {
+                let mut product = self.next_f64();
+                let mut count = 0;
+                while product > limit {
+                    product *= self.next_f64();
+                    count += 1;
+                }
+                count
+            })
+            .sum()
+    }
+
+    /// Shuffles `items` in place, every permutation equally likely
+    /// (Fisher-Yates, one `next_usize()` per element after the first).
+    ///
+    /// Needs a sized generator; shuffle through a `Box<dyn Rng>` by
+    /// calling it on the box.
+    fn shuffle<T>(&mut self, items: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.next_usize(i + 1));
+        }
+    }
+}
+
+/// Boxed generators, e.g. from [`RngAlgorithm::build`], are generators too.
+impl<R: Rng + ?Sized> Rng for Box<R> {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// FNV-1a 64-bit offset basis.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a 64-bit prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a hash of `bytes`.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(FNV_OFFSET_BASIS, bytes)
+}
+
+/// Continues the FNV-1a `hash` over more `bytes`:
+/// `fnv1a_extend(fnv1a(a), b)` is the hash of `a` followed by `b`.
+pub fn fnv1a_extend(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The SplitMix64 output function: a bijective 64-bit finalizer under
+/// which nearby inputs give unrelated outputs.
+fn mix64(z: u64) -> u64 {
+    let z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Derives the seed of sub-stream `label` from `root`.
+///
+/// The root is mixed, XORed with the FNV-1a hash of the label, and mixed
+/// again, so streams with different labels, or the same label under
+/// nearby roots, are uncorrelated. Labels are compared byte for byte. The
+/// derivation is part of the replay format and must never change.
+pub fn derive_seed(root: u64, label: &str) -> u64 {
+    mix64(mix64(root) ^ fnv1a(label.as_bytes()))
+}
+
+/// Largest mean [`Rng::next_poisson`] samples in one product of uniforms.
+const POISSON_CHUNK: f64 = 256.0;
+
 /// Xorshift64 deterministic PRNG. Same seed always produces the same sequence.
 ///
 /// Uses the standard shift parameters (13, 7, 17) for good statistical
@@ -32,39 +211,169 @@ impl Xorshift64 {
         }
     }
 
-    /// Advances the state and returns the next 64-bit value.
-    ///
+    /// Returns an independent generator for sub-stream `label`, seeded by
+    /// [`derive_seed`] from the current state. Does not advance `self`.
+    pub fn fork(&self, label: &str) -> Self {
+        Self::new(derive_seed(self.state, label))
+    }
+}
+
+impl Rng for Xorshift64 {
     /// Implements xorshift64 with shifts (13, 7, 17).
-    pub fn next_u64(&mut self) -> u64 {
+    fn next_u64(&mut self) -> u64 {
         self.state ^= self.state << 13;
         self.state ^= self.state >> 7;
         self.state ^= self.state << 17;
         self.state
     }
+}
 
-    /// Returns a uniformly distributed f64 in [0, 1).
-    ///
-    /// Uses the upper 53 bits of `next_u64()` divided by 2^53 for
-    /// full mantissa precision.
-    pub fn next_f64(&mut self) -> f64 {
-        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+/// PCG32 (PCG-XSH-RR, O'Neill 2014): a 64-bit LCG whose output is
+/// permuted down to 32 bits, with better statistical quality than
+/// xorshift.
+///
+/// Each `next_u64` joins two 32-bit outputs, high word first. Any seed,
+/// including 0, is valid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    /// LCG multiplier from the reference implementation.
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+    /// Stream used by [`Pcg32::new`], the reference default increment.
+    const DEFAULT_STREAM: u64 = 1_442_695_040_888_963_407 >> 1;
+
+    /// Creates a new PRNG with the given seed on the default stream.
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, Self::DEFAULT_STREAM)
     }
 
-    /// Returns a uniformly distributed f64 in [min, max).
-    pub fn next_range(&mut self, min: f64, max: f64) -> f64 {
-        min + self.next_f64() * (max - min)
+    /// Creates a new PRNG with the given seed on one of 2^63 independent
+    /// streams, seeded like the reference `pcg32_srandom`.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
     }
 
-    /// Returns a uniformly distributed usize in [0, max).
-    ///
-    /// Uses simple modulo reduction. For non-power-of-two `max` values,
-    /// this introduces negligible bias at 64-bit state width.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `max` is 0 (division by zero in modulo).
-    pub fn next_usize(&mut self, max: usize) -> usize {
-        (self.next_u64() as usize) % max
+    /// Returns an independent generator for sub-stream `label`: the state
+    /// and stream are both derived with [`derive_seed`]. Does not advance
+    /// `self`.
+    pub fn fork(&self, label: &str) -> Self {
+        Self::with_stream(
+            derive_seed(self.state, label),
+            derive_seed(self.increment, label),
+        )
+    }
+
+    /// Advances the state and returns the next 32-bit value.
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+}
+
+impl Rng for Pcg32 {
+    fn next_u64(&mut self) -> u64 {
+        let high = u64::from(self.next_u32());
+        (high << 32) | u64::from(self.next_u32())
+    }
+}
+
+/// SplitMix64 (Steele, Lea, and Flood 2014): a Weyl sequence passed through
+/// a 64-bit mixing function.
+///
+/// Very fast with a full 2^64 period; any seed, including 0, is valid.
+/// Nearby seeds give unrelated sequences, which also makes it a good way to
+/// derive seeds for other generators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Weyl sequence increment (the 64-bit golden ratio).
+    const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    /// Creates a new PRNG with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns an independent generator for sub-stream `label`, seeded by
+    /// [`derive_seed`] from the current state. Does not advance `self`.
+    pub fn fork(&self, label: &str) -> Self {
+        Self::new(derive_seed(self.state, label))
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(Self::GAMMA);
+        mix64(self.state)
+    }
+}
+
+/// Names a PRNG algorithm, so engines can take it as a parameter.
+///
+/// Serialized by [`RngAlgorithm::name`]: `"xorshift64"`, `"pcg32"`, or
+/// `"splitmix64"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RngAlgorithm {
+    /// [`Xorshift64`], the default: existing seeds replay unchanged.
+    #[default]
+    Xorshift64,
+    /// [`Pcg32`] on its default stream.
+    Pcg32,
+    /// [`SplitMix64`].
+    SplitMix64,
+}
+
+impl RngAlgorithm {
+    /// Every algorithm, in declaration order.
+    pub const ALL: [RngAlgorithm; 3] = [
+        RngAlgorithm::Xorshift64,
+        RngAlgorithm::Pcg32,
+        RngAlgorithm::SplitMix64,
+    ];
+
+    /// The algorithm's parameter name, e.g. `"pcg32"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            RngAlgorithm::Xorshift64 => "xorshift64",
+            RngAlgorithm::Pcg32 => "pcg32",
+            RngAlgorithm::SplitMix64 => "splitmix64",
+        }
+    }
+
+    /// Looks an algorithm up by [`RngAlgorithm::name`], ignoring case.
+    pub fn from_name(name: &str) -> Option<RngAlgorithm> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Creates a generator of this algorithm with the given seed.
+    pub fn build(self, seed: u64) -> Box<dyn Rng> {
+        match self {
+            RngAlgorithm::Xorshift64 => Box::new(Xorshift64::new(seed)),
+            RngAlgorithm::Pcg32 => Box::new(Pcg32::new(seed)),
+            RngAlgorithm::SplitMix64 => Box::new(SplitMix64::new(seed)),
+        }
     }
 }
 
@@ -83,6 +392,67 @@ mod tests {
         assert_eq!(rng.next_u64(), 45_454_805_674);
     }
 
+    #[test]
+    fn pcg32_matches_the_reference_implementation() {
+        // First outputs of the reference pcg32-demo: pcg32_srandom(42, 54)
+        let mut rng = Pcg32::with_stream(42, 54);
+        let outputs: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+        assert_eq!(
+            outputs,
+            [
+                0xa15c_02b7,
+                0x7b47_f409,
+                0xba1d_3330,
+                0x83d2_f293,
+                0xbfa4_784b,
+                0xcbed_606e
+            ]
+        );
+    }
+
+    #[test]
+    fn splitmix64_matches_the_reference_implementation() {
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+    }
+
+    #[test]
+    fn every_algorithm_produces_its_golden_values_for_seed_42() {
+        // If this test breaks, an algorithm changed and every replay file
+        // that selects it is invalidated.
+        let golden = [
+            (
+                RngAlgorithm::Xorshift64,
+                [45_454_805_674, 11_532_217_803_599_905_471],
+            ),
+            (
+                RngAlgorithm::Pcg32,
+                [14_048_270_773_501_019_305, 8_266_272_020_994_544_515],
+            ),
+            (
+                RngAlgorithm::SplitMix64,
+                [13_679_457_532_755_275_413, 2_949_826_092_126_892_291],
+            ),
+        ];
+        for (algorithm, expected) in golden {
+            let mut rng = algorithm.build(42);
+            assert_eq!([rng.next_u64(), rng.next_u64()], expected, "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn algorithms_round_trip_through_their_names() {
+        for algorithm in RngAlgorithm::ALL {
+            assert_eq!(RngAlgorithm::from_name(algorithm.name()), Some(algorithm));
+            let json = serde_json::to_string(&algorithm).unwrap();
+            assert_eq!(json, format!("\"{}\"", algorithm.name()));
+        }
+        assert_eq!(RngAlgorithm::from_name("Pcg32"), Some(RngAlgorithm::Pcg32));
+        assert_eq!(RngAlgorithm::from_name("mt19937"), None);
+        assert_eq!(RngAlgorithm::default(), RngAlgorithm::Xorshift64);
+    }
+
     // -- Test 2: Seed=0 guard --
 
     #[test]
@@ -153,6 +523,154 @@ mod tests {
         }
     }
 
+    // -- Distributions --
+
+    #[test]
+    fn gaussian_samples_have_unit_moments() {
+        let mut rng = Xorshift64::new(31);
+        let samples: Vec<f64> = (0..20_000).map(|_| rng.next_gaussian()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.05, "mean {mean}");
+        assert!((variance - 1.0).abs() < 0.05, "variance {variance}");
+        assert!(samples.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn exponential_samples_have_mean_one_over_lambda() {
+        let mut rng = Pcg32::new(5);
+        let mean = (0..20_000).map(|_| rng.next_exponential(4.0)).sum::<f64>() / 20_000.0;
+        assert!((mean - 0.25).abs() < 0.01, "mean {mean}");
+        assert_eq!(rng.next_exponential(0.0), 0.0);
+        assert_eq!(rng.next_exponential(f64::NAN), 0.0);
+    }
+
+    #[test]
+    fn weighted_choice_follows_the_weights() {
+        let mut rng = SplitMix64::new(9);
+        let weights = [1.0, 0.0, 3.0, -2.0, f64::NAN];
+        let mut counts = [0; 5];
+        for _ in 0..8000 {
+            counts[rng.next_weighted(&weights).unwrap()] += 1;
+        }
+        assert_eq!((counts[1], counts[3], counts[4]), (0, 0, 0));
+        let ratio = f64::from(counts[2]) / f64::from(counts[0]);
+        assert!((ratio - 3.0).abs() < 0.3, "ratio {ratio}");
+        assert_eq!(rng.next_weighted(&[]), None);
+        assert_eq!(rng.next_weighted(&[0.0, -1.0]), None);
+    }
+
+    #[test]
+    fn poisson_samples_match_their_mean_at_small_and_large_lambda() {
+        let mut rng = Xorshift64::new(77);
+        for lambda in [0.5, 4.0, 1000.0] {
+            let mean = (0..2000)
+                .map(|_| rng.next_poisson(lambda) as f64)
+                .sum::<f64>()
+                / 2000.0;
+            assert!(
+                (mean - lambda).abs() < 0.1 * lambda.sqrt() + 0.05,
+                "lambda {lambda}: mean {mean}"
+            );
+        }
+        assert_eq!(rng.next_poisson(0.0), 0);
+        assert_eq!(rng.next_poisson(f64::INFINITY), 0);
+    }
+
+    #[test]
+    fn shuffle_permutes_deterministically() {
+        let shuffled = |seed| {
+            let mut items: Vec<u32> = (0..20).collect();
+            Xorshift64::new(seed).shuffle(&mut items);
+            items
+        };
+        let a = shuffled(3);
+        assert_eq!(a, shuffled(3));
+        assert_ne!(a, shuffled(4));
+        assert_ne!(a, (0..20).collect::<Vec<_>>());
+        let mut sorted = a.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+        // Boxed generators shuffle too, identically to the concrete type
+        let mut items: Vec<u32> = (0..20).collect();
+        RngAlgorithm::Xorshift64.build(3).shuffle(&mut items);
+        assert_eq!(items, a);
+        Xorshift64::new(1).shuffle::<u8>(&mut []);
+    }
+
+    #[test]
+    fn distributions_produce_their_golden_values() {
+        // If this test breaks, a distribution changed and every replay
+        // that draws from it is invalidated.
+        let mut rng = Xorshift64::new(42);
+        assert_eq!(rng.next_gaussian(), -0.000_049_589_005_425_150_234);
+        assert_eq!(rng.next_exponential(2.0), 0.391_822_788_856_701_46);
+        assert_eq!(rng.next_weighted(&[1.0, 2.0, 3.0]), Some(0));
+        assert_eq!(rng.next_poisson(3.0), 4);
+    }
+
+    // -- Sub-streams --
+
+    #[test]
+    fn derived_seeds_are_stable() {
+        assert_eq!(derive_seed(42, "particles"), 10_717_212_384_620_330_546);
+        assert_eq!(derive_seed(42, "palette"), 7_766_728_754_772_839_340);
+        assert_eq!(derive_seed(0, ""), mix64(mix64(0) ^ 0xcbf2_9ce4_8422_2325));
+    }
+
+    #[test]
+    fn derived_streams_are_uncorrelated() {
+        // Seeds for two labels, and for neighbouring roots, should differ
+        // in about half their bits
+        let mean_distance = |pairs: &mut dyn Iterator<Item = (u64, u64)>| {
+            let distances: Vec<u32> = pairs.map(|(a, b)| (a ^ b).count_ones()).collect();
+            f64::from(distances.iter().sum::<u32>()) / distances.len() as f64
+        };
+        let labels = mean_distance(
+            &mut (0..2000).map(|root| (derive_seed(root, "a"), derive_seed(root, "b"))),
+        );
+        let roots = mean_distance(
+            &mut (0..2000).map(|root| (derive_seed(root, "a"), derive_seed(root + 1, "a"))),
+        );
+        assert!((labels - 32.0).abs() < 0.5, "labels: {labels}");
+        assert!((roots - 32.0).abs() < 0.5, "roots: {roots}");
+
+        // Outputs of sibling streams should not track each other
+        let mut a = Xorshift64::new(derive_seed(7, "particles"));
+        let mut b = Xorshift64::new(derive_seed(7, "palette"));
+        let n = 10_000;
+        let covariance = (0..n)
+            .map(|_| (a.next_f64() - 0.5) * (b.next_f64() - 0.5))
+            .sum::<f64>()
+            / f64::from(n);
+        // Independent uniforms: mean 0, standard deviation 1/12/sqrt(n)
+        assert!(covariance.abs() < 0.004, "covariance: {covariance}");
+    }
+
+    #[test]
+    fn forks_are_deterministic_and_leave_the_parent_alone() {
+        let parent = Xorshift64::new(42);
+        let mut fork_a = parent.fork("layer-1");
+        let mut fork_b = parent.fork("layer-1");
+        assert_eq!(fork_a.next_u64(), fork_b.next_u64());
+        assert_ne!(
+            parent.fork("layer-1").next_u64(),
+            parent.fork("layer-2").next_u64()
+        );
+        let mut parent = parent;
+        assert_eq!(parent.next_u64(), Xorshift64::new(42).next_u64());
+
+        let pcg = Pcg32::new(42);
+        assert_eq!(pcg.fork("x").next_u64(), pcg.fork("x").next_u64());
+        assert_ne!(pcg.fork("x").next_u64(), pcg.fork("y").next_u64());
+        let splitmix = SplitMix64::new(42);
+        assert_eq!(
+            splitmix.fork("x").next_u64(),
+            SplitMix64::new(derive_seed(42, "x")).next_u64()
+        );
+    }
+
     // -- Serialization roundtrip --
 
     #[test]
@@ -179,6 +697,8 @@ mod tests {
 
     mod proptests {
         use super::*;
+        // The prelude also exports a `Rng` trait
+        use super::Rng;
         use proptest::prelude::*;
 
         proptest! {
@@ -196,6 +716,20 @@ mod tests {
                 }
             }
 
+            #[test]
+            fn next_f64_in_unit_interval_for_every_algorithm(seed: u64) {
+                for algorithm in RngAlgorithm::ALL {
+                    let mut rng = algorithm.build(seed);
+                    for _ in 0..100 {
+                        let v = rng.next_f64();
+                        prop_assert!(
+                            (0.0..1.0).contains(&v),
+                            "{algorithm:?} next_f64() = {v} out of [0, 1) for seed {seed}"
+                        );
+                    }
+                }
+            }
+
             // -- Test 8: next_range in bounds for any seed and range --
 
             #[test]
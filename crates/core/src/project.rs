@@ -0,0 +1,358 @@
+//! Versioned project files: a reproducible multi-layer artwork.
+//!
+//! A [`Project`] bundles a [`Canvas`] (layers with engine bindings, masks,
+//! transforms and effects), custom named palettes, a global seed, a step
+//! count, a post-processing stack and optional feedback trails for
+//! animations. Projects are plain serde data, so they load from JSON here and from
+//! any other serde format (e.g. TOML) in front ends.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "seed": 7,
+//!   "steps": 500,
+//!   "palettes": { "dusk": ["#1b1b3a", "#693668", "#f7b267"] },
+//!   "canvas": { "width": 512, "height": 512, "background": "#000000", "layers": [...] },
+//!   "post": [{ "kind": "bloom", "intensity": 0.8 }, { "kind": "vignette", "strength": 0.4 }],
+//!   "feedback": { "decay": 0.92, "zoom": 1.01, "rotation": 0.005 }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::Canvas;
+use crate::color::{srgb_to_oklch, Srgb};
+use crate::effect::Effect;
+use crate::error::EngineError;
+use crate::feedback::Feedback;
+use crate::palette::Palette;
+use crate::post::PostEffect;
+
+/// Newest project schema version this crate reads and writes.
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A complete, reproducible multi-layer artwork description.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Project {
+    /// Schema version; see [`PROJECT_VERSION`].
+    pub version: u32,
+    /// Global seed, combined with each engine binding's own seed.
+    #[serde(default)]
+    pub seed: u64,
+    /// Number of simulation steps to run before rendering.
+    #[serde(default)]
+    pub steps: usize,
+    /// Custom palettes by name; these shadow built-in palettes.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub palettes: BTreeMap<String, Vec<Srgb>>,
+    /// The layer stack to render.
+    pub canvas: Canvas,
+    /// Post effects applied to the composited image, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<PostEffect>,
+    /// Feedback trails carried from frame to frame in animations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<Feedback>,
+}
+
+impl Project {
+    /// Creates a project at the current schema version with seed 0 and no steps.
+    pub fn new(canvas: Canvas) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            seed: 0,
+            steps: 0,
+            palettes: BTreeMap::new(),
+            canvas,
+            post: Vec::new(),
+            feedback: None,
+        }
+    }
+
+    /// Returns a copy with the given global seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns a copy with the given step count.
+    pub fn with_steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Returns a copy with a custom palette registered under `name`.
+    pub fn with_palette(mut self, name: impl Into<String>, colors: Vec<Srgb>) -> Self {
+        self.palettes.insert(name.into(), colors);
+        self
+    }
+
+    /// Returns a copy with `effect` appended to the post stack.
+    pub fn with_post(mut self, effect: PostEffect) -> Self {
+        self.post.push(effect);
+        self
+    }
+
+    /// Returns a copy whose animations use `feedback` trails.
+    pub fn with_feedback(mut self, feedback: Feedback) -> Self {
+        self.feedback = Some(feedback);
+        self
+    }
+
+    /// Parses and validates a JSON project.
+    ///
+    /// Returns `EngineError::InvalidProject` for malformed JSON or any
+    /// [`Project::validate`] failure.
+    pub fn from_json(json: &str) -> Result<Self, EngineError> {
+        let project: Project =
+            serde_json::from_str(json).map_err(|e| EngineError::InvalidProject(e.to_string()))?;
+        project.validate()?;
+        Ok(project)
+    }
+
+    /// Checks invariants that serde alone cannot enforce.
+    ///
+    /// Returns `EngineError::InvalidProject` if the version is unsupported,
+    /// the canvas has zero or overflowing dimensions, layer names repeat, or
+    /// a custom palette is empty.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        if self.version == 0 || self.version > PROJECT_VERSION {
+            return Err(EngineError::InvalidProject(format!(
+                "unsupported version {} (this build reads up to {PROJECT_VERSION})",
+                self.version
+            )));
+        }
+        Canvas::new(
+            self.canvas.width(),
+            self.canvas.height(),
+            self.canvas.background(),
+        )
+        .map_err(|e| EngineError::InvalidProject(e.to_string()))?;
+        self.canvas
+            .validate_names()
+            .map_err(|e| EngineError::InvalidProject(e.to_string()))?;
+        if let Some((name, _)) = self.palettes.iter().find(|(_, colors)| colors.is_empty()) {
+            return Err(EngineError::InvalidProject(format!(
+                "palette '{name}' has no colors"
+            )));
+        }
+        for layer in self.canvas.layers() {
+            let bad = layer.effects().iter().find_map(|effect| {
+                let radius = match effect {
+                    Effect::Blur { radius }
+                    | Effect::Glow { radius, .. }
+                    | Effect::DropShadow { radius, .. } => *radius,
+                };
+                (!(radius.is_finite() && radius >= 0.0)).then_some(radius)
+            });
+            if let Some(radius) = bad {
+                return Err(EngineError::InvalidProject(format!(
+                    "layer '{}': effect radius must be finite and non-negative, got {radius}",
+                    layer.name()
+                )));
+            }
+        }
+        if let Some(feedback) = &self.feedback {
+            if !(feedback.zoom.is_finite() && feedback.zoom > 0.0) {
+                return Err(EngineError::InvalidProject(format!(
+                    "feedback zoom must be positive, got {}",
+                    feedback.zoom
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves a palette name, preferring the project's custom palettes.
+    ///
+    /// Returns `EngineError::UnknownPalette` if the name is neither custom
+    /// nor built in.
+    pub fn palette(&self, name: &str) -> Result<Palette, EngineError> {
+        match self.palettes.get(name) {
+            Some(colors) => Palette::new(colors.iter().copied().map(srgb_to_oklch).collect()),
+            None => Palette::from_name(name),
+        }
+    }
+
+    /// Returns the engine seed for a binding: the binding's own seed offset
+    /// by the global seed, so changing the global seed re-rolls every layer.
+    pub fn layer_seed(&self, binding_seed: u64) -> u64 {
+        self.seed.wrapping_add(binding_seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::{ContentType, EngineBinding, Layer, LayerContent};
+    use crate::mask::Mask;
+
+    fn black() -> Srgb {
+        Srgb {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+        }
+    }
+
+    fn sample() -> Project {
+        let mut canvas = Canvas::new(32, 16, black()).unwrap();
+        canvas
+            .add_layer(
+                Layer::new("rd", ContentType::Field)
+                    .with_content(LayerContent::Engine(EngineBinding::new(
+                        "gray-scott",
+                        3,
+                        "dusk",
+                    )))
+                    .with_mask(Mask::Circle {
+                        cx: 0.5,
+                        cy: 0.5,
+                        radius: 0.4,
+                        feather: 0.1,
+                    })
+                    .with_effect(Effect::Blur { radius: 0.01 }),
+            )
+            .unwrap();
+        Project::new(canvas)
+            .with_seed(7)
+            .with_steps(100)
+            .with_palette(
+                "dusk",
+                vec![
+                    Srgb::from_hex("#1b1b3a").unwrap(),
+                    Srgb::from_hex("#f7b267").unwrap(),
+                ],
+            )
+            .with_post(PostEffect::Vignette {
+                strength: 0.4,
+                radius: 0.5,
+                softness: 0.5,
+            })
+            .with_feedback(Feedback::new(0.9).with_rotation(0.01))
+    }
+
+    // -- Serde --
+
+    #[test]
+    fn json_round_trip() {
+        let project = sample();
+        let json = serde_json::to_string_pretty(&project).unwrap();
+        assert_eq!(Project::from_json(&json).unwrap(), project);
+    }
+
+    #[test]
+    fn optional_fields_default() {
+        let json =
+            r##"{"version":1,"canvas":{"width":4,"height":4,"background":"#000000","layers":[]}}"##;
+        let project = Project::from_json(json).unwrap();
+        assert_eq!(project.seed, 0);
+        assert_eq!(project.steps, 0);
+        assert!(project.palettes.is_empty());
+        assert!(project.post.is_empty());
+        assert!(project.feedback.is_none());
+        let value = serde_json::to_value(&project).unwrap();
+        assert!(value.get("palettes").is_none());
+        assert!(value.get("post").is_none());
+        assert!(value.get("feedback").is_none());
+    }
+
+    #[test]
+    fn malformed_json_is_invalid_project() {
+        assert!(matches!(
+            Project::from_json("{"),
+            Err(EngineError::InvalidProject(_))
+        ));
+    }
+
+    // -- Validation --
+
+    #[test]
+    fn validate_rejects_unsupported_versions() {
+        for version in [0, PROJECT_VERSION + 1] {
+            let mut project = sample();
+            project.version = version;
+            assert!(matches!(
+                project.validate(),
+                Err(EngineError::InvalidProject(msg)) if msg.contains("version")
+            ));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_zero_size_canvas_and_duplicate_layers() {
+        let json =
+            r##"{"version":1,"canvas":{"width":0,"height":4,"background":"#000000","layers":[]}}"##;
+        assert!(Project::from_json(json).is_err());
+
+        let mut value = serde_json::to_value(sample()).unwrap();
+        let layer = value["canvas"]["layers"][0].clone();
+        value["canvas"]["layers"]
+            .as_array_mut()
+            .unwrap()
+            .push(layer);
+        let err = Project::from_json(&value.to_string()).unwrap_err();
+        assert!(format!("{err}").contains("duplicate layer name: rd"));
+    }
+
+    #[test]
+    fn validate_rejects_empty_palette() {
+        let project = sample().with_palette("empty", Vec::new());
+        assert!(matches!(
+            project.validate(),
+            Err(EngineError::InvalidProject(msg)) if msg.contains("empty")
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_non_finite_and_negative_effect_radii() {
+        for radius in [-0.1, f64::NAN, f64::INFINITY] {
+            let mut project = sample();
+            project
+                .canvas
+                .layer_mut("rd")
+                .unwrap()
+                .set_effects(vec![Effect::Blur { radius }]);
+            assert!(matches!(
+                project.validate(),
+                Err(EngineError::InvalidProject(msg)) if msg.contains("effect radius")
+            ));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_feedback_zoom() {
+        assert!(sample().validate().is_ok());
+        let project = sample().with_feedback(Feedback::new(0.9).with_zoom(0.0));
+        assert!(matches!(
+            project.validate(),
+            Err(EngineError::InvalidProject(msg)) if msg.contains("feedback zoom")
+        ));
+    }
+
+    // -- Palettes and seeds --
+
+    #[test]
+    fn custom_palettes_shadow_builtins() {
+        let project = sample().with_palette("fire", vec![Srgb::from_hex("#00ff00").unwrap()]);
+        assert_eq!(
+            project.palette("fire").unwrap().sample(0.5).to_hex(),
+            "#00ff00"
+        );
+        assert_eq!(project.palette("dusk").unwrap().len(), 2);
+        assert!(project.palette("ocean").is_ok());
+        assert!(matches!(
+            project.palette("nope"),
+            Err(EngineError::UnknownPalette(_))
+        ));
+    }
+
+    #[test]
+    fn layer_seed_offsets_binding_seed() {
+        let project = sample();
+        assert_eq!(project.layer_seed(3), 10);
+        assert_eq!(project.with_seed(u64::MAX).layer_seed(2), 1);
+    }
+}
@@ -0,0 +1,502 @@
+//! Anti-aliased targets for GPU-rendered geometry.
+//!
+//! Shapes and particles drawn straight into a [`RenderTarget`] cover each
+//! pixel fully or not at all, so hard edges come out stair-stepped in
+//! exported frames. An `AntiAliasedTarget` is drawn into like any
+//! [`DrawTarget`] and then [resolved](AntiAliasedTarget::resolve) into a
+//! plain target for compositing or read-back, in one of two ways:
+//!
+//! - **MSAA** -- draws land in a multisampled renderbuffer, averaged into
+//!   the output with a framebuffer blit. Exact coverage; preferred.
+//! - **FXAA** -- draws land in a single-sampled target and a fast
+//!   approximate anti-aliasing pass blurs along luma edges. The fallback
+//!   when the target format cannot be multisampled (see
+//!   [`GpuContext::max_samples`]); flat regions pass through unchanged.
+
+use super::context::GpuContext;
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::preprocess::preprocess;
+use super::shader::compile_program;
+use super::simulation::Uniform;
+use super::target::{DrawTarget, RenderTarget, TargetFormat};
+
+/// MSAA samples [`AntiAliasedTarget::new`] asks for by default.
+pub const DEFAULT_SAMPLES: u32 = 4;
+
+/// FXAA over `u_source` (after the widely used FXAA 3.11 "console"
+/// variant): taps along the edge direction found from the four diagonal
+/// lumas, falling back to the narrower average when the wide one
+/// overshoots the local luma range.
+const FXAA_SHADER: &str = r#"#version 300 es
+precision highp float;
+uniform sampler2D u_source;
+uniform float u_dither;
+out vec4 fragColor;
+#include "common.glsl"
+
+const float REDUCE_MIN = 1.0 / 128.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float SPAN_MAX = 8.0;
+
+vec4 at(vec2 p) { return texture(u_source, p / vec2(textureSize(u_source, 0))); }
+float luma(vec4 c) { return dot(c.rgb, vec3(0.299, 0.587, 0.114)); }
+
+void main() {
+    vec2 p = gl_FragCoord.xy;
+    vec4 m = at(p);
+    float lm = luma(m);
+    float nw = luma(at(p + vec2(-1.0, -1.0)));
+    float ne = luma(at(p + vec2(1.0, -1.0)));
+    float sw = luma(at(p + vec2(-1.0, 1.0)));
+    float se = luma(at(p + vec2(1.0, 1.0)));
+    float lo = min(lm, min(min(nw, ne), min(sw, se)));
+    float hi = max(lm, max(max(nw, ne), max(sw, se)));
+
+    vec2 dir = vec2(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+    float reduce = max((nw + ne + sw + se) * (0.25 * REDUCE_MUL), REDUCE_MIN);
+    float scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+    dir = clamp(dir * scale, vec2(-SPAN_MAX), vec2(SPAN_MAX));
+
+    vec4 a = 0.5 * (at(p + dir * (1.0 / 3.0 - 0.5)) + at(p + dir * (2.0 / 3.0 - 0.5)));
+    vec4 b = 0.5 * a + 0.25 * (at(p - dir * 0.5) + at(p + dir * 0.5));
+    float lb = luma(b);
+    vec4 color = (lb < lo || lb > hi) ? a : b;
+    color.rgb += dither(p, u_dither);
+    fragColor = color;
+}
+"#;
+
+/// How an [`AntiAliasedTarget`] smooths edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AntiAlias {
+    /// No smoothing; resolving is free.
+    Off,
+    /// Multisampling with the given sample count (at least 2).
+    Msaa(u32),
+    /// A post-resolve FXAA pass.
+    Fxaa,
+}
+
+impl AntiAlias {
+    /// Picks MSAA with `samples` (capped at `max_samples`) when the format
+    /// supports at least 2 samples, else FXAA; `samples` below 2 turns
+    /// anti-aliasing off.
+    pub fn choose(samples: u32, max_samples: u32) -> Self {
+        if samples < 2 {
+            AntiAlias::Off
+        } else if max_samples >= 2 {
+            AntiAlias::Msaa(samples.min(max_samples))
+        } else {
+            AntiAlias::Fxaa
+        }
+    }
+}
+
+/// The framebuffer draws land in, per mode.
+enum Scene {
+    Direct,
+    Msaa {
+        fbo: glow::Framebuffer,
+        renderbuffer: glow::Renderbuffer,
+    },
+    Fxaa {
+        target: RenderTarget,
+        program: glow::Program,
+        vao: glow::VertexArray,
+    },
+}
+
+/// A draw target whose contents are anti-aliased when resolved.
+pub struct AntiAliasedTarget {
+    scene: Scene,
+    output: RenderTarget,
+    mode: AntiAlias,
+}
+
+impl AntiAliasedTarget {
+    /// Creates a target in the context's [`target_format`] with `samples`
+    /// MSAA samples where supported, FXAA otherwise (see
+    /// [`AntiAlias::choose`]).
+    ///
+    /// [`target_format`]: GpuContext::target_format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a framebuffer, renderbuffer, or the FXAA shader
+    /// cannot be created.
+    pub fn new(gpu: &GpuContext, width: u32, height: u32, samples: u32) -> Result<Self, String> {
+        let format = gpu.target_format();
+        let mode = AntiAlias::choose(samples, gpu.max_samples(format));
+        Self::with_mode(gpu.gl(), width, height, format, mode)
+    }
+
+    /// Creates a target with an explicit mode and format.
+    ///
+    /// # Errors
+    ///
+    /// As for [`AntiAliasedTarget::new`]; MSAA fails if the format cannot
+    /// be multisampled with that many samples.
+    pub fn with_mode(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+        mode: AntiAlias,
+    ) -> Result<Self, String> {
+        let output = RenderTarget::with_format(gl, width, height, format)?;
+        let scene = match mode {
+            AntiAlias::Off => Ok(Scene::Direct),
+            AntiAlias::Msaa(samples) => msaa_scene(gl, width, height, format, samples),
+            AntiAlias::Fxaa => fxaa_scene(gl, width, height, format),
+        };
+        let scene = scene.inspect_err(|_| output.destroy(gl))?;
+        let target = Self {
+            scene,
+            output,
+            mode,
+        };
+        target.clear(gl);
+        Ok(target)
+    }
+
+    /// Returns how this target smooths edges.
+    pub fn mode(&self) -> AntiAlias {
+        self.mode
+    }
+
+    /// Returns the format of the resolved output.
+    pub fn format(&self) -> TargetFormat {
+        self.output.format()
+    }
+
+    /// Returns the resolved output; current after [`resolve`](Self::resolve).
+    pub fn output(&self) -> &RenderTarget {
+        &self.output
+    }
+
+    /// Clears the scene to transparent black.
+    #[allow(unsafe_code)]
+    pub fn clear(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        DrawTarget::bind(self, gl);
+        // SAFETY: the scene framebuffer is bound above.
+        unsafe {
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    /// Anti-aliases everything drawn so far into the output and returns
+    /// it. The scene is kept, so later draws add to it.
+    #[allow(unsafe_code)]
+    pub fn resolve(&self, gl: &glow::Context) -> &RenderTarget {
+        use glow::HasContext;
+
+        let (w, h) = (self.output.width() as i32, self.output.height() as i32);
+        match &self.scene {
+            Scene::Direct => {}
+            // SAFETY: both framebuffers are complete and the same size.
+            Scene::Msaa { fbo, .. } => unsafe {
+                gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(*fbo));
+                gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(self.output.framebuffer()));
+                gl.blit_framebuffer(
+                    0,
+                    0,
+                    w,
+                    h,
+                    0,
+                    0,
+                    w,
+                    h,
+                    glow::COLOR_BUFFER_BIT,
+                    glow::NEAREST,
+                );
+                gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            },
+            Scene::Fxaa {
+                target,
+                program,
+                vao,
+            } => {
+                self.output.bind(gl);
+                // SAFETY: program, vao, and the scene texture are valid; the
+                // scene is not attached to the bound framebuffer.
+                unsafe {
+                    gl.use_program(Some(*program));
+                    gl.active_texture(glow::TEXTURE0);
+                    gl.bind_texture(glow::TEXTURE_2D, Some(target.texture()));
+                    for (name, value) in [
+                        ("u_source", Uniform::Int(0)),
+                        ("u_dither", Uniform::Float(self.format().dither())),
+                    ] {
+                        if let Some(location) = gl.get_uniform_location(*program, name) {
+                            value.upload(gl, &location);
+                        }
+                    }
+                    gl.bind_vertex_array(Some(*vao));
+                    gl.draw_arrays(glow::TRIANGLES, 0, 3);
+                    gl.bind_vertex_array(None);
+                    gl.bind_texture(glow::TEXTURE_2D, None);
+                    gl.use_program(None);
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                }
+            }
+        }
+        &self.output
+    }
+
+    /// Deletes the scene and output.
+    ///
+    /// Like [`RenderTarget::destroy`], call this before dropping the
+    /// target for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        match &self.scene {
+            Scene::Direct => {}
+            // SAFETY: the handles were created in with_mode().
+            Scene::Msaa { fbo, renderbuffer } => unsafe {
+                gl.delete_framebuffer(*fbo);
+                gl.delete_renderbuffer(*renderbuffer);
+            },
+            Scene::Fxaa {
+                target,
+                program,
+                vao,
+            } => {
+                target.destroy(gl);
+                // SAFETY: the handles were created in with_mode().
+                unsafe {
+                    gl.delete_program(*program);
+                    gl.delete_vertex_array(*vao);
+                }
+            }
+        }
+        self.output.destroy(gl);
+    }
+}
+
+impl DrawTarget for AntiAliasedTarget {
+    /// Binds the scene framebuffer: the multisampled one, the FXAA input,
+    /// or the output itself when anti-aliasing is off.
+    #[allow(unsafe_code)]
+    fn bind(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        match &self.scene {
+            Scene::Direct => self.output.bind(gl),
+            Scene::Fxaa { target, .. } => target.bind(gl),
+            // SAFETY: fbo is a complete framebuffer from with_mode().
+            Scene::Msaa { fbo, .. } => unsafe {
+                gl.bind_framebuffer(glow::FRAMEBUFFER, Some(*fbo));
+                gl.viewport(0, 0, self.width() as i32, self.height() as i32);
+            },
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.output.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.output.height()
+    }
+}
+
+/// Creates a multisampled framebuffer of `format`.
+#[allow(unsafe_code)]
+fn msaa_scene(
+    gl: &glow::Context,
+    width: u32,
+    height: u32,
+    format: TargetFormat,
+    samples: u32,
+) -> Result<Scene, String> {
+    use glow::HasContext;
+
+    let internal_format = format.texture_config(width, height).internal_format;
+    // SAFETY: glow wraps raw GL calls as unsafe; everything created here is
+    // released if the framebuffer is incomplete.
+    unsafe {
+        let renderbuffer = gl.create_renderbuffer()?;
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(renderbuffer));
+        gl.renderbuffer_storage_multisample(
+            glow::RENDERBUFFER,
+            samples as i32,
+            internal_format,
+            width as i32,
+            height as i32,
+        );
+        gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+        let fbo = gl
+            .create_framebuffer()
+            .inspect_err(|_| gl.delete_renderbuffer(renderbuffer))?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_renderbuffer(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::RENDERBUFFER,
+            Some(renderbuffer),
+        );
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            gl.delete_framebuffer(fbo);
+            gl.delete_renderbuffer(renderbuffer);
+            return Err(format!(
+                "multisampled framebuffer incomplete: status 0x{status:04X}"
+            ));
+        }
+        Ok(Scene::Msaa { fbo, renderbuffer })
+    }
+}
+
+/// Creates the FXAA input target and program.
+#[allow(unsafe_code)]
+fn fxaa_scene(
+    gl: &glow::Context,
+    width: u32,
+    height: u32,
+    format: TargetFormat,
+) -> Result<Scene, String> {
+    use glow::HasContext;
+
+    let program = preprocess(FXAA_SHADER)
+        .map_err(|e| e.to_string())
+        .and_then(|source| {
+            compile_program(gl, FULLSCREEN_VERTEX_SHADER, &source).map_err(|e| e.to_string())
+        })?;
+    let release = || {
+        // SAFETY: program was linked above and is not in use.
+        unsafe { gl.delete_program(program) };
+    };
+    // SAFETY: glow wraps raw GL calls as unsafe; the program is released on
+    // error.
+    let vao = unsafe { gl.create_vertex_array() }.inspect_err(|_| release())?;
+    let target = RenderTarget::with_format(gl, width, height, format).inspect_err(|_| {
+        release();
+        // SAFETY: vao was created above.
+        unsafe { gl.delete_vertex_array(vao) };
+    })?;
+    Ok(Scene::Fxaa {
+        target,
+        program,
+        vao,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_fall_back_from_msaa_to_fxaa() {
+        assert_eq!(AntiAlias::choose(4, 8), AntiAlias::Msaa(4));
+        assert_eq!(AntiAlias::choose(16, 8), AntiAlias::Msaa(8));
+        assert_eq!(AntiAlias::choose(4, 0), AntiAlias::Fxaa);
+        assert_eq!(AntiAlias::choose(4, 1), AntiAlias::Fxaa);
+        assert_eq!(AntiAlias::choose(1, 8), AntiAlias::Off);
+        assert_eq!(AntiAlias::choose(0, 0), AntiAlias::Off);
+    }
+
+    #[cfg(feature = "headless")]
+    mod gpu {
+        use super::*;
+        use crate::image::Image;
+        use crate::render::{read_image, HeadlessContext};
+
+        const SIZE: u32 = 16;
+
+        /// A white triangle whose long edge is a shallow diagonal.
+        const WEDGE_VERTEX_SHADER: &str = r#"#version 300 es
+void main() {
+    vec2 corners[3] = vec2[3](vec2(-1.0, -1.0), vec2(1.0, -1.0), vec2(1.0, 0.7));
+    gl_Position = vec4(corners[gl_VertexID], 0.0, 1.0);
+}
+"#;
+        const WHITE_SHADER: &str = r#"#version 300 es
+precision highp float;
+out vec4 fragColor;
+void main() { fragColor = vec4(1.0); }
+"#;
+
+        /// The wedge's area in pixels.
+        const WEDGE_AREA: f64 = 0.5 * 16.0 * 16.0 * 0.85;
+
+        #[allow(unsafe_code)]
+        fn draw_wedge(gl: &glow::Context, mode: AntiAlias) -> Image {
+            use glow::HasContext;
+
+            let target =
+                AntiAliasedTarget::with_mode(gl, SIZE, SIZE, TargetFormat::Rgba16F, mode).unwrap();
+            assert_eq!(target.mode(), mode);
+            let program = compile_program(gl, WEDGE_VERTEX_SHADER, WHITE_SHADER).unwrap();
+            DrawTarget::bind(&target, gl);
+            // SAFETY: program and the bound scene framebuffer are valid.
+            unsafe {
+                let vao = gl.create_vertex_array().unwrap();
+                gl.use_program(Some(program));
+                gl.bind_vertex_array(Some(vao));
+                gl.draw_arrays(glow::TRIANGLES, 0, 3);
+                gl.delete_vertex_array(vao);
+                gl.delete_program(program);
+            }
+            let image = read_image(gl, target.resolve(gl)).unwrap();
+            target.destroy(gl);
+            image
+        }
+
+        fn partial(image: &Image) -> usize {
+            image
+                .data()
+                .chunks_exact(4)
+                .filter(|px| px[0] > 0.05 && px[0] < 0.95)
+                .count()
+        }
+
+        fn total(image: &Image) -> f64 {
+            image.data().chunks_exact(4).map(|px| px[0]).sum()
+        }
+
+        #[test]
+        fn aliased_edges_are_hard() {
+            let headless = HeadlessContext::new().unwrap();
+            let image = draw_wedge(headless.gl(), AntiAlias::Off);
+            assert_eq!(partial(&image), 0);
+            assert!((total(&image) - WEDGE_AREA).abs() < 8.0);
+        }
+
+        #[test]
+        fn msaa_resolves_edge_coverage() {
+            let headless = HeadlessContext::new().unwrap();
+            let gpu = headless.gpu();
+            let samples = gpu.max_samples(gpu.target_format());
+            assert!(samples >= 4, "llvmpipe multisamples RGBA16F");
+            let target = AntiAliasedTarget::new(gpu, SIZE, SIZE, DEFAULT_SAMPLES).unwrap();
+            assert_eq!(target.mode(), AntiAlias::Msaa(4));
+            target.destroy(headless.gl());
+
+            let image = draw_wedge(headless.gl(), AntiAlias::Msaa(4));
+            assert!(partial(&image) >= 10, "{} partial pixels", partial(&image));
+            assert!((total(&image) - WEDGE_AREA).abs() < 2.0);
+            // Far from the edge, pixels are fully in or out
+            assert_eq!(image.pixel(15, 0).unwrap(), [1.0; 4]);
+            assert_eq!(image.pixel(0, 15).unwrap(), [0.0; 4]);
+        }
+
+        #[test]
+        fn fxaa_softens_edges_and_keeps_flat_areas() {
+            let headless = HeadlessContext::new().unwrap();
+            let aliased = draw_wedge(headless.gl(), AntiAlias::Off);
+            let image = draw_wedge(headless.gl(), AntiAlias::Fxaa);
+            assert!(partial(&image) >= 10, "{} partial pixels", partial(&image));
+            assert!((total(&image) - WEDGE_AREA).abs() < 8.0);
+            for (x, y) in [(15, 0), (12, 2), (0, 15), (2, 12)] {
+                assert_eq!(image.pixel(x, y), aliased.pixel(x, y), "pixel ({x}, {y})");
+            }
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! GPU bloom pass over float (or RGBA8 fallback) render targets.
+//!
+//! Runs the pipeline described in [`crate::bloom`] -- bright pass, halving
+//! pyramid with a separable binomial blur per level, additive recombine --
+//! one fullscreen draw per step, so its output matches [`Bloom::apply`] on
+//! the CPU up to half-float precision. Intermediate targets are kept
+//! between calls and reallocated only when the size changes.
+
+use crate::bloom::{level_size, Bloom};
+
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::preprocess::preprocess;
+use super::shader::compile_program;
+use super::simulation::Uniform;
+use super::target::{RenderTarget, TargetFormat};
+
+/// Keeps the color above the luma threshold.
+const BRIGHT_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_source;
+uniform float u_threshold;
+out vec4 fragColor;
+void main() {
+    vec3 c = texture(u_source, v_uv).rgb;
+    float luma = dot(c, vec3(0.2126, 0.7152, 0.0722));
+    float keep = luma > 0.0 ? max(luma - u_threshold, 0.0) / luma : 0.0;
+    fragColor = vec4(c * keep, 1.0);
+}
+"#;
+
+/// Halves the source: four bilinear taps half a source texel around the
+/// pixel center.
+const DOWNSAMPLE_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_source;
+out vec4 fragColor;
+void main() {
+    vec2 d = 0.5 / vec2(textureSize(u_source, 0));
+    vec3 sum = texture(u_source, v_uv + vec2(-d.x, -d.y)).rgb
+             + texture(u_source, v_uv + vec2(d.x, -d.y)).rgb
+             + texture(u_source, v_uv + vec2(-d.x, d.y)).rgb
+             + texture(u_source, v_uv + vec2(d.x, d.y)).rgb;
+    fragColor = vec4(sum * 0.25, 1.0);
+}
+"#;
+
+/// One direction of the 5-tap binomial blur with clamped texel fetches,
+/// scaled by `u_weight` and optionally adding the bilinear upsample of the
+/// next smaller level.
+const BLUR_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_source;
+uniform sampler2D u_add;
+uniform vec2 u_direction;
+uniform float u_weight;
+uniform int u_has_add;
+out vec4 fragColor;
+const float WEIGHTS[5] = float[5](0.0625, 0.25, 0.375, 0.25, 0.0625);
+void main() {
+    ivec2 size = textureSize(u_source, 0);
+    ivec2 p = ivec2(gl_FragCoord.xy);
+    ivec2 dir = ivec2(u_direction);
+    vec3 sum = vec3(0.0);
+    for (int k = 0; k < 5; k++) {
+        ivec2 q = clamp(p + (k - 2) * dir, ivec2(0), size - 1);
+        sum += texelFetch(u_source, q, 0).rgb * WEIGHTS[k];
+    }
+    sum *= u_weight;
+    if (u_has_add != 0) {
+        sum += texture(u_add, v_uv).rgb;
+    }
+    fragColor = vec4(sum, 1.0);
+}
+"#;
+
+/// Adds the scaled glow to the source image, dithered by `u_dither`.
+const COMBINE_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_source;
+uniform sampler2D u_glow;
+uniform float u_scale;
+uniform float u_dither;
+out vec4 fragColor;
+#include "common.glsl"
+void main() {
+    vec4 c = texture(u_source, v_uv);
+    vec3 glow = texture(u_glow, v_uv).rgb;
+    vec3 rgb = c.rgb + u_scale * glow;
+    rgb += dither(gl_FragCoord.xy, u_dither);
+    fragColor = vec4(clamp(rgb, 0.0, 1.0), c.a);
+}
+"#;
+
+/// Targets for one pyramid level: the downsampled bright image, the
+/// horizontal blur, and the blurred level plus everything below it.
+struct Level {
+    down: RenderTarget,
+    blur: RenderTarget,
+    sum: RenderTarget,
+}
+
+impl Level {
+    fn destroy(&self, gl: &glow::Context) {
+        for target in [&self.down, &self.blur, &self.sum] {
+            target.destroy(gl);
+        }
+    }
+}
+
+/// Applies [`Bloom`] to a texture on the GPU.
+pub struct BloomPass {
+    bright_program: glow::Program,
+    downsample_program: glow::Program,
+    blur_program: glow::Program,
+    combine_program: glow::Program,
+    vao: glow::VertexArray,
+    bright: RenderTarget,
+    levels: Vec<Level>,
+    output: RenderTarget,
+    format: TargetFormat,
+}
+
+impl BloomPass {
+    /// Compiles the bloom shaders and allocates full-size RGBA16F targets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a shader fails to compile or a target cannot be
+    /// created.
+    pub fn new(gl: &glow::Context, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_format(gl, width, height, TargetFormat::Rgba16F)
+    }
+
+    /// Like [`BloomPass::new`] with targets of the given format.
+    ///
+    /// On RGBA8 the pyramid still fits (each level's sum is kept divided by
+    /// the level count), so the bloom only loses precision, and the output
+    /// is dithered.
+    ///
+    /// # Errors
+    ///
+    /// As for [`BloomPass::new`].
+    #[allow(unsafe_code)]
+    pub fn with_format(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+    ) -> Result<Self, String> {
+        use glow::HasContext;
+
+        let mut programs = Vec::with_capacity(4);
+        for source in [
+            BRIGHT_SHADER,
+            DOWNSAMPLE_SHADER,
+            BLUR_SHADER,
+            COMBINE_SHADER,
+        ] {
+            let program = preprocess(source)
+                .map_err(|e| e.to_string())
+                .and_then(|source| {
+                    compile_program(gl, FULLSCREEN_VERTEX_SHADER, &source)
+                        .map_err(|e| e.to_string())
+                });
+            match program {
+                Ok(program) => programs.push(program),
+                Err(e) => {
+                    // SAFETY: every program in the list was linked above.
+                    programs
+                        .into_iter()
+                        .for_each(|p| unsafe { gl.delete_program(p) });
+                    return Err(e);
+                }
+            }
+        }
+        let release = |programs: &[glow::Program]| {
+            // SAFETY: every program in the list was linked above.
+            programs
+                .iter()
+                .for_each(|&p| unsafe { gl.delete_program(p) });
+        };
+
+        // SAFETY: glow wraps raw GL calls as unsafe; everything created so
+        // far is released on error.
+        let vao = unsafe { gl.create_vertex_array() }.inspect_err(|_| release(&programs))?;
+        let targets = RenderTarget::with_format(gl, width, height, format).and_then(|bright| {
+            match RenderTarget::with_format(gl, width, height, format) {
+                Ok(output) => Ok((bright, output)),
+                Err(e) => {
+                    bright.destroy(gl);
+                    Err(e)
+                }
+            }
+        });
+        let (bright, output) = targets.inspect_err(|_| {
+            release(&programs);
+            // SAFETY: vao was created above.
+            unsafe { gl.delete_vertex_array(vao) };
+        })?;
+
+        Ok(Self {
+            bright_program: programs[0],
+            downsample_program: programs[1],
+            blur_program: programs[2],
+            combine_program: programs[3],
+            vao,
+            bright,
+            levels: Vec::new(),
+            output,
+            format,
+        })
+    }
+
+    /// Returns the format of the pass's targets.
+    pub fn format(&self) -> TargetFormat {
+        self.format
+    }
+
+    /// Returns the target holding the latest result.
+    pub fn output(&self) -> &RenderTarget {
+        &self.output
+    }
+
+    /// Renders `source` with `bloom` applied into [`output`](Self::output).
+    ///
+    /// `source` should be the size the pass was created with (or last
+    /// resized to); it is sampled at pixel centers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if pyramid targets cannot be created.
+    pub fn apply(
+        &mut self,
+        gl: &glow::Context,
+        source: glow::Texture,
+        bloom: &Bloom,
+    ) -> Result<&RenderTarget, String> {
+        let (width, height) = (self.output.width() as usize, self.output.height() as usize);
+        let levels = bloom.levels(width, height);
+        self.ensure_levels(gl, levels)?;
+
+        self.draw(
+            gl,
+            self.bright_program,
+            &self.bright,
+            &[("u_source", source)],
+            &[("u_threshold", Uniform::Float(bloom.threshold as f32))],
+        );
+        for k in 0..levels {
+            let input = match k {
+                0 => self.bright.texture(),
+                _ => self.levels[k - 1].down.texture(),
+            };
+            self.draw(
+                gl,
+                self.downsample_program,
+                &self.levels[k].down,
+                &[("u_source", input)],
+                &[],
+            );
+        }
+        for k in (0..levels).rev() {
+            let level = &self.levels[k];
+            self.draw(
+                gl,
+                self.blur_program,
+                &level.blur,
+                &[("u_source", level.down.texture())],
+                &[
+                    ("u_direction", Uniform::Vec2([1.0, 0.0])),
+                    ("u_weight", Uniform::Float(1.0)),
+                    ("u_has_add", Uniform::Int(0)),
+                ],
+            );
+            let below = self.levels.get(k + 1).filter(|_| k + 1 < levels);
+            let add = below.map_or(level.blur.texture(), |b| b.sum.texture());
+            self.draw(
+                gl,
+                self.blur_program,
+                &level.sum,
+                &[("u_source", level.blur.texture()), ("u_add", add)],
+                &[
+                    ("u_direction", Uniform::Vec2([0.0, 1.0])),
+                    // Averaging as we go keeps the sums in [0, 1] for RGBA8
+                    ("u_weight", Uniform::Float(1.0 / levels as f32)),
+                    ("u_has_add", Uniform::Int(i32::from(below.is_some()))),
+                ],
+            );
+        }
+        self.draw(
+            gl,
+            self.combine_program,
+            &self.output,
+            &[
+                ("u_source", source),
+                ("u_glow", self.levels[0].sum.texture()),
+            ],
+            &[
+                ("u_scale", Uniform::Float(bloom.intensity as f32)),
+                ("u_dither", Uniform::Float(self.format.dither())),
+            ],
+        );
+        Ok(&self.output)
+    }
+
+    /// Reallocates every target at a new size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a target cannot be resized.
+    pub fn resize(&mut self, gl: &glow::Context, width: u32, height: u32) -> Result<(), String> {
+        self.levels.drain(..).for_each(|level| level.destroy(gl));
+        self.bright.resize(gl, width, height)?;
+        self.output.resize(gl, width, height)
+    }
+
+    /// Allocates pyramid levels up to `count`.
+    fn ensure_levels(&mut self, gl: &glow::Context, count: usize) -> Result<(), String> {
+        let (width, height) = (self.output.width() as usize, self.output.height() as usize);
+        while self.levels.len() < count {
+            let (w, h) = level_size(width, height, self.levels.len());
+            let new = || RenderTarget::with_format(gl, w as u32, h as u32, self.format);
+            let down = new()?;
+            let blur = new()?;
+            let sum = new()?;
+            self.levels.push(Level { down, blur, sum });
+        }
+        Ok(())
+    }
+
+    /// Draws a fullscreen triangle into `target` with `program`, binding
+    /// `textures` to consecutive units and setting `uniforms`.
+    #[allow(unsafe_code)]
+    fn draw(
+        &self,
+        gl: &glow::Context,
+        program: glow::Program,
+        target: &RenderTarget,
+        textures: &[(&str, glow::Texture)],
+        uniforms: &[(&str, Uniform)],
+    ) {
+        use glow::HasContext;
+
+        target.bind(gl);
+        // SAFETY: program, vao, and textures are valid handles. No input
+        // texture is attached to `target` (each step reads earlier targets).
+        unsafe {
+            gl.use_program(Some(program));
+            for (unit, (name, texture)) in textures.iter().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(glow::TEXTURE_2D, Some(*texture));
+                if let Some(location) = gl.get_uniform_location(program, name) {
+                    Uniform::Int(unit as i32).upload(gl, &location);
+                }
+            }
+            for (name, value) in uniforms {
+                if let Some(location) = gl.get_uniform_location(program, name) {
+                    value.upload(gl, &location);
+                }
+            }
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.bind_vertex_array(None);
+            for unit in (0..textures.len()).rev() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(glow::TEXTURE_2D, None);
+            }
+            gl.use_program(None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    /// Deletes the programs, vertex array, and every target.
+    ///
+    /// Like [`RenderTarget::destroy`], call this before dropping the pass
+    /// for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: the programs and vao are valid handles from new().
+        unsafe {
+            for program in [
+                self.bright_program,
+                self.downsample_program,
+                self.blur_program,
+                self.combine_program,
+            ] {
+                gl.delete_program(program);
+            }
+            gl.delete_vertex_array(self.vao);
+        }
+        self.bright.destroy(gl);
+        self.output.destroy(gl);
+        self.levels.iter().for_each(|level| level.destroy(gl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bloom::BLUR_KERNEL;
+
+    #[test]
+    fn blur_shader_weights_match_the_cpu_kernel() {
+        let weights = BLUR_KERNEL.map(|w| format!("{w}"));
+        assert!(
+            BLUR_SHADER.contains(&format!("float[5]({})", weights.join(", "))),
+            "shader weights differ from {weights:?}"
+        );
+    }
+
+    #[test]
+    fn bright_shader_uses_rec709_luma() {
+        assert!(BRIGHT_SHADER.contains("vec3(0.2126, 0.7152, 0.0722)"));
+    }
+
+    /// Runs `BloomPass` in `format` and checks it against the CPU path.
+    #[cfg(feature = "headless")]
+    fn assert_matches_cpu(format: TargetFormat, tolerance: f64) {
+        use crate::image::Image;
+        use crate::render::{compositor, HeadlessContext};
+
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        // Odd sizes exercise the clamped edges of every pyramid level
+        let (w, h) = (37, 23);
+        let mut image = Image::new(w, h).unwrap();
+        for (i, px) in image.data_mut().chunks_exact_mut(4).enumerate() {
+            let (x, y) = ((i % w) as f64, (i / w) as f64);
+            let spot = (-((x - 20.0).powi(2) + (y - 9.0).powi(2)) / 8.0).exp();
+            px.copy_from_slice(&[spot, 0.8 * spot, 0.3 + 0.2 * (x / 37.0), 1.0]);
+        }
+        let bloom = Bloom::new(1.2).with_threshold(0.3).with_radius(0.4);
+        assert_eq!(bloom.levels(w, h), 4);
+
+        let texture = compositor::upload_image(gl, &image).unwrap();
+        let mut pass = BloomPass::with_format(gl, w as u32, h as u32, format).unwrap();
+        let output = pass.apply(gl, texture, &bloom).unwrap();
+        let gpu = compositor::read_image(gl, output).unwrap();
+        let cpu = bloom.apply(&image);
+        for (i, (g, c)) in gpu.data().iter().zip(cpu.data()).enumerate() {
+            assert!((g - c).abs() < tolerance, "value {i}: gpu {g} vs cpu {c}");
+        }
+        pass.destroy(gl);
+    }
+
+    #[cfg(feature = "headless")]
+    #[test]
+    fn matches_the_cpu_bloom() {
+        assert_matches_cpu(TargetFormat::Rgba16F, 4e-3);
+    }
+
+    #[cfg(feature = "headless")]
+    #[test]
+    fn rgba8_targets_stay_close_to_the_cpu_bloom() {
+        // Every pyramid step rounds to 1/255 and the output is dithered
+        assert_matches_cpu(TargetFormat::Rgba8, 4.0 / 255.0);
+    }
+}
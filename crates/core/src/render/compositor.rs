@@ -0,0 +1,727 @@
+//! GPU compositing of a [`Canvas`] layer stack.
+//!
+//! The GPU counterpart of [`crate::compositor`]: layers are blended
+//! bottom-to-top onto a composite `RenderTarget` filled with the canvas
+//! background, following the same W3C formulas. `Normal` and `Additive`
+//! layers on the opaque canvas use hardware `blendFunc` fast paths; every
+//! other mode, and every layer inside a group (whose isolated buffer starts
+//! transparent), goes through a blend fragment shader that reads the
+//! backdrop from the other half of a ping-pong pair.
+//!
+//! Layer pixels are supplied by the caller per layer name as straight-alpha
+//! RGBA textures at canvas resolution ([`upload_image`] turns a CPU
+//! [`Image`] into one). Visibility and opacity are respected; layer
+//! transforms, effects and masks are CPU-only for now and are ignored here.
+//!
+//! Additive layers blend in half-float HDR range on the fast path, so they
+//! only clamp when the result is read back; the CPU compositor clamps each
+//! step. A compositor built for [`TargetFormat::Rgba8`] clamps each step
+//! too and dithers the blend shader's output.
+
+use crate::canvas::{BlendMode, Canvas, LayerNode};
+use crate::image::Image;
+
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::ping_pong::PingPong;
+use super::preprocess::preprocess;
+use super::shader::compile_program;
+use super::target::{RenderTarget, TargetFormat};
+use super::texture::{create_texture, TextureConfig};
+
+/// Draws a layer's straight-alpha color with opacity applied to alpha, for
+/// the hardware blend fast paths.
+const SOURCE_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_source;
+uniform float u_opacity;
+out vec4 fragColor;
+void main() {
+    vec4 s = texture(u_source, v_uv);
+    fragColor = vec4(s.rgb, clamp(s.a * u_opacity, 0.0, 1.0));
+}
+"#;
+
+/// Composites a layer over the backdrop with any blend mode. Mode indices
+/// follow [`BlendMode::ALL`]; the formulas mirror `compositor::blend_channel`
+/// and `compositor::composite_pixel`.
+const BLEND_SHADER: &str = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_backdrop;
+uniform sampler2D u_source;
+uniform float u_opacity;
+uniform int u_mode;
+uniform float u_dither;
+out vec4 fragColor;
+#include "common.glsl"
+
+float screen(float cb, float cs) { return cb + cs - cb * cs; }
+
+float hard_light(float cs, float cb) {
+    return cs <= 0.5 ? cb * 2.0 * cs : screen(cb, 2.0 * cs - 1.0);
+}
+
+float color_dodge(float cb, float cs) {
+    if (cb == 0.0) return 0.0;
+    if (cs >= 1.0) return 1.0;
+    return min(cb / (1.0 - cs), 1.0);
+}
+
+float color_burn(float cb, float cs) {
+    if (cb >= 1.0) return 1.0;
+    if (cs <= 0.0) return 0.0;
+    return 1.0 - min((1.0 - cb) / cs, 1.0);
+}
+
+float soft_light(float cb, float cs) {
+    if (cs <= 0.5) return cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb);
+    float d = cb <= 0.25 ? ((16.0 * cb - 12.0) * cb + 4.0) * cb : sqrt(cb);
+    return cb + (2.0 * cs - 1.0) * (d - cb);
+}
+
+float blend(float cb, float cs) {
+    switch (u_mode) {
+        case 0: return cs;
+        case 1: return min(cb + cs, 1.0);
+        case 2: return cb * cs;
+        case 3: return screen(cb, cs);
+        case 4: return hard_light(cb, cs);
+        case 5: return min(cb, cs);
+        case 6: return max(cb, cs);
+        case 7: return color_dodge(cb, cs);
+        case 8: return color_burn(cb, cs);
+        case 9: return soft_light(cb, cs);
+        case 10: return abs(cb - cs);
+        default: return cb + cs - 2.0 * cb * cs;
+    }
+}
+
+void main() {
+    vec4 b = texture(u_backdrop, v_uv);
+    vec4 s = texture(u_source, v_uv);
+    float as_ = clamp(s.a * u_opacity, 0.0, 1.0);
+    float ao = as_ + b.a * (1.0 - as_);
+    if (ao <= 0.0) {
+        fragColor = vec4(0.0);
+        return;
+    }
+    vec3 blended = vec3(blend(b.r, s.r), blend(b.g, s.g), blend(b.b, s.b));
+    vec3 mixed = (1.0 - b.a) * s.rgb + b.a * blended;
+    vec3 rgb = (as_ * mixed + b.a * b.rgb * (1.0 - as_)) / ao;
+    rgb += dither(gl_FragCoord.xy, u_dither);
+    fragColor = vec4(clamp(rgb, 0.0, 1.0), ao);
+}
+"#;
+
+/// Hardware blend factors `(src_rgb, dst_rgb, src_alpha, dst_alpha)` for
+/// `glBlendFuncSeparate`.
+pub type BlendFactors = (u32, u32, u32, u32);
+
+/// Returns the hardware blend factors for modes with a `blendFunc` fast
+/// path over an opaque backdrop, or `None` for modes that need the blend
+/// shader.
+pub fn fast_path(mode: BlendMode) -> Option<BlendFactors> {
+    match mode {
+        BlendMode::Normal => Some((
+            glow::SRC_ALPHA,
+            glow::ONE_MINUS_SRC_ALPHA,
+            glow::ONE,
+            glow::ONE_MINUS_SRC_ALPHA,
+        )),
+        BlendMode::Additive => Some((
+            glow::SRC_ALPHA,
+            glow::ONE,
+            glow::ONE,
+            glow::ONE_MINUS_SRC_ALPHA,
+        )),
+        _ => None,
+    }
+}
+
+/// Returns the `u_mode` value the blend shader uses for `mode`: its index
+/// in [`BlendMode::ALL`].
+pub fn mode_index(mode: BlendMode) -> i32 {
+    match mode {
+        BlendMode::Normal => 0,
+        BlendMode::Additive => 1,
+        BlendMode::Multiply => 2,
+        BlendMode::Screen => 3,
+        BlendMode::Overlay => 4,
+        BlendMode::Darken => 5,
+        BlendMode::Lighten => 6,
+        BlendMode::ColorDodge => 7,
+        BlendMode::ColorBurn => 8,
+        BlendMode::SoftLight => 9,
+        BlendMode::Difference => 10,
+        BlendMode::Exclusion => 11,
+    }
+}
+
+/// Uploads a straight-alpha image as an RGBA16F texture for use as a layer.
+///
+/// # Errors
+///
+/// Returns an error if the texture cannot be created.
+#[allow(unsafe_code)]
+pub fn upload_image(gl: &glow::Context, image: &Image) -> Result<glow::Texture, String> {
+    use glow::HasContext;
+
+    let (width, height) = (image.width() as u32, image.height() as u32);
+    let texture = create_texture(gl, &TextureConfig::rgba16f(width, height))?;
+    let bytes: Vec<u8> = image
+        .data()
+        .iter()
+        .flat_map(|&v| (v as f32).to_ne_bytes())
+        .collect();
+
+    // SAFETY: texture is valid and `bytes` holds width x height RGBA floats.
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            0,
+            0,
+            width as i32,
+            height as i32,
+            glow::RGBA,
+            glow::FLOAT,
+            glow::PixelUnpackData::Slice(Some(&bytes)),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+    Ok(texture)
+}
+
+/// Reads a render target back as a straight-alpha [`Image`], channels
+/// clamped to [0, 1].
+///
+/// Stalls until pending draws into the target finish; use it for export.
+///
+/// # Errors
+///
+/// Returns an error if the target has zero size.
+pub fn read_image(gl: &glow::Context, target: &RenderTarget) -> Result<Image, String> {
+    let (width, height) = (target.width() as usize, target.height() as usize);
+    let mut image = Image::new(width, height).map_err(|e| e.to_string())?;
+    image
+        .data_mut()
+        .iter_mut()
+        .zip(target.read_texels(gl))
+        .for_each(|(v, texel)| *v = f64::from(texel).clamp(0.0, 1.0));
+    Ok(image)
+}
+
+/// A ping-pong pair of targets holding one compositing level: the canvas,
+/// or the isolated buffer of a group at some nesting depth.
+struct Level {
+    targets: [RenderTarget; 2],
+    ping_pong: PingPong,
+}
+
+impl Level {
+    fn new(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+    ) -> Result<Self, String> {
+        let first = RenderTarget::with_format(gl, width, height, format)?;
+        match RenderTarget::with_format(gl, width, height, format) {
+            Ok(second) => Ok(Self {
+                targets: [first, second],
+                ping_pong: PingPong::new(),
+            }),
+            Err(e) => {
+                first.destroy(gl);
+                Err(e)
+            }
+        }
+    }
+
+    /// The target holding this level's composite so far.
+    fn current(&self) -> &RenderTarget {
+        &self.targets[self.ping_pong.src_index()]
+    }
+
+    /// The target the blend shader writes the next composite into.
+    fn next(&self) -> &RenderTarget {
+        &self.targets[self.ping_pong.dst_index()]
+    }
+
+    /// Fills the current target with a color.
+    #[allow(unsafe_code)]
+    fn fill(&self, gl: &glow::Context, rgba: [f32; 4]) {
+        use glow::HasContext;
+
+        self.current().bind(gl);
+        // SAFETY: the current target's framebuffer is bound above.
+        unsafe {
+            gl.clear_color(rgba[0], rgba[1], rgba[2], rgba[3]);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+    }
+
+    fn destroy(&self, gl: &glow::Context) {
+        self.targets.iter().for_each(|target| target.destroy(gl));
+    }
+}
+
+/// Composites canvases on the GPU.
+///
+/// Keeps its render targets between calls: one level for the canvas and
+/// one per group nesting depth seen so far, all at the canvas size.
+pub struct GpuCompositor {
+    source_program: glow::Program,
+    blend_program: glow::Program,
+    vao: glow::VertexArray,
+    levels: Vec<Level>,
+    width: u32,
+    height: u32,
+    format: TargetFormat,
+}
+
+impl GpuCompositor {
+    /// Compiles the compositing shaders and allocates the canvas level on
+    /// RGBA16F targets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a shader fails to compile or a render target
+    /// cannot be created.
+    pub fn new(gl: &glow::Context, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_format(gl, width, height, TargetFormat::Rgba16F)
+    }
+
+    /// Like [`GpuCompositor::new`] with levels of the given format.
+    ///
+    /// # Errors
+    ///
+    /// As for [`GpuCompositor::new`].
+    #[allow(unsafe_code)]
+    pub fn with_format(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+    ) -> Result<Self, String> {
+        use glow::HasContext;
+
+        let source_program = compile_program(gl, FULLSCREEN_VERTEX_SHADER, SOURCE_SHADER)
+            .map_err(|e| e.to_string())?;
+        let blend_program = preprocess(BLEND_SHADER)
+            .map_err(|e| e.to_string())
+            .and_then(|source| {
+                compile_program(gl, FULLSCREEN_VERTEX_SHADER, &source).map_err(|e| e.to_string())
+            });
+        let blend_program = match blend_program {
+            Ok(program) => program,
+            Err(e) => {
+                // SAFETY: source_program is a valid handle from compile_program.
+                unsafe { gl.delete_program(source_program) };
+                return Err(e);
+            }
+        };
+        // SAFETY: glow wraps raw GL calls as unsafe; the programs are
+        // released if the vertex array cannot be created.
+        let vao = match unsafe { gl.create_vertex_array() } {
+            Ok(vao) => vao,
+            Err(e) => {
+                unsafe {
+                    gl.delete_program(source_program);
+                    gl.delete_program(blend_program);
+                }
+                return Err(e);
+            }
+        };
+
+        let mut compositor = Self {
+            source_program,
+            blend_program,
+            vao,
+            levels: Vec::new(),
+            width,
+            height,
+            format,
+        };
+        if let Err(e) = compositor.ensure_level(gl, 0) {
+            compositor.destroy(gl);
+            return Err(e);
+        }
+        Ok(compositor)
+    }
+
+    /// Returns the composite width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the composite height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the format of the compositing targets.
+    pub fn format(&self) -> TargetFormat {
+        self.format
+    }
+
+    /// Renders the canvas: fills the background, then blends each visible
+    /// layer's texture bottom-to-top with its blend mode and opacity.
+    ///
+    /// `layer_texture` supplies a straight-alpha RGBA texture per layer
+    /// name; layers for which it returns `None` are skipped. Targets are
+    /// reallocated if the canvas size changed. Returns the target holding
+    /// the result, valid until the next call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a render target cannot be created.
+    pub fn composite(
+        &mut self,
+        gl: &glow::Context,
+        canvas: &Canvas,
+        layer_texture: impl Fn(&str) -> Option<glow::Texture>,
+    ) -> Result<&RenderTarget, String> {
+        let (width, height) = (canvas.width() as u32, canvas.height() as u32);
+        if (width, height) != (self.width, self.height) {
+            self.levels.drain(..).for_each(|level| level.destroy(gl));
+            self.width = width;
+            self.height = height;
+        }
+        self.ensure_level(gl, 0)?;
+
+        let bg = canvas.background();
+        self.levels[0].fill(gl, [bg.r as f32, bg.g as f32, bg.b as f32, 1.0]);
+        self.composite_nodes(gl, canvas.nodes(), 0, &layer_texture)?;
+        Ok(self.result())
+    }
+
+    /// Returns the target holding the latest composite.
+    pub fn result(&self) -> &RenderTarget {
+        self.levels[0].current()
+    }
+
+    /// Reads the latest composite back as a straight-alpha [`Image`].
+    ///
+    /// Stalls until compositing finishes; use it for export.
+    pub fn read_image(&self, gl: &glow::Context) -> Result<Image, String> {
+        read_image(gl, self.result())
+    }
+
+    /// Allocates levels up to and including `depth`.
+    fn ensure_level(&mut self, gl: &glow::Context, depth: usize) -> Result<(), String> {
+        while self.levels.len() <= depth {
+            self.levels
+                .push(Level::new(gl, self.width, self.height, self.format)?);
+        }
+        Ok(())
+    }
+
+    /// Composites a sibling list of nodes onto the level at `depth`,
+    /// recursing into groups through the level below.
+    fn composite_nodes(
+        &mut self,
+        gl: &glow::Context,
+        nodes: &[LayerNode],
+        depth: usize,
+        layer_texture: &impl Fn(&str) -> Option<glow::Texture>,
+    ) -> Result<(), String> {
+        for node in nodes {
+            match node {
+                LayerNode::Layer(layer) => {
+                    if !layer.visible() || layer.opacity() <= 0.0 {
+                        continue;
+                    }
+                    if let Some(texture) = layer_texture(layer.name()) {
+                        self.blend_onto(gl, depth, texture, layer.blend_mode(), layer.opacity());
+                    }
+                }
+                LayerNode::Group(group) if group.visible() && group.opacity() > 0.0 => {
+                    self.ensure_level(gl, depth + 1)?;
+                    self.levels[depth + 1].fill(gl, [0.0; 4]);
+                    self.composite_nodes(gl, group.children(), depth + 1, layer_texture)?;
+                    let buffer = self.levels[depth + 1].current().texture();
+                    self.blend_onto(gl, depth, buffer, group.blend_mode(), group.opacity());
+                }
+                LayerNode::Group(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Blends `source` onto the level at `depth`, using a hardware fast
+    /// path when the level is the opaque canvas.
+    #[allow(unsafe_code)]
+    fn blend_onto(
+        &mut self,
+        gl: &glow::Context,
+        depth: usize,
+        source: glow::Texture,
+        mode: BlendMode,
+        opacity: f64,
+    ) {
+        use glow::HasContext;
+
+        let fast = fast_path(mode).filter(|_| depth == 0);
+        let level = &mut self.levels[depth];
+
+        // SAFETY: programs, vao, and textures are valid handles owned by
+        // this compositor or supplied by the caller. The blend shader reads
+        // the current target and writes the other, never the same texture.
+        unsafe {
+            let program = match fast {
+                Some((src_rgb, dst_rgb, src_alpha, dst_alpha)) => {
+                    level.current().bind(gl);
+                    gl.enable(glow::BLEND);
+                    gl.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+                    gl.use_program(Some(self.source_program));
+                    self.source_program
+                }
+                None => {
+                    level.next().bind(gl);
+                    gl.use_program(Some(self.blend_program));
+                    gl.active_texture(glow::TEXTURE1);
+                    gl.bind_texture(glow::TEXTURE_2D, Some(level.current().texture()));
+                    let backdrop = gl.get_uniform_location(self.blend_program, "u_backdrop");
+                    gl.uniform_1_i32(backdrop.as_ref(), 1);
+                    let mode_location = gl.get_uniform_location(self.blend_program, "u_mode");
+                    gl.uniform_1_i32(mode_location.as_ref(), mode_index(mode));
+                    let dither = gl.get_uniform_location(self.blend_program, "u_dither");
+                    gl.uniform_1_f32(dither.as_ref(), self.format.dither());
+                    self.blend_program
+                }
+            };
+
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(source));
+            let source_location = gl.get_uniform_location(program, "u_source");
+            gl.uniform_1_i32(source_location.as_ref(), 0);
+            let opacity_location = gl.get_uniform_location(program, "u_opacity");
+            gl.uniform_1_f32(opacity_location.as_ref(), opacity as f32);
+
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+
+            gl.bind_vertex_array(None);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE1);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE0);
+            gl.use_program(None);
+            gl.disable(glow::BLEND);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        if fast.is_none() {
+            level.ping_pong.swap();
+        }
+    }
+
+    /// Deletes the programs, vertex array, and every level's targets.
+    ///
+    /// Like [`RenderTarget::destroy`], call this before dropping the
+    /// compositor for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: the programs and vao are valid handles from new().
+        unsafe {
+            gl.delete_program(self.source_program);
+            gl.delete_program(self.blend_program);
+            gl.delete_vertex_array(self.vao);
+        }
+        self.levels.iter().for_each(|level| level.destroy(gl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_normal_and_additive_have_fast_paths() {
+        for mode in BlendMode::ALL {
+            let expected = matches!(mode, BlendMode::Normal | BlendMode::Additive);
+            assert_eq!(fast_path(mode).is_some(), expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn normal_fast_path_is_source_over() {
+        assert_eq!(
+            fast_path(BlendMode::Normal),
+            Some((
+                glow::SRC_ALPHA,
+                glow::ONE_MINUS_SRC_ALPHA,
+                glow::ONE,
+                glow::ONE_MINUS_SRC_ALPHA
+            ))
+        );
+    }
+
+    #[test]
+    fn mode_indices_follow_blend_mode_all() {
+        for (i, mode) in BlendMode::ALL.into_iter().enumerate() {
+            assert_eq!(mode_index(mode), i as i32);
+        }
+    }
+
+    #[test]
+    fn blend_shader_handles_every_mode_index() {
+        // The last mode is the switch's default branch
+        for i in 0..BlendMode::ALL.len() - 1 {
+            assert!(
+                BLEND_SHADER.contains(&format!("case {i}:")),
+                "missing case {i} in blend shader"
+            );
+        }
+        assert!(BLEND_SHADER.contains("default:"));
+    }
+
+    #[cfg(feature = "headless")]
+    mod gpu {
+        use super::super::*;
+        use crate::canvas::{ContentType, Layer, LayerGroup};
+        use crate::color::Srgb;
+        use crate::compositor;
+        use crate::render::HeadlessContext;
+        use std::collections::HashMap;
+
+        /// A 4x2 image with a distinct straight-alpha color per pixel,
+        /// channels scaled by `gain`.
+        fn pattern(seed: f64, gain: f64) -> Image {
+            let mut image = Image::new(4, 2).unwrap();
+            for (i, px) in image.data_mut().chunks_exact_mut(4).enumerate() {
+                let t = (i as f64 * 0.13 + seed).fract();
+                let rgb = [t, 1.0 - t, (t * 2.0).fract()].map(|c| c * gain);
+                px.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 0.25 + 0.5 * t]);
+            }
+            image
+        }
+
+        fn layer(name: &str, mode: BlendMode, opacity: f64) -> Layer {
+            Layer::new(name, ContentType::Field)
+                .with_blend_mode(mode)
+                .with_opacity(opacity)
+        }
+
+        fn assert_close(gpu: &Image, cpu: &Image) {
+            assert_within(gpu, cpu, 4e-3);
+        }
+
+        fn assert_within(gpu: &Image, cpu: &Image, tolerance: f64) {
+            for (i, (g, c)) in gpu.data().iter().zip(cpu.data()).enumerate() {
+                assert!((g - c).abs() < tolerance, "value {i}: gpu {g} vs cpu {c}");
+            }
+        }
+
+        #[test]
+        fn matches_the_cpu_compositor_for_every_mode() {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let background = Srgb {
+                r: 0.2,
+                g: 0.4,
+                b: 0.6,
+            };
+            let mut compositor = GpuCompositor::new(gl, 4, 2).unwrap();
+
+            for mode in BlendMode::ALL {
+                let mut canvas = Canvas::new(4, 2, background).unwrap();
+                canvas
+                    .add_layer(layer("base", BlendMode::Normal, 1.0))
+                    .unwrap();
+                canvas.add_layer(layer("top", mode, 0.8)).unwrap();
+                canvas
+                    .add_layer(layer("hidden", BlendMode::Normal, 1.0).with_visible(false))
+                    .unwrap();
+
+                // The additive fast path does not clamp until readback, so
+                // keep its sums in range to compare against the CPU
+                let gain = if mode == BlendMode::Additive {
+                    0.4
+                } else {
+                    1.0
+                };
+                let images: HashMap<&str, Image> = [
+                    ("base", pattern(0.1, gain)),
+                    ("top", pattern(0.55, gain)),
+                    ("hidden", pattern(0.9, 1.0)),
+                ]
+                .into_iter()
+                .collect();
+                let textures: HashMap<&str, glow::Texture> = images
+                    .iter()
+                    .map(|(name, image)| (*name, upload_image(gl, image).unwrap()))
+                    .collect();
+
+                compositor
+                    .composite(gl, &canvas, |name| textures.get(name).copied())
+                    .unwrap();
+                let gpu = compositor.read_image(gl).unwrap();
+                let cpu = compositor::composite(&canvas, |name| images.get(name)).unwrap();
+                assert_close(&gpu, &cpu);
+            }
+            compositor.destroy(gl);
+        }
+
+        /// Composites a blended group over gray with `format` levels and
+        /// returns the GPU and CPU results.
+        fn composite_group(format: TargetFormat) -> (Image, Image) {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let mut canvas = Canvas::new(
+                4,
+                2,
+                Srgb {
+                    r: 0.5,
+                    g: 0.5,
+                    b: 0.5,
+                },
+            )
+            .unwrap();
+            canvas
+                .add_group(
+                    LayerGroup::new("group")
+                        .with_blend_mode(BlendMode::Multiply)
+                        .with_opacity(0.7)
+                        .with_child(layer("a", BlendMode::Normal, 1.0))
+                        .with_child(layer("b", BlendMode::Screen, 0.6)),
+                )
+                .unwrap();
+
+            let images: HashMap<&str, Image> = [("a", pattern(0.3, 1.0)), ("b", pattern(0.7, 1.0))]
+                .into_iter()
+                .collect();
+            let textures: HashMap<&str, glow::Texture> = images
+                .iter()
+                .map(|(name, image)| (*name, upload_image(gl, image).unwrap()))
+                .collect();
+
+            let mut compositor = GpuCompositor::with_format(gl, 4, 2, format).unwrap();
+            compositor
+                .composite(gl, &canvas, |name| textures.get(name).copied())
+                .unwrap();
+            let gpu = compositor.read_image(gl).unwrap();
+            let cpu = compositor::composite(&canvas, |name| images.get(name)).unwrap();
+            compositor.destroy(gl);
+            (gpu, cpu)
+        }
+
+        #[test]
+        fn groups_composite_in_an_isolated_buffer() {
+            let (gpu, cpu) = composite_group(TargetFormat::Rgba16F);
+            assert_close(&gpu, &cpu);
+        }
+
+        #[test]
+        fn rgba8_levels_stay_close_to_the_cpu_compositor() {
+            // Each blend rounds to 1/255 after dithering
+            let (gpu, cpu) = composite_group(TargetFormat::Rgba8);
+            assert_within(&gpu, &cpu, 4.0 / 255.0);
+        }
+    }
+}
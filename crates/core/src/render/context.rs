@@ -1,14 +1,17 @@
 //! GPU context wrapper with capability detection.
 //!
-//! `GpuContext` wraps a `glow::Context` and queries for required
-//! extensions at initialization. The rendering pipeline requires
-//! `EXT_color_buffer_float` for RGBA16F framebuffer attachments.
+//! `GpuContext` wraps a `glow::Context` and queries for optional
+//! extensions at initialization. The rendering pipeline prefers
+//! `EXT_color_buffer_float` for RGBA16F framebuffer attachments and falls
+//! back to RGBA8 targets without it (common on older mobile GPUs).
+
+use super::target::TargetFormat;
 
 /// Wraps a `glow::Context` with detected GPU capabilities.
 ///
-/// Created once at initialization. Stores whether critical extensions
-/// like `EXT_color_buffer_float` are available, allowing the pipeline
-/// to fail fast or select fallback paths.
+/// Created once at initialization. Stores whether extensions like
+/// `EXT_color_buffer_float` are available, so the pipeline can select
+/// fallback paths.
 pub struct GpuContext {
     gl: glow::Context,
     supports_color_buffer_float: bool,
@@ -16,25 +19,23 @@ pub struct GpuContext {
 
 impl GpuContext {
     /// Creates a new `GpuContext` by wrapping the given GL context
-    /// and querying for required extensions.
+    /// and querying for optional extensions.
     ///
-    /// Checks for `EXT_color_buffer_float` which is **required** for
-    /// rendering to RGBA16F framebuffer attachments. All intermediate
-    /// FBOs in the pipeline use RGBA16F for HDR range.
+    /// Checks for `EXT_color_buffer_float`, which enables rendering to
+    /// RGBA16F framebuffer attachments for HDR range. Without it,
+    /// [`target_format`](Self::target_format) selects RGBA8.
     ///
     /// # Errors
     ///
-    /// Returns an error if `EXT_color_buffer_float` is not supported,
-    /// since the rendering pipeline cannot function without it.
+    /// Currently infallible; the `Result` leaves room for checks of hard
+    /// requirements.
     pub fn new(gl: glow::Context) -> Result<Self, String> {
         use glow::HasContext;
 
-        let supports_color_buffer_float =
-            gl.supported_extensions().contains("EXT_color_buffer_float");
-
-        if !supports_color_buffer_float {
-            return Err("required extension EXT_color_buffer_float is not supported".to_string());
-        }
+        // WebGL reports bare extension names; native GL prefixes them with `GL_`.
+        let extensions = gl.supported_extensions();
+        let supports_color_buffer_float = extensions.contains("EXT_color_buffer_float")
+            || extensions.contains("GL_EXT_color_buffer_float");
 
         Ok(Self {
             gl,
@@ -59,6 +60,58 @@ impl GpuContext {
     pub fn supports_color_buffer_float(&self) -> bool {
         self.supports_color_buffer_float
     }
+
+    /// Returns the render target format this context supports: RGBA16F
+    /// with `EXT_color_buffer_float`, otherwise RGBA8.
+    ///
+    /// Pass it to the `with_format` constructors of render targets and
+    /// passes. Float state textures (e.g. `SimulationPass`) still need the
+    /// extension.
+    pub fn target_format(&self) -> TargetFormat {
+        if self.supports_color_buffer_float {
+            TargetFormat::Rgba16F
+        } else {
+            TargetFormat::Rgba8
+        }
+    }
+
+    /// Returns the most MSAA samples a renderbuffer of `format` supports,
+    /// or 0 if it cannot be multisampled (or rendered to at all).
+    ///
+    /// An [`AntiAliasedTarget`](super::AntiAliasedTarget) falls back to
+    /// FXAA when this is below 2.
+    #[allow(unsafe_code)]
+    pub fn max_samples(&self, format: TargetFormat) -> u32 {
+        use glow::HasContext;
+
+        if format == TargetFormat::Rgba16F && !self.supports_color_buffer_float {
+            return 0;
+        }
+        let internal_format = format.texture_config(1, 1).internal_format;
+        // SAFETY: plain queries; WebGL needs the result slice to match the
+        // number of sample counts, which is asked for first.
+        unsafe {
+            let mut count = [0];
+            self.gl.get_internal_format_i32_slice(
+                glow::RENDERBUFFER,
+                internal_format,
+                glow::NUM_SAMPLE_COUNTS,
+                &mut count,
+            );
+            if count[0] <= 0 {
+                return 0;
+            }
+            // Sample counts are listed in descending order
+            let mut samples = vec![0; count[0] as usize];
+            self.gl.get_internal_format_i32_slice(
+                glow::RENDERBUFFER,
+                internal_format,
+                glow::SAMPLES,
+                &mut samples,
+            );
+            samples[0].max(0) as u32
+        }
+    }
 }
 
 #[cfg(test)]
@@ -74,6 +127,7 @@ mod tests {
         fn _assert_api(ctx: &GpuContext) {
             let _gl: &glow::Context = ctx.gl();
             let _flag: bool = ctx.supports_color_buffer_float();
+            let _format: TargetFormat = ctx.target_format();
         }
     }
 
@@ -0,0 +1,286 @@
+//! GPU feedback trail pass.
+//!
+//! `FeedbackPass` keeps the accumulated output of an animation in one of
+//! two targets and, each frame, draws the new frame over the faded, warped
+//! previous output with the formula of [`Feedback::apply`], so results
+//! match the CPU path up to half-float precision. The history starts
+//! cleared, which leaves the first frame unchanged.
+
+use crate::feedback::Feedback;
+
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::ping_pong::PingPong;
+use super::preprocess::preprocess;
+use super::shader::compile_program;
+use super::simulation::Uniform;
+use super::target::{RenderTarget, TargetFormat};
+
+/// Merges `u_current` over the warped, faded `u_previous` with a
+/// per-channel max; see [`crate::feedback`].
+const FEEDBACK_SHADER: &str = r#"#version 300 es
+precision highp float;
+uniform sampler2D u_current;
+uniform sampler2D u_previous;
+uniform float u_decay;
+uniform float u_zoom;
+uniform float u_rotation;
+uniform float u_dither;
+out vec4 fragColor;
+#include "common.glsl"
+void main() {
+    vec2 size = vec2(textureSize(u_previous, 0));
+    vec2 p = gl_FragCoord.xy;
+    vec2 center = size * 0.5;
+    vec2 d = (p - center) / u_zoom;
+    float s = sin(u_rotation);
+    float c = cos(u_rotation);
+    vec2 q = center + vec2(c * d.x + s * d.y, -s * d.x + c * d.y);
+    bool inside = all(greaterThanEqual(q, vec2(0.0))) && all(lessThanEqual(q, size));
+    vec4 trail = inside ? texture(u_previous, q / size) : vec4(0.0);
+    vec4 color = max(texelFetch(u_current, ivec2(p), 0), u_decay * trail);
+    color.rgb += dither(p, u_dither);
+    fragColor = clamp(color, 0.0, 1.0);
+}
+"#;
+
+/// Accumulates feedback trails on the GPU.
+pub struct FeedbackPass {
+    program: glow::Program,
+    vao: glow::VertexArray,
+    targets: [RenderTarget; 2],
+    ping_pong: PingPong,
+    format: TargetFormat,
+}
+
+impl FeedbackPass {
+    /// Compiles the feedback shader and allocates two cleared RGBA16F
+    /// targets of the given size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shader fails to compile or a target cannot be
+    /// created.
+    pub fn new(gl: &glow::Context, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_format(gl, width, height, TargetFormat::Rgba16F)
+    }
+
+    /// Like [`FeedbackPass::new`] with targets of the given format; RGBA8
+    /// output is dithered.
+    ///
+    /// # Errors
+    ///
+    /// As for [`FeedbackPass::new`].
+    #[allow(unsafe_code)]
+    pub fn with_format(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+    ) -> Result<Self, String> {
+        use glow::HasContext;
+
+        let program = preprocess(FEEDBACK_SHADER)
+            .map_err(|e| e.to_string())
+            .and_then(|source| {
+                compile_program(gl, FULLSCREEN_VERTEX_SHADER, &source).map_err(|e| e.to_string())
+            })?;
+        let release = || {
+            // SAFETY: program was linked above and is not in use.
+            unsafe { gl.delete_program(program) };
+        };
+        // SAFETY: glow wraps raw GL calls as unsafe; the program is
+        // released on error.
+        let vao = unsafe { gl.create_vertex_array() }.inspect_err(|_| release())?;
+        let targets = RenderTarget::with_format(gl, width, height, format).and_then(|first| {
+            match RenderTarget::with_format(gl, width, height, format) {
+                Ok(second) => Ok([first, second]),
+                Err(e) => {
+                    first.destroy(gl);
+                    Err(e)
+                }
+            }
+        });
+        let targets = targets.inspect_err(|_| {
+            release();
+            // SAFETY: vao was created above.
+            unsafe { gl.delete_vertex_array(vao) };
+        })?;
+        let pass = Self {
+            program,
+            vao,
+            targets,
+            ping_pong: PingPong::new(),
+            format,
+        };
+        pass.reset(gl);
+        Ok(pass)
+    }
+
+    /// Returns the format of the pass's targets.
+    pub fn format(&self) -> TargetFormat {
+        self.format
+    }
+
+    /// Returns the target holding the latest output.
+    pub fn output(&self) -> &RenderTarget {
+        &self.targets[self.ping_pong.src_index()]
+    }
+
+    /// Clears the history, so the next frame starts a fresh trail.
+    #[allow(unsafe_code)]
+    pub fn reset(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        for target in &self.targets {
+            target.bind(gl);
+            // SAFETY: the target's framebuffer is bound above.
+            unsafe {
+                gl.clear_color(0.0, 0.0, 0.0, 0.0);
+                gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+        }
+        // SAFETY: unbinding restores the default framebuffer.
+        unsafe { gl.bind_framebuffer(glow::FRAMEBUFFER, None) };
+    }
+
+    /// Draws `current` over the previous output with `feedback` and returns
+    /// the target holding the result, which becomes the next frame's
+    /// history.
+    ///
+    /// `current` should be the size of the pass and must not be one of its
+    /// own targets.
+    #[allow(unsafe_code)]
+    pub fn apply(
+        &mut self,
+        gl: &glow::Context,
+        current: glow::Texture,
+        feedback: &Feedback,
+    ) -> &RenderTarget {
+        use glow::HasContext;
+
+        let previous = self.output().texture();
+        self.targets[self.ping_pong.dst_index()].bind(gl);
+        // SAFETY: program, vao, and textures are valid handles; neither
+        // input is attached to the bound framebuffer.
+        unsafe {
+            gl.use_program(Some(self.program));
+            for (unit, texture) in [current, previous].into_iter().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            }
+            for (name, value) in [
+                ("u_current", Uniform::Int(0)),
+                ("u_previous", Uniform::Int(1)),
+                ("u_decay", Uniform::Float(feedback.decay as f32)),
+                ("u_zoom", Uniform::Float(feedback.zoom as f32)),
+                ("u_rotation", Uniform::Float(feedback.rotation as f32)),
+                ("u_dither", Uniform::Float(self.format.dither())),
+            ] {
+                if let Some(location) = gl.get_uniform_location(self.program, name) {
+                    value.upload(gl, &location);
+                }
+            }
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.bind_vertex_array(None);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.use_program(None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        self.ping_pong.swap();
+        self.output()
+    }
+
+    /// Reallocates (and clears) the targets at a new size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a target cannot be resized.
+    pub fn resize(&mut self, gl: &glow::Context, width: u32, height: u32) -> Result<(), String> {
+        for target in &mut self.targets {
+            target.resize(gl, width, height)?;
+        }
+        self.reset(gl);
+        Ok(())
+    }
+
+    /// Deletes the program, vertex array, and targets.
+    ///
+    /// Like [`RenderTarget::destroy`], call this before dropping the pass
+    /// for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: the program and vao are valid handles from with_format().
+        unsafe {
+            gl.delete_program(self.program);
+            gl.delete_vertex_array(self.vao);
+        }
+        self.targets.iter().for_each(|target| target.destroy(gl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shader_merges_with_a_lighten_blend() {
+        assert!(
+            FEEDBACK_SHADER.contains("max(texelFetch(u_current, ivec2(p), 0), u_decay * trail)")
+        );
+    }
+
+    #[cfg(feature = "headless")]
+    #[test]
+    fn matches_the_cpu_feedback_over_several_frames() {
+        use crate::image::Image;
+        use crate::render::{read_image, upload_image, HeadlessContext};
+
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        let (w, h) = (29, 21);
+        let frame = |k: usize| {
+            let mut image = Image::new(w, h).unwrap();
+            for (i, px) in image.data_mut().chunks_exact_mut(4).enumerate() {
+                let (x, y) = ((i % w) as f64, (i / w) as f64);
+                let (cx, cy) = (8.0 + 3.0 * k as f64, 10.0);
+                let spot = (-((x - cx).powi(2) + (y - cy).powi(2)) / 6.0).exp();
+                px.copy_from_slice(&[spot, 0.6 * spot, 0.2 * spot, spot]);
+            }
+            image
+        };
+        let feedback = Feedback::new(0.8).with_zoom(1.05).with_rotation(0.1);
+
+        let mut pass = FeedbackPass::new(gl, w as u32, h as u32).unwrap();
+        let mut cpu: Option<Image> = None;
+        for k in 0..4 {
+            let current = frame(k);
+            let texture = upload_image(gl, &current).unwrap();
+            let gpu = read_image(gl, pass.apply(gl, texture, &feedback)).unwrap();
+            let expected = match &cpu {
+                Some(previous) => feedback.apply(previous, &current),
+                None => current,
+            };
+            for (i, (g, c)) in gpu.data().iter().zip(expected.data()).enumerate() {
+                assert!(
+                    (g - c).abs() < 4e-3,
+                    "frame {k} value {i}: gpu {g} vs cpu {c}"
+                );
+            }
+            cpu = Some(expected);
+        }
+
+        // After a reset the next frame is drawn as is
+        pass.reset(gl);
+        let texture = upload_image(gl, &frame(0)).unwrap();
+        let gpu = read_image(gl, pass.apply(gl, texture, &feedback)).unwrap();
+        for (g, c) in gpu.data().iter().zip(frame(0).data()) {
+            assert!((g - c).abs() < 4e-3);
+        }
+        pass.destroy(gl);
+    }
+}
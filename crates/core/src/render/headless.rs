@@ -0,0 +1,392 @@
+//! Windowless GL contexts for native rendering and tests.
+//!
+//! `HeadlessContext` creates an OpenGL ES 3.0 context through EGL with a
+//! 1x1 pbuffer surface, so the render module can run on servers and in
+//! `cargo test` without a window system. It prefers Mesa's surfaceless
+//! platform (works without a display server, e.g. llvmpipe in CI) and falls
+//! back to the default EGL display.
+//!
+//! Only available with the `headless` feature, which links against the
+//! system `libEGL`. All rendering goes to `RenderTarget`s, so the pbuffer
+//! is never drawn to.
+
+use std::ffi::{c_void, CString};
+use std::ptr;
+
+use super::context::GpuContext;
+
+type EglDisplay = *mut c_void;
+type EglConfig = *mut c_void;
+type EglContext = *mut c_void;
+type EglSurface = *mut c_void;
+
+const EGL_SUCCESS: i32 = 0x3000;
+const EGL_NONE: i32 = 0x3038;
+const EGL_ALPHA_SIZE: i32 = 0x3021;
+const EGL_BLUE_SIZE: i32 = 0x3022;
+const EGL_GREEN_SIZE: i32 = 0x3023;
+const EGL_RED_SIZE: i32 = 0x3024;
+const EGL_SURFACE_TYPE: i32 = 0x3033;
+const EGL_RENDERABLE_TYPE: i32 = 0x3040;
+const EGL_HEIGHT: i32 = 0x3056;
+const EGL_WIDTH: i32 = 0x3057;
+const EGL_CONTEXT_MAJOR_VERSION: i32 = 0x3098;
+const EGL_CONTEXT_MINOR_VERSION: i32 = 0x30FB;
+const EGL_PBUFFER_BIT: i32 = 0x0001;
+const EGL_OPENGL_ES3_BIT: i32 = 0x0040;
+const EGL_OPENGL_ES_API: u32 = 0x30A0;
+const EGL_PLATFORM_SURFACELESS_MESA: u32 = 0x31DD;
+
+#[link(name = "EGL")]
+extern "C" {
+    fn eglGetError() -> i32;
+    fn eglGetDisplay(native_display: *mut c_void) -> EglDisplay;
+    fn eglGetPlatformDisplay(
+        platform: u32,
+        native_display: *mut c_void,
+        attrib_list: *const isize,
+    ) -> EglDisplay;
+    fn eglInitialize(display: EglDisplay, major: *mut i32, minor: *mut i32) -> u32;
+    fn eglTerminate(display: EglDisplay) -> u32;
+    fn eglBindAPI(api: u32) -> u32;
+    fn eglChooseConfig(
+        display: EglDisplay,
+        attrib_list: *const i32,
+        configs: *mut EglConfig,
+        config_size: i32,
+        num_config: *mut i32,
+    ) -> u32;
+    fn eglCreateContext(
+        display: EglDisplay,
+        config: EglConfig,
+        share_context: EglContext,
+        attrib_list: *const i32,
+    ) -> EglContext;
+    fn eglDestroyContext(display: EglDisplay, context: EglContext) -> u32;
+    fn eglCreatePbufferSurface(
+        display: EglDisplay,
+        config: EglConfig,
+        attrib_list: *const i32,
+    ) -> EglSurface;
+    fn eglDestroySurface(display: EglDisplay, surface: EglSurface) -> u32;
+    fn eglMakeCurrent(
+        display: EglDisplay,
+        draw: EglSurface,
+        read: EglSurface,
+        context: EglContext,
+    ) -> u32;
+    fn eglGetProcAddress(name: *const std::ffi::c_char) -> *const c_void;
+}
+
+/// Formats the pending EGL error for `call`.
+#[allow(unsafe_code)]
+fn egl_error(call: &str) -> String {
+    // SAFETY: eglGetError has no preconditions.
+    let code = unsafe { eglGetError() };
+    if code == EGL_SUCCESS {
+        format!("{call} failed")
+    } else {
+        format!("{call} failed: EGL error 0x{code:04X}")
+    }
+}
+
+/// An EGL-backed GL ES 3.0 context that needs no window.
+///
+/// The context is current on the thread that created it, so the type is
+/// neither `Send` nor `Sync`. Dropping it releases the context and the
+/// EGL display.
+pub struct HeadlessContext {
+    gpu: GpuContext,
+    display: EglDisplay,
+    context: EglContext,
+    surface: EglSurface,
+}
+
+impl HeadlessContext {
+    /// Creates a context, makes it current on this thread, and wraps it in
+    /// a [`GpuContext`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no EGL display, ES 3.0 config, or context is
+    /// available, or if the context lacks the extensions `GpuContext`
+    /// requires.
+    #[allow(unsafe_code)]
+    pub fn new() -> Result<Self, String> {
+        let display = Self::display()?;
+
+        // SAFETY: display is a valid, initialized EGL display. Each
+        // attribute list is EGL_NONE-terminated, and every handle created
+        // here is released on the error paths below or in Drop.
+        unsafe {
+            if eglBindAPI(EGL_OPENGL_ES_API) == 0 {
+                eglTerminate(display);
+                return Err(egl_error("eglBindAPI"));
+            }
+
+            let config_attribs = [
+                EGL_SURFACE_TYPE,
+                EGL_PBUFFER_BIT,
+                EGL_RENDERABLE_TYPE,
+                EGL_OPENGL_ES3_BIT,
+                EGL_RED_SIZE,
+                8,
+                EGL_GREEN_SIZE,
+                8,
+                EGL_BLUE_SIZE,
+                8,
+                EGL_ALPHA_SIZE,
+                8,
+                EGL_NONE,
+            ];
+            let mut config: EglConfig = ptr::null_mut();
+            let mut count = 0;
+            if eglChooseConfig(display, config_attribs.as_ptr(), &mut config, 1, &mut count) == 0
+                || count == 0
+            {
+                eglTerminate(display);
+                return Err("no EGL config supports OpenGL ES 3.0 pbuffers".to_string());
+            }
+
+            let context_attribs = [
+                EGL_CONTEXT_MAJOR_VERSION,
+                3,
+                EGL_CONTEXT_MINOR_VERSION,
+                0,
+                EGL_NONE,
+            ];
+            let context =
+                eglCreateContext(display, config, ptr::null_mut(), context_attribs.as_ptr());
+            if context.is_null() {
+                let err = egl_error("eglCreateContext");
+                eglTerminate(display);
+                return Err(err);
+            }
+
+            let surface_attribs = [EGL_WIDTH, 1, EGL_HEIGHT, 1, EGL_NONE];
+            let surface = eglCreatePbufferSurface(display, config, surface_attribs.as_ptr());
+            if surface.is_null() {
+                let err = egl_error("eglCreatePbufferSurface");
+                eglDestroyContext(display, context);
+                eglTerminate(display);
+                return Err(err);
+            }
+
+            if eglMakeCurrent(display, surface, surface, context) == 0 {
+                let err = egl_error("eglMakeCurrent");
+                eglDestroySurface(display, surface);
+                eglDestroyContext(display, context);
+                eglTerminate(display);
+                return Err(err);
+            }
+
+            // A name with a NUL byte cannot be looked up; report it missing
+            let gl = glow::Context::from_loader_function(|name| match CString::new(name) {
+                Ok(name) => eglGetProcAddress(name.as_ptr()),
+                Err(_) => ptr::null(),
+            });
+
+            match GpuContext::new(gl) {
+                Ok(gpu) => Ok(Self {
+                    gpu,
+                    display,
+                    context,
+                    surface,
+                }),
+                Err(e) => {
+                    Self::release(display, context, surface);
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Opens and initializes the surfaceless display, or the default one.
+    #[allow(unsafe_code)]
+    fn display() -> Result<EglDisplay, String> {
+        // SAFETY: both calls accept a null native display; an attribute
+        // list of just EGL_NONE is valid.
+        unsafe {
+            let attribs = [EGL_NONE as isize];
+            let surfaceless = eglGetPlatformDisplay(
+                EGL_PLATFORM_SURFACELESS_MESA,
+                ptr::null_mut(),
+                attribs.as_ptr(),
+            );
+            for display in [surfaceless, eglGetDisplay(ptr::null_mut())] {
+                if !display.is_null()
+                    && eglInitialize(display, ptr::null_mut(), ptr::null_mut()) != 0
+                {
+                    return Ok(display);
+                }
+            }
+        }
+        Err(egl_error("eglInitialize"))
+    }
+
+    /// Unbinds and destroys the EGL objects.
+    #[allow(unsafe_code)]
+    fn release(display: EglDisplay, context: EglContext, surface: EglSurface) {
+        // SAFETY: the handles were created together in new() and are
+        // released exactly once.
+        unsafe {
+            eglMakeCurrent(display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            eglDestroySurface(display, surface);
+            eglDestroyContext(display, context);
+            eglTerminate(display);
+        }
+    }
+
+    /// Returns the wrapped GPU context.
+    pub fn gpu(&self) -> &GpuContext {
+        &self.gpu
+    }
+
+    /// Returns the underlying `glow::Context`.
+    pub fn gl(&self) -> &glow::Context {
+        self.gpu.gl()
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        Self::release(self.display, self.context, self.surface);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{RenderTarget, SimulationPass, TargetFormat};
+
+    // These tests create a real context, so they run whenever the
+    // `headless` feature is enabled: `cargo test -p art-engine-core
+    // --features headless`. Mesa's llvmpipe is enough.
+
+    #[test]
+    fn creates_a_context_with_float_color_buffers() {
+        let headless = HeadlessContext::new().unwrap();
+        assert!(headless.gpu().supports_color_buffer_float());
+    }
+
+    #[test]
+    fn render_targets_resize() {
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        let mut target = RenderTarget::new(gl, 16, 8).unwrap();
+        target.resize(gl, 32, 4).unwrap();
+        assert_eq!((target.width(), target.height()), (32, 4));
+        target.destroy(gl);
+    }
+
+    #[test]
+    fn picks_float_targets_and_reads_back_rgba8_ones() {
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        assert_eq!(headless.gpu().target_format(), TargetFormat::Rgba16F);
+
+        let target = RenderTarget::with_format(gl, 2, 1, TargetFormat::Rgba8).unwrap();
+        target.bind(gl);
+        // SAFETY: the target's framebuffer is bound above.
+        #[allow(unsafe_code)]
+        unsafe {
+            use glow::HasContext;
+            gl.clear_color(0.4, 1.5, -0.2, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+        let texels = target.read_texels(gl);
+        let expected = [102.0 / 255.0, 1.0, 0.0, 1.0];
+        for (texel, want) in texels
+            .chunks_exact(4)
+            .flatten()
+            .zip(expected.iter().cycle())
+        {
+            assert!((texel - want).abs() < 1e-6, "{texels:?}");
+        }
+        target.destroy(gl);
+    }
+
+    #[test]
+    fn render_targets_read_back_into_fields() {
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        // 3x2 with odd row length; half floats hold these values exactly
+        let shader = "#version 300 es\nprecision highp float;\nin vec2 v_uv;\nuniform sampler2D u_state;\nout vec4 fragColor;\nvoid main() { fragColor = texture(u_state, v_uv); }\n";
+        let mut pass = SimulationPass::new(gl, 3, 2, shader).unwrap();
+        let values = [0.0, 0.25, 0.5, 0.75, 1.0, -2.5];
+        let texels: Vec<f32> = values.iter().flat_map(|&v| [v, 9.0, 9.0, 1.0]).collect();
+        pass.upload_state(gl, &texels).unwrap();
+        pass.step(gl);
+
+        let field = pass.state().read_to_field(headless.gpu()).unwrap();
+        assert_eq!((field.width(), field.height()), (3, 2));
+        assert_eq!(field.data(), values.map(f64::from));
+        assert_eq!(field.get(0, 1), 0.75);
+        pass.destroy(gl);
+    }
+
+    #[test]
+    fn fields_upload_and_update_in_place() {
+        use crate::field::Field;
+        use crate::render::{update_field, upload_field, upload_field_as, FieldFormat, PostPass};
+
+        let headless = HeadlessContext::new().unwrap();
+        let gpu = headless.gpu();
+        let gl = headless.gl();
+        // 3x2 with odd row length; half floats hold these values exactly
+        let field = Field::from_data(3, 2, vec![0.0, 0.125, 0.25, 0.5, 0.75, 1.0]).unwrap();
+        let mut copy = PostPass::new(gl, 3, 2).unwrap();
+        for format in [FieldFormat::R32F, FieldFormat::R16F] {
+            let texture = upload_field_as(gpu, &field, format).unwrap();
+            let back = copy.apply(gl, texture, &[]).unwrap().read_to_field(gpu);
+            assert_eq!(back.unwrap(), field, "{format:?}");
+        }
+
+        let texture = upload_field(gpu, &field).unwrap();
+        let flipped =
+            Field::from_data(3, 2, field.data().iter().map(|v| 1.0 - v).collect()).unwrap();
+        update_field(gpu, texture, &flipped);
+        let back = copy.apply(gl, texture, &[]).unwrap().read_to_field(gpu);
+        assert_eq!(back.unwrap(), flipped);
+        copy.destroy(gl);
+    }
+
+    #[test]
+    fn simulation_pass_steps_on_the_gpu() {
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        let shader = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_state;
+uniform float u_rate;
+out vec4 fragColor;
+void main() {
+    // Reads the right-hand neighbor, so values shift left with wrapping
+    vec2 texel = 1.0 / vec2(textureSize(u_state, 0));
+    vec4 right = texture(u_state, v_uv + vec2(texel.x, 0.0));
+    fragColor = vec4(right.r, texture(u_state, v_uv).g + u_rate, 0.0, 1.0);
+}
+"#;
+        let mut pass = SimulationPass::new(gl, 4, 1, shader)
+            .unwrap()
+            .with_uniform("u_rate", 0.25f32);
+        #[rustfmt::skip]
+        let initial = [
+            1.0, 0.0, 0.0, 1.0,
+            2.0, 0.0, 0.0, 1.0,
+            3.0, 0.0, 0.0, 1.0,
+            4.0, 0.0, 0.0, 1.0,
+        ];
+        pass.upload_state(gl, &initial).unwrap();
+        pass.run(gl, 2);
+        assert_eq!(pass.steps(), 2);
+
+        let state = pass.read_state(gl);
+        let red: Vec<f32> = state.chunks(4).map(|texel| texel[0]).collect();
+        let green: Vec<f32> = state.chunks(4).map(|texel| texel[1]).collect();
+        assert_eq!(red, [3.0, 4.0, 1.0, 2.0]);
+        assert_eq!(green, [0.5; 4]);
+        pass.destroy(gl);
+    }
+}
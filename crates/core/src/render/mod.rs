@@ -2,28 +2,69 @@
 //!
 //! This module is only available when the `render` feature is enabled.
 //! It provides shader compilation, texture management, render targets
-//! with ping-pong double buffering, and GPU context initialization.
+//! with ping-pong double buffering, GPU context initialization,
+//! fragment-shader simulation passes, and post-processing.
 //!
 //! # Module overview
 //!
+//! - [`antialias`] -- MSAA or FXAA-resolved targets for drawn geometry.
 //! - [`ping_pong`] -- Index tracking for double-buffered render targets.
 //! - [`shader`] -- Shader compilation, linking, and error formatting.
 //! - [`fullscreen`] -- Fullscreen triangle vertex shader constant.
 //! - [`texture`] -- Texture configuration and creation helpers.
-//! - [`target`] -- FBO + texture render targets.
+//! - [`target`] -- FBO + texture render targets (RGBA16F, or RGBA8 fallback).
+//! - [`bloom`] -- Bloom pyramid matching the CPU [`Bloom`](crate::Bloom).
 //! - [`context`] -- GPU context wrapper with capability detection.
+//! - [`compositor`] -- Canvas compositing with blend-mode shaders.
+//! - [`feedback`] -- Feedback trails matching the CPU [`Feedback`](crate::Feedback).
+//! - `headless` -- Windowless EGL contexts (`headless` feature, native only).
+//! - [`palette_lut`] -- Palettes baked into lookup textures, with a GLSL lookup.
+//! - [`particles`] -- Instanced additive particle sprites.
+//! - [`post`] -- Post-effect stacks matching the CPU [`post`](crate::post) path.
+//! - [`preprocess`](mod@preprocess) -- `#include` and `#define` expansion for shared GLSL.
+//! - [`program_cache`] -- Program cache by source, with shader file hot reload.
+//! - [`simulation`] -- Ping-pong state textures stepped by a fragment shader.
 
+pub mod antialias;
+pub mod bloom;
+pub mod compositor;
 pub mod context;
+pub mod feedback;
 pub mod fullscreen;
+#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+pub mod headless;
+pub mod palette_lut;
+pub mod particles;
 pub mod ping_pong;
+pub mod post;
+pub mod preprocess;
+pub mod program_cache;
 pub mod shader;
+pub mod simulation;
 pub mod target;
 pub mod texture;
 
 // Re-export key types at the render module level for convenience.
+pub use antialias::{AntiAlias, AntiAliasedTarget};
+pub use bloom::BloomPass;
+pub use compositor::{read_image, upload_image, GpuCompositor};
 pub use context::GpuContext;
+pub use feedback::FeedbackPass;
 pub use fullscreen::FULLSCREEN_VERTEX_SHADER;
+#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+pub use headless::HeadlessContext;
+pub use palette_lut::{PaletteLut, PALETTE_LUT_GLSL};
+pub use particles::{ParticleRenderer, ParticleShape, ParticleSource};
 pub use ping_pong::PingPong;
+pub use post::PostPass;
+pub use preprocess::{preprocess, PreprocessError, Preprocessor, COMMON_GLSL};
+#[cfg(not(target_arch = "wasm32"))]
+pub use program_cache::HotProgram;
+pub use program_cache::{CachedProgram, ProgramCache};
 pub use shader::{compile_program, compile_shader, format_shader_error, link_program, ShaderError};
-pub use target::RenderTarget;
-pub use texture::{create_texture, pixel_type_for_format, TextureConfig};
+pub use simulation::{SimulationPass, Uniform, Uniforms};
+pub use target::{DrawTarget, RenderTarget, TargetFormat};
+pub use texture::{
+    create_texture, pixel_format_for_format, pixel_type_for_format, update_field, upload_field,
+    upload_field_as, FieldFormat, TextureConfig,
+};
@@ -0,0 +1,263 @@
+//! Palettes baked into lookup textures for GPU color mapping.
+//!
+//! `PaletteLut` samples a [`Palette`] at evenly spaced field values into a
+//! one-row RGBA8 texture (256 to 1024 entries), with an optional tone curve
+//! applied to the field value first. Shaders include [`PALETTE_LUT_GLSL`]
+//! (`#include "palette_lut.glsl"` with the
+//! [`Preprocessor`](super::preprocess::Preprocessor)) and call
+//! `palette_lut(u_lut, t)`, which snaps to the nearest entry, so a
+//! field value on an entry gets exactly the bytes the CPU `field_to_rgba`
+//! path writes for the toned value with the palette's own table off
+//! ([`Palette::with_lut`]); [`PaletteLut::color`] is the CPU mirror of the
+//! lookup.
+
+use crate::palette::Palette;
+
+use super::texture::{create_texture, TextureConfig};
+
+/// Fewest entries a LUT can have.
+pub const MIN_LUT_SIZE: usize = 256;
+
+/// Most entries a LUT can have.
+pub const MAX_LUT_SIZE: usize = 1024;
+
+/// GLSL for sampling a LUT texture; include it after the precision
+/// statements of a fragment shader.
+pub const PALETTE_LUT_GLSL: &str = r#"
+// Looks up field value t in a palette LUT row, snapping to the nearest entry.
+vec4 palette_lut(sampler2D lut, float t) {
+    float last = float(textureSize(lut, 0).x - 1);
+    float index = floor(clamp(t, 0.0, 1.0) * last + 0.5);
+    return texelFetch(lut, ivec2(int(index), 0), 0);
+}
+"#;
+
+/// Settings for baking a palette LUT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteLut {
+    /// Number of entries, in [`MIN_LUT_SIZE`, `MAX_LUT_SIZE`].
+    pub size: usize,
+    /// Exponent of the tone curve `t^gamma`; 1 leaves values unchanged,
+    /// below 1 brightens, above 1 darkens.
+    pub gamma: f64,
+    /// Whether field values are flipped (`1 - t`) before the tone curve.
+    pub invert: bool,
+}
+
+impl Default for PaletteLut {
+    fn default() -> Self {
+        Self {
+            size: MIN_LUT_SIZE,
+            gamma: 1.0,
+            invert: false,
+        }
+    }
+}
+
+impl PaletteLut {
+    /// A 256-entry LUT without tone mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns these settings with a different entry count, clamped to
+    /// [`MIN_LUT_SIZE`, `MAX_LUT_SIZE`].
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.size = size.clamp(MIN_LUT_SIZE, MAX_LUT_SIZE);
+        self
+    }
+
+    /// Returns these settings with a different tone curve exponent.
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Returns these settings with field values flipped or not.
+    pub fn with_invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Applies the tone options to a field value, clamped to [0, 1] (NaN
+    /// maps to 0, as in [`Palette::sample`]).
+    pub fn tone(&self, t: f64) -> f64 {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let t = if self.invert { 1.0 - t } else { t };
+        if self.gamma == 1.0 {
+            t
+        } else {
+            t.powf(self.gamma)
+        }
+    }
+
+    /// Returns the entry the shader snippet reads for field value `t`.
+    pub fn index(&self, t: f64) -> usize {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        (t * (self.size - 1) as f64 + 0.5).floor() as usize
+    }
+
+    /// Returns the RGBA8 color the GPU lookup gives for field value `t`.
+    pub fn color(&self, palette: &Palette, t: f64) -> [u8; 4] {
+        self.entry(palette, self.index(t))
+    }
+
+    /// Bakes `palette` into `size` RGBA8 entries for t = 0, 1/(size-1),
+    /// ..., 1.
+    pub fn bake(&self, palette: &Palette) -> Vec<u8> {
+        (0..self.size)
+            .flat_map(|i| self.entry(palette, i))
+            .collect()
+    }
+
+    /// Bakes `palette` and uploads it as a `size x 1` RGBA8 texture with
+    /// `NEAREST` filtering.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texture cannot be created.
+    #[allow(unsafe_code)]
+    pub fn upload(&self, gl: &glow::Context, palette: &Palette) -> Result<glow::Texture, String> {
+        use glow::HasContext;
+
+        let config = TextureConfig {
+            filter: glow::NEAREST,
+            ..TextureConfig::rgba8(self.size as u32, 1)
+        };
+        let texture = create_texture(gl, &config)?;
+        let bytes = self.bake(palette);
+        // SAFETY: texture is valid and `bytes` holds `size` RGBA8 texels;
+        // rows of 4-byte texels meet the default unpack alignment.
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                0,
+                0,
+                self.size as i32,
+                1,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(Some(&bytes)),
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        Ok(texture)
+    }
+
+    /// The color of entry `i`, converted to bytes like `field_to_rgba`.
+    fn entry(&self, palette: &Palette, i: usize) -> [u8; 4] {
+        let t = i as f64 / (self.size - 1) as f64;
+        let srgb = palette.sample(self.tone(t));
+        let byte = |v: f64| {
+            if v.is_nan() {
+                0
+            } else {
+                (v.clamp(0.0, 1.0) * 255.0).round() as u8
+            }
+        };
+        [byte(srgb.r), byte(srgb.g), byte(srgb.b), 255]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_clamp_to_the_supported_range() {
+        assert_eq!(PaletteLut::new().size, 256);
+        assert_eq!(PaletteLut::new().with_size(16).size, MIN_LUT_SIZE);
+        assert_eq!(PaletteLut::new().with_size(4096).size, MAX_LUT_SIZE);
+        assert_eq!(PaletteLut::new().with_size(512).size, 512);
+    }
+
+    #[test]
+    fn untoned_entries_are_the_palette_samples() {
+        let palette = Palette::neon();
+        let lut = PaletteLut::new().with_size(300);
+        let bytes = lut.bake(&palette);
+        assert_eq!(bytes.len(), 300 * 4);
+        let hex = |rgba: &[u8]| format!("#{:02x}{:02x}{:02x}", rgba[0], rgba[1], rgba[2]);
+        for i in [0, 1, 150, 299] {
+            let t = i as f64 / 299.0;
+            assert_eq!(hex(&bytes[i * 4..i * 4 + 4]), palette.sample(t).to_hex());
+            assert_eq!(lut.index(t), i);
+            assert_eq!(bytes[i * 4 + 3], 255);
+        }
+    }
+
+    #[test]
+    fn lookups_snap_to_the_nearest_entry() {
+        let lut = PaletteLut::new();
+        assert_eq!(lut.index(-1.0), 0);
+        assert_eq!(lut.index(f64::NAN), 0);
+        assert_eq!(lut.index(0.4 / 255.0), 0);
+        assert_eq!(lut.index(0.6 / 255.0), 1);
+        assert_eq!(lut.index(2.0), 255);
+        let palette = Palette::fire();
+        assert_eq!(
+            lut.color(&palette, 0.6 / 255.0),
+            lut.color(&palette, 1.0 / 255.0)
+        );
+    }
+
+    #[test]
+    fn tone_options_remap_field_values() {
+        let lut = PaletteLut::new().with_gamma(2.0).with_invert(true);
+        assert_eq!(lut.tone(0.0), 1.0);
+        assert!((lut.tone(0.25) - 0.5625).abs() < 1e-12);
+        assert_eq!(lut.tone(1.0), 0.0);
+        assert_eq!(PaletteLut::new().tone(0.3), 0.3);
+
+        let palette = Palette::ocean();
+        let bytes = lut.bake(&palette);
+        assert_eq!(&bytes[..4], &PaletteLut::new().bake(&palette)[255 * 4..]);
+    }
+
+    #[test]
+    fn glsl_snippet_defines_the_lookup() {
+        assert!(PALETTE_LUT_GLSL.contains("vec4 palette_lut(sampler2D lut, float t)"));
+    }
+
+    #[cfg(feature = "headless")]
+    #[test]
+    fn shader_lookups_match_the_cpu_mirror() {
+        use crate::render::{
+            compile_program, HeadlessContext, RenderTarget, FULLSCREEN_VERTEX_SHADER,
+        };
+        use glow::HasContext;
+
+        let headless = HeadlessContext::new().unwrap();
+        let gl = headless.gl();
+        let palette = Palette::vapor();
+        let lut = PaletteLut::new().with_size(1000).with_gamma(0.7);
+        let texture = lut.upload(gl, &palette).unwrap();
+        let shader = format!(
+            "#version 300 es\nprecision highp float;\nuniform sampler2D u_lut;\nout vec4 fragColor;\n{PALETTE_LUT_GLSL}\nvoid main() {{ fragColor = palette_lut(u_lut, gl_FragCoord.x / 64.0); }}\n"
+        );
+        let program = compile_program(gl, FULLSCREEN_VERTEX_SHADER, &shader).unwrap();
+        let target = RenderTarget::new(gl, 64, 1).unwrap();
+        target.bind(gl);
+        // SAFETY: program, texture, and the bound target are valid.
+        #[allow(unsafe_code)]
+        unsafe {
+            gl.use_program(Some(program));
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            let vao = gl.create_vertex_array().unwrap();
+            gl.bind_vertex_array(Some(vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.delete_vertex_array(vao);
+            gl.delete_program(program);
+            gl.delete_texture(texture);
+        }
+        let texels = target.read_texels(gl);
+        for (x, texel) in texels.chunks_exact(4).enumerate() {
+            let expected = lut.color(&palette, (x as f64 + 0.5) / 64.0);
+            let got: Vec<u8> = texel.iter().map(|v| (v * 255.0).round() as u8).collect();
+            assert_eq!(got, expected, "pixel {x}");
+        }
+        target.destroy(gl);
+    }
+}
@@ -0,0 +1,433 @@
+//! Instanced particle rendering for agent-based engines.
+//!
+//! `ParticleRenderer` draws one soft sprite per particle with additive
+//! blending, so dense regions accumulate into bright trails on the RGBA16F
+//! targets (values above 1 are kept for later tone mapping or bloom).
+//! Each particle is four floats, `(x, y, t, size)`:
+//!
+//! - `x`, `y` -- position in [0, 1], in the same orientation as `Field` and
+//!   `Image` rows (y = 0 is the first row);
+//! - `t` -- palette coordinate in [0, 1], looked up in a LUT texture such
+//!   as a [`PaletteLut`](super::PaletteLut);
+//! - `size` -- multiplier on the renderer's base size in pixels.
+//!
+//! Particles come either from a vertex buffer uploaded from the CPU
+//! ([`ParticleRenderer::upload`]) or straight from an RGBA float texture
+//! such as a [`SimulationPass`](super::SimulationPass) state, read one texel
+//! per particle in row-major order -- the GPU-resident path that scales to
+//! millions of particles with no readback. Sprites are drawn as points
+//! (cheapest, but capped by the driver's point size range) or as instanced
+//! quads.
+
+use super::shader::compile_program;
+use super::simulation::{texels_to_bytes, Uniform};
+use super::target::DrawTarget;
+
+/// Vertex attribute location of the per-particle `(x, y, t, size)` data.
+const PARTICLE_ATTRIBUTE: u32 = 0;
+
+const PARTICLE_VERTEX_SHADER: &str = r#"#version 300 es
+precision highp float;
+precision highp int;
+layout(location = 0) in vec4 a_particle;
+uniform sampler2D u_particles;
+uniform int u_from_texture;
+uniform int u_points;
+uniform float u_size;
+uniform vec2 u_resolution;
+out vec2 v_corner;
+out float v_t;
+
+const vec2 CORNERS[6] = vec2[6](
+    vec2(-1.0, -1.0), vec2(1.0, -1.0), vec2(1.0, 1.0),
+    vec2(-1.0, -1.0), vec2(1.0, 1.0), vec2(-1.0, 1.0)
+);
+
+void main() {
+    int id = u_points == 1 ? gl_VertexID : gl_InstanceID;
+    vec4 p = a_particle;
+    if (u_from_texture == 1) {
+        int width = textureSize(u_particles, 0).x;
+        p = texelFetch(u_particles, ivec2(id % width, id / width), 0);
+    }
+    float size = u_size * p.w;
+    vec2 center = p.xy * 2.0 - 1.0;
+    v_t = p.z;
+    if (u_points == 1) {
+        v_corner = vec2(0.0);
+        gl_PointSize = size;
+        gl_Position = vec4(center, 0.0, 1.0);
+    } else {
+        v_corner = CORNERS[gl_VertexID];
+        gl_Position = vec4(center + v_corner * size / u_resolution, 0.0, 1.0);
+    }
+}
+"#;
+
+const PARTICLE_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+precision highp int;
+in vec2 v_corner;
+in float v_t;
+uniform sampler2D u_lut;
+uniform int u_points;
+uniform float u_intensity;
+out vec4 fragColor;
+void main() {
+    vec2 c = u_points == 1 ? gl_PointCoord * 2.0 - 1.0 : v_corner;
+    float falloff = max(1.0 - dot(c, c), 0.0);
+    falloff *= falloff * u_intensity;
+    float n = float(textureSize(u_lut, 0).x);
+    vec4 color = texture(u_lut, vec2((clamp(v_t, 0.0, 1.0) * (n - 1.0) + 0.5) / n, 0.5));
+    fragColor = vec4(color.rgb * color.a, color.a) * falloff;
+}
+"#;
+
+/// How each particle is rasterized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParticleShape {
+    /// One `GL_POINTS` sprite per particle. Sizes are clamped to the
+    /// driver's point size range.
+    Point,
+    /// An instanced quad per particle, for any size.
+    #[default]
+    Quad,
+}
+
+/// Where a draw call reads particles from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleSource {
+    /// The particles last passed to [`ParticleRenderer::upload`].
+    Buffer,
+    /// The first `count` texels of an RGBA float texture, row-major.
+    Texture {
+        texture: glow::Texture,
+        count: usize,
+    },
+}
+
+/// Draws particles as additive soft sprites colored through a LUT.
+pub struct ParticleRenderer {
+    program: glow::Program,
+    vao: glow::VertexArray,
+    buffer: glow::Buffer,
+    count: usize,
+    shape: ParticleShape,
+    size: f32,
+    intensity: f32,
+}
+
+impl ParticleRenderer {
+    /// Compiles the particle shaders and creates an empty particle buffer.
+    ///
+    /// Defaults to quads 4 pixels across at full intensity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shaders fail to compile or the vertex array
+    /// or buffer cannot be created.
+    #[allow(unsafe_code)]
+    pub fn new(gl: &glow::Context) -> Result<Self, String> {
+        use glow::HasContext;
+
+        let program = compile_program(gl, PARTICLE_VERTEX_SHADER, PARTICLE_FRAGMENT_SHADER)
+            .map_err(|e| e.to_string())?;
+        // SAFETY: glow wraps raw GL calls as unsafe; every handle created
+        // here is released on the error paths.
+        unsafe {
+            let vao = gl.create_vertex_array().inspect_err(|_| {
+                gl.delete_program(program);
+            })?;
+            let buffer = gl.create_buffer().inspect_err(|_| {
+                gl.delete_vertex_array(vao);
+                gl.delete_program(program);
+            })?;
+            gl.bind_vertex_array(Some(vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(buffer));
+            gl.vertex_attrib_pointer_f32(PARTICLE_ATTRIBUTE, 4, glow::FLOAT, false, 16, 0);
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            Ok(Self {
+                program,
+                vao,
+                buffer,
+                count: 0,
+                shape: ParticleShape::default(),
+                size: 4.0,
+                intensity: 1.0,
+            })
+        }
+    }
+
+    /// Sets the sprite shape.
+    pub fn with_shape(mut self, shape: ParticleShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets the base sprite diameter in pixels.
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the brightness each sprite adds at its center.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Returns the sprite shape.
+    pub fn shape(&self) -> ParticleShape {
+        self.shape
+    }
+
+    /// Returns the number of particles in the buffer.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Replaces the buffered particles with `particles`.
+    #[allow(unsafe_code)]
+    pub fn upload(&mut self, gl: &glow::Context, particles: &[[f32; 4]]) {
+        use glow::HasContext;
+
+        let bytes = texels_to_bytes(particles.as_flattened());
+        // SAFETY: the buffer is valid and `bytes` is a plain byte slice.
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer));
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &bytes, glow::STREAM_DRAW);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        }
+        self.count = particles.len();
+    }
+
+    /// Adds the particles from `source` onto `target` (which is not
+    /// cleared), colored through `lut`. Draw into an
+    /// [`AntiAliasedTarget`](super::AntiAliasedTarget) for smooth edges on
+    /// small or hard-edged sprites.
+    ///
+    /// `lut` is sampled along its first row: `t = 0` hits the first texel
+    /// center and `t = 1` the last.
+    #[allow(unsafe_code)]
+    pub fn draw(
+        &self,
+        gl: &glow::Context,
+        target: &impl DrawTarget,
+        source: ParticleSource,
+        lut: glow::Texture,
+    ) {
+        use glow::HasContext;
+
+        let (count, positions) = match source {
+            ParticleSource::Buffer => (self.count, None),
+            ParticleSource::Texture { texture, count } => (count, Some(texture)),
+        };
+        if count == 0 {
+            return;
+        }
+        let points = self.shape == ParticleShape::Point;
+
+        target.bind(gl);
+        // SAFETY: program, vao, buffer, and textures are valid handles; the
+        // attribute array is only enabled for the buffer source, which holds
+        // `count` particles.
+        unsafe {
+            gl.use_program(Some(self.program));
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(lut));
+            gl.active_texture(glow::TEXTURE1);
+            gl.bind_texture(glow::TEXTURE_2D, positions);
+            for (name, value) in [
+                ("u_lut", Uniform::Int(0)),
+                ("u_particles", Uniform::Int(1)),
+                (
+                    "u_from_texture",
+                    Uniform::Int(i32::from(positions.is_some())),
+                ),
+                ("u_points", Uniform::Int(i32::from(points))),
+                ("u_size", Uniform::Float(self.size)),
+                ("u_intensity", Uniform::Float(self.intensity)),
+                (
+                    "u_resolution",
+                    Uniform::Vec2([target.width() as f32, target.height() as f32]),
+                ),
+            ] {
+                if let Some(location) = gl.get_uniform_location(self.program, name) {
+                    value.upload(gl, &location);
+                }
+            }
+
+            gl.bind_vertex_array(Some(self.vao));
+            if positions.is_none() {
+                gl.enable_vertex_attrib_array(PARTICLE_ATTRIBUTE);
+                gl.vertex_attrib_divisor(PARTICLE_ATTRIBUTE, u32::from(!points));
+            }
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::ONE, glow::ONE);
+            if points {
+                gl.draw_arrays(glow::POINTS, 0, count as i32);
+            } else {
+                gl.draw_arrays_instanced(glow::TRIANGLES, 0, 6, count as i32);
+            }
+            gl.disable(glow::BLEND);
+            gl.disable_vertex_attrib_array(PARTICLE_ATTRIBUTE);
+            gl.bind_vertex_array(None);
+
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.use_program(None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    /// Deletes the program, vertex array, and buffer.
+    ///
+    /// Like [`RenderTarget::destroy`](super::RenderTarget::destroy), call
+    /// this before dropping the renderer for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: the handles are valid and were created in new().
+        unsafe {
+            gl.delete_program(self.program);
+            gl.delete_vertex_array(self.vao);
+            gl.delete_buffer(self.buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quads_are_the_default_shape() {
+        assert_eq!(ParticleShape::default(), ParticleShape::Quad);
+    }
+
+    #[test]
+    fn particle_attribute_matches_the_shader_layout() {
+        assert!(PARTICLE_VERTEX_SHADER.contains(&format!(
+            "layout(location = {PARTICLE_ATTRIBUTE}) in vec4 a_particle"
+        )));
+    }
+
+    #[cfg(feature = "headless")]
+    mod gpu {
+        use super::*;
+        use crate::image::Image;
+        use crate::render::{
+            read_image, upload_image, HeadlessContext, RenderTarget, SimulationPass,
+        };
+
+        const SIZE: usize = 16;
+
+        /// Two-entry LUT from pure red (t = 0) to pure blue (t = 1).
+        fn lut(gl: &glow::Context) -> glow::Texture {
+            let mut image = Image::new(2, 1).unwrap();
+            image.set_pixel(0, 0, [1.0, 0.0, 0.0, 1.0]).unwrap();
+            image.set_pixel(1, 0, [0.0, 0.0, 1.0, 1.0]).unwrap();
+            upload_image(gl, &image).unwrap()
+        }
+
+        fn render(
+            gl: &glow::Context,
+            renderer: &ParticleRenderer,
+            source: ParticleSource,
+        ) -> Image {
+            let target = RenderTarget::new(gl, SIZE as u32, SIZE as u32).unwrap();
+            target.bind(gl);
+            let lut = lut(gl);
+            renderer.draw(gl, &target, source, lut);
+            let image = read_image(gl, &target).unwrap();
+            target.destroy(gl);
+            image
+        }
+
+        #[test]
+        fn sprites_are_soft_colored_and_placed_by_row() {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let mut renderer = ParticleRenderer::new(gl).unwrap().with_size(8.0);
+            // Centered on the pixel corner at (4, 4), upper-left quadrant
+            renderer.upload(gl, &[[0.25, 0.25, 1.0, 1.0]]);
+            let image = render(gl, &renderer, ParticleSource::Buffer);
+
+            let center = image.pixel(4, 4).unwrap();
+            assert!(center[2] > 0.8 && center[0] == 0.0, "{center:?}");
+            assert_eq!(image.pixel(12, 12).unwrap(), [0.0; 4]);
+            assert_eq!(image.pixel(4, 12).unwrap(), [0.0; 4]);
+            // Symmetric about the center, fading to nothing at the radius
+            assert_eq!(image.pixel(3, 3), image.pixel(4, 4));
+            assert!(image.pixel(0, 4).unwrap()[2] < 0.1);
+            renderer.destroy(gl);
+        }
+
+        #[test]
+        fn overlapping_sprites_add() {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let mut renderer = ParticleRenderer::new(gl)
+                .unwrap()
+                .with_size(8.0)
+                .with_intensity(0.25);
+            renderer.upload(gl, &[[0.5, 0.5, 0.0, 1.0]]);
+            let once = render(gl, &renderer, ParticleSource::Buffer);
+            renderer.upload(gl, &[[0.5, 0.5, 0.0, 1.0]; 3]);
+            let thrice = render(gl, &renderer, ParticleSource::Buffer);
+            for (a, b) in once.data().iter().zip(thrice.data()) {
+                assert!((3.0 * a - b).abs() < 2e-3, "{a} x 3 vs {b}");
+            }
+            renderer.destroy(gl);
+        }
+
+        #[test]
+        fn texture_source_and_points_match_buffered_quads() {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let particles = [
+                [0.25, 0.5, 0.0, 1.0],
+                [0.75, 0.5, 1.0, 0.5],
+                [0.5, 0.75, 0.5, 1.5],
+            ];
+            let mut renderer = ParticleRenderer::new(gl).unwrap().with_size(6.0);
+            renderer.upload(gl, &particles);
+            let buffered = render(gl, &renderer, ParticleSource::Buffer);
+
+            // A 2x2 state texture with one unused texel
+            let passthrough = r#"#version 300 es
+precision highp float;
+in vec2 v_uv;
+uniform sampler2D u_state;
+out vec4 fragColor;
+void main() { fragColor = texture(u_state, v_uv); }
+"#;
+            let mut state = SimulationPass::new(gl, 2, 2, passthrough).unwrap();
+            let mut texels = particles.as_flattened().to_vec();
+            texels.extend([9.0; 4]);
+            state.upload_state(gl, &texels).unwrap();
+            let source = ParticleSource::Texture {
+                texture: state.state().texture(),
+                count: particles.len(),
+            };
+            assert_eq!(render(gl, &renderer, source), buffered);
+
+            let points = ParticleRenderer::new(gl)
+                .unwrap()
+                .with_size(6.0)
+                .with_shape(ParticleShape::Point);
+            let drawn = render(gl, &points, source);
+            for (a, b) in drawn.data().iter().zip(buffered.data()) {
+                assert!((a - b).abs() < 2e-3, "point {a} vs quad {b}");
+            }
+            // The first particle is red, at (4, 8)
+            assert!(buffered.pixel(4, 8).unwrap()[0] > 0.5);
+            points.destroy(gl);
+            state.destroy(gl);
+            renderer.destroy(gl);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+//! GPU post-effect stack.
+//!
+//! `PostPass` runs a [`PostEffect`] list over a texture, one fullscreen
+//! draw per effect through a shader that switches on the effect kind, with
+//! the formulas of the CPU path in [`crate::post`]. Bloom entries are
+//! delegated to a [`BloomPass`] created on first use. Results ping-pong
+//! between two targets kept between calls, RGBA16F unless the pass is built
+//! for the RGBA8 fallback, where each draw is dithered.
+
+use crate::post::{grain_seed, PostEffect};
+
+use super::bloom::BloomPass;
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::ping_pong::PingPong;
+use super::preprocess::preprocess;
+use super::shader::compile_program;
+use super::simulation::Uniform;
+use super::target::{RenderTarget, TargetFormat};
+
+/// Applies one post effect, selected by `u_kind`, to `u_source`.
+///
+/// Kinds: 0 copy, 1 vignette, 2 chromatic aberration, 3 grain, 4
+/// scanlines. `u_params` holds the effect's parameters in declaration order.
+const POST_SHADER: &str = r#"#version 300 es
+precision highp float;
+precision highp int;
+in vec2 v_uv;
+uniform sampler2D u_source;
+uniform int u_kind;
+uniform vec4 u_params;
+uniform int u_seed;
+uniform float u_dither;
+out vec4 fragColor;
+#include "common.glsl"
+
+void main() {
+    vec2 size = vec2(textureSize(u_source, 0));
+    vec2 p = gl_FragCoord.xy;
+    vec4 c = texelFetch(u_source, ivec2(p), 0);
+    vec2 center = size * 0.5;
+    float corner = length(center);
+    vec3 rgb = c.rgb;
+    if (u_kind == 1) {
+        float d = length(p - center) / corner;
+        rgb *= 1.0 - u_params.x * smoothstep(u_params.y, u_params.y + u_params.z, d);
+    } else if (u_kind == 2) {
+        vec2 offset = (p - center) * (u_params.x * min(size.x, size.y) / corner);
+        rgb.r = texture(u_source, (p + offset) / size).r;
+        rgb.b = texture(u_source, (p - offset) / size).b;
+    } else if (u_kind == 3) {
+        uvec2 q = uvec2(p);
+        float noise = float(hash(q.x ^ hash(q.y ^ uint(u_seed)))) / 4294967296.0;
+        rgb += (noise - 0.5) * u_params.x;
+    } else if (u_kind == 4) {
+        float phase = p.y * u_params.x / size.y;
+        rgb *= 1.0 - u_params.y * 0.5 * (1.0 + cos(6.283185307179586 * phase));
+    }
+    rgb += dither(p, u_dither);
+    fragColor = vec4(clamp(rgb, 0.0, 1.0), c.a);
+}
+"#;
+
+/// `u_kind` that copies the source unchanged (apart from clamping and
+/// dithering).
+const COPY: i32 = 0;
+
+/// Returns the shader kind and parameters for a non-bloom effect.
+fn shader_inputs(effect: &PostEffect) -> Option<(i32, [f32; 4], u32)> {
+    match *effect {
+        PostEffect::Bloom(_) => None,
+        PostEffect::Vignette {
+            strength,
+            radius,
+            softness,
+        } => Some((1, [strength as f32, radius as f32, softness as f32, 0.0], 0)),
+        PostEffect::ChromaticAberration { amount } => Some((2, [amount as f32, 0.0, 0.0, 0.0], 0)),
+        PostEffect::Grain { amount, seed } => {
+            Some((3, [amount as f32, 0.0, 0.0, 0.0], grain_seed(seed)))
+        }
+        PostEffect::Scanlines { count, intensity } => {
+            Some((4, [count as f32, intensity as f32, 0.0, 0.0], 0))
+        }
+    }
+}
+
+/// Runs post stacks on the GPU.
+pub struct PostPass {
+    program: glow::Program,
+    vao: glow::VertexArray,
+    targets: [RenderTarget; 2],
+    ping_pong: PingPong,
+    bloom: Option<BloomPass>,
+    format: TargetFormat,
+}
+
+impl PostPass {
+    /// Compiles the post shader and allocates two RGBA16F targets of the
+    /// given size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shader fails to compile or a target cannot be
+    /// created.
+    pub fn new(gl: &glow::Context, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_format(gl, width, height, TargetFormat::Rgba16F)
+    }
+
+    /// Like [`PostPass::new`] with targets (and bloom) of the given format.
+    ///
+    /// # Errors
+    ///
+    /// As for [`PostPass::new`].
+    #[allow(unsafe_code)]
+    pub fn with_format(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+    ) -> Result<Self, String> {
+        use glow::HasContext;
+
+        let program = preprocess(POST_SHADER)
+            .map_err(|e| e.to_string())
+            .and_then(|source| {
+                compile_program(gl, FULLSCREEN_VERTEX_SHADER, &source).map_err(|e| e.to_string())
+            })?;
+        let release = || {
+            // SAFETY: program was linked above and is not in use.
+            unsafe { gl.delete_program(program) };
+        };
+        // SAFETY: glow wraps raw GL calls as unsafe; the program is
+        // released on error.
+        let vao = unsafe { gl.create_vertex_array() }.inspect_err(|_| release())?;
+        let targets = RenderTarget::with_format(gl, width, height, format).and_then(|first| {
+            match RenderTarget::with_format(gl, width, height, format) {
+                Ok(second) => Ok([first, second]),
+                Err(e) => {
+                    first.destroy(gl);
+                    Err(e)
+                }
+            }
+        });
+        let targets = targets.inspect_err(|_| {
+            release();
+            // SAFETY: vao was created above.
+            unsafe { gl.delete_vertex_array(vao) };
+        })?;
+        Ok(Self {
+            program,
+            vao,
+            targets,
+            ping_pong: PingPong::new(),
+            bloom: None,
+            format,
+        })
+    }
+
+    /// Returns the format of the pass's targets.
+    pub fn format(&self) -> TargetFormat {
+        self.format
+    }
+
+    /// Returns the width of the output.
+    pub fn width(&self) -> u32 {
+        self.targets[0].width()
+    }
+
+    /// Returns the height of the output.
+    pub fn height(&self) -> u32 {
+        self.targets[0].height()
+    }
+
+    /// Returns the target holding the latest result.
+    pub fn output(&self) -> &RenderTarget {
+        &self.targets[self.ping_pong.src_index()]
+    }
+
+    /// Runs `effects` over `source`, in order, and returns the target
+    /// holding the result. An empty stack copies `source`.
+    ///
+    /// `source` should be the size of the pass and must not be one of its
+    /// own targets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bloom pass cannot be created or allocate its
+    /// targets.
+    pub fn apply(
+        &mut self,
+        gl: &glow::Context,
+        source: glow::Texture,
+        effects: &[PostEffect],
+    ) -> Result<&RenderTarget, String> {
+        let mut input = source;
+        // Whether `input` is our own latest output rather than an outside
+        // texture that still needs copying in
+        let mut owned = false;
+        for effect in effects {
+            match effect {
+                PostEffect::Bloom(bloom) => {
+                    let pass = match &mut self.bloom {
+                        Some(pass) => pass,
+                        None => self.bloom.insert(BloomPass::with_format(
+                            gl,
+                            self.width(),
+                            self.height(),
+                            self.format,
+                        )?),
+                    };
+                    input = pass.apply(gl, input, bloom)?.texture();
+                    owned = false;
+                }
+                other => {
+                    if let Some((kind, params, seed)) = shader_inputs(other) {
+                        self.draw(gl, input, kind, params, seed);
+                        input = self.output().texture();
+                        owned = true;
+                    }
+                }
+            }
+        }
+        if !owned {
+            self.draw(gl, input, COPY, [0.0; 4], 0);
+        }
+        Ok(self.output())
+    }
+
+    /// Reallocates the targets (and the bloom pass, if any) at a new size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a target cannot be resized.
+    pub fn resize(&mut self, gl: &glow::Context, width: u32, height: u32) -> Result<(), String> {
+        for target in &mut self.targets {
+            target.resize(gl, width, height)?;
+        }
+        match &mut self.bloom {
+            Some(bloom) => bloom.resize(gl, width, height),
+            None => Ok(()),
+        }
+    }
+
+    /// Draws one effect from `input` into the next target and swaps.
+    #[allow(unsafe_code)]
+    fn draw(
+        &mut self,
+        gl: &glow::Context,
+        input: glow::Texture,
+        kind: i32,
+        params: [f32; 4],
+        seed: u32,
+    ) {
+        use glow::HasContext;
+
+        self.targets[self.ping_pong.dst_index()].bind(gl);
+        // SAFETY: program, vao, and input are valid handles; input is not
+        // attached to the bound framebuffer (it is the other target or an
+        // outside texture).
+        unsafe {
+            gl.use_program(Some(self.program));
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(input));
+            for (name, value) in [
+                ("u_source", Uniform::Int(0)),
+                ("u_kind", Uniform::Int(kind)),
+                ("u_params", Uniform::Vec4(params)),
+                ("u_seed", Uniform::Int(seed as i32)),
+                ("u_dither", Uniform::Float(self.format.dither())),
+            ] {
+                if let Some(location) = gl.get_uniform_location(self.program, name) {
+                    value.upload(gl, &location);
+                }
+            }
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.bind_vertex_array(None);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.use_program(None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        self.ping_pong.swap();
+    }
+
+    /// Deletes the program, vertex array, targets, and bloom pass.
+    ///
+    /// Like [`RenderTarget::destroy`], call this before dropping the pass
+    /// for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: the program and vao are valid handles from new().
+        unsafe {
+            gl.delete_program(self.program);
+            gl.delete_vertex_array(self.vao);
+        }
+        self.targets.iter().for_each(|target| target.destroy(gl));
+        if let Some(bloom) = &self.bloom {
+            bloom.destroy(gl);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bloom::Bloom;
+
+    #[test]
+    fn every_effect_but_bloom_has_a_shader_kind() {
+        let effects = [
+            PostEffect::Vignette {
+                strength: 0.5,
+                radius: 0.2,
+                softness: 0.6,
+            },
+            PostEffect::ChromaticAberration { amount: 0.01 },
+            PostEffect::Grain {
+                amount: 0.1,
+                seed: 9,
+            },
+            PostEffect::Scanlines {
+                count: 100.0,
+                intensity: 0.3,
+            },
+        ];
+        let kinds: Vec<i32> = effects
+            .iter()
+            .map(|e| shader_inputs(e).unwrap().0)
+            .collect();
+        assert_eq!(kinds, [1, 2, 3, 4]);
+        assert_eq!(shader_inputs(&effects[2]).unwrap().2, grain_seed(9));
+        assert!(shader_inputs(&PostEffect::Bloom(Bloom::default())).is_none());
+    }
+
+    #[cfg(feature = "headless")]
+    mod gpu {
+        use super::*;
+        use crate::image::Image;
+        use crate::post::apply_stack;
+        use crate::render::{read_image, upload_image, HeadlessContext};
+
+        /// A smooth test image with a bright spot, so bilinear taps agree
+        /// closely with the CPU sampler.
+        fn image(w: usize, h: usize) -> Image {
+            let mut image = Image::new(w, h).unwrap();
+            for (i, px) in image.data_mut().chunks_exact_mut(4).enumerate() {
+                let (x, y) = ((i % w) as f64, (i / w) as f64);
+                let spot = (-((x - 22.0).powi(2) + (y - 8.0).powi(2)) / 30.0).exp();
+                let ramp = x / w as f64;
+                px.copy_from_slice(&[spot, 0.5 * ramp, 0.2 + 0.5 * spot, 0.75]);
+            }
+            image
+        }
+
+        fn assert_matches_cpu(effects: &[PostEffect]) {
+            assert_close_to_cpu(effects, TargetFormat::Rgba16F, 4e-3);
+        }
+
+        fn assert_close_to_cpu(effects: &[PostEffect], format: TargetFormat, tolerance: f64) {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let (w, h) = (33, 19);
+            let image = image(w, h);
+            let texture = upload_image(gl, &image).unwrap();
+            let mut pass = PostPass::with_format(gl, w as u32, h as u32, format).unwrap();
+            let gpu = read_image(gl, pass.apply(gl, texture, effects).unwrap()).unwrap();
+            let cpu = apply_stack(effects, &image);
+            for (i, (g, c)) in gpu.data().iter().zip(cpu.data()).enumerate() {
+                assert!(
+                    (g - c).abs() < tolerance,
+                    "{effects:?} value {i}: gpu {g} vs cpu {c}"
+                );
+            }
+            pass.destroy(gl);
+        }
+
+        #[test]
+        fn empty_stack_copies_the_source() {
+            assert_matches_cpu(&[]);
+        }
+
+        #[test]
+        fn each_effect_matches_the_cpu_path() {
+            for effect in [
+                PostEffect::Vignette {
+                    strength: 0.7,
+                    radius: 0.2,
+                    softness: 0.6,
+                },
+                PostEffect::ChromaticAberration { amount: 0.1 },
+                PostEffect::Grain {
+                    amount: 0.3,
+                    seed: 42,
+                },
+                PostEffect::Scanlines {
+                    count: 7.0,
+                    intensity: 0.6,
+                },
+            ] {
+                assert_matches_cpu(&[effect]);
+            }
+        }
+
+        #[test]
+        fn stacks_with_bloom_match_the_cpu_path() {
+            let bloom = PostEffect::Bloom(Bloom::new(1.0).with_threshold(0.3));
+            let vignette = PostEffect::Vignette {
+                strength: 0.5,
+                radius: 0.3,
+                softness: 0.5,
+            };
+            assert_matches_cpu(&[bloom.clone(), vignette.clone()]);
+            assert_matches_cpu(&[vignette, bloom]);
+        }
+
+        #[test]
+        fn rgba8_stacks_stay_close_to_the_cpu_path() {
+            // Dithering moves a copy by at most one 8-bit step from rounding
+            assert_close_to_cpu(&[], TargetFormat::Rgba8, 1.5 / 255.0);
+            let stack = [
+                PostEffect::Bloom(Bloom::new(1.0).with_threshold(0.3)),
+                PostEffect::Vignette {
+                    strength: 0.5,
+                    radius: 0.3,
+                    softness: 0.5,
+                },
+                PostEffect::Scanlines {
+                    count: 7.0,
+                    intensity: 0.4,
+                },
+            ];
+            assert_close_to_cpu(&stack, TargetFormat::Rgba8, 4.0 / 255.0);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! A tiny GLSL preprocessor for sharing code between shader strings.
+//!
+//! [`Preprocessor::process`] expands `#include "name"` lines from a table of
+//! named snippets and injects `#define`s right after the `#version` line
+//! (which GLSL requires to come first). Each snippet is pasted at most once
+//! per shader, so snippets may include each other freely; a snippet that
+//! (indirectly) includes itself is an error. Everything else, including
+//! `#ifdef` blocks on the injected defines, is left to the driver's own
+//! preprocessor.
+//!
+//! [`Preprocessor::new`] registers the built-in snippets:
+//!
+//! - `common.glsl` -- [`COMMON_GLSL`]: `dither` and `hash`.
+//! - `palette_lut.glsl` -- [`PALETTE_LUT_GLSL`]: `palette_lut`.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use super::palette_lut::PALETTE_LUT_GLSL;
+
+/// Helpers shared by the built-in shaders.
+pub const COMMON_GLSL: &str = r#"
+// Interleaved gradient noise at pixel p, scaled to +-amount/2; with amount
+// 1/255 it stays within half a step of an 8-bit target.
+vec3 dither(vec2 p, float amount) {
+    return vec3(amount * (fract(52.9829189 * fract(dot(p, vec2(0.06711056, 0.00583715)))) - 0.5));
+}
+
+// Integer hash with good avalanche (lowbias32).
+uint hash(uint x) {
+    x ^= x >> 16u;
+    x *= 0x7feb352du;
+    x ^= x >> 15u;
+    x *= 0x846ca68bu;
+    x ^= x >> 16u;
+    return x;
+}
+"#;
+
+/// Errors from expanding a shader source.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PreprocessError {
+    /// An `#include` named a snippet that is not registered.
+    #[error("unknown include \"{0}\"")]
+    UnknownInclude(String),
+    /// A snippet includes itself, directly or through others.
+    #[error("include cycle: {0}")]
+    IncludeCycle(String),
+    /// An `#include` line without a quoted name.
+    #[error("malformed include on line {line}: {text}")]
+    MalformedInclude {
+        /// The 1-based line number within the file containing it.
+        line: usize,
+        /// The offending line.
+        text: String,
+    },
+}
+
+/// Include snippets and defines applied to shader sources.
+#[derive(Debug, Clone)]
+pub struct Preprocessor {
+    includes: BTreeMap<String, String>,
+    defines: BTreeMap<String, String>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preprocessor {
+    /// A preprocessor with the built-in snippets and no defines.
+    pub fn new() -> Self {
+        Self {
+            includes: BTreeMap::new(),
+            defines: BTreeMap::new(),
+        }
+        .with_include("common.glsl", COMMON_GLSL)
+        .with_include("palette_lut.glsl", PALETTE_LUT_GLSL)
+    }
+
+    /// Returns this preprocessor with a snippet registered under `name`,
+    /// replacing any snippet of the same name.
+    pub fn with_include(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.includes.insert(name.into(), source.into());
+        self
+    }
+
+    /// Returns this preprocessor defining `name` as `value`.
+    ///
+    /// Values are pasted as written, so float constants need a decimal
+    /// point (`255.0`, not `255`) to type-check in GLSL ES.
+    pub fn with_define(mut self, name: impl Into<String>, value: impl Display) -> Self {
+        self.defines.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// Returns this preprocessor with `name` defined (without a value) or
+    /// not, for `#ifdef` toggles.
+    pub fn with_flag(mut self, name: impl Into<String>, on: bool) -> Self {
+        let name = name.into();
+        if on {
+            self.defines.insert(name, String::new());
+        } else {
+            self.defines.remove(&name);
+        }
+        self
+    }
+
+    /// Returns the registered snippet names.
+    pub fn includes(&self) -> impl Iterator<Item = &str> {
+        self.includes.keys().map(String::as_str)
+    }
+
+    /// Expands `source`: includes are pasted in place and defines follow
+    /// the `#version` line (or lead the output if there is none).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for unknown, malformed, or cyclic includes.
+    pub fn process(&self, source: &str) -> Result<String, PreprocessError> {
+        let mut body = Vec::new();
+        let mut included = Vec::new();
+        self.expand(source, &mut Vec::new(), &mut included, &mut body)?;
+
+        let defines = self.defines.iter().map(|(name, value)| {
+            if value.is_empty() {
+                format!("#define {name}")
+            } else {
+                format!("#define {name} {value}")
+            }
+        });
+        let at = body
+            .iter()
+            .position(|line| line.trim_start().starts_with("#version"))
+            .map_or(0, |i| i + 1);
+        let tail = body.split_off(at);
+        body.extend(defines);
+        body.extend(tail);
+
+        let mut out = body.join("\n");
+        if source.ends_with('\n') {
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Appends the lines of `source` to `out`, expanding includes; `stack`
+    /// holds the snippets being expanded and `included` every snippet
+    /// pasted so far.
+    fn expand<'a>(
+        &'a self,
+        source: &'a str,
+        stack: &mut Vec<&'a str>,
+        included: &mut Vec<&'a str>,
+        out: &mut Vec<String>,
+    ) -> Result<(), PreprocessError> {
+        for (i, line) in source.lines().enumerate() {
+            let Some(rest) = line.trim_start().strip_prefix("#include") else {
+                out.push(line.to_string());
+                continue;
+            };
+            let name = rest
+                .trim()
+                .strip_prefix('"')
+                .and_then(|rest| rest.strip_suffix('"'))
+                .filter(|name| !name.is_empty() && !name.contains('"'))
+                .ok_or_else(|| PreprocessError::MalformedInclude {
+                    line: i + 1,
+                    text: line.to_string(),
+                })?;
+            let (name, snippet) = self
+                .includes
+                .get_key_value(name)
+                .ok_or_else(|| PreprocessError::UnknownInclude(name.to_string()))?;
+            if stack.contains(&name.as_str()) {
+                let mut chain = stack.clone();
+                chain.push(name);
+                return Err(PreprocessError::IncludeCycle(chain.join(" -> ")));
+            }
+            if included.contains(&name.as_str()) {
+                continue;
+            }
+            included.push(name);
+            stack.push(name);
+            self.expand(snippet, stack, included, out)?;
+            stack.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Expands `source` with the built-in snippets and no defines.
+///
+/// # Errors
+///
+/// As for [`Preprocessor::process`].
+pub fn preprocess(source: &str) -> Result<String, PreprocessError> {
+    Preprocessor::new().process(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_without_directives_pass_through() {
+        let source = "#version 300 es\nprecision highp float;\nvoid main() {}\n";
+        assert_eq!(preprocess(source).unwrap(), source);
+        assert_eq!(preprocess("void main() {}").unwrap(), "void main() {}");
+    }
+
+    #[test]
+    fn includes_are_pasted_once() {
+        let pre = Preprocessor::new()
+            .with_include(
+                "a.glsl",
+                "#include \"common.glsl\"\nfloat a() { return 1.0; }",
+            )
+            .with_include("b.glsl", "  #include \"a.glsl\"\nfloat b() { return a(); }");
+        let out = pre
+            .process("#include \"b.glsl\"\n#include \"common.glsl\"\nvoid main() {}")
+            .unwrap();
+        assert_eq!(out.matches("vec3 dither(").count(), 1);
+        let (a, b) = (
+            out.find("float a()").unwrap(),
+            out.find("float b()").unwrap(),
+        );
+        assert!(out.find("uint hash(").unwrap() < a && a < b);
+        assert!(out.ends_with("void main() {}"));
+        assert!(!out.contains("#include"));
+    }
+
+    #[test]
+    fn defines_follow_the_version_line() {
+        let pre = Preprocessor::new()
+            .with_define("LUT_SIZE", 256)
+            .with_define("GAIN", "2.0")
+            .with_flag("USE_BLOOM", true)
+            .with_flag("DEBUG", true)
+            .with_flag("DEBUG", false);
+        let out = pre.process("#version 300 es\nvoid main() {}\n").unwrap();
+        assert_eq!(
+            out,
+            "#version 300 es\n#define GAIN 2.0\n#define LUT_SIZE 256\n#define USE_BLOOM\nvoid main() {}\n"
+        );
+        assert!(pre
+            .process("void main() {}")
+            .unwrap()
+            .starts_with("#define GAIN 2.0\n"));
+    }
+
+    #[test]
+    fn bad_includes_are_errors() {
+        assert_eq!(
+            preprocess("#include \"missing.glsl\""),
+            Err(PreprocessError::UnknownInclude("missing.glsl".into()))
+        );
+        assert_eq!(
+            preprocess("void main() {}\n#include common.glsl"),
+            Err(PreprocessError::MalformedInclude {
+                line: 2,
+                text: "#include common.glsl".into(),
+            })
+        );
+        let pre = Preprocessor::new()
+            .with_include("a.glsl", "#include \"b.glsl\"")
+            .with_include("b.glsl", "#include \"a.glsl\"");
+        assert_eq!(
+            pre.process("#include \"a.glsl\""),
+            Err(PreprocessError::IncludeCycle(
+                "a.glsl -> b.glsl -> a.glsl".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn built_in_snippets_are_registered() {
+        let pre = Preprocessor::new();
+        let names: Vec<&str> = pre.includes().collect();
+        assert_eq!(names, ["common.glsl", "palette_lut.glsl"]);
+        assert!(preprocess("#include \"palette_lut.glsl\"")
+            .unwrap()
+            .contains("vec4 palette_lut(sampler2D lut, float t)"));
+    }
+}
@@ -0,0 +1,382 @@
+//! Compiled program cache with uniform location tracking and hot reload.
+//!
+//! `ProgramCache` compiles each (vertex, fragment) source pair once, keyed
+//! by a hash of the sources, and remembers the uniform locations looked up
+//! on each program, so passes that rebuild shaders from strings (or share
+//! them) skip both recompiles and per-frame `glGetUniformLocation` calls.
+//!
+//! On native targets, [`HotProgram`] loads a program from shader files and
+//! recompiles it when their modification times change, keeping the last
+//! good program on compile errors -- useful while iterating on an effect.
+
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::shader::{compile_program, ShaderError};
+use super::simulation::{Uniform, Uniforms};
+
+/// Hashes a source pair; the stages are hashed separately so swapping them
+/// changes the key.
+fn source_key(vertex: &str, fragment: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    vertex.hash(&mut hasher);
+    fragment.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A linked program with its looked-up uniform locations.
+pub struct CachedProgram {
+    program: glow::Program,
+    locations: HashMap<String, Option<glow::UniformLocation>>,
+}
+
+impl CachedProgram {
+    /// Returns the program handle.
+    pub fn program(&self) -> glow::Program {
+        self.program
+    }
+
+    /// Makes the program current.
+    #[allow(unsafe_code)]
+    pub fn bind(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: the program is a valid handle owned by the cache.
+        unsafe { gl.use_program(Some(self.program)) };
+    }
+
+    /// Returns the location of `name`, querying GL only the first time.
+    ///
+    /// `None` means the uniform is not active (absent, or optimized out).
+    #[allow(unsafe_code)]
+    pub fn uniform_location(
+        &mut self,
+        gl: &glow::Context,
+        name: &str,
+    ) -> Option<&glow::UniformLocation> {
+        use glow::HasContext;
+
+        if !self.locations.contains_key(name) {
+            // SAFETY: the program is a valid, linked handle.
+            let location = unsafe { gl.get_uniform_location(self.program, name) };
+            self.locations.insert(name.to_string(), location);
+        }
+        self.locations[name].as_ref()
+    }
+
+    /// Uploads `value` to `name` if it is active. The program must be
+    /// current (see [`CachedProgram::bind`]).
+    ///
+    /// Returns whether the uniform was active.
+    pub fn set_uniform(
+        &mut self,
+        gl: &glow::Context,
+        name: &str,
+        value: impl Into<Uniform>,
+    ) -> bool {
+        match self.uniform_location(gl, name) {
+            Some(location) => {
+                value.into().upload(gl, location);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Uploads every value in `uniforms`, skipping inactive ones. The
+    /// program must be current.
+    pub fn set_uniforms(&mut self, gl: &glow::Context, uniforms: &Uniforms) {
+        for (name, value) in uniforms.iter() {
+            self.set_uniform(gl, name, value);
+        }
+    }
+
+    /// Returns the number of uniform names looked up so far.
+    pub fn tracked_uniforms(&self) -> usize {
+        self.locations.len()
+    }
+}
+
+/// Caches compiled programs by source.
+#[derive(Default)]
+pub struct ProgramCache {
+    programs: HashMap<u64, CachedProgram>,
+}
+
+impl ProgramCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the program for the source pair, compiling and linking it
+    /// on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns the compile or link error; failures are not cached.
+    pub fn get(
+        &mut self,
+        gl: &glow::Context,
+        vertex: &str,
+        fragment: &str,
+    ) -> Result<&mut CachedProgram, ShaderError> {
+        match self.programs.entry(source_key(vertex, fragment)) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(CachedProgram {
+                program: compile_program(gl, vertex, fragment)?,
+                locations: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Like [`ProgramCache::get`] with [`FULLSCREEN_VERTEX_SHADER`].
+    ///
+    /// # Errors
+    ///
+    /// As for [`ProgramCache::get`].
+    pub fn fullscreen(
+        &mut self,
+        gl: &glow::Context,
+        fragment: &str,
+    ) -> Result<&mut CachedProgram, ShaderError> {
+        self.get(gl, FULLSCREEN_VERTEX_SHADER, fragment)
+    }
+
+    /// Returns whether the source pair has been compiled.
+    pub fn contains(&self, vertex: &str, fragment: &str) -> bool {
+        self.programs.contains_key(&source_key(vertex, fragment))
+    }
+
+    /// Deletes the program for the source pair, returning whether it was
+    /// cached.
+    pub fn remove(&mut self, gl: &glow::Context, vertex: &str, fragment: &str) -> bool {
+        self.remove_key(gl, source_key(vertex, fragment))
+    }
+
+    /// Returns the number of cached programs.
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    /// Deletes every cached program.
+    ///
+    /// Like [`RenderTarget::destroy`](super::RenderTarget::destroy), call
+    /// this before dropping the cache for deterministic cleanup.
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        let keys: Vec<u64> = self.programs.keys().copied().collect();
+        for key in keys {
+            self.remove_key(gl, key);
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn remove_key(&mut self, gl: &glow::Context, key: u64) -> bool {
+        use glow::HasContext;
+
+        match self.programs.remove(&key) {
+            Some(cached) => {
+                // SAFETY: the program is a valid handle owned by the cache.
+                unsafe { gl.delete_program(cached.program) };
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A program loaded from shader files and recompiled when they change.
+///
+/// The vertex stage defaults to [`FULLSCREEN_VERTEX_SHADER`]. Programs live
+/// in a [`ProgramCache`]; each successful reload evicts the previous
+/// version. Native only: it polls file modification times.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct HotProgram {
+    fragment: std::path::PathBuf,
+    vertex: Option<std::path::PathBuf>,
+    modified: Vec<Option<std::time::SystemTime>>,
+    key: Option<u64>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HotProgram {
+    /// Creates a program from a fragment shader file. Nothing is read
+    /// until the first [`HotProgram::poll`].
+    pub fn new(fragment: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            fragment: fragment.into(),
+            vertex: None,
+            modified: Vec::new(),
+            key: None,
+        }
+    }
+
+    /// Reads the vertex stage from a file too.
+    pub fn with_vertex(mut self, vertex: impl Into<std::path::PathBuf>) -> Self {
+        self.vertex = Some(vertex.into());
+        self
+    }
+
+    /// Recompiles the program if a file changed since the last poll (or
+    /// nothing has compiled yet). Returns whether a new program was built.
+    ///
+    /// Call it once per frame in dev builds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or the new source fails to
+    /// compile. The previous program, if any, stays in use, and the same
+    /// files are not retried until they change again.
+    pub fn poll(&mut self, gl: &glow::Context, cache: &mut ProgramCache) -> Result<bool, String> {
+        let paths: Vec<&std::path::Path> = self
+            .vertex
+            .iter()
+            .chain([&self.fragment])
+            .map(|p| p.as_path())
+            .collect();
+        let modified: Vec<_> = paths
+            .iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect();
+        if !self.modified.is_empty() && modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+
+        let read = |path: &std::path::Path| {
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))
+        };
+        let fragment = read(&self.fragment)?;
+        let vertex = match &self.vertex {
+            Some(path) => read(path)?,
+            None => FULLSCREEN_VERTEX_SHADER.to_string(),
+        };
+        let key = source_key(&vertex, &fragment);
+        if self.key == Some(key) {
+            return Ok(false);
+        }
+        cache
+            .get(gl, &vertex, &fragment)
+            .map_err(|e| format!("{}: {e}", self.fragment.display()))?;
+        if let Some(previous) = self.key.replace(key) {
+            cache.remove_key(gl, previous);
+        }
+        Ok(true)
+    }
+
+    /// Returns the latest good program, if one has compiled.
+    pub fn get<'a>(&self, cache: &'a mut ProgramCache) -> Option<&'a mut CachedProgram> {
+        self.key.and_then(|key| cache.programs.get_mut(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_keys_depend_on_both_stages_in_order() {
+        let key = source_key("a", "b");
+        assert_eq!(key, source_key("a", "b"));
+        assert_ne!(key, source_key("b", "a"));
+        assert_ne!(key, source_key("a", "c"));
+        // Concatenation alone must not collide
+        assert_ne!(source_key("ab", ""), source_key("a", "b"));
+    }
+
+    #[test]
+    fn new_caches_are_empty() {
+        let cache = ProgramCache::new();
+        assert!(cache.is_empty());
+        assert!(!cache.contains(FULLSCREEN_VERTEX_SHADER, "void main() {}"));
+    }
+
+    #[cfg(feature = "headless")]
+    mod gpu {
+        use super::*;
+        use crate::render::HeadlessContext;
+
+        fn shader(value: f32) -> String {
+            format!(
+                "#version 300 es\nprecision highp float;\nuniform float u_gain;\nout vec4 fragColor;\nvoid main() {{ fragColor = vec4({value:?} * u_gain); }}\n"
+            )
+        }
+
+        #[test]
+        fn compiles_each_source_once_and_tracks_uniforms() {
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let mut cache = ProgramCache::new();
+
+            let first = cache.fullscreen(gl, &shader(0.5)).unwrap().program();
+            let cached = cache.fullscreen(gl, &shader(0.5)).unwrap();
+            assert_eq!(cached.program(), first);
+            cached.bind(gl);
+            assert!(cached.set_uniform(gl, "u_gain", 2.0));
+            assert!(!cached.set_uniform(gl, "u_missing", 1.0));
+            assert!(cached.uniform_location(gl, "u_gain").is_some());
+            assert_eq!(cached.tracked_uniforms(), 2);
+
+            cache.fullscreen(gl, &shader(0.25)).unwrap();
+            assert_eq!(cache.len(), 2);
+            assert!(cache.remove(gl, FULLSCREEN_VERTEX_SHADER, &shader(0.5)));
+            assert!(!cache.contains(FULLSCREEN_VERTEX_SHADER, &shader(0.5)));
+
+            assert!(cache.fullscreen(gl, "not glsl").is_err());
+            assert_eq!(cache.len(), 1);
+            cache.destroy(gl);
+            assert!(cache.is_empty());
+        }
+
+        #[test]
+        fn hot_programs_reload_changed_files() {
+            use std::time::{Duration, SystemTime};
+
+            let headless = HeadlessContext::new().unwrap();
+            let gl = headless.gl();
+            let dir = std::env::temp_dir().join(format!("art-engine-hot-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("effect.frag");
+            // Bumps the modification time explicitly, since writes within
+            // the file system's timestamp resolution would look unchanged
+            let write = |source: &str, age: u64| {
+                std::fs::write(&path, source).unwrap();
+                let file = std::fs::File::options().write(true).open(&path).unwrap();
+                file.set_modified(SystemTime::now() + Duration::from_secs(age))
+                    .unwrap();
+            };
+
+            let mut cache = ProgramCache::new();
+            let mut hot = HotProgram::new(&path);
+            assert!(hot.get(&mut cache).is_none());
+            write(&shader(0.5), 1);
+            assert!(hot.poll(gl, &mut cache).unwrap());
+            assert!(!hot.poll(gl, &mut cache).unwrap());
+            let first = hot.get(&mut cache).unwrap().program();
+
+            write(&shader(0.25), 2);
+            assert!(hot.poll(gl, &mut cache).unwrap());
+            assert_ne!(hot.get(&mut cache).unwrap().program(), first);
+            assert_eq!(cache.len(), 1, "the old version is evicted");
+
+            write("broken", 3);
+            assert!(hot.poll(gl, &mut cache).is_err());
+            assert!(
+                !hot.poll(gl, &mut cache).unwrap(),
+                "not retried until changed"
+            );
+            assert!(hot.get(&mut cache).is_some(), "last good program is kept");
+
+            cache.destroy(gl);
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,556 @@
+//! Fragment-shader simulations that run entirely on the GPU.
+//!
+//! A `SimulationPass` keeps the simulation state in a ping-pong pair of
+//! `RenderTarget`s. Each step draws a fullscreen triangle into the
+//! destination target with a user-supplied fragment shader that samples
+//! the source target, then swaps the pair. Engines provide only the GLSL
+//! step shader and its uniforms.
+//!
+//! The fragment shader is linked against [`FULLSCREEN_VERTEX_SHADER`] and
+//! sees these built-in uniforms alongside its own:
+//!
+//! ```text
+//! in vec2 v_uv;                  // cell center in [0, 1]
+//! uniform sampler2D u_state;     // previous state (texture unit 0)
+//! uniform vec2 u_resolution;     // state size in cells
+//! uniform int u_step;            // steps run before this one
+//! out vec4 fragColor;            // next state
+//! ```
+//!
+//! State textures sample with `NEAREST` filtering and `REPEAT` wrapping, so
+//! `texture(u_state, v_uv + offset / u_resolution)` reads neighbors with the
+//! same toroidal wrapping as [`Field`](crate::Field). Channels are RGBA16F:
+//! half precision is enough for most reaction-diffusion and automata rules
+//! but not for long accumulations.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::fullscreen::FULLSCREEN_VERTEX_SHADER;
+use super::ping_pong::PingPong;
+use super::shader::compile_program;
+use super::target::RenderTarget;
+
+/// Name of the sampler bound to the previous state.
+pub const STATE_UNIFORM: &str = "u_state";
+
+/// Name of the `vec2` holding the state size in cells.
+pub const RESOLUTION_UNIFORM: &str = "u_resolution";
+
+/// Name of the `int` holding the number of steps run so far.
+pub const STEP_UNIFORM: &str = "u_step";
+
+/// A uniform value uploaded before every step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Uniform {
+    /// A `float`.
+    Float(f32),
+    /// A `vec2`.
+    Vec2([f32; 2]),
+    /// A `vec3`.
+    Vec3([f32; 3]),
+    /// A `vec4`.
+    Vec4([f32; 4]),
+    /// An `int` (or `bool`).
+    Int(i32),
+}
+
+impl Uniform {
+    /// Uploads the value to `location` of the program currently in use.
+    #[allow(unsafe_code)]
+    pub fn upload(self, gl: &glow::Context, location: &glow::UniformLocation) {
+        use glow::HasContext;
+
+        // SAFETY: `location` is a valid location handle; GL reports an
+        // error (without undefined behavior) if its program is not in use
+        // or the type does not match.
+        unsafe {
+            match self {
+                Uniform::Float(x) => gl.uniform_1_f32(Some(location), x),
+                Uniform::Vec2([x, y]) => gl.uniform_2_f32(Some(location), x, y),
+                Uniform::Vec3([x, y, z]) => gl.uniform_3_f32(Some(location), x, y, z),
+                Uniform::Vec4([x, y, z, w]) => gl.uniform_4_f32(Some(location), x, y, z, w),
+                Uniform::Int(x) => gl.uniform_1_i32(Some(location), x),
+            }
+        }
+    }
+}
+
+impl From<f32> for Uniform {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<[f32; 2]> for Uniform {
+    fn from(value: [f32; 2]) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<[f32; 3]> for Uniform {
+    fn from(value: [f32; 3]) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<[f32; 4]> for Uniform {
+    fn from(value: [f32; 4]) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+impl From<i32> for Uniform {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+/// Named uniform values for a pass, kept in name order.
+///
+/// Pure bookkeeping with no GPU dependency: values are uploaded by
+/// [`SimulationPass::step`], so setting one between steps takes effect on
+/// the next step.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Uniforms {
+    values: BTreeMap<String, Uniform>,
+}
+
+impl Uniforms {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `name` to `value`, replacing any previous value.
+    pub fn set(&mut self, name: &str, value: impl Into<Uniform>) {
+        self.values.insert(name.to_string(), value.into());
+    }
+
+    /// Returns the value of `name`, if set.
+    pub fn get(&self, name: &str) -> Option<Uniform> {
+        self.values.get(name).copied()
+    }
+
+    /// Removes `name`, returning its value if it was set.
+    pub fn remove(&mut self, name: &str) -> Option<Uniform> {
+        self.values.remove(name)
+    }
+
+    /// Iterates over `(name, value)` pairs in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Uniform)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Returns the number of uniforms set.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether no uniforms are set.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Converts RGBA `f32` texels to the byte layout GL uploads and reads.
+pub(super) fn texels_to_bytes(texels: &[f32]) -> Vec<u8> {
+    texels.iter().flat_map(|v| v.to_ne_bytes()).collect()
+}
+
+/// Inverse of [`texels_to_bytes`].
+pub(super) fn bytes_to_texels(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// A GPU simulation stepped by a fragment shader over ping-pong state
+/// textures.
+pub struct SimulationPass {
+    program: glow::Program,
+    vao: glow::VertexArray,
+    targets: [RenderTarget; 2],
+    ping_pong: PingPong,
+    uniforms: Uniforms,
+    /// Uniform locations looked up so far; `None` for names the linked
+    /// program does not use.
+    locations: HashMap<String, Option<glow::UniformLocation>>,
+    width: u32,
+    height: u32,
+    steps: u64,
+}
+
+impl SimulationPass {
+    /// Compiles `fragment_src` against the fullscreen vertex shader and
+    /// allocates two `width x height` state textures, zeroed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shader fails to compile or link, or if the
+    /// state targets or vertex array cannot be created.
+    #[allow(unsafe_code)]
+    pub fn new(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        fragment_src: &str,
+    ) -> Result<Self, String> {
+        use glow::HasContext;
+
+        let program = compile_program(gl, FULLSCREEN_VERTEX_SHADER, fragment_src)
+            .map_err(|e| e.to_string())?;
+
+        let targets = match Self::create_targets(gl, width, height) {
+            Ok(targets) => targets,
+            Err(e) => {
+                // SAFETY: program is a valid handle from compile_program.
+                unsafe { gl.delete_program(program) };
+                return Err(e);
+            }
+        };
+
+        // SAFETY: glow wraps raw GL calls as unsafe. The fullscreen triangle
+        // needs an empty VAO bound; resources are released on error.
+        let vao = match unsafe { gl.create_vertex_array() } {
+            Ok(vao) => vao,
+            Err(e) => {
+                targets.iter().for_each(|target| target.destroy(gl));
+                unsafe { gl.delete_program(program) };
+                return Err(e);
+            }
+        };
+
+        let mut pass = Self {
+            program,
+            vao,
+            targets,
+            ping_pong: PingPong::new(),
+            uniforms: Uniforms::new(),
+            locations: HashMap::new(),
+            width,
+            height,
+            steps: 0,
+        };
+        pass.clear(gl);
+        Ok(pass)
+    }
+
+    /// Creates both state targets and switches them to `NEAREST` filtering
+    /// and `REPEAT` wrapping.
+    #[allow(unsafe_code)]
+    fn create_targets(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+    ) -> Result<[RenderTarget; 2], String> {
+        use glow::HasContext;
+
+        let first = RenderTarget::new(gl, width, height)?;
+        let second = match RenderTarget::new(gl, width, height) {
+            Ok(target) => target,
+            Err(e) => {
+                first.destroy(gl);
+                return Err(e);
+            }
+        };
+
+        for target in [&first, &second] {
+            // SAFETY: the texture handle is valid for the target's lifetime.
+            unsafe {
+                gl.bind_texture(glow::TEXTURE_2D, Some(target.texture()));
+                for (param, value) in [
+                    (glow::TEXTURE_MIN_FILTER, glow::NEAREST),
+                    (glow::TEXTURE_MAG_FILTER, glow::NEAREST),
+                    (glow::TEXTURE_WRAP_S, glow::REPEAT),
+                    (glow::TEXTURE_WRAP_T, glow::REPEAT),
+                ] {
+                    gl.tex_parameter_i32(glow::TEXTURE_2D, param, value as i32);
+                }
+            }
+        }
+        // SAFETY: unbinding is always valid.
+        unsafe { gl.bind_texture(glow::TEXTURE_2D, None) };
+
+        Ok([first, second])
+    }
+
+    /// Builder-style [`set_uniform`](Self::set_uniform).
+    pub fn with_uniform(mut self, name: &str, value: impl Into<Uniform>) -> Self {
+        self.set_uniform(name, value);
+        self
+    }
+
+    /// Sets a uniform uploaded before every subsequent step.
+    pub fn set_uniform(&mut self, name: &str, value: impl Into<Uniform>) {
+        self.uniforms.set(name, value);
+    }
+
+    /// Returns the uniforms uploaded before each step.
+    pub fn uniforms(&self) -> &Uniforms {
+        &self.uniforms
+    }
+
+    /// Returns the uniforms for direct editing.
+    pub fn uniforms_mut(&mut self) -> &mut Uniforms {
+        &mut self.uniforms
+    }
+
+    /// Returns the state width in cells.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the state height in cells.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of steps run since creation or the last
+    /// [`upload_state`](Self::upload_state) / [`clear`](Self::clear).
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Returns the target holding the current state, e.g. for sampling in
+    /// a display pass.
+    pub fn state(&self) -> &RenderTarget {
+        &self.targets[self.ping_pong.src_index()]
+    }
+
+    /// Zeroes the current state and resets the step counter.
+    #[allow(unsafe_code)]
+    pub fn clear(&mut self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        self.state().bind(gl);
+        // SAFETY: the current state's framebuffer is bound above.
+        unsafe {
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        self.steps = 0;
+    }
+
+    /// Replaces the current state with row-major RGBA texels
+    /// (`width * height * 4` values) and resets the step counter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `texels` has the wrong length.
+    #[allow(unsafe_code)]
+    pub fn upload_state(&mut self, gl: &glow::Context, texels: &[f32]) -> Result<(), String> {
+        use glow::HasContext;
+
+        let expected = self.width as usize * self.height as usize * 4;
+        if texels.len() != expected {
+            return Err(format!(
+                "state upload needs {expected} RGBA values, got {}",
+                texels.len()
+            ));
+        }
+
+        let bytes = texels_to_bytes(texels);
+        // SAFETY: the texture is valid and `bytes` covers the full
+        // width x height RGBA float rectangle checked above.
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.state().texture()));
+            gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                glow::RGBA,
+                glow::FLOAT,
+                glow::PixelUnpackData::Slice(Some(&bytes)),
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        self.steps = 0;
+        Ok(())
+    }
+
+    /// Reads the current state back as row-major RGBA texels.
+    ///
+    /// Stalls the pipeline until pending steps finish; use it for export,
+    /// not every frame.
+    pub fn read_state(&self, gl: &glow::Context) -> Vec<f32> {
+        self.state().read_texels(gl)
+    }
+
+    /// Runs one step: draws the fragment shader into the destination state
+    /// with the source state bound to [`STATE_UNIFORM`], then swaps.
+    #[allow(unsafe_code)]
+    pub fn step(&mut self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        let src = self.state().texture();
+        self.targets[self.ping_pong.dst_index()].bind(gl);
+
+        // SAFETY: program, vao, and src are valid handles owned by this
+        // pass; the destination framebuffer is bound above and never
+        // samples itself.
+        unsafe {
+            gl.use_program(Some(self.program));
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(src));
+        }
+
+        self.upload(gl, STATE_UNIFORM, Uniform::Int(0));
+        self.upload(
+            gl,
+            RESOLUTION_UNIFORM,
+            Uniform::Vec2([self.width as f32, self.height as f32]),
+        );
+        self.upload(gl, STEP_UNIFORM, Uniform::Int(self.steps as i32));
+        let uniforms = std::mem::take(&mut self.uniforms);
+        for (name, value) in uniforms.iter() {
+            self.upload(gl, name, value);
+        }
+        self.uniforms = uniforms;
+
+        // SAFETY: as above; everything bound here is unbound afterwards.
+        unsafe {
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.bind_vertex_array(None);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.use_program(None);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        self.ping_pong.swap();
+        self.steps += 1;
+    }
+
+    /// Runs `steps` steps.
+    pub fn run(&mut self, gl: &glow::Context, steps: u32) {
+        for _ in 0..steps {
+            self.step(gl);
+        }
+    }
+
+    /// Uploads one uniform to the bound program, caching its location.
+    /// Names the program does not use are skipped.
+    #[allow(unsafe_code)]
+    fn upload(&mut self, gl: &glow::Context, name: &str, value: Uniform) {
+        use glow::HasContext;
+
+        let program = self.program;
+        let location = self
+            .locations
+            .entry(name.to_string())
+            // SAFETY: program is a valid, linked program handle.
+            .or_insert_with(|| unsafe { gl.get_uniform_location(program, name) });
+        let Some(location) = location.as_ref() else {
+            return;
+        };
+
+        // The program owning `location` is in use (see step()).
+        value.upload(gl, location);
+    }
+
+    /// Deletes the program, vertex array, and both state targets.
+    ///
+    /// Like [`RenderTarget::destroy`], call this before dropping the pass
+    /// for deterministic cleanup.
+    #[allow(unsafe_code)]
+    pub fn destroy(&self, gl: &glow::Context) {
+        use glow::HasContext;
+
+        // SAFETY: program and vao are valid handles from new().
+        unsafe {
+            gl.delete_program(self.program);
+            gl.delete_vertex_array(self.vao);
+        }
+        self.targets.iter().for_each(|target| target.destroy(gl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniforms_replace_values_by_name() {
+        let mut uniforms = Uniforms::new();
+        assert!(uniforms.is_empty());
+        uniforms.set("u_feed", 0.055f32);
+        uniforms.set("u_feed", 0.062f32);
+        uniforms.set("u_mode", 2);
+        assert_eq!(uniforms.len(), 2);
+        assert_eq!(uniforms.get("u_feed"), Some(Uniform::Float(0.062)));
+        assert_eq!(uniforms.get("u_mode"), Some(Uniform::Int(2)));
+        assert_eq!(uniforms.remove("u_mode"), Some(Uniform::Int(2)));
+        assert_eq!(uniforms.get("u_mode"), None);
+    }
+
+    #[test]
+    fn uniforms_iterate_in_name_order() {
+        let mut uniforms = Uniforms::new();
+        uniforms.set("u_kill", 0.062f32);
+        uniforms.set("u_diffusion", [1.0f32, 0.5]);
+        uniforms.set("u_brush", [0.5f32, 0.5, 0.1, 1.0]);
+        let names: Vec<&str> = uniforms.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["u_brush", "u_diffusion", "u_kill"]);
+    }
+
+    #[test]
+    fn uniform_conversions_pick_the_glsl_type() {
+        assert_eq!(Uniform::from(1.5f32), Uniform::Float(1.5));
+        assert_eq!(Uniform::from([1.0f32, 2.0]), Uniform::Vec2([1.0, 2.0]));
+        assert_eq!(
+            Uniform::from([1.0f32, 2.0, 3.0]),
+            Uniform::Vec3([1.0, 2.0, 3.0])
+        );
+        assert_eq!(
+            Uniform::from([1.0f32, 2.0, 3.0, 4.0]),
+            Uniform::Vec4([1.0, 2.0, 3.0, 4.0])
+        );
+        assert_eq!(Uniform::from(-3), Uniform::Int(-3));
+    }
+
+    #[test]
+    fn texel_bytes_round_trip() {
+        let texels = [0.0f32, 0.25, -1.5, 1e6, f32::MIN_POSITIVE];
+        let bytes = texels_to_bytes(&texels);
+        assert_eq!(bytes.len(), texels.len() * 4);
+        assert_eq!(bytes_to_texels(&bytes), texels);
+    }
+
+    #[test]
+    fn simulation_pass_struct_compiles_with_expected_api() {
+        // Compile-time check that the public API exists.
+        // This test passes if the module compiles.
+        fn _assert_api(pass: &mut SimulationPass, gl: &glow::Context) {
+            pass.set_uniform("u_feed", 0.055f32);
+            pass.run(gl, 4);
+            let _target: &RenderTarget = pass.state();
+            let _texels: Vec<f32> = pass.read_state(gl);
+            let _steps: u64 = pass.steps();
+        }
+    }
+
+    #[test]
+    #[ignore = "requires GL context"]
+    fn step_swaps_state_and_counts() {
+        // Would test: after step(), state() is the other target and steps() is 1.
+    }
+
+    #[test]
+    #[ignore = "requires GL context"]
+    fn upload_then_read_round_trips_state() {
+        // Would test: read_state() returns the uploaded texels (to half precision).
+    }
+
+    #[test]
+    #[ignore = "requires GL context"]
+    fn unused_uniforms_are_skipped() {
+        // Would test: setting a uniform the shader does not declare is harmless.
+    }
+}
@@ -1,13 +1,53 @@
 //! Render target (FBO + texture) for off-screen rendering.
 //!
 //! A `RenderTarget` pairs a framebuffer object with an RGBA16F color
-//! attachment. Used for layer FBOs, composite FBOs, post-processing
-//! ping-pong pairs, and the feedback texture.
+//! attachment (or RGBA8, see [`TargetFormat`]). Used for layer FBOs,
+//! composite FBOs, post-processing ping-pong pairs, and the feedback
+//! texture.
 
+use crate::field::Field;
+
+use super::context::GpuContext;
+use super::simulation::bytes_to_texels;
 use super::texture::{create_texture, TextureConfig};
 
+/// Color format of a render target.
+///
+/// RGBA16F keeps HDR range and is the default. RGBA8 is the fallback for
+/// GPUs without `EXT_color_buffer_float` (see
+/// [`GpuContext::target_format`]): values clamp to [0, 1], and passes
+/// writing RGBA8 targets add [`dither`](Self::dither) noise to hide
+/// banding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetFormat {
+    /// Half-float color, the standard pipeline format.
+    #[default]
+    Rgba16F,
+    /// 8-bit normalized color.
+    Rgba8,
+}
+
+impl TargetFormat {
+    /// Returns the texture config for a target of this format.
+    pub fn texture_config(self, width: u32, height: u32) -> TextureConfig {
+        match self {
+            TargetFormat::Rgba16F => TextureConfig::rgba16f(width, height),
+            TargetFormat::Rgba8 => TextureConfig::rgba8(width, height),
+        }
+    }
+
+    /// Amplitude of the dither noise passes add before writing a target of
+    /// this format: none for RGBA16F, one 8-bit step for RGBA8.
+    pub fn dither(self) -> f32 {
+        match self {
+            TargetFormat::Rgba16F => 0.0,
+            TargetFormat::Rgba8 => 1.0 / 255.0,
+        }
+    }
+}
+
 /// An off-screen render target consisting of a framebuffer object and
-/// its attached RGBA16F color texture.
+/// its attached color texture.
 ///
 /// All rendering in the pipeline goes through `RenderTarget`s rather
 /// than the default framebuffer, enabling multi-pass effects and
@@ -17,6 +57,7 @@ pub struct RenderTarget {
     texture: glow::Texture,
     width: u32,
     height: u32,
+    format: TargetFormat,
 }
 
 impl RenderTarget {
@@ -29,11 +70,26 @@ impl RenderTarget {
     ///
     /// Returns an error if the framebuffer or texture cannot be created,
     /// or if the framebuffer is not complete.
-    #[allow(unsafe_code)]
     pub fn new(gl: &glow::Context, width: u32, height: u32) -> Result<Self, String> {
+        Self::with_format(gl, width, height, TargetFormat::Rgba16F)
+    }
+
+    /// Creates a new render target with a texture of the given format.
+    ///
+    /// # Errors
+    ///
+    /// As for [`RenderTarget::new`]; RGBA16F targets are incomplete without
+    /// `EXT_color_buffer_float`.
+    #[allow(unsafe_code)]
+    pub fn with_format(
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        format: TargetFormat,
+    ) -> Result<Self, String> {
         use glow::HasContext;
 
-        let config = TextureConfig::rgba16f(width, height);
+        let config = format.texture_config(width, height);
         let texture = create_texture(gl, &config)?;
 
         // SAFETY: glow wraps raw GL calls as unsafe. We create, configure,
@@ -65,6 +121,7 @@ impl RenderTarget {
             texture,
             width,
             height,
+            format,
         })
     }
 
@@ -86,6 +143,11 @@ impl RenderTarget {
         self.texture
     }
 
+    /// Returns the framebuffer handle, for blits into this target.
+    pub(crate) fn framebuffer(&self) -> glow::Framebuffer {
+        self.fbo
+    }
+
     /// Returns the width of this render target in pixels.
     pub fn width(&self) -> u32 {
         self.width
@@ -96,10 +158,73 @@ impl RenderTarget {
         self.height
     }
 
+    /// Returns the color format of this render target.
+    pub fn format(&self) -> TargetFormat {
+        self.format
+    }
+
+    /// Reads the whole target back as RGBA `f32` values, row-major with
+    /// texture row 0 (the first row drawn at `v_uv.y = 0`) first.
+    ///
+    /// Half-float texels are widened by GL and RGBA8 texels normalized to
+    /// [0, 1]; rows are tightly packed. This stalls the pipeline until
+    /// pending draws finish, so use it for export and tests, not every
+    /// frame.
+    #[allow(unsafe_code)]
+    pub fn read_texels(&self, gl: &glow::Context) -> Vec<f32> {
+        use glow::HasContext;
+
+        // Float buffers must be read as FLOAT and RGBA8 as UNSIGNED_BYTE
+        let (ty, texel_size) = match self.format {
+            TargetFormat::Rgba16F => (glow::FLOAT, 16),
+            TargetFormat::Rgba8 => (glow::UNSIGNED_BYTE, 4),
+        };
+        let mut bytes = vec![0u8; self.width as usize * self.height as usize * texel_size];
+        self.bind(gl);
+        // SAFETY: this target's framebuffer is bound and `bytes` holds
+        // exactly width x height RGBA texels of `ty`; 4- and 16-byte texels
+        // keep every row aligned for PACK_ALIGNMENT 4.
+        unsafe {
+            gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+            gl.read_pixels(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                glow::RGBA,
+                ty,
+                glow::PixelPackData::Slice(Some(&mut bytes)),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        match self.format {
+            TargetFormat::Rgba16F => bytes_to_texels(&bytes),
+            TargetFormat::Rgba8 => bytes.iter().map(|&b| f32::from(b) / 255.0).collect(),
+        }
+    }
+
+    /// Reads the red channel back into a [`Field`] of the target's size,
+    /// so GPU-simulated state can go through the CPU pixel and snapshot
+    /// paths.
+    ///
+    /// Field row `y` is texture row `y`, matching how fields are uploaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target has a zero dimension.
+    pub fn read_to_field(&self, gpu: &GpuContext) -> Result<Field, String> {
+        let data = self
+            .read_texels(gpu.gl())
+            .chunks_exact(4)
+            .map(|texel| f64::from(texel[0]))
+            .collect();
+        Field::from_data(self.width as usize, self.height as usize, data).map_err(|e| e.to_string())
+    }
+
     /// Recreates the texture at a new size, keeping the same framebuffer.
     ///
-    /// Deletes the old texture, creates a new RGBA16F texture at the given
-    /// dimensions, and re-attaches it to the framebuffer.
+    /// Deletes the old texture, creates a new texture of the same format at
+    /// the given dimensions, and re-attaches it to the framebuffer.
     ///
     /// # Errors
     ///
@@ -109,7 +234,7 @@ impl RenderTarget {
     pub fn resize(&mut self, gl: &glow::Context, width: u32, height: u32) -> Result<(), String> {
         use glow::HasContext;
 
-        let config = TextureConfig::rgba16f(width, height);
+        let config = self.format.texture_config(width, height);
         let new_texture = create_texture(gl, &config)?;
 
         // SAFETY: self.fbo is a valid framebuffer from new(). We swap
@@ -172,6 +297,31 @@ impl RenderTarget {
     }
 }
 
+/// A framebuffer that draws can be aimed at, such as a [`RenderTarget`] or
+/// a multisampled [`AntiAliasedTarget`](super::AntiAliasedTarget).
+pub trait DrawTarget {
+    /// Binds the framebuffer and sets the viewport to cover it.
+    fn bind(&self, gl: &glow::Context);
+    /// Returns the width in pixels.
+    fn width(&self) -> u32;
+    /// Returns the height in pixels.
+    fn height(&self) -> u32;
+}
+
+impl DrawTarget for RenderTarget {
+    fn bind(&self, gl: &glow::Context) {
+        RenderTarget::bind(self, gl);
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,9 +339,23 @@ mod tests {
             let _tex = rt.texture;
             let _w = rt.width;
             let _h = rt.height;
+            let _format = rt.format;
         }
     }
 
+    #[test]
+    fn formats_pick_texture_configs_and_dither() {
+        let config = TargetFormat::Rgba8.texture_config(4, 2);
+        assert_eq!(config, TextureConfig::rgba8(4, 2));
+        assert_eq!(config.internal_format, glow::RGBA8);
+        assert_eq!(
+            TargetFormat::default().texture_config(4, 2),
+            TextureConfig::rgba16f(4, 2)
+        );
+        assert_eq!(TargetFormat::Rgba16F.dither(), 0.0);
+        assert_eq!(TargetFormat::Rgba8.dither(), 1.0 / 255.0);
+    }
+
     #[test]
     #[ignore = "requires GL context"]
     fn new_creates_valid_render_target() {
@@ -1,8 +1,19 @@
 //! Texture creation helpers for WebGL2 / OpenGL.
 //!
 //! Provides `TextureConfig` for specifying texture parameters and
-//! `create_texture` for allocating GPU textures. All intermediate
-//! framebuffer textures use RGBA16F for HDR range.
+//! `create_texture` for allocating GPU textures. Intermediate framebuffer
+//! textures use RGBA16F for HDR range, or RGBA8 where float color buffers
+//! are unavailable.
+//!
+//! [`upload_field`] puts a CPU [`Field`] on the GPU as a single-channel
+//! float texture (field row `y` is texture row `y`, as for
+//! [`RenderTarget::read_to_field`](super::RenderTarget::read_to_field)),
+//! and [`update_field`] refreshes it in place each frame, so CPU engines can
+//! be colored and post-processed by the GPU passes.
+
+use crate::field::Field;
+
+use super::context::GpuContext;
 
 /// Configuration for creating a GPU texture.
 ///
@@ -35,6 +46,19 @@ impl TextureConfig {
             filter: glow::LINEAR,
         }
     }
+
+    /// Creates a config for an RGBA8 texture with LINEAR filtering.
+    ///
+    /// The fallback format for intermediate FBOs on devices without
+    /// `EXT_color_buffer_float`: values clamp to [0, 1] in 1/255 steps.
+    pub fn rgba8(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            internal_format: glow::RGBA8,
+            filter: glow::LINEAR,
+        }
+    }
 }
 
 /// Returns the GL pixel type that corresponds to a given internal format.
@@ -43,12 +67,48 @@ impl TextureConfig {
 /// assuming `HALF_FLOAT`, so that `RGBA8` textures use `UNSIGNED_BYTE`.
 pub fn pixel_type_for_format(internal_format: u32) -> u32 {
     match internal_format {
-        glow::RGBA16F | glow::RGB16F => glow::HALF_FLOAT,
-        glow::RGBA32F | glow::RGB32F => glow::FLOAT,
+        glow::RGBA16F | glow::RGB16F | glow::R16F => glow::HALF_FLOAT,
+        glow::RGBA32F | glow::RGB32F | glow::R32F => glow::FLOAT,
         _ => glow::UNSIGNED_BYTE,
     }
 }
 
+/// Returns the GL pixel format (channel layout) for a given internal
+/// format: `RED` for single-channel formats, `RGBA` otherwise.
+pub fn pixel_format_for_format(internal_format: u32) -> u32 {
+    match internal_format {
+        glow::R8 | glow::R16F | glow::R32F => glow::RED,
+        _ => glow::RGBA,
+    }
+}
+
+/// Storage for a [`Field`] uploaded as a texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldFormat {
+    /// 32-bit floats, exact for fields, `NEAREST` filtering (linear
+    /// filtering of 32-bit floats needs an extension).
+    #[default]
+    R32F,
+    /// Half floats, about three decimal digits, with `LINEAR` filtering.
+    R16F,
+}
+
+impl FieldFormat {
+    /// Returns the texture configuration for a `width x height` field.
+    pub fn texture_config(self, width: u32, height: u32) -> TextureConfig {
+        let (internal_format, filter) = match self {
+            FieldFormat::R32F => (glow::R32F, glow::NEAREST),
+            FieldFormat::R16F => (glow::R16F, glow::LINEAR),
+        };
+        TextureConfig {
+            width,
+            height,
+            internal_format,
+            filter,
+        }
+    }
+}
+
 /// Creates a GPU texture from the given configuration.
 ///
 /// Sets wrap mode to `CLAMP_TO_EDGE` on both axes, applies the specified
@@ -98,7 +158,7 @@ pub fn create_texture(gl: &glow::Context, config: &TextureConfig) -> Result<glow
             config.width as i32,
             config.height as i32,
             0,
-            glow::RGBA,
+            pixel_format_for_format(config.internal_format),
             pixel_type,
             glow::PixelUnpackData::Slice(None),
         );
@@ -109,6 +169,66 @@ pub fn create_texture(gl: &glow::Context, config: &TextureConfig) -> Result<glow
     Ok(texture)
 }
 
+/// Uploads `field` as an `R32F` texture; see [`upload_field_as`].
+///
+/// # Errors
+///
+/// Returns an error if the texture cannot be created.
+pub fn upload_field(gpu: &GpuContext, field: &Field) -> Result<glow::Texture, String> {
+    upload_field_as(gpu, field, FieldFormat::R32F)
+}
+
+/// Uploads `field` as a single-channel texture of the given format. Shaders
+/// read the value from the red channel.
+///
+/// # Errors
+///
+/// Returns an error if the texture cannot be created.
+pub fn upload_field_as(
+    gpu: &GpuContext,
+    field: &Field,
+    format: FieldFormat,
+) -> Result<glow::Texture, String> {
+    let config = format.texture_config(field.width() as u32, field.height() as u32);
+    let texture = create_texture(gpu.gl(), &config)?;
+    update_field(gpu, texture, field);
+    Ok(texture)
+}
+
+/// Overwrites a texture from [`upload_field`] or [`upload_field_as`] with
+/// `field`, which must have the size it was created at.
+///
+/// Values are sent as 32-bit floats; GL converts them for `R16F` storage.
+#[allow(unsafe_code)]
+pub fn update_field(gpu: &GpuContext, texture: glow::Texture, field: &Field) {
+    use glow::HasContext;
+
+    let gl = gpu.gl();
+    let bytes: Vec<u8> = field
+        .data()
+        .iter()
+        .flat_map(|&v| (v as f32).to_ne_bytes())
+        .collect();
+    // SAFETY: `bytes` holds width x height 4-byte floats, so every row
+    // meets the default unpack alignment; GL reports a size mismatch as an
+    // error without reading past the slice.
+    unsafe {
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            0,
+            0,
+            field.width() as i32,
+            field.height() as i32,
+            glow::RED,
+            glow::FLOAT,
+            glow::PixelUnpackData::Slice(Some(&bytes)),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,6 +274,7 @@ mod tests {
     fn texture_config_is_copy_and_clone() {
         let config = TextureConfig::rgba16f(128, 128);
         let copy = config;
+        #[allow(clippy::clone_on_copy)]
         let clone = config.clone();
         assert_eq!(config, copy);
         assert_eq!(config, clone);
@@ -174,6 +295,29 @@ mod tests {
         assert_eq!(pixel_type_for_format(glow::RGBA8), glow::UNSIGNED_BYTE);
     }
 
+    #[test]
+    fn single_channel_formats_upload_as_red() {
+        assert_eq!(pixel_format_for_format(glow::R32F), glow::RED);
+        assert_eq!(pixel_format_for_format(glow::RGBA16F), glow::RGBA);
+        assert_eq!(pixel_type_for_format(glow::R16F), glow::HALF_FLOAT);
+        assert_eq!(pixel_type_for_format(glow::R32F), glow::FLOAT);
+    }
+
+    #[test]
+    fn field_formats_pick_filters() {
+        let exact = FieldFormat::default().texture_config(8, 4);
+        assert_eq!(
+            (exact.internal_format, exact.filter),
+            (glow::R32F, glow::NEAREST)
+        );
+        let half = FieldFormat::R16F.texture_config(8, 4);
+        assert_eq!(
+            (half.internal_format, half.filter),
+            (glow::R16F, glow::LINEAR)
+        );
+        assert_eq!((half.width, half.height), (8, 4));
+    }
+
     #[test]
     fn texture_config_debug_format_is_readable() {
         let config = TextureConfig::rgba16f(100, 200);
@@ -0,0 +1,357 @@
+//! Versioned interaction recordings that replay deterministically.
+//!
+//! A [`Recording`] captures an engine's starting configuration and a list of
+//! [`Event`]s (parameter changes, palette switches, restarts), each stamped
+//! with the step clock at which it happened. The clock counts every step
+//! advanced since recording began, across restarts, so replaying the events
+//! at the same clock values reproduces the session exactly; the wall-clock
+//! `ms` stamp is kept for pacing playback but never affects the output.
+//!
+//! Engines take their parameters at construction, so a parameter change
+//! rebuilds the engine from step 0 with the new values, exactly as the
+//! interactive front ends do.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "engine": "gray-scott",
+//!   "width": 128, "height": 128, "seed": 42,
+//!   "params": { "feed_rate": 0.055 },
+//!   "palette": "ocean",
+//!   "steps": 600,
+//!   "events": [
+//!     { "step": 200, "ms": 3150, "action": "set_param", "name": "feed_rate", "value": 0.037 },
+//!     { "step": 450, "ms": 7020, "action": "set_palette", "palette": "fire" }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::Engine;
+use crate::error::EngineError;
+
+/// Newest recording schema version this crate reads and writes.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// Something the user did during a session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// Set one engine parameter; the engine restarts with the new params.
+    SetParam { name: String, value: Value },
+    /// Switch the palette (a name or inline hex list); the simulation
+    /// continues undisturbed.
+    SetPalette { palette: String },
+    /// Restart the engine from step 0 with the current params.
+    Restart,
+}
+
+/// An [`Action`] stamped with when it happened.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Event {
+    /// Steps advanced since the recording began; the action applies before
+    /// the next step runs.
+    pub step: usize,
+    /// Milliseconds since the recording began.
+    #[serde(default)]
+    pub ms: u64,
+    /// What happened.
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// A recorded session: the starting configuration and every interaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Recording {
+    /// Schema version; see [`REPLAY_VERSION`].
+    pub version: u32,
+    /// Engine name.
+    pub engine: String,
+    /// Simulation width.
+    pub width: usize,
+    /// Simulation height.
+    pub height: usize,
+    /// PRNG seed, reused every time the engine is rebuilt.
+    pub seed: u64,
+    /// Parameters at the start of the recording.
+    #[serde(default = "empty_params")]
+    pub params: Value,
+    /// Palette at the start of the recording, if one was chosen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    /// Length of the recording on the step clock.
+    #[serde(default)]
+    pub steps: usize,
+    /// Interactions in clock order.
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+fn empty_params() -> Value {
+    Value::Object(Default::default())
+}
+
+impl Recording {
+    /// Starts an empty recording of `engine` with the given configuration.
+    pub fn new(engine: &str, width: usize, height: usize, seed: u64, params: Value) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            engine: engine.to_string(),
+            width,
+            height,
+            seed,
+            params,
+            palette: None,
+            steps: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns a copy with the starting palette set.
+    pub fn with_palette(mut self, palette: impl Into<String>) -> Self {
+        self.palette = Some(palette.into());
+        self
+    }
+
+    /// Appends an event at clock `step`, extending the recording to it.
+    ///
+    /// Returns `EngineError::InvalidReplay` if `step` is earlier than the
+    /// last recorded event.
+    pub fn record(&mut self, step: usize, ms: u64, action: Action) -> Result<(), EngineError> {
+        if let Some(last) = self.events.last().filter(|last| last.step > step) {
+            return Err(EngineError::InvalidReplay(format!(
+                "event at step {step} recorded after one at step {}",
+                last.step
+            )));
+        }
+        self.events.push(Event { step, ms, action });
+        self.steps = self.steps.max(step);
+        Ok(())
+    }
+
+    /// Parses and validates a JSON recording.
+    ///
+    /// Returns `EngineError::InvalidReplay` for malformed JSON or any
+    /// [`Recording::validate`] failure.
+    pub fn from_json(json: &str) -> Result<Self, EngineError> {
+        let recording: Recording =
+            serde_json::from_str(json).map_err(|e| EngineError::InvalidReplay(e.to_string()))?;
+        recording.validate()?;
+        Ok(recording)
+    }
+
+    /// Checks invariants that serde alone cannot enforce.
+    ///
+    /// Returns `EngineError::InvalidReplay` if the version is unsupported,
+    /// a dimension is zero, the params are not an object, or the events are
+    /// out of order or past the end of the recording.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        let invalid = |msg: String| Err(EngineError::InvalidReplay(msg));
+        if self.version == 0 || self.version > REPLAY_VERSION {
+            return invalid(format!(
+                "unsupported version {} (this build reads up to {REPLAY_VERSION})",
+                self.version
+            ));
+        }
+        if self.width == 0 || self.height == 0 {
+            return invalid("width and height must be non-zero".into());
+        }
+        if !self.params.is_object() {
+            return invalid("params must be a JSON object".into());
+        }
+        if let Some(pair) = self
+            .events
+            .windows(2)
+            .find(|pair| pair[0].step > pair[1].step)
+        {
+            return invalid(format!(
+                "event at step {} follows one at step {}",
+                pair[1].step, pair[0].step
+            ));
+        }
+        match self.events.last() {
+            Some(last) if last.step > self.steps => invalid(format!(
+                "event at step {} is past the end of the recording ({} steps)",
+                last.step, self.steps
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replays the session.
+    ///
+    /// `make` builds the engine from a params object, at the start and after
+    /// every parameter change or restart. `on_step` is called at every clock
+    /// value from 0 to [`Recording::steps`] inclusive, after that step's
+    /// events are applied, with the current engine and palette.
+    pub fn replay<E, Err>(
+        &self,
+        mut make: impl FnMut(&Value) -> Result<E, Err>,
+        mut on_step: impl FnMut(usize, &E, Option<&str>) -> Result<(), Err>,
+    ) -> Result<(), Err>
+    where
+        E: Engine,
+        Err: From<EngineError>,
+    {
+        self.validate()?;
+        let mut params = self.params.clone();
+        let mut palette = self.palette.clone();
+        let mut engine = make(&params)?;
+        let mut events = self.events.iter().peekable();
+        for step in 0..=self.steps {
+            let mut rebuild = false;
+            while let Some(event) = events.next_if(|event| event.step == step) {
+                match &event.action {
+                    Action::SetParam { name, value } => {
+                        if let Some(map) = params.as_object_mut() {
+                            map.insert(name.clone(), value.clone());
+                        }
+                        rebuild = true;
+                    }
+                    Action::SetPalette { palette: name } => palette = Some(name.clone()),
+                    Action::Restart => rebuild = true,
+                }
+            }
+            if rebuild {
+                engine = make(&params)?;
+            }
+            on_step(step, &engine, palette.as_deref())?;
+            if step < self.steps {
+                engine.step()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Field;
+    use serde_json::json;
+
+    /// Exposes `offset + steps / 8` as its single cell value.
+    struct Counter {
+        offset: f64,
+        field: Field,
+    }
+
+    impl Engine for Counter {
+        fn step(&mut self) -> Result<(), EngineError> {
+            self.field.data_mut().iter_mut().for_each(|v| *v += 0.125);
+            Ok(())
+        }
+        fn field(&self) -> &Field {
+            &self.field
+        }
+        fn params(&self) -> Value {
+            json!({ "offset": self.offset })
+        }
+        fn param_schema(&self) -> Value {
+            json!({})
+        }
+    }
+
+    fn counter(params: &Value) -> Result<Counter, EngineError> {
+        let offset = params["offset"].as_f64().unwrap_or(0.0);
+        Ok(Counter {
+            offset,
+            field: Field::filled(1, 1, offset)?,
+        })
+    }
+
+    fn sample() -> Recording {
+        let mut recording =
+            Recording::new("counter", 1, 1, 7, json!({ "offset": 0.0 })).with_palette("ocean");
+        recording
+            .record(
+                2,
+                100,
+                Action::SetParam {
+                    name: "offset".into(),
+                    value: json!(0.5),
+                },
+            )
+            .unwrap();
+        recording
+            .record(
+                3,
+                150,
+                Action::SetPalette {
+                    palette: "fire".into(),
+                },
+            )
+            .unwrap();
+        recording.record(4, 200, Action::Restart).unwrap();
+        recording.steps = 5;
+        recording
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let recording = sample();
+        let json = serde_json::to_string(&recording).unwrap();
+        assert!(json.contains(r#""action":"set_param","name":"offset""#));
+        assert_eq!(Recording::from_json(&json).unwrap(), recording);
+    }
+
+    #[test]
+    fn replay_applies_events_at_their_steps() {
+        let mut seen = Vec::new();
+        sample()
+            .replay(counter, |step, engine, palette| {
+                seen.push((step, engine.field().data()[0], palette.unwrap().to_string()));
+                Ok::<_, EngineError>(())
+            })
+            .unwrap();
+        let values: Vec<_> = seen.iter().map(|(step, v, _)| (*step, *v)).collect();
+        assert_eq!(
+            values,
+            [
+                (0, 0.0),
+                (1, 0.125),
+                (2, 0.5),
+                (3, 0.625),
+                (4, 0.5),
+                (5, 0.625)
+            ]
+        );
+        assert_eq!(seen[2].2, "ocean");
+        assert_eq!(seen[3].2, "fire");
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let run = || {
+            let mut values = Vec::new();
+            sample()
+                .replay(counter, |_, engine, _| {
+                    values.push(engine.field().data()[0]);
+                    Ok::<_, EngineError>(())
+                })
+                .unwrap();
+            values
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn validation_rejects_bad_recordings() {
+        let mut recording = sample();
+        assert!(recording.record(1, 0, Action::Restart).is_err());
+
+        let invalid = |change: fn(&mut Recording)| {
+            let mut recording = sample();
+            change(&mut recording);
+            recording.validate().unwrap_err().to_string()
+        };
+        assert!(invalid(|r| r.version = 2).contains("unsupported version 2"));
+        assert!(invalid(|r| r.width = 0).contains("non-zero"));
+        assert!(invalid(|r| r.params = json!([])).contains("object"));
+        assert!(invalid(|r| r.steps = 3).contains("past the end"));
+        assert!(invalid(|r| r.events.swap(0, 1)).contains("follows"));
+        assert!(Recording::from_json("{}").is_err());
+    }
+}
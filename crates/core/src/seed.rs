@@ -1,16 +1,95 @@
 //! Reproducible specification for a generative art piece.
 //!
 //! A [`Seed`] captures everything needed to recreate an artwork:
-//! engine name, canvas dimensions, parameters, PRNG seed, and step count.
+//! engine name, canvas dimensions, parameters, PRNG seed, and step count,
+//! plus optional palette and producer version.
+//!
+//! The PRNG seed may come from a memorable phrase such as
+//! `"crimson-tide-42"`: [`SeedValue`] parses either form, hashing phrases
+//! with [`phrase_to_seed`], and a `Seed` keeps the phrase next to the
+//! number so it can be shared.
+
+use std::fmt;
+use std::str::FromStr;
 
+use crate::color::ColorSpace;
 use crate::error::EngineError;
 use serde::{Deserialize, Serialize};
 
+/// Hashes a seed phrase to a PRNG seed.
+///
+/// The algorithm is 64-bit FNV-1a over the phrase's UTF-8 bytes, exactly
+/// as written: case and whitespace matter. It is part of the replay
+/// format and must never change, or shared phrases would stop
+/// reproducing their artworks.
+pub fn phrase_to_seed(phrase: &str) -> u64 {
+    crate::prng::fnv1a(phrase.as_bytes())
+}
+
+/// A PRNG seed given as a number or as a phrase hashed to one.
+///
+/// Parses from a string: a decimal `u64` is taken as is, anything else is
+/// a phrase hashed with [`phrase_to_seed`]. Displays as the phrase and its
+/// number, `crimson-tide-42 (3627603032977509322)`, or just the number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedValue {
+    /// The PRNG seed.
+    pub value: u64,
+    /// The phrase the seed was hashed from, if any.
+    pub phrase: Option<String>,
+}
+
+impl SeedValue {
+    /// A seed given as a number.
+    pub fn number(value: u64) -> Self {
+        Self {
+            value,
+            phrase: None,
+        }
+    }
+
+    /// A seed hashed from `phrase`.
+    pub fn phrase(phrase: &str) -> Self {
+        Self {
+            value: phrase_to_seed(phrase),
+            phrase: Some(phrase.to_string()),
+        }
+    }
+}
+
+impl FromStr for SeedValue {
+    type Err = EngineError;
+
+    /// Returns `EngineError::InvalidSeed` for an empty or blank string.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.parse() {
+            Ok(value) => Ok(Self::number(value)),
+            Err(_) if text.trim().is_empty() => Err(EngineError::InvalidSeed(
+                "expected a number or a non-empty phrase".into(),
+            )),
+            Err(_) => Ok(Self::phrase(text)),
+        }
+    }
+}
+
+impl fmt::Display for SeedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.phrase {
+            Some(phrase) => write!(f, "{phrase} ({})", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
 /// Reproducible specification for a generative art piece.
 ///
 /// Contains the engine name, canvas dimensions, parameter overrides,
 /// PRNG seed, and simulation step count. Two identical `Seed` values
 /// fed to the same engine binary produce bit-identical output.
+///
+/// `phrase`, `palette`, `color_space`, `oversample`, and `version` are
+/// optional and omitted from JSON when unset (or sRGB, or 1), so older
+/// seed files still deserialize.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Seed {
     pub engine: String,
@@ -18,7 +97,34 @@ pub struct Seed {
     pub height: usize,
     pub params: serde_json::Value,
     pub seed: u64,
+    /// Phrase `seed` was hashed from (see [`phrase_to_seed`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phrase: Option<String>,
     pub steps: usize,
+    /// Palette name used to color the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    /// Color space the output pixels are encoded in.
+    #[serde(default, skip_serializing_if = "ColorSpace::is_srgb")]
+    pub color_space: ColorSpace,
+    /// Factor the engine simulates at: `width * oversample` by
+    /// `height * oversample` cells, area-downsampled to the output size.
+    #[serde(
+        default = "default_oversample",
+        skip_serializing_if = "is_default_oversample"
+    )]
+    pub oversample: u32,
+    /// Version of the tool that produced the artwork.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+fn default_oversample() -> u32 {
+    1
+}
+
+fn is_default_oversample(oversample: &u32) -> bool {
+    *oversample == 1
 }
 
 impl Seed {
@@ -30,20 +136,65 @@ impl Seed {
             height,
             params: serde_json::Value::Object(serde_json::Map::new()),
             seed,
+            phrase: None,
             steps: 0,
+            palette: None,
+            color_space: ColorSpace::Srgb,
+            oversample: 1,
+            version: None,
         }
     }
 
-    /// Validates that the seed has non-zero dimensions and that
-    /// `width * height` does not overflow.
+    /// Returns this seed with its PRNG seed (and phrase) replaced.
+    pub fn with_seed(mut self, seed: SeedValue) -> Self {
+        self.seed = seed.value;
+        self.phrase = seed.phrase;
+        self
+    }
+
+    /// Derives the seed of an independent sub-stream, such as
+    /// `"particles"` or `"palette"`, from this seed (see
+    /// [`derive_seed`](crate::prng::derive_seed)).
+    pub fn derive(&self, label: &str) -> u64 {
+        crate::prng::derive_seed(self.seed, label)
+    }
+
+    /// The PRNG seed together with its phrase.
+    pub fn seed_value(&self) -> SeedValue {
+        SeedValue {
+            value: self.seed,
+            phrase: self.phrase.clone(),
+        }
+    }
+
+    /// Validates that the seed has non-zero dimensions and oversample
+    /// factor, that the simulated cell count does not overflow, and that a
+    /// phrase hashes to `seed`.
     pub fn validate(&self) -> Result<(), EngineError> {
         if self.width == 0 || self.height == 0 {
             return Err(EngineError::InvalidDimensions);
         }
+        if self.oversample == 0 {
+            return Err(EngineError::InvalidSeed(
+                "oversample must be at least 1".into(),
+            ));
+        }
+        let factor = self.oversample as usize;
         self.width
-            .checked_mul(self.height)
+            .checked_mul(factor)
+            .zip(self.height.checked_mul(factor))
+            .and_then(|(w, h)| w.checked_mul(h))
             .ok_or(EngineError::InvalidDimensions)?;
-        Ok(())
+        match &self.phrase {
+            Some(phrase) if phrase_to_seed(phrase) != self.seed => {
+                Err(EngineError::InvalidSeed(format!(
+                    "phrase '{phrase}' hashes to {}, not {}",
+                    phrase_to_seed(phrase),
+                    self.seed
+                )))
+            }
+            _ => Ok(()),
+        }
     }
 }
 
@@ -97,6 +248,58 @@ mod tests {
         assert!(v.get("steps").is_some());
     }
 
+    #[test]
+    fn optional_fields_are_omitted_when_unset() {
+        let s = Seed::new("dla", 128, 128, 1);
+        let v: serde_json::Value = serde_json::to_value(&s).unwrap();
+        assert!(v.get("palette").is_none());
+        assert!(v.get("phrase").is_none());
+        assert!(v.get("color_space").is_none());
+        assert!(v.get("oversample").is_none());
+        assert!(v.get("version").is_none());
+    }
+
+    #[test]
+    fn json_without_optional_fields_deserializes() {
+        let json = r#"{"engine":"dla","width":8,"height":8,"params":{},"seed":1,"steps":0}"#;
+        let s: Seed = serde_json::from_str(json).unwrap();
+        assert_eq!(s.palette, None);
+        assert_eq!(s.oversample, 1);
+        assert_eq!(s.version, None);
+    }
+
+    #[test]
+    fn oversample_round_trips_and_is_validated() {
+        let s = Seed {
+            oversample: 3,
+            ..Seed::new("dla", 8, 8, 1)
+        };
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(json.contains(r#""oversample":3"#), "{json}");
+        assert_eq!(serde_json::from_str::<Seed>(&json).unwrap(), s);
+
+        let zero = Seed { oversample: 0, ..s };
+        assert!(matches!(zero.validate(), Err(EngineError::InvalidSeed(_))));
+        let huge = Seed {
+            oversample: u32::MAX,
+            ..Seed::new("dla", usize::MAX / 2, 2, 1)
+        };
+        assert!(matches!(
+            huge.validate(),
+            Err(EngineError::InvalidDimensions)
+        ));
+    }
+
+    #[test]
+    fn json_round_trip_with_palette_and_version() {
+        let mut s = Seed::new("gray-scott", 64, 64, 7);
+        s.palette = Some("fire".into());
+        s.version = Some("0.1.0".into());
+        let json = serde_json::to_string(&s).unwrap();
+        let restored: Seed = serde_json::from_str(&json).unwrap();
+        assert_eq!(s, restored);
+    }
+
     #[test]
     fn clone_produces_equal_value() {
         let s = Seed::new("rose", 800, 600, 777);
@@ -122,6 +325,84 @@ mod tests {
         assert!(s.validate().is_err());
     }
 
+    // -- Seed phrases --
+
+    #[test]
+    fn phrases_hash_with_fnv1a() {
+        // Reference FNV-1a 64 values; the hash is part of the replay format
+        assert_eq!(phrase_to_seed(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(phrase_to_seed("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(phrase_to_seed("foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(phrase_to_seed("crimson-tide-42"), 3_627_603_032_977_509_322);
+        assert_ne!(
+            phrase_to_seed("crimson-tide-42"),
+            phrase_to_seed("Crimson-tide-42")
+        );
+    }
+
+    #[test]
+    fn seed_values_parse_numbers_and_phrases() {
+        assert_eq!("42".parse::<SeedValue>().unwrap(), SeedValue::number(42));
+        let phrase: SeedValue = "crimson-tide-42".parse().unwrap();
+        assert_eq!(phrase.value, phrase_to_seed("crimson-tide-42"));
+        assert_eq!(phrase.phrase.as_deref(), Some("crimson-tide-42"));
+        // Too large or signed numbers are phrases, not errors
+        assert!("-1".parse::<SeedValue>().unwrap().phrase.is_some());
+        assert!("".parse::<SeedValue>().is_err());
+        assert!("  ".parse::<SeedValue>().is_err());
+    }
+
+    #[test]
+    fn seed_values_display_the_phrase_and_number() {
+        assert_eq!(SeedValue::number(7).to_string(), "7");
+        let phrase = SeedValue::phrase("a");
+        assert_eq!(phrase.to_string(), format!("a ({})", phrase.value));
+    }
+
+    #[test]
+    fn seeds_carry_their_phrase_through_json() {
+        let s = Seed::new("dla", 8, 8, 0).with_seed(SeedValue::phrase("moss-field"));
+        assert_eq!(s.seed, phrase_to_seed("moss-field"));
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(json.contains(r#""phrase":"moss-field""#), "{json}");
+        let restored: Seed = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.seed_value(), SeedValue::phrase("moss-field"));
+        assert!(restored.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_fails_for_a_phrase_that_does_not_match() {
+        let mut s = Seed::new("dla", 8, 8, 0).with_seed(SeedValue::phrase("moss-field"));
+        s.seed += 1;
+        assert!(matches!(s.validate(), Err(EngineError::InvalidSeed(_))));
+    }
+
+    #[test]
+    fn color_space_round_trips_when_wide() {
+        let s = Seed {
+            color_space: ColorSpace::DisplayP3,
+            ..Seed::new("dla", 8, 8, 0)
+        };
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(json.contains(r#""color_space":"display-p3""#), "{json}");
+        let restored: Seed = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.color_space, ColorSpace::DisplayP3);
+    }
+
+    #[test]
+    fn derive_splits_sub_streams_from_the_root_seed() {
+        let s = Seed::new("dla", 8, 8, 42);
+        assert_eq!(
+            s.derive("particles"),
+            crate::prng::derive_seed(42, "particles")
+        );
+        assert_ne!(s.derive("particles"), s.derive("palette"));
+        assert_ne!(
+            s.derive("particles"),
+            Seed::new("dla", 8, 8, 43).derive("particles")
+        );
+    }
+
     #[test]
     fn validate_fails_for_overflow() {
         let s = Seed::new("gray-scott", usize::MAX, 2, 42);
@@ -0,0 +1,154 @@
+//! Compact binary encoding of engine state for checkpoints.
+//!
+//! Engines implement [`Engine::save_state`](crate::Engine::save_state) by
+//! writing their evolving state (fields, counters, PRNG state) with a
+//! [`StateWriter`], and read it back in the same order with a
+//! [`StateReader`]. Values are little-endian and fields are stored as their
+//! dimensions followed by raw `f64` data, so a restored engine continues
+//! bit-identically. Parameters are not part of the state; a checkpoint is
+//! resumed on an engine built with the same parameters and size.
+
+use crate::error::EngineError;
+use crate::field::Field;
+
+/// Appends state values to a byte buffer.
+#[derive(Debug, Default)]
+pub struct StateWriter {
+    buf: Vec<u8>,
+}
+
+impl StateWriter {
+    /// Creates an empty writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a `u64`.
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Appends an `f64` bit-exactly.
+    pub fn f64(&mut self, value: f64) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Appends a field's dimensions and data.
+    pub fn field(&mut self, field: &Field) -> &mut Self {
+        self.u64(field.width() as u64).u64(field.height() as u64);
+        self.buf.reserve(field.data().len() * 8);
+        field.data().iter().for_each(|&v| {
+            self.f64(v);
+        });
+        self
+    }
+
+    /// Returns the encoded bytes.
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads state values written by a [`StateWriter`], in the same order.
+///
+/// Every read returns `EngineError::InvalidState` if the data runs out or
+/// does not fit the engine.
+#[derive(Debug)]
+pub struct StateReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    /// Starts reading `data` from the beginning.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], EngineError> {
+        let (head, rest) = self
+            .data
+            .split_first_chunk::<N>()
+            .ok_or_else(|| EngineError::InvalidState("truncated state".into()))?;
+        self.data = rest;
+        Ok(*head)
+    }
+
+    /// Reads a `u64`.
+    pub fn u64(&mut self) -> Result<u64, EngineError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    /// Reads an `f64`.
+    pub fn f64(&mut self) -> Result<f64, EngineError> {
+        self.take().map(f64::from_le_bytes)
+    }
+
+    /// Reads a field, which must be `width x height`.
+    pub fn field(&mut self, width: usize, height: usize) -> Result<Field, EngineError> {
+        let (w, h) = (self.u64()?, self.u64()?);
+        if (w, h) != (width as u64, height as u64) {
+            return Err(EngineError::InvalidState(format!(
+                "field is {w}x{h}, expected {width}x{height}"
+            )));
+        }
+        if self.data.len() / 8 < width * height {
+            return Err(EngineError::InvalidState("truncated state".into()));
+        }
+        let data = (0..width * height)
+            .map(|_| self.f64())
+            .collect::<Result<Vec<_>, _>>()?;
+        Field::from_data(width, height, data)
+    }
+
+    /// Checks that every byte was consumed.
+    pub fn finish(self) -> Result<(), EngineError> {
+        match self.data.len() {
+            0 => Ok(()),
+            n => Err(EngineError::InvalidState(format!(
+                "{n} unexpected trailing bytes"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip_bit_exactly() {
+        let field = Field::from_data(2, 1, vec![0.1, 1.0 / 3.0]).unwrap();
+        let mut writer = StateWriter::new();
+        writer.u64(7).f64(-0.0).field(&field);
+        let bytes = writer.finish();
+        assert_eq!(bytes.len(), 8 + 8 + 16 + 16);
+
+        let mut reader = StateReader::new(&bytes);
+        assert_eq!(reader.u64().unwrap(), 7);
+        assert_eq!(reader.f64().unwrap().to_bits(), (-0.0f64).to_bits());
+        assert_eq!(reader.field(2, 1).unwrap(), field);
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn mismatched_or_truncated_state_is_rejected() {
+        let field = Field::new(2, 2).unwrap();
+        let mut writer = StateWriter::new();
+        writer.field(&field);
+        let bytes = writer.finish();
+        assert!(matches!(
+            StateReader::new(&bytes).field(4, 1),
+            Err(EngineError::InvalidState(_))
+        ));
+        assert!(StateReader::new(&bytes[..bytes.len() - 1])
+            .field(2, 2)
+            .is_err());
+        assert!(StateReader::new(&[1, 2, 3]).u64().is_err());
+
+        let mut reader = StateReader::new(&bytes);
+        reader.u64().unwrap();
+        assert!(reader.finish().is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! 2D layer transforms: translate, rotate and scale around a pivot.
+//!
+//! A [`Transform`] maps a layer's pixels onto the canvas before compositing.
+//! Translation and pivot are normalized to the image size (so `0.5` is half
+//! the width or height), rotation is in degrees clockwise on screen (the y
+//! axis points down), and negative scale mirrors. The transform is applied by
+//! inverse-mapping each output pixel and sampling the source bilinearly; the
+//! [`WrapMode`] decides what is sampled outside the source, which is what
+//! turns a single layer into a tiling or a mirrored kaleidoscope.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::Reframe;
+use crate::image::Image;
+use crate::math;
+
+/// How samples outside the source image are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    /// Outside pixels are fully transparent.
+    #[default]
+    Transparent,
+    /// Outside pixels repeat the nearest edge pixel.
+    Clamp,
+    /// The image tiles periodically.
+    Repeat,
+    /// The image tiles, flipping every other tile.
+    Mirror,
+}
+
+impl WrapMode {
+    /// Resolves integer coordinate `i` against a length `n`, or `None` when
+    /// the sample is transparent.
+    fn resolve(self, i: isize, n: usize) -> Option<usize> {
+        let n = n as isize;
+        match self {
+            WrapMode::Transparent => (0..n).contains(&i).then_some(i as usize),
+            WrapMode::Clamp => Some(i.clamp(0, n - 1) as usize),
+            WrapMode::Repeat => Some(i.rem_euclid(n) as usize),
+            WrapMode::Mirror => {
+                let m = i.rem_euclid(2 * n);
+                Some(if m < n { m } else { 2 * n - 1 - m } as usize)
+            }
+        }
+    }
+}
+
+/// Translate / rotate / scale around a pivot, with a wrap mode for sampling.
+///
+/// The forward mapping is `p' = pivot + translate + R(rotation) * S(scale) * (p - pivot)`.
+/// Every field has a serde default, so `{"rotation": 45}` is a valid transform.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transform {
+    /// Offset as a fraction of image width and height.
+    pub translate: [f64; 2],
+    /// Clockwise rotation in degrees.
+    pub rotation: f64,
+    /// Per-axis scale; negative values mirror.
+    pub scale: [f64; 2],
+    /// Rotation and scale origin as a fraction of image width and height.
+    pub pivot: [f64; 2],
+    /// Sampling behavior outside the source image.
+    pub wrap: WrapMode,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// The identity transform centered on the image.
+    pub const IDENTITY: Transform = Transform {
+        translate: [0.0, 0.0],
+        rotation: 0.0,
+        scale: [1.0, 1.0],
+        pivot: [0.5, 0.5],
+        wrap: WrapMode::Transparent,
+    };
+
+    /// Returns a copy with the given translation.
+    pub fn with_translate(mut self, x: f64, y: f64) -> Self {
+        self.translate = [x, y];
+        self
+    }
+
+    /// Returns a copy with the given rotation in degrees.
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Returns a copy with the given per-axis scale.
+    pub fn with_scale(mut self, x: f64, y: f64) -> Self {
+        self.scale = [x, y];
+        self
+    }
+
+    /// Returns a copy with the given pivot.
+    pub fn with_pivot(mut self, x: f64, y: f64) -> Self {
+        self.pivot = [x, y];
+        self
+    }
+
+    /// Returns a copy with the given wrap mode.
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Returns `true` if applying the transform leaves every pixel unchanged.
+    ///
+    /// The pivot and wrap mode are irrelevant when nothing moves.
+    pub fn is_identity(&self) -> bool {
+        self.translate == [0.0, 0.0] && self.rotation == 0.0 && self.scale == [1.0, 1.0]
+    }
+
+    /// Re-expresses the transform in a resized canvas frame.
+    pub(crate) fn reframe(&self, frame: &Reframe) -> Transform {
+        Transform {
+            translate: frame.vector(self.translate),
+            pivot: frame.point(self.pivot),
+            ..*self
+        }
+    }
+
+    /// Resamples `source` through the transform into an image of the same size.
+    ///
+    /// A zero scale collapses the layer, producing a fully transparent image;
+    /// so does any non-finite component.
+    pub fn apply(&self, source: &Image) -> Image {
+        let (w, h) = (source.width(), source.height());
+        if self.is_identity() {
+            return source.clone();
+        }
+        let mut out = Image::blank_like(source);
+        let det = self.scale[0] * self.scale[1];
+        let finite = [self.rotation, self.translate[0], self.translate[1], det]
+            .iter()
+            .chain(&self.pivot)
+            .all(|v| v.is_finite());
+        if det == 0.0 || !finite {
+            return out;
+        }
+        let (wf, hf) = (w as f64, h as f64);
+        let (px, py) = (self.pivot[0] * wf, self.pivot[1] * hf);
+        let (tx, ty) = (self.translate[0] * wf, self.translate[1] * hf);
+        let (sin, cos) = math::sin_cos(self.rotation.to_radians());
+        out.data_mut()
+            .chunks_exact_mut(4)
+            .enumerate()
+            .for_each(|(i, dst)| {
+                // Undo translate, rotation, then scale, relative to the pivot
+                let dx = (i % w) as f64 + 0.5 - px - tx;
+                let dy = (i / w) as f64 + 0.5 - py - ty;
+                let rx = cos * dx + sin * dy;
+                let ry = -sin * dx + cos * dy;
+                let sx = px + rx / self.scale[0];
+                let sy = py + ry / self.scale[1];
+                dst.copy_from_slice(&sample_bilinear(source, sx, sy, self.wrap));
+            });
+        out
+    }
+}
+
+/// Samples `image` at continuous pixel coordinates (pixel centers sit at
+/// `i + 0.5`) with bilinear filtering.
+///
+/// Interpolation is done on premultiplied color so transparent neighbors do
+/// not darken edges; the result is straight alpha.
+pub fn sample_bilinear(image: &Image, x: f64, y: f64, wrap: WrapMode) -> [f64; 4] {
+    let (fx, fy) = (x - 0.5, y - 0.5);
+    let (x0, y0) = (fx.floor(), fy.floor());
+    let (ax, ay) = (fx - x0, fy - y0);
+    let (x0, y0) = (x0 as isize, y0 as isize);
+    let texel = |xi: isize, yi: isize| -> [f64; 4] {
+        match (
+            wrap.resolve(xi, image.width()),
+            wrap.resolve(yi, image.height()),
+        ) {
+            (Some(xi), Some(yi)) => {
+                let [r, g, b, a] = image.pixel(xi, yi).unwrap_or([0.0; 4]);
+                [r * a, g * a, b * a, a]
+            }
+            _ => [0.0; 4],
+        }
+    };
+    let taps = [
+        (texel(x0, y0), (1.0 - ax) * (1.0 - ay)),
+        (texel(x0 + 1, y0), ax * (1.0 - ay)),
+        (texel(x0, y0 + 1), (1.0 - ax) * ay),
+        (texel(x0 + 1, y0 + 1), ax * ay),
+    ];
+    let mut acc = [0.0; 4];
+    for (px, weight) in taps {
+        acc.iter_mut().zip(px).for_each(|(a, v)| *a += v * weight);
+    }
+    let alpha = acc[3];
+    if alpha <= 0.0 {
+        return [0.0; 4];
+    }
+    [
+        (acc[0] / alpha).clamp(0.0, 1.0),
+        (acc[1] / alpha).clamp(0.0, 1.0),
+        (acc[2] / alpha).clamp(0.0, 1.0),
+        alpha.clamp(0.0, 1.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 4x1 image whose red channel ramps 0, 1/3, 2/3, 1.
+    fn ramp() -> Image {
+        let mut img = Image::new(4, 1).unwrap();
+        (0..4).for_each(|x| {
+            img.set_pixel(x, 0, [x as f64 / 3.0, 0.0, 0.0, 1.0])
+                .unwrap()
+        });
+        img
+    }
+
+    fn red(img: &Image) -> Vec<f64> {
+        img.data().chunks_exact(4).map(|px| px[0]).collect()
+    }
+
+    fn approx(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    // -- Wrap modes --
+
+    #[test]
+    fn wrap_modes_resolve_out_of_range_indices() {
+        assert_eq!(WrapMode::Transparent.resolve(-1, 4), None);
+        assert_eq!(WrapMode::Transparent.resolve(3, 4), Some(3));
+        assert_eq!(WrapMode::Clamp.resolve(-3, 4), Some(0));
+        assert_eq!(WrapMode::Clamp.resolve(9, 4), Some(3));
+        assert_eq!(WrapMode::Repeat.resolve(-1, 4), Some(3));
+        assert_eq!(WrapMode::Repeat.resolve(5, 4), Some(1));
+        assert_eq!(WrapMode::Mirror.resolve(4, 4), Some(3));
+        assert_eq!(WrapMode::Mirror.resolve(-1, 4), Some(0));
+        assert_eq!(WrapMode::Mirror.resolve(8, 4), Some(0));
+    }
+
+    // -- Bilinear sampling --
+
+    #[test]
+    fn sampling_at_pixel_centers_is_exact() {
+        let img = ramp();
+        for x in 0..4 {
+            let s = sample_bilinear(&img, x as f64 + 0.5, 0.5, WrapMode::Clamp);
+            assert!((s[0] - x as f64 / 3.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn sampling_between_centers_interpolates() {
+        let s = sample_bilinear(&ramp(), 1.0, 0.5, WrapMode::Clamp);
+        assert!((s[0] - 1.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn transparent_neighbors_fade_alpha_not_color() {
+        let img =
+            Image::filled(1, 1, crate::color::Srgb::from_hex("#ffffff").unwrap(), 1.0).unwrap();
+        // Halfway off the right edge
+        let s = sample_bilinear(&img, 1.0, 0.5, WrapMode::Transparent);
+        assert!(approx(&s, &[1.0, 1.0, 1.0, 0.5]));
+    }
+
+    // -- Transforms --
+
+    #[test]
+    fn identity_returns_source() {
+        let img = ramp();
+        assert!(Transform::default().is_identity());
+        assert_eq!(Transform::IDENTITY.apply(&img), img);
+    }
+
+    #[test]
+    fn whole_pixel_translate_shifts_and_wraps() {
+        let t = Transform::IDENTITY
+            .with_translate(0.25, 0.0)
+            .with_wrap(WrapMode::Repeat);
+        let out = t.apply(&ramp());
+        assert!(approx(&red(&out), &[1.0, 0.0, 1.0 / 3.0, 2.0 / 3.0]));
+    }
+
+    #[test]
+    fn translate_with_transparent_wrap_leaves_gap() {
+        let out = Transform::IDENTITY.with_translate(0.25, 0.0).apply(&ramp());
+        assert_eq!(out.pixel(0, 0).unwrap()[3], 0.0);
+        assert_eq!(out.pixel(1, 0).unwrap(), [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn negative_scale_mirrors_around_pivot() {
+        let out = Transform::IDENTITY.with_scale(-1.0, 1.0).apply(&ramp());
+        assert!(approx(&red(&out), &[1.0, 2.0 / 3.0, 1.0 / 3.0, 0.0]));
+    }
+
+    #[test]
+    fn half_rotation_equals_point_mirror() {
+        let mut img = Image::new(2, 2).unwrap();
+        img.set_pixel(0, 0, [1.0, 0.0, 0.0, 1.0]).unwrap();
+        img.set_pixel(1, 1, [0.0, 0.0, 1.0, 1.0]).unwrap();
+        let rotated = Transform::IDENTITY.with_rotation(180.0).apply(&img);
+        let mirrored = Transform::IDENTITY.with_scale(-1.0, -1.0).apply(&img);
+        assert!(approx(rotated.data(), mirrored.data()));
+        assert!(approx(&rotated.pixel(1, 1).unwrap(), &[1.0, 0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn quarter_rotation_is_clockwise() {
+        // Top-left pixel of a 2x2 image moves to the top-right
+        let mut img = Image::new(2, 2).unwrap();
+        img.set_pixel(0, 0, [1.0, 1.0, 1.0, 1.0]).unwrap();
+        let out = Transform::IDENTITY.with_rotation(90.0).apply(&img);
+        assert!(approx(&out.pixel(1, 0).unwrap(), &[1.0, 1.0, 1.0, 1.0]));
+        assert!(out.pixel(0, 0).unwrap()[3] < 1e-9);
+    }
+
+    #[test]
+    fn half_scale_with_repeat_tiles() {
+        let t = Transform::IDENTITY
+            .with_scale(0.5, 1.0)
+            .with_pivot(0.0, 0.0)
+            .with_wrap(WrapMode::Repeat);
+        let img = Image::from_rgba8(2, 1, &[0, 0, 0, 255, 255, 0, 0, 255]).unwrap();
+        // Output pixel 0 samples source x = 1.0 (the boundary), pixel 1 samples x = 3.0 (wraps)
+        let out = t.apply(&img);
+        assert!(approx(&red(&out), &[0.5, 0.5]));
+    }
+
+    #[test]
+    fn zero_scale_is_transparent() {
+        let out = Transform::IDENTITY.with_scale(0.0, 1.0).apply(&ramp());
+        assert!(out.data().iter().all(|&v| v == 0.0));
+    }
+
+    // -- Serde --
+
+    #[test]
+    fn serde_fills_missing_fields_with_identity() {
+        let t: Transform = serde_json::from_str(r#"{"rotation": 45, "wrap": "mirror"}"#).unwrap();
+        assert_eq!(t.rotation, 45.0);
+        assert_eq!(t.scale, [1.0, 1.0]);
+        assert_eq!(t.pivot, [0.5, 0.5]);
+        assert_eq!(t.wrap, WrapMode::Mirror);
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(serde_json::from_str::<Transform>(&json).unwrap(), t);
+    }
+
+    // -- Property-based tests --
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn wrap() -> impl Strategy<Value = WrapMode> {
+            prop_oneof![
+                Just(WrapMode::Transparent),
+                Just(WrapMode::Clamp),
+                Just(WrapMode::Repeat),
+                Just(WrapMode::Mirror),
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn sample_stays_in_unit_range(
+                x in -20.0_f64..20.0,
+                y in -20.0_f64..20.0,
+                wrap in wrap(),
+            ) {
+                let img = Image::from_rgba8(2, 2, &[0, 50, 100, 255, 255, 0, 0, 128, 10, 20, 30, 0, 90, 90, 90, 255]).unwrap();
+                for v in sample_bilinear(&img, x, y, wrap) {
+                    prop_assert!((0.0..=1.0).contains(&v));
+                }
+            }
+
+            #[test]
+            fn repeat_translate_by_whole_image_is_identity(k in -3i32..3) {
+                let img = ramp();
+                let t = Transform::IDENTITY
+                    .with_translate(f64::from(k), 0.0)
+                    .with_wrap(WrapMode::Repeat);
+                prop_assert!(approx(t.apply(&img).data(), img.data()));
+            }
+        }
+    }
+}
@@ -0,0 +1,914 @@
+#![deny(unsafe_code)]
+//! Differential line growth engine.
+//!
+//! A closed curve of nodes grows like a coral edge or a lettuce leaf. Each
+//! step every node is pulled toward its two neighbors (attraction), eased
+//! toward their midpoint (alignment) and pushed away from every node
+//! within `repulsion_radius` (repulsion). Any edge longer than
+//! `edge_length` gets a new node at its midpoint, and any edge longer than
+//! half that does with chance `growth`; the crowded nodes push outward, stretching more
+//! edges, so the curve lengthens and folds into lace where it meets itself.
+//!
+//! The field is the curve stroked with antialiased lines, shaded by age:
+//! segments between the newest nodes are brightest and the original ring
+//! fades by `age_fade`. A nonzero `trail` keeps earlier curves, decaying
+//! by that factor per step.
+//!
+//! Moves are computed from the previous positions, so node order does not
+//! matter, and each step's jitter comes from its own stream derived from
+//! the seed and step number.
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::math;
+use art_engine_core::params::{check_fixed, merge_params, param_f64, param_rng, param_usize};
+use art_engine_core::prng::{derive_seed, Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Value};
+
+/// Default node count of the starting ring.
+const DEFAULT_NODES: usize = 32;
+/// Smallest and largest starting ring.
+const NODES_RANGE: (usize, usize) = (3, 1024);
+/// Default starting ring radius, as a fraction of the shorter side.
+const DEFAULT_START_RADIUS: f64 = 0.08;
+/// Default longest edge before it splits, in pixels.
+const DEFAULT_EDGE_LENGTH: f64 = 4.0;
+/// Shortest and longest split length, in pixels.
+const EDGE_LENGTH_RANGE: (f64, f64) = (1.0, 32.0);
+/// Default distance within which nodes push each other apart, in pixels.
+const DEFAULT_REPULSION_RADIUS: f64 = 10.0;
+/// Smallest and largest repulsion radius, in pixels.
+const REPULSION_RADIUS_RANGE: (f64, f64) = (1.0, 64.0);
+/// Default pull toward neighbors.
+const DEFAULT_ATTRACTION: f64 = 0.5;
+/// Default push from nearby nodes.
+const DEFAULT_REPULSION: f64 = 0.5;
+/// Default easing toward the neighbors' midpoint.
+const DEFAULT_ALIGNMENT: f64 = 0.3;
+/// Default chance per edge per step of inserting a node anyway.
+const DEFAULT_GROWTH: f64 = 0.02;
+/// Default random nudge per node per step, in pixels.
+const DEFAULT_JITTER: f64 = 0.1;
+/// Default cap on nodes, after which edges stop splitting.
+const DEFAULT_MAX_NODES: usize = 5000;
+/// Smallest and largest node cap.
+const MAX_NODES_RANGE: (usize, usize) = (16, 100_000);
+/// Default stroke width, in pixels.
+const DEFAULT_STROKE_WIDTH: f64 = 1.0;
+/// Thinnest and thickest stroke, in pixels.
+const STROKE_WIDTH_RANGE: (f64, f64) = (0.25, 8.0);
+/// Default brightness lost by the oldest segments.
+const DEFAULT_AGE_FADE: f64 = 0.7;
+/// Largest trail decay; 1 would never fade.
+const MAX_TRAIL: f64 = 0.999;
+/// Named (name, edge length, repulsion radius, attraction, repulsion,
+/// alignment, trail) growths.
+const PRESETS: &[(&str, f64, f64, f64, f64, f64, f64)] = &[
+    ("lace", 4.0, 10.0, 0.5, 0.5, 0.3, 0.0),
+    ("brain", 2.5, 6.0, 0.6, 0.6, 0.5, 0.0),
+    ("coral", 5.0, 14.0, 0.4, 0.6, 0.2, 0.9),
+    ("ripples", 3.0, 12.0, 0.5, 0.3, 0.8, 0.97),
+];
+
+/// Simulation parameters for the differential growth engine.
+///
+/// Use [`Default`] for a small ring growing into lace, seeded by
+/// Xorshift64.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrowthParams {
+    /// Nodes in the starting ring.
+    pub nodes: usize,
+    /// Starting ring radius, as a fraction of the shorter side.
+    pub start_radius: f64,
+    /// Longest edge before a node is inserted at its midpoint, in pixels.
+    pub edge_length: f64,
+    /// Distance within which nodes push each other apart, in pixels.
+    pub repulsion_radius: f64,
+    /// Pull toward each neighbor farther than half `edge_length`.
+    pub attraction: f64,
+    /// Push from nodes within `repulsion_radius`, strongest when touching.
+    pub repulsion: f64,
+    /// Easing toward the neighbors' midpoint, which smooths the curve.
+    pub alignment: f64,
+    /// Chance per edge per step of inserting a node even when not
+    /// stretched.
+    pub growth: f64,
+    /// Largest random nudge per node per step, in pixels.
+    pub jitter: f64,
+    /// Node cap, after which edges stop splitting.
+    pub max_nodes: usize,
+    /// Stroke width, in pixels.
+    pub stroke_width: f64,
+    /// Brightness lost by the oldest segments, relative to the newest.
+    pub age_fade: f64,
+    /// Per-step decay of earlier curves; 0 draws only the current one.
+    pub trail: f64,
+    /// PRNG algorithm for the ring and jitter.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for GrowthParams {
+    fn default() -> Self {
+        Self {
+            nodes: DEFAULT_NODES,
+            start_radius: DEFAULT_START_RADIUS,
+            edge_length: DEFAULT_EDGE_LENGTH,
+            repulsion_radius: DEFAULT_REPULSION_RADIUS,
+            attraction: DEFAULT_ATTRACTION,
+            repulsion: DEFAULT_REPULSION,
+            alignment: DEFAULT_ALIGNMENT,
+            growth: DEFAULT_GROWTH,
+            jitter: DEFAULT_JITTER,
+            max_nodes: DEFAULT_MAX_NODES,
+            stroke_width: DEFAULT_STROKE_WIDTH,
+            age_fade: DEFAULT_AGE_FADE,
+            trail: 0.0,
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl GrowthParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each into its range.
+    pub fn from_json(params: &Value) -> Self {
+        Self {
+            nodes: param_usize(params, "nodes", DEFAULT_NODES),
+            start_radius: param_f64(params, "start_radius", DEFAULT_START_RADIUS),
+            edge_length: param_f64(params, "edge_length", DEFAULT_EDGE_LENGTH),
+            repulsion_radius: param_f64(params, "repulsion_radius", DEFAULT_REPULSION_RADIUS),
+            attraction: param_f64(params, "attraction", DEFAULT_ATTRACTION),
+            repulsion: param_f64(params, "repulsion", DEFAULT_REPULSION),
+            alignment: param_f64(params, "alignment", DEFAULT_ALIGNMENT),
+            growth: param_f64(params, "growth", DEFAULT_GROWTH),
+            jitter: param_f64(params, "jitter", DEFAULT_JITTER),
+            max_nodes: param_usize(params, "max_nodes", DEFAULT_MAX_NODES),
+            stroke_width: param_f64(params, "stroke_width", DEFAULT_STROKE_WIDTH),
+            age_fade: param_f64(params, "age_fade", DEFAULT_AGE_FADE),
+            trail: param_f64(params, "trail", 0.0),
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped()
+    }
+
+    /// Returns the params with each clamped into its range; NaN falls back
+    /// to the default.
+    fn clamped(self) -> Self {
+        let clamp = |v: f64, (min, max): (f64, f64), default: f64| {
+            if v.is_nan() {
+                default
+            } else {
+                v.clamp(min, max)
+            }
+        };
+        let unit = |v: f64, default: f64| clamp(v, (0.0, 1.0), default);
+        Self {
+            nodes: self.nodes.clamp(NODES_RANGE.0, NODES_RANGE.1),
+            start_radius: clamp(self.start_radius, (0.01, 0.45), DEFAULT_START_RADIUS),
+            edge_length: clamp(self.edge_length, EDGE_LENGTH_RANGE, DEFAULT_EDGE_LENGTH),
+            repulsion_radius: clamp(
+                self.repulsion_radius,
+                REPULSION_RADIUS_RANGE,
+                DEFAULT_REPULSION_RADIUS,
+            ),
+            attraction: unit(self.attraction, DEFAULT_ATTRACTION),
+            repulsion: unit(self.repulsion, DEFAULT_REPULSION),
+            alignment: unit(self.alignment, DEFAULT_ALIGNMENT),
+            growth: unit(self.growth, DEFAULT_GROWTH),
+            jitter: clamp(self.jitter, (0.0, 2.0), DEFAULT_JITTER),
+            max_nodes: self.max_nodes.clamp(MAX_NODES_RANGE.0, MAX_NODES_RANGE.1),
+            stroke_width: clamp(self.stroke_width, STROKE_WIDTH_RANGE, DEFAULT_STROKE_WIDTH),
+            age_fade: unit(self.age_fade, DEFAULT_AGE_FADE),
+            trail: clamp(self.trail, (0.0, MAX_TRAIL), 0.0),
+            ..self
+        }
+    }
+}
+
+/// A point on the curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Node {
+    /// Position in pixels.
+    pub x: f64,
+    /// Position in pixels.
+    pub y: f64,
+    /// The step that inserted the node; 0 for the starting ring.
+    pub born: u64,
+}
+
+/// Differential line growth engine.
+pub struct DifferentialGrowth {
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: GrowthParams,
+    /// The closed curve, in order; the last node joins the first.
+    nodes: Vec<Node>,
+    steps: u64,
+    field: Field,
+}
+
+impl DifferentialGrowth {
+    /// Creates a new engine with a slightly ragged ring of nodes at the
+    /// center. Out-of-range params are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: GrowthParams,
+    ) -> Result<Self, EngineError> {
+        let field = Field::new(width, height)?;
+        let params = params.clamped();
+        let mut rng = params.rng.build(derive_seed(seed, "ring"));
+        let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+        let radius = params.start_radius * width.min(height) as f64;
+        let nodes = (0..params.nodes)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / params.nodes as f64;
+                let r = radius * (1.0 + 0.1 * (rng.next_f64() - 0.5));
+                Node {
+                    x: cx + r * math::cos(angle),
+                    y: cy + r * math::sin(angle),
+                    born: 0,
+                }
+            })
+            .collect();
+        let mut engine = Self {
+            width,
+            height,
+            seed,
+            params,
+            nodes,
+            steps: 0,
+            field,
+        };
+        engine.update_field();
+        Ok(engine)
+    }
+
+    /// Creates an engine from a JSON params object.
+    ///
+    /// Extracts `nodes`, `start_radius`, `edge_length`, `repulsion_radius`,
+    /// `attraction`, `repulsion`, `alignment`, `growth`, `jitter`, `max_nodes`,
+    /// `stroke_width`, `age_fade`, `trail` and `rng`, falling back to
+    /// defaults for missing keys.
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, GrowthParams::from_json(json_params))
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// field, plus `max_nodes` nodes with their moves and lookup grid.
+    pub fn memory_estimate(width: usize, height: usize, params: &GrowthParams) -> u64 {
+        let per_node = std::mem::size_of::<Node>() + 2 * 8 + 8;
+        let max_nodes = params.max_nodes.clamp(MAX_NODES_RANGE.0, MAX_NODES_RANGE.1);
+        grid_bytes(width, height, 8).saturating_add((max_nodes * per_node) as u64)
+    }
+
+    /// The curve's nodes, in order around it.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Where each node moves this step, from the current positions.
+    fn moves(&self) -> Vec<(f64, f64)> {
+        let p = &self.params;
+        let n = self.nodes.len();
+        let grid = Grid::new(self.width, self.height, p.repulsion_radius, &self.nodes);
+        let rest = p.edge_length / 2.0;
+        let limit = p.edge_length / 2.0;
+        (0..n)
+            .map(|i| {
+                let node = self.nodes[i];
+                let prev = self.nodes[(i + n - 1) % n];
+                let next = self.nodes[(i + 1) % n];
+                let (mut fx, mut fy) = (0.0, 0.0);
+                for neighbor in [prev, next] {
+                    let (dx, dy) = (neighbor.x - node.x, neighbor.y - node.y);
+                    let d = math::hypot(dx, dy);
+                    if d > rest {
+                        let pull = p.attraction * (d - rest) / d / 2.0;
+                        fx += pull * dx;
+                        fy += pull * dy;
+                    }
+                }
+                fx += p.alignment * ((prev.x + next.x) / 2.0 - node.x);
+                fy += p.alignment * ((prev.y + next.y) / 2.0 - node.y);
+                for j in grid.near(node.x, node.y) {
+                    let other = self.nodes[j];
+                    let (dx, dy) = (node.x - other.x, node.y - other.y);
+                    let d = math::hypot(dx, dy);
+                    if j != i && d > 0.0 && d < p.repulsion_radius {
+                        let push = p.repulsion * (1.0 - d / p.repulsion_radius) / d;
+                        fx += push * dx;
+                        fy += push * dy;
+                    }
+                }
+                let length = math::hypot(fx, fy);
+                if length > limit {
+                    (fx * limit / length, fy * limit / length)
+                } else {
+                    (fx, fy)
+                }
+            })
+            .collect()
+    }
+
+    /// Inserts a node at the midpoint of every edge longer than
+    /// `edge_length`, and with chance `growth` of those longer than half
+    /// that, while under `max_nodes`. Edges squashed against the canvas
+    /// border therefore stop growing.
+    fn split_edges(&mut self, rng: &mut dyn Rng) {
+        let n = self.nodes.len();
+        let rest = self.params.edge_length / 2.0;
+        let mut grown = Vec::with_capacity(n * 2);
+        let mut count = n;
+        for i in 0..n {
+            let (a, b) = (self.nodes[i], self.nodes[(i + 1) % n]);
+            grown.push(a);
+            let length = math::hypot(b.x - a.x, b.y - a.y);
+            let long = length > self.params.edge_length;
+            let grow = rng.next_f64() < self.params.growth && length > rest;
+            if (long || grow) && count < self.params.max_nodes {
+                grown.push(Node {
+                    x: (a.x + b.x) / 2.0,
+                    y: (a.y + b.y) / 2.0,
+                    born: self.steps,
+                });
+                count += 1;
+            }
+        }
+        self.nodes = grown;
+    }
+
+    /// Brightness of a segment whose nodes were born at `born`: 1 for the
+    /// newest, `1 - age_fade` for the starting ring.
+    fn shade(&self, born: u64) -> f64 {
+        let age = (self.steps - born.min(self.steps)) as f64;
+        1.0 - self.params.age_fade * age / self.steps.max(1) as f64
+    }
+
+    /// Fades earlier curves by `trail` and strokes the current one.
+    fn update_field(&mut self) {
+        let trail = self.params.trail;
+        self.field.data_mut().iter_mut().for_each(|v| *v *= trail);
+        let n = self.nodes.len();
+        let radius = self.params.stroke_width / 2.0;
+        for i in 0..n {
+            let (a, b) = (self.nodes[i], self.nodes[(i + 1) % n]);
+            let shade = (self.shade(a.born) + self.shade(b.born)) / 2.0;
+            stroke(&mut self.field, (a.x, a.y), (b.x, b.y), radius, shade);
+        }
+    }
+}
+
+/// Draws an antialiased capsule from `a` to `b`, keeping the brighter value
+/// where strokes overlap.
+fn stroke(field: &mut Field, a: (f64, f64), b: (f64, f64), radius: f64, intensity: f64) {
+    let (w, h) = (field.width(), field.height());
+    let reach = radius + 0.5;
+    let cells = |lo: f64, hi: f64, size: usize| {
+        let first = (lo - reach).floor().max(0.0) as usize;
+        let last = ((hi + reach).ceil().max(0.0) as usize).min(size);
+        first..last
+    };
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let data = field.data_mut();
+    for y in cells(a.1.min(b.1), a.1.max(b.1), h) {
+        for x in cells(a.0.min(b.0), a.0.max(b.0), w) {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let t = if length_sq > 0.0 {
+                (((px - a.0) * dx + (py - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = math::hypot(px - a.0 - t * dx, py - a.1 - t * dy);
+            let value = (reach - distance).clamp(0.0, 1.0) * intensity;
+            let cell = &mut data[y * w + x];
+            *cell = cell.max(value);
+        }
+    }
+}
+
+/// Buckets nodes by square cells the size of the repulsion radius.
+struct Grid {
+    cell: f64,
+    columns: usize,
+    rows: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize, cell: f64, nodes: &[Node]) -> Self {
+        let columns = (width as f64 / cell).ceil().max(1.0) as usize;
+        let rows = (height as f64 / cell).ceil().max(1.0) as usize;
+        let mut buckets = vec![Vec::new(); columns * rows];
+        let mut grid = Self {
+            cell,
+            columns,
+            rows,
+            buckets: Vec::new(),
+        };
+        for (i, node) in nodes.iter().enumerate() {
+            let (column, row) = grid.locate(node.x, node.y);
+            buckets[row * columns + column].push(i);
+        }
+        grid.buckets = buckets;
+        grid
+    }
+
+    /// Bucket coordinates of (`x`, `y`).
+    fn locate(&self, x: f64, y: f64) -> (usize, usize) {
+        let column = ((x / self.cell).max(0.0) as usize).min(self.columns - 1);
+        let row = ((y / self.cell).max(0.0) as usize).min(self.rows - 1);
+        (column, row)
+    }
+
+    /// Nodes in the 3×3 buckets around (`x`, `y`), a superset of those
+    /// within one cell.
+    fn near(&self, x: f64, y: f64) -> impl Iterator<Item = usize> + '_ {
+        let (column, row) = self.locate(x, y);
+        let columns = column.saturating_sub(1)..(column + 2).min(self.columns);
+        let rows = row.saturating_sub(1)..(row + 2).min(self.rows);
+        rows.flat_map(move |r| columns.clone().map(move |c| r * self.columns + c))
+            .flat_map(|bucket| self.buckets[bucket].iter().copied())
+    }
+}
+
+impl Engine for DifferentialGrowth {
+    /// Moves every node, nudges it by the jitter, keeps it on the canvas,
+    /// then splits edges.
+    fn step(&mut self) -> Result<(), EngineError> {
+        let moves = self.moves();
+        let mut rng = self
+            .params
+            .rng
+            .build(derive_seed(self.seed, &format!("step-{}", self.steps)));
+        let jitter = self.params.jitter;
+        let (w, h) = (self.width as f64, self.height as f64);
+        for (node, (mx, my)) in self.nodes.iter_mut().zip(moves) {
+            let jx = jitter * (2.0 * rng.next_f64() - 1.0);
+            let jy = jitter * (2.0 * rng.next_f64() - 1.0);
+            node.x = (node.x + mx + jx).clamp(0.0, w);
+            node.y = (node.y + my + jy).clamp(0.0, h);
+        }
+        self.steps += 1;
+        self.split_edges(rng.as_mut());
+        self.update_field();
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.field
+    }
+
+    fn params(&self) -> Value {
+        json!({
+            "nodes": self.params.nodes,
+            "start_radius": self.params.start_radius,
+            "edge_length": self.params.edge_length,
+            "repulsion_radius": self.params.repulsion_radius,
+            "attraction": self.params.attraction,
+            "repulsion": self.params.repulsion,
+            "alignment": self.params.alignment,
+            "growth": self.params.growth,
+            "jitter": self.params.jitter,
+            "max_nodes": self.params.max_nodes,
+            "stroke_width": self.params.stroke_width,
+            "age_fade": self.params.age_fade,
+            "trail": self.params.trail,
+            "rng": self.params.rng.name(),
+        })
+    }
+
+    /// Forces, growth and drawing params apply from the next step. The
+    /// starting ring (`nodes`, `start_radius`) and `max_nodes`, which sizes
+    /// the memory estimate, are fixed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["nodes", "start_radius", "max_nodes"])?;
+        self.params = GrowthParams::from_json(&merge_params(&current, params)?);
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "nodes": {
+                "type": "integer",
+                "default": DEFAULT_NODES,
+                "min": NODES_RANGE.0,
+                "max": NODES_RANGE.1,
+                "description": "Nodes in the starting ring"
+            },
+            "start_radius": {
+                "type": "number",
+                "default": DEFAULT_START_RADIUS,
+                "min": 0.01,
+                "max": 0.45,
+                "description": "Starting ring radius, as a fraction of the shorter side"
+            },
+            "edge_length": {
+                "type": "number",
+                "default": DEFAULT_EDGE_LENGTH,
+                "min": EDGE_LENGTH_RANGE.0,
+                "max": EDGE_LENGTH_RANGE.1,
+                "description": "Longest edge before a node is inserted at its midpoint, in pixels"
+            },
+            "repulsion_radius": {
+                "type": "number",
+                "default": DEFAULT_REPULSION_RADIUS,
+                "min": REPULSION_RADIUS_RANGE.0,
+                "max": REPULSION_RADIUS_RANGE.1,
+                "description": "Distance within which nodes push each other apart, in pixels"
+            },
+            "attraction": {
+                "type": "number",
+                "default": DEFAULT_ATTRACTION,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Pull toward each neighbor farther than half the edge length"
+            },
+            "repulsion": {
+                "type": "number",
+                "default": DEFAULT_REPULSION,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Push from nearby nodes, strongest when touching"
+            },
+            "alignment": {
+                "type": "number",
+                "default": DEFAULT_ALIGNMENT,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Easing toward the neighbors' midpoint, which smooths the curve"
+            },
+            "growth": {
+                "type": "number",
+                "default": DEFAULT_GROWTH,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Chance per edge per step of inserting a node even when not stretched"
+            },
+            "jitter": {
+                "type": "number",
+                "default": DEFAULT_JITTER,
+                "min": 0.0,
+                "max": 2.0,
+                "description": "Largest random nudge per node per step, in pixels"
+            },
+            "max_nodes": {
+                "type": "integer",
+                "default": DEFAULT_MAX_NODES,
+                "min": MAX_NODES_RANGE.0,
+                "max": MAX_NODES_RANGE.1,
+                "description": "Node cap, after which edges stop splitting"
+            },
+            "stroke_width": {
+                "type": "number",
+                "default": DEFAULT_STROKE_WIDTH,
+                "min": STROKE_WIDTH_RANGE.0,
+                "max": STROKE_WIDTH_RANGE.1,
+                "description": "Stroke width, in pixels"
+            },
+            "age_fade": {
+                "type": "number",
+                "default": DEFAULT_AGE_FADE,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Brightness lost by the oldest segments, relative to the newest"
+            },
+            "trail": {
+                "type": "number",
+                "default": 0.0,
+                "min": 0.0,
+                "max": MAX_TRAIL,
+                "description": "Per-step decay of earlier curves; 0 draws only the current one"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG for the starting ring and jitter: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(
+                |&(
+                    name,
+                    edge_length,
+                    repulsion_radius,
+                    attraction,
+                    repulsion,
+                    alignment,
+                    trail,
+                )| {
+                    let params = json!({
+                        "edge_length": edge_length,
+                        "repulsion_radius": repulsion_radius,
+                        "attraction": attraction,
+                        "repulsion": repulsion,
+                        "alignment": alignment,
+                        "trail": trail,
+                    });
+                    (name.to_string(), params)
+                },
+            )
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(self.steps).u64(self.nodes.len() as u64);
+        for node in &self.nodes {
+            writer.f64(node.x).f64(node.y).u64(node.born);
+        }
+        writer.field(&self.field);
+        Ok(writer.finish())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let steps = reader.u64()?;
+        let count = reader.u64()?;
+        // Each node takes 24 bytes, so a count beyond the state is corrupt
+        if count < NODES_RANGE.0 as u64 || count > (state.len() / 24) as u64 {
+            return Err(EngineError::InvalidState(format!(
+                "{count} nodes do not make a curve in {} bytes",
+                state.len()
+            )));
+        }
+        let (w, h) = (self.width as f64, self.height as f64);
+        let mut nodes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (x, y, born) = (reader.f64()?, reader.f64()?, reader.u64()?);
+            if !((0.0..=w).contains(&x) && (0.0..=h).contains(&y)) || born > steps {
+                return Err(EngineError::InvalidState(format!(
+                    "node ({x}, {y}) born at step {born} does not fit the {}x{} canvas at step {steps}",
+                    self.width, self.height
+                )));
+            }
+            nodes.push(Node { x, y, born });
+        }
+        let field = reader.field(self.width, self.height)?;
+        reader.finish()?;
+        self.steps = steps;
+        self.nodes = nodes;
+        self.field = field;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: construct with default params.
+    fn growth(width: usize, height: usize, seed: u64) -> DifferentialGrowth {
+        DifferentialGrowth::new(width, height, seed, GrowthParams::default()).unwrap()
+    }
+
+    /// Helper: an engine stepped `steps` times.
+    fn stepped(params: GrowthParams, seed: u64, steps: usize) -> DifferentialGrowth {
+        let mut engine = DifferentialGrowth::new(64, 64, seed, params).unwrap();
+        for _ in 0..steps {
+            engine.step().unwrap();
+        }
+        engine
+    }
+
+    /// Helper: the longest edge of the closed curve.
+    fn longest_edge(nodes: &[Node]) -> f64 {
+        let n = nodes.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (nodes[i], nodes[(i + 1) % n]);
+                (b.x - a.x).hypot(b.y - a.y)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_starts_with_a_ring_at_the_center() {
+        let engine = growth(100, 80, 1);
+        assert_eq!(engine.nodes().len(), DEFAULT_NODES);
+        let radius = DEFAULT_START_RADIUS * 80.0;
+        for node in engine.nodes() {
+            let r = (node.x - 50.0).hypot(node.y - 40.0);
+            assert!((r - radius).abs() <= radius * 0.05 + 1e-9, "{r}");
+            assert_eq!(node.born, 0);
+        }
+        assert!(engine.field().data().iter().any(|&v| v > 0.5));
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = DifferentialGrowth::new(0, 10, 1, GrowthParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        assert_eq!(GrowthParams::from_json(&json!({})), GrowthParams::default());
+        let params = GrowthParams::from_json(&json!({
+            "nodes": 1,
+            "edge_length": 100.0,
+            "attraction": -1.0,
+            "trail": 1.0,
+            "max_nodes": 1,
+            "rng": "splitmix64"
+        }));
+        assert_eq!(params.nodes, NODES_RANGE.0);
+        assert_eq!(params.edge_length, EDGE_LENGTH_RANGE.1);
+        assert_eq!(params.attraction, 0.0);
+        assert_eq!(params.trail, MAX_TRAIL);
+        assert_eq!(params.max_nodes, MAX_NODES_RANGE.0);
+        assert_eq!(params.rng, RngAlgorithm::SplitMix64);
+    }
+
+    #[test]
+    fn params_round_trip_through_json() {
+        let engine =
+            DifferentialGrowth::from_json(8, 8, 1, &json!({"nodes": 12, "trail": 0.5})).unwrap();
+        let params = engine.params();
+        assert_eq!(params["nodes"], json!(12));
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+        assert_eq!(GrowthParams::from_json(&params), engine.params);
+    }
+
+    // ---- Growth tests ----
+
+    #[test]
+    fn the_curve_lengthens() {
+        let engine = stepped(GrowthParams::default(), 3, 60);
+        assert!(engine.nodes().len() > 2 * DEFAULT_NODES);
+        assert!(engine.nodes().iter().any(|node| node.born > 0));
+    }
+
+    #[test]
+    fn edges_split_before_exceeding_the_edge_length() {
+        let mut engine = growth(64, 64, 5);
+        for _ in 0..40 {
+            engine.step().unwrap();
+            assert!(longest_edge(engine.nodes()) <= DEFAULT_EDGE_LENGTH + 1e-9);
+        }
+    }
+
+    #[test]
+    fn max_nodes_caps_the_curve() {
+        let params = GrowthParams {
+            max_nodes: 48,
+            ..GrowthParams::default()
+        };
+        let engine = stepped(params, 3, 80);
+        assert_eq!(engine.nodes().len(), 48);
+    }
+
+    #[test]
+    fn nodes_stay_on_the_canvas() {
+        let params = GrowthParams {
+            start_radius: 0.45,
+            repulsion: 1.0,
+            jitter: 0.5,
+            ..GrowthParams::default()
+        };
+        let engine = stepped(params, 8, 40);
+        for node in engine.nodes() {
+            assert!((0.0..=64.0).contains(&node.x) && (0.0..=64.0).contains(&node.y));
+        }
+    }
+
+    #[test]
+    fn new_segments_are_brighter_than_old_ones() {
+        let engine = stepped(GrowthParams::default(), 3, 20);
+        assert_eq!(engine.shade(engine.steps), 1.0);
+        assert!((engine.shade(0) - (1.0 - DEFAULT_AGE_FADE)).abs() < 1e-12);
+        assert!(engine.shade(10) > engine.shade(5));
+    }
+
+    // ---- Determinism tests ----
+
+    #[test]
+    fn same_seed_same_curve() {
+        let a = stepped(GrowthParams::default(), 9, 20);
+        let b = stepped(GrowthParams::default(), 9, 20);
+        let c = stepped(GrowthParams::default(), 10, 20);
+        assert_eq!(a.nodes(), b.nodes());
+        assert_ne!(a.nodes(), c.nodes());
+    }
+
+    // ---- Field tests ----
+
+    #[test]
+    fn trail_keeps_earlier_curves() {
+        let trailed = GrowthParams {
+            trail: 0.9,
+            ..GrowthParams::default()
+        };
+        let a = stepped(GrowthParams::default(), 4, 15);
+        let b = stepped(trailed, 4, 15);
+        assert_eq!(a.nodes(), b.nodes());
+        let (plain, kept) = (a.field().data(), b.field().data());
+        assert!(plain.iter().zip(kept).all(|(p, k)| k >= p));
+        assert!(plain.iter().zip(kept).any(|(p, k)| k > p));
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_changes_forces_from_the_next_step() {
+        let mut engine = stepped(GrowthParams::default(), 3, 5);
+        let nodes = engine.nodes().to_vec();
+        engine
+            .set_params(&json!({"growth": 1.0, "jitter": 0.0}))
+            .unwrap();
+        assert_eq!(engine.nodes(), nodes);
+        engine.step().unwrap();
+        assert!(engine.nodes().len() > nodes.len());
+
+        for patch in [json!({"nodes": 7}), json!({"max_nodes": 9000})] {
+            let result = engine.set_params(&patch);
+            assert!(
+                matches!(result, Err(EngineError::Unsupported(_))),
+                "{patch}"
+            );
+        }
+    }
+
+    // ---- State tests ----
+
+    #[test]
+    fn state_round_trips() {
+        let mut engine = stepped(GrowthParams::default(), 6, 10);
+        let state = engine.save_state().unwrap();
+        let mut restored = growth(64, 64, 6);
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.nodes(), engine.nodes());
+        assert_eq!(restored.field().data(), engine.field().data());
+        engine.step().unwrap();
+        restored.step().unwrap();
+        assert_eq!(restored.nodes(), engine.nodes());
+    }
+
+    #[test]
+    fn load_state_rejects_broken_curves() {
+        let mut engine = growth(16, 16, 1);
+        let mut writer = StateWriter::new();
+        writer.u64(1).u64(2);
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+
+        let mut writer = StateWriter::new();
+        writer.u64(1).u64(3);
+        for born in [0, 1, 2] {
+            writer.f64(8.0).f64(8.0).u64(born);
+        }
+        writer.field(&Field::new(16, 16).unwrap());
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn fields_stay_in_unit_interval(
+                width in 1usize..32,
+                height in 1usize..32,
+                seed in any::<u64>(),
+                trail in 0.0f64..MAX_TRAIL,
+                stroke_width in 0.25f64..8.0,
+            ) {
+                let params = GrowthParams { trail, stroke_width, ..GrowthParams::default() };
+                let mut engine = DifferentialGrowth::new(width, height, seed, params).unwrap();
+                for _ in 0..5 {
+                    engine.step().unwrap();
+                }
+                let unit = engine.field().data().iter().all(|v| (0.0..=1.0).contains(v));
+                prop_assert!(unit);
+            }
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Multi-engine compositions: realizes engine-bound canvas layers.
+//!
+//! A [`Composition`] instantiates one engine per [`ContentType::Field`] layer
+//! whose content is [`LayerContent::Engine`], steps them together, and renders
+//! the canvas through the core [`compositor`](art_engine_core::compositor).
+//! Layers without an engine binding are skipped by the compositor. A
+//! [`Project`] can be realized directly with [`Composition::from_project`],
+//! which applies its global seed, custom palettes, post stack and feedback.
+
+use art_engine_core::canvas::{Canvas, ContentType, LayerContent};
+use art_engine_core::compositor::composite;
+use art_engine_core::error::EngineError;
+use art_engine_core::feedback::Feedback;
+use art_engine_core::image::Image;
+use art_engine_core::palette::Palette;
+use art_engine_core::post::{apply_stack, PostEffect};
+use art_engine_core::project::Project;
+use art_engine_core::Engine;
+use std::collections::HashMap;
+
+use crate::pixel::engine_to_rgba;
+use crate::EngineKind;
+
+/// A running engine bound to a canvas layer.
+struct LayerEngine {
+    engine: EngineKind,
+    palette: Palette,
+}
+
+/// A canvas whose engine-bound layers have live engine instances.
+pub struct Composition {
+    canvas: Canvas,
+    engines: HashMap<String, LayerEngine>,
+    post: Vec<PostEffect>,
+    feedback: Option<Feedback>,
+    /// Last composite of [`Composition::render_animated`], before post.
+    trail: Option<Image>,
+}
+
+impl Composition {
+    /// Instantiates an engine for every field layer bound to one.
+    ///
+    /// Engines run at the canvas resolution. Returns `EngineError::UnknownEngine`
+    /// or `EngineError::UnknownPalette` for bad bindings, or any engine
+    /// construction error.
+    pub fn new(canvas: Canvas) -> Result<Self, EngineError> {
+        Self::build(canvas, |seed| seed, Palette::from_name)
+    }
+
+    /// Instantiates the engines of a project's canvas.
+    ///
+    /// Binding seeds are offset by the project's global seed and palette names
+    /// resolve against the project's custom palettes first; renders run the
+    /// project's post stack, and animated renders its feedback. Errors are as
+    /// for [`Composition::new`]; the project is not re-validated.
+    pub fn from_project(project: &Project) -> Result<Self, EngineError> {
+        let comp = Self::build(
+            project.canvas.clone(),
+            |seed| project.layer_seed(seed),
+            |name| project.palette(name),
+        )?;
+        Ok(comp
+            .with_post(project.post.clone())
+            .with_feedback(project.feedback))
+    }
+
+    /// Returns the composition with `post` applied to every render.
+    pub fn with_post(mut self, post: Vec<PostEffect>) -> Self {
+        self.post = post;
+        self
+    }
+
+    /// Returns the composition with `feedback` trails in
+    /// [`Composition::render_animated`].
+    pub fn with_feedback(mut self, feedback: Option<Feedback>) -> Self {
+        self.feedback = feedback;
+        self
+    }
+
+    fn build(
+        canvas: Canvas,
+        seed: impl Fn(u64) -> u64,
+        palette: impl Fn(&str) -> Result<Palette, EngineError>,
+    ) -> Result<Self, EngineError> {
+        let engines = canvas
+            .layers()
+            .filter(|layer| layer.content_type() == ContentType::Field)
+            .filter_map(|layer| match layer.content()? {
+                LayerContent::Engine(binding) => Some((layer.name(), binding)),
+            })
+            .map(|(name, binding)| {
+                let engine = EngineKind::from_name(
+                    &binding.engine,
+                    canvas.width(),
+                    canvas.height(),
+                    seed(binding.seed),
+                    &binding.params,
+                )?;
+                let palette = palette(&binding.palette)?;
+                Ok((name.to_string(), LayerEngine { engine, palette }))
+            })
+            .collect::<Result<HashMap<_, _>, EngineError>>()?;
+        Ok(Self {
+            canvas,
+            engines,
+            post: Vec::new(),
+            feedback: None,
+            trail: None,
+        })
+    }
+
+    /// Returns the canvas being composed.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Returns the number of layers backed by a live engine.
+    pub fn engine_count(&self) -> usize {
+        self.engines.len()
+    }
+
+    /// Returns the engine realizing the named layer, if it has one.
+    pub fn engine(&self, layer: &str) -> Option<&EngineKind> {
+        self.engines.get(layer).map(|le| &le.engine)
+    }
+
+    /// Advances every layer engine by one step.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn step(&mut self) -> Result<(), EngineError> {
+        self.engines
+            .values_mut()
+            .try_for_each(|le| le.engine.step())
+    }
+
+    /// Renders each layer engine, composites the canvas, and runs the post
+    /// stack.
+    pub fn render(&self) -> Result<Image, EngineError> {
+        self.render_canvas(&self.canvas)
+    }
+
+    /// Renders like [`Composition::render`] with layer keyframes evaluated
+    /// at `frame` (see [`Canvas::at_frame`]).
+    pub fn render_frame(&self, frame: f64) -> Result<Image, EngineError> {
+        self.render_canvas(&self.canvas.at_frame(frame))
+    }
+
+    /// Renders the next frame of an animation like
+    /// [`Composition::render_frame`], drawing the composite over the
+    /// feedback trail of the previous call (before the post stack, so post
+    /// effects do not accumulate).
+    ///
+    /// Without feedback this is the same as `render_frame`.
+    pub fn render_animated(&mut self, frame: f64) -> Result<Image, EngineError> {
+        let Some(feedback) = self.feedback else {
+            return self.render_frame(frame);
+        };
+        let image = self.composite_canvas(&self.canvas.at_frame(frame))?;
+        let image = match &self.trail {
+            Some(previous) => feedback.apply(previous, &image),
+            None => image,
+        };
+        let out = self.post_process(image.clone());
+        self.trail = Some(image);
+        Ok(out)
+    }
+
+    /// Forgets the feedback trail, so the next animated frame starts fresh.
+    pub fn reset_feedback(&mut self) {
+        self.trail = None;
+    }
+
+    fn render_canvas(&self, canvas: &Canvas) -> Result<Image, EngineError> {
+        Ok(self.post_process(self.composite_canvas(canvas)?))
+    }
+
+    fn post_process(&self, image: Image) -> Image {
+        match self.post.as_slice() {
+            [] => image,
+            post => apply_stack(post, &image),
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(layers = canvas.layer_count()))]
+    fn composite_canvas(&self, canvas: &Canvas) -> Result<Image, EngineError> {
+        let (w, h) = (canvas.width(), canvas.height());
+        let images = self
+            .engines
+            .iter()
+            .map(|(name, le)| {
+                let rgba = engine_to_rgba(&le.engine, &le.palette)?;
+                Ok((name.as_str(), Image::from_rgba8(w, h, &rgba)?))
+            })
+            .collect::<Result<HashMap<_, _>, EngineError>>()?;
+        composite(canvas, |name| images.get(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use art_engine_core::canvas::{BlendMode, EngineBinding, Layer};
+    use art_engine_core::color::Srgb;
+
+    fn black() -> Srgb {
+        Srgb {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+        }
+    }
+
+    fn engine_layer(name: &str, seed: u64, palette: &str) -> Layer {
+        Layer::new(name, ContentType::Field).with_content(LayerContent::Engine(EngineBinding::new(
+            "gray-scott",
+            seed,
+            palette,
+        )))
+    }
+
+    #[test]
+    fn new_instantiates_only_bound_field_layers() {
+        let mut canvas = Canvas::new(16, 16, black()).unwrap();
+        canvas.add_layer(engine_layer("a", 1, "ocean")).unwrap();
+        canvas
+            .add_layer(Layer::new("plain", ContentType::Field))
+            .unwrap();
+        // Engine content on a non-field layer is not realized
+        canvas
+            .add_layer(Layer::new("dots", ContentType::Particles).with_content(
+                LayerContent::Engine(EngineBinding::new("gray-scott", 2, "fire")),
+            ))
+            .unwrap();
+        let comp = Composition::new(canvas).unwrap();
+        assert_eq!(comp.engine_count(), 1);
+        assert!(comp.engine("a").is_some());
+        assert!(comp.engine("plain").is_none());
+    }
+
+    #[test]
+    fn new_rejects_unknown_engine_and_palette() {
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas
+            .add_layer(
+                Layer::new("x", ContentType::Field)
+                    .with_content(LayerContent::Engine(EngineBinding::new("nope", 1, "ocean"))),
+            )
+            .unwrap();
+        assert!(matches!(
+            Composition::new(canvas),
+            Err(EngineError::UnknownEngine(_))
+        ));
+
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas.add_layer(engine_layer("x", 1, "plaid")).unwrap();
+        assert!(matches!(
+            Composition::new(canvas),
+            Err(EngineError::UnknownPalette(_))
+        ));
+    }
+
+    #[test]
+    fn single_opaque_layer_renders_engine_pixels() {
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas.add_layer(engine_layer("a", 3, "neon")).unwrap();
+        let mut comp = Composition::new(canvas).unwrap();
+        (0..5).try_for_each(|_| comp.step()).unwrap();
+
+        let img = comp.render().unwrap();
+        let expected = engine_to_rgba(comp.engine("a").unwrap(), &Palette::neon()).unwrap();
+        assert_eq!(img.to_rgba8(), expected);
+    }
+
+    #[test]
+    fn step_advances_every_engine() {
+        let mut canvas = Canvas::new(16, 16, black()).unwrap();
+        canvas.add_layer(engine_layer("a", 1, "ocean")).unwrap();
+        canvas
+            .add_layer(engine_layer("b", 2, "fire").with_blend_mode(BlendMode::Screen))
+            .unwrap();
+        let mut comp = Composition::new(canvas).unwrap();
+        let before: Vec<Vec<f64>> = ["a", "b"]
+            .iter()
+            .map(|n| comp.engine(n).unwrap().field().data().to_vec())
+            .collect();
+        (0..10).try_for_each(|_| comp.step()).unwrap();
+        for (name, old) in ["a", "b"].iter().zip(before) {
+            assert_ne!(comp.engine(name).unwrap().field().data(), old.as_slice());
+        }
+    }
+
+    #[test]
+    fn from_project_applies_global_seed_and_custom_palettes() {
+        let mut canvas = Canvas::new(16, 16, black()).unwrap();
+        canvas.add_layer(engine_layer("a", 1, "mine")).unwrap();
+        let red = Srgb {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+        };
+        let project = Project::new(canvas)
+            .with_seed(4)
+            .with_palette("mine", vec![red]);
+        let comp = Composition::from_project(&project).unwrap();
+
+        let mut direct = Canvas::new(16, 16, black()).unwrap();
+        direct.add_layer(engine_layer("a", 5, "ocean")).unwrap();
+        let direct = Composition::new(direct).unwrap();
+        assert_eq!(
+            comp.engine("a").unwrap().field().data(),
+            direct.engine("a").unwrap().field().data()
+        );
+        // A single-color palette paints every opaque pixel that color
+        assert_eq!(comp.render().unwrap().to_rgba8()[..4], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn from_project_runs_the_post_stack() {
+        let mut canvas = Canvas::new(16, 16, black()).unwrap();
+        canvas.add_layer(engine_layer("a", 1, "ocean")).unwrap();
+        let plain = Project::new(canvas);
+        let vignette = PostEffect::Vignette {
+            strength: 1.0,
+            radius: 0.0,
+            softness: 0.5,
+        };
+        let styled = plain.clone().with_post(vignette.clone());
+        let image = Composition::from_project(&plain).unwrap().render().unwrap();
+        assert_eq!(
+            Composition::from_project(&styled)
+                .unwrap()
+                .render()
+                .unwrap(),
+            vignette.apply(&image)
+        );
+    }
+
+    #[test]
+    fn render_animated_carries_feedback_trails() {
+        let mut canvas = Canvas::new(16, 16, black()).unwrap();
+        canvas.add_layer(engine_layer("a", 2, "neon")).unwrap();
+        let feedback = Feedback::new(0.8).with_zoom(1.1);
+        let vignette = PostEffect::Vignette {
+            strength: 0.5,
+            radius: 0.2,
+            softness: 0.5,
+        };
+        let project = Project::new(canvas)
+            .with_post(vignette.clone())
+            .with_feedback(feedback);
+        let mut comp = Composition::from_project(&project).unwrap();
+        let mut plain = Composition::from_project(&Project {
+            post: Vec::new(),
+            feedback: None,
+            ..project
+        })
+        .unwrap();
+
+        let first = plain.render().unwrap();
+        assert_eq!(comp.render_animated(0.0).unwrap(), vignette.apply(&first));
+        comp.step().unwrap();
+        plain.step().unwrap();
+        let trail = feedback.apply(&first, &plain.render().unwrap());
+        assert_eq!(comp.render_animated(1.0).unwrap(), vignette.apply(&trail));
+
+        comp.reset_feedback();
+        assert_eq!(comp.render_animated(1.0).unwrap(), comp.render().unwrap());
+        assert_eq!(plain.render_animated(1.0).unwrap(), plain.render().unwrap());
+    }
+
+    #[test]
+    fn render_frame_applies_layer_keyframes() {
+        use art_engine_core::keyframe::{Keyframe, LayerAnimation, Track};
+        let fade = LayerAnimation {
+            opacity: Some(Track::new(vec![
+                Keyframe::new(0.0, 0.0),
+                Keyframe::new(10.0, 1.0),
+            ])),
+            ..LayerAnimation::default()
+        };
+        let mut canvas = Canvas::new(8, 8, black()).unwrap();
+        canvas
+            .add_layer(engine_layer("a", 3, "neon").with_animation(fade))
+            .unwrap();
+        let comp = Composition::new(canvas).unwrap();
+        let background = Composition::new(Canvas::new(8, 8, black()).unwrap()).unwrap();
+        assert_eq!(
+            comp.render_frame(0.0).unwrap(),
+            background.render().unwrap()
+        );
+        assert_eq!(comp.render_frame(10.0).unwrap(), comp.render().unwrap());
+    }
+
+    #[test]
+    fn empty_composition_renders_background() {
+        let bg = Srgb {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+        };
+        let comp = Composition::new(Canvas::new(2, 2, bg).unwrap()).unwrap();
+        assert_eq!(comp.render().unwrap().to_rgba8()[..4], [255, 0, 0, 255]);
+    }
+}
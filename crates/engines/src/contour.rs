@@ -0,0 +1,336 @@
+//! Iso-contour extraction (marching squares) and SVG serialization.
+//!
+//! Like [`crate::pixel`], this module is pure computation and always
+//! available so WASM builds can produce vector output without the `png`
+//! feature. The field is treated as bounded (not toroidal): it is padded with
+//! a below-threshold border so every contour comes back as a closed ring,
+//! which keeps even-odd filling well defined.
+
+use art_engine_core::field::Field;
+use art_engine_core::palette::Palette;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// A closed polygon in pixel coordinates. The last point connects back to the first.
+pub type Ring = Vec<(f64, f64)>;
+
+/// Identifies a cell edge in the padded grid: `(x, y, vertical)`.
+///
+/// A horizontal edge joins grid points `(x, y)` and `(x + 1, y)`; a vertical
+/// edge joins `(x, y)` and `(x, y + 1)`.
+type EdgeKey = (usize, usize, bool);
+
+/// Extracts the iso-contour of `field` at `level` as closed rings.
+///
+/// A cell is inside when its value is `>= level`. Points are placed at pixel
+/// centers (`x + 0.5`, `y + 0.5`), linearly interpolated along cell edges, and
+/// clamped to the `[0, width] x [0, height]` rectangle. Saddle cells are
+/// disambiguated by the average of their four corners.
+pub fn contour_rings(field: &Field, level: f64) -> Vec<Ring> {
+    let (w, h) = (field.width(), field.height());
+    // Padded grid is (w + 2) x (h + 2); padded index i maps to field index i - 1.
+    let pad = level - 1.0;
+    let value = |x: usize, y: usize| -> f64 {
+        if x == 0 || y == 0 || x > w || y > h {
+            pad
+        } else {
+            let v = field.data()[(y - 1) * w + (x - 1)];
+            if v.is_nan() {
+                pad
+            } else {
+                v
+            }
+        }
+    };
+
+    let segments: Vec<(EdgeKey, EdgeKey)> = (0..=h)
+        .flat_map(|y| (0..=w).map(move |x| (x, y)))
+        .flat_map(|(x, y)| cell_segments(x, y, level, &value))
+        .collect();
+
+    chain_segments(&segments)
+        .into_iter()
+        .map(|keys| {
+            keys.into_iter()
+                .map(|key| edge_point(key, level, &value, w, h))
+                .collect()
+        })
+        .collect()
+}
+
+/// Renders filled, stroked iso-bands of `field` as an SVG document.
+///
+/// Thresholds outside [0, 1] or NaN are ignored; the rest are drawn in
+/// ascending order so higher bands paint over lower ones. Each band is filled
+/// and stroked with `palette.sample(level)`, over a background of
+/// `palette.sample(0.0)`.
+pub fn field_to_svg(field: &Field, palette: &Palette, thresholds: &[f64]) -> String {
+    let (w, h) = (field.width(), field.height());
+    let mut levels: Vec<f64> = thresholds
+        .iter()
+        .copied()
+        .filter(|t| (0.0..=1.0).contains(t))
+        .collect();
+    levels.sort_by(f64::total_cmp);
+    levels.dedup();
+
+    let mut svg = String::new();
+    // Writing to a String cannot fail.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <rect width="{w}" height="{h}" fill="{}"/>"#,
+        palette.sample(0.0).to_hex()
+    );
+    for level in levels {
+        let rings = contour_rings(field, level);
+        if rings.is_empty() {
+            continue;
+        }
+        let color = palette.sample(level).to_hex();
+        let _ = writeln!(
+            svg,
+            r#"  <path d="{}" fill="{color}" fill-rule="evenodd" stroke="{color}" stroke-width="0.5"/>"#,
+            path_data(&rings)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Returns `count` evenly spaced thresholds strictly inside (0, 1).
+pub fn even_thresholds(count: usize) -> Vec<f64> {
+    (1..=count).map(|i| i as f64 / (count + 1) as f64).collect()
+}
+
+/// Serializes rings as SVG path data (`M x y L ... Z` per ring).
+fn path_data(rings: &[Ring]) -> String {
+    rings
+        .iter()
+        .filter_map(|ring| {
+            let (first, rest) = ring.split_first()?;
+            let mut d = format!("M{:.2} {:.2}", first.0, first.1);
+            rest.iter().for_each(|(x, y)| {
+                let _ = write!(d, "L{x:.2} {y:.2}");
+            });
+            d.push('Z');
+            Some(d)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Emits the contour segments crossing the padded cell whose top-left grid
+/// point is `(x, y)`.
+fn cell_segments(
+    x: usize,
+    y: usize,
+    level: f64,
+    value: &impl Fn(usize, usize) -> f64,
+) -> Vec<(EdgeKey, EdgeKey)> {
+    let (tl, tr, br, bl) = (
+        value(x, y),
+        value(x + 1, y),
+        value(x + 1, y + 1),
+        value(x, y + 1),
+    );
+    let case = (usize::from(tl >= level) << 3)
+        | (usize::from(tr >= level) << 2)
+        | (usize::from(br >= level) << 1)
+        | usize::from(bl >= level);
+
+    let top = (x, y, false);
+    let bottom = (x, y + 1, false);
+    let left = (x, y, true);
+    let right = (x + 1, y, true);
+    let center_inside = (tl + tr + br + bl) / 4.0 >= level;
+
+    match case {
+        1 | 14 => vec![(left, bottom)],
+        2 | 13 => vec![(bottom, right)],
+        3 | 12 => vec![(left, right)],
+        4 | 11 => vec![(top, right)],
+        6 | 9 => vec![(top, bottom)],
+        7 | 8 => vec![(left, top)],
+        // tr and bl inside: join them through the center or separate them
+        5 if center_inside => vec![(left, top), (bottom, right)],
+        5 => vec![(top, right), (left, bottom)],
+        // tl and br inside
+        10 if center_inside => vec![(top, right), (left, bottom)],
+        10 => vec![(left, top), (bottom, right)],
+        _ => Vec::new(),
+    }
+}
+
+/// Joins segments sharing edge endpoints into chains of edge keys.
+fn chain_segments(segments: &[(EdgeKey, EdgeKey)]) -> Vec<Vec<EdgeKey>> {
+    let mut by_edge: HashMap<EdgeKey, Vec<usize>> = HashMap::new();
+    segments.iter().enumerate().for_each(|(i, &(a, b))| {
+        by_edge.entry(a).or_default().push(i);
+        by_edge.entry(b).or_default().push(i);
+    });
+
+    let mut used = vec![false; segments.len()];
+    let mut chains = Vec::new();
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let (first, mut current) = segments[start];
+        let mut chain = vec![first, current];
+        while let Some(next) = by_edge
+            .get(&current)
+            .and_then(|ids| ids.iter().copied().find(|&i| !used[i]))
+        {
+            used[next] = true;
+            let (a, b) = segments[next];
+            current = if a == current { b } else { a };
+            if current == first {
+                break;
+            }
+            chain.push(current);
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+/// Interpolates the crossing point along an edge, in clamped pixel coordinates.
+fn edge_point(
+    (x, y, vertical): EdgeKey,
+    level: f64,
+    value: &impl Fn(usize, usize) -> f64,
+    w: usize,
+    h: usize,
+) -> (f64, f64) {
+    let (x1, y1) = if vertical { (x, y + 1) } else { (x + 1, y) };
+    let (a, b) = (value(x, y), value(x1, y1));
+    let frac = if (b - a).abs() < f64::EPSILON {
+        0.5
+    } else {
+        ((level - a) / (b - a)).clamp(0.0, 1.0)
+    };
+    // Padded index i sits at pixel center (i - 1) + 0.5.
+    let px = x as f64 - 0.5 + if vertical { 0.0 } else { frac };
+    let py = y as f64 - 0.5 + if vertical { frac } else { 0.0 };
+    (px.clamp(0.0, w as f64), py.clamp(0.0, h as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disc(size: usize, radius: f64) -> Field {
+        let c = size as f64 / 2.0;
+        let data = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f64 + 0.5, (i / size) as f64 + 0.5);
+                let d = ((x - c).powi(2) + (y - c).powi(2)).sqrt();
+                if d < radius {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        Field::from_data(size, size, data).unwrap()
+    }
+
+    // -- Marching squares --
+
+    #[test]
+    fn uniform_field_below_level_has_no_contours() {
+        let field = Field::filled(8, 8, 0.2).unwrap();
+        assert!(contour_rings(&field, 0.5).is_empty());
+    }
+
+    #[test]
+    fn uniform_field_above_level_has_one_border_ring() {
+        let field = Field::filled(8, 8, 0.8).unwrap();
+        let rings = contour_rings(&field, 0.5);
+        assert_eq!(rings.len(), 1);
+        for &(x, y) in &rings[0] {
+            assert!((0.0..=8.0).contains(&x) && (0.0..=8.0).contains(&y));
+        }
+    }
+
+    #[test]
+    fn single_disc_produces_one_closed_ring_around_center() {
+        let field = disc(32, 8.0);
+        let rings = contour_rings(&field, 0.5);
+        assert_eq!(rings.len(), 1);
+        let ring = &rings[0];
+        assert!(ring.len() > 8, "ring too coarse: {} points", ring.len());
+        for &(x, y) in ring {
+            let d = ((x - 16.0).powi(2) + (y - 16.0).powi(2)).sqrt();
+            assert!((d - 8.0).abs() < 1.5, "point ({x}, {y}) at distance {d}");
+        }
+    }
+
+    #[test]
+    fn two_separate_discs_produce_two_rings() {
+        let mut data = vec![0.0; 20 * 10];
+        [(2, 2), (2, 3), (3, 2), (3, 3), (15, 6), (16, 6)]
+            .iter()
+            .for_each(|&(x, y)| data[y * 20 + x] = 1.0);
+        let field = Field::from_data(20, 10, data).unwrap();
+        assert_eq!(contour_rings(&field, 0.5).len(), 2);
+    }
+
+    #[test]
+    fn hole_produces_inner_and_outer_rings() {
+        let mut field = Field::filled(9, 9, 1.0).unwrap();
+        field.set(4, 4, 0.0);
+        assert_eq!(contour_rings(&field, 0.5).len(), 2);
+    }
+
+    #[test]
+    fn nan_values_are_treated_as_outside() {
+        let field = Field::from_data(2, 2, vec![f64::NAN; 4]).unwrap();
+        assert!(contour_rings(&field, 0.5).is_empty());
+    }
+
+    // -- SVG --
+
+    #[test]
+    fn even_thresholds_are_strictly_inside_unit_interval() {
+        let t = even_thresholds(3);
+        assert_eq!(t, vec![0.25, 0.5, 0.75]);
+        assert!(even_thresholds(0).is_empty());
+    }
+
+    #[test]
+    fn svg_has_header_background_and_one_path_per_nonempty_level() {
+        let field = disc(16, 5.0);
+        let palette = Palette::ocean();
+        let svg = field_to_svg(&field, &palette, &[0.5, 2.0, f64::NAN]);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"viewBox="0 0 16 16""#));
+        assert!(svg.contains("<rect "));
+        assert_eq!(svg.matches("<path ").count(), 1);
+        assert!(svg.contains(&palette.sample(0.5).to_hex()));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn svg_skips_levels_without_contours() {
+        let field = Field::filled(4, 4, 0.1).unwrap();
+        let svg = field_to_svg(&field, &Palette::fire(), &[0.5, 0.9]);
+        assert_eq!(svg.matches("<path ").count(), 0);
+    }
+
+    #[test]
+    fn svg_output_is_deterministic() {
+        let field = disc(12, 4.0);
+        let palette = Palette::neon();
+        let thresholds = even_thresholds(4);
+        assert_eq!(
+            field_to_svg(&field, &palette, &thresholds),
+            field_to_svg(&field, &palette, &thresholds)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Reference digests that pin every engine's output bit for bit.
+//!
+//! [`REFERENCES`] holds, per engine, the [`Digest`] of a short fixed run:
+//! a hash of the final field (and hue field, if any) and of its pixels
+//! through the ocean palette. The values are recorded with core's
+//! `portable` feature, under which engine and color math use only
+//! operations every target rounds the same way. Any target built with the
+//! feature (native on any OS or CPU, or WASM) must reproduce them exactly;
+//! [`mismatches`] re-runs the references to check.
+//!
+//! Without the feature, transcendental functions come from the platform's
+//! math library and the digests are not expected to match.
+
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::palette::Palette;
+use art_engine_core::prng::fnv1a;
+use art_engine_core::Engine;
+use serde_json::json;
+
+use crate::pixel::engine_to_rgba;
+use crate::EngineKind;
+
+/// Grid width of a reference run.
+pub const WIDTH: usize = 48;
+/// Grid height of a reference run.
+pub const HEIGHT: usize = 32;
+/// Seed of a reference run.
+pub const SEED: u64 = 7;
+/// Steps in a reference run.
+pub const STEPS: usize = 24;
+
+/// Hashes of an engine's output after a reference run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Digest {
+    /// FNV-1a of the field's (and hue field's) dimensions and value bits.
+    pub field: u64,
+    /// FNV-1a of the RGBA pixels through [`Palette::ocean`].
+    pub pixels: u64,
+}
+
+/// Digests of every engine under the `portable` feature, by engine name.
+pub const REFERENCES: &[(&str, Digest)] = &[
+    (
+        "differential-growth",
+        Digest {
+            field: 0x16af06423aecd2db,
+            pixels: 0x94c6dc876e176309,
+        },
+    ),
+    (
+        "gray-scott",
+        Digest {
+            field: 0x4d87d81626b82908,
+            pixels: 0xdde0fec15780ecf2,
+        },
+    ),
+    (
+        "gray-scott-advected",
+        Digest {
+            field: 0xa05e32f87770af14,
+            pixels: 0xefa428d35b806bcb,
+        },
+    ),
+    (
+        "lsystem",
+        Digest {
+            field: 0x814945864799921d,
+            pixels: 0x290ee3a09a441bc1,
+        },
+    ),
+    (
+        "rps",
+        Digest {
+            field: 0x557e4c94cc82125e,
+            pixels: 0x9b439340f5278e08,
+        },
+    ),
+    (
+        "schelling",
+        Digest {
+            field: 0x653b6645c98db798,
+            pixels: 0x3efe91253a839547,
+        },
+    ),
+    (
+        "stipple",
+        Digest {
+            field: 0xd4a17e4568633b67,
+            pixels: 0x2b90dbcf3d5a1eb9,
+        },
+    ),
+    (
+        "truchet",
+        Digest {
+            field: 0x6df23bf019921729,
+            pixels: 0x035c854f3c29b493,
+        },
+    ),
+    (
+        "voronoi",
+        Digest {
+            field: 0xe470db73a652d2d1,
+            pixels: 0x89b6e8c798406d79,
+        },
+    ),
+];
+
+/// Hashes `field`'s dimensions and the bits of its values, so any
+/// difference, however small, changes the hash.
+pub fn field_hash(field: &Field) -> u64 {
+    fnv1a(&field_bytes(field))
+}
+
+fn field_bytes(field: &Field) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(16 + field.data().len() * 8);
+    bytes.extend_from_slice(&(field.width() as u64).to_le_bytes());
+    bytes.extend_from_slice(&(field.height() as u64).to_le_bytes());
+    for value in field.data() {
+        bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+    }
+    bytes
+}
+
+/// Runs `engine` with default parameters at [`WIDTH`] x [`HEIGHT`] from
+/// [`SEED`] for [`STEPS`] steps and digests the result.
+pub fn digest(engine: &str) -> Result<Digest, EngineError> {
+    let mut instance = EngineKind::from_name(engine, WIDTH, HEIGHT, SEED, &json!({}))?;
+    (0..STEPS).try_for_each(|_| instance.step())?;
+    let mut bytes = field_bytes(instance.field());
+    if let Some(hue) = instance.hue_field() {
+        bytes.extend(field_bytes(hue));
+    }
+    Ok(Digest {
+        field: fnv1a(&bytes),
+        pixels: fnv1a(&engine_to_rgba(&instance, &Palette::ocean())?),
+    })
+}
+
+/// Re-runs every reference, returning `(engine, expected, actual)` for
+/// each whose digest differs.
+pub fn mismatches() -> Result<Vec<(&'static str, Digest, Digest)>, EngineError> {
+    REFERENCES
+        .iter()
+        .map(|&(engine, expected)| Ok((engine, expected, digest(engine)?)))
+        .filter(|result| !matches!(result, Ok((_, expected, actual)) if expected == actual))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_cover_every_engine() {
+        let names: Vec<&str> = REFERENCES.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, EngineKind::list_engines());
+    }
+
+    #[test]
+    fn field_hash_sees_every_bit() {
+        let field = Field::filled(3, 2, 0.5).unwrap();
+        let mut nudged = field.clone();
+        nudged.data_mut()[5] = f64::from_bits(0.5f64.to_bits() + 1);
+        assert_ne!(field_hash(&field), field_hash(&nudged));
+        // Same values, different shape
+        let reshaped = Field::filled(2, 3, 0.5).unwrap();
+        assert_ne!(field_hash(&field), field_hash(&reshaped));
+    }
+
+    #[test]
+    fn digests_repeat_within_a_build() {
+        for engine in EngineKind::list_engines() {
+            assert_eq!(digest(engine).unwrap(), digest(engine).unwrap(), "{engine}");
+        }
+        assert!(digest("nope").is_err());
+    }
+
+    #[test]
+    fn basic_arithmetic_engines_match_in_every_build() {
+        // No transcendental math in their steps, so the field digests hold
+        // with or without the feature
+        for engine in ["gray-scott", "rps", "schelling"] {
+            let (_, expected) = REFERENCES.iter().find(|(name, _)| *name == engine).unwrap();
+            assert_eq!(digest(engine).unwrap().field, expected.field, "{engine}");
+        }
+    }
+
+    #[cfg(feature = "portable")]
+    #[test]
+    fn portable_builds_reproduce_the_references() {
+        let mismatches = mismatches().unwrap();
+        assert!(mismatches.is_empty(), "{mismatches:#x?}");
+    }
+}
@@ -0,0 +1,116 @@
+//! Heightmap and normal-map conversion from a [`Field`].
+//!
+//! Treats field values in [0, 1] as surface height. Both conversions are pure
+//! and always available; PNG encoding lives in [`crate::snapshot`]. Gradients
+//! use the field's toroidal wrapping, so tileable fields produce seamless
+//! normal maps.
+
+use art_engine_core::field::Field;
+
+/// Converts field values to 16-bit grayscale samples (0 = low, 65535 = high).
+///
+/// Values outside [0, 1] are clamped and NaN maps to 0. The buffer length is
+/// `width * height`.
+pub fn field_to_gray16(field: &Field) -> Vec<u16> {
+    field
+        .data()
+        .iter()
+        .map(|&v| {
+            if v.is_nan() {
+                0
+            } else {
+                (v.clamp(0.0, 1.0) * f64::from(u16::MAX)).round() as u16
+            }
+        })
+        .collect()
+}
+
+/// Derives a tangent-space normal map as an RGBA8 buffer.
+///
+/// Slopes are central differences scaled by `strength` (height units per
+/// pixel). Normals follow the OpenGL convention: +X right (red), +Y up
+/// (green), +Z out of the surface (blue), each encoded as `n * 0.5 + 0.5`.
+/// A flat field encodes as `(128, 128, 255)`. Alpha is always 255.
+pub fn field_to_normal_rgba(field: &Field, strength: f64) -> Vec<u8> {
+    let h = |x: usize, y: usize, dx: isize, dy: isize| {
+        let v = field.get(x as isize + dx, y as isize + dy);
+        if v.is_nan() {
+            0.0
+        } else {
+            v
+        }
+    };
+    field
+        .iter()
+        .flat_map(|(x, y, _)| {
+            let slope_x = (h(x, y, 1, 0) - h(x, y, -1, 0)) * 0.5 * strength;
+            // Image rows grow downward, so "up" is the negative row direction.
+            let slope_y = (h(x, y, 0, -1) - h(x, y, 0, 1)) * 0.5 * strength;
+            let (nx, ny, nz) = (-slope_x, -slope_y, 1.0);
+            let len = (nx * nx + ny * ny + nz * nz).sqrt();
+            let encode = |n: f64| ((n / len * 0.5 + 0.5) * 255.0).round() as u8;
+            [encode(nx), encode(ny), encode(nz), 255]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -- Heightmap --
+
+    #[test]
+    fn gray16_maps_extremes_and_clamps() {
+        let field = Field::from_data(4, 1, vec![0.0, 1.0, 1.5, f64::NAN]).unwrap();
+        assert_eq!(field_to_gray16(&field), vec![0, 65535, 65535, 0]);
+    }
+
+    #[test]
+    fn gray16_midpoint_is_half_range() {
+        let field = Field::filled(1, 1, 0.5).unwrap();
+        assert_eq!(field_to_gray16(&field), vec![32768]);
+    }
+
+    // -- Normal map --
+
+    #[test]
+    fn flat_field_encodes_straight_up_normal() {
+        let field = Field::filled(4, 4, 0.7).unwrap();
+        let buf = field_to_normal_rgba(&field, 4.0);
+        assert_eq!(buf.len(), 4 * 4 * 4);
+        for px in buf.chunks_exact(4) {
+            assert_eq!(px, [128, 128, 255, 255]);
+        }
+    }
+
+    #[test]
+    fn ramp_rising_to_the_right_tilts_normal_left() {
+        let data = (0..8 * 3).map(|i| (i % 8) as f64 / 8.0).collect();
+        let field = Field::from_data(8, 3, data).unwrap();
+        let buf = field_to_normal_rgba(&field, 4.0);
+        // Interior pixel (x=3, y=1): height increases with x, so normal points -X.
+        let px = &buf[(8 + 3) * 4..(8 + 3) * 4 + 4];
+        assert!(px[0] < 128, "red should be below midpoint: {px:?}");
+        assert_eq!(px[1], 128);
+    }
+
+    #[test]
+    fn ramp_rising_downward_tilts_normal_up() {
+        let data = (0..3 * 8).map(|i| (i / 3) as f64 / 8.0).collect();
+        let field = Field::from_data(3, 8, data).unwrap();
+        let buf = field_to_normal_rgba(&field, 4.0);
+        // Interior pixel (x=1, y=3): height increases downward, so normal points +Y (up).
+        let px = &buf[(3 * 3 + 1) * 4..(3 * 3 + 1) * 4 + 4];
+        assert!(px[1] > 128, "green should be above midpoint: {px:?}");
+        assert_eq!(px[0], 128);
+    }
+
+    #[test]
+    fn zero_strength_is_flat() {
+        let data = (0..16).map(|i| i as f64 / 16.0).collect();
+        let field = Field::from_data(4, 4, data).unwrap();
+        let buf = field_to_normal_rgba(&field, 0.0);
+        assert!(buf.chunks_exact(4).all(|px| px == [128, 128, 255, 255]));
+    }
+}
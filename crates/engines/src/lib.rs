@@ -6,32 +6,67 @@
 //! and the individual engine crates (`art-engine-gray-scott`, etc.). Both the
 //! CLI and WASM bindings depend on this crate to avoid duplicating dispatch logic.
 
+pub mod composition;
+pub mod contour;
+pub mod determinism;
+pub mod heightmap;
 pub mod pixel;
+pub mod presets;
 
 #[cfg(feature = "png")]
 pub mod snapshot;
 
+use art_engine_core::budget::MemoryBudget;
 use art_engine_core::error::EngineError;
 use art_engine_core::field::Field;
 use art_engine_core::Engine;
 use serde_json::Value;
 
-/// All available engine names.
-const ENGINE_NAMES: &[&str] = &["gray-scott"];
+/// All available engine names, sorted.
+const ENGINE_NAMES: &[&str] = &[
+    "differential-growth",
+    "gray-scott",
+    "gray-scott-advected",
+    "lsystem",
+    "rps",
+    "schelling",
+    "stipple",
+    "truchet",
+    "voronoi",
+];
 
 /// Enumeration of all available generative art engines.
 ///
 /// Wraps each engine implementation and delegates `Engine` trait methods.
 /// Use [`EngineKind::from_name`] for string-based construction (CLI, WASM).
+/// Every engine is `Send`, so an instance can be built on one thread and
+/// stepped on another (a native worker thread, or a Web Worker's module).
 pub enum EngineKind {
-    /// Gray-Scott reaction-diffusion.
+    /// Gray-Scott reaction-diffusion, optionally advected by a velocity
+    /// field (`gray-scott-advected`).
     GrayScott(art_engine_gray_scott::GrayScott),
+    /// Schelling segregation model.
+    Schelling(art_engine_schelling::Schelling),
+    /// Spatial rock-paper-scissors (cyclic dominance).
+    Rps(art_engine_rps::Rps),
+    /// L-system drawn by a turtle.
+    LSystem(art_engine_lsystem::LSystem),
+    /// Poisson-disk (blue-noise) stippling.
+    Stipple(art_engine_stipple::Stipple),
+    /// Differential line growth.
+    DifferentialGrowth(art_engine_differential_growth::DifferentialGrowth),
+    /// Truchet tiling, revealed one subdivision level per step.
+    Truchet(art_engine_truchet::Truchet),
+    /// Voronoi cells and Delaunay mesh over moving sites.
+    Voronoi(art_engine_voronoi::Voronoi),
 }
 
 impl EngineKind {
-    /// Constructs an engine by name.
+    /// Constructs an engine by name, within the default [`MemoryBudget`].
     ///
-    /// Returns `EngineError::UnknownEngine` if the name is not recognized.
+    /// Returns `EngineError::UnknownEngine` if the name is not recognized,
+    /// and `EngineError::OverMemoryBudget` if the size would need more than
+    /// the default budget.
     pub fn from_name(
         name: &str,
         width: usize,
@@ -39,10 +74,101 @@ impl EngineKind {
         seed: u64,
         params: &Value,
     ) -> Result<Self, EngineError> {
+        Self::from_name_within(name, width, height, seed, params, MemoryBudget::default())
+    }
+
+    /// Constructs an engine by name, first checking its
+    /// [`memory estimate`](EngineKind::estimate_memory) against `budget`.
+    ///
+    /// Returns `EngineError::UnknownEngine` if the name is not recognized,
+    /// and `EngineError::OverMemoryBudget`, before allocating anything, if
+    /// the engine would not fit.
+    pub fn from_name_within(
+        name: &str,
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: &Value,
+        budget: MemoryBudget,
+    ) -> Result<Self, EngineError> {
+        let needed = Self::estimate_memory(name, width, height, params)?;
+        budget.check(&format!("{name} at {width}x{height}"), needed)?;
         match name {
             "gray-scott" => Ok(EngineKind::GrayScott(
                 art_engine_gray_scott::GrayScott::from_json(width, height, seed, params)?,
             )),
+            "gray-scott-advected" => Ok(EngineKind::GrayScott(
+                art_engine_gray_scott::GrayScott::advected_from_json(width, height, seed, params)?,
+            )),
+            "schelling" => Ok(EngineKind::Schelling(
+                art_engine_schelling::Schelling::from_json(width, height, seed, params)?,
+            )),
+            "rps" => Ok(EngineKind::Rps(art_engine_rps::Rps::from_json(
+                width, height, seed, params,
+            )?)),
+            "lsystem" => Ok(EngineKind::LSystem(art_engine_lsystem::LSystem::from_json(
+                width, height, seed, params,
+            )?)),
+            "stipple" => Ok(EngineKind::Stipple(stipple_from_json(
+                width, height, seed, params,
+            )?)),
+            "differential-growth" => Ok(EngineKind::DifferentialGrowth(
+                art_engine_differential_growth::DifferentialGrowth::from_json(
+                    width, height, seed, params,
+                )?,
+            )),
+            "truchet" => Ok(EngineKind::Truchet(art_engine_truchet::Truchet::from_json(
+                width, height, seed, params,
+            )?)),
+            "voronoi" => Ok(EngineKind::Voronoi(art_engine_voronoi::Voronoi::from_json(
+                width, height, seed, params,
+            )?)),
+            _ => Err(EngineError::UnknownEngine(name.to_string())),
+        }
+    }
+
+    /// Estimates the peak bytes the named engine holds at `width`×`height`
+    /// with `params`, without building it.
+    ///
+    /// Returns `EngineError::UnknownEngine` if the name is not recognized.
+    pub fn estimate_memory(
+        name: &str,
+        width: usize,
+        height: usize,
+        params: &Value,
+    ) -> Result<u64, EngineError> {
+        match name {
+            "gray-scott" => Ok(art_engine_gray_scott::GrayScott::memory_estimate(
+                width,
+                height,
+                &art_engine_gray_scott::GrayScottParams::from_json(params),
+            )),
+            "gray-scott-advected" => {
+                Ok(art_engine_gray_scott::GrayScott::advected_memory_estimate(
+                    width,
+                    height,
+                    &art_engine_gray_scott::GrayScottParams::from_json(params),
+                ))
+            }
+            "schelling" => Ok(art_engine_schelling::Schelling::memory_estimate(
+                width, height,
+            )),
+            "rps" => Ok(art_engine_rps::Rps::memory_estimate(width, height)),
+            "lsystem" => Ok(art_engine_lsystem::LSystem::memory_estimate(width, height)),
+            "stipple" => Ok(art_engine_stipple::Stipple::memory_estimate(width, height)),
+            "differential-growth" => Ok(
+                art_engine_differential_growth::DifferentialGrowth::memory_estimate(
+                    width,
+                    height,
+                    &art_engine_differential_growth::GrowthParams::from_json(params),
+                ),
+            ),
+            "truchet" => Ok(art_engine_truchet::Truchet::memory_estimate(width, height)),
+            "voronoi" => Ok(art_engine_voronoi::Voronoi::memory_estimate(
+                width,
+                height,
+                &art_engine_voronoi::VoronoiParams::from_json(params)?,
+            )),
             _ => Err(EngineError::UnknownEngine(name.to_string())),
         }
     }
@@ -57,32 +183,146 @@ impl Engine for EngineKind {
     fn step(&mut self) -> Result<(), EngineError> {
         match self {
             EngineKind::GrayScott(e) => e.step(),
+            EngineKind::Schelling(e) => e.step(),
+            EngineKind::Rps(e) => e.step(),
+            EngineKind::LSystem(e) => e.step(),
+            EngineKind::Stipple(e) => e.step(),
+            EngineKind::DifferentialGrowth(e) => e.step(),
+            EngineKind::Truchet(e) => e.step(),
+            EngineKind::Voronoi(e) => e.step(),
         }
     }
 
     fn field(&self) -> &Field {
         match self {
             EngineKind::GrayScott(e) => e.field(),
+            EngineKind::Schelling(e) => e.field(),
+            EngineKind::Rps(e) => e.field(),
+            EngineKind::LSystem(e) => e.field(),
+            EngineKind::Stipple(e) => e.field(),
+            EngineKind::DifferentialGrowth(e) => e.field(),
+            EngineKind::Truchet(e) => e.field(),
+            EngineKind::Voronoi(e) => e.field(),
         }
     }
 
     fn params(&self) -> Value {
         match self {
             EngineKind::GrayScott(e) => e.params(),
+            EngineKind::Schelling(e) => e.params(),
+            EngineKind::Rps(e) => e.params(),
+            EngineKind::LSystem(e) => e.params(),
+            EngineKind::Stipple(e) => e.params(),
+            EngineKind::DifferentialGrowth(e) => e.params(),
+            EngineKind::Truchet(e) => e.params(),
+            EngineKind::Voronoi(e) => e.params(),
+        }
+    }
+
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        match self {
+            EngineKind::GrayScott(e) => e.set_params(params),
+            EngineKind::Schelling(e) => e.set_params(params),
+            EngineKind::Rps(e) => e.set_params(params),
+            EngineKind::LSystem(e) => e.set_params(params),
+            EngineKind::Stipple(e) => e.set_params(params),
+            EngineKind::DifferentialGrowth(e) => e.set_params(params),
+            EngineKind::Truchet(e) => e.set_params(params),
+            EngineKind::Voronoi(e) => e.set_params(params),
         }
     }
 
     fn param_schema(&self) -> Value {
         match self {
             EngineKind::GrayScott(e) => e.param_schema(),
+            EngineKind::Schelling(e) => e.param_schema(),
+            EngineKind::Rps(e) => e.param_schema(),
+            EngineKind::LSystem(e) => e.param_schema(),
+            EngineKind::Stipple(e) => e.param_schema(),
+            EngineKind::DifferentialGrowth(e) => e.param_schema(),
+            EngineKind::Truchet(e) => e.param_schema(),
+            EngineKind::Voronoi(e) => e.param_schema(),
+        }
+    }
+
+    fn presets(&self) -> Value {
+        match self {
+            EngineKind::GrayScott(e) => e.presets(),
+            EngineKind::Schelling(e) => e.presets(),
+            EngineKind::Rps(e) => e.presets(),
+            EngineKind::LSystem(e) => e.presets(),
+            EngineKind::Stipple(e) => e.presets(),
+            EngineKind::DifferentialGrowth(e) => e.presets(),
+            EngineKind::Truchet(e) => e.presets(),
+            EngineKind::Voronoi(e) => e.presets(),
         }
     }
 
     fn hue_field(&self) -> Option<&Field> {
         match self {
             EngineKind::GrayScott(e) => e.hue_field(),
+            EngineKind::Schelling(e) => e.hue_field(),
+            EngineKind::Rps(e) => e.hue_field(),
+            EngineKind::LSystem(e) => e.hue_field(),
+            EngineKind::Stipple(e) => e.hue_field(),
+            EngineKind::DifferentialGrowth(e) => e.hue_field(),
+            EngineKind::Truchet(e) => e.hue_field(),
+            EngineKind::Voronoi(e) => e.hue_field(),
         }
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        match self {
+            EngineKind::GrayScott(e) => e.save_state(),
+            EngineKind::Schelling(e) => e.save_state(),
+            EngineKind::Rps(e) => e.save_state(),
+            EngineKind::LSystem(e) => e.save_state(),
+            EngineKind::Stipple(e) => e.save_state(),
+            EngineKind::DifferentialGrowth(e) => e.save_state(),
+            EngineKind::Truchet(e) => e.save_state(),
+            EngineKind::Voronoi(e) => e.save_state(),
+        }
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        match self {
+            EngineKind::GrayScott(e) => e.load_state(state),
+            EngineKind::Schelling(e) => e.load_state(state),
+            EngineKind::Rps(e) => e.load_state(state),
+            EngineKind::LSystem(e) => e.load_state(state),
+            EngineKind::Stipple(e) => e.load_state(state),
+            EngineKind::DifferentialGrowth(e) => e.load_state(state),
+            EngineKind::Truchet(e) => e.load_state(state),
+            EngineKind::Voronoi(e) => e.load_state(state),
+        }
+    }
+}
+
+/// Builds a stipple engine, reading an image density from a PNG file.
+#[cfg(feature = "png")]
+fn stipple_from_json(
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: &Value,
+) -> Result<art_engine_stipple::Stipple, EngineError> {
+    let load = |path: &str| {
+        let (rgba, w, h) = snapshot::read_rgba_png(std::path::Path::new(path))?;
+        art_engine_core::image::Image::from_rgba8(w, h, &rgba)
+    };
+    art_engine_stipple::Stipple::from_json_with_images(width, height, seed, params, &load)
+}
+
+/// Builds a stipple engine; without the `png` feature, image densities
+/// are unsupported.
+#[cfg(not(feature = "png"))]
+fn stipple_from_json(
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: &Value,
+) -> Result<art_engine_stipple::Stipple, EngineError> {
+    art_engine_stipple::Stipple::from_json(width, height, seed, params)
 }
 
 #[cfg(test)]
@@ -96,12 +336,71 @@ mod tests {
         assert!(engine.is_ok());
     }
 
+    #[test]
+    fn gray_scott_advected_echoes_a_rebuildable_velocity() {
+        let mut engine =
+            EngineKind::from_name("gray-scott-advected", 24, 16, 42, &json!({})).unwrap();
+        let params = engine.params();
+        assert_eq!(params["velocity"]["kind"], "curl");
+        assert_eq!(params["velocity"]["period"], json!([24.0, 16.0]));
+        assert!(engine.param_schema().get("velocity").is_some());
+
+        let mut rebuilt =
+            EngineKind::from_name("gray-scott-advected", 24, 16, 42, &params).unwrap();
+        (0..5).for_each(|_| {
+            engine.step().unwrap();
+            rebuilt.step().unwrap();
+        });
+        assert_eq!(engine.field(), rebuilt.field());
+
+        let bad = json!({"velocity": {"kind": "hurricane"}});
+        assert!(matches!(
+            EngineKind::from_name("gray-scott-advected", 8, 8, 1, &bad),
+            Err(EngineError::InvalidFieldSource(_))
+        ));
+        let plain = EngineKind::estimate_memory("gray-scott", 64, 64, &json!({})).unwrap();
+        let advected =
+            EngineKind::estimate_memory("gray-scott-advected", 64, 64, &json!({})).unwrap();
+        assert!(advected > plain);
+    }
+
     #[test]
     fn from_name_unknown_returns_error() {
         let result = EngineKind::from_name("nonexistent", 32, 32, 42, &json!({}));
         assert!(matches!(result, Err(EngineError::UnknownEngine(_))));
     }
 
+    #[test]
+    fn from_name_refuses_sizes_over_budget() {
+        let result = EngineKind::from_name("gray-scott", 1 << 20, 1 << 20, 42, &json!({}));
+        assert!(matches!(result, Err(EngineError::OverMemoryBudget(_))));
+        let needed = EngineKind::estimate_memory("gray-scott", 64, 64, &json!({})).unwrap();
+        let tight = MemoryBudget::new(needed - 1);
+        let result = EngineKind::from_name_within("gray-scott", 64, 64, 42, &json!({}), tight);
+        assert!(matches!(result, Err(EngineError::OverMemoryBudget(_))));
+        let exact = MemoryBudget::new(needed);
+        assert!(EngineKind::from_name_within("gray-scott", 64, 64, 42, &json!({}), exact).is_ok());
+    }
+
+    #[test]
+    fn estimate_memory_follows_params() {
+        let wide = EngineKind::estimate_memory("gray-scott", 64, 64, &json!({})).unwrap();
+        let narrow =
+            EngineKind::estimate_memory("gray-scott", 64, 64, &json!({"precision": "f32"}))
+                .unwrap();
+        assert!(narrow < wide);
+        assert!(matches!(
+            EngineKind::estimate_memory("nonexistent", 64, 64, &json!({})),
+            Err(EngineError::UnknownEngine(_))
+        ));
+    }
+
+    #[test]
+    fn engine_kind_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<EngineKind>();
+    }
+
     #[test]
     fn list_engines_includes_gray_scott() {
         let names = EngineKind::list_engines();
@@ -116,6 +415,47 @@ mod tests {
         engine.step().unwrap();
     }
 
+    #[test]
+    fn every_engine_accepts_its_own_params_mid_run() {
+        for &name in EngineKind::list_engines() {
+            let mut engine = EngineKind::from_name(name, 24, 24, 42, &json!({})).unwrap();
+            engine.step().unwrap();
+            let params = engine.params();
+            engine
+                .set_params(&params)
+                .unwrap_or_else(|e| panic!("{name}: {e}"));
+            assert_eq!(engine.params(), params, "{name}");
+        }
+    }
+
+    #[test]
+    fn presets_build_and_stay_within_the_schema() {
+        for &name in EngineKind::list_engines() {
+            let engine = EngineKind::from_name(name, 8, 8, 1, &json!({})).unwrap();
+            let schema = engine.param_schema();
+            for (preset, params) in engine.presets().as_object().unwrap() {
+                EngineKind::from_name(name, 8, 8, 1, params)
+                    .unwrap_or_else(|e| panic!("{name}/{preset}: {e}"));
+                for (key, value) in params.as_object().unwrap() {
+                    let spec = &schema[key];
+                    if let (Some(value), Some(min), Some(max)) =
+                        (value.as_f64(), spec["min"].as_f64(), spec["max"].as_f64())
+                    {
+                        assert!(
+                            (min..=max).contains(&value),
+                            "{name}/{preset}.{key} = {value} is out of range"
+                        );
+                    }
+                    if let (Some(value), Some(options)) =
+                        (value.as_str(), spec["options"].as_array())
+                    {
+                        assert!(options.contains(&json!(value)), "{name}/{preset}.{key}");
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn trait_delegation_params_and_schema() {
         let engine = EngineKind::from_name("gray-scott", 16, 16, 42, &json!({})).unwrap();
@@ -2,7 +2,19 @@
 //!
 //! This module is always available (no feature gate) so that both the `png`
 //! snapshot path and the WASM `ImageData` path can share the same conversion.
+//!
+//! Unrotated sRGB pixels read the palette's cached lookup table
+//! ([`Palette::sample_fast`]), which writes the same bytes as the exact
+//! OKLCh chain. Hue-rotated and wide-gamut pixels always take the exact
+//! chain.
+//!
+//! With the `parallel` feature pixels are converted across threads. Each
+//! pixel depends only on its own field values, so the bytes are identical
+//! to a single-threaded conversion.
 
+use art_engine_core::color::{oklch_to_srgb, ColorSpace, OkLch};
+use art_engine_core::engine::Engine;
+use art_engine_core::error::EngineError;
 use art_engine_core::field::Field;
 use art_engine_core::palette::Palette;
 
@@ -11,17 +23,168 @@ use art_engine_core::palette::Palette;
 /// Each field value `t` in [0, 1] is sampled from the palette and written as
 /// four bytes (R, G, B, 255). The buffer length is `width * height * 4`.
 pub fn field_to_rgba(field: &Field, palette: &Palette) -> Vec<u8> {
-    field
-        .data()
-        .iter()
-        .flat_map(|&t| {
-            let srgb = palette.sample(t);
-            let r = (srgb.r * 255.0).round() as u8;
-            let g = (srgb.g * 255.0).round() as u8;
-            let b = (srgb.b * 255.0).round() as u8;
-            [r, g, b, 255u8]
+    map_pixels(field.data().len(), |i| {
+        palette_rgba(palette, field.data()[i], 0.0, 255)
+    })
+}
+
+/// Maps field values through a palette, taking per-pixel alpha from a
+/// secondary field.
+///
+/// Each alpha value in [0, 1] is scaled to a byte (values outside the range
+/// are clamped). With `alpha = None` this is identical to [`field_to_rgba`].
+///
+/// Returns `EngineError::DimensionMismatch` if the alpha field differs in
+/// size from the color field.
+pub fn field_to_rgba_with_alpha(
+    field: &Field,
+    palette: &Palette,
+    alpha: Option<&Field>,
+) -> Result<Vec<u8>, EngineError> {
+    fields_to_rgba(field, palette, None, alpha, ColorSpace::Srgb)
+}
+
+/// Maps field values through a palette, rotating each pixel's hue by a
+/// secondary hue field.
+///
+/// The primary field selects the palette position (and therefore lightness
+/// and chroma); the hue field value in [0, 1] rotates the sampled color's
+/// OKLCh hue by a full turn. With `hue = None` this is identical to
+/// [`field_to_rgba`].
+///
+/// Returns `EngineError::DimensionMismatch` if the hue field differs in size
+/// from the color field.
+pub fn field_to_rgba_with_hue(
+    field: &Field,
+    palette: &Palette,
+    hue: Option<&Field>,
+) -> Result<Vec<u8>, EngineError> {
+    fields_to_rgba(field, palette, hue, None, ColorSpace::Srgb)
+}
+
+/// Renders an engine's primary field through a palette, applying the
+/// engine's [`Engine::hue_field`] when it provides one.
+pub fn engine_to_rgba(engine: &dyn Engine, palette: &Palette) -> Result<Vec<u8>, EngineError> {
+    field_to_rgba_with_hue(engine.field(), palette, engine.hue_field())
+}
+
+/// Like [`field_to_rgba_with_hue`], but encodes the pixels in `space`
+/// instead of sRGB, keeping palette colors that sRGB would clamp.
+///
+/// With [`ColorSpace::Srgb`] this is identical to
+/// [`field_to_rgba_with_hue`].
+///
+/// Returns `EngineError::DimensionMismatch` if the hue field differs in size
+/// from the color field.
+pub fn field_to_rgba_in(
+    field: &Field,
+    palette: &Palette,
+    hue: Option<&Field>,
+    space: ColorSpace,
+) -> Result<Vec<u8>, EngineError> {
+    fields_to_rgba(field, palette, hue, None, space)
+}
+
+/// Shared conversion for the optional hue and alpha fields and color space.
+fn fields_to_rgba(
+    field: &Field,
+    palette: &Palette,
+    hue: Option<&Field>,
+    alpha: Option<&Field>,
+    space: ColorSpace,
+) -> Result<Vec<u8>, EngineError> {
+    hue.into_iter()
+        .chain(alpha)
+        .try_for_each(|other| check_same_size(field, other))?;
+    Ok(map_pixels(field.data().len(), |i| {
+        let t = field.data()[i];
+        let h = hue.map_or(0.0, |f| f.data()[i]);
+        let a = alpha.map_or(255, |f| unit_to_byte(f.data()[i]));
+        palette_rgba_in(palette, t, h, a, space)
+    }))
+}
+
+/// Builds an RGBA8 buffer of `len` pixels from a per-pixel function, across
+/// threads with the `parallel` feature.
+fn map_pixels(len: usize, pixel: impl Fn(usize) -> [u8; 4] + Sync) -> Vec<u8> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        let mut buf = vec![0; len * 4];
+        buf.par_chunks_mut(4)
+            .enumerate()
+            .for_each(|(i, rgba)| rgba.copy_from_slice(&pixel(i)));
+        buf
+    }
+    #[cfg(not(feature = "parallel"))]
+    (0..len).flat_map(pixel).collect()
+}
+
+/// Returns `EngineError::DimensionMismatch` unless both fields have the same size.
+fn check_same_size(field: &Field, other: &Field) -> Result<(), EngineError> {
+    if field.width() == other.width() && field.height() == other.height() {
+        Ok(())
+    } else {
+        Err(EngineError::DimensionMismatch {
+            lhs_w: field.width(),
+            lhs_h: field.height(),
+            rhs_w: other.width(),
+            rhs_h: other.height(),
+        })
+    }
+}
+
+/// Samples the palette at `t`, rotates the hue by `hue_turns` full turns, and
+/// packs the result with the given alpha byte.
+fn palette_rgba(palette: &Palette, t: f64, hue_turns: f64, alpha: u8) -> [u8; 4] {
+    let srgb = if hue_turns == 0.0 || !hue_turns.is_finite() {
+        palette.sample_fast(t)
+    } else {
+        let base = palette.sample_oklch(t);
+        oklch_to_srgb(OkLch {
+            h: (base.h + hue_turns * 360.0).rem_euclid(360.0),
+            ..base
         })
-        .collect()
+    };
+    [
+        unit_to_byte(srgb.r),
+        unit_to_byte(srgb.g),
+        unit_to_byte(srgb.b),
+        alpha,
+    ]
+}
+
+/// [`palette_rgba`] encoding the color in `space`.
+fn palette_rgba_in(
+    palette: &Palette,
+    t: f64,
+    hue_turns: f64,
+    alpha: u8,
+    space: ColorSpace,
+) -> [u8; 4] {
+    if space.is_srgb() {
+        return palette_rgba(palette, t, hue_turns, alpha);
+    }
+    let base = palette.sample_oklch(t);
+    let color = if hue_turns == 0.0 || !hue_turns.is_finite() {
+        base
+    } else {
+        OkLch {
+            h: (base.h + hue_turns * 360.0).rem_euclid(360.0),
+            ..base
+        }
+    };
+    let [r, g, b] = space.encode_oklch(color);
+    [unit_to_byte(r), unit_to_byte(g), unit_to_byte(b), alpha]
+}
+
+/// Converts a [0, 1] channel value to a byte. NaN maps to 0.
+fn unit_to_byte(v: f64) -> u8 {
+    if v.is_nan() {
+        0
+    } else {
+        (v.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
 }
 
 #[cfg(test)]
@@ -70,4 +233,145 @@ mod tests {
         assert!(buf_one[1] > 245, "g at t=1: {}", buf_one[1]);
         assert!(buf_one[2] > 245, "b at t=1: {}", buf_one[2]);
     }
+
+    // -- Palette lookup table --
+
+    #[test]
+    fn field_to_rgba_lut_matches_exact_bytes() {
+        let data = (0..4096).map(|i| f64::from(i) / 4095.0).collect();
+        let field = Field::from_data(64, 64, data).unwrap();
+        for palette in [Palette::neon(), Palette::earth()] {
+            let fast = field_to_rgba(&field, &palette);
+            let exact = field_to_rgba(&field, &palette.clone().with_lut(false));
+            assert_eq!(fast, exact);
+            assert_eq!(
+                exact[..4],
+                palette_rgba(&palette.with_lut(false), 0.0, 0.0, 255)
+            );
+        }
+    }
+
+    // -- Color spaces --
+
+    #[test]
+    fn field_to_rgba_in_srgb_matches_the_default_conversion() {
+        let field = Field::from_data(4, 1, vec![0.0, 0.3, 0.6, 1.0]).unwrap();
+        let hue = Field::filled(4, 1, 0.25).unwrap();
+        let palette = Palette::neon();
+        assert_eq!(
+            field_to_rgba_in(&field, &palette, Some(&hue), ColorSpace::Srgb).unwrap(),
+            field_to_rgba_with_hue(&field, &palette, Some(&hue)).unwrap()
+        );
+    }
+
+    #[test]
+    fn field_to_rgba_in_wide_gamut_desaturates_srgb_primaries() {
+        // Pure sRGB red sits inside the P3 and Rec.2020 gamuts
+        let palette = Palette::from_hex(&["#ff0000"]).unwrap();
+        let field = Field::filled(1, 1, 0.5).unwrap();
+        let p3 = field_to_rgba_in(&field, &palette, None, ColorSpace::DisplayP3).unwrap();
+        let rec2020 = field_to_rgba_in(&field, &palette, None, ColorSpace::Rec2020).unwrap();
+        assert!(p3[0] < 255 && p3[1] > 0, "{p3:?}");
+        assert!(rec2020[0] < p3[0], "{rec2020:?} vs {p3:?}");
+        assert_eq!(p3[3], 255);
+    }
+
+    // -- Alpha field --
+
+    #[test]
+    fn field_to_rgba_with_alpha_none_matches_opaque() {
+        let field = Field::filled(4, 4, 0.3).unwrap();
+        let palette = Palette::ocean();
+        let opaque = field_to_rgba(&field, &palette);
+        let buf = field_to_rgba_with_alpha(&field, &palette, None).unwrap();
+        assert_eq!(buf, opaque);
+    }
+
+    #[test]
+    fn field_to_rgba_with_alpha_maps_alpha_field_to_bytes() {
+        let field = Field::filled(2, 1, 0.5).unwrap();
+        let alpha = Field::from_data(2, 1, vec![0.0, 1.0]).unwrap();
+        let palette = Palette::neon();
+        let buf = field_to_rgba_with_alpha(&field, &palette, Some(&alpha)).unwrap();
+        assert_eq!(buf[3], 0);
+        assert_eq!(buf[7], 255);
+        // Color channels are unaffected by alpha
+        assert_eq!(buf[0..3], buf[4..7]);
+    }
+
+    #[test]
+    fn field_to_rgba_with_alpha_clamps_out_of_range_alpha() {
+        let field = Field::filled(3, 1, 0.5).unwrap();
+        let alpha = Field::from_data(3, 1, vec![-1.0, 2.0, f64::NAN]).unwrap();
+        let palette = Palette::ocean();
+        let buf = field_to_rgba_with_alpha(&field, &palette, Some(&alpha)).unwrap();
+        assert_eq!(buf[3], 0);
+        assert_eq!(buf[7], 255);
+        assert_eq!(buf[11], 0);
+    }
+
+    #[test]
+    fn field_to_rgba_with_alpha_rejects_mismatched_dimensions() {
+        let field = Field::new(4, 4).unwrap();
+        let alpha = Field::new(4, 3).unwrap();
+        let palette = Palette::ocean();
+        let err = field_to_rgba_with_alpha(&field, &palette, Some(&alpha)).unwrap_err();
+        assert!(matches!(err, EngineError::DimensionMismatch { .. }));
+    }
+
+    // -- Hue field --
+
+    #[test]
+    fn field_to_rgba_with_hue_none_matches_plain() {
+        let field = Field::filled(4, 4, 0.6).unwrap();
+        let palette = Palette::earth();
+        let buf = field_to_rgba_with_hue(&field, &palette, None).unwrap();
+        assert_eq!(buf, field_to_rgba(&field, &palette));
+    }
+
+    #[test]
+    fn field_to_rgba_with_hue_full_turn_is_identity() {
+        let field = Field::filled(2, 2, 0.5).unwrap();
+        let zero = Field::filled(2, 2, 0.0).unwrap();
+        let full = Field::filled(2, 2, 1.0).unwrap();
+        let palette = Palette::neon();
+        let a = field_to_rgba_with_hue(&field, &palette, Some(&zero)).unwrap();
+        let b = field_to_rgba_with_hue(&field, &palette, Some(&full)).unwrap();
+        for (x, y) in a.iter().zip(&b) {
+            assert!(
+                x.abs_diff(*y) <= 1,
+                "full hue turn changed color: {x} vs {y}"
+            );
+        }
+    }
+
+    #[test]
+    fn field_to_rgba_with_hue_half_turn_changes_color() {
+        let field = Field::filled(1, 1, 0.0).unwrap();
+        let half = Field::filled(1, 1, 0.5).unwrap();
+        let palette = Palette::from_hex(&["#cc3333"]).unwrap();
+        let plain = field_to_rgba(&field, &palette);
+        let rotated = field_to_rgba_with_hue(&field, &palette, Some(&half)).unwrap();
+        // Red rotated by 180 degrees should lose its red dominance
+        assert!(plain[0] > plain[2]);
+        assert!(rotated[0] < rotated[2], "rotated: {:?}", &rotated[..3]);
+    }
+
+    #[test]
+    fn field_to_rgba_with_hue_rejects_mismatched_dimensions() {
+        let field = Field::new(4, 4).unwrap();
+        let hue = Field::new(2, 4).unwrap();
+        let palette = Palette::ocean();
+        let err = field_to_rgba_with_hue(&field, &palette, Some(&hue)).unwrap_err();
+        assert!(matches!(err, EngineError::DimensionMismatch { .. }));
+    }
+
+    #[test]
+    fn engine_to_rgba_without_hue_field_matches_field_to_rgba() {
+        let engine =
+            crate::EngineKind::from_name("gray-scott", 8, 8, 42, &serde_json::json!({})).unwrap();
+        let palette = Palette::ocean();
+        let buf = engine_to_rgba(&engine, &palette).unwrap();
+        assert_eq!(buf, field_to_rgba(engine.field(), &palette));
+    }
 }
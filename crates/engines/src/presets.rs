@@ -0,0 +1,100 @@
+//! Built-in preset library.
+//!
+//! Every engine's [`Engine::presets`] become [`Preset`]s addressed as
+//! `engine/name`, e.g. `gray-scott/mitosis`.
+
+use art_engine_core::error::EngineError;
+use art_engine_core::preset::{split_id, Preset};
+use art_engine_core::Engine;
+use serde_json::{json, Value};
+
+use crate::EngineKind;
+
+/// Returns the built-in presets of `engine`, sorted by name.
+///
+/// Returns `EngineError::UnknownEngine` if the engine is not recognized.
+pub fn for_engine(engine: &str) -> Result<Vec<(String, Preset)>, EngineError> {
+    // Presets do not depend on size or seed; build a tiny instance
+    let presets = EngineKind::from_name(engine, 8, 8, 0, &json!({}))?.presets();
+    let Value::Object(presets) = presets else {
+        return Ok(Vec::new());
+    };
+    Ok(presets
+        .into_iter()
+        .map(|(name, params)| (format!("{engine}/{name}"), Preset::new(engine, params)))
+        .collect())
+}
+
+/// Returns every built-in preset as `(id, preset)`, sorted by id.
+pub fn builtin() -> Vec<(String, Preset)> {
+    // Engine order alone is not id order: `gray-scott-advected/…` sorts
+    // before `gray-scott/…`
+    let mut presets: Vec<_> = EngineKind::list_engines()
+        .iter()
+        .filter_map(|engine| for_engine(engine).ok())
+        .flatten()
+        .collect();
+    presets.sort_by(|(a, _), (b, _)| a.cmp(b));
+    presets
+}
+
+/// Looks up a built-in preset by `engine/name` id.
+///
+/// Returns `EngineError::UnknownEngine` for an unknown engine and
+/// `EngineError::InvalidPreset`, listing the engine's presets, for an
+/// unknown name or an id without an engine.
+pub fn find(id: &str) -> Result<Preset, EngineError> {
+    let (engine, _) = split_id(id).ok_or_else(|| {
+        EngineError::InvalidPreset(format!("'{id}' is not an engine/name preset id"))
+    })?;
+    let presets = for_engine(engine)?;
+    presets
+        .iter()
+        .find(|(found, _)| found == id)
+        .map(|(_, preset)| preset.clone())
+        .ok_or_else(|| {
+            let names: Vec<_> = presets.iter().map(|(id, _)| id.as_str()).collect();
+            EngineError::InvalidPreset(format!(
+                "unknown preset '{id}' (built in: {})",
+                names.join(", ")
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gray_scott_presets_are_built_in() {
+        let mitosis = find("gray-scott/mitosis").unwrap();
+        assert_eq!(mitosis.engine, "gray-scott");
+        assert_eq!(mitosis.params["feed_rate"], json!(0.0367));
+        let ids: Vec<_> = builtin().into_iter().map(|(id, _)| id).collect();
+        assert!(ids.contains(&"gray-scott/coral".to_string()));
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]), "{ids:?}");
+    }
+
+    #[test]
+    fn every_builtin_preset_builds_its_engine() {
+        for (id, preset) in builtin() {
+            assert!(preset.validate().is_ok(), "{id}");
+            let engine = EngineKind::from_name(&preset.engine, 8, 8, 1, &preset.params);
+            assert!(engine.is_ok(), "{id}");
+        }
+    }
+
+    #[test]
+    fn unknown_presets_are_errors() {
+        assert!(matches!(
+            find("nope/mitosis"),
+            Err(EngineError::UnknownEngine(_))
+        ));
+        let err = find("gray-scott/nope").unwrap_err().to_string();
+        assert!(err.contains("gray-scott/mitosis"), "{err}");
+        assert!(matches!(
+            find("mitosis"),
+            Err(EngineError::InvalidPreset(_))
+        ));
+    }
+}
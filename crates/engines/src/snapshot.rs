@@ -3,33 +3,298 @@
 //! This module is feature-gated behind `png` (default on) so that WASM builds
 //! can depend on the `engines` crate without pulling in the `image` crate.
 //! The pixel buffer conversion itself lives in [`crate::pixel`] (always available).
+//!
+//! PNGs can carry the full [`Seed`] as an iTXt chunk (keyword
+//! [`SEED_KEYWORD`]) so every exported artwork is self-describing. A seed
+//! with a wide-gamut [`ColorSpace`] also tags the PNG with that space
+//! (cICP, plus cHRM and gAMA for older decoders); [`write_rgba_png_in`]
+//! tags images without a seed the same way.
 
+use art_engine_core::color::ColorSpace;
+use art_engine_core::engine::Engine;
 use art_engine_core::error::EngineError;
 use art_engine_core::field::Field;
 use art_engine_core::palette::Palette;
+use art_engine_core::seed::Seed;
+use std::fs::File;
+use std::io::BufReader;
 use std::path::Path;
 
-use crate::pixel::field_to_rgba;
+use crate::contour::field_to_svg;
+use crate::heightmap::{field_to_gray16, field_to_normal_rgba};
+use crate::pixel::{engine_to_rgba, field_to_rgba, field_to_rgba_with_alpha};
 
 /// Writes a field as a PNG image, mapping values through the given palette.
 ///
 /// Returns `EngineError::InvalidDimensions` if the field dimensions overflow
 /// `u32`, or `EngineError::Io` on write failure.
 pub fn write_png(field: &Field, palette: &Palette, path: &Path) -> Result<(), EngineError> {
-    let rgba = field_to_rgba(field, palette);
-    let w = u32::try_from(field.width()).map_err(|_| EngineError::InvalidDimensions)?;
-    let h = u32::try_from(field.height()).map_err(|_| EngineError::InvalidDimensions)?;
+    save_rgba(field_to_rgba(field, palette), field, path)
+}
+
+/// Writes a field as a PNG image whose alpha channel is driven by a
+/// secondary field (e.g. an engine's U field or a mask).
+///
+/// Returns `EngineError::DimensionMismatch` if the alpha field differs in
+/// size, plus the same errors as [`write_png`].
+pub fn write_png_with_alpha(
+    field: &Field,
+    palette: &Palette,
+    alpha: Option<&Field>,
+    path: &Path,
+) -> Result<(), EngineError> {
+    save_rgba(
+        field_to_rgba_with_alpha(field, palette, alpha)?,
+        field,
+        path,
+    )
+}
+
+/// Writes an engine's primary field as a PNG image, rotating hue by the
+/// engine's hue field when it provides one.
+///
+/// Returns the same errors as [`write_png`].
+pub fn write_engine_png(
+    engine: &dyn Engine,
+    palette: &Palette,
+    path: &Path,
+) -> Result<(), EngineError> {
+    save_rgba(engine_to_rgba(engine, palette)?, engine.field(), path)
+}
+
+/// Writes iso-contours of a field as a filled, stroked SVG document.
+///
+/// See [`crate::contour::field_to_svg`] for how thresholds map to bands.
+/// Returns `EngineError::Io` on write failure.
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+pub fn write_svg(
+    field: &Field,
+    palette: &Palette,
+    thresholds: &[f64],
+    path: &Path,
+) -> Result<(), EngineError> {
+    std::fs::write(path, field_to_svg(field, palette, thresholds))
+        .map_err(|e| EngineError::Io(e.to_string()))
+}
+
+/// Writes a pre-built RGBA8 buffer (e.g. from [`crate::pixel`]) as a PNG.
+///
+/// Returns `EngineError::InvalidDimensions` if the dimensions overflow `u32`,
+/// or `EngineError::Io` if the buffer length does not match or the write fails.
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display(), width = width, height = height))]
+pub fn write_rgba_png(
+    rgba: Vec<u8>,
+    width: usize,
+    height: usize,
+    path: &Path,
+) -> Result<(), EngineError> {
+    let w = u32::try_from(width).map_err(|_| EngineError::InvalidDimensions)?;
+    let h = u32::try_from(height).map_err(|_| EngineError::InvalidDimensions)?;
     let img = image::RgbaImage::from_raw(w, h, rgba)
         .ok_or_else(|| EngineError::Io("RGBA buffer size mismatch".into()))?;
     img.save(path).map_err(|e| EngineError::Io(e.to_string()))
 }
 
+/// Writes a field as a 16-bit grayscale heightmap PNG.
+///
+/// Returns `EngineError::InvalidDimensions` if the field dimensions overflow
+/// `u32`, or `EngineError::Io` on write failure.
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+pub fn write_heightmap_png(field: &Field, path: &Path) -> Result<(), EngineError> {
+    let w = u32::try_from(field.width()).map_err(|_| EngineError::InvalidDimensions)?;
+    let h = u32::try_from(field.height()).map_err(|_| EngineError::InvalidDimensions)?;
+    let img = image::ImageBuffer::<image::Luma<u16>, _>::from_raw(w, h, field_to_gray16(field))
+        .ok_or_else(|| EngineError::Io("heightmap buffer size mismatch".into()))?;
+    img.save(path).map_err(|e| EngineError::Io(e.to_string()))
+}
+
+/// Writes a tangent-space normal map derived from the field as a PNG.
+///
+/// See [`crate::heightmap::field_to_normal_rgba`] for the encoding. Returns
+/// the same errors as [`write_png`].
+pub fn write_normal_map_png(field: &Field, strength: f64, path: &Path) -> Result<(), EngineError> {
+    save_rgba(field_to_normal_rgba(field, strength), field, path)
+}
+
+/// PNG text chunk keyword under which the seed JSON is stored.
+pub const SEED_KEYWORD: &str = "art-engine:seed";
+
+/// Writes an RGBA8 buffer as a PNG with the seed JSON embedded in an iTXt
+/// chunk under [`SEED_KEYWORD`], tagged with the seed's color space unless
+/// that is sRGB.
+///
+/// Returns `EngineError::InvalidDimensions` if the dimensions overflow `u32`,
+/// or `EngineError::Io` on a buffer size mismatch, serialization, or write
+/// failure.
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display(), width = width, height = height))]
+pub fn write_rgba_png_with_seed(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    seed: &Seed,
+    path: &Path,
+) -> Result<(), EngineError> {
+    let bytes = encode_rgba_png_with_seed(rgba, width, height, seed)?;
+    std::fs::write(path, bytes).map_err(|e| EngineError::Io(e.to_string()))
+}
+
+/// Encodes an RGBA8 buffer as PNG bytes with the seed embedded, exactly as
+/// [`write_rgba_png_with_seed`] writes them.
+///
+/// Returns `EngineError::InvalidDimensions` if the dimensions overflow `u32`,
+/// or `EngineError::Io` on a buffer size mismatch or encoding failure.
+pub fn encode_rgba_png_with_seed(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    seed: &Seed,
+) -> Result<Vec<u8>, EngineError> {
+    encode_rgba_png(rgba, width, height, seed.color_space, Some(seed))
+}
+
+/// Writes an RGBA8 buffer as a PNG without a seed, tagged with `space`
+/// unless that is sRGB, for images such as animation frames and contact
+/// sheets whose pixels were encoded in that space.
+///
+/// Returns the same errors as [`write_rgba_png_with_seed`].
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display(), width = width, height = height))]
+pub fn write_rgba_png_in(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    space: ColorSpace,
+    path: &Path,
+) -> Result<(), EngineError> {
+    let bytes = encode_rgba_png(rgba, width, height, space, None)?;
+    std::fs::write(path, bytes).map_err(|e| EngineError::Io(e.to_string()))
+}
+
+/// Encodes an RGBA8 buffer tagged with `space`, embedding `seed` if given.
+fn encode_rgba_png(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    space: ColorSpace,
+    seed: Option<&Seed>,
+) -> Result<Vec<u8>, EngineError> {
+    let w = u32::try_from(width).map_err(|_| EngineError::InvalidDimensions)?;
+    let h = u32::try_from(height).map_err(|_| EngineError::InvalidDimensions)?;
+    if rgba.len() != width.saturating_mul(height).saturating_mul(4) {
+        return Err(EngineError::Io("RGBA buffer size mismatch".into()));
+    }
+    let io_err = |e: png::EncodingError| EngineError::Io(e.to_string());
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, w, h);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let tag = color_space_tag(space);
+    if let Some((_, chromaticities)) = tag {
+        encoder.set_source_chromaticities(chromaticities);
+        // The sRGB curve approximated as a pure power law
+        encoder.set_source_gamma(png::ScaledFloat::new(1.0 / 2.2));
+    }
+    if let Some(seed) = seed {
+        let json = serde_json::to_string(seed).map_err(|e| EngineError::Io(e.to_string()))?;
+        encoder
+            .add_itxt_chunk(SEED_KEYWORD.to_string(), json)
+            .map_err(io_err)?;
+    }
+    let mut writer = encoder.write_header().map_err(io_err)?;
+    if let Some((cicp, _)) = tag {
+        writer
+            .write_chunk(png::chunk::cICP, &cicp)
+            .map_err(io_err)?;
+    }
+    writer.write_image_data(rgba).map_err(io_err)?;
+    writer.finish().map_err(io_err)?;
+    Ok(bytes)
+}
+
+/// Returns the cICP chunk data (H.273 primaries, sRGB transfer, RGB, full
+/// range) and cHRM chromaticities tagging `space`, or `None` for sRGB,
+/// which is left untagged as PNG's default.
+fn color_space_tag(space: ColorSpace) -> Option<([u8; 4], png::SourceChromaticities)> {
+    const SRGB_TRANSFER: u8 = 13;
+    const D65: (f32, f32) = (0.3127, 0.3290);
+    let (primaries, chromaticities) = match space {
+        ColorSpace::Srgb => return None,
+        ColorSpace::DisplayP3 => (
+            12,
+            png::SourceChromaticities::new(D65, (0.680, 0.320), (0.265, 0.690), (0.150, 0.060)),
+        ),
+        ColorSpace::Rec2020 => (
+            9,
+            png::SourceChromaticities::new(D65, (0.708, 0.292), (0.170, 0.797), (0.131, 0.046)),
+        ),
+    };
+    Some(([primaries, SRGB_TRANSFER, 0, 1], chromaticities))
+}
+
+/// Reads the seed embedded by [`write_rgba_png_with_seed`] from a PNG file.
+///
+/// Returns `Ok(None)` if the PNG has no seed chunk, or `EngineError::Io` if
+/// the file cannot be read or the chunk is not valid seed JSON.
+pub fn read_png_seed(path: &Path) -> Result<Option<Seed>, EngineError> {
+    let file = File::open(path).map_err(|e| EngineError::Io(e.to_string()))?;
+    let reader = png::Decoder::new(BufReader::new(file))
+        .read_info()
+        .map_err(|e| EngineError::Io(e.to_string()))?;
+    let info = reader.info();
+
+    let text = info
+        .utf8_text
+        .iter()
+        .find(|chunk| chunk.keyword == SEED_KEYWORD)
+        .map(|chunk| chunk.get_text())
+        .transpose()
+        .map_err(|e| EngineError::Io(e.to_string()))?
+        .or_else(|| {
+            info.uncompressed_latin1_text
+                .iter()
+                .find(|chunk| chunk.keyword == SEED_KEYWORD)
+                .map(|chunk| chunk.text.clone())
+        });
+
+    text.map(|json| {
+        serde_json::from_str(&json)
+            .map_err(|e| EngineError::Io(format!("invalid seed metadata: {e}")))
+    })
+    .transpose()
+}
+
+/// Reads a PNG of any color type as an RGBA8 buffer with its width and height.
+///
+/// Returns `EngineError::Io` if the file cannot be read or decoded.
+pub fn read_rgba_png(path: &Path) -> Result<(Vec<u8>, usize, usize), EngineError> {
+    let img = image::open(path)
+        .map_err(|e| EngineError::Io(format!("{}: {e}", path.display())))?
+        .to_rgba8();
+    let (w, h) = (img.width() as usize, img.height() as usize);
+    Ok((img.into_raw(), w, h))
+}
+
+/// Saves an RGBA8 buffer with the field's dimensions as a PNG.
+fn save_rgba(rgba: Vec<u8>, field: &Field, path: &Path) -> Result<(), EngineError> {
+    write_rgba_png(rgba, field.width(), field.height(), path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use art_engine_core::field::Field;
     use art_engine_core::palette::Palette;
 
+    #[test]
+    fn read_rgba_png_returns_written_pixels() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rgba.png");
+        let rgba = vec![1, 2, 3, 255, 10, 20, 30, 40];
+        write_rgba_png(rgba.clone(), 2, 1, &path).unwrap();
+        assert_eq!(read_rgba_png(&path).unwrap(), (rgba, 2, 1));
+        assert!(read_rgba_png(&dir.path().join("missing.png")).is_err());
+    }
+
     #[test]
     fn write_png_round_trip() {
         let field = Field::filled(16, 16, 0.3).unwrap();
@@ -44,4 +309,172 @@ mod tests {
         assert_eq!(img.width(), 16);
         assert_eq!(img.height(), 16);
     }
+
+    #[test]
+    fn write_png_with_alpha_preserves_transparency() {
+        let field = Field::filled(4, 4, 0.3).unwrap();
+        let alpha = Field::filled(4, 4, 0.0).unwrap();
+        let palette = Palette::ocean();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alpha.png");
+
+        write_png_with_alpha(&field, &palette, Some(&alpha), &path).unwrap();
+
+        let img = image::open(&path).unwrap().to_rgba8();
+        assert!(img.pixels().all(|p| p.0[3] == 0));
+    }
+
+    #[test]
+    fn write_svg_writes_svg_document() {
+        let field = Field::filled(8, 8, 0.7).unwrap();
+        let palette = Palette::ocean();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.svg");
+
+        write_svg(&field, &palette, &[0.5], &path).unwrap();
+
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("<path "));
+    }
+
+    #[test]
+    fn write_svg_to_missing_directory_is_io_error() {
+        let field = Field::filled(4, 4, 0.7).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("test.svg");
+        let err = write_svg(&field, &Palette::ocean(), &[0.5], &path).unwrap_err();
+        assert!(matches!(err, EngineError::Io(_)));
+    }
+
+    #[test]
+    fn write_rgba_png_rejects_wrong_buffer_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("short.png");
+        let err = write_rgba_png(vec![0; 12], 2, 2, &path).unwrap_err();
+        assert!(matches!(err, EngineError::Io(_)));
+    }
+
+    // -- Seed metadata --
+
+    #[test]
+    fn seed_metadata_round_trip() {
+        let field = Field::filled(4, 3, 0.5).unwrap();
+        let palette = Palette::ocean();
+        let mut seed = Seed::new("gray-scott", 4, 3, 42);
+        seed.steps = 10;
+        seed.palette = Some("ocean".into());
+        seed.params = serde_json::json!({"feed_rate": 0.055, "label": "ünïcode"});
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seed.png");
+
+        let rgba = field_to_rgba(&field, &palette);
+        write_rgba_png_with_seed(&rgba, 4, 3, &seed, &path).unwrap();
+
+        assert_eq!(read_png_seed(&path).unwrap(), Some(seed.clone()));
+        assert_eq!(
+            encode_rgba_png_with_seed(&rgba, 4, 3, &seed).unwrap(),
+            std::fs::read(&path).unwrap()
+        );
+        // Pixels are still readable by ordinary decoders
+        let img = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(img.as_raw(), &rgba);
+    }
+
+    #[test]
+    fn wide_gamut_seeds_tag_the_png() {
+        let rgba = vec![200, 10, 20, 255];
+        let decode = |seed: &Seed| {
+            let bytes = encode_rgba_png_with_seed(&rgba, 1, 1, seed).unwrap();
+            let reader = png::Decoder::new(std::io::Cursor::new(bytes))
+                .read_info()
+                .unwrap();
+            let info = reader.info();
+            (
+                info.coding_independent_code_points
+                    .map(|c| (c.color_primaries, c.transfer_function)),
+                info.chrm_chunk.is_some(),
+            )
+        };
+        let seed = Seed::new("gray-scott", 1, 1, 1);
+        assert_eq!(decode(&seed), (None, false));
+        let p3 = Seed {
+            color_space: ColorSpace::DisplayP3,
+            ..seed.clone()
+        };
+        assert_eq!(decode(&p3), (Some((12, 13)), true));
+        let rec2020 = Seed {
+            color_space: ColorSpace::Rec2020,
+            ..seed
+        };
+        assert_eq!(decode(&rec2020), (Some((9, 13)), true));
+    }
+
+    #[test]
+    fn seedless_pngs_carry_the_color_space_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frame.png");
+        let rgba = vec![200, 10, 20, 255];
+        write_rgba_png_in(&rgba, 1, 1, ColorSpace::DisplayP3, &path).unwrap();
+        let reader = png::Decoder::new(BufReader::new(File::open(&path).unwrap()))
+            .read_info()
+            .unwrap();
+        let cicp = reader.info().coding_independent_code_points.unwrap();
+        assert_eq!(cicp.color_primaries, 12);
+        assert_eq!(read_png_seed(&path).unwrap(), None);
+        assert_eq!(image::open(&path).unwrap().to_rgba8().as_raw(), &rgba);
+    }
+
+    #[test]
+    fn read_png_seed_returns_none_without_metadata() {
+        let field = Field::filled(4, 4, 0.5).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.png");
+        write_png(&field, &Palette::ocean(), &path).unwrap();
+        assert_eq!(read_png_seed(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn read_png_seed_on_missing_file_is_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = read_png_seed(&dir.path().join("nope.png")).unwrap_err();
+        assert!(matches!(err, EngineError::Io(_)));
+    }
+
+    #[test]
+    fn write_rgba_png_with_seed_rejects_wrong_buffer_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = Seed::new("gray-scott", 2, 2, 1);
+        let err =
+            write_rgba_png_with_seed(&[0; 8], 2, 2, &seed, &dir.path().join("x.png")).unwrap_err();
+        assert!(matches!(err, EngineError::Io(_)));
+    }
+
+    // -- Heightmap / normal map --
+
+    #[test]
+    fn write_heightmap_png_is_16_bit_grayscale() {
+        let field = Field::from_data(2, 1, vec![0.0, 1.0]).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("height.png");
+
+        write_heightmap_png(&field, &path).unwrap();
+
+        let img = image::open(&path).unwrap();
+        assert_eq!(img.color(), image::ColorType::L16);
+        let luma = img.to_luma16();
+        assert_eq!(luma.as_raw(), &vec![0, 65535]);
+    }
+
+    #[test]
+    fn write_normal_map_png_round_trip() {
+        let field = Field::filled(8, 8, 0.4).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("normal.png");
+
+        write_normal_map_png(&field, 2.0, &path).unwrap();
+
+        let img = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(img.get_pixel(3, 3).0, [128, 128, 255, 255]);
+    }
 }
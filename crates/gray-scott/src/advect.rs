@@ -0,0 +1,208 @@
+//! Semi-Lagrangian advection: carries U and V along a steady velocity field.
+//!
+//! Each cell traces the flow backwards for one time step and takes the
+//! bilinearly interpolated value found there, wrapping toroidally. The
+//! backtrace is unconditionally stable, so strong winds smear patterns
+//! rather than blowing up; it does add a little numerical diffusion.
+
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::field_source::{rasterize_vectors, FieldSourceSpec};
+
+use crate::Real;
+
+/// A velocity field, in cells per unit time, sampled once from its spec.
+pub(crate) struct Flow {
+    /// The spec the velocities were sampled from, echoed in `params()`.
+    pub(crate) spec: FieldSourceSpec,
+    dx: Field,
+    dy: Field,
+}
+
+impl Flow {
+    /// Samples `spec` at the cell centres of a `width`×`height` grid.
+    ///
+    /// A noise spec without a `period` tiles at the grid size, so the wind
+    /// wraps with the toroidal edges instead of tearing along them.
+    /// Non-finite velocities are treated as still air.
+    pub(crate) fn new(
+        mut spec: FieldSourceSpec,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, EngineError> {
+        tile(&mut spec, width, height);
+        let (mut dx, mut dy) = rasterize_vectors(spec.build().as_ref(), width, height, 0.0)?;
+        for (vx, vy) in dx.data_mut().iter_mut().zip(dy.data_mut().iter_mut()) {
+            if !(vx.is_finite() && vy.is_finite()) {
+                (*vx, *vy) = (0.0, 0.0);
+            }
+        }
+        Ok(Self { spec, dx, dy })
+    }
+
+    /// Returns `data` carried along the flow for time `dt`.
+    pub(crate) fn advect<T: Real>(&self, data: &[T], w: usize, h: usize, dt: f64) -> Vec<T> {
+        advect_grid(data, (self.dx.data(), self.dy.data()), w, h, dt)
+    }
+}
+
+/// Gives a top-level noise spec without a period the grid's size as one.
+fn tile(spec: &mut FieldSourceSpec, width: usize, height: usize) {
+    match spec {
+        FieldSourceSpec::Perlin { period, .. }
+        | FieldSourceSpec::Simplex { period, .. }
+        | FieldSourceSpec::Curl { period, .. }
+        | FieldSourceSpec::Worley { period, .. }
+        | FieldSourceSpec::Turbulence { period, .. }
+        | FieldSourceSpec::Fbm { period, .. }
+        | FieldSourceSpec::Ridged { period, .. } => {
+            period.get_or_insert([width as f64, height as f64]);
+        }
+        _ => {}
+    }
+}
+
+/// Moves every cell of `data` along `velocity` (x and y components) for
+/// `dt`.
+pub(crate) fn advect_grid<T: Real>(
+    data: &[T],
+    velocity: (&[f64], &[f64]),
+    w: usize,
+    h: usize,
+    dt: f64,
+) -> Vec<T> {
+    let mut next = vec![T::from_f64(0.0); w * h];
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        next.par_chunks_mut(w)
+            .enumerate()
+            .for_each(|(y, row)| advect_row(data, velocity, w, h, y, dt, row));
+    }
+    #[cfg(not(feature = "parallel"))]
+    for (y, row) in next.chunks_mut(w).enumerate() {
+        advect_row(data, velocity, w, h, y, dt, row);
+    }
+
+    next
+}
+
+/// Computes row `y` of the advected grid; rows are independent.
+fn advect_row<T: Real>(
+    data: &[T],
+    (vx, vy): (&[f64], &[f64]),
+    w: usize,
+    h: usize,
+    y: usize,
+    dt: f64,
+    row: &mut [T],
+) {
+    for (x, out) in row.iter_mut().enumerate() {
+        let idx = y * w + x;
+        let sx = x as f64 - dt * vx[idx];
+        let sy = y as f64 - dt * vy[idx];
+        *out = sample_bilinear(data, w, h, sx, sy);
+    }
+}
+
+/// Bilinearly interpolates `data` at (`x`, `y`) in cell coordinates,
+/// wrapping toroidally.
+fn sample_bilinear<T: Real>(data: &[T], w: usize, h: usize, x: f64, y: f64) -> T {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (T::from_f64(x - x0), T::from_f64(y - y0));
+    let one = T::from_f64(1.0);
+    let col = |c: f64| (c.rem_euclid(w as f64) as usize).min(w - 1);
+    let line = |r: f64| (r.rem_euclid(h as f64) as usize).min(h - 1);
+    let (xa, xb) = (col(x0), col(x0 + 1.0));
+    let (ya, yb) = (line(y0), line(y0 + 1.0));
+
+    let top = data[ya * w + xa] * (one - fx) + data[ya * w + xb] * fx;
+    let bottom = data[yb * w + xa] * (one - fx) + data[yb * w + xb] * fx;
+    top * (one - fy) + bottom * fy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(w: usize, h: usize) -> Vec<f64> {
+        (0..w * h).map(|i| i as f64 / (w * h) as f64).collect()
+    }
+
+    // ---- Flow tests ----
+
+    #[test]
+    fn noise_specs_tile_at_the_grid_size_unless_given_a_period() {
+        let spec = |period: Option<[f64; 2]>| FieldSourceSpec::Curl {
+            scale: 0.1,
+            strength: 1.0,
+            seed: 3,
+            period,
+        };
+        assert_eq!(
+            Flow::new(spec(None), 12, 8).unwrap().spec,
+            spec(Some([12.0, 8.0]))
+        );
+        let given = spec(Some([6.0, 4.0]));
+        assert_eq!(Flow::new(given.clone(), 12, 8).unwrap().spec, given);
+
+        let flow = Flow::new(spec(None), 12, 8).unwrap();
+        let (dx, dy) = (flow.dx.data(), flow.dy.data());
+        assert!(dx.iter().chain(dy).all(|v| v.is_finite()));
+        assert!(dx.iter().any(|&v| v != 0.0));
+    }
+
+    // ---- Advection tests ----
+
+    #[test]
+    fn still_air_leaves_the_grid_unchanged() {
+        let data = ramp(8, 6);
+        let still = vec![0.0; 48];
+        assert_eq!(advect_grid(&data, (&still, &still), 8, 6, 1.0), data);
+    }
+
+    #[test]
+    fn whole_cell_flow_shifts_and_wraps() {
+        let (w, h) = (8, 6);
+        let data = ramp(w, h);
+        let (east, south) = (vec![2.0; w * h], vec![1.0; w * h]);
+        let moved = advect_grid(&data, (&east, &south), w, h, 1.0);
+        for y in 0..h {
+            for x in 0..w {
+                let from = ((y + h - 1) % h) * w + (x + w - 2) % w;
+                assert_eq!(moved[y * w + x], data[from], "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn half_cell_flow_averages_neighbours() {
+        let data = vec![0.0, 1.0, 0.0, 1.0];
+        let (half, still) = (vec![0.5; 4], vec![0.0; 4]);
+        let moved = advect_grid(&data, (&half, &still), 4, 1, 1.0);
+        assert_eq!(moved, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn dt_scales_the_displacement() {
+        let data = ramp(8, 1);
+        let (one, still) = (vec![1.0; 8], vec![0.0; 8]);
+        let doubled = advect_grid(&data, (&one, &still), 8, 1, 2.0);
+        let once = advect_grid(&data, (&one, &still), 8, 1, 1.0);
+        let twice = advect_grid(&once, (&one, &still), 8, 1, 1.0);
+        assert_eq!(doubled, twice);
+    }
+
+    #[test]
+    fn f32_matches_f64_for_whole_cell_shifts() {
+        let wide = ramp(4, 4);
+        let narrow: Vec<f32> = wide.iter().map(|&c| c as f32).collect();
+        let (vx, vy) = (vec![-1.0; 16], vec![3.0; 16]);
+        let moved_wide = advect_grid(&wide, (&vx, &vy), 4, 4, 1.0);
+        let moved_narrow = advect_grid(&narrow, (&vx, &vy), 4, 4, 1.0);
+        for (a, b) in moved_wide.iter().zip(&moved_narrow) {
+            assert_eq!(*a as f32, *b);
+        }
+    }
+}
@@ -8,14 +8,41 @@
 //!
 //! The primary output field is the V (activator) concentration, which the
 //! rendering pipeline maps to pixels via a palette.
+//!
+//! With the `parallel` feature each step updates rows across threads with
+//! rayon. Every cell is computed from the previous step alone, with the
+//! same floating-point operations in the same order, so results are
+//! bit-identical to a single-threaded build for any thread count.
+//!
+//! The `precision` param selects `f64` (the default) or `f32` state. `f32`
+//! halves the memory the simulation holds and is kinder to caches on very
+//! large grids; its patterns match `f64` closely but not bit for bit, so
+//! seeds keep `f64` unless they ask otherwise.
+//!
+//! [`GrayScott::with_velocity`] adds advection: after each reaction step U
+//! and V are carried along a steady velocity field built from a
+//! [`FieldSourceSpec`], stretching spots into streaks and labyrinths into
+//! wind-blown coral. The registry exposes it as `gray-scott-advected`,
+//! which reads the spec from the `velocity` param.
+
+use std::ops::{Add, Mul, Sub};
+use std::sync::OnceLock;
 
+use art_engine_core::budget::grid_bytes;
 use art_engine_core::error::EngineError;
 use art_engine_core::field::Field;
-use art_engine_core::params::param_f64;
-use art_engine_core::prng::Xorshift64;
-use art_engine_core::Engine;
+use art_engine_core::field_source::FieldSourceSpec;
+use art_engine_core::params::{
+    check_fixed, merge_params, param_f64, param_precision, param_rng, Precision,
+};
+use art_engine_core::prng::{Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
 use serde_json::{json, Value};
 
+use advect::Flow;
+
+mod advect;
+
 /// Default feed rate — controls how fast U is replenished.
 const DEFAULT_FEED_RATE: f64 = 0.055;
 /// Default kill rate — controls how fast V is removed.
@@ -26,6 +53,27 @@ const DEFAULT_DIFFUSION_A: f64 = 1.0;
 const DEFAULT_DIFFUSION_B: f64 = 0.5;
 /// Default time step per `step()` call.
 const DEFAULT_DT: f64 = 1.0;
+/// Well-known (name, feed rate, kill rate) regimes for the default
+/// diffusion constants.
+const PRESETS: &[(&str, f64, f64)] = &[
+    ("coral", 0.0545, 0.062),
+    ("mitosis", 0.0367, 0.0649),
+    ("maze", 0.029, 0.057),
+    ("worms", 0.078, 0.061),
+    ("holes", 0.039, 0.058),
+    ("chaos", 0.026, 0.051),
+];
+/// Well-known (name, feed rate, kill rate, curl scale, curl strength)
+/// regimes for the advected variant.
+const ADVECTED_PRESETS: &[(&str, f64, f64, f64, f64)] = &[
+    ("windblown-coral", 0.0545, 0.062, 0.01, 0.2),
+    ("stretched-maze", 0.029, 0.057, 0.02, 0.15),
+    ("comet-spots", 0.0367, 0.0649, 0.005, 0.1),
+];
+/// Noise scale of the default velocity: a few large eddies per canvas.
+const DEFAULT_VELOCITY_SCALE: f64 = 0.01;
+/// Strength of the default velocity, roughly cells per step at its peak.
+const DEFAULT_VELOCITY_STRENGTH: f64 = 0.2;
 /// Spot radius in cells for initial V seeding.
 const SPOT_RADIUS: isize = 3;
 /// Fraction of total area used to determine spot count.
@@ -33,8 +81,9 @@ const SPOT_DENSITY: f64 = 0.0005;
 
 /// Simulation parameters for the Gray-Scott model.
 ///
-/// Bundles the five tunable constants that control pattern formation.
-/// Use [`Default`] for the classic coral parameters (F=0.055, k=0.062).
+/// Bundles the five tunable constants that control pattern formation, plus
+/// the PRNG that places the initial spots. Use [`Default`] for the classic
+/// coral parameters (F=0.055, k=0.062) seeded by Xorshift64.
 #[derive(Debug, Clone, Copy)]
 pub struct GrayScottParams {
     /// Feed rate (F): how fast substrate U is replenished.
@@ -47,6 +96,10 @@ pub struct GrayScottParams {
     pub diffusion_b: f64,
     /// Time step per `step()` call.
     pub dt: f64,
+    /// PRNG algorithm placing the initial V spots.
+    pub rng: RngAlgorithm,
+    /// Floating-point type of the simulation state.
+    pub precision: Precision,
 }
 
 impl Default for GrayScottParams {
@@ -57,6 +110,8 @@ impl Default for GrayScottParams {
             diffusion_a: DEFAULT_DIFFUSION_A,
             diffusion_b: DEFAULT_DIFFUSION_B,
             dt: DEFAULT_DT,
+            rng: RngAlgorithm::default(),
+            precision: Precision::default(),
         }
     }
 }
@@ -70,6 +125,8 @@ impl GrayScottParams {
             diffusion_a: param_f64(params, "diffusion_a", DEFAULT_DIFFUSION_A),
             diffusion_b: param_f64(params, "diffusion_b", DEFAULT_DIFFUSION_B),
             dt: param_f64(params, "dt", DEFAULT_DT),
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+            precision: param_precision(params, "precision", Precision::default()),
         }
     }
 }
@@ -84,16 +141,88 @@ impl GrayScottParams {
 /// Uses a 9-point Laplacian stencil for isotropic diffusion and explicit
 /// Euler integration.
 pub struct GrayScott {
-    u: Field,
-    v: Field,
+    state: State,
     params: GrayScottParams,
+    /// Velocity carrying U and V each step, if advected. Boxed: most
+    /// engines are not, and the spec is large.
+    flow: Option<Box<Flow>>,
+}
+
+/// U and V concentrations, stored at the engine's precision.
+enum State {
+    /// The fields are the state.
+    F64 { u: Field, v: Field },
+    /// Single-precision state. V, the rendered field, is widened into
+    /// `v_field` after each step; U only on the first read after a step,
+    /// into a copy of `v_field`'s shape.
+    F32 {
+        u: Vec<f32>,
+        v: Vec<f32>,
+        u_field: OnceLock<Field>,
+        v_field: Field,
+    },
+}
+
+impl State {
+    /// Stores `u` and `v` at `precision`.
+    fn new(u: Field, v: Field, precision: Precision) -> Self {
+        match precision {
+            Precision::F64 => State::F64 { u, v },
+            Precision::F32 => {
+                let narrow = |f: &Field| f.data().iter().map(|&c| c as f32).collect::<Vec<_>>();
+                let (u, narrow_v) = (narrow(&u), narrow(&v));
+                // Reuse V's allocation, rounded like the state it mirrors
+                let mut v_field = v;
+                widen_into(&mut v_field, &narrow_v);
+                State::F32 {
+                    u,
+                    v: narrow_v,
+                    u_field: OnceLock::new(),
+                    v_field,
+                }
+            }
+        }
+    }
+
+    fn u(&self) -> &Field {
+        match self {
+            State::F64 { u, .. } => u,
+            State::F32 {
+                u,
+                u_field,
+                v_field,
+                ..
+            } => u_field.get_or_init(|| {
+                let mut field = v_field.clone();
+                widen_into(&mut field, u);
+                field
+            }),
+        }
+    }
+
+    fn v(&self) -> &Field {
+        match self {
+            State::F64 { v, .. } => v,
+            State::F32 { v_field, .. } => v_field,
+        }
+    }
+}
+
+/// Copies single-precision state into a field of the same size (exactly).
+fn widen_into(field: &mut Field, data: &[f32]) {
+    field
+        .data_mut()
+        .iter_mut()
+        .zip(data)
+        .for_each(|(cell, &c)| *cell = f64::from(c));
 }
 
 impl GrayScott {
     /// Creates a new Gray-Scott engine.
     ///
     /// U is initialized to 1.0 everywhere. V is initialized to 0.0 with
-    /// circular spots of V=1.0 seeded at random positions (determined by `seed`).
+    /// circular spots of V=1.0 seeded at random positions (determined by `seed`
+    /// and the params' PRNG algorithm).
     /// Spot count scales with grid area.
     ///
     /// Returns `EngineError::InvalidDimensions` if width or height is zero.
@@ -105,15 +234,30 @@ impl GrayScott {
     ) -> Result<Self, EngineError> {
         let u = Field::filled(width, height, 1.0)?;
         let mut v = Field::new(width, height)?;
-        let mut rng = Xorshift64::new(seed);
-        seed_initial_spots(&mut v, &mut rng, width, height);
-        Ok(Self { u, v, params })
+        let mut rng = params.rng.build(seed);
+        seed_initial_spots(&mut v, rng.as_mut(), width, height);
+        Ok(Self {
+            state: State::new(u, v, params.precision),
+            params,
+            flow: None,
+        })
+    }
+
+    /// Returns this engine advected by `velocity`, in cells per unit time.
+    ///
+    /// The spec is sampled once at the cell centres; noise kinds without a
+    /// `period` tile at the grid size so the flow wraps with the torus.
+    pub fn with_velocity(mut self, velocity: FieldSourceSpec) -> Result<Self, EngineError> {
+        let (width, height) = (self.u_field().width(), self.u_field().height());
+        self.flow = Some(Box::new(Flow::new(velocity, width, height)?));
+        Ok(self)
     }
 
     /// Creates a Gray-Scott engine from a JSON params object.
     ///
-    /// Extracts `feed_rate`, `kill_rate`, `diffusion_a`, `diffusion_b`, and `dt`
-    /// from the JSON, falling back to defaults for missing keys.
+    /// Extracts `feed_rate`, `kill_rate`, `diffusion_a`, `diffusion_b`, `dt`,
+    /// `rng` and `precision` from the JSON, falling back to defaults for
+    /// missing keys.
     pub fn from_json(
         width: usize,
         height: usize,
@@ -123,14 +267,57 @@ impl GrayScott {
         Self::new(width, height, seed, GrayScottParams::from_json(json_params))
     }
 
+    /// Creates an advected Gray-Scott engine from a JSON params object.
+    ///
+    /// Reads the usual params plus `velocity`, a field-source spec; without
+    /// one the wind is a gentle curl-noise flow.
+    ///
+    /// Returns `EngineError::InvalidFieldSource` if `velocity` is not a
+    /// field-source spec.
+    pub fn advected_from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        let velocity = match json_params.get("velocity") {
+            Some(spec) => serde_json::from_value(spec.clone())
+                .map_err(|e| EngineError::InvalidFieldSource(e.to_string()))?,
+            None => default_velocity(),
+        };
+        Self::from_json(width, height, seed, json_params)?.with_velocity(velocity)
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak.
+    ///
+    /// Each step builds the next U and V beside the current ones; `f32`
+    /// state also keeps V widened into a field for reading.
+    pub fn memory_estimate(width: usize, height: usize, params: &GrayScottParams) -> u64 {
+        let bytes_per_cell = match params.precision {
+            Precision::F64 => 4 * 8,
+            Precision::F32 => 4 * 4 + 8,
+        };
+        grid_bytes(width, height, bytes_per_cell)
+    }
+
+    /// Like [`GrayScott::memory_estimate`], plus the velocity field the
+    /// advected variant holds.
+    pub fn advected_memory_estimate(width: usize, height: usize, params: &GrayScottParams) -> u64 {
+        Self::memory_estimate(width, height, params).saturating_add(grid_bytes(
+            width,
+            height,
+            2 * 8,
+        ))
+    }
+
     /// Read-only access to the U (substrate) field.
     pub fn u_field(&self) -> &Field {
-        &self.u
+        self.state.u()
     }
 
     /// Read-only access to the V (activator) field.
     pub fn v_field(&self) -> &Field {
-        &self.v
+        self.state.v()
     }
 
     /// Current feed rate (F).
@@ -146,59 +333,74 @@ impl GrayScott {
 
 impl Engine for GrayScott {
     fn step(&mut self) -> Result<(), EngineError> {
-        let w = self.u.width();
-        let h = self.u.height();
-        let u_data = self.u.data();
-        let v_data = self.v.data();
-
-        let len = w * h;
-        let mut u_next = vec![0.0_f64; len];
-        let mut v_next = vec![0.0_f64; len];
-
-        let f = self.params.feed_rate;
-        let k = self.params.kill_rate;
-        let du = self.params.diffusion_a;
-        let dv = self.params.diffusion_b;
-        let dt = self.params.dt;
-
-        for y in 0..h {
-            for x in 0..w {
-                let idx = y * w + x;
-                let u = u_data[idx];
-                let v = v_data[idx];
-
-                let lap_u = laplacian_9pt(u_data, x, y, w, h);
-                let lap_v = laplacian_9pt(v_data, x, y, w, h);
-
-                let reaction = u * v * v;
-
-                u_next[idx] = (u + dt * (du * lap_u - reaction + f * (1.0 - u))).clamp(0.0, 1.0);
-                v_next[idx] = (v + dt * (dv * lap_v + reaction - (f + k) * v)).clamp(0.0, 1.0);
+        match &mut self.state {
+            State::F64 { u, v } => {
+                let (w, h) = (u.width(), u.height());
+                let (mut u_next, mut v_next) = step_grid(u.data(), v.data(), w, h, &self.params);
+                if let Some(flow) = &self.flow {
+                    u_next = flow.advect(&u_next, w, h, self.params.dt);
+                    v_next = flow.advect(&v_next, w, h, self.params.dt);
+                }
+                u.data_mut().copy_from_slice(&u_next);
+                v.data_mut().copy_from_slice(&v_next);
+            }
+            State::F32 {
+                u,
+                v,
+                u_field,
+                v_field,
+            } => {
+                let (w, h) = (v_field.width(), v_field.height());
+                (*u, *v) = step_grid(u, v, w, h, &self.params);
+                if let Some(flow) = &self.flow {
+                    *u = flow.advect(u, w, h, self.params.dt);
+                    *v = flow.advect(v, w, h, self.params.dt);
+                }
+                u_field.take();
+                widen_into(v_field, v);
             }
         }
-
-        self.u.data_mut().copy_from_slice(&u_next);
-        self.v.data_mut().copy_from_slice(&v_next);
-
         Ok(())
     }
 
     fn field(&self) -> &Field {
-        &self.v
+        self.state.v()
     }
 
     fn params(&self) -> Value {
-        json!({
+        let mut params = json!({
             "feed_rate": self.params.feed_rate,
             "kill_rate": self.params.kill_rate,
             "diffusion_a": self.params.diffusion_a,
             "diffusion_b": self.params.diffusion_b,
             "dt": self.params.dt,
-        })
+            "rng": self.params.rng.name(),
+            "precision": self.params.precision.name(),
+        });
+        if let Some(flow) = &self.flow {
+            params["velocity"] = json!(flow.spec);
+        }
+        params
+    }
+
+    /// Rates, `dt` and (for the advected variant) `velocity` can change
+    /// mid-run; `rng` and `precision` are fixed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["rng", "precision"])?;
+        let next = GrayScottParams::from_json(&merge_params(&current, params)?);
+        if let (Some(flow), Some(spec)) = (&mut self.flow, params.get("velocity")) {
+            let spec = serde_json::from_value(spec.clone())
+                .map_err(|e| EngineError::InvalidFieldSource(e.to_string()))?;
+            let (width, height) = (self.state.u().width(), self.state.u().height());
+            **flow = Flow::new(spec, width, height)?;
+        }
+        self.params = next;
+        Ok(())
     }
 
     fn param_schema(&self) -> Value {
-        json!({
+        let mut schema = json!({
             "feed_rate": {
                 "type": "number",
                 "default": DEFAULT_FEED_RATE,
@@ -233,8 +435,86 @@ impl Engine for GrayScott {
                 "min": 0.0,
                 "max": 2.0,
                 "description": "Time step per step() call"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG placing the initial spots: xorshift64, pcg32 or splitmix64"
+            },
+            "precision": {
+                "type": "string",
+                "default": Precision::default().name(),
+                "options": Precision::ALL.map(Precision::name),
+                "description": "Simulation precision: f64, or f32 for half the memory on large grids"
             }
-        })
+        });
+        if self.flow.is_some() {
+            schema["velocity"] = json!({
+                "type": "object",
+                "default": default_velocity(),
+                "description": "Field-source spec for the wind carrying U and V, in cells per unit time"
+            });
+        }
+        schema
+    }
+
+    fn presets(&self) -> Value {
+        if self.flow.is_some() {
+            return ADVECTED_PRESETS
+                .iter()
+                .map(|&(name, feed_rate, kill_rate, scale, strength)| {
+                    let velocity = FieldSourceSpec::Curl {
+                        scale,
+                        strength,
+                        seed: 0,
+                        period: None,
+                    };
+                    let params = json!({
+                        "feed_rate": feed_rate,
+                        "kill_rate": kill_rate,
+                        "velocity": velocity,
+                    });
+                    (name.to_string(), params)
+                })
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        PRESETS
+            .iter()
+            .map(|&(name, feed_rate, kill_rate)| {
+                let params = json!({ "feed_rate": feed_rate, "kill_rate": kill_rate });
+                (name.to_string(), params)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.field(self.u_field()).field(self.v_field());
+        Ok(writer.finish())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let (w, h) = (self.u_field().width(), self.u_field().height());
+        let mut reader = StateReader::new(state);
+        let u = reader.field(w, h)?;
+        let v = reader.field(w, h)?;
+        reader.finish()?;
+        self.state = State::new(u, v, self.params.precision);
+        Ok(())
+    }
+}
+
+/// The advected variant's wind when none is given: a curl-noise flow,
+/// which is divergence-free so it shears patterns without piling them up.
+fn default_velocity() -> FieldSourceSpec {
+    FieldSourceSpec::Curl {
+        scale: DEFAULT_VELOCITY_SCALE,
+        strength: DEFAULT_VELOCITY_STRENGTH,
+        seed: 0,
+        period: None,
     }
 }
 
@@ -243,7 +523,7 @@ impl Engine for GrayScott {
 /// Spot count scales with grid area: `(w * h) as f64 * SPOT_DENSITY`, minimum 1.
 /// Each spot is a filled circle of radius [`SPOT_RADIUS`]. Uses `Field::set()`
 /// which handles toroidal wrapping for spots near edges.
-fn seed_initial_spots(v: &mut Field, rng: &mut Xorshift64, width: usize, height: usize) {
+fn seed_initial_spots(v: &mut Field, rng: &mut dyn Rng, width: usize, height: usize) {
     let spot_count = ((width * height) as f64 * SPOT_DENSITY).ceil().max(1.0) as usize;
     let r = SPOT_RADIUS;
 
@@ -260,6 +540,95 @@ fn seed_initial_spots(v: &mut Field, rng: &mut Xorshift64, width: usize, height:
     }
 }
 
+/// Floating-point type the simulation runs in.
+trait Real: Copy + Send + Sync + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    fn from_f64(v: f64) -> Self;
+    fn clamp_unit(self) -> Self;
+}
+
+impl Real for f64 {
+    fn from_f64(v: f64) -> Self {
+        v
+    }
+
+    fn clamp_unit(self) -> Self {
+        self.clamp(0.0, 1.0)
+    }
+}
+
+impl Real for f32 {
+    fn from_f64(v: f64) -> Self {
+        v as f32
+    }
+
+    fn clamp_unit(self) -> Self {
+        self.clamp(0.0, 1.0)
+    }
+}
+
+/// Advances U and V by one explicit Euler step, returning the next grids.
+fn step_grid<T: Real>(
+    u: &[T],
+    v: &[T],
+    w: usize,
+    h: usize,
+    params: &GrayScottParams,
+) -> (Vec<T>, Vec<T>) {
+    let mut u_next = vec![T::from_f64(0.0); w * h];
+    let mut v_next = vec![T::from_f64(0.0); w * h];
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        u_next
+            .par_chunks_mut(w)
+            .zip(v_next.par_chunks_mut(w))
+            .enumerate()
+            .for_each(|(y, rows)| step_row(u, v, w, h, y, params, rows));
+    }
+    #[cfg(not(feature = "parallel"))]
+    for (y, rows) in u_next.chunks_mut(w).zip(v_next.chunks_mut(w)).enumerate() {
+        step_row(u, v, w, h, y, params, rows);
+    }
+
+    (u_next, v_next)
+}
+
+/// Computes row `y` of the next U and V grids from the current ones.
+///
+/// Each cell reads only the current grids, so rows are independent and the
+/// result is the same whether they run in order or across threads.
+fn step_row<T: Real>(
+    u_data: &[T],
+    v_data: &[T],
+    w: usize,
+    h: usize,
+    y: usize,
+    params: &GrayScottParams,
+    (u_row, v_row): (&mut [T], &mut [T]),
+) {
+    let f = T::from_f64(params.feed_rate);
+    let k = T::from_f64(params.kill_rate);
+    let du = T::from_f64(params.diffusion_a);
+    let dv = T::from_f64(params.diffusion_b);
+    let dt = T::from_f64(params.dt);
+    let one = T::from_f64(1.0);
+
+    for x in 0..w {
+        let idx = y * w + x;
+        let u = u_data[idx];
+        let v = v_data[idx];
+
+        let lap_u = laplacian_9pt(u_data, x, y, w, h);
+        let lap_v = laplacian_9pt(v_data, x, y, w, h);
+
+        let reaction = u * v * v;
+
+        u_row[x] = (u + dt * (du * lap_u - reaction + f * (one - u))).clamp_unit();
+        v_row[x] = (v + dt * (dv * lap_v + reaction - (f + k) * v)).clamp_unit();
+    }
+}
+
 /// 9-point Laplacian stencil for isotropic diffusion.
 ///
 /// Kernel weights:
@@ -271,7 +640,7 @@ fn seed_initial_spots(v: &mut Field, rng: &mut Xorshift64, width: usize, height:
 ///
 /// Operates on raw data slice with explicit toroidal coordinate wrapping
 /// for performance (avoids `Field::get()` per-access overhead in hot loop).
-fn laplacian_9pt(data: &[f64], x: usize, y: usize, w: usize, h: usize) -> f64 {
+fn laplacian_9pt<T: Real>(data: &[T], x: usize, y: usize, w: usize, h: usize) -> T {
     let xm = wrap(x, -1, w);
     let xp = wrap(x, 1, w);
     let ym = wrap(y, -1, h);
@@ -291,7 +660,7 @@ fn laplacian_9pt(data: &[f64], x: usize, y: usize, w: usize, h: usize) -> f64 {
     let sw = data[yp * w + xm];
     let se = data[yp * w + xp];
 
-    0.2 * (n + s + we + e) + 0.05 * (nw + ne + sw + se) - center
+    T::from_f64(0.2) * (n + s + we + e) + T::from_f64(0.05) * (nw + ne + sw + se) - center
 }
 
 /// Toroidal coordinate wrap: `(coord + offset) mod size`.
@@ -313,6 +682,13 @@ mod tests {
         GrayScott::new(width, height, seed, default_params()).unwrap()
     }
 
+    /// Helper: zero out V to remove the seeded spots.
+    fn clear_v(engine: &mut GrayScott) {
+        let u = engine.u_field().clone();
+        let v = Field::new(u.width(), u.height()).unwrap();
+        engine.state = State::new(u, v, engine.params.precision);
+    }
+
     // ---- Construction tests ----
 
     #[test]
@@ -385,6 +761,8 @@ mod tests {
             diffusion_a: 0.9,
             diffusion_b: 0.4,
             dt: 0.7,
+            rng: RngAlgorithm::Pcg32,
+            precision: Precision::F32,
         };
         let engine = GrayScott::new(16, 16, 42, params).unwrap();
         let p = engine.params();
@@ -393,6 +771,27 @@ mod tests {
         assert!((p["diffusion_a"].as_f64().unwrap() - 0.9).abs() < f64::EPSILON);
         assert!((p["diffusion_b"].as_f64().unwrap() - 0.4).abs() < f64::EPSILON);
         assert!((p["dt"].as_f64().unwrap() - 0.7).abs() < f64::EPSILON);
+        assert_eq!(p["rng"], "pcg32");
+        assert_eq!(p["precision"], "f32");
+    }
+
+    #[test]
+    fn presets_stay_within_the_schema_ranges() {
+        let engine = gs(8, 8, 1);
+        let schema = engine.param_schema();
+        let presets = engine.presets();
+        assert_eq!(presets.as_object().unwrap().len(), PRESETS.len());
+        assert_eq!(presets["mitosis"]["kill_rate"], 0.0649);
+        for (name, params) in presets.as_object().unwrap() {
+            for (key, value) in params.as_object().unwrap() {
+                let value = value.as_f64().unwrap();
+                let (min, max) = (schema[key]["min"].as_f64(), schema[key]["max"].as_f64());
+                assert!(
+                    (min.unwrap()..=max.unwrap()).contains(&value),
+                    "{name}.{key} = {value} is out of range"
+                );
+            }
+        }
     }
 
     #[test]
@@ -461,6 +860,116 @@ mod tests {
             .any(|(va, vb)| va.to_bits() != vb.to_bits()));
     }
 
+    #[test]
+    fn step_matches_a_sequential_row_by_row_update() {
+        // With the `parallel` feature, rows run across threads; the result
+        // must still match updating them one after another bit for bit.
+        let mut engine = gs(37, 23, 9);
+        for _ in 0..20 {
+            engine.step().unwrap();
+        }
+        let (w, h) = (37, 23);
+        let mut u_next = vec![0.0; w * h];
+        let mut v_next = vec![0.0; w * h];
+        let (u, v) = (engine.u_field().data(), engine.v_field().data());
+        for (y, rows) in u_next.chunks_mut(w).zip(v_next.chunks_mut(w)).enumerate() {
+            step_row(u, v, w, h, y, &engine.params, rows);
+        }
+        engine.step().unwrap();
+        let bits = |data: &[f64]| data.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(engine.u_field().data()), bits(&u_next));
+        assert_eq!(bits(engine.v_field().data()), bits(&v_next));
+    }
+
+    #[test]
+    fn rng_param_pins_the_initial_spots_per_algorithm() {
+        // Sum of the seeded cell indices: if this breaks, the spot layout
+        // for that algorithm changed and its replays are invalidated.
+        let golden = [
+            ("xorshift64", 214_094),
+            ("pcg32", 78_864),
+            ("splitmix64", 56_405),
+        ];
+        for (name, expected) in golden {
+            let engine = GrayScott::from_json(64, 64, 42, &json!({ "rng": name })).unwrap();
+            let checksum: usize = engine
+                .v_field()
+                .data()
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v > 0.0)
+                .map(|(i, _)| i)
+                .sum();
+            assert_eq!(checksum, expected, "{name}");
+        }
+        // The default is Xorshift64, so existing seeds replay unchanged
+        let default = gs(64, 64, 42);
+        let explicit = GrayScott::from_json(64, 64, 42, &json!({"rng": "xorshift64"})).unwrap();
+        assert_eq!(default.v_field(), explicit.v_field());
+    }
+
+    // ---- Precision tests ----
+
+    #[test]
+    fn f32_halves_the_state_memory_estimate() {
+        let f32_params = GrayScottParams {
+            precision: Precision::F32,
+            ..default_params()
+        };
+        let wide = GrayScott::memory_estimate(100, 50, &default_params());
+        let narrow = GrayScott::memory_estimate(100, 50, &f32_params);
+        assert_eq!(wide, 100 * 50 * 32);
+        assert!(narrow < wide, "{narrow} vs {wide}");
+    }
+
+    #[test]
+    fn precision_defaults_to_f64_and_reads_from_json() {
+        assert_eq!(GrayScottParams::default().precision, Precision::F64);
+        let params = GrayScottParams::from_json(&json!({"precision": "f32"}));
+        assert_eq!(params.precision, Precision::F32);
+    }
+
+    #[test]
+    fn f32_tracks_f64_closely() {
+        let f32_params = GrayScottParams {
+            precision: Precision::F32,
+            ..default_params()
+        };
+        let mut wide = gs(48, 48, 7);
+        let mut narrow = GrayScott::new(48, 48, 7, f32_params).unwrap();
+        assert_eq!(wide.v_field().data(), narrow.v_field().data());
+        for _ in 0..200 {
+            wide.step().unwrap();
+            narrow.step().unwrap();
+        }
+        let max_diff = wide
+            .v_field()
+            .data()
+            .iter()
+            .zip(narrow.field().data())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        assert!(max_diff > 0.0, "f32 should not be bit-identical to f64");
+        assert!(max_diff < 1e-3, "f32 drifted {max_diff} from f64");
+    }
+
+    #[test]
+    fn f32_state_round_trips() {
+        let params = GrayScottParams::from_json(&json!({"precision": "f32"}));
+        let mut a = GrayScott::new(24, 16, 3, params).unwrap();
+        for _ in 0..10 {
+            a.step().unwrap();
+        }
+        let mut b = GrayScott::new(24, 16, 99, params).unwrap();
+        b.load_state(&a.save_state().unwrap()).unwrap();
+        for _ in 0..10 {
+            a.step().unwrap();
+            b.step().unwrap();
+        }
+        assert_eq!(a.u_field().data(), b.u_field().data());
+        assert_eq!(a.v_field().data(), b.v_field().data());
+    }
+
     // ---- Step correctness tests ----
 
     #[test]
@@ -473,7 +982,7 @@ mod tests {
     fn uniform_u_no_v_is_steady_state() {
         let mut engine = gs(16, 16, 42);
         // Zero out V to remove seeded spots
-        engine.v.data_mut().fill(0.0);
+        clear_v(&mut engine);
         for _ in 0..10 {
             engine.step().unwrap();
         }
@@ -511,7 +1020,7 @@ mod tests {
 
     #[test]
     fn laplacian_of_uniform_field_is_zero() {
-        let data = vec![0.5; 16 * 16];
+        let data = vec![0.5_f64; 16 * 16];
         for y in 0..16 {
             for x in 0..16 {
                 let lap = laplacian_9pt(&data, x, y, 16, 16);
@@ -636,7 +1145,7 @@ mod tests {
         let engine = gs(16, 16, 42);
         let field = engine.field();
         let has_nonzero = field.data().iter().any(|&v| v > 0.0);
-        let has_zero = field.data().iter().any(|&v| v == 0.0);
+        let has_zero = field.data().contains(&0.0);
         assert!(
             has_nonzero && has_zero,
             "field() should return V (mix of 0s and spots)"
@@ -649,6 +1158,24 @@ mod tests {
         assert!(engine.hue_field().is_none());
     }
 
+    #[test]
+    fn restored_state_continues_bit_identically() {
+        let mut original = gs(16, 16, 42);
+        (0..20).for_each(|_| original.step().unwrap());
+        let state = original.save_state().unwrap();
+
+        let mut restored = gs(16, 16, 7);
+        restored.load_state(&state).unwrap();
+        (0..20).for_each(|_| {
+            original.step().unwrap();
+            restored.step().unwrap();
+        });
+        assert_eq!(original.u_field(), restored.u_field());
+        assert_eq!(original.v_field(), restored.v_field());
+
+        assert!(gs(8, 16, 42).load_state(&state).is_err());
+    }
+
     #[test]
     fn engine_is_object_safe() {
         let engine = gs(16, 16, 42);
@@ -656,6 +1183,152 @@ mod tests {
         assert_eq!(boxed.field().width(), 16);
     }
 
+    // ---- Advection tests ----
+
+    fn advected(width: usize, height: usize, seed: u64, params: Value) -> GrayScott {
+        GrayScott::advected_from_json(width, height, seed, &params).unwrap()
+    }
+
+    #[test]
+    fn still_wind_matches_plain_gray_scott() {
+        for precision in ["f64", "f32"] {
+            let params = json!({
+                "precision": precision,
+                "velocity": {"kind": "curl", "scale": 0.05, "strength": 0.0},
+            });
+            let mut still = advected(24, 24, 42, params.clone());
+            let mut plain = GrayScott::from_json(24, 24, 42, &params).unwrap();
+            (0..30).for_each(|_| {
+                still.step().unwrap();
+                plain.step().unwrap();
+            });
+            assert_eq!(still.v_field(), plain.v_field(), "{precision}");
+        }
+    }
+
+    #[test]
+    fn wind_reshapes_the_pattern() {
+        let mut windy = advected(32, 32, 42, json!({}));
+        let mut plain = gs(32, 32, 42);
+        (0..200).for_each(|_| {
+            windy.step().unwrap();
+            plain.step().unwrap();
+        });
+        assert_ne!(windy.v_field(), plain.v_field());
+        assert!(windy
+            .v_field()
+            .data()
+            .iter()
+            .all(|v| (0.0..=1.0).contains(v)));
+        assert!(windy.v_field().data().iter().any(|&v| v > 0.1));
+    }
+
+    #[test]
+    fn advected_from_json_defaults_and_rejects_bad_velocity() {
+        let engine = advected(16, 8, 42, json!({"feed_rate": 0.03}));
+        assert_eq!(engine.feed_rate(), 0.03);
+        let velocity = &engine.params()["velocity"];
+        assert_eq!(velocity["kind"], "curl");
+        assert_eq!(velocity["strength"], json!(DEFAULT_VELOCITY_STRENGTH));
+        assert_eq!(velocity["period"], json!([16.0, 8.0]));
+
+        for bad in [
+            json!({"kind": "gale"}),
+            json!("curl"),
+            json!({"kind": "vortex"}),
+        ] {
+            let result = GrayScott::advected_from_json(16, 8, 42, &json!({ "velocity": bad }));
+            assert!(
+                matches!(result, Err(EngineError::InvalidFieldSource(_))),
+                "{bad}"
+            );
+        }
+        assert!(GrayScott::advected_from_json(0, 8, 42, &json!({})).is_err());
+    }
+
+    #[test]
+    fn only_advected_engines_describe_velocity() {
+        let plain = gs(8, 8, 42);
+        assert!(plain.params().get("velocity").is_none());
+        assert!(plain.param_schema().get("velocity").is_none());
+        assert!(plain.presets().get("coral").is_some());
+
+        let windy = advected(8, 8, 42, json!({}));
+        assert_eq!(windy.param_schema()["velocity"]["type"], "object");
+        let presets = windy.presets();
+        let presets = presets.as_object().unwrap();
+        assert_eq!(presets.len(), ADVECTED_PRESETS.len());
+        for (name, params) in presets {
+            let engine = GrayScott::advected_from_json(8, 8, 1, params);
+            assert!(engine.is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn advected_state_round_trips() {
+        let params = json!({"velocity": {"kind": "curl", "scale": 0.1, "strength": 0.8}});
+        let mut original = advected(16, 16, 42, params.clone());
+        (0..20).for_each(|_| original.step().unwrap());
+        let state = original.save_state().unwrap();
+
+        let mut restored = advected(16, 16, 7, params);
+        restored.load_state(&state).unwrap();
+        (0..20).for_each(|_| {
+            original.step().unwrap();
+            restored.step().unwrap();
+        });
+        assert_eq!(original.v_field(), restored.v_field());
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_changes_rates_without_resetting() {
+        let mut engine = gs(32, 32, 42);
+        (0..50).for_each(|_| engine.step().unwrap());
+        let before = engine.v_field().clone();
+        engine.set_params(&json!({"feed_rate": 0.03})).unwrap();
+        assert_eq!(engine.feed_rate(), 0.03);
+        assert_eq!(engine.kill_rate(), DEFAULT_KILL_RATE);
+        assert_eq!(engine.v_field(), &before);
+
+        let mut unchanged = gs(32, 32, 42);
+        (0..50).for_each(|_| unchanged.step().unwrap());
+        (0..20).for_each(|_| {
+            engine.step().unwrap();
+            unchanged.step().unwrap();
+        });
+        assert_ne!(engine.v_field(), unchanged.v_field());
+    }
+
+    #[test]
+    fn set_params_rejects_fixed_params() {
+        let mut engine = gs(8, 8, 42);
+        for patch in [json!({"rng": "pcg32"}), json!({"precision": "f32"})] {
+            assert!(
+                matches!(engine.set_params(&patch), Err(EngineError::Unsupported(_))),
+                "{patch}"
+            );
+        }
+        engine
+            .set_params(&json!({"rng": RngAlgorithm::default().name()}))
+            .unwrap();
+        assert!(engine.set_params(&json!(0.5)).is_err());
+    }
+
+    #[test]
+    fn set_params_rebuilds_the_wind() {
+        let params = json!({"velocity": {"kind": "curl", "scale": 0.1, "strength": 0.0}});
+        let mut engine = advected(16, 16, 42, params);
+        let windy = json!({"kind": "curl", "scale": 0.1, "strength": 1.0});
+        engine.set_params(&json!({ "velocity": windy })).unwrap();
+        assert_eq!(engine.params()["velocity"]["strength"], json!(1.0));
+
+        let bad = engine.set_params(&json!({"velocity": {"kind": "gale"}, "feed_rate": 0.01}));
+        assert!(matches!(bad, Err(EngineError::InvalidFieldSource(_))));
+        assert_eq!(engine.feed_rate(), DEFAULT_FEED_RATE);
+    }
+
     // ---- Property-based tests ----
 
     mod proptests {
@@ -680,6 +1353,8 @@ mod tests {
                     diffusion_a: da,
                     diffusion_b: db,
                     dt,
+                    rng: RngAlgorithm::default(),
+                    precision: Precision::default(),
                 })
         }
 
@@ -748,7 +1423,7 @@ mod tests {
             ) {
                 let p = GrayScottParams::default();
                 let mut engine = GrayScott::new(w, h, seed, p).unwrap();
-                engine.v.data_mut().fill(0.0);
+                clear_v(&mut engine);
                 for _ in 0..10 {
                     engine.step().unwrap();
                 }
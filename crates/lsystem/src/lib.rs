@@ -0,0 +1,962 @@
+#![deny(unsafe_code)]
+//! L-system engine.
+//!
+//! An L-system rewrites every symbol of a string in parallel by its rule,
+//! starting from an axiom; each `step()` expands one more level, up to the
+//! `iterations` param. A rule may list several successors, and then each
+//! occurrence of its symbol picks one at random by weight, so seeds grow
+//! different plants from the same grammar.
+//!
+//! The string is drawn by a turtle:
+//!
+//! - `F` and `G` move forward one unit, drawing a line,
+//! - `f` moves forward without drawing,
+//! - `+` and `-` turn left and right by `angle` degrees, `|` turns around,
+//! - `[` and `]` push and pop the position and heading, starting a branch,
+//!
+//! and every other symbol only takes part in rewriting. The path is scaled
+//! to fit the canvas and stroked with antialiased lines of `stroke_width`
+//! pixels, each branch level `depth_fade` times as bright as its parent.
+//!
+//! Each level's random choices come from their own stream derived from the
+//! seed and level, so a checkpoint needs only the level.
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::math;
+use art_engine_core::params::{
+    check_fixed, merge_params, param_f64, param_rng, param_string, param_usize,
+};
+use art_engine_core::prng::{derive_seed, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Map, Value};
+
+/// Rules as `(symbol, successors)` pairs, successors equally likely.
+type Grammar = &'static [(&'static str, &'static [&'static str])];
+
+/// Default axiom.
+const DEFAULT_AXIOM: &str = "F";
+/// Default rules: a weed whose every stem branches left, right or both.
+const DEFAULT_RULES: Grammar = &[("F", &["F[+F]F[-F]F", "F[+F]F", "F[-F]F"])];
+/// Default turning angle in degrees.
+const DEFAULT_ANGLE: f64 = 25.7;
+/// Default number of expansion levels.
+const DEFAULT_ITERATIONS: usize = 5;
+/// Most expansion levels.
+const MAX_ITERATIONS: usize = 16;
+/// Default stroke width in pixels.
+const DEFAULT_STROKE_WIDTH: f64 = 1.0;
+/// Thinnest stroke in pixels.
+const MIN_STROKE_WIDTH: f64 = 0.5;
+/// Widest stroke in pixels.
+const MAX_STROKE_WIDTH: f64 = 32.0;
+/// Default brightness of each branch level relative to its parent.
+const DEFAULT_DEPTH_FADE: f64 = 0.85;
+/// Longest string an expansion may produce; larger ones stop growing.
+const MAX_SYMBOLS: usize = 1 << 20;
+/// Margin around the drawing, as a fraction of the canvas's shorter side.
+const MARGIN: f64 = 0.05;
+/// Named (name, axiom, rules, angle, iterations) grammars.
+const PRESETS: &[(&str, &str, Grammar, f64, usize)] = &[
+    (
+        "plant",
+        "X",
+        &[("X", &["F+[[X]-X]-F[-FX]+X"]), ("F", &["FF"])],
+        25.0,
+        6,
+    ),
+    ("weed", DEFAULT_AXIOM, DEFAULT_RULES, DEFAULT_ANGLE, 5),
+    ("bush", "F", &[("F", &["FF+[+F-F-F]-[-F+F+F]"])], 22.5, 4),
+    (
+        "koch-snowflake",
+        "F--F--F",
+        &[("F", &["F+F--F+F"])],
+        60.0,
+        4,
+    ),
+    (
+        "dragon",
+        "FX",
+        &[("X", &["X+YF+"]), ("Y", &["-FX-Y"])],
+        90.0,
+        12,
+    ),
+    (
+        "sierpinski",
+        "F-G-G",
+        &[("F", &["F-G+F+G-F"]), ("G", &["GG"])],
+        120.0,
+        6,
+    ),
+];
+/// What the `rules` param must look like, for errors.
+const RULES_EXPECTED: &str = "an object mapping single symbols to a successor string, \
+     or a list of successor strings or {\"to\", \"weight\"} objects";
+
+/// The successors of one symbol, chosen between by weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    /// Strings the symbol may be replaced by.
+    pub successors: Vec<String>,
+    /// Relative chance of each successor; a single successor always wins.
+    pub weights: Vec<f64>,
+}
+
+impl Rule {
+    /// A rule that always rewrites to `successor`.
+    pub fn new(successor: &str) -> Self {
+        Self::weighted(&[(successor, 1.0)])
+    }
+
+    /// A rule choosing between `(successor, weight)` pairs.
+    pub fn weighted(choices: &[(&str, f64)]) -> Self {
+        Self {
+            successors: choices.iter().map(|(s, _)| s.to_string()).collect(),
+            weights: choices.iter().map(|&(_, w)| w).collect(),
+        }
+    }
+
+    /// The rule as JSON: a string for one successor, a list of strings for
+    /// equally likely ones, else a list of `{"to", "weight"}` objects.
+    fn to_json(&self) -> Value {
+        match (&self.successors[..], &self.weights[..]) {
+            ([successor], _) => json!(successor),
+            (successors, weights) if weights.iter().all(|&w| w == weights[0]) => {
+                json!(successors)
+            }
+            (successors, weights) => successors
+                .iter()
+                .zip(weights)
+                .map(|(to, weight)| json!({ "to": to, "weight": weight }))
+                .collect(),
+        }
+    }
+}
+
+/// Simulation parameters for the L-system.
+///
+/// Use [`Default`] for a stochastic weed, seeded by Xorshift64.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LSystemParams {
+    /// The string at level 0.
+    pub axiom: String,
+    /// Rewriting rules by symbol; symbols without one are copied.
+    pub rules: BTreeMap<char, Rule>,
+    /// Turning angle of `+` and `-`, in degrees (0-180).
+    pub angle: f64,
+    /// Expansion levels to grow to, one per step.
+    pub iterations: usize,
+    /// Line width in pixels.
+    pub stroke_width: f64,
+    /// Brightness of each branch level relative to its parent (0-1).
+    pub depth_fade: f64,
+    /// PRNG algorithm choosing between weighted successors.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for LSystemParams {
+    fn default() -> Self {
+        Self {
+            axiom: DEFAULT_AXIOM.to_string(),
+            rules: rules_from(DEFAULT_RULES),
+            angle: DEFAULT_ANGLE,
+            iterations: DEFAULT_ITERATIONS,
+            stroke_width: DEFAULT_STROKE_WIDTH,
+            depth_fade: DEFAULT_DEPTH_FADE,
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl LSystemParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each number into its range.
+    ///
+    /// `rules` maps each single-character symbol to a successor string, or
+    /// to a list of successor strings (equally likely) or `{"to", "weight"}`
+    /// objects. Returns `EngineError::ParamTypeMismatch` if it is present
+    /// but malformed, or a rule has no positive weight.
+    pub fn from_json(params: &Value) -> Result<Self, EngineError> {
+        let rules = match params.get("rules") {
+            Some(rules) => parse_rules(rules)?,
+            None => rules_from(DEFAULT_RULES),
+        };
+        Ok(Self {
+            axiom: param_string(params, "axiom", DEFAULT_AXIOM),
+            rules,
+            angle: param_f64(params, "angle", DEFAULT_ANGLE),
+            iterations: param_usize(params, "iterations", DEFAULT_ITERATIONS),
+            stroke_width: param_f64(params, "stroke_width", DEFAULT_STROKE_WIDTH),
+            depth_fade: param_f64(params, "depth_fade", DEFAULT_DEPTH_FADE),
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped())
+    }
+
+    /// Returns the params with each number clamped into its range; NaN
+    /// falls back to the default.
+    fn clamped(self) -> Self {
+        let clamp = |v: f64, min: f64, max: f64, default: f64| {
+            if v.is_nan() {
+                default
+            } else {
+                v.clamp(min, max)
+            }
+        };
+        Self {
+            angle: clamp(self.angle, 0.0, 180.0, DEFAULT_ANGLE),
+            iterations: self.iterations.min(MAX_ITERATIONS),
+            stroke_width: clamp(
+                self.stroke_width,
+                MIN_STROKE_WIDTH,
+                MAX_STROKE_WIDTH,
+                DEFAULT_STROKE_WIDTH,
+            ),
+            depth_fade: clamp(self.depth_fade, 0.0, 1.0, DEFAULT_DEPTH_FADE),
+            ..self
+        }
+    }
+}
+
+/// Rules as a JSON object, in the form `LSystemParams::from_json` reads.
+fn rules_json(rules: &BTreeMap<char, Rule>) -> Value {
+    rules
+        .iter()
+        .map(|(symbol, rule)| (symbol.to_string(), rule.to_json()))
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+/// Builds the rules of `grammar`.
+fn rules_from(grammar: Grammar) -> BTreeMap<char, Rule> {
+    grammar
+        .iter()
+        .filter_map(|&(symbol, successors)| {
+            let choices: Vec<_> = successors.iter().map(|&s| (s, 1.0)).collect();
+            Some((symbol.chars().next()?, Rule::weighted(&choices)))
+        })
+        .collect()
+}
+
+/// Parses the `rules` param.
+fn parse_rules(rules: &Value) -> Result<BTreeMap<char, Rule>, EngineError> {
+    let mismatch = |got: String| EngineError::ParamTypeMismatch {
+        name: "rules".to_string(),
+        expected: RULES_EXPECTED.to_string(),
+        got,
+    };
+    let Some(rules) = rules.as_object() else {
+        return Err(mismatch(rules.to_string()));
+    };
+    rules
+        .iter()
+        .map(|(key, rule)| {
+            let mut chars = key.chars();
+            let (Some(symbol), None) = (chars.next(), chars.next()) else {
+                return Err(mismatch(format!("the key {key:?}")));
+            };
+            let choice = |item: &Value| match item {
+                Value::String(to) => Some((to.clone(), 1.0)),
+                Value::Object(choice) => {
+                    let to = choice.get("to")?.as_str()?;
+                    let weight = choice.get("weight").map_or(Some(1.0), Value::as_f64)?;
+                    (weight >= 0.0).then(|| (to.to_string(), weight))
+                }
+                _ => None,
+            };
+            let choices = match rule {
+                Value::Array(items) => items.iter().map(choice).collect::<Option<Vec<_>>>(),
+                single => choice(single).map(|c| vec![c]),
+            };
+            match choices {
+                Some(choices) if choices.iter().any(|&(_, w)| w > 0.0) => {
+                    let (successors, weights) = choices.into_iter().unzip();
+                    Ok((
+                        symbol,
+                        Rule {
+                            successors,
+                            weights,
+                        },
+                    ))
+                }
+                _ => Err(mismatch(format!("{rule} for {key:?}"))),
+            }
+        })
+        .collect()
+}
+
+/// A line of the turtle's path, in turtle units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    from: (f64, f64),
+    to: (f64, f64),
+    /// Branch nesting at which it was drawn; the trunk is 0.
+    depth: usize,
+}
+
+/// L-system engine.
+///
+/// Holds the current string; the field is redrawn from it at every level.
+pub struct LSystem {
+    seed: u64,
+    params: LSystemParams,
+    symbols: Vec<char>,
+    /// Expansion levels applied to the axiom so far.
+    level: usize,
+    field: Field,
+}
+
+impl LSystem {
+    /// Creates a new L-system engine showing the axiom (level 0).
+    /// Out-of-range params are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: LSystemParams,
+    ) -> Result<Self, EngineError> {
+        let field = Field::new(width, height)?;
+        let params = params.clamped();
+        let mut engine = Self {
+            seed,
+            symbols: params.axiom.chars().collect(),
+            params,
+            level: 0,
+            field,
+        };
+        engine.update_field();
+        Ok(engine)
+    }
+
+    /// Creates an L-system engine from a JSON params object.
+    ///
+    /// Extracts `axiom`, `rules`, `angle`, `iterations`, `stroke_width`,
+    /// `depth_fade` and `rng`, falling back to defaults for missing keys.
+    ///
+    /// Returns `EngineError::ParamTypeMismatch` for malformed `rules`.
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, LSystemParams::from_json(json_params)?)
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// field, plus the longest string, its next expansion and its path.
+    pub fn memory_estimate(width: usize, height: usize) -> u64 {
+        let per_symbol = 2 * std::mem::size_of::<char>() + std::mem::size_of::<Segment>();
+        grid_bytes(width, height, 8).saturating_add((MAX_SYMBOLS * per_symbol) as u64)
+    }
+
+    /// The current string.
+    pub fn symbols(&self) -> String {
+        self.symbols.iter().collect()
+    }
+
+    /// Expansion levels applied so far.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Rewrites every symbol once, choosing weighted successors from this
+    /// level's stream. Returns false, leaving the string alone, if the
+    /// result would exceed [`MAX_SYMBOLS`].
+    fn expand(&mut self) -> bool {
+        let mut rng = self
+            .params
+            .rng
+            .build(derive_seed(self.seed, &format!("level-{}", self.level)));
+        let mut next = Vec::with_capacity(self.symbols.len());
+        for symbol in &self.symbols {
+            match self.params.rules.get(symbol) {
+                Some(rule) => {
+                    let choice = match rule.successors.len() {
+                        1 => 0,
+                        _ => rng.next_weighted(&rule.weights).unwrap_or(0),
+                    };
+                    next.extend(rule.successors[choice].chars());
+                }
+                None => next.push(*symbol),
+            }
+            if next.len() > MAX_SYMBOLS {
+                return false;
+            }
+        }
+        self.symbols = next;
+        self.level += 1;
+        true
+    }
+
+    /// Redraws the field from the current string.
+    fn update_field(&mut self) {
+        let segments = trace(&self.symbols, self.params.angle.to_radians());
+        let radius = self.params.stroke_width / 2.0;
+        let (width, height) = (self.field.width(), self.field.height());
+        self.field.data_mut().fill(0.0);
+        let Some(fit) = Fit::new(&segments, width, height, radius) else {
+            return;
+        };
+        for segment in &segments {
+            let intensity = math::powi(self.params.depth_fade, segment.depth as i32);
+            stroke(
+                &mut self.field,
+                fit.apply(segment.from),
+                fit.apply(segment.to),
+                radius,
+                intensity,
+            );
+        }
+    }
+}
+
+/// Walks the turtle over `symbols`, returning the lines it draws. Starts
+/// at the origin heading up (negative y, as on screen).
+fn trace(symbols: &[char], angle: f64) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let (mut position, mut heading) = ((0.0, 0.0), PI / 2.0);
+    let mut stack = Vec::new();
+    for &symbol in symbols {
+        match symbol {
+            'F' | 'G' | 'f' => {
+                let to = (
+                    position.0 + math::cos(heading),
+                    position.1 - math::sin(heading),
+                );
+                if symbol != 'f' {
+                    segments.push(Segment {
+                        from: position,
+                        to,
+                        depth: stack.len(),
+                    });
+                }
+                position = to;
+            }
+            '+' => heading += angle,
+            '-' => heading -= angle,
+            '|' => heading += PI,
+            '[' => stack.push((position, heading)),
+            ']' => {
+                if let Some(saved) = stack.pop() {
+                    (position, heading) = saved;
+                }
+            }
+            _ => {}
+        }
+    }
+    segments
+}
+
+/// Maps turtle coordinates onto the canvas, centred and scaled to fill it
+/// inside the margin.
+struct Fit {
+    scale: f64,
+    offset: (f64, f64),
+}
+
+impl Fit {
+    /// Fits the bounds of `segments` to a `width`×`height` canvas, keeping
+    /// strokes of `radius` inside; `None` if nothing is drawn.
+    fn new(segments: &[Segment], width: usize, height: usize, radius: f64) -> Option<Self> {
+        let points = segments.iter().flat_map(|s| [s.from, s.to]);
+        let (min, max) = points.fold(None, |bounds, (x, y)| match bounds {
+            None => Some(((x, y), (x, y))),
+            Some(((x0, y0), (x1, y1))) => Some(((x.min(x0), y.min(y0)), (x.max(x1), y.max(y1)))),
+        })?;
+        let (w, h) = (width as f64, height as f64);
+        let margin = MARGIN * w.min(h) + radius;
+        let axis =
+            |span: f64, room: f64| (span > 1e-9).then(|| (room - 2.0 * margin).max(1.0) / span);
+        let scale = match (axis(max.0 - min.0, w), axis(max.1 - min.1, h)) {
+            (Some(sx), Some(sy)) => sx.min(sy),
+            (Some(s), None) | (None, Some(s)) => s,
+            (None, None) => return None,
+        };
+        let centre = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+        Some(Self {
+            scale,
+            offset: (w / 2.0 - centre.0 * scale, h / 2.0 - centre.1 * scale),
+        })
+    }
+
+    /// Canvas position of turtle point `p`.
+    fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            x * self.scale + self.offset.0,
+            y * self.scale + self.offset.1,
+        )
+    }
+}
+
+/// Draws an antialiased line from `a` to `b` with round caps, keeping the
+/// brighter value where strokes overlap.
+fn stroke(field: &mut Field, a: (f64, f64), b: (f64, f64), radius: f64, intensity: f64) {
+    let (w, h) = (field.width(), field.height());
+    let reach = radius + 0.5;
+    let cells = |lo: f64, hi: f64, size: usize| {
+        let first = (lo - reach).floor().max(0.0) as usize;
+        let last = ((hi + reach).ceil().max(0.0) as usize).min(size);
+        first..last
+    };
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let data = field.data_mut();
+    for y in cells(a.1.min(b.1), a.1.max(b.1), h) {
+        for x in cells(a.0.min(b.0), a.0.max(b.0), w) {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let t = if length_sq > 0.0 {
+                (((px - a.0) * dx + (py - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = math::hypot(px - a.0 - t * dx, py - a.1 - t * dy);
+            let value = (reach - distance).clamp(0.0, 1.0) * intensity;
+            let cell = &mut data[y * w + x];
+            *cell = cell.max(value);
+        }
+    }
+}
+
+impl Engine for LSystem {
+    /// Expands one level, until `iterations` levels or the symbol limit;
+    /// after that steps change nothing.
+    fn step(&mut self) -> Result<(), EngineError> {
+        if self.level < self.params.iterations && self.expand() {
+            self.update_field();
+        }
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.field
+    }
+
+    fn params(&self) -> Value {
+        json!({
+            "axiom": self.params.axiom,
+            "rules": rules_json(&self.params.rules),
+            "angle": self.params.angle,
+            "iterations": self.params.iterations,
+            "stroke_width": self.params.stroke_width,
+            "depth_fade": self.params.depth_fade,
+            "rng": self.params.rng.name(),
+        })
+    }
+
+    /// Drawing params redraw the current string; `rules`, `rng` and
+    /// `iterations` apply from the next level. `axiom` is fixed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["axiom"])?;
+        self.params = LSystemParams::from_json(&merge_params(&current, params)?)?;
+        self.update_field();
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "axiom": {
+                "type": "string",
+                "default": DEFAULT_AXIOM,
+                "description": "Starting string; F and G draw, f moves, + and - turn, | turns around, [ and ] branch"
+            },
+            "rules": {
+                "type": "object",
+                "default": rules_json(&rules_from(DEFAULT_RULES)),
+                "description": "Symbol to successor string, or to a list of successors (strings or {\"to\", \"weight\"}) chosen at random"
+            },
+            "angle": {
+                "type": "number",
+                "default": DEFAULT_ANGLE,
+                "min": 0.0,
+                "max": 180.0,
+                "description": "Turning angle of + and -, in degrees"
+            },
+            "iterations": {
+                "type": "integer",
+                "default": DEFAULT_ITERATIONS,
+                "min": 0,
+                "max": MAX_ITERATIONS,
+                "description": "Expansion levels to grow to, one per step"
+            },
+            "stroke_width": {
+                "type": "number",
+                "default": DEFAULT_STROKE_WIDTH,
+                "min": MIN_STROKE_WIDTH,
+                "max": MAX_STROKE_WIDTH,
+                "description": "Line width in pixels"
+            },
+            "depth_fade": {
+                "type": "number",
+                "default": DEFAULT_DEPTH_FADE,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Brightness of each branch level relative to its parent"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG choosing between weighted successors: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(|&(name, axiom, rules, angle, iterations)| {
+                let params = json!({
+                    "axiom": axiom,
+                    "rules": rules_json(&rules_from(rules)),
+                    "angle": angle,
+                    "iterations": iterations,
+                });
+                (name.to_string(), params)
+            })
+            .collect::<Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(self.level as u64);
+        Ok(writer.finish())
+    }
+
+    /// Restores a level by expanding the axiom again; the choices replay
+    /// from the seed.
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let level = reader.u64()?;
+        reader.finish()?;
+        if level > self.params.iterations as u64 {
+            return Err(EngineError::InvalidState(format!(
+                "level {level} is past the {} iterations",
+                self.params.iterations
+            )));
+        }
+        self.symbols = self.params.axiom.chars().collect();
+        self.level = 0;
+        while (self.level as u64) < level {
+            if !self.expand() {
+                return Err(EngineError::InvalidState(format!(
+                    "level {level} exceeds the {MAX_SYMBOLS}-symbol limit"
+                )));
+            }
+        }
+        self.update_field();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: construct from JSON params.
+    fn lsystem(width: usize, height: usize, seed: u64, params: Value) -> LSystem {
+        LSystem::from_json(width, height, seed, &params).unwrap()
+    }
+
+    /// Helper: a deterministic grammar doubling each line.
+    fn doubling() -> Value {
+        json!({"axiom": "F", "rules": {"F": "F+F"}, "angle": 90.0, "iterations": 3})
+    }
+
+    /// Helper: number of cells the drawing touches.
+    fn lit(engine: &LSystem) -> usize {
+        engine.field().data().iter().filter(|&&v| v > 0.0).count()
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_draws_the_axiom() {
+        let engine = lsystem(32, 24, 1, json!({"axiom": "F"}));
+        assert_eq!(engine.field().width(), 32);
+        assert_eq!(engine.field().height(), 24);
+        assert_eq!((engine.level(), engine.symbols()), (0, "F".to_string()));
+        assert!(lit(&engine) > 0);
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = LSystem::new(0, 10, 42, LSystemParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        let params = LSystemParams::from_json(&json!({})).unwrap();
+        assert_eq!(params, LSystemParams::default());
+        let params = LSystemParams::from_json(&json!({
+            "angle": 400.0,
+            "iterations": 99,
+            "stroke_width": 0.0,
+            "depth_fade": 2.0,
+            "rng": "pcg32",
+        }))
+        .unwrap();
+        assert_eq!(params.angle, 180.0);
+        assert_eq!(params.iterations, MAX_ITERATIONS);
+        assert_eq!(params.stroke_width, MIN_STROKE_WIDTH);
+        assert_eq!(params.depth_fade, 1.0);
+        assert_eq!(params.rng, RngAlgorithm::Pcg32);
+    }
+
+    #[test]
+    fn from_json_reads_every_rule_form() {
+        let params = LSystemParams::from_json(&json!({"rules": {
+            "A": "AB",
+            "B": ["A", "BB"],
+            "C": [{"to": "C", "weight": 3.0}, {"to": "", "weight": 1.0}, "CC"],
+        }}))
+        .unwrap();
+        assert_eq!(params.rules[&'A'], Rule::new("AB"));
+        assert_eq!(
+            params.rules[&'B'],
+            Rule::weighted(&[("A", 1.0), ("BB", 1.0)])
+        );
+        assert_eq!(
+            params.rules[&'C'],
+            Rule::weighted(&[("C", 3.0), ("", 1.0), ("CC", 1.0)])
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_malformed_rules() {
+        for rules in [
+            json!("F+F"),
+            json!({"FF": "F"}),
+            json!({"": "F"}),
+            json!({"F": 3}),
+            json!({"F": []}),
+            json!({"F": [{"weight": 1.0}]}),
+            json!({"F": [{"to": "F", "weight": -1.0}]}),
+            json!({"F": [{"to": "F", "weight": 0.0}]}),
+        ] {
+            let result = LSystemParams::from_json(&json!({ "rules": rules }));
+            assert!(
+                matches!(result, Err(EngineError::ParamTypeMismatch { .. })),
+                "{rules}"
+            );
+        }
+    }
+
+    #[test]
+    fn params_round_trip_through_json() {
+        let engine = lsystem(
+            8,
+            8,
+            1,
+            json!({"rules": {"F": [{"to": "FF", "weight": 2.0}, "F"], "X": ["F", "X"]}}),
+        );
+        let params = engine.params();
+        assert_eq!(
+            LSystemParams::from_json(&params).unwrap(),
+            engine.params.clone()
+        );
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+    }
+
+    #[test]
+    fn presets_build_and_stay_within_the_schema_ranges() {
+        let engine = lsystem(8, 8, 1, json!({}));
+        let schema = engine.param_schema();
+        for (name, params) in engine.presets().as_object().unwrap() {
+            assert!(LSystem::from_json(8, 8, 1, params).is_ok(), "{name}");
+            for key in ["angle", "iterations"] {
+                let value = params[key].as_f64().unwrap();
+                let (min, max) = (schema[key]["min"].as_f64(), schema[key]["max"].as_f64());
+                assert!(
+                    value >= min.unwrap() && value <= max.unwrap(),
+                    "{name}.{key}"
+                );
+            }
+        }
+    }
+
+    // ---- Rewriting tests ----
+
+    #[test]
+    fn each_step_expands_one_level_up_to_the_iterations() {
+        let mut engine = lsystem(16, 16, 1, doubling());
+        let expected = ["F+F", "F+F+F+F", "F+F+F+F+F+F+F+F"];
+        for (level, symbols) in expected.iter().enumerate() {
+            engine.step().unwrap();
+            assert_eq!(engine.level(), level + 1);
+            assert_eq!(engine.symbols(), *symbols);
+        }
+        engine.step().unwrap();
+        assert_eq!(engine.level(), 3);
+        assert_eq!(engine.symbols(), expected[2]);
+    }
+
+    #[test]
+    fn weighted_choices_follow_the_seed() {
+        let params = json!({"axiom": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", "rules": {"A": ["x", "y"]}});
+        let grown = |seed: u64| {
+            let mut engine = lsystem(8, 8, seed, params.clone());
+            engine.step().unwrap();
+            engine.symbols()
+        };
+        assert_eq!(grown(3), grown(3));
+        assert_ne!(grown(3), grown(4));
+        assert!(grown(3).contains('x') && grown(3).contains('y'));
+
+        let never = json!({"axiom": "AAAA", "rules": {"A": [{"to": "x", "weight": 0.0}, "y"]}});
+        let mut engine = lsystem(8, 8, 3, never);
+        engine.step().unwrap();
+        assert_eq!(engine.symbols(), "yyyy");
+    }
+
+    #[test]
+    fn runaway_growth_stops_at_the_symbol_limit() {
+        let params = json!({"rules": {"F": "FFFFFFFFFFFFFFFF"}, "iterations": 8});
+        let mut engine = lsystem(8, 8, 1, params);
+        for _ in 0..8 {
+            engine.step().unwrap();
+        }
+        assert_eq!(engine.level(), 5);
+        assert_eq!(engine.symbols().len(), 1 << 20);
+    }
+
+    // ---- Drawing tests ----
+
+    #[test]
+    fn drawing_fills_the_canvas_inside_the_margin() {
+        let engine = lsystem(40, 40, 1, json!({"axiom": "F+F+F+F", "angle": 90.0}));
+        let field = engine.field();
+        let column: Vec<f64> = (0..40).map(|y| field.get(20, y as isize)).collect();
+        let first = column.iter().position(|&v| v > 0.5).unwrap();
+        let last = column.iter().rposition(|&v| v > 0.5).unwrap();
+        assert!((1..=4).contains(&first), "{column:?}");
+        assert!((35..=38).contains(&last), "{column:?}");
+        assert_eq!(field.get(20, 20), 0.0);
+    }
+
+    #[test]
+    fn branches_fade_with_depth() {
+        let drawn = |fade: f64| {
+            let params = json!({"axiom": "F[+F]", "angle": 90.0, "depth_fade": fade});
+            lsystem(40, 40, 1, params).field().data().to_vec()
+        };
+        let (full, faded) = (drawn(1.0), drawn(0.5));
+        let cells: Vec<_> = full.iter().zip(&faded).filter(|(&f, _)| f > 0.5).collect();
+        assert!(cells.iter().all(|(f, h)| *h == *f || **h == **f * 0.5));
+        assert!(cells.iter().any(|(f, h)| *h == *f));
+        assert!(cells.iter().any(|(f, h)| **h == **f * 0.5));
+    }
+
+    #[test]
+    fn wider_strokes_cover_more_cells() {
+        let thin = lsystem(48, 48, 1, json!({"iterations": 3, "stroke_width": 1.0}));
+        let wide = lsystem(48, 48, 1, json!({"iterations": 3, "stroke_width": 4.0}));
+        assert!(lit(&wide) > lit(&thin));
+    }
+
+    #[test]
+    fn nothing_to_draw_leaves_the_field_empty() {
+        let engine = lsystem(8, 8, 1, json!({"axiom": "X+f-Y"}));
+        assert_eq!(lit(&engine), 0);
+    }
+
+    // ---- State tests ----
+
+    #[test]
+    fn restored_state_replays_the_level() {
+        let mut original = lsystem(24, 16, 5, json!({}));
+        (0..3).for_each(|_| original.step().unwrap());
+        let state = original.save_state().unwrap();
+        let mut resumed = lsystem(24, 16, 5, json!({}));
+        resumed.load_state(&state).unwrap();
+        assert_eq!(resumed.symbols(), original.symbols());
+        assert_eq!(resumed.field(), original.field());
+        original.step().unwrap();
+        resumed.step().unwrap();
+        assert_eq!(resumed.symbols(), original.symbols());
+
+        let mut writer = StateWriter::new();
+        writer.u64(9);
+        assert!(matches!(
+            lsystem(8, 8, 5, json!({})).load_state(&writer.finish()),
+            Err(EngineError::InvalidState(_))
+        ));
+    }
+
+    #[test]
+    fn engine_is_object_safe() {
+        let boxed: Box<dyn Engine> = Box::new(lsystem(8, 8, 1, json!({})));
+        assert!(boxed.hue_field().is_none());
+        assert_eq!(boxed.field().width(), 8);
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_redraws_and_rewrites_from_the_next_level() {
+        let mut engine = lsystem(48, 48, 1, doubling());
+        engine.step().unwrap();
+        let thin = lit(&engine);
+        engine.set_params(&json!({"stroke_width": 4.0})).unwrap();
+        assert_eq!(engine.symbols(), "F+F");
+        assert!(lit(&engine) > thin);
+
+        engine.set_params(&json!({"rules": {"F": "FF"}})).unwrap();
+        engine.step().unwrap();
+        assert_eq!(engine.symbols(), "FF+FF");
+    }
+
+    #[test]
+    fn set_params_rejects_a_new_axiom_and_bad_rules() {
+        let mut engine = lsystem(16, 16, 1, doubling());
+        let result = engine.set_params(&json!({"axiom": "X"}));
+        assert!(matches!(result, Err(EngineError::Unsupported(_))));
+        let result = engine.set_params(&json!({"rules": {"FF": "F"}, "angle": 45.0}));
+        assert!(matches!(result, Err(EngineError::ParamTypeMismatch { .. })));
+        assert_eq!(engine.params()["angle"], json!(90.0));
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn field_stays_in_unit_interval(
+                width in 1usize..24,
+                height in 1usize..24,
+                seed in any::<u64>(),
+                axiom in "[FGf+\\-|\\[\\]X]{0,12}",
+                angle in 0.0f64..180.0,
+                stroke_width in 0.5f64..6.0,
+            ) {
+                let params = json!({
+                    "axiom": axiom,
+                    "rules": {"F": ["F[+F]", "F-F", "FF"], "X": "F[X]"},
+                    "angle": angle,
+                    "stroke_width": stroke_width,
+                    "iterations": 3,
+                });
+                let mut engine = lsystem(width, height, seed, params);
+                for _ in 0..3 {
+                    engine.step().unwrap();
+                }
+                prop_assert!(engine.field().data().iter().all(|v| (0.0..=1.0).contains(v)));
+            }
+        }
+    }
+}
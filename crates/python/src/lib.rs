@@ -0,0 +1,326 @@
+#![deny(unsafe_code)]
+//! Python bindings for the art-engine, for notebooks and field analysis.
+//!
+//! [`Engine`] wraps an [`EngineKind`] and a [`Palette`]. Python steps it and
+//! reads the field back as a NumPy array:
+//!
+//! ```python
+//! import art_engine
+//!
+//! eng = art_engine.Engine("gray-scott", 256, 256, seed=42, params={"feed_rate": 0.04})
+//! eng.step(500)
+//! field = eng.field()          # float64, shape (256, 256)
+//! image = eng.render("fire")   # uint8 RGBA, shape (256, 256, 4)
+//! eng.set_params({"kill_rate": 0.062})
+//! ```
+//!
+//! Arrays are copies, indexed `[y, x]` like the engine's row-major field, so
+//! they stay valid as the engine keeps stepping. `render_field` colors any
+//! 2-D array through a palette, e.g. a field after NumPy post-processing.
+//! Params, schemas and presets cross as Python dicts via JSON. Failures
+//! raise `art_engine.EngineError`. Stepping releases the GIL.
+//!
+//! Build a wheel with `maturin build --release` in this directory (the
+//! `extension-module` feature is set in `pyproject.toml`).
+
+use art_engine_core::{Engine as _, EngineError as CoreError, Field, Palette};
+use art_engine_engines::pixel::{engine_to_rgba, field_to_rgba_with_hue};
+use art_engine_engines::EngineKind;
+use numpy::{PyArray1, PyArray2, PyArray3, PyArrayMethods, PyReadonlyArray2};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use serde_json::Value;
+
+create_exception!(
+    art_engine,
+    EngineError,
+    PyException,
+    "Raised when an engine cannot be built, stepped or rendered."
+);
+
+fn engine_error(e: CoreError) -> PyErr {
+    EngineError::new_err(e.to_string())
+}
+
+/// A running engine with the palette its frames are drawn in.
+#[pyclass(module = "art_engine")]
+pub struct Engine {
+    engine: EngineKind,
+    name: String,
+    palette: Palette,
+    palette_name: String,
+    /// Simulation steps run since the engine was built.
+    steps: u64,
+}
+
+#[pymethods]
+impl Engine {
+    /// Builds `name` at `width x height` from `seed` and `params` (a dict of
+    /// engine params; missing ones take their defaults), drawn with a
+    /// built-in palette.
+    #[new]
+    #[pyo3(signature = (name, width, height, seed = 0, params = None, palette = "ocean"))]
+    fn new(
+        name: &str,
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: Option<&Bound<'_, PyAny>>,
+        palette: &str,
+    ) -> PyResult<Self> {
+        let params = match params {
+            Some(params) => to_json(params)?,
+            None => Value::Object(Default::default()),
+        };
+        if !params.is_object() {
+            return Err(EngineError::new_err("params must be a dict"));
+        }
+        Self::create(name, width, height, seed, &params, palette).map_err(engine_error)
+    }
+
+    /// Engine name.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Field width in cells.
+    #[getter]
+    fn width(&self) -> usize {
+        self.engine.field().width()
+    }
+
+    /// Field height in cells.
+    #[getter]
+    fn height(&self) -> usize {
+        self.engine.field().height()
+    }
+
+    /// Simulation steps run so far.
+    #[getter]
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Name of the palette `render` uses by default.
+    #[getter]
+    fn palette(&self) -> &str {
+        &self.palette_name
+    }
+
+    /// Switches to another built-in palette.
+    #[setter]
+    fn set_palette(&mut self, name: &str) -> PyResult<()> {
+        self.palette = Palette::from_name(name).map_err(engine_error)?;
+        self.palette_name = name.to_string();
+        Ok(())
+    }
+
+    /// Advances the simulation by `steps`, without holding the GIL.
+    #[pyo3(signature = (steps = 1))]
+    fn step(&mut self, py: Python<'_>, steps: u32) -> PyResult<()> {
+        py.detach(|| self.advance(steps)).map_err(engine_error)
+    }
+
+    /// The scalar field as a `(height, width)` float64 array.
+    fn field<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        field_array(py, self.engine.field())
+    }
+
+    /// The hue field as a `(height, width)` float64 array, or `None` for
+    /// engines without one.
+    fn hue_field<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyArray2<f64>>>> {
+        self.engine
+            .hue_field()
+            .map(|hue| field_array(py, hue))
+            .transpose()
+    }
+
+    /// The current frame as a `(height, width, 4)` uint8 RGBA array, in the
+    /// engine's palette or the named one; the hue field, if any, is applied.
+    #[pyo3(signature = (palette = None))]
+    fn render<'py>(
+        &self,
+        py: Python<'py>,
+        palette: Option<&str>,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let rgba = match palette {
+            Some(name) => engine_to_rgba(
+                &self.engine,
+                &Palette::from_name(name).map_err(engine_error)?,
+            ),
+            None => self.rgba(),
+        }
+        .map_err(engine_error)?;
+        rgba_array(py, rgba, self.width(), self.height())
+    }
+
+    /// Current params as a dict.
+    fn params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &self.engine.params())
+    }
+
+    /// Changes some params mid-run; keys left out keep their values.
+    fn set_params(&mut self, params: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.engine
+            .set_params(&to_json(params)?)
+            .map_err(engine_error)
+    }
+
+    /// The engine's param schema: types, ranges and defaults by name.
+    fn param_schema<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &self.engine.param_schema())
+    }
+
+    /// Named param sets worth starting from.
+    fn presets<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &self.engine.presets())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Engine({:?}, {}, {}, steps={})",
+            self.name,
+            self.width(),
+            self.height(),
+            self.steps
+        )
+    }
+}
+
+impl Engine {
+    /// Rust-side constructor behind [`Engine::new`], taking parsed params.
+    fn create(
+        name: &str,
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: &Value,
+        palette: &str,
+    ) -> Result<Self, CoreError> {
+        Ok(Self {
+            engine: EngineKind::from_name(name, width, height, seed, params)?,
+            name: name.to_string(),
+            palette: Palette::from_name(palette)?,
+            palette_name: palette.to_string(),
+            steps: 0,
+        })
+    }
+
+    /// Runs `steps` simulation steps.
+    fn advance(&mut self, steps: u32) -> Result<(), CoreError> {
+        for _ in 0..steps {
+            self.engine.step()?;
+            self.steps += 1;
+        }
+        Ok(())
+    }
+
+    /// Renders the current frame in the engine's palette.
+    fn rgba(&self) -> Result<Vec<u8>, CoreError> {
+        engine_to_rgba(&self.engine, &self.palette)
+    }
+}
+
+/// Names of the engines `Engine` can build.
+#[pyfunction]
+fn engines() -> Vec<&'static str> {
+    EngineKind::list_engines().to_vec()
+}
+
+/// Names of the built-in palettes.
+#[pyfunction]
+fn palettes() -> Vec<&'static str> {
+    Palette::list_names().to_vec()
+}
+
+/// Colors a 2-D array of values in [0, 1] through a built-in palette,
+/// optionally rotating hues by a same-shaped `hue` array, into a
+/// `(height, width, 4)` uint8 RGBA array.
+#[pyfunction]
+#[pyo3(signature = (field, palette = "ocean", hue = None))]
+fn render_field<'py>(
+    py: Python<'py>,
+    field: PyReadonlyArray2<'py, f64>,
+    palette: &str,
+    hue: Option<PyReadonlyArray2<'py, f64>>,
+) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let field = array_field(&field).map_err(engine_error)?;
+    let hue = hue
+        .map(|hue| array_field(&hue))
+        .transpose()
+        .map_err(engine_error)?;
+    let palette = Palette::from_name(palette).map_err(engine_error)?;
+    let rgba = field_to_rgba_with_hue(&field, &palette, hue.as_ref()).map_err(engine_error)?;
+    rgba_array(py, rgba, field.width(), field.height())
+}
+
+/// Copies a field into a `(height, width)` array.
+fn field_array<'py>(py: Python<'py>, field: &Field) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    PyArray1::from_slice(py, field.data()).reshape([field.height(), field.width()])
+}
+
+/// Wraps RGBA8 pixels as a `(height, width, 4)` array.
+fn rgba_array(
+    py: Python<'_>,
+    rgba: Vec<u8>,
+    width: usize,
+    height: usize,
+) -> PyResult<Bound<'_, PyArray3<u8>>> {
+    PyArray1::from_vec(py, rgba).reshape([height, width, 4])
+}
+
+/// Copies a 2-D array (of any memory layout) into a field.
+fn array_field(array: &PyReadonlyArray2<'_, f64>) -> Result<Field, CoreError> {
+    let view = array.as_array();
+    let (height, width) = view.dim();
+    Field::from_data(width, height, view.iter().copied().collect())
+}
+
+fn to_json(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let text: String = value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()?;
+    serde_json::from_str(&text).map_err(|e| EngineError::new_err(format!("invalid params: {e}")))
+}
+
+fn from_json<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    py.import("json")?
+        .call_method1("loads", (value.to_string(),))
+}
+
+/// The `art_engine` Python module.
+#[pymodule]
+fn art_engine(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Engine>()?;
+    m.add("EngineError", m.py().get_type::<EngineError>())?;
+    m.add_function(wrap_pyfunction!(engines, m)?)?;
+    m.add_function(wrap_pyfunction!(palettes, m)?)?;
+    m.add_function(wrap_pyfunction!(render_field, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn steps_are_counted_and_frames_sized_to_the_field() {
+        let mut engine = Engine::create("gray-scott", 12, 8, 1, &json!({}), "ocean").unwrap();
+        engine.advance(5).unwrap();
+        assert_eq!(engine.steps, 5);
+        let rgba = engine.rgba().unwrap();
+        assert_eq!(rgba.len(), 12 * 8 * 4);
+        assert!(rgba.chunks(4).all(|px| px[3] == 255));
+    }
+
+    #[test]
+    fn rejects_unknown_engine_and_palette() {
+        assert!(Engine::create("nope", 8, 8, 1, &json!({}), "ocean").is_err());
+        assert!(Engine::create("gray-scott", 8, 8, 1, &json!({}), "nope").is_err());
+    }
+}
@@ -0,0 +1,643 @@
+#![deny(unsafe_code)]
+//! Spatial rock-paper-scissors engine.
+//!
+//! A stochastic cellular automaton of cyclic dominance (the May-Leonard
+//! model): each cell is empty or holds one of three species, where rock
+//! beats scissors, scissors beat paper and paper beats rock. With five
+//! species (rock-paper-scissors-lizard-Spock) each one beats the next two
+//! around the cycle. Random neighbor pairs interact by
+//!
+//! - selection: a species empties a neighbor cell of a species it beats,
+//! - reproduction: a species fills an empty neighbor cell with its kind,
+//! - exchange: the two cells swap, which lets species move.
+//!
+//! No species can win everywhere, so the populations chase one another in
+//! rotating spiral waves. The `mobility` rate sets the spirals' size; too
+//! much mixing on a small grid lets one species take over.
+//!
+//! The output field is the local population density (the occupied share of
+//! each 3×3 neighborhood), which dips along the empty wave fronts. The hue
+//! field holds each cell's species, evenly spaced around the hue wheel.
+//!
+//! Each step draws from its own stream derived from the seed and step
+//! number, so runs replay exactly and a checkpoint needs only the grid and
+//! the step count.
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::params::{check_fixed, merge_params, param_f64, param_rng, param_usize};
+use art_engine_core::prng::{derive_seed, Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Value};
+
+/// Default number of species.
+const DEFAULT_SPECIES: usize = 3;
+/// Most species; counts are odd so every species beats as many as beat it.
+const MAX_SPECIES: usize = 7;
+/// Default selection rate.
+const DEFAULT_SELECTION: f64 = 1.0;
+/// Default reproduction rate.
+const DEFAULT_REPRODUCTION: f64 = 1.0;
+/// Default exchange rate.
+const DEFAULT_MOBILITY: f64 = 3.0;
+/// Largest selection, reproduction or exchange rate.
+const MAX_RATE: f64 = 100.0;
+/// Marks an empty cell in the grid.
+const EMPTY: u8 = 0;
+/// Named (name, species, mobility) regimes.
+const PRESETS: &[(&str, usize, f64)] = &[
+    ("spirals", 3, 3.0),
+    ("turbulent", 3, 0.3),
+    ("waves", 3, 12.0),
+    ("lizard-spock", 5, 3.0),
+];
+
+/// Simulation parameters for the rock-paper-scissors model.
+///
+/// Use [`Default`] for three species with equal selection and reproduction
+/// rates, seeded by Xorshift64.
+#[derive(Debug, Clone, Copy)]
+pub struct RpsParams {
+    /// Number of species: 3, 5 or 7 (even counts round up).
+    pub species: usize,
+    /// Rate at which a species empties a neighbor it beats.
+    pub selection: f64,
+    /// Rate at which a species fills an empty neighbor.
+    pub reproduction: f64,
+    /// Rate at which neighbors swap places; larger means bigger spirals.
+    pub mobility: f64,
+    /// PRNG algorithm placing species and choosing interactions.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for RpsParams {
+    fn default() -> Self {
+        Self {
+            species: DEFAULT_SPECIES,
+            selection: DEFAULT_SELECTION,
+            reproduction: DEFAULT_REPRODUCTION,
+            mobility: DEFAULT_MOBILITY,
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl RpsParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each into its range.
+    pub fn from_json(params: &Value) -> Self {
+        Self {
+            species: param_usize(params, "species", DEFAULT_SPECIES),
+            selection: param_f64(params, "selection", DEFAULT_SELECTION),
+            reproduction: param_f64(params, "reproduction", DEFAULT_REPRODUCTION),
+            mobility: param_f64(params, "mobility", DEFAULT_MOBILITY),
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped()
+    }
+
+    /// Returns the params with each value clamped into its range and the
+    /// species count made odd.
+    fn clamped(self) -> Self {
+        let rate = |r: f64| {
+            if r.is_nan() {
+                0.0
+            } else {
+                r.clamp(0.0, MAX_RATE)
+            }
+        };
+        Self {
+            species: self.species.clamp(DEFAULT_SPECIES, MAX_SPECIES) | 1,
+            selection: rate(self.selection),
+            reproduction: rate(self.reproduction),
+            mobility: rate(self.mobility),
+            ..self
+        }
+    }
+}
+
+/// Spatial rock-paper-scissors engine.
+///
+/// The grid holds `0` for an empty cell and `1..=species` for a species.
+pub struct Rps {
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: RpsParams,
+    cells: Vec<u8>,
+    /// Steps taken, which picks each step's PRNG stream.
+    steps: u64,
+    density: Field,
+    hue: Field,
+}
+
+impl Rps {
+    /// Creates a new rock-paper-scissors engine.
+    ///
+    /// Every cell starts empty or holding a species, all equally likely,
+    /// drawn from `seed` and the params' PRNG algorithm. Out-of-range
+    /// params are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: RpsParams,
+    ) -> Result<Self, EngineError> {
+        let density = Field::new(width, height)?;
+        let hue = Field::new(width, height)?;
+        let params = params.clamped();
+        let mut rng = params.rng.build(seed);
+        let cells = (0..width * height)
+            .map(|_| rng.next_usize(params.species + 1) as u8)
+            .collect();
+        let mut engine = Self {
+            width,
+            height,
+            seed,
+            params,
+            cells,
+            steps: 0,
+            density,
+            hue,
+        };
+        engine.update_fields();
+        Ok(engine)
+    }
+
+    /// Creates a rock-paper-scissors engine from a JSON params object.
+    ///
+    /// Extracts `species`, `selection`, `reproduction`, `mobility` and
+    /// `rng`, falling back to defaults for missing keys.
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, RpsParams::from_json(json_params))
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// grid, the density and hue fields, and the density being rebuilt.
+    pub fn memory_estimate(width: usize, height: usize) -> u64 {
+        grid_bytes(width, height, 1 + 3 * 8)
+    }
+
+    /// The grid, row-major: `0` for empty, else the species from 1.
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    /// Returns true if species `a` beats species `b`: `b` is one of the
+    /// `(species - 1) / 2` species following `a` around the cycle.
+    fn beats(&self, a: u8, b: u8) -> bool {
+        if a == EMPTY || b == EMPTY {
+            return false;
+        }
+        let n = self.params.species;
+        let ahead = (usize::from(b) + n - usize::from(a)) % n;
+        (1..=(n - 1) / 2).contains(&ahead)
+    }
+
+    /// Index of the von Neumann neighbor of cell `i` in `direction` (0-3),
+    /// wrapping toroidally.
+    fn neighbor(&self, i: usize, direction: usize) -> usize {
+        let (w, h) = (self.width, self.height);
+        let (x, y) = (i % w, i / w);
+        match direction {
+            0 => y * w + (x + 1) % w,
+            1 => y * w + (x + w - 1) % w,
+            2 => (y + 1) % h * w + x,
+            _ => (y + h - 1) % h * w + x,
+        }
+    }
+
+    /// Recomputes the density and hue fields from the grid.
+    fn update_fields(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let occupied = |x: usize, y: usize| f64::from(u8::from(self.cells[y * w + x] != EMPTY));
+        let density: Vec<f64> = (0..self.cells.len())
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                let rows = [(y + h - 1) % h, y, (y + 1) % h];
+                let cols = [(x + w - 1) % w, x, (x + 1) % w];
+                let sum: f64 = rows
+                    .iter()
+                    .flat_map(|&ny| cols.iter().map(move |&nx| occupied(nx, ny)))
+                    .sum();
+                sum / 9.0
+            })
+            .collect();
+        self.density.data_mut().copy_from_slice(&density);
+        let turn = 1.0 / self.params.species as f64;
+        for (hue, &cell) in self.hue.data_mut().iter_mut().zip(&self.cells) {
+            *hue = match cell {
+                EMPTY => 0.0,
+                species => f64::from(species - 1) * turn,
+            };
+        }
+    }
+}
+
+impl Engine for Rps {
+    /// Runs one sweep: as many random interactions as there are cells, each
+    /// between a random cell and a random von Neumann neighbor, chosen as
+    /// selection, reproduction or exchange in proportion to their rates.
+    fn step(&mut self) -> Result<(), EngineError> {
+        let RpsParams {
+            selection,
+            reproduction,
+            mobility,
+            ..
+        } = self.params;
+        let total = selection + reproduction + mobility;
+        if total > 0.0 {
+            let mut rng = self
+                .params
+                .rng
+                .build(derive_seed(self.seed, &format!("step-{}", self.steps)));
+            for _ in 0..self.cells.len() {
+                let i = pick(rng.as_mut(), self.cells.len());
+                let j = self.neighbor(i, pick(rng.as_mut(), 4));
+                let (a, b) = (self.cells[i], self.cells[j]);
+                let action = rng.next_f64() * total;
+                if action < selection {
+                    if self.beats(a, b) {
+                        self.cells[j] = EMPTY;
+                    }
+                } else if action < selection + reproduction {
+                    if b == EMPTY {
+                        self.cells[j] = a;
+                    }
+                } else {
+                    self.cells.swap(i, j);
+                }
+            }
+        }
+        self.steps += 1;
+        self.update_fields();
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.density
+    }
+
+    fn hue_field(&self) -> Option<&Field> {
+        Some(&self.hue)
+    }
+
+    fn params(&self) -> Value {
+        json!({
+            "species": self.params.species,
+            "selection": self.params.selection,
+            "reproduction": self.params.reproduction,
+            "mobility": self.params.mobility,
+            "rng": self.params.rng.name(),
+        })
+    }
+
+    /// Rates and `rng` can change mid-run; `species` is fixed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["species"])?;
+        self.params = RpsParams::from_json(&merge_params(&current, params)?);
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "species": {
+                "type": "integer",
+                "default": DEFAULT_SPECIES,
+                "min": DEFAULT_SPECIES,
+                "max": MAX_SPECIES,
+                "description": "Number of species in the cycle: 3 (rock-paper-scissors), 5 or 7"
+            },
+            "selection": {
+                "type": "number",
+                "default": DEFAULT_SELECTION,
+                "min": 0.0,
+                "max": MAX_RATE,
+                "description": "Rate at which a species empties a neighbor it beats"
+            },
+            "reproduction": {
+                "type": "number",
+                "default": DEFAULT_REPRODUCTION,
+                "min": 0.0,
+                "max": MAX_RATE,
+                "description": "Rate at which a species fills an empty neighbor"
+            },
+            "mobility": {
+                "type": "number",
+                "default": DEFAULT_MOBILITY,
+                "min": 0.0,
+                "max": MAX_RATE,
+                "description": "Rate at which neighbors swap places; larger means bigger spirals"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG placing species and choosing interactions: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(|&(name, species, mobility)| {
+                let params = json!({ "species": species, "mobility": mobility });
+                (name.to_string(), params)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(self.steps);
+        // Eight cells to a word
+        for chunk in self.cells.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            writer.u64(u64::from_le_bytes(word));
+        }
+        Ok(writer.finish())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let steps = reader.u64()?;
+        let mut cells = Vec::with_capacity(self.cells.len().next_multiple_of(8));
+        for _ in 0..self.cells.len().div_ceil(8) {
+            cells.extend(reader.u64()?.to_le_bytes());
+        }
+        reader.finish()?;
+        cells.truncate(self.cells.len());
+        if let Some(&cell) = cells
+            .iter()
+            .find(|&&c| usize::from(c) > self.params.species)
+        {
+            return Err(EngineError::InvalidState(format!(
+                "cell holds species {cell}, but there are {} species",
+                self.params.species
+            )));
+        }
+        self.cells = cells;
+        self.steps = steps;
+        self.update_fields();
+        Ok(())
+    }
+}
+
+/// Returns a uniform index below `n` from the high bits of one draw.
+///
+/// Successive `next_usize` draws share correlated low bits under
+/// Xorshift64, which would tie each cell to the neighbor it picks and
+/// stripe the grid.
+fn pick(rng: &mut dyn Rng, n: usize) -> usize {
+    ((rng.next_f64() * n as f64) as usize).min(n - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: construct with default params.
+    fn rps(width: usize, height: usize, seed: u64) -> Rps {
+        Rps::new(width, height, seed, RpsParams::default()).unwrap()
+    }
+
+    /// Helper: count of cells holding each value, empty first.
+    fn census(engine: &Rps) -> Vec<usize> {
+        let mut counts = vec![0; engine.params.species + 1];
+        engine
+            .cells()
+            .iter()
+            .for_each(|&c| counts[usize::from(c)] += 1);
+        counts
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_scatters_every_species_and_gaps() {
+        let engine = rps(40, 30, 42);
+        assert_eq!(engine.field().width(), 40);
+        assert_eq!(engine.field().height(), 30);
+        let counts = census(&engine);
+        assert!(counts.iter().all(|&c| c > 250 && c < 350), "{counts:?}");
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = Rps::new(10, 0, 42, RpsParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        let params = RpsParams::from_json(&json!({}));
+        assert_eq!(params.species, DEFAULT_SPECIES);
+        assert_eq!(params.mobility, DEFAULT_MOBILITY);
+        let params = RpsParams::from_json(
+            &json!({"species": 4, "selection": -1.0, "mobility": 1e9, "rng": "splitmix64"}),
+        );
+        assert_eq!(params.species, 5);
+        assert_eq!(params.selection, 0.0);
+        assert_eq!(params.mobility, MAX_RATE);
+        assert_eq!(params.rng, RngAlgorithm::SplitMix64);
+        assert_eq!(RpsParams::from_json(&json!({"species": 2})).species, 3);
+        assert_eq!(RpsParams::from_json(&json!({"species": 9})).species, 7);
+    }
+
+    #[test]
+    fn params_are_all_in_the_schema() {
+        let engine = Rps::from_json(8, 8, 1, &json!({"species": 5})).unwrap();
+        let params = engine.params();
+        assert_eq!(params["species"], json!(5));
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+    }
+
+    // ---- Dominance tests ----
+
+    #[test]
+    fn three_species_dominate_cyclically() {
+        let engine = rps(4, 4, 1);
+        assert!(engine.beats(1, 2) && engine.beats(2, 3) && engine.beats(3, 1));
+        assert!(!engine.beats(2, 1) && !engine.beats(1, 1));
+        assert!(!engine.beats(1, EMPTY) && !engine.beats(EMPTY, 1));
+    }
+
+    #[test]
+    fn five_species_each_beat_the_next_two() {
+        let engine = Rps::from_json(4, 4, 1, &json!({"species": 5})).unwrap();
+        for a in 1..=5u8 {
+            let beaten: Vec<u8> = (1..=5).filter(|&b| engine.beats(a, b)).collect();
+            let expected: Vec<u8> = [a % 5 + 1, (a + 1) % 5 + 1].into();
+            let mut sorted = expected.clone();
+            sorted.sort();
+            assert_eq!(beaten, sorted, "species {a}");
+        }
+    }
+
+    // ---- Determinism tests ----
+
+    #[test]
+    fn same_seed_replays_identically() {
+        let mut a = rps(32, 32, 7);
+        let mut b = rps(32, 32, 7);
+        for _ in 0..5 {
+            a.step().unwrap();
+            b.step().unwrap();
+        }
+        assert_eq!(a.cells(), b.cells());
+        assert_eq!(a.field(), b.field());
+        assert_eq!(a.hue_field(), b.hue_field());
+        assert_ne!(rps(32, 32, 7).cells(), rps(32, 32, 8).cells());
+    }
+
+    #[test]
+    fn restored_state_continues_identically() {
+        let mut original = rps(24, 16, 3);
+        original.step().unwrap();
+        let state = original.save_state().unwrap();
+        let mut resumed = rps(24, 16, 3);
+        resumed.load_state(&state).unwrap();
+        assert_eq!(resumed.field(), original.field());
+        original.step().unwrap();
+        resumed.step().unwrap();
+        assert_eq!(resumed.cells(), original.cells());
+        assert!(rps(8, 8, 3).load_state(&state).is_err());
+        let mut writer = StateWriter::new();
+        writer.u64(0).u64(0).u64(9);
+        assert!(matches!(
+            rps(4, 4, 3).load_state(&writer.finish()),
+            Err(EngineError::InvalidState(_))
+        ));
+    }
+
+    // ---- Dynamics tests ----
+
+    #[test]
+    fn pure_exchange_conserves_every_species() {
+        let mut engine = Rps::from_json(
+            20,
+            20,
+            5,
+            &json!({"selection": 0.0, "reproduction": 0.0, "mobility": 1.0}),
+        )
+        .unwrap();
+        let before = census(&engine);
+        let cells = engine.cells().to_vec();
+        engine.step().unwrap();
+        assert_eq!(census(&engine), before);
+        assert_ne!(engine.cells(), &cells[..]);
+    }
+
+    #[test]
+    fn all_zero_rates_freeze_the_grid() {
+        let mut engine = Rps::from_json(
+            8,
+            8,
+            5,
+            &json!({"selection": 0.0, "reproduction": 0.0, "mobility": 0.0}),
+        )
+        .unwrap();
+        let before = engine.cells().to_vec();
+        engine.step().unwrap();
+        assert_eq!(engine.cells(), &before[..]);
+    }
+
+    #[test]
+    fn species_coexist_on_a_large_grid() {
+        let mut engine = rps(96, 96, 11);
+        for _ in 0..150 {
+            engine.step().unwrap();
+        }
+        let counts = census(&engine);
+        let total: usize = counts.iter().sum();
+        // Empty cells survive only as thin wave fronts
+        assert!(counts[0] < total / 4, "{counts:?}");
+        assert!(counts[1..].iter().all(|&c| c > total / 10), "{counts:?}");
+    }
+
+    // ---- Field tests ----
+
+    #[test]
+    fn fields_show_density_and_species() {
+        let mut engine = Rps::from_json(4, 4, 0, &json!({"species": 5})).unwrap();
+        engine.cells = vec![EMPTY; 16];
+        engine.cells[0] = 1;
+        engine.cells[5] = 3;
+        engine.update_fields();
+        let (density, hue) = (engine.field(), engine.hue_field().unwrap());
+        assert_eq!(density.get(0, 0), 2.0 / 9.0);
+        assert_eq!(density.get(3, 3), 1.0 / 9.0);
+        assert_eq!(density.get(2, 2), 1.0 / 9.0);
+        assert_eq!(density.get(2, 3), 0.0);
+        assert_eq!(hue.get(0, 0), 0.0);
+        assert_eq!(hue.get(1, 1), 0.4);
+    }
+
+    #[test]
+    fn engine_is_object_safe() {
+        let boxed: Box<dyn Engine> = Box::new(rps(8, 8, 1));
+        assert!(boxed.hue_field().is_some());
+        assert_eq!(boxed.field().width(), 8);
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_changes_rates_but_not_species() {
+        let mut engine = rps(16, 16, 42);
+        (0..5).for_each(|_| engine.step().unwrap());
+        let cells = engine.cells().to_vec();
+        engine
+            .set_params(&json!({"mobility": 12.0, "selection": -1.0}))
+            .unwrap();
+        assert_eq!(engine.params.mobility, 12.0);
+        assert_eq!(engine.params.selection, 0.0);
+        assert_eq!(engine.cells(), cells);
+
+        let result = engine.set_params(&json!({"species": 5}));
+        assert!(matches!(result, Err(EngineError::Unsupported(_))));
+        assert_eq!(engine.params.species, 3);
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn fields_stay_in_unit_interval(
+                width in 1usize..16,
+                height in 1usize..16,
+                seed in any::<u64>(),
+                species in 3usize..=MAX_SPECIES,
+                mobility in 0.0f64..20.0,
+            ) {
+                let params = RpsParams { species, mobility, ..RpsParams::default() };
+                let mut engine = Rps::new(width, height, seed, params).unwrap();
+                for _ in 0..3 {
+                    engine.step().unwrap();
+                }
+                let unit = |f: &Field| f.data().iter().all(|v| (0.0..=1.0).contains(v));
+                prop_assert!(unit(engine.field()));
+                prop_assert!(unit(engine.hue_field().unwrap()));
+            }
+        }
+    }
+}
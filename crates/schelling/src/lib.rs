@@ -0,0 +1,607 @@
+#![deny(unsafe_code)]
+//! Schelling segregation model engine.
+//!
+//! Agents of two or more groups live on a toroidal grid with some cells
+//! left vacant. An agent is content when at least `tolerance` of its
+//! occupied Moore neighbors belong to its own group; every step, each
+//! discontented agent moves to a random vacant cell. Even mild preferences
+//! sort a well-mixed grid into large single-group regions within a few
+//! dozen steps.
+//!
+//! The output field is the local group density: for each cell, the mean
+//! group of the occupied cells in its 3×3 neighborhood, scaled to [0, 1].
+//! With two groups that is the share of the second group, so clusters read
+//! as 0 and 1 with soft borders between them.
+//!
+//! The initial layout comes from the seed, and each step draws from its own
+//! stream derived from the seed and step number, so runs replay exactly and
+//! a checkpoint needs only the grid and the step count.
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::params::{check_fixed, merge_params, param_f64, param_rng, param_usize};
+use art_engine_core::prng::{derive_seed, Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Value};
+
+/// Default share of like neighbors an agent needs to stay put.
+const DEFAULT_TOLERANCE: f64 = 0.5;
+/// Default share of cells left vacant.
+const DEFAULT_VACANCY: f64 = 0.1;
+/// Default number of groups.
+const DEFAULT_GROUPS: usize = 2;
+/// Most groups a grid can hold.
+const MAX_GROUPS: usize = 6;
+/// Largest vacancy share; some cells must stay occupied.
+const MAX_VACANCY: f64 = 0.95;
+/// Marks a vacant cell in the grid.
+const VACANT: u8 = 0;
+/// Field value of a cell with no occupied cell around it.
+const EMPTY_DENSITY: f64 = 0.5;
+/// Named (name, tolerance, vacancy, groups) regimes.
+const PRESETS: &[(&str, f64, f64, usize)] = &[
+    ("classic", 0.34, 0.1, 2),
+    ("segregated", 0.6, 0.1, 2),
+    ("crowded", 0.5, 0.03, 2),
+    ("mosaic", 0.4, 0.15, 4),
+];
+
+/// Simulation parameters for the Schelling model.
+///
+/// Use [`Default`] for two groups, half-like tolerance and 10% vacancy,
+/// seeded by Xorshift64.
+#[derive(Debug, Clone, Copy)]
+pub struct SchellingParams {
+    /// Share (0 to 1) of occupied neighbors that must be like an agent for
+    /// it to stay.
+    pub tolerance: f64,
+    /// Share (0 to 0.95) of cells left vacant.
+    pub vacancy: f64,
+    /// Number of groups, 2 to 6.
+    pub groups: usize,
+    /// PRNG algorithm placing agents and choosing moves.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for SchellingParams {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_TOLERANCE,
+            vacancy: DEFAULT_VACANCY,
+            groups: DEFAULT_GROUPS,
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl SchellingParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each into its range.
+    pub fn from_json(params: &Value) -> Self {
+        Self {
+            tolerance: param_f64(params, "tolerance", DEFAULT_TOLERANCE),
+            vacancy: param_f64(params, "vacancy", DEFAULT_VACANCY),
+            groups: param_usize(params, "groups", DEFAULT_GROUPS),
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped()
+    }
+
+    /// Returns the params with each value clamped into its range.
+    fn clamped(self) -> Self {
+        Self {
+            tolerance: self.tolerance.clamp(0.0, 1.0),
+            vacancy: self.vacancy.clamp(0.0, MAX_VACANCY),
+            groups: self.groups.clamp(2, MAX_GROUPS),
+            ..self
+        }
+    }
+}
+
+/// Schelling segregation engine.
+///
+/// The grid holds `0` for a vacant cell and `1..=groups` for an agent.
+pub struct Schelling {
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: SchellingParams,
+    cells: Vec<u8>,
+    /// Steps taken, which picks each step's PRNG stream.
+    steps: u64,
+    density: Field,
+}
+
+impl Schelling {
+    /// Creates a new Schelling engine.
+    ///
+    /// Leaves `vacancy` of the cells empty and splits the rest evenly among
+    /// the groups, scattered by a shuffle drawn from `seed` and the params'
+    /// PRNG algorithm. Out-of-range params are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: SchellingParams,
+    ) -> Result<Self, EngineError> {
+        let density = Field::new(width, height)?;
+        let params = params.clamped();
+        let len = width * height;
+        let vacant = ((len as f64 * params.vacancy).round() as usize).min(len - 1);
+        let occupied = len - vacant;
+        let mut cells: Vec<u8> = (0..len)
+            .map(|i| match i < occupied {
+                true => (i * params.groups / occupied) as u8 + 1,
+                false => VACANT,
+            })
+            .collect();
+        params.rng.build(seed).shuffle(&mut cells);
+        let mut engine = Self {
+            width,
+            height,
+            seed,
+            params,
+            cells,
+            steps: 0,
+            density,
+        };
+        engine.update_density();
+        Ok(engine)
+    }
+
+    /// Creates a Schelling engine from a JSON params object.
+    ///
+    /// Extracts `tolerance`, `vacancy`, `groups` and `rng`, falling back to
+    /// defaults for missing keys.
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, SchellingParams::from_json(json_params))
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// grid, the density field, and each step's lists of movers and
+    /// vacancies.
+    pub fn memory_estimate(width: usize, height: usize) -> u64 {
+        grid_bytes(width, height, 1 + 8 + 2 * 8)
+    }
+
+    /// The grid, row-major: `0` for vacant, else the agent's group from 1.
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    /// Number of agents that would move on the next step.
+    pub fn unhappy_count(&self) -> usize {
+        (0..self.cells.len())
+            .filter(|&i| self.is_unhappy(i))
+            .count()
+    }
+
+    /// Returns true if cell `i` holds an agent with too few like neighbors.
+    fn is_unhappy(&self, i: usize) -> bool {
+        let group = self.cells[i];
+        if group == VACANT {
+            return false;
+        }
+        let (mut like, mut occupied) = (0, 0);
+        for neighbor in self.neighbors(i) {
+            match self.cells[neighbor] {
+                VACANT => {}
+                g => {
+                    occupied += 1;
+                    like += usize::from(g == group);
+                }
+            }
+        }
+        occupied > 0 && (like as f64) < self.params.tolerance * occupied as f64
+    }
+
+    /// Indices of the eight toroidal Moore neighbors of cell `i`.
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> {
+        let (w, h) = (self.width, self.height);
+        let (x, y) = (i % w, i / w);
+        (-1..=1isize)
+            .flat_map(|dy| (-1..=1isize).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .map(move |(dx, dy)| wrap(y, dy, h) * w + wrap(x, dx, w))
+    }
+
+    /// Recomputes the density field from the grid.
+    fn update_density(&mut self) {
+        let scale = 1.0 / (self.params.groups - 1) as f64;
+        let density: Vec<f64> = (0..self.cells.len())
+            .map(|i| {
+                let (sum, count) = std::iter::once(i)
+                    .chain(self.neighbors(i))
+                    .filter(|&n| self.cells[n] != VACANT)
+                    .fold((0.0, 0usize), |(sum, count), n| {
+                        (sum + f64::from(self.cells[n] - 1) * scale, count + 1)
+                    });
+                match count {
+                    0 => EMPTY_DENSITY,
+                    _ => sum / count as f64,
+                }
+            })
+            .collect();
+        self.density.data_mut().copy_from_slice(&density);
+    }
+}
+
+impl Engine for Schelling {
+    /// Moves every agent that is discontented at the start of the step, in
+    /// random order, each to a random vacant cell (vacated cells become
+    /// available to later movers).
+    fn step(&mut self) -> Result<(), EngineError> {
+        let mut rng = self
+            .params
+            .rng
+            .build(derive_seed(self.seed, &format!("step-{}", self.steps)));
+        let mut movers: Vec<usize> = (0..self.cells.len())
+            .filter(|&i| self.is_unhappy(i))
+            .collect();
+        let mut vacancies: Vec<usize> = (0..self.cells.len())
+            .filter(|&i| self.cells[i] == VACANT)
+            .collect();
+        if !vacancies.is_empty() {
+            rng.shuffle(&mut movers);
+            for from in movers {
+                let slot = rng.next_usize(vacancies.len());
+                let to = vacancies[slot];
+                self.cells.swap(from, to);
+                vacancies[slot] = from;
+            }
+        }
+        self.steps += 1;
+        self.update_density();
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.density
+    }
+
+    fn params(&self) -> Value {
+        json!({
+            "tolerance": self.params.tolerance,
+            "vacancy": self.params.vacancy,
+            "groups": self.params.groups,
+            "rng": self.params.rng.name(),
+        })
+    }
+
+    /// `tolerance` and `rng` can change mid-run; `groups` and `vacancy`
+    /// are fixed by the starting grid.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["groups", "vacancy"])?;
+        self.params = SchellingParams::from_json(&merge_params(&current, params)?);
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "tolerance": {
+                "type": "number",
+                "default": DEFAULT_TOLERANCE,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Share of occupied neighbors that must be like an agent for it to stay"
+            },
+            "vacancy": {
+                "type": "number",
+                "default": DEFAULT_VACANCY,
+                "min": 0.0,
+                "max": MAX_VACANCY,
+                "description": "Share of cells left vacant for agents to move into"
+            },
+            "groups": {
+                "type": "integer",
+                "default": DEFAULT_GROUPS,
+                "min": 2,
+                "max": MAX_GROUPS,
+                "description": "Number of groups"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG placing agents and choosing moves: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(|&(name, tolerance, vacancy, groups)| {
+                let params =
+                    json!({ "tolerance": tolerance, "vacancy": vacancy, "groups": groups });
+                (name.to_string(), params)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(self.steps);
+        // Eight cells to a word
+        for chunk in self.cells.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            writer.u64(u64::from_le_bytes(word));
+        }
+        Ok(writer.finish())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let steps = reader.u64()?;
+        let mut cells = Vec::with_capacity(self.cells.len().next_multiple_of(8));
+        for _ in 0..self.cells.len().div_ceil(8) {
+            cells.extend(reader.u64()?.to_le_bytes());
+        }
+        reader.finish()?;
+        cells.truncate(self.cells.len());
+        if let Some(&cell) = cells.iter().find(|&&c| usize::from(c) > self.params.groups) {
+            return Err(EngineError::InvalidState(format!(
+                "cell holds group {cell}, but there are {} groups",
+                self.params.groups
+            )));
+        }
+        self.cells = cells;
+        self.steps = steps;
+        self.update_density();
+        Ok(())
+    }
+}
+
+/// Toroidal coordinate wrap: `(coord + offset) mod size`.
+fn wrap(coord: usize, offset: isize, size: usize) -> usize {
+    ((coord as isize + offset).rem_euclid(size as isize)) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: construct with default params.
+    fn schelling(width: usize, height: usize, seed: u64) -> Schelling {
+        Schelling::new(width, height, seed, SchellingParams::default()).unwrap()
+    }
+
+    /// Helper: mean share of like neighbors over agents with any neighbor.
+    fn like_share(engine: &Schelling) -> f64 {
+        let shares: Vec<f64> = (0..engine.cells.len())
+            .filter(|&i| engine.cells[i] != VACANT)
+            .filter_map(|i| {
+                let occupied: Vec<u8> = engine
+                    .neighbors(i)
+                    .map(|n| engine.cells[n])
+                    .filter(|&c| c != VACANT)
+                    .collect();
+                let like = occupied.iter().filter(|&&c| c == engine.cells[i]).count();
+                (!occupied.is_empty()).then(|| like as f64 / occupied.len() as f64)
+            })
+            .collect();
+        shares.iter().sum::<f64>() / shares.len() as f64
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_places_groups_evenly_around_vacancies() {
+        let engine = schelling(40, 25, 42);
+        assert_eq!(engine.field().width(), 40);
+        assert_eq!(engine.field().height(), 25);
+        let count = |group: u8| engine.cells().iter().filter(|&&c| c == group).count();
+        assert_eq!(count(VACANT), 100);
+        assert_eq!((count(1), count(2)), (450, 450));
+        assert_eq!(count(3), 0);
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = Schelling::new(0, 10, 42, SchellingParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        let params = SchellingParams::from_json(&json!({}));
+        assert_eq!(params.tolerance, DEFAULT_TOLERANCE);
+        assert_eq!(params.groups, DEFAULT_GROUPS);
+        let params = SchellingParams::from_json(
+            &json!({"tolerance": 1.5, "vacancy": 1.0, "groups": 9, "rng": "pcg32"}),
+        );
+        assert_eq!(params.tolerance, 1.0);
+        assert_eq!(params.vacancy, MAX_VACANCY);
+        assert_eq!(params.groups, MAX_GROUPS);
+        assert_eq!(params.rng, RngAlgorithm::Pcg32);
+    }
+
+    #[test]
+    fn params_round_trip_through_json() {
+        let engine = Schelling::from_json(8, 8, 1, &json!({"groups": 3, "vacancy": 0.2})).unwrap();
+        let params = engine.params();
+        assert_eq!(params["groups"], json!(3));
+        assert_eq!(params["vacancy"], json!(0.2));
+        assert_eq!(params["rng"], json!("xorshift64"));
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+    }
+
+    // ---- Determinism tests ----
+
+    #[test]
+    fn same_seed_replays_identically() {
+        let mut a = schelling(32, 32, 7);
+        let mut b = schelling(32, 32, 7);
+        for _ in 0..5 {
+            a.step().unwrap();
+            b.step().unwrap();
+        }
+        assert_eq!(a.cells(), b.cells());
+        assert_eq!(a.field(), b.field());
+        let c = schelling(32, 32, 8);
+        assert_ne!(schelling(32, 32, 7).cells(), c.cells());
+    }
+
+    #[test]
+    fn restored_state_continues_identically() {
+        let mut original = schelling(24, 16, 3);
+        original.step().unwrap();
+        let state = original.save_state().unwrap();
+        let mut restored = schelling(24, 16, 99);
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.field(), original.field());
+        // The step streams come from the seed, so restore onto the same one
+        let mut resumed = schelling(24, 16, 3);
+        resumed.load_state(&state).unwrap();
+        original.step().unwrap();
+        resumed.step().unwrap();
+        assert_eq!(resumed.cells(), original.cells());
+        assert!(schelling(8, 8, 3).load_state(&state).is_err());
+    }
+
+    #[test]
+    fn load_state_rejects_unknown_groups() {
+        let mut engine = schelling(4, 4, 3);
+        let mut writer = StateWriter::new();
+        writer.u64(0).u64(0).u64(5);
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+    }
+
+    // ---- Dynamics tests ----
+
+    #[test]
+    fn steps_conserve_every_group() {
+        let mut engine = Schelling::from_json(30, 30, 5, &json!({"groups": 4})).unwrap();
+        let census = |engine: &Schelling| {
+            let mut counts = [0usize; MAX_GROUPS + 1];
+            engine
+                .cells()
+                .iter()
+                .for_each(|&c| counts[usize::from(c)] += 1);
+            counts
+        };
+        let before = census(&engine);
+        for _ in 0..10 {
+            engine.step().unwrap();
+        }
+        assert_eq!(census(&engine), before);
+    }
+
+    #[test]
+    fn agents_cluster_and_settle() {
+        let mut engine = schelling(48, 48, 11);
+        let (mixed, unhappy) = (like_share(&engine), engine.unhappy_count());
+        assert!((mixed - 0.5).abs() < 0.05, "{mixed}");
+        assert!(unhappy > 0);
+        for _ in 0..60 {
+            engine.step().unwrap();
+        }
+        let sorted = like_share(&engine);
+        assert!(
+            sorted > 0.8,
+            "like share only rose from {mixed} to {sorted}"
+        );
+        assert!(engine.unhappy_count() < unhappy / 10);
+    }
+
+    #[test]
+    fn zero_tolerance_never_moves() {
+        let mut engine = Schelling::from_json(16, 16, 2, &json!({"tolerance": 0.0})).unwrap();
+        let before = engine.cells().to_vec();
+        assert_eq!(engine.unhappy_count(), 0);
+        engine.step().unwrap();
+        assert_eq!(engine.cells(), &before[..]);
+    }
+
+    #[test]
+    fn a_full_grid_cannot_move() {
+        let mut engine = Schelling::from_json(8, 8, 2, &json!({"vacancy": 0.0})).unwrap();
+        let before = engine.cells().to_vec();
+        assert!(!before.contains(&VACANT));
+        engine.step().unwrap();
+        assert_eq!(engine.cells(), &before[..]);
+    }
+
+    // ---- Field tests ----
+
+    #[test]
+    fn density_is_the_local_share_of_the_second_group() {
+        let mut engine = Schelling::from_json(4, 4, 0, &json!({"vacancy": 0.5})).unwrap();
+        engine.cells = vec![VACANT; 16];
+        engine.cells[0] = 1;
+        engine.cells[1] = 2;
+        engine.update_density();
+        let field = engine.field();
+        assert_eq!(field.get(0, 0), 0.5);
+        assert_eq!(field.get(2, 0), 1.0);
+        assert_eq!(field.get(3, 1), 0.0);
+        assert_eq!(field.get(2, 2), EMPTY_DENSITY);
+    }
+
+    #[test]
+    fn engine_is_object_safe() {
+        let boxed: Box<dyn Engine> = Box::new(schelling(8, 8, 1));
+        assert!(boxed.hue_field().is_none());
+        assert_eq!(boxed.field().width(), 8);
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_changes_tolerance_but_not_the_population() {
+        let mut engine = schelling(16, 16, 42);
+        engine.set_params(&json!({"tolerance": 0.0})).unwrap();
+        let cells = engine.cells.clone();
+        engine.step().unwrap();
+        assert_eq!(engine.cells, cells, "nobody is unhappy at tolerance 0");
+
+        for patch in [json!({"groups": 3}), json!({"vacancy": 0.5})] {
+            let result = engine.set_params(&patch);
+            assert!(
+                matches!(result, Err(EngineError::Unsupported(_))),
+                "{patch}"
+            );
+        }
+        assert_eq!(engine.params.tolerance, 0.0);
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn density_stays_in_unit_interval(
+                width in 1usize..20,
+                height in 1usize..20,
+                seed in any::<u64>(),
+                groups in 2usize..=MAX_GROUPS,
+                vacancy in 0.0f64..0.9,
+            ) {
+                let params = SchellingParams { groups, vacancy, ..SchellingParams::default() };
+                let mut engine = Schelling::new(width, height, seed, params).unwrap();
+                for _ in 0..3 {
+                    engine.step().unwrap();
+                }
+                prop_assert!(engine.field().data().iter().all(|v| (0.0..=1.0).contains(v)));
+            }
+        }
+    }
+}
@@ -0,0 +1,1015 @@
+#![deny(unsafe_code)]
+//! Poisson-disk stippling engine.
+//!
+//! Scatters dots so that no two lie closer than a minimum spacing: a
+//! Poisson-disk, or blue-noise, distribution, even but never regular. The
+//! spacing stretches by `1 / sqrt(density)`, so dots crowd where a density
+//! map is high and thin out where it is low, like an engraver's stipple.
+//!
+//! Each step throws darts at a spacing `shrink` times the last, down to the
+//! `spacing` param, and keeps those that fit between the dots already
+//! placed. The set grows progressively denser, and any prefix of
+//! [`Stipple::samples`] is itself blue noise: the `rank` output shades each
+//! dot by its order, which thresholds into a blue-noise dithering mask.
+//!
+//! The `density` param is `{"kind": "uniform"}`, a field-source spec with
+//! scalar values (the noise and SDF kinds), normalized to [0, 1] over the
+//! canvas, or `{"kind": "image", "path": ...}`, whose darkness is the
+//! density. Images are read through the loader given to
+//! [`Stipple::from_json_with_images`].
+//!
+//! Each step draws from its own stream derived from the seed and step
+//! number, so runs replay exactly and a checkpoint needs only the dots and
+//! the step count.
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::color::{linear_to_oklab, srgb_to_linear, Srgb};
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::field_source::{rasterize, DistanceMetric, FieldSourceSpec, WorleyMode};
+use art_engine_core::image::Image;
+use art_engine_core::math;
+use art_engine_core::params::{
+    check_fixed, merge_params, param_bool, param_f64, param_rng, param_string,
+};
+use art_engine_core::prng::{derive_seed, Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Value};
+
+/// Default closest spacing between dots, in pixels.
+const DEFAULT_SPACING: f64 = 4.0;
+/// Closest spacing allowed, in pixels.
+const MIN_SPACING: f64 = 1.0;
+/// Widest spacing allowed, in pixels.
+const MAX_SPACING: f64 = 64.0;
+/// The first step's spacing, as a multiple of the final one.
+const START_FACTOR: f64 = 8.0;
+/// Default spacing multiplier per step.
+const DEFAULT_SHRINK: f64 = 0.8;
+/// Default radius at which a dot fades to half brightness, in pixels.
+const DEFAULT_DOT_RADIUS: f64 = 1.0;
+/// Smallest and largest dot radius, in pixels.
+const DOT_RADIUS_RANGE: (f64, f64) = (0.25, 16.0);
+/// Default exponent applied to the density.
+const DEFAULT_GAMMA: f64 = 1.0;
+/// Smallest and largest density exponent.
+const GAMMA_RANGE: (f64, f64) = (0.2, 5.0);
+/// Densities below this get no dots, which bounds the search for
+/// neighbors at eight times the spacing.
+const MIN_DENSITY: f64 = 1.0 / 64.0;
+/// Darts thrown per step for each spacing-sized square of canvas.
+const ATTEMPTS_PER_CELL: f64 = 4.0;
+/// Named (name, spacing, dot radius, output, density) stipples.
+const PRESETS: &[(&str, f64, f64, &str, DensitySpec)] = &[
+    ("clouds", 3.0, 1.0, "dots", fbm_density(0.01, 4)),
+    (
+        "cells",
+        3.0,
+        1.2,
+        "dots",
+        DensitySpec::Source(FieldSourceSpec::Worley {
+            scale: 0.03,
+            strength: 1.0,
+            seed: 0,
+            mode: WorleyMode::F1,
+            metric: DistanceMetric::Euclidean,
+            period: None,
+        }),
+    ),
+    (
+        "fine-grain",
+        1.5,
+        0.5,
+        "dots",
+        DensitySpec::Source(FieldSourceSpec::Ridged {
+            scale: 0.008,
+            strength: 1.0,
+            seed: 0,
+            octaves: 5,
+            lacunarity: 2.0,
+            gain: 0.5,
+            period: None,
+        }),
+    ),
+    ("dither-mask", 1.0, 0.5, "rank", DensitySpec::Uniform),
+];
+
+/// An fBm density at `scale` with the field source's default strength,
+/// lacunarity and gain.
+const fn fbm_density(scale: f64, octaves: u32) -> DensitySpec {
+    DensitySpec::Source(FieldSourceSpec::Fbm {
+        scale,
+        strength: 1.0,
+        seed: 0,
+        octaves,
+        lacunarity: 2.0,
+        gain: 0.5,
+        period: None,
+    })
+}
+
+/// What the stipple writes into the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Output {
+    /// Every dot at full brightness.
+    #[default]
+    Dots,
+    /// Each dot as bright as it is early: the first full, the last nearly
+    /// dark, so a threshold keeps a blue-noise prefix.
+    Rank,
+}
+
+impl Output {
+    /// Every output, in schema order.
+    pub const ALL: [Output; 2] = [Output::Dots, Output::Rank];
+
+    /// The output's param name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Output::Dots => "dots",
+            Output::Rank => "rank",
+        }
+    }
+
+    /// Looks an output up by [`Output::name`].
+    pub fn from_name(name: &str) -> Option<Output> {
+        Output::ALL.into_iter().find(|output| output.name() == name)
+    }
+}
+
+/// Where the density weighting the dots comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DensitySpec {
+    /// The same density everywhere.
+    Uniform,
+    /// A field source with scalar values, normalized to [0, 1] over the
+    /// canvas.
+    Source(FieldSourceSpec),
+    /// The darkness of the image at this path, stretched over the canvas.
+    Image(String),
+}
+
+impl Default for DensitySpec {
+    /// Soft fBm clouds.
+    fn default() -> Self {
+        fbm_density(0.01, 5)
+    }
+}
+
+impl DensitySpec {
+    /// Parses `{"kind": "uniform"}`, `{"kind": "image", "path": ...}` or a
+    /// field-source spec.
+    ///
+    /// Returns `EngineError::InvalidFieldSource` for anything else, or a
+    /// field source without scalar values (such as a vortex).
+    pub fn from_json(value: &Value) -> Result<Self, EngineError> {
+        match value.get("kind").and_then(Value::as_str) {
+            Some("uniform") => Ok(DensitySpec::Uniform),
+            Some("image") => match value.get("path").and_then(Value::as_str) {
+                Some(path) => Ok(DensitySpec::Image(path.to_string())),
+                None => Err(EngineError::InvalidFieldSource(
+                    "an image density needs a \"path\"".to_string(),
+                )),
+            },
+            kind => {
+                let spec: FieldSourceSpec = serde_json::from_value(value.clone())
+                    .map_err(|e| EngineError::InvalidFieldSource(e.to_string()))?;
+                match spec.build_scalar() {
+                    Some(_) => Ok(DensitySpec::Source(spec)),
+                    None => Err(EngineError::InvalidFieldSource(format!(
+                        "'{}' has no scalar values to use as a density; use a noise or SDF kind",
+                        kind.unwrap_or_default()
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// The spec as JSON, in the form `from_json` reads.
+    pub fn to_json(&self) -> Value {
+        match self {
+            DensitySpec::Uniform => json!({ "kind": "uniform" }),
+            DensitySpec::Source(spec) => json!(spec),
+            DensitySpec::Image(path) => json!({ "kind": "image", "path": path }),
+        }
+    }
+}
+
+/// Simulation parameters for the stippling engine.
+///
+/// Use [`Default`] for 4-pixel spacing over soft fBm clouds, seeded by
+/// Xorshift64.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StippleParams {
+    /// Closest spacing between dots where the density is 1, in pixels.
+    pub spacing: f64,
+    /// Spacing multiplier per step, until it reaches `spacing`.
+    pub shrink: f64,
+    /// Radius at which a dot fades to half brightness, in pixels.
+    pub dot_radius: f64,
+    /// Exponent applied to the density; above 1 deepens contrast.
+    pub gamma: f64,
+    /// Swaps dense and sparse.
+    pub invert: bool,
+    /// What the field shows.
+    pub output: Output,
+    /// Where the density comes from.
+    pub density: DensitySpec,
+    /// PRNG algorithm throwing the darts.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for StippleParams {
+    fn default() -> Self {
+        Self {
+            spacing: DEFAULT_SPACING,
+            shrink: DEFAULT_SHRINK,
+            dot_radius: DEFAULT_DOT_RADIUS,
+            gamma: DEFAULT_GAMMA,
+            invert: false,
+            output: Output::default(),
+            density: DensitySpec::default(),
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl StippleParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each number into its range.
+    ///
+    /// Returns `EngineError::InvalidFieldSource` if `density` is present
+    /// but not a density (see [`DensitySpec::from_json`]).
+    pub fn from_json(params: &Value) -> Result<Self, EngineError> {
+        let density = match params.get("density") {
+            Some(density) => DensitySpec::from_json(density)?,
+            None => DensitySpec::default(),
+        };
+        let output = param_string(params, "output", Output::default().name());
+        Ok(Self {
+            spacing: param_f64(params, "spacing", DEFAULT_SPACING),
+            shrink: param_f64(params, "shrink", DEFAULT_SHRINK),
+            dot_radius: param_f64(params, "dot_radius", DEFAULT_DOT_RADIUS),
+            gamma: param_f64(params, "gamma", DEFAULT_GAMMA),
+            invert: param_bool(params, "invert", false),
+            output: Output::from_name(&output).unwrap_or_default(),
+            density,
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped())
+    }
+
+    /// Returns the params with each number clamped into its range; NaN
+    /// falls back to the default.
+    fn clamped(self) -> Self {
+        let clamp = |v: f64, (min, max): (f64, f64), default: f64| {
+            if v.is_nan() {
+                default
+            } else {
+                v.clamp(min, max)
+            }
+        };
+        Self {
+            spacing: clamp(self.spacing, (MIN_SPACING, MAX_SPACING), DEFAULT_SPACING),
+            shrink: clamp(self.shrink, (0.5, 0.99), DEFAULT_SHRINK),
+            dot_radius: clamp(self.dot_radius, DOT_RADIUS_RANGE, DEFAULT_DOT_RADIUS),
+            gamma: clamp(self.gamma, GAMMA_RANGE, DEFAULT_GAMMA),
+            ..self
+        }
+    }
+}
+
+/// Poisson-disk stippling engine.
+pub struct Stipple {
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: StippleParams,
+    /// Density weighting the spacing, after `invert` and `gamma`.
+    density: Field,
+    /// Dot centers in pixels, in the order they were placed.
+    samples: Vec<(f64, f64)>,
+    /// Steps taken, which picks each step's spacing and PRNG stream.
+    steps: u64,
+    field: Field,
+}
+
+impl Stipple {
+    /// Creates a new stippling engine with no dots yet. Out-of-range params
+    /// are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero,
+    /// and `EngineError::Unsupported` for an image density, which needs
+    /// [`Stipple::with_image`].
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: StippleParams,
+    ) -> Result<Self, EngineError> {
+        let raw = match &params.density {
+            DensitySpec::Uniform => Field::filled(width, height, 1.0)?,
+            DensitySpec::Source(spec) => {
+                let source = spec.build_scalar().ok_or_else(|| {
+                    EngineError::InvalidFieldSource("density has no scalar values".to_string())
+                })?;
+                normalized(rasterize(source.as_ref(), width, height, 0.0)?)
+            }
+            DensitySpec::Image(path) => {
+                return Err(EngineError::Unsupported(format!(
+                    "density image '{path}' needs an image loader"
+                )))
+            }
+        };
+        Self::build(width, height, seed, params, raw)
+    }
+
+    /// Creates a stippling engine whose density is the darkness of `image`,
+    /// stretched over the canvas; transparent pixels count as white.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn with_image(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: StippleParams,
+        image: &Image,
+    ) -> Result<Self, EngineError> {
+        let mut raw = Field::new(width, height)?;
+        let (iw, ih) = (image.width(), image.height());
+        for (i, cell) in raw.data_mut().iter_mut().enumerate() {
+            let ix = ((i % width) * iw / width).min(iw - 1);
+            let iy = ((i / width) * ih / height).min(ih - 1);
+            let [r, g, b, alpha] = image.pixel(ix, iy).unwrap_or([1.0; 4]);
+            let lightness = linear_to_oklab(srgb_to_linear(Srgb { r, g, b })).l;
+            *cell = (1.0 - (alpha * lightness + 1.0 - alpha)).clamp(0.0, 1.0);
+        }
+        Self::build(width, height, seed, params, raw)
+    }
+
+    /// Creates a stippling engine from a JSON params object.
+    ///
+    /// Extracts `spacing`, `shrink`, `dot_radius`, `gamma`, `invert`,
+    /// `output`, `density` and `rng`, falling back to defaults for missing
+    /// keys. Returns `EngineError::Unsupported` for an image density; see
+    /// [`Stipple::from_json_with_images`].
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, StippleParams::from_json(json_params)?)
+    }
+
+    /// Like [`Stipple::from_json`], reading an image density's path with
+    /// `load`.
+    pub fn from_json_with_images(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+        load: &dyn Fn(&str) -> Result<Image, EngineError>,
+    ) -> Result<Self, EngineError> {
+        let params = StippleParams::from_json(json_params)?;
+        match &params.density {
+            DensitySpec::Image(path) => {
+                let image = load(path)?;
+                Self::with_image(width, height, seed, params, &image)
+            }
+            _ => Self::new(width, height, seed, params),
+        }
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// field and density, and the dots with their lookup grid at the
+    /// closest spacing. An image density's decoded image is extra.
+    pub fn memory_estimate(width: usize, height: usize) -> u64 {
+        grid_bytes(width, height, 2 * 8 + 48)
+    }
+
+    /// Dot centers in pixels, in the order they were placed.
+    pub fn samples(&self) -> &[(f64, f64)] {
+        &self.samples
+    }
+
+    /// The density weighting the dots, after `invert` and `gamma`.
+    pub fn density(&self) -> &Field {
+        &self.density
+    }
+
+    /// Finishes construction from the density before `invert` and `gamma`.
+    fn build(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: StippleParams,
+        mut density: Field,
+    ) -> Result<Self, EngineError> {
+        let field = Field::new(width, height)?;
+        let params = params.clamped();
+        for d in density.data_mut() {
+            let oriented = if params.invert { 1.0 - *d } else { *d };
+            *d = math::powf(oriented, params.gamma);
+        }
+        Ok(Self {
+            width,
+            height,
+            seed,
+            params,
+            density,
+            samples: Vec::new(),
+            steps: 0,
+            field,
+        })
+    }
+
+    /// Spacing where the density is 1 during step `step`.
+    fn spacing_at(&self, step: u64) -> f64 {
+        let start = self.params.spacing * START_FACTOR;
+        let shrunk = start * math::powi(self.params.shrink, step.min(i32::MAX as u64) as i32);
+        shrunk.max(self.params.spacing)
+    }
+
+    /// Redraws every dot into the field.
+    fn update_field(&mut self) {
+        self.field.data_mut().fill(0.0);
+        let count = self.samples.len() as f64;
+        for (i, &center) in self.samples.iter().enumerate() {
+            let brightness = match self.params.output {
+                Output::Dots => 1.0,
+                Output::Rank => 1.0 - i as f64 / count,
+            };
+            splat(&mut self.field, center, self.params.dot_radius, brightness);
+        }
+    }
+}
+
+/// Rescales `field` to span [0, 1]; a flat field becomes all 1.
+fn normalized(mut field: Field) -> Field {
+    let (min, max) = field
+        .data()
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let range = max - min;
+    for v in field.data_mut() {
+        *v = if range > 1e-12 {
+            (*v - min) / range
+        } else {
+            1.0
+        };
+    }
+    field
+}
+
+/// Adds a soft dot that fades to half `brightness` at `radius`, keeping the
+/// brighter value where dots overlap.
+fn splat(field: &mut Field, (cx, cy): (f64, f64), radius: f64, brightness: f64) {
+    let (w, h) = (field.width(), field.height());
+    let reach = 3.0 * radius;
+    let span = |c: f64, size: usize| {
+        let first = (c - reach).floor().max(0.0) as usize;
+        let last = ((c + reach).ceil().max(0.0) as usize).min(size);
+        first..last
+    };
+    let data = field.data_mut();
+    for y in span(cy, h) {
+        for x in span(cx, w) {
+            let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+            let value = brightness * (-(dx * dx + dy * dy) / (radius * radius)).exp2();
+            let cell = &mut data[y * w + x];
+            *cell = cell.max(value);
+        }
+    }
+}
+
+/// Buckets dots by square cells for neighbor queries.
+struct Grid {
+    cell: f64,
+    columns: usize,
+    rows: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    /// A grid of `cell`-sized buckets over a `width`×`height` canvas,
+    /// holding `samples`.
+    fn new(width: usize, height: usize, cell: f64, samples: &[(f64, f64)]) -> Self {
+        let columns = (width as f64 / cell).ceil().max(1.0) as usize;
+        let rows = (height as f64 / cell).ceil().max(1.0) as usize;
+        let mut grid = Self {
+            cell,
+            columns,
+            rows,
+            buckets: vec![Vec::new(); columns * rows],
+        };
+        for (i, &p) in samples.iter().enumerate() {
+            grid.insert(i, p);
+        }
+        grid
+    }
+
+    /// Bucket coordinates of `p`.
+    fn locate(&self, (x, y): (f64, f64)) -> (usize, usize) {
+        let column = ((x / self.cell) as usize).min(self.columns - 1);
+        let row = ((y / self.cell) as usize).min(self.rows - 1);
+        (column, row)
+    }
+
+    fn insert(&mut self, i: usize, p: (f64, f64)) {
+        let (column, row) = self.locate(p);
+        self.buckets[row * self.columns + column].push(i);
+    }
+
+    /// Returns true if no sample lies within `radius` of `p`.
+    fn is_clear(&self, p: (f64, f64), radius: f64, samples: &[(f64, f64)]) -> bool {
+        let (column, row) = self.locate(p);
+        let reach = (radius / self.cell).ceil() as usize;
+        let columns = column.saturating_sub(reach)..(column + reach + 1).min(self.columns);
+        let rows = row.saturating_sub(reach)..(row + reach + 1).min(self.rows);
+        rows.flat_map(|r| columns.clone().map(move |c| r * self.columns + c))
+            .flat_map(|bucket| &self.buckets[bucket])
+            .all(|&i| {
+                let (dx, dy) = (samples[i].0 - p.0, samples[i].1 - p.1);
+                dx * dx + dy * dy >= radius * radius
+            })
+    }
+}
+
+impl Engine for Stipple {
+    /// Throws darts at this step's spacing, stretched by `1 / sqrt(density)`
+    /// at each dart, and keeps those clear of every dot placed so far.
+    fn step(&mut self) -> Result<(), EngineError> {
+        let spacing = self.spacing_at(self.steps);
+        let (w, h) = (self.width as f64, self.height as f64);
+        let mut grid = Grid::new(self.width, self.height, spacing, &self.samples);
+        let mut rng = self
+            .params
+            .rng
+            .build(derive_seed(self.seed, &format!("step-{}", self.steps)));
+        let attempts = (ATTEMPTS_PER_CELL * w * h / (spacing * spacing)).ceil() as usize;
+        for _ in 0..attempts {
+            let p = (rng.next_f64() * w, rng.next_f64() * h);
+            let cell = (p.1 as usize).min(self.height - 1) * self.width
+                + (p.0 as usize).min(self.width - 1);
+            let density = self.density.data()[cell];
+            if density < MIN_DENSITY {
+                continue;
+            }
+            if grid.is_clear(p, spacing / density.sqrt(), &self.samples) {
+                grid.insert(self.samples.len(), p);
+                self.samples.push(p);
+            }
+        }
+        self.steps += 1;
+        self.update_field();
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.field
+    }
+
+    fn params(&self) -> Value {
+        json!({
+            "spacing": self.params.spacing,
+            "shrink": self.params.shrink,
+            "dot_radius": self.params.dot_radius,
+            "gamma": self.params.gamma,
+            "invert": self.params.invert,
+            "output": self.params.output.name(),
+            "density": self.params.density.to_json(),
+            "rng": self.params.rng.name(),
+        })
+    }
+
+    /// `spacing`, `shrink` and `rng` apply from the next step, and
+    /// `dot_radius` and `output` redraw the dots. The density (`density`,
+    /// `invert` and `gamma`) is fixed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["density", "invert", "gamma"])?;
+        self.params = StippleParams::from_json(&merge_params(&current, params)?)?;
+        self.update_field();
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "spacing": {
+                "type": "number",
+                "default": DEFAULT_SPACING,
+                "min": MIN_SPACING,
+                "max": MAX_SPACING,
+                "description": "Closest spacing between dots where the density is 1, in pixels"
+            },
+            "shrink": {
+                "type": "number",
+                "default": DEFAULT_SHRINK,
+                "min": 0.5,
+                "max": 0.99,
+                "description": "Spacing multiplier per step, from 8x the spacing down to it"
+            },
+            "dot_radius": {
+                "type": "number",
+                "default": DEFAULT_DOT_RADIUS,
+                "min": DOT_RADIUS_RANGE.0,
+                "max": DOT_RADIUS_RANGE.1,
+                "description": "Radius at which a dot fades to half brightness, in pixels"
+            },
+            "gamma": {
+                "type": "number",
+                "default": DEFAULT_GAMMA,
+                "min": GAMMA_RANGE.0,
+                "max": GAMMA_RANGE.1,
+                "description": "Exponent applied to the density; above 1 deepens contrast"
+            },
+            "invert": {
+                "type": "boolean",
+                "default": false,
+                "description": "Swap dense and sparse"
+            },
+            "output": {
+                "type": "string",
+                "default": Output::default().name(),
+                "options": Output::ALL.map(Output::name),
+                "description": "dots, or rank to shade dots by order for a blue-noise dithering mask"
+            },
+            "density": {
+                "type": "object",
+                "default": DensitySpec::default().to_json(),
+                "description": "{\"kind\": \"uniform\"}, {\"kind\": \"image\", \"path\": ...} (darkness), or a noise or SDF field-source spec"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG throwing the darts: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(|(name, spacing, dot_radius, output, density)| {
+                let params = json!({
+                    "spacing": spacing,
+                    "dot_radius": dot_radius,
+                    "output": output,
+                    "density": density.to_json(),
+                });
+                (name.to_string(), params)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(self.steps).u64(self.samples.len() as u64);
+        for &(x, y) in &self.samples {
+            writer.f64(x).f64(y);
+        }
+        Ok(writer.finish())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let steps = reader.u64()?;
+        let count = reader.u64()?;
+        let (w, h) = (self.width as f64, self.height as f64);
+        // Each dot takes 16 bytes, so a count beyond the state is corrupt
+        if count > (state.len() / 16) as u64 {
+            return Err(EngineError::InvalidState(format!(
+                "{count} dots do not fit in {} bytes",
+                state.len()
+            )));
+        }
+        let mut samples = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (x, y) = (reader.f64()?, reader.f64()?);
+            if !((0.0..w).contains(&x) && (0.0..h).contains(&y)) {
+                return Err(EngineError::InvalidState(format!(
+                    "dot ({x}, {y}) is outside the {}x{} canvas",
+                    self.width, self.height
+                )));
+            }
+            samples.push((x, y));
+        }
+        reader.finish()?;
+        self.steps = steps;
+        self.samples = samples;
+        self.update_field();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: params with a uniform density.
+    fn uniform() -> StippleParams {
+        StippleParams {
+            density: DensitySpec::Uniform,
+            ..StippleParams::default()
+        }
+    }
+
+    /// Helper: an engine stepped `steps` times.
+    fn stepped(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: StippleParams,
+        steps: usize,
+    ) -> Stipple {
+        let mut engine = Stipple::new(width, height, seed, params).unwrap();
+        for _ in 0..steps {
+            engine.step().unwrap();
+        }
+        engine
+    }
+
+    /// Helper: the closest distance between two samples.
+    fn closest_pair(samples: &[(f64, f64)]) -> f64 {
+        let mut closest = f64::INFINITY;
+        for (i, a) in samples.iter().enumerate() {
+            for b in &samples[i + 1..] {
+                closest = closest.min((a.0 - b.0).hypot(a.1 - b.1));
+            }
+        }
+        closest
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_starts_without_dots() {
+        let engine = Stipple::new(20, 10, 1, StippleParams::default()).unwrap();
+        assert!(engine.samples().is_empty());
+        assert_eq!(engine.field().width(), 20);
+        assert!(engine.field().data().iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = Stipple::new(0, 10, 1, StippleParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        let params = StippleParams::from_json(&json!({})).unwrap();
+        assert_eq!(params, StippleParams::default());
+        let params = StippleParams::from_json(&json!({
+            "spacing": 0.1,
+            "shrink": 2.0,
+            "dot_radius": 100.0,
+            "invert": true,
+            "output": "rank",
+            "density": {"kind": "uniform"},
+            "rng": "pcg32"
+        }))
+        .unwrap();
+        assert_eq!(params.spacing, MIN_SPACING);
+        assert_eq!(params.shrink, 0.99);
+        assert_eq!(params.dot_radius, DOT_RADIUS_RANGE.1);
+        assert!(params.invert);
+        assert_eq!(params.output, Output::Rank);
+        assert_eq!(params.density, DensitySpec::Uniform);
+        assert_eq!(params.rng, RngAlgorithm::Pcg32);
+    }
+
+    #[test]
+    fn densities_must_have_scalar_values() {
+        for bad in [
+            json!({"kind": "vortex"}),
+            json!({"kind": "no-such-kind"}),
+            json!({"kind": "image"}),
+        ] {
+            let result = StippleParams::from_json(&json!({ "density": bad }));
+            assert!(
+                matches!(result, Err(EngineError::InvalidFieldSource(_))),
+                "{bad}"
+            );
+        }
+        let sdf =
+            json!({"kind": "sdf", "shape": {"kind": "circle", "x": 5.0, "y": 5.0, "radius": 3.0}});
+        let params = StippleParams::from_json(&json!({ "density": sdf })).unwrap();
+        assert!(matches!(params.density, DensitySpec::Source(_)));
+    }
+
+    #[test]
+    fn image_densities_need_a_loader() {
+        let params = json!({"density": {"kind": "image", "path": "in.png"}});
+        let result = Stipple::from_json(8, 8, 1, &params);
+        assert!(matches!(result, Err(EngineError::Unsupported(_))));
+
+        let white = |_: &str| {
+            Image::filled(
+                2,
+                2,
+                Srgb {
+                    r: 1.0,
+                    g: 1.0,
+                    b: 1.0,
+                },
+                1.0,
+            )
+        };
+        let engine = Stipple::from_json_with_images(8, 8, 1, &params, &white).unwrap();
+        assert!(engine.density().data().iter().all(|&d| d < 1e-6));
+        assert_eq!(engine.params()["density"], params["density"]);
+
+        let missing = |path: &str| Err(EngineError::Io(path.to_string()));
+        let result = Stipple::from_json_with_images(8, 8, 1, &params, &missing);
+        assert!(matches!(result, Err(EngineError::Io(_))));
+    }
+
+    #[test]
+    fn params_round_trip_through_json() {
+        let engine = Stipple::from_json(8, 8, 1, &json!({"spacing": 2.5, "gamma": 2.0})).unwrap();
+        let params = engine.params();
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+        let rebuilt = StippleParams::from_json(&params).unwrap();
+        assert_eq!(rebuilt, engine.params);
+    }
+
+    // ---- Density tests ----
+
+    #[test]
+    fn source_densities_span_the_unit_interval() {
+        let engine = Stipple::new(32, 32, 1, StippleParams::default()).unwrap();
+        let data = engine.density().data();
+        let max = data.iter().cloned().fold(f64::MIN, f64::max);
+        let min = data.iter().cloned().fold(f64::MAX, f64::min);
+        assert!(
+            (max - 1.0).abs() < 1e-12 && min.abs() < 1e-12,
+            "{min}..{max}"
+        );
+    }
+
+    #[test]
+    fn invert_and_gamma_reshape_the_density() {
+        let base = Stipple::new(16, 16, 1, StippleParams::default()).unwrap();
+        let params = StippleParams {
+            invert: true,
+            gamma: 2.0,
+            ..StippleParams::default()
+        };
+        let reshaped = Stipple::new(16, 16, 1, params).unwrap();
+        for (a, b) in base.density().data().iter().zip(reshaped.density().data()) {
+            assert!((b - (1.0 - a).powi(2)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn dark_image_areas_get_the_dots() {
+        // Left half black, right half white
+        let image = Image::from_rgba8(2, 1, &[0, 0, 0, 255, 255, 255, 255, 255]).unwrap();
+        let mut engine = Stipple::with_image(64, 32, 3, uniform(), &image).unwrap();
+        for _ in 0..15 {
+            engine.step().unwrap();
+        }
+        assert!(engine.samples().len() > 30);
+        assert!(engine.samples().iter().all(|&(x, _)| x < 32.0));
+    }
+
+    // ---- Sampling tests ----
+
+    #[test]
+    fn dots_keep_the_minimum_spacing() {
+        let engine = stepped(48, 48, 7, uniform(), 20);
+        assert!(engine.samples().len() > 50);
+        assert!(closest_pair(engine.samples()) >= DEFAULT_SPACING);
+    }
+
+    #[test]
+    fn dots_grow_denser_and_keep_their_order() {
+        let mut engine = Stipple::new(48, 48, 7, uniform()).unwrap();
+        engine.step().unwrap();
+        let first = engine.samples().to_vec();
+        assert!(closest_pair(&first) >= DEFAULT_SPACING * START_FACTOR);
+        for _ in 0..5 {
+            engine.step().unwrap();
+        }
+        assert!(engine.samples().len() > first.len());
+        assert_eq!(&engine.samples()[..first.len()], first.as_slice());
+    }
+
+    #[test]
+    fn same_seed_same_dots() {
+        let a = stepped(24, 24, 9, StippleParams::default(), 5);
+        let b = stepped(24, 24, 9, StippleParams::default(), 5);
+        let c = stepped(24, 24, 10, StippleParams::default(), 5);
+        assert_eq!(a.samples(), b.samples());
+        assert_ne!(a.samples(), c.samples());
+    }
+
+    // ---- Field tests ----
+
+    #[test]
+    fn dots_are_bright_at_their_centers() {
+        let engine = stepped(32, 32, 2, uniform(), 3);
+        let field = engine.field();
+        for &(x, y) in engine.samples() {
+            assert!(field.get(x as isize, y as isize) > 0.4);
+        }
+    }
+
+    #[test]
+    fn rank_output_dims_later_dots() {
+        let params = StippleParams {
+            output: Output::Rank,
+            dot_radius: 0.25,
+            ..uniform()
+        };
+        let engine = stepped(32, 32, 2, params, 10);
+        let at = |&(x, y): &(f64, f64)| engine.field().get(x as isize, y as isize);
+        let samples = engine.samples();
+        assert!(at(&samples[0]) > at(samples.last().unwrap()));
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_redraws_dots_but_keeps_the_density() {
+        let mut engine = stepped(32, 32, 2, uniform(), 3);
+        let samples = engine.samples().to_vec();
+        let lit = |e: &Stipple| e.field().data().iter().filter(|&&v| v > 0.1).count();
+        let before = lit(&engine);
+        engine.set_params(&json!({"dot_radius": 2.0})).unwrap();
+        assert_eq!(engine.samples(), samples);
+        assert!(lit(&engine) > before);
+
+        for patch in [json!({"gamma": 2.0}), json!({"invert": true})] {
+            let result = engine.set_params(&patch);
+            assert!(
+                matches!(result, Err(EngineError::Unsupported(_))),
+                "{patch}"
+            );
+        }
+        engine
+            .set_params(&json!({"density": {"kind": "uniform"}}))
+            .unwrap();
+    }
+
+    // ---- State tests ----
+
+    #[test]
+    fn state_round_trips() {
+        let mut engine = stepped(24, 24, 4, uniform(), 4);
+        let state = engine.save_state().unwrap();
+        let mut restored = Stipple::new(24, 24, 4, uniform()).unwrap();
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.samples(), engine.samples());
+        assert_eq!(restored.field().data(), engine.field().data());
+        engine.step().unwrap();
+        restored.step().unwrap();
+        assert_eq!(restored.samples(), engine.samples());
+    }
+
+    #[test]
+    fn load_state_rejects_dots_off_the_canvas() {
+        let mut writer = StateWriter::new();
+        writer.u64(1).u64(1).f64(30.0).f64(2.0);
+        let mut engine = Stipple::new(24, 24, 4, uniform()).unwrap();
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+        let mut writer = StateWriter::new();
+        writer.u64(1).u64(u64::MAX);
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn fields_stay_in_unit_interval(
+                width in 1usize..24,
+                height in 1usize..24,
+                seed in any::<u64>(),
+                spacing in MIN_SPACING..8.0,
+                rank in any::<bool>(),
+            ) {
+                let output = if rank { Output::Rank } else { Output::Dots };
+                let params = StippleParams { spacing, output, ..StippleParams::default() };
+                let engine = stepped(width, height, seed, params, 4);
+                let unit = engine.field().data().iter().all(|v| (0.0..=1.0).contains(v));
+                prop_assert!(unit);
+            }
+        }
+    }
+}
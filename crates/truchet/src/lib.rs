@@ -0,0 +1,773 @@
+#![deny(unsafe_code)]
+//! Truchet tiling engine, with multi-scale subdivision.
+//!
+//! The canvas is covered by square tiles of `tile_size` pixels, each drawn
+//! in one `style` at a randomly chosen orientation:
+//!
+//! - `quarter-circle`: a disc filling the tile from one corner;
+//! - `diagonal`: a line from corner to corner, the "10 PRINT" maze;
+//! - `smith`: two quarter-circle arcs joining edge midpoints (Smith tiles),
+//!   which link into continuous meanders;
+//! - `triangles`: Truchet's original half-filled square.
+//!
+//! Each step splits every tile of the last level, with chance `subdivide`,
+//! into four half-size tiles, down to `min_tile`, so the pattern reveals
+//! finer detail step by step. With `alternate`, every other level swaps
+//! light and dark so small tiles read on top of large ones, as in
+//! Christopher Carlson's multi-scale Truchet patterns.
+//!
+//! Each tile's orientation and split come from a stream derived from the
+//! seed, level and tile position, so a tile looks the same whichever step
+//! reveals it and a checkpoint needs only the step count.
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::math;
+use art_engine_core::params::{merge_params, param_bool, param_f64, param_rng, param_string};
+use art_engine_core::prng::{derive_seed, Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Value};
+
+/// Default top-level tile size, in pixels.
+const DEFAULT_TILE_SIZE: f64 = 64.0;
+/// Default smallest tile size, in pixels.
+const DEFAULT_MIN_TILE: f64 = 8.0;
+/// Smallest and largest tile sizes, in pixels.
+const TILE_RANGE: (f64, f64) = (2.0, 512.0);
+/// Default chance that a tile splits into four.
+const DEFAULT_SUBDIVIDE: f64 = 0.5;
+/// Default stroke width, as a fraction of the tile size.
+const DEFAULT_LINE_WIDTH: f64 = 0.12;
+/// Thinnest and thickest strokes, as a fraction of the tile size.
+const LINE_WIDTH_RANGE: (f64, f64) = (0.01, 0.5);
+/// Named (name, style, tile size, min tile, subdivide, alternate) tilings.
+const PRESETS: &[(&str, Style, f64, f64, f64, bool)] = &[
+    ("maze", Style::Diagonal, 16.0, 16.0, 0.0, false),
+    ("meanders", Style::Smith, 32.0, 32.0, 0.0, false),
+    ("multiscale-smith", Style::Smith, 64.0, 8.0, 0.5, true),
+    (
+        "quarter-circles",
+        Style::QuarterCircle,
+        64.0,
+        8.0,
+        0.4,
+        true,
+    ),
+    ("shards", Style::Triangles, 64.0, 4.0, 0.6, false),
+];
+
+/// What each tile draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// A disc of the tile's size centered on one corner.
+    QuarterCircle,
+    /// A line from corner to corner.
+    Diagonal,
+    /// Two quarter-circle arcs joining edge midpoints.
+    #[default]
+    Smith,
+    /// The half of the tile on one side of a diagonal, filled.
+    Triangles,
+}
+
+impl Style {
+    /// Every style, in schema order.
+    pub const ALL: [Style; 4] = [
+        Style::QuarterCircle,
+        Style::Diagonal,
+        Style::Smith,
+        Style::Triangles,
+    ];
+
+    /// The style's param name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::QuarterCircle => "quarter-circle",
+            Style::Diagonal => "diagonal",
+            Style::Smith => "smith",
+            Style::Triangles => "triangles",
+        }
+    }
+
+    /// Looks a style up by [`Style::name`].
+    pub fn from_name(name: &str) -> Option<Style> {
+        Style::ALL.into_iter().find(|style| style.name() == name)
+    }
+
+    /// Distinct orientations of the style's tile.
+    fn orientations(self) -> u8 {
+        match self {
+            Style::Diagonal | Style::Smith => 2,
+            Style::QuarterCircle | Style::Triangles => 4,
+        }
+    }
+
+    /// Ink coverage at (`u`, `v`) in a tile of `size` pixels, where `u` and
+    /// `v` run over [0, 1] and orientation `o` mirrors the tile to put its
+    /// corner in any of the four. Edges are antialiased over one pixel.
+    fn coverage(self, u: f64, v: f64, o: u8, size: f64, half_width: f64) -> f64 {
+        // Mirror the tile so orientation 0's corner is at the origin
+        let (u, v) = match o {
+            0 => (u, v),
+            1 => (1.0 - u, v),
+            2 => (1.0 - u, 1.0 - v),
+            _ => (u, 1.0 - v),
+        };
+        let fill = |signed: f64| (0.5 - signed * size).clamp(0.0, 1.0);
+        let line = |distance: f64| (half_width + 0.5 - distance * size).clamp(0.0, 1.0);
+        match self {
+            Style::QuarterCircle => fill(math::hypot(u, v) - 1.0),
+            Style::Diagonal => line((u - v).abs() / std::f64::consts::SQRT_2),
+            Style::Smith => {
+                let near = (math::hypot(u, v) - 0.5).abs();
+                let far = (math::hypot(1.0 - u, 1.0 - v) - 0.5).abs();
+                line(near.min(far))
+            }
+            Style::Triangles => fill((u + v - 1.0) / std::f64::consts::SQRT_2),
+        }
+    }
+}
+
+/// Simulation parameters for the Truchet engine.
+///
+/// Use [`Default`] for multi-scale Smith tiles from 64 down to 8 pixels,
+/// seeded by Xorshift64.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TruchetParams {
+    /// What each tile draws.
+    pub style: Style,
+    /// Top-level tile size, in pixels.
+    pub tile_size: f64,
+    /// Smallest tile size, in pixels; tiles split while their halves are at
+    /// least this big.
+    pub min_tile: f64,
+    /// Chance that a tile splits into four.
+    pub subdivide: f64,
+    /// Stroke width of `diagonal` and `smith` tiles, as a fraction of the
+    /// tile size.
+    pub line_width: f64,
+    /// Swaps light and dark on every other level.
+    pub alternate: bool,
+    /// PRNG algorithm for tile choices.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for TruchetParams {
+    fn default() -> Self {
+        Self {
+            style: Style::default(),
+            tile_size: DEFAULT_TILE_SIZE,
+            min_tile: DEFAULT_MIN_TILE,
+            subdivide: DEFAULT_SUBDIVIDE,
+            line_width: DEFAULT_LINE_WIDTH,
+            alternate: true,
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl TruchetParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each into its range. An unknown `style` falls back to
+    /// `smith`.
+    pub fn from_json(params: &Value) -> Self {
+        let style = param_string(params, "style", Style::default().name());
+        Self {
+            style: Style::from_name(&style).unwrap_or_default(),
+            tile_size: param_f64(params, "tile_size", DEFAULT_TILE_SIZE),
+            min_tile: param_f64(params, "min_tile", DEFAULT_MIN_TILE),
+            subdivide: param_f64(params, "subdivide", DEFAULT_SUBDIVIDE),
+            line_width: param_f64(params, "line_width", DEFAULT_LINE_WIDTH),
+            alternate: param_bool(params, "alternate", true),
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped()
+    }
+
+    /// Returns the params with each clamped into its range; NaN falls back
+    /// to the default. `min_tile` never exceeds `tile_size`.
+    fn clamped(self) -> Self {
+        let clamp = |v: f64, (min, max): (f64, f64), default: f64| {
+            if v.is_nan() {
+                default
+            } else {
+                v.clamp(min, max)
+            }
+        };
+        let tile_size = clamp(self.tile_size, TILE_RANGE, DEFAULT_TILE_SIZE);
+        Self {
+            tile_size,
+            min_tile: clamp(self.min_tile, TILE_RANGE, DEFAULT_MIN_TILE).min(tile_size),
+            subdivide: clamp(self.subdivide, (0.0, 1.0), DEFAULT_SUBDIVIDE),
+            line_width: clamp(self.line_width, LINE_WIDTH_RANGE, DEFAULT_LINE_WIDTH),
+            ..self
+        }
+    }
+
+    /// The deepest level whose tiles are still at least `min_tile`.
+    fn max_level(&self) -> u32 {
+        let mut level = 0;
+        while self.tile_size / 2f64.powi(level as i32 + 1) >= self.min_tile {
+            level += 1;
+        }
+        level
+    }
+}
+
+/// A placed tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile {
+    /// Column among tiles of its level, from the left edge.
+    pub column: u64,
+    /// Row among tiles of its level, from the top edge.
+    pub row: u64,
+    /// Subdivision depth; 0 for top-level tiles.
+    pub level: u32,
+    /// Orientation: which mirror image of the style's tile is drawn.
+    pub orientation: u8,
+    /// Whether the tile splits when the next level is revealed.
+    splits: bool,
+}
+
+/// Truchet tiling engine.
+pub struct Truchet {
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: TruchetParams,
+    /// The leaves of the subdivision, each covering its square exactly once.
+    tiles: Vec<Tile>,
+    /// Levels revealed past the top one.
+    level: u32,
+    field: Field,
+}
+
+impl Truchet {
+    /// Creates a new engine showing the top-level tiles. Out-of-range
+    /// params are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: TruchetParams,
+    ) -> Result<Self, EngineError> {
+        let field = Field::new(width, height)?;
+        let params = params.clamped();
+        let columns = (width as f64 / params.tile_size).ceil() as u64;
+        let rows = (height as f64 / params.tile_size).ceil() as u64;
+        let mut engine = Self {
+            width,
+            height,
+            seed,
+            params,
+            tiles: Vec::new(),
+            level: 0,
+            field,
+        };
+        engine.tiles = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| engine.tile(column, row, 0))
+            .collect();
+        engine.update_field();
+        Ok(engine)
+    }
+
+    /// Creates an engine from a JSON params object.
+    ///
+    /// Extracts `style`, `tile_size`, `min_tile`, `subdivide`,
+    /// `line_width`, `alternate` and `rng`, falling back to defaults for
+    /// missing keys.
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, TruchetParams::from_json(json_params))
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// field, plus tiles as small as 2 pixels.
+    pub fn memory_estimate(width: usize, height: usize) -> u64 {
+        grid_bytes(width, height, 8 + std::mem::size_of::<Tile>() as u64 / 4)
+    }
+
+    /// The visible tiles.
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    /// Levels revealed past the top one.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// The tile at `column`, `row` of `level`, with its seeded choices.
+    fn tile(&self, column: u64, row: u64, level: u32) -> Tile {
+        let stream = derive_seed(self.seed, &format!("tile-{level}-{column}-{row}"));
+        let mut rng = self.params.rng.build(stream);
+        let orientations = self.params.style.orientations();
+        let orientation = ((rng.next_f64() * orientations as f64) as u8).min(orientations - 1);
+        Tile {
+            column,
+            row,
+            level,
+            orientation,
+            splits: rng.next_f64() < self.params.subdivide,
+        }
+    }
+
+    /// Pixel size of tiles at `level`.
+    fn size_at(&self, level: u32) -> f64 {
+        self.params.tile_size / 2f64.powi(level as i32)
+    }
+
+    /// Splits the deepest tiles that chose to, revealing the next level.
+    /// Returns false once at `min_tile`.
+    fn reveal(&mut self) -> bool {
+        if self.level >= self.params.max_level() {
+            return false;
+        }
+        let next = self.level + 1;
+        let tiles = std::mem::take(&mut self.tiles);
+        let (w, h) = (self.width as f64, self.height as f64);
+        let size = self.size_at(next);
+        for tile in tiles {
+            if tile.level != self.level || !tile.splits {
+                self.tiles.push(tile);
+                continue;
+            }
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let (column, row) = (tile.column * 2 + dx, tile.row * 2 + dy);
+                // Quarters entirely off the canvas are dropped
+                if (column as f64) * size < w && (row as f64) * size < h {
+                    self.tiles.push(self.tile(column, row, next));
+                }
+            }
+        }
+        self.level = next;
+        true
+    }
+
+    /// Draws every tile into the field.
+    fn update_field(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let style = self.params.style;
+        for tile in &self.tiles {
+            let size = self.size_at(tile.level);
+            let half_width = self.params.line_width * size / 2.0;
+            let flip = self.params.alternate && tile.level % 2 == 1;
+            let (x0, y0) = (tile.column as f64 * size, tile.row as f64 * size);
+            let span = |start: f64, limit: usize| {
+                let first = (start.round().max(0.0) as usize).min(limit);
+                let last = ((start + size).round().max(0.0) as usize).min(limit);
+                first..last
+            };
+            let data = self.field.data_mut();
+            for y in span(y0, h) {
+                let v = (y as f64 + 0.5 - y0) / size;
+                for x in span(x0, w) {
+                    let u = (x as f64 + 0.5 - x0) / size;
+                    let ink = style.coverage(u, v, tile.orientation, size, half_width);
+                    data[y * w + x] = if flip { 1.0 - ink } else { ink };
+                }
+            }
+        }
+    }
+}
+
+impl Engine for Truchet {
+    /// Reveals one more level of subdivision; after `min_tile` is reached
+    /// steps change nothing.
+    fn step(&mut self) -> Result<(), EngineError> {
+        if self.reveal() {
+            self.update_field();
+        }
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.field
+    }
+
+    fn params(&self) -> Value {
+        json!({
+            "style": self.params.style.name(),
+            "tile_size": self.params.tile_size,
+            "min_tile": self.params.min_tile,
+            "subdivide": self.params.subdivide,
+            "line_width": self.params.line_width,
+            "alternate": self.params.alternate,
+            "rng": self.params.rng.name(),
+        })
+    }
+
+    /// Rebuilds the tiling with the new params at the current level (or
+    /// the deepest the new `min_tile` allows), since tile choices are
+    /// derived from the seed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let params = TruchetParams::from_json(&merge_params(&self.params(), params)?);
+        let mut fresh = Truchet::new(self.width, self.height, self.seed, params)?;
+        while fresh.level < self.level && fresh.reveal() {}
+        fresh.update_field();
+        *self = fresh;
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "style": {
+                "type": "string",
+                "default": Style::default().name(),
+                "options": Style::ALL.map(Style::name),
+                "description": "Tile drawing: quarter-circle, diagonal, smith (arcs) or triangles"
+            },
+            "tile_size": {
+                "type": "number",
+                "default": DEFAULT_TILE_SIZE,
+                "min": TILE_RANGE.0,
+                "max": TILE_RANGE.1,
+                "description": "Top-level tile size, in pixels"
+            },
+            "min_tile": {
+                "type": "number",
+                "default": DEFAULT_MIN_TILE,
+                "min": TILE_RANGE.0,
+                "max": TILE_RANGE.1,
+                "description": "Smallest tile size, in pixels"
+            },
+            "subdivide": {
+                "type": "number",
+                "default": DEFAULT_SUBDIVIDE,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Chance that a tile splits into four at the next step"
+            },
+            "line_width": {
+                "type": "number",
+                "default": DEFAULT_LINE_WIDTH,
+                "min": LINE_WIDTH_RANGE.0,
+                "max": LINE_WIDTH_RANGE.1,
+                "description": "Stroke width of diagonal and smith tiles, as a fraction of the tile size"
+            },
+            "alternate": {
+                "type": "boolean",
+                "default": true,
+                "description": "Swap light and dark on every other level"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG for tile choices: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(
+                |&(name, style, tile_size, min_tile, subdivide, alternate)| {
+                    let params = json!({
+                        "style": style.name(),
+                        "tile_size": tile_size,
+                        "min_tile": min_tile,
+                        "subdivide": subdivide,
+                        "alternate": alternate,
+                    });
+                    (name.to_string(), params)
+                },
+            )
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(u64::from(self.level));
+        Ok(writer.finish())
+    }
+
+    /// Rebuilds the tiling from the top level, since tile choices are
+    /// derived from the seed.
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let level = reader.u64()?;
+        reader.finish()?;
+        if level > u64::from(self.params.max_level()) {
+            return Err(EngineError::InvalidState(format!(
+                "level {level} is below the {}-pixel smallest tile",
+                self.params.min_tile
+            )));
+        }
+        let mut fresh = Truchet::new(self.width, self.height, self.seed, self.params)?;
+        while u64::from(fresh.level) < level {
+            fresh.reveal();
+        }
+        fresh.update_field();
+        *self = fresh;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: construct with `params`.
+    fn truchet(width: usize, height: usize, seed: u64, params: TruchetParams) -> Truchet {
+        Truchet::new(width, height, seed, params).unwrap()
+    }
+
+    /// Helper: total tile area, clipped to the canvas.
+    fn covered_area(engine: &Truchet) -> f64 {
+        let (w, h) = (engine.width as f64, engine.height as f64);
+        engine
+            .tiles()
+            .iter()
+            .map(|tile| {
+                let size = engine.size_at(tile.level);
+                let (x, y) = (tile.column as f64 * size, tile.row as f64 * size);
+                (w.min(x + size) - x) * (h.min(y + size) - y)
+            })
+            .sum()
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_covers_the_canvas_with_top_tiles() {
+        let engine = truchet(100, 60, 1, TruchetParams::default());
+        assert_eq!(engine.tiles().len(), 2);
+        assert!(engine.tiles().iter().all(|tile| tile.level == 0));
+        assert_eq!(covered_area(&engine), 6000.0);
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = Truchet::new(10, 0, 1, TruchetParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        assert_eq!(
+            TruchetParams::from_json(&json!({})),
+            TruchetParams::default()
+        );
+        let params = TruchetParams::from_json(&json!({
+            "style": "diagonal",
+            "tile_size": 16.0,
+            "min_tile": 64.0,
+            "subdivide": 2.0,
+            "line_width": 0.0,
+            "alternate": false,
+            "rng": "pcg32"
+        }));
+        assert_eq!(params.style, Style::Diagonal);
+        assert_eq!(params.min_tile, 16.0);
+        assert_eq!(params.subdivide, 1.0);
+        assert_eq!(params.line_width, LINE_WIDTH_RANGE.0);
+        assert!(!params.alternate);
+        assert_eq!(params.rng, RngAlgorithm::Pcg32);
+        let unknown = TruchetParams::from_json(&json!({"style": "hexagons"}));
+        assert_eq!(unknown.style, Style::Smith);
+    }
+
+    #[test]
+    fn params_round_trip_through_json() {
+        let engine =
+            Truchet::from_json(8, 8, 1, &json!({"style": "triangles", "min_tile": 4.0})).unwrap();
+        let params = engine.params();
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+        assert_eq!(TruchetParams::from_json(&params), engine.params);
+    }
+
+    // ---- Subdivision tests ----
+
+    #[test]
+    fn each_step_reveals_one_level_until_min_tile() {
+        let params = TruchetParams {
+            subdivide: 1.0,
+            ..TruchetParams::default()
+        };
+        let mut engine = truchet(128, 128, 1, params);
+        assert_eq!(engine.tiles().len(), 4);
+        for level in 1..=3 {
+            engine.step().unwrap();
+            assert_eq!(engine.level(), level);
+            assert_eq!(engine.tiles().len(), 4 * 4usize.pow(level));
+        }
+        let before = engine.field().data().to_vec();
+        engine.step().unwrap();
+        assert_eq!(engine.level(), 3);
+        assert_eq!(engine.field().data(), before.as_slice());
+    }
+
+    #[test]
+    fn subdivision_keeps_the_canvas_covered_once() {
+        let mut engine = truchet(100, 70, 5, TruchetParams::default());
+        for _ in 0..4 {
+            engine.step().unwrap();
+            assert_eq!(covered_area(&engine), 7000.0);
+        }
+        let levels: Vec<u32> = engine.tiles().iter().map(|tile| tile.level).collect();
+        assert!(levels.contains(&0) || levels.contains(&1));
+        assert!(levels.contains(&3));
+    }
+
+    #[test]
+    fn zero_subdivide_keeps_the_top_tiles() {
+        let params = TruchetParams {
+            subdivide: 0.0,
+            ..TruchetParams::default()
+        };
+        let mut engine = truchet(128, 128, 1, params);
+        let before = engine.tiles().to_vec();
+        engine.step().unwrap();
+        assert_eq!(engine.tiles(), before.as_slice());
+    }
+
+    #[test]
+    fn tile_choices_depend_only_on_seed_and_position() {
+        let a = truchet(256, 128, 9, TruchetParams::default());
+        let b = truchet(128, 128, 9, TruchetParams::default());
+        assert_eq!(a.tiles()[0], b.tiles()[0]);
+        let c = truchet(256, 128, 10, TruchetParams::default());
+        assert_ne!(
+            a.tiles().iter().map(|t| t.orientation).collect::<Vec<_>>(),
+            c.tiles().iter().map(|t| t.orientation).collect::<Vec<_>>()
+        );
+    }
+
+    // ---- Drawing tests ----
+
+    #[test]
+    fn styles_draw_their_shapes() {
+        let at = |style: Style, u: f64, v: f64, o: u8| style.coverage(u, v, o, 64.0, 2.0);
+        assert_eq!(at(Style::QuarterCircle, 0.1, 0.1, 0), 1.0);
+        assert_eq!(at(Style::QuarterCircle, 0.9, 0.9, 0), 0.0);
+        assert_eq!(at(Style::QuarterCircle, 0.9, 0.9, 2), 1.0);
+        assert_eq!(at(Style::Diagonal, 0.5, 0.5, 0), 1.0);
+        assert_eq!(at(Style::Diagonal, 0.5, 0.5, 1), 1.0);
+        assert_eq!(at(Style::Diagonal, 0.9, 0.1, 0), 0.0);
+        assert_eq!(at(Style::Smith, 0.5, 0.0, 0), 1.0);
+        assert_eq!(at(Style::Smith, 0.5, 0.5, 0), 0.0);
+        assert_eq!(at(Style::Triangles, 0.2, 0.2, 0), 1.0);
+        assert_eq!(at(Style::Triangles, 0.8, 0.8, 0), 0.0);
+    }
+
+    #[test]
+    fn smith_arcs_meet_at_edge_midpoints() {
+        for o in 0..2 {
+            for (u, v) in [(0.5, 0.0), (0.0, 0.5), (1.0, 0.5), (0.5, 1.0)] {
+                assert_eq!(
+                    Style::Smith.coverage(u, v, o, 64.0, 2.0),
+                    1.0,
+                    "{o} ({u}, {v})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn alternate_flips_odd_levels() {
+        let params = |alternate| TruchetParams {
+            subdivide: 1.0,
+            alternate,
+            ..TruchetParams::default()
+        };
+        let mut plain = truchet(64, 64, 2, params(false));
+        let mut flipped = truchet(64, 64, 2, params(true));
+        assert_eq!(plain.field().data(), flipped.field().data());
+        plain.step().unwrap();
+        flipped.step().unwrap();
+        for (a, b) in plain.field().data().iter().zip(flipped.field().data()) {
+            assert!((a + b - 1.0).abs() < 1e-12);
+        }
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_rebuilds_at_the_current_level() {
+        let mut engine = truchet(64, 64, 4, TruchetParams::default());
+        engine.step().unwrap();
+        engine.set_params(&json!({"style": "diagonal"})).unwrap();
+        assert_eq!(engine.level(), 1);
+        let expected = TruchetParams {
+            style: Style::Diagonal,
+            ..TruchetParams::default()
+        };
+        let mut direct = truchet(64, 64, 4, expected);
+        direct.step().unwrap();
+        assert_eq!(engine.tiles(), direct.tiles());
+        assert_eq!(engine.field().data(), direct.field().data());
+
+        engine.set_params(&json!({"min_tile": 64.0})).unwrap();
+        assert_eq!(engine.level(), 0);
+    }
+
+    // ---- State tests ----
+
+    #[test]
+    fn state_round_trips() {
+        let mut engine = truchet(96, 64, 4, TruchetParams::default());
+        engine.step().unwrap();
+        engine.step().unwrap();
+        let state = engine.save_state().unwrap();
+        let mut restored = truchet(96, 64, 4, TruchetParams::default());
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.tiles(), engine.tiles());
+        assert_eq!(restored.field().data(), engine.field().data());
+    }
+
+    #[test]
+    fn load_state_rejects_levels_past_min_tile() {
+        let mut writer = StateWriter::new();
+        writer.u64(4);
+        let mut engine = truchet(64, 64, 4, TruchetParams::default());
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn fields_stay_in_unit_interval(
+                width in 1usize..48,
+                height in 1usize..48,
+                seed in any::<u64>(),
+                style in 0usize..4,
+                tile_size in TILE_RANGE.0..64.0,
+                line_width in LINE_WIDTH_RANGE.0..LINE_WIDTH_RANGE.1,
+            ) {
+                let params = TruchetParams {
+                    style: Style::ALL[style],
+                    tile_size,
+                    min_tile: TILE_RANGE.0,
+                    line_width,
+                    ..TruchetParams::default()
+                };
+                let mut engine = Truchet::new(width, height, seed, params).unwrap();
+                for _ in 0..3 {
+                    engine.step().unwrap();
+                }
+                let unit = engine.field().data().iter().all(|v| (0.0..=1.0).contains(v));
+                prop_assert!(unit);
+            }
+        }
+    }
+}
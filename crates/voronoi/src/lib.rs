@@ -0,0 +1,945 @@
+#![deny(unsafe_code)]
+//! Voronoi and Delaunay mesh engine.
+//!
+//! Scatters `points` seeded sites over the canvas and draws the diagram
+//! they define. Each pixel finds its nearest two sites through a bucket
+//! grid; the nearest owns the pixel's Voronoi cell, and the two together
+//! give the exact distance to the cell wall between them. Cells whose
+//! pixels touch are neighbors, which is the Delaunay triangulation.
+//!
+//! The `render` param picks what the field shows: `edges` strokes the
+//! Voronoi cell walls, `cells` fills each cell with its site's seeded
+//! shade, and `delaunay` strokes the lines joining neighboring sites.
+//!
+//! Each step moves the sites: `relax` eases them toward their cells'
+//! centroids (Lloyd relaxation, which evens the cells out), an optional
+//! `velocity` field-source spec carries them along, and `jitter` nudges
+//! them at random. Sites leaving one edge re-enter at the opposite one.
+//! The jitter comes from a stream derived from the seed and step number,
+//! so runs replay exactly.
+
+use art_engine_core::budget::grid_bytes;
+use art_engine_core::error::EngineError;
+use art_engine_core::field::Field;
+use art_engine_core::field_source::{FieldSource, FieldSourceSpec};
+use art_engine_core::math;
+use art_engine_core::params::{
+    check_fixed, merge_params, param_f64, param_rng, param_string, param_usize,
+};
+use art_engine_core::prng::{derive_seed, Rng, RngAlgorithm};
+use art_engine_core::{Engine, StateReader, StateWriter};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/// Default number of sites.
+const DEFAULT_POINTS: usize = 256;
+/// Fewest and most sites.
+const POINTS_RANGE: (usize, usize) = (2, 20_000);
+/// Default stroke width, in pixels.
+const DEFAULT_LINE_WIDTH: f64 = 1.5;
+/// Thinnest and thickest stroke, in pixels.
+const LINE_WIDTH_RANGE: (f64, f64) = (0.25, 16.0);
+/// Default random nudge per site per step, in pixels.
+const DEFAULT_JITTER: f64 = 0.5;
+/// Largest random nudge, in pixels.
+const MAX_JITTER: f64 = 8.0;
+/// A named mesh: (name, render, points, relax, jitter, velocity).
+type Preset = (
+    &'static str,
+    Render,
+    usize,
+    f64,
+    f64,
+    Option<FieldSourceSpec>,
+);
+/// Named meshes.
+const PRESETS: &[Preset] = &[
+    ("stained-glass", Render::Cells, 300, 0.2, 0.0, None),
+    ("cracked-earth", Render::Edges, 150, 0.0, 0.0, None),
+    ("foam", Render::Edges, 400, 1.0, 0.0, None),
+    ("triangulation", Render::Delaunay, 120, 0.5, 0.3, None),
+    (
+        "drift",
+        Render::Edges,
+        200,
+        0.1,
+        0.0,
+        Some(FieldSourceSpec::Curl {
+            scale: 0.01,
+            strength: 1.5,
+            seed: 0,
+            period: None,
+        }),
+    ),
+];
+
+/// What the engine draws into the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Render {
+    /// Voronoi cell walls.
+    #[default]
+    Edges,
+    /// Each cell filled with its site's shade.
+    Cells,
+    /// Lines joining neighboring sites.
+    Delaunay,
+}
+
+impl Render {
+    /// Every render mode, in schema order.
+    pub const ALL: [Render; 3] = [Render::Edges, Render::Cells, Render::Delaunay];
+
+    /// The mode's param name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Render::Edges => "edges",
+            Render::Cells => "cells",
+            Render::Delaunay => "delaunay",
+        }
+    }
+
+    /// Looks a mode up by [`Render::name`].
+    pub fn from_name(name: &str) -> Option<Render> {
+        Render::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+/// Simulation parameters for the Voronoi engine.
+///
+/// Use [`Default`] for the cell walls of 256 gently jittering sites,
+/// seeded by Xorshift64.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoronoiParams {
+    /// Number of sites.
+    pub points: usize,
+    /// What the field shows.
+    pub render: Render,
+    /// Stroke width of cell walls and Delaunay lines, in pixels.
+    pub line_width: f64,
+    /// Fraction of the way each site moves toward its cell's centroid per
+    /// step.
+    pub relax: f64,
+    /// Largest random nudge per site per step, in pixels.
+    pub jitter: f64,
+    /// Field-source spec carrying the sites, in pixels per step.
+    pub velocity: Option<FieldSourceSpec>,
+    /// PRNG algorithm for the sites and jitter.
+    pub rng: RngAlgorithm,
+}
+
+impl Default for VoronoiParams {
+    fn default() -> Self {
+        Self {
+            points: DEFAULT_POINTS,
+            render: Render::default(),
+            line_width: DEFAULT_LINE_WIDTH,
+            relax: 0.0,
+            jitter: DEFAULT_JITTER,
+            velocity: None,
+            rng: RngAlgorithm::default(),
+        }
+    }
+}
+
+impl VoronoiParams {
+    /// Extracts parameters from a JSON object, falling back to defaults and
+    /// clamping each number into its range. An unknown `render` falls back
+    /// to `edges`.
+    ///
+    /// Returns `EngineError::InvalidFieldSource` if `velocity` is present
+    /// but not a field-source spec.
+    pub fn from_json(params: &Value) -> Result<Self, EngineError> {
+        let velocity = match params.get("velocity") {
+            Some(spec) => Some(
+                serde_json::from_value(spec.clone())
+                    .map_err(|e| EngineError::InvalidFieldSource(e.to_string()))?,
+            ),
+            None => None,
+        };
+        let render = param_string(params, "render", Render::default().name());
+        Ok(Self {
+            points: param_usize(params, "points", DEFAULT_POINTS),
+            render: Render::from_name(&render).unwrap_or_default(),
+            line_width: param_f64(params, "line_width", DEFAULT_LINE_WIDTH),
+            relax: param_f64(params, "relax", 0.0),
+            jitter: param_f64(params, "jitter", DEFAULT_JITTER),
+            velocity,
+            rng: param_rng(params, "rng", RngAlgorithm::default()),
+        }
+        .clamped())
+    }
+
+    /// Returns the params with each number clamped into its range; NaN
+    /// falls back to the default.
+    fn clamped(self) -> Self {
+        let clamp = |v: f64, (min, max): (f64, f64), default: f64| {
+            if v.is_nan() {
+                default
+            } else {
+                v.clamp(min, max)
+            }
+        };
+        Self {
+            points: self.points.clamp(POINTS_RANGE.0, POINTS_RANGE.1),
+            line_width: clamp(self.line_width, LINE_WIDTH_RANGE, DEFAULT_LINE_WIDTH),
+            relax: clamp(self.relax, (0.0, 1.0), 0.0),
+            jitter: clamp(self.jitter, (0.0, MAX_JITTER), DEFAULT_JITTER),
+            ..self
+        }
+    }
+}
+
+/// A Voronoi site.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Site {
+    /// Position in pixels.
+    pub x: f64,
+    /// Position in pixels.
+    pub y: f64,
+    /// Brightness of the site's cell in the `cells` render.
+    pub shade: f64,
+}
+
+/// Voronoi and Delaunay mesh engine.
+pub struct Voronoi {
+    width: usize,
+    height: usize,
+    seed: u64,
+    params: VoronoiParams,
+    /// The built `velocity` source.
+    flow: Option<Box<dyn FieldSource>>,
+    sites: Vec<Site>,
+    /// Each cell's centroid in the last drawn diagram, for relaxation.
+    centroids: Vec<(f64, f64)>,
+    /// Pairs of neighboring sites, lower index first.
+    neighbors: BTreeSet<(usize, usize)>,
+    steps: u64,
+    field: Field,
+}
+
+impl Voronoi {
+    /// Creates a new engine with uniformly scattered sites. Out-of-range
+    /// params are clamped.
+    ///
+    /// Returns `EngineError::InvalidDimensions` if width or height is zero.
+    pub fn new(
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: VoronoiParams,
+    ) -> Result<Self, EngineError> {
+        let field = Field::new(width, height)?;
+        let params = params.clamped();
+        let mut rng = params.rng.build(derive_seed(seed, "sites"));
+        let (w, h) = (width as f64, height as f64);
+        let sites = (0..params.points)
+            .map(|_| Site {
+                x: rng.next_f64() * w,
+                y: rng.next_f64() * h,
+                shade: rng.next_f64(),
+            })
+            .collect();
+        let mut engine = Self {
+            width,
+            height,
+            seed,
+            flow: params.velocity.as_ref().map(FieldSourceSpec::build),
+            params,
+            sites,
+            centroids: Vec::new(),
+            neighbors: BTreeSet::new(),
+            steps: 0,
+            field,
+        };
+        engine.update_field();
+        Ok(engine)
+    }
+
+    /// Creates an engine from a JSON params object.
+    ///
+    /// Extracts `points`, `render`, `line_width`, `relax`, `jitter`,
+    /// `velocity` and `rng`, falling back to defaults for missing keys.
+    /// Returns `EngineError::InvalidFieldSource` for a malformed
+    /// `velocity`.
+    pub fn from_json(
+        width: usize,
+        height: usize,
+        seed: u64,
+        json_params: &Value,
+    ) -> Result<Self, EngineError> {
+        Self::new(width, height, seed, VoronoiParams::from_json(json_params)?)
+    }
+
+    /// Estimates the bytes an engine of this size holds at its peak: the
+    /// field and per-pixel cell owners, plus each site with its centroid,
+    /// bucket entry and a few neighbor pairs.
+    pub fn memory_estimate(width: usize, height: usize, params: &VoronoiParams) -> u64 {
+        let points = params.points.clamp(POINTS_RANGE.0, POINTS_RANGE.1);
+        let per_site = std::mem::size_of::<Site>() + 2 * 8 + 8 + 6 * 2 * 8;
+        grid_bytes(width, height, 8 + 8).saturating_add((points * per_site) as u64)
+    }
+
+    /// The sites, in creation order.
+    pub fn sites(&self) -> &[Site] {
+        &self.sites
+    }
+
+    /// Pairs of sites whose cells touch (the Delaunay edges), lower index
+    /// first.
+    pub fn neighbors(&self) -> &BTreeSet<(usize, usize)> {
+        &self.neighbors
+    }
+
+    /// Redraws the diagram, recording each cell's centroid and neighbors.
+    fn update_field(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let grid = Grid::new(w, h, &self.sites);
+        let mut owners = vec![0usize; w * h];
+        let mut sums = vec![(0.0, 0.0, 0usize); self.sites.len()];
+        let half_width = self.params.line_width / 2.0;
+        let render = self.params.render;
+        let data = self.field.data_mut();
+        for (i, (value, owner)) in data.iter_mut().zip(&mut owners).enumerate() {
+            let (px, py) = ((i % w) as f64 + 0.5, (i / w) as f64 + 0.5);
+            let [(near, near_sq), (far, far_sq)] = grid.nearest_two(&self.sites, px, py);
+            *owner = near;
+            let sum = &mut sums[near];
+            *sum = (sum.0 + px, sum.1 + py, sum.2 + 1);
+            *value = match render {
+                Render::Edges => {
+                    let (a, b) = (self.sites[near], self.sites[far]);
+                    let separation = math::hypot(a.x - b.x, a.y - b.y);
+                    let wall = if separation > 1e-12 {
+                        (far_sq - near_sq) / (2.0 * separation)
+                    } else {
+                        0.0
+                    };
+                    (half_width + 0.5 - wall).clamp(0.0, 1.0)
+                }
+                Render::Cells => self.sites[near].shade,
+                Render::Delaunay => 0.0,
+            };
+        }
+
+        self.neighbors.clear();
+        for y in 0..h {
+            for x in 0..w {
+                let here = owners[y * w + x];
+                let right = (x + 1 < w).then(|| owners[y * w + x + 1]);
+                let below = (y + 1 < h).then(|| owners[(y + 1) * w + x]);
+                for other in [right, below].into_iter().flatten() {
+                    if other != here {
+                        self.neighbors.insert((here.min(other), here.max(other)));
+                    }
+                }
+            }
+        }
+        self.centroids = sums
+            .iter()
+            .zip(&self.sites)
+            .map(|(&(sx, sy, count), site)| match count {
+                0 => (site.x, site.y),
+                _ => (sx / count as f64, sy / count as f64),
+            })
+            .collect();
+
+        if render == Render::Delaunay {
+            for &(a, b) in &self.neighbors {
+                let (a, b) = (self.sites[a], self.sites[b]);
+                stroke(&mut self.field, (a.x, a.y), (b.x, b.y), half_width);
+            }
+        }
+    }
+}
+
+/// Draws an antialiased capsule from `a` to `b`, keeping the brighter value
+/// where strokes overlap.
+fn stroke(field: &mut Field, a: (f64, f64), b: (f64, f64), radius: f64) {
+    let (w, h) = (field.width(), field.height());
+    let reach = radius + 0.5;
+    let cells = |lo: f64, hi: f64, size: usize| {
+        let first = (lo - reach).floor().max(0.0) as usize;
+        let last = ((hi + reach).ceil().max(0.0) as usize).min(size);
+        first..last
+    };
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let data = field.data_mut();
+    for y in cells(a.1.min(b.1), a.1.max(b.1), h) {
+        for x in cells(a.0.min(b.0), a.0.max(b.0), w) {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let t = if length_sq > 0.0 {
+                (((px - a.0) * dx + (py - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = math::hypot(px - a.0 - t * dx, py - a.1 - t * dy);
+            let value = (reach - distance).clamp(0.0, 1.0);
+            let cell = &mut data[y * w + x];
+            *cell = cell.max(value);
+        }
+    }
+}
+
+/// Buckets sites by square cells holding about two sites each.
+struct Grid {
+    cell: f64,
+    columns: usize,
+    rows: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize, sites: &[Site]) -> Self {
+        let cell = (2.0 * (width * height) as f64 / sites.len() as f64)
+            .sqrt()
+            .max(1.0);
+        let columns = (width as f64 / cell).ceil().max(1.0) as usize;
+        let rows = (height as f64 / cell).ceil().max(1.0) as usize;
+        let mut buckets = vec![Vec::new(); columns * rows];
+        for (i, site) in sites.iter().enumerate() {
+            let column = ((site.x / cell) as usize).min(columns - 1);
+            let row = ((site.y / cell) as usize).min(rows - 1);
+            buckets[row * columns + column].push(i);
+        }
+        Self {
+            cell,
+            columns,
+            rows,
+            buckets,
+        }
+    }
+
+    /// The nearest and second-nearest sites to (`x`, `y`) with their
+    /// squared distances, searching rings of buckets outward until no
+    /// farther ring could hold a closer site.
+    fn nearest_two(&self, sites: &[Site], x: f64, y: f64) -> [(usize, f64); 2] {
+        let column = ((x / self.cell) as usize).min(self.columns - 1) as isize;
+        let row = ((y / self.cell) as usize).min(self.rows - 1) as isize;
+        let mut best = [(0, f64::INFINITY); 2];
+        for ring in 0..=self.columns.max(self.rows) as isize {
+            let closest = (ring - 1).max(0) as f64 * self.cell;
+            if closest * closest >= best[1].1 {
+                break;
+            }
+            for r in row - ring..=row + ring {
+                for c in column - ring..=column + ring {
+                    let on_ring = (r - row).abs() == ring || (c - column).abs() == ring;
+                    let inside = (0..self.rows as isize).contains(&r)
+                        && (0..self.columns as isize).contains(&c);
+                    if !(on_ring && inside) {
+                        continue;
+                    }
+                    for &i in &self.buckets[r as usize * self.columns + c as usize] {
+                        let d = (sites[i].x - x).powi(2) + (sites[i].y - y).powi(2);
+                        if d < best[0].1 {
+                            best = [(i, d), best[0]];
+                        } else if d < best[1].1 {
+                            best[1] = (i, d);
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+impl Engine for Voronoi {
+    /// Relaxes, carries and jitters the sites, wraps them onto the canvas,
+    /// then redraws the diagram.
+    fn step(&mut self) -> Result<(), EngineError> {
+        let mut rng = self
+            .params
+            .rng
+            .build(derive_seed(self.seed, &format!("step-{}", self.steps)));
+        let (w, h) = (self.width as f64, self.height as f64);
+        let (relax, jitter) = (self.params.relax, self.params.jitter);
+        let time = self.steps as f64;
+        for (site, &(cx, cy)) in self.sites.iter_mut().zip(&self.centroids) {
+            let (mut x, mut y) = (
+                site.x + relax * (cx - site.x),
+                site.y + relax * (cy - site.y),
+            );
+            if let Some(flow) = &self.flow {
+                let (dx, dy) = flow.sample(x, y, time);
+                if dx.is_finite() && dy.is_finite() {
+                    (x, y) = (x + dx, y + dy);
+                }
+            }
+            x += jitter * (2.0 * rng.next_f64() - 1.0);
+            y += jitter * (2.0 * rng.next_f64() - 1.0);
+            // rem_euclid can round up to the modulus itself for tiny negatives
+            site.x = x.rem_euclid(w).min(w - f64::EPSILON * w);
+            site.y = y.rem_euclid(h).min(h - f64::EPSILON * h);
+        }
+        self.steps += 1;
+        self.update_field();
+        Ok(())
+    }
+
+    fn field(&self) -> &Field {
+        &self.field
+    }
+
+    fn params(&self) -> Value {
+        let mut params = json!({
+            "points": self.params.points,
+            "render": self.params.render.name(),
+            "line_width": self.params.line_width,
+            "relax": self.params.relax,
+            "jitter": self.params.jitter,
+            "rng": self.params.rng.name(),
+        });
+        if let Some(velocity) = &self.params.velocity {
+            params["velocity"] = json!(velocity);
+        }
+        params
+    }
+
+    /// Motion params and `velocity` apply from the next step; `render`
+    /// and `line_width` redraw the diagram. `points` is fixed.
+    fn set_params(&mut self, params: &Value) -> Result<(), EngineError> {
+        let current = self.params();
+        check_fixed(&current, params, &["points"])?;
+        self.params = VoronoiParams::from_json(&merge_params(&current, params)?)?;
+        self.flow = self.params.velocity.as_ref().map(FieldSourceSpec::build);
+        self.update_field();
+        Ok(())
+    }
+
+    fn param_schema(&self) -> Value {
+        json!({
+            "points": {
+                "type": "integer",
+                "default": DEFAULT_POINTS,
+                "min": POINTS_RANGE.0,
+                "max": POINTS_RANGE.1,
+                "description": "Number of sites"
+            },
+            "render": {
+                "type": "string",
+                "default": Render::default().name(),
+                "options": Render::ALL.map(Render::name),
+                "description": "edges (cell walls), cells (shaded cells) or delaunay (lines between neighbors)"
+            },
+            "line_width": {
+                "type": "number",
+                "default": DEFAULT_LINE_WIDTH,
+                "min": LINE_WIDTH_RANGE.0,
+                "max": LINE_WIDTH_RANGE.1,
+                "description": "Stroke width of cell walls and Delaunay lines, in pixels"
+            },
+            "relax": {
+                "type": "number",
+                "default": 0.0,
+                "min": 0.0,
+                "max": 1.0,
+                "description": "Fraction of the way each site moves toward its cell's centroid per step"
+            },
+            "jitter": {
+                "type": "number",
+                "default": DEFAULT_JITTER,
+                "min": 0.0,
+                "max": MAX_JITTER,
+                "description": "Largest random nudge per site per step, in pixels"
+            },
+            "velocity": {
+                "type": "object",
+                "default": null,
+                "description": "Field-source spec carrying the sites, in pixels per step; none by default"
+            },
+            "rng": {
+                "type": "string",
+                "default": RngAlgorithm::default().name(),
+                "options": RngAlgorithm::ALL.map(RngAlgorithm::name),
+                "description": "PRNG for the sites and jitter: xorshift64, pcg32 or splitmix64"
+            }
+        })
+    }
+
+    fn presets(&self) -> Value {
+        PRESETS
+            .iter()
+            .map(|(name, render, points, relax, jitter, velocity)| {
+                let mut params = json!({
+                    "render": render.name(),
+                    "points": points,
+                    "relax": relax,
+                    "jitter": jitter,
+                });
+                if let Some(velocity) = velocity {
+                    params["velocity"] = json!(velocity);
+                }
+                (name.to_string(), params)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, EngineError> {
+        let mut writer = StateWriter::new();
+        writer.u64(self.steps).u64(self.sites.len() as u64);
+        for site in &self.sites {
+            writer.f64(site.x).f64(site.y).f64(site.shade);
+        }
+        Ok(writer.finish())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), EngineError> {
+        let mut reader = StateReader::new(state);
+        let steps = reader.u64()?;
+        let count = reader.u64()?;
+        if count != self.sites.len() as u64 {
+            return Err(EngineError::InvalidState(format!(
+                "state has {count} sites, engine has {}",
+                self.sites.len()
+            )));
+        }
+        let (w, h) = (self.width as f64, self.height as f64);
+        let mut sites = Vec::with_capacity(self.sites.len());
+        for _ in 0..count {
+            let (x, y, shade) = (reader.f64()?, reader.f64()?, reader.f64()?);
+            let on_canvas = (0.0..w).contains(&x) && (0.0..h).contains(&y);
+            if !on_canvas || !(0.0..=1.0).contains(&shade) {
+                return Err(EngineError::InvalidState(format!(
+                    "site ({x}, {y}) with shade {shade} does not fit the {}x{} canvas",
+                    self.width, self.height
+                )));
+            }
+            sites.push(Site { x, y, shade });
+        }
+        reader.finish()?;
+        self.steps = steps;
+        self.sites = sites;
+        self.update_field();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: construct with `params`.
+    fn voronoi(width: usize, height: usize, seed: u64, params: VoronoiParams) -> Voronoi {
+        Voronoi::new(width, height, seed, params).unwrap()
+    }
+
+    /// Helper: the site nearest (`x`, `y`) by brute force.
+    fn brute_nearest(sites: &[Site], x: f64, y: f64) -> usize {
+        (0..sites.len())
+            .min_by(|&a, &b| {
+                let d = |s: &Site| (s.x - x).hypot(s.y - y);
+                d(&sites[a]).total_cmp(&d(&sites[b]))
+            })
+            .unwrap()
+    }
+
+    // ---- Construction tests ----
+
+    #[test]
+    fn new_scatters_sites_on_the_canvas() {
+        let engine = voronoi(80, 50, 1, VoronoiParams::default());
+        assert_eq!(engine.sites().len(), DEFAULT_POINTS);
+        for site in engine.sites() {
+            assert!((0.0..80.0).contains(&site.x) && (0.0..50.0).contains(&site.y));
+            assert!((0.0..1.0).contains(&site.shade));
+        }
+        assert!(!engine.neighbors().is_empty());
+    }
+
+    #[test]
+    fn new_with_zero_dimensions_returns_error() {
+        let result = Voronoi::new(0, 10, 1, VoronoiParams::default());
+        assert!(matches!(result, Err(EngineError::InvalidDimensions)));
+    }
+
+    #[test]
+    fn from_json_reads_and_clamps_params() {
+        let params = VoronoiParams::from_json(&json!({})).unwrap();
+        assert_eq!(params, VoronoiParams::default());
+        let params = VoronoiParams::from_json(&json!({
+            "points": 1,
+            "render": "delaunay",
+            "line_width": 100.0,
+            "relax": 2.0,
+            "jitter": -1.0,
+            "velocity": {"kind": "curl", "scale": 0.01},
+            "rng": "pcg32"
+        }))
+        .unwrap();
+        assert_eq!(params.points, POINTS_RANGE.0);
+        assert_eq!(params.render, Render::Delaunay);
+        assert_eq!(params.line_width, LINE_WIDTH_RANGE.1);
+        assert_eq!(params.relax, 1.0);
+        assert_eq!(params.jitter, 0.0);
+        assert!(params.velocity.is_some());
+        assert_eq!(params.rng, RngAlgorithm::Pcg32);
+        let bad = VoronoiParams::from_json(&json!({"velocity": {"kind": "nope"}}));
+        assert!(matches!(bad, Err(EngineError::InvalidFieldSource(_))));
+    }
+
+    #[test]
+    fn params_round_trip_through_json() {
+        let json_params = json!({
+            "points": 40,
+            "render": "cells",
+            "velocity": {"kind": "curl", "scale": 0.02, "strength": 1.0, "seed": 0}
+        });
+        let engine = Voronoi::from_json(16, 16, 1, &json_params).unwrap();
+        let params = engine.params();
+        let schema = engine.param_schema();
+        for key in params.as_object().unwrap().keys() {
+            assert!(schema.get(key).is_some(), "{key}");
+        }
+        assert_eq!(VoronoiParams::from_json(&params).unwrap(), engine.params);
+    }
+
+    // ---- Diagram tests ----
+
+    #[test]
+    fn grid_search_matches_brute_force() {
+        for points in [2, 7, 100] {
+            let params = VoronoiParams {
+                points,
+                ..VoronoiParams::default()
+            };
+            let engine = voronoi(37, 23, points as u64, params);
+            let grid = Grid::new(37, 23, engine.sites());
+            for y in 0..23 {
+                for x in 0..37 {
+                    let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                    let [(near, _), (far, far_sq)] = grid.nearest_two(engine.sites(), px, py);
+                    assert_eq!(near, brute_nearest(engine.sites(), px, py));
+                    assert_ne!(near, far);
+                    let others = engine
+                        .sites()
+                        .iter()
+                        .enumerate()
+                        .filter(|&(i, _)| i != near);
+                    let second = others
+                        .map(|(_, s)| (s.x - px).powi(2) + (s.y - py).powi(2))
+                        .fold(f64::INFINITY, f64::min);
+                    assert_eq!(far_sq, second);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_sites_share_one_straight_wall() {
+        let mut engine = voronoi(20, 10, 1, VoronoiParams::default());
+        engine.sites = vec![
+            Site {
+                x: 5.0,
+                y: 5.0,
+                shade: 0.2,
+            },
+            Site {
+                x: 15.0,
+                y: 5.0,
+                shade: 0.8,
+            },
+        ];
+        engine.update_field();
+        assert_eq!(
+            engine.neighbors().iter().copied().collect::<Vec<_>>(),
+            [(0, 1)]
+        );
+        for y in 0..10 {
+            // The wall is the line x = 10, between columns 9 and 10
+            assert!(engine.field().get(9, y) > 0.7 && engine.field().get(10, y) > 0.7);
+            assert_eq!(engine.field().get(3, y), 0.0);
+        }
+        let (cx, _) = engine.centroids[0];
+        assert!((cx - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cells_render_fills_with_site_shades() {
+        let params = VoronoiParams {
+            render: Render::Cells,
+            points: 30,
+            ..VoronoiParams::default()
+        };
+        let engine = voronoi(40, 40, 3, params);
+        for (x, y, value) in engine.field().iter() {
+            let near = brute_nearest(engine.sites(), x as f64 + 0.5, y as f64 + 0.5);
+            assert_eq!(value, engine.sites()[near].shade);
+        }
+    }
+
+    #[test]
+    fn delaunay_render_passes_through_every_site() {
+        let params = VoronoiParams {
+            render: Render::Delaunay,
+            points: 20,
+            ..VoronoiParams::default()
+        };
+        let engine = voronoi(60, 60, 4, params);
+        for site in engine.sites() {
+            let value = engine.field().get(site.x as isize, site.y as isize);
+            assert!(value > 0.4, "{site:?}");
+        }
+    }
+
+    // ---- Motion tests ----
+
+    #[test]
+    fn relaxation_evens_out_cell_sizes() {
+        let spread = |engine: &Voronoi| {
+            let mut areas = vec![0usize; engine.sites().len()];
+            for (x, y, _) in engine.field().iter() {
+                areas[brute_nearest(engine.sites(), x as f64 + 0.5, y as f64 + 0.5)] += 1;
+            }
+            let max = *areas.iter().max().unwrap() as f64;
+            let min = *areas.iter().min().unwrap() as f64;
+            max - min
+        };
+        let params = VoronoiParams {
+            points: 16,
+            relax: 1.0,
+            jitter: 0.0,
+            ..VoronoiParams::default()
+        };
+        let mut engine = voronoi(48, 48, 5, params);
+        let before = spread(&engine);
+        for _ in 0..20 {
+            engine.step().unwrap();
+        }
+        assert!(
+            spread(&engine) < before / 2.0,
+            "{before} -> {}",
+            spread(&engine)
+        );
+    }
+
+    /// A steady wind of (3, 0) pixels per step.
+    struct East;
+
+    impl FieldSource for East {
+        fn sample(&self, _x: f64, _y: f64, _time: f64) -> (f64, f64) {
+            (3.0, 0.0)
+        }
+    }
+
+    #[test]
+    fn velocity_carries_sites_and_wraps_them() {
+        let params = VoronoiParams {
+            points: 5,
+            jitter: 0.0,
+            ..VoronoiParams::default()
+        };
+        let mut engine = voronoi(10, 10, 6, params);
+        engine.flow = Some(Box::new(East));
+        let before = engine.sites().to_vec();
+        engine.step().unwrap();
+        for (a, b) in before.iter().zip(engine.sites()) {
+            assert!(((a.x + 3.0) % 10.0 - b.x).abs() < 1e-9);
+            assert_eq!(a.y, b.y);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_sites() {
+        let run = |seed| {
+            let mut engine = voronoi(32, 32, seed, VoronoiParams::default());
+            for _ in 0..5 {
+                engine.step().unwrap();
+            }
+            engine.sites().to_vec()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    // ---- Set params tests ----
+
+    #[test]
+    fn set_params_redraws_and_steers_the_sites() {
+        let mut engine = voronoi(32, 32, 3, VoronoiParams::default());
+        engine.step().unwrap();
+        let sites = engine.sites().to_vec();
+        let edges = engine.field().clone();
+        engine.set_params(&json!({"render": "cells"})).unwrap();
+        assert_eq!(engine.sites(), sites);
+        assert_ne!(engine.field(), &edges);
+
+        let velocity = json!({"kind": "curl", "scale": 0.05, "strength": 2.0});
+        engine.set_params(&json!({ "velocity": velocity })).unwrap();
+        assert!(engine.flow.is_some());
+        let bad = engine.set_params(&json!({"velocity": {"kind": "gale"}}));
+        assert!(matches!(bad, Err(EngineError::InvalidFieldSource(_))));
+        let fixed = engine.set_params(&json!({"points": 3}));
+        assert!(matches!(fixed, Err(EngineError::Unsupported(_))));
+    }
+
+    // ---- State tests ----
+
+    #[test]
+    fn state_round_trips() {
+        let mut engine = voronoi(32, 32, 2, VoronoiParams::default());
+        engine.step().unwrap();
+        let state = engine.save_state().unwrap();
+        let mut restored = voronoi(32, 32, 2, VoronoiParams::default());
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.sites(), engine.sites());
+        assert_eq!(restored.field().data(), engine.field().data());
+        engine.step().unwrap();
+        restored.step().unwrap();
+        assert_eq!(restored.sites(), engine.sites());
+    }
+
+    #[test]
+    fn load_state_rejects_mismatched_sites() {
+        let mut engine = voronoi(16, 16, 2, VoronoiParams::default());
+        let mut writer = StateWriter::new();
+        writer.u64(0).u64(3);
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+
+        let mut writer = StateWriter::new();
+        writer.u64(0).u64(DEFAULT_POINTS as u64);
+        for _ in 0..DEFAULT_POINTS {
+            writer.f64(20.0).f64(1.0).f64(0.5);
+        }
+        let result = engine.load_state(&writer.finish());
+        assert!(matches!(result, Err(EngineError::InvalidState(_))));
+    }
+
+    // ---- Property-based tests ----
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn fields_stay_in_unit_interval(
+                width in 1usize..32,
+                height in 1usize..32,
+                seed in any::<u64>(),
+                points in 2usize..64,
+                render in 0usize..3,
+            ) {
+                let params = VoronoiParams {
+                    points,
+                    render: Render::ALL[render],
+                    relax: 0.5,
+                    ..VoronoiParams::default()
+                };
+                let mut engine = Voronoi::new(width, height, seed, params).unwrap();
+                for _ in 0..3 {
+                    engine.step().unwrap();
+                }
+                let unit = engine.field().data().iter().all(|v| (0.0..=1.0).contains(v));
+                prop_assert!(unit);
+                let on_canvas = engine.sites().iter().all(|s| {
+                    (0.0..width as f64).contains(&s.x) && (0.0..height as f64).contains(&s.y)
+                });
+                prop_assert!(on_canvas);
+            }
+        }
+    }
+}
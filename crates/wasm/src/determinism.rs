@@ -0,0 +1,67 @@
+//! Cross-target determinism checks.
+//!
+//! Built with the `portable` feature, this module's engines must reproduce
+//! the native reference digests bit for bit. A test page (or Node) calls
+//! `determinismMismatches()` and expects an empty list:
+//!
+//! ```js
+//! if (!isPortable()) throw new Error("build with --features portable");
+//! const mismatches = determinismMismatches();
+//! // [] when every engine matches; otherwise [{ engine, expected, actual }]
+//! ```
+
+use art_engine_core::math::PORTABLE;
+use art_engine_core::EngineError;
+use art_engine_engines::determinism::{mismatches, Digest};
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::schema::to_js;
+
+/// Whether this module was built with the `portable` feature, without
+/// which the reference digests are not expected to match.
+#[wasm_bindgen(js_name = isPortable)]
+pub fn is_portable() -> bool {
+    PORTABLE
+}
+
+/// Engines whose output differs from the reference digests, as
+/// `{engine, expected, actual}` objects with `{field, pixels}` hex digests.
+#[wasm_bindgen(js_name = determinismMismatches)]
+pub fn determinism_mismatches() -> Result<JsValue, JsError> {
+    to_js(&report()?)
+}
+
+/// Builds the list behind [`determinism_mismatches`].
+fn report() -> Result<Value, EngineError> {
+    let hex = |digest: Digest| {
+        json!({
+            "field": format!("{:016x}", digest.field),
+            "pixels": format!("{:016x}", digest.pixels),
+        })
+    };
+    Ok(mismatches()?
+        .into_iter()
+        .map(|(engine, expected, actual)| {
+            json!({"engine": engine, "expected": hex(expected), "actual": hex(actual)})
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_only_mismatches() {
+        let report = report().unwrap();
+        let listed = report.as_array().unwrap();
+        if PORTABLE {
+            assert!(listed.is_empty(), "{report}");
+        }
+        for entry in listed {
+            assert_ne!(entry["expected"], entry["actual"]);
+            assert_eq!(entry["actual"]["field"].as_str().unwrap().len(), 16);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! `requestAnimationFrame` loop around a [`Lab`].
+//!
+//! A [`Driver`] takes ownership of a lab and, while playing, runs a fixed
+//! number of simulation steps per animation frame, then hands the frame to
+//! the `onFrame` callback:
+//!
+//! ```js
+//! const driver = new Driver(new Lab("gray-scott", 256, 256, 42n, "{}", "ocean"), 8);
+//! driver.onFrame((image, step) => ctx.putImageData(image, 0, 0));
+//! driver.play();
+//! ```
+//!
+//! The callback may call back into the driver (e.g. `pause()` after some
+//! step). An error while stepping pauses playback and is thrown from the
+//! frame handler.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use art_engine_core::EngineError;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::Lab;
+
+/// Frame handler registered with `requestAnimationFrame`.
+type Tick = Closure<dyn FnMut() -> Result<(), JsValue>>;
+
+/// Playback state shared between the driver and its frame handler.
+struct State {
+    lab: Lab,
+    steps_per_frame: u32,
+    playing: bool,
+    /// Simulation steps run since the driver was created.
+    step: u64,
+    callback: Option<js_sys::Function>,
+    /// Pending `requestAnimationFrame` handle.
+    request: Option<i32>,
+    tick: Option<Tick>,
+}
+
+impl State {
+    /// Runs one frame's worth of steps; returns `false` when paused.
+    fn advance_frame(&mut self) -> Result<bool, EngineError> {
+        if !self.playing {
+            return Ok(false);
+        }
+        self.lab.advance(self.steps_per_frame)?;
+        self.step += u64::from(self.steps_per_frame);
+        Ok(true)
+    }
+
+    /// Schedules the frame handler unless a frame is already pending.
+    fn schedule(&mut self) -> Result<(), JsValue> {
+        if self.request.is_some() {
+            return Ok(());
+        }
+        if let Some(tick) = &self.tick {
+            self.request = Some(window()?.request_animation_frame(tick.as_ref().unchecked_ref())?);
+        }
+        Ok(())
+    }
+
+    /// Cancels the pending frame, if any.
+    fn cancel(&mut self) -> Result<(), JsValue> {
+        match self.request.take() {
+            Some(handle) => window()?.cancel_animation_frame(handle),
+            None => Ok(()),
+        }
+    }
+}
+
+fn window() -> Result<web_sys::Window, JsValue> {
+    web_sys::window().ok_or_else(|| JsValue::from_str("no window to animate in"))
+}
+
+/// Steps a [`Lab`] on every animation frame while playing.
+#[wasm_bindgen]
+pub struct Driver {
+    state: Rc<RefCell<State>>,
+}
+
+#[wasm_bindgen]
+impl Driver {
+    /// Takes over `lab`, paused, running `steps_per_frame` steps per frame
+    /// once playing.
+    #[wasm_bindgen(constructor)]
+    pub fn new(lab: Lab, steps_per_frame: u32) -> Driver {
+        let driver = Self::paused(lab, steps_per_frame);
+        let state = Rc::downgrade(&driver.state);
+        let tick: Tick = Closure::new(move || frame(&state));
+        driver.state.borrow_mut().tick = Some(tick);
+        driver
+    }
+
+    /// Starts or resumes playback.
+    pub fn play(&self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.playing = true;
+        state.schedule()
+    }
+
+    /// Stops playback after the current frame.
+    pub fn pause(&self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.playing = false;
+        state.cancel()
+    }
+
+    /// Sets the simulation steps run per animation frame (0 redraws the
+    /// same state every frame).
+    #[wasm_bindgen(js_name = setSpeed)]
+    pub fn set_speed(&self, steps_per_frame: u32) {
+        self.state.borrow_mut().steps_per_frame = steps_per_frame;
+    }
+
+    /// Registers `callback(imageData, step)`, called after every frame's
+    /// steps; replaces any previous callback.
+    #[wasm_bindgen(js_name = onFrame)]
+    pub fn on_frame(&self, callback: js_sys::Function) {
+        self.state.borrow_mut().callback = Some(callback);
+    }
+
+    /// Switches the lab to another built-in palette.
+    #[wasm_bindgen(js_name = setPalette)]
+    pub fn set_palette(&self, name: &str) -> Result<(), JsError> {
+        self.state.borrow_mut().lab.set_palette(name)
+    }
+
+    /// Whether playback is running.
+    #[wasm_bindgen(getter)]
+    pub fn playing(&self) -> bool {
+        self.state.borrow().playing
+    }
+
+    /// Simulation steps per animation frame.
+    #[wasm_bindgen(getter)]
+    pub fn speed(&self) -> u32 {
+        self.state.borrow().steps_per_frame
+    }
+
+    /// Simulation steps run so far.
+    #[wasm_bindgen(getter)]
+    pub fn step(&self) -> u64 {
+        self.state.borrow().step
+    }
+}
+
+impl Driver {
+    /// Builds a paused driver without a frame handler.
+    fn paused(lab: Lab, steps_per_frame: u32) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                lab,
+                steps_per_frame,
+                playing: false,
+                step: 0,
+                callback: None,
+                request: None,
+                tick: None,
+            })),
+        }
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        // The handler only holds a weak reference, so a late frame is a no-op
+        let _ = self.state.borrow_mut().cancel();
+    }
+}
+
+/// Animation frame handler: steps, reports the frame, and schedules the
+/// next one while still playing.
+fn frame(state: &Weak<RefCell<State>>) -> Result<(), JsValue> {
+    let Some(state) = state.upgrade() else {
+        return Ok(());
+    };
+    // Release the borrow before calling out, so the callback can use the driver
+    let (image, step, callback) = {
+        let mut state = state.borrow_mut();
+        state.request = None;
+        let stepped = state.advance_frame().inspect_err(|_| state.playing = false);
+        if !stepped.map_err(JsError::from)? {
+            return Ok(());
+        }
+        (state.lab.image_data()?, state.step, state.callback.clone())
+    };
+    if let Some(callback) = callback {
+        callback.call2(&JsValue::NULL, &image, &JsValue::from(step))?;
+    }
+    let mut state = state.borrow_mut();
+    match state.playing {
+        true => state.schedule(),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn driver(steps_per_frame: u32) -> Driver {
+        let lab = Lab::create("gray-scott", 8, 8, 1, &json!({}), "ocean").unwrap();
+        Driver::paused(lab, steps_per_frame)
+    }
+
+    #[test]
+    fn frames_step_only_while_playing() {
+        let driver = driver(4);
+        let mut state = driver.state.borrow_mut();
+        assert!(!state.advance_frame().unwrap());
+        assert_eq!(state.step, 0);
+        state.playing = true;
+        assert!(state.advance_frame().unwrap());
+        assert!(state.advance_frame().unwrap());
+        assert_eq!(state.step, 8);
+    }
+
+    #[test]
+    fn speed_applies_to_the_next_frame() {
+        let driver = driver(4);
+        driver.set_speed(10);
+        assert_eq!(driver.speed(), 10);
+        let mut state = driver.state.borrow_mut();
+        state.playing = true;
+        state.advance_frame().unwrap();
+        assert_eq!(state.step, 10);
+    }
+}
@@ -1,2 +1,227 @@
 #![deny(unsafe_code)]
 //! WASM bindings for the art-engine, exposing the engine to browser environments.
+//!
+//! [`Lab`] wraps an [`EngineKind`] and a [`Palette`]. JavaScript steps it and
+//! blits each frame to a 2D canvas with a single call:
+//!
+//! ```js
+//! const lab = new Lab("gray-scott", 256, 256, 42n, "{}", "ocean");
+//! lab.step(8);
+//! ctx.putImageData(lab.imageData(), 0, 0);
+//! ```
+//!
+//! For continuous playback, hand the lab to a [`Driver`]; to keep stepping
+//! off the main thread, run a [`WorkerHost`] in a Web Worker. The [`schema`]
+//! functions describe engines and palettes for building controls, and the
+//! [`determinism`] checks confirm a `portable` build matches native renders.
+//!
+//! ## Field views
+//!
+//! `fieldView()` and `fieldViewF32()` return typed arrays over WASM memory
+//! rather than copies, for visualizers and WebGL texture uploads. A view is
+//! only valid until the next call into the lab (or anything else in the
+//! module): stepping may move the field, and any allocation may grow the
+//! memory, which detaches every view. Fetch a fresh view each frame and
+//! never keep one across calls.
+
+pub mod determinism;
+pub mod driver;
+pub mod schema;
+pub mod worker;
+
+pub use driver::Driver;
+pub use worker::WorkerHost;
+
+use art_engine_core::{Engine, EngineError, Palette};
+use art_engine_engines::pixel::engine_to_rgba;
+use art_engine_engines::EngineKind;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::ImageData;
+
+/// A running engine with the palette its frames are drawn in.
+#[wasm_bindgen]
+pub struct Lab {
+    engine: EngineKind,
+    palette: Palette,
+    width: usize,
+    height: usize,
+    /// Single-precision copy of the field backing `fieldViewF32`.
+    field_f32: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl Lab {
+    /// Starts `engine` at `width x height` with `params` (a JSON object)
+    /// and a built-in palette.
+    ///
+    /// Throws for an unknown engine or palette, or params that are not a
+    /// JSON object.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        engine: &str,
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: &str,
+        palette: &str,
+    ) -> Result<Lab, JsError> {
+        let params: serde_json::Value = serde_json::from_str(params)
+            .map_err(|e| JsError::new(&format!("invalid params JSON: {e}")))?;
+        if !params.is_object() {
+            return Err(JsError::new("params must be a JSON object"));
+        }
+        Ok(Self::create(engine, width, height, seed, &params, palette)?)
+    }
+
+    /// Field width in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Field height in pixels.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Advances the simulation by `steps`.
+    pub fn step(&mut self, steps: u32) -> Result<(), JsError> {
+        Ok(self.advance(steps)?)
+    }
+
+    /// Switches to another built-in palette.
+    #[wasm_bindgen(js_name = setPalette)]
+    pub fn set_palette(&mut self, name: &str) -> Result<(), JsError> {
+        self.palette = Palette::from_name(name)?;
+        Ok(())
+    }
+
+    /// The current frame as RGBA bytes (`width * height * 4`), returned as
+    /// a `Uint8ClampedArray`.
+    pub fn pixels(&self) -> Result<Clamped<Vec<u8>>, JsError> {
+        Ok(Clamped(self.rgba()?))
+    }
+
+    /// The current frame as an `ImageData` sized to the field, ready for
+    /// `putImageData`.
+    #[wasm_bindgen(js_name = imageData)]
+    pub fn image_data(&self) -> Result<ImageData, JsValue> {
+        let rgba = self.rgba().map_err(JsError::from)?;
+        ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&rgba),
+            self.width as u32,
+            self.height as u32,
+        )
+    }
+
+    /// Zero-copy `Float64Array` over the engine's field, row-major. Valid
+    /// only until the next call into the module (see the crate docs).
+    #[wasm_bindgen(js_name = fieldView)]
+    pub fn field_view(&self) -> js_sys::Float64Array {
+        let data = self.engine.field().data();
+        js_sys::Float64Array::new_with_byte_offset_and_length(
+            &memory_buffer(),
+            data.as_ptr() as u32,
+            data.len() as u32,
+        )
+    }
+
+    /// `Float32Array` over a single-precision copy of the field, kept in
+    /// WASM memory so `texImage2D` can read it directly. Valid only until
+    /// the next call into the module (see the crate docs).
+    #[wasm_bindgen(js_name = fieldViewF32)]
+    pub fn field_view_f32(&mut self) -> js_sys::Float32Array {
+        let data = self.field_f32();
+        js_sys::Float32Array::new_with_byte_offset_and_length(
+            &memory_buffer(),
+            data.as_ptr() as u32,
+            data.len() as u32,
+        )
+    }
+}
+
+/// The `ArrayBuffer` behind the module's linear memory.
+fn memory_buffer() -> JsValue {
+    wasm_bindgen::memory()
+        .unchecked_into::<js_sys::WebAssembly::Memory>()
+        .buffer()
+}
+
+impl Lab {
+    /// Rust-side constructor behind [`Lab::new`], taking parsed params.
+    fn create(
+        engine: &str,
+        width: usize,
+        height: usize,
+        seed: u64,
+        params: &serde_json::Value,
+        palette: &str,
+    ) -> Result<Self, EngineError> {
+        Ok(Self {
+            engine: EngineKind::from_name(engine, width, height, seed, params)?,
+            palette: Palette::from_name(palette)?,
+            width,
+            height,
+            field_f32: Vec::new(),
+        })
+    }
+
+    /// Refreshes and returns the single-precision copy of the field. The
+    /// buffer is reused, so its address is stable between steps.
+    fn field_f32(&mut self) -> &[f32] {
+        let data = self.engine.field().data();
+        self.field_f32.clear();
+        self.field_f32.extend(data.iter().map(|&v| v as f32));
+        &self.field_f32
+    }
+
+    /// Runs `steps` simulation steps.
+    fn advance(&mut self, steps: u32) -> Result<(), EngineError> {
+        (0..steps).try_for_each(|_| Engine::step(&mut self.engine))
+    }
+
+    /// Renders the current frame, applying the engine's hue field if any.
+    fn rgba(&self) -> Result<Vec<u8>, EngineError> {
+        engine_to_rgba(&self.engine, &self.palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn frames_are_rgba_sized_to_the_field() {
+        let mut lab = Lab::create("gray-scott", 12, 8, 1, &json!({}), "ocean").unwrap();
+        lab.advance(5).unwrap();
+        let rgba = lab.rgba().unwrap();
+        assert_eq!(rgba.len(), 12 * 8 * 4);
+        assert!(rgba.chunks(4).all(|px| px[3] == 255));
+    }
+
+    #[test]
+    fn f32_field_tracks_the_engine_in_a_reused_buffer() {
+        let mut lab = Lab::create("gray-scott", 6, 4, 1, &json!({}), "ocean").unwrap();
+        let before = lab.field_f32().as_ptr();
+        lab.advance(3).unwrap();
+        let expected: Vec<f32> = lab
+            .engine
+            .field()
+            .data()
+            .iter()
+            .map(|&v| v as f32)
+            .collect();
+        assert_eq!(lab.field_f32(), expected.as_slice());
+        assert_eq!(lab.field_f32().as_ptr(), before);
+        assert_eq!(expected.len(), 6 * 4);
+    }
+
+    #[test]
+    fn rejects_unknown_engine_and_palette() {
+        assert!(Lab::create("nope", 8, 8, 1, &json!({}), "ocean").is_err());
+        assert!(Lab::create("gray-scott", 8, 8, 1, &json!({}), "nope").is_err());
+    }
+}
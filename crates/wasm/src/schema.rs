@@ -0,0 +1,92 @@
+//! Engine and palette metadata for generated UIs.
+//!
+//! Frontends list the engines, then build a slider per numeric entry of an
+//! engine's `params` schema (`type`, `default`, `min`, `max`,
+//! `description`) and a dropdown from its `presets`:
+//!
+//! ```js
+//! for (const name of engineNames()) {
+//!   const { params, presets } = engineSchema(name);
+//!   // params.feed_rate = { type: "number", default: 0.055, min: 0, max: 0.1, ... }
+//!   // presets.coral = { feed_rate: 0.0545, kill_rate: 0.062 }
+//! }
+//! ```
+
+use art_engine_core::{Engine, EngineError, Palette};
+use art_engine_engines::EngineKind;
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::Lab;
+
+/// Names accepted by the `Lab` constructor.
+#[wasm_bindgen(js_name = engineNames)]
+pub fn engine_names() -> Vec<String> {
+    EngineKind::list_engines()
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Built-in palette names accepted by `Lab` and `setPalette`.
+#[wasm_bindgen(js_name = paletteNames)]
+pub fn palette_names() -> Vec<String> {
+    Palette::list_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// `{params, presets}` for `engine`: its parameter schema and named
+/// parameter sets.
+///
+/// Throws for an unknown engine.
+#[wasm_bindgen(js_name = engineSchema)]
+pub fn engine_schema(engine: &str) -> Result<JsValue, JsError> {
+    to_js(&schema(engine)?)
+}
+
+#[wasm_bindgen]
+impl Lab {
+    /// The running engine's current parameter values.
+    pub fn params(&self) -> Result<JsValue, JsError> {
+        to_js(&self.engine.params())
+    }
+}
+
+/// Builds the schema object behind [`engine_schema`].
+fn schema(engine: &str) -> Result<Value, EngineError> {
+    // Schemas do not depend on size or seed, so a tiny instance will do
+    let instance = EngineKind::from_name(engine, 8, 8, 0, &json!({}))?;
+    Ok(json!({
+        "params": instance.param_schema(),
+        "presets": instance.presets(),
+    }))
+}
+
+/// Converts JSON to plain JS objects and arrays (not `Map`s).
+pub(crate) fn to_js(value: &Value) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemas_cover_params_and_presets() {
+        let gray_scott = schema("gray-scott").unwrap();
+        assert_eq!(gray_scott["params"]["feed_rate"]["type"], "number");
+        assert!(gray_scott["presets"]["coral"]["feed_rate"].is_number());
+        assert!(matches!(schema("nope"), Err(EngineError::UnknownEngine(_))));
+    }
+
+    #[test]
+    fn names_match_the_registries() {
+        assert!(engine_names().iter().any(|name| name == "gray-scott"));
+        assert_eq!(palette_names().len(), Palette::list_names().len());
+    }
+}
@@ -0,0 +1,369 @@
+//! Message protocol for stepping a [`Lab`] inside a Web Worker.
+//!
+//! Big grids take longer to step than a frame lasts, so the simulation
+//! belongs off the main thread. The worker instantiates this module, owns a
+//! [`WorkerHost`], and forwards every message to [`WorkerHost::handle`];
+//! each request gets exactly one reply. Pixel and field buffers in replies
+//! are fresh copies outside WASM memory, so they can be *transferred* back
+//! to the main thread without a further copy:
+//!
+//! ```js
+//! // worker.js
+//! import init, { WorkerHost } from "./art_engine_wasm.js";
+//! await init();
+//! const host = new WorkerHost();
+//! onmessage = ({ data }) => {
+//!   const reply = host.handle(data);
+//!   const transfer = reply.pixels ? [reply.pixels.buffer] : reply.values ? [reply.values.buffer] : [];
+//!   postMessage(reply, transfer);
+//! };
+//!
+//! // main.js
+//! const worker = new Worker("worker.js", { type: "module" });
+//! worker.postMessage({ type: "init", engine: "gray-scott", width: 1024, height: 1024, seed: 42 });
+//! worker.onmessage = ({ data }) => {
+//!   if (data.type === "frame") ctx.putImageData(new ImageData(data.pixels, data.width, data.height), 0, 0);
+//!   if (data.type !== "error") worker.postMessage({ type: "frame", steps: 8 });
+//! };
+//! ```
+//!
+//! Requests (`type` selects the variant, other keys are its fields):
+//! - `init {engine, width, height, seed?, params?, palette?}` → `ready {width, height}`
+//! - `setPalette {palette}` → `ack {step}`
+//! - `frame {steps?}`: step, then render → `frame {step, width, height, pixels}`
+//!   with `pixels` a `Uint8ClampedArray` of RGBA bytes
+//! - `field {steps?}`: step, then copy the field → `field {step, width, height, values}`
+//!   with `values` a `Float32Array`
+//!
+//! Failures reply `error {message}` instead of throwing, so the worker
+//! keeps running.
+
+use serde::Deserialize;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use art_engine_core::{EngineError, Palette};
+
+use crate::Lab;
+
+/// A message from the main thread.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase", deny_unknown_fields)]
+pub enum Request {
+    /// Creates (or replaces) the lab.
+    Init {
+        engine: String,
+        width: usize,
+        height: usize,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "empty_params")]
+        params: Value,
+        #[serde(default = "default_palette")]
+        palette: String,
+    },
+    /// Switches to another built-in palette.
+    SetPalette { palette: String },
+    /// Runs `steps` steps, then renders RGBA pixels.
+    Frame {
+        #[serde(default)]
+        steps: u32,
+    },
+    /// Runs `steps` steps, then copies the raw field.
+    Field {
+        #[serde(default)]
+        steps: u32,
+    },
+}
+
+fn empty_params() -> Value {
+    Value::Object(Default::default())
+}
+
+fn default_palette() -> String {
+    "ocean".into()
+}
+
+/// The answer to one [`Request`].
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    Ready {
+        width: usize,
+        height: usize,
+    },
+    Ack {
+        step: u64,
+    },
+    Frame {
+        step: u64,
+        width: usize,
+        height: usize,
+        pixels: Vec<u8>,
+    },
+    Field {
+        step: u64,
+        width: usize,
+        height: usize,
+        values: Vec<f32>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Worker-side owner of a [`Lab`], driven by [`Request`] messages.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct WorkerHost {
+    lab: Option<Lab>,
+    /// Steps run since the last `init`.
+    step: u64,
+}
+
+#[wasm_bindgen]
+impl WorkerHost {
+    /// Creates a host with no lab; the first request should be `init`.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WorkerHost {
+        Self::default()
+    }
+
+    /// Handles one message and returns the reply object to post back.
+    pub fn handle(&mut self, message: JsValue) -> Result<JsValue, JsValue> {
+        let reply = match serde_wasm_bindgen::from_value::<Request>(message) {
+            Ok(request) => self.apply(request),
+            Err(e) => Reply::Error {
+                message: format!("invalid request: {e}"),
+            },
+        };
+        reply.to_js()
+    }
+}
+
+impl WorkerHost {
+    /// Applies a request, turning failures into [`Reply::Error`].
+    pub fn apply(&mut self, request: Request) -> Reply {
+        self.try_apply(request).unwrap_or_else(|e| Reply::Error {
+            message: e.to_string(),
+        })
+    }
+
+    fn try_apply(&mut self, request: Request) -> Result<Reply, EngineError> {
+        let Self { lab, step } = self;
+        match request {
+            Request::Init {
+                engine,
+                width,
+                height,
+                seed,
+                params,
+                palette,
+            } => {
+                if !params.is_object() {
+                    return Err(EngineError::ParamTypeMismatch {
+                        name: "params".into(),
+                        expected: "object".into(),
+                        got: params.to_string(),
+                    });
+                }
+                *lab = Some(Lab::create(
+                    &engine, width, height, seed, &params, &palette,
+                )?);
+                *step = 0;
+                Ok(Reply::Ready { width, height })
+            }
+            Request::SetPalette { palette } => {
+                loaded(lab)?.palette = Palette::from_name(&palette)?;
+                Ok(Reply::Ack { step: *step })
+            }
+            Request::Frame { steps } => {
+                let lab = loaded(lab)?;
+                lab.advance(steps)?;
+                *step += u64::from(steps);
+                Ok(Reply::Frame {
+                    step: *step,
+                    width: lab.width,
+                    height: lab.height,
+                    pixels: lab.rgba()?,
+                })
+            }
+            Request::Field { steps } => {
+                let lab = loaded(lab)?;
+                lab.advance(steps)?;
+                *step += u64::from(steps);
+                Ok(Reply::Field {
+                    step: *step,
+                    width: lab.width,
+                    height: lab.height,
+                    values: lab.field_f32().to_vec(),
+                })
+            }
+        }
+    }
+}
+
+/// Returns the lab, or an error if `init` has not been handled yet.
+fn loaded(lab: &mut Option<Lab>) -> Result<&mut Lab, EngineError> {
+    lab.as_mut()
+        .ok_or_else(|| EngineError::Unsupported("send init before other requests".into()))
+}
+
+impl Reply {
+    /// Builds the plain JS object posted back to the main thread.
+    fn to_js(&self) -> Result<JsValue, JsValue> {
+        let object = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| js_sys::Reflect::set(&object, &key.into(), &value);
+        let size = |width: usize, height: usize| -> Result<(), JsValue> {
+            set("width", (width as u32).into())?;
+            set("height", (height as u32).into())?;
+            Ok(())
+        };
+        match self {
+            Reply::Ready { width, height } => {
+                set("type", "ready".into())?;
+                size(*width, *height)?;
+            }
+            Reply::Ack { step } => {
+                set("type", "ack".into())?;
+                set("step", (*step as f64).into())?;
+            }
+            Reply::Frame {
+                step,
+                width,
+                height,
+                pixels,
+            } => {
+                set("type", "frame".into())?;
+                set("step", (*step as f64).into())?;
+                size(*width, *height)?;
+                set(
+                    "pixels",
+                    js_sys::Uint8ClampedArray::from(pixels.as_slice()).into(),
+                )?;
+            }
+            Reply::Field {
+                step,
+                width,
+                height,
+                values,
+            } => {
+                set("type", "field".into())?;
+                set("step", (*step as f64).into())?;
+                size(*width, *height)?;
+                set(
+                    "values",
+                    js_sys::Float32Array::from(values.as_slice()).into(),
+                )?;
+            }
+            Reply::Error { message } => {
+                set("type", "error".into())?;
+                set("message", message.as_str().into())?;
+            }
+        }
+        Ok(object.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(value: Value) -> Request {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn requests_parse_with_defaults() {
+        assert_eq!(
+            request(json!({"type": "init", "engine": "gray-scott", "width": 8, "height": 4})),
+            Request::Init {
+                engine: "gray-scott".into(),
+                width: 8,
+                height: 4,
+                seed: 0,
+                params: json!({}),
+                palette: "ocean".into(),
+            }
+        );
+        assert_eq!(
+            request(json!({"type": "frame"})),
+            Request::Frame { steps: 0 }
+        );
+        assert_eq!(
+            request(json!({"type": "setPalette", "palette": "fire"})),
+            Request::SetPalette {
+                palette: "fire".into()
+            }
+        );
+        assert!(serde_json::from_value::<Request>(json!({"type": "frame", "stpes": 3})).is_err());
+    }
+
+    #[test]
+    fn host_steps_and_renders_after_init() {
+        let mut host = WorkerHost::new();
+        assert!(matches!(
+            host.apply(Request::Frame { steps: 1 }),
+            Reply::Error { .. }
+        ));
+        let init =
+            json!({"type": "init", "engine": "gray-scott", "width": 8, "height": 4, "seed": 3});
+        assert_eq!(
+            host.apply(request(init.clone())),
+            Reply::Ready {
+                width: 8,
+                height: 4
+            }
+        );
+        let Reply::Frame { step, pixels, .. } = host.apply(Request::Frame { steps: 5 }) else {
+            panic!("expected a frame");
+        };
+        assert_eq!((step, pixels.len()), (5, 8 * 4 * 4));
+        let Reply::Field { step, values, .. } = host.apply(Request::Field { steps: 2 }) else {
+            panic!("expected a field");
+        };
+        assert_eq!((step, values.len()), (7, 8 * 4));
+
+        // The same requests on a fresh lab give the same field
+        host.apply(request(init));
+        host.apply(Request::Frame { steps: 5 });
+        assert_eq!(
+            host.apply(Request::Field { steps: 2 }),
+            Reply::Field {
+                step: 7,
+                width: 8,
+                height: 4,
+                values
+            }
+        );
+    }
+
+    #[test]
+    fn failures_become_error_replies() {
+        let mut host = WorkerHost::new();
+        let reply = host.apply(request(
+            json!({"type": "init", "engine": "nope", "width": 8, "height": 8}),
+        ));
+        assert_eq!(
+            reply,
+            Reply::Error {
+                message: "unknown engine: nope".into()
+            }
+        );
+        host.apply(request(
+            json!({"type": "init", "engine": "gray-scott", "width": 8, "height": 8}),
+        ));
+        assert!(matches!(
+            host.apply(Request::SetPalette {
+                palette: "nope".into()
+            }),
+            Reply::Error { .. }
+        ));
+        assert_eq!(
+            host.apply(Request::SetPalette {
+                palette: "fire".into()
+            }),
+            Reply::Ack { step: 0 }
+        );
+    }
+}
@@ -33,6 +33,14 @@ cmd_clippy() {
     cargo clippy --all -- -D warnings && pass "clippy (workspace)" || fail "clippy (workspace)"
     step "cargo clippy (core + render)"
     cargo clippy -p art-engine-core --features render -- -D warnings && pass "clippy (render)" || fail "clippy (render)"
+    step "cargo clippy (engines + parallel)"
+    cargo clippy -p art-engine-engines -p art-engine-gray-scott --features art-engine-engines/parallel -- -D warnings && pass "clippy (parallel)" || fail "clippy (parallel)"
+    step "cargo clippy (engines + portable)"
+    cargo clippy -p art-engine-core -p art-engine-engines --features art-engine-engines/portable -- -D warnings && pass "clippy (portable)" || fail "clippy (portable)"
+    step "cargo clippy (cli + audio, control, http, tui)"
+    cargo clippy -p art-engine-cli --features audio,control,http,tui --all-targets -- -D warnings && pass "clippy (cli features)" || fail "clippy (cli features)"
+    step "cargo clippy (cli + video, gpu, parallel, portable)"
+    cargo clippy -p art-engine-cli --features video,gpu,parallel,portable --all-targets -- -D warnings && pass "clippy (cli build features)" || fail "clippy (cli build features)"
 }
 
 cmd_test() {
@@ -44,6 +52,14 @@ cmd_test() {
         cargo test --all && pass "test (workspace)" || fail "test (workspace)"
         step "cargo test (core + render)"
         cargo test -p art-engine-core --features render && pass "test (render)" || fail "test (render)"
+        step "cargo test (engines + parallel)"
+        cargo test -p art-engine-engines -p art-engine-gray-scott --features art-engine-engines/parallel && pass "test (parallel)" || fail "test (parallel)"
+        step "cargo test (engines + portable, reference digests)"
+        cargo test -p art-engine-core -p art-engine-engines --features art-engine-engines/portable && pass "test (portable)" || fail "test (portable)"
+        step "cargo test (cli + audio, control, http, tui)"
+        cargo test -p art-engine-cli --features audio,control,http,tui && pass "test (cli features)" || fail "test (cli features)"
+        step "cargo test (cli + video, gpu, parallel, portable)"
+        cargo test -p art-engine-cli --features video,gpu,parallel,portable && pass "test (cli build features)" || fail "test (cli build features)"
     fi
 }
 
@@ -62,6 +78,8 @@ cmd_build() {
 cmd_wasm() {
     step "cargo build (wasm32)"
     cargo build -p art-engine-wasm --target wasm32-unknown-unknown && pass "wasm" || fail "wasm"
+    step "cargo build (wasm32 + portable)"
+    cargo build -p art-engine-wasm --target wasm32-unknown-unknown --features portable && pass "wasm (portable)" || fail "wasm (portable)"
 }
 
 cmd_clean() {